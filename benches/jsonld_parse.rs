@@ -0,0 +1,61 @@
+//! Compares parsing JSON-LD text directly with parsing it into a
+//! `serde_json::Value` first. Run with `cargo bench --bench jsonld_parse`.
+
+use std::hint::black_box;
+
+use aideon_tools::aideon::tools::io::jsonld;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use serde_json::{Value, json};
+
+/// A `@graph` document of `count` people with literals, references, and a
+/// nested node each.
+fn document(count: usize) -> String {
+    let people: Vec<Value> = (0..count)
+        .map(|index| {
+            json!({
+                "@id": format!("https://example.com/people/{index}"),
+                "@type": "Person",
+                "name": format!("Person {index}"),
+                "description": "A person with a somewhat longer description \"quoted\".",
+                "age": index % 90,
+                "knows": [
+                    format!("https://example.com/people/{}", (index + 1) % count),
+                    format!("https://example.com/people/{}", (index + 7) % count)
+                ],
+                "address": {
+                    "@type": "PostalAddress",
+                    "addressLocality": "Wien",
+                    "postalCode": format!("{:04}", index % 10_000)
+                }
+            })
+        })
+        .collect();
+    json!({
+        "@context": {
+            "@vocab": "https://schema.org/",
+            "knows": { "@type": "@id" }
+        },
+        "@graph": people
+    })
+    .to_string()
+}
+
+fn parse(c: &mut Criterion) {
+    let source = document(20_000);
+    let mut group = c.benchmark_group("jsonld_parse");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.sample_size(10);
+    group.bench_function("text", |b| {
+        b.iter(|| jsonld::parse_jsonld_str(black_box(&source)).unwrap())
+    });
+    group.bench_function("value", |b| {
+        b.iter(|| {
+            let document: Value = serde_json::from_str(black_box(&source)).unwrap();
+            jsonld::parse_jsonld_document(&document).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
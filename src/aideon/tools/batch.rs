@@ -0,0 +1,137 @@
+//! Selection of the files converted together by a batch run.
+//!
+//! A batch converts every file under an input directory whose path matches
+//! a glob, writing each output at the same relative path under an output
+//! directory. [`matching_files`] finds the inputs and [`output_path`] places
+//! their outputs.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+
+use crate::aideon::tools::error::{Result, ToolError};
+
+/// Shell-style pattern matched against paths relative to the input
+/// directory, written with `/` separators.
+///
+/// `*` matches any run of characters within a path segment, `**` any run of
+/// whole segments, `?` one character, and `[abc]` or `[a-z]` one of a set.
+/// A pattern without `/` is matched against file names at any depth, so
+/// `*.jsonld` selects every JSON-LD file in the tree.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pattern: String,
+    regex: Regex,
+    names_only: bool,
+}
+
+impl Glob {
+    /// Compiles `pattern`, failing with [`ToolError::InvalidConfig`] on an
+    /// unclosed `[`.
+    pub fn new(pattern: &str) -> Result<Self> {
+        let mut regex = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        while let Some(character) = chars.next() {
+            match character {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        regex.push_str("(?:.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                '[' => {
+                    let mut class = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') if !class.is_empty() => break,
+                            Some('!') if class.is_empty() => class.push('^'),
+                            Some('-') => class.push('-'),
+                            Some(character) => {
+                                class.push_str(&regex::escape(&character.to_string()))
+                            }
+                            None => {
+                                return Err(ToolError::InvalidConfig(format!(
+                                    "unclosed '[' in glob '{pattern}'"
+                                )));
+                            }
+                        }
+                    }
+                    regex.push('[');
+                    regex.push_str(&class);
+                    regex.push(']');
+                }
+                other => regex.push_str(&regex::escape(&other.to_string())),
+            }
+        }
+        regex.push('$');
+        let regex = Regex::new(&regex)
+            .map_err(|err| ToolError::InvalidConfig(format!("invalid glob '{pattern}': {err}")))?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex,
+            names_only: !pattern.contains('/'),
+        })
+    }
+
+    /// Returns whether `relative`, a path below the input directory,
+    /// matches the pattern.
+    pub fn matches(&self, relative: &Path) -> bool {
+        if self.names_only {
+            return relative
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| self.regex.is_match(name));
+        }
+        let segments: Vec<&str> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(segment) => segment.to_str(),
+                _ => None,
+            })
+            .collect();
+        self.regex.is_match(&segments.join("/"))
+    }
+
+    /// Returns the pattern as written.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+}
+
+/// Returns the files below `dir` whose relative path matches `glob`, as
+/// paths relative to `dir` in sorted order. Hidden files and directories,
+/// such as the partial files of [`atomic`](crate::io::atomic) writes, are
+/// left out.
+pub fn matching_files(dir: &Path, glob: &Glob) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        for entry in fs::read_dir(dir.join(&relative))? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = relative.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else if glob.matches(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Returns where the output of the input at `relative` is written: the same
+/// relative path under `output_dir`, with `extension` in place of the
+/// input's. An empty `extension`, as for CSV directories, drops it.
+pub fn output_path(output_dir: &Path, relative: &Path, extension: &str) -> PathBuf {
+    output_dir.join(relative).with_extension(extension)
+}
@@ -0,0 +1,489 @@
+//! Stable labels for blank nodes.
+//!
+//! Parsers label blank nodes (`_:b0`) in the order they meet them, so the
+//! same data read twice, or after an unrelated edit, may label them
+//! differently, which shows up as changes in diffs and as new rows in
+//! workbooks. [`relabel_blank_nodes`] labels each blank node after its
+//! content and surroundings instead, in the spirit of RDF dataset
+//! canonicalization: a hash of its graph, types, literals, and the named
+//! nodes it links to and from, refined with the hashes of the blank nodes
+//! around it until they settle. Blank nodes still alike after that, such as
+//! those of a cycle, are told apart by singling out each of them in turn
+//! and refining again, keeping the labelling under which the data sorts
+//! first, so the labels do not follow the parser's either. Data too
+//! symmetric to settle within [`SEARCH_BUDGET`] labellings is refused
+//! rather than labelled unstably. Blank graph names keep their labels.
+//!
+//! Skolemizing turns blank nodes into well-known IRIs under [`GENID_PATH`],
+//! which survive steps such as workbooks that cannot scope blank nodes, and
+//! deskolemizing turns those IRIs back into blank nodes.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use uuid::Uuid;
+
+use crate::aideon::tools::equivalence::{is_blank, relabel, sorted_items, targets};
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::model::{ArrayValue, Node, NodeId, PropertyValue};
+
+/// Path segment of the well-known IRIs standing for skolemized blank nodes.
+pub const GENID_PATH: &str = "/.well-known/genid/";
+
+/// Separates the fields hashed into a blank node's label.
+const SEPARATOR: char = '\u{1f}';
+
+/// Upper bound on the labellings compared for blank nodes that refinement
+/// cannot tell apart.
+pub const SEARCH_BUDGET: usize = 1024;
+
+/// How conversions label the blank nodes they read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BlankNodeLabels {
+    /// The labels the parser gave them.
+    #[default]
+    Parsed,
+    /// Labels derived from their content, such as
+    /// `_:c14n3f0c9a0e5d1b5e0c8f1d2a4b6c7e9f01`, the same whenever the data
+    /// is.
+    Canonical,
+    /// Well-known IRIs under the given base, such as
+    /// `https://example.com/.well-known/genid/3f0c…`, which turn the blank
+    /// nodes into named ones.
+    Skolemized(String),
+    /// Blank nodes in place of the well-known IRIs of skolemized ones, under
+    /// any base, labelled after the rest of the IRI: `_:3f0c…` for
+    /// `https://example.com/.well-known/genid/3f0c…`. Other blank nodes
+    /// keep the labels the parser gave them.
+    Deskolemized,
+}
+
+/// Returns `nodes` with their blank nodes labelled as `labels` asks and
+/// sorted by graph and id, or unchanged for [`BlankNodeLabels::Parsed`].
+/// Arrays of references to blank nodes are sorted too, since their order
+/// followed the parsed labels. Deskolemizing keeps the order of nodes and
+/// arrays, and only needs the nodes at hand, so it applies to each chunk of
+/// a streamed input alike.
+///
+/// Canonical and skolemized labels fail with
+/// [`ToolError::SymmetricBlankNodes`] when the blank nodes are too
+/// symmetric to label within [`SEARCH_BUDGET`] labellings.
+pub fn relabel_blank_nodes(nodes: Vec<Node>, labels: &BlankNodeLabels) -> Result<Vec<Node>> {
+    match labels {
+        BlankNodeLabels::Parsed => return Ok(nodes),
+        BlankNodeLabels::Deskolemized => return Ok(deskolemize(nodes)),
+        BlankNodeLabels::Canonical | BlankNodeLabels::Skolemized(_) => {}
+    }
+    let renamed: BTreeMap<NodeId, NodeId> = canonical_digests(&nodes)?
+        .into_iter()
+        .map(|(label, digest)| {
+            let id = match labels {
+                BlankNodeLabels::Skolemized(base) => format!(
+                    "{}{GENID_PATH}{}",
+                    base.trim_end_matches('/'),
+                    digest.simple()
+                ),
+                _ => format!("_:c14n{}", digest.simple()),
+            };
+            (NodeId::from(label), NodeId::from(id))
+        })
+        .collect();
+    if renamed.is_empty() {
+        return Ok(nodes);
+    }
+    let relabelled: BTreeSet<&NodeId> = renamed.values().collect();
+    let mut nodes: Vec<Node> = nodes
+        .iter()
+        .map(|node| {
+            let mut node = relabel(node, &renamed);
+            for value in node.properties.values_mut() {
+                if let PropertyValue::Array(ArrayValue::ObjectRefs(targets)) = value
+                    && targets.iter().any(|target| relabelled.contains(target))
+                {
+                    targets.sort();
+                }
+            }
+            node
+        })
+        .collect();
+    nodes.sort_by(|lhs, rhs| lhs.graph.cmp(&rhs.graph).then_with(|| lhs.id.cmp(&rhs.id)));
+    Ok(nodes)
+}
+
+/// Replaces the well-known IRIs among the ids and references of `nodes`
+/// with blank nodes, see [`BlankNodeLabels::Deskolemized`].
+fn deskolemize(nodes: Vec<Node>) -> Vec<Node> {
+    let mut renamed = BTreeMap::new();
+    for node in &nodes {
+        let ids = std::iter::once(&node.id).chain(node.properties.values().flat_map(targets));
+        for id in ids {
+            if let Some((_, genid)) = id.as_str().split_once(GENID_PATH)
+                && !genid.is_empty()
+            {
+                let label = genid.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+                renamed.insert(id.clone(), NodeId::from(format!("_:{label}")));
+            }
+        }
+    }
+    if renamed.is_empty() {
+        return nodes;
+    }
+    nodes.iter().map(|node| relabel(node, &renamed)).collect()
+}
+
+/// Hashes every blank node of `nodes`, refining and individualising until
+/// no two share a hash.
+fn canonical_digests(nodes: &[Node]) -> Result<BTreeMap<&str, Uuid>> {
+    let graph = BlankEdges::new(nodes);
+    let initial = graph.initial_digests(nodes);
+    let mut search = Labelling {
+        graph: &graph,
+        initial: &initial,
+        budget: SEARCH_BUDGET,
+        first: None,
+        best: None,
+        automorphisms: Vec::new(),
+    };
+    search.explore(graph.refine(initial.clone()), &mut Vec::new())?;
+    Ok(search.best.map(|leaf| leaf.digests).unwrap_or_default())
+}
+
+/// Returns the labels sharing the smallest digest held by several, if any.
+fn first_tie<'a>(digests: &BTreeMap<&'a str, Uuid>) -> Option<Vec<&'a str>> {
+    let mut classes: BTreeMap<Uuid, Vec<&str>> = BTreeMap::new();
+    for (label, digest) in digests {
+        classes.entry(*digest).or_default().push(label);
+    }
+    classes.into_values().find(|labels| labels.len() > 1)
+}
+
+/// A labelling in which no two blank nodes share a digest, reached by
+/// singling out the blank nodes of `path` in turn.
+#[derive(Clone)]
+struct Leaf<'a> {
+    path: Vec<&'a str>,
+    /// The data under this labelling, as sorted lines, which two labellings
+    /// share exactly when they label the data alike.
+    certificate: Vec<String>,
+    digests: BTreeMap<&'a str, Uuid>,
+}
+
+/// Search for the labelling whose certificate sorts first among those
+/// reached by singling out tied blank nodes, pruned with the symmetries of
+/// the data found along the way, as in canonical graph labelling tools.
+struct Labelling<'a, 'b> {
+    graph: &'b BlankEdges<'a>,
+    initial: &'b BTreeMap<&'a str, Uuid>,
+    budget: usize,
+    first: Option<Leaf<'a>>,
+    best: Option<Leaf<'a>>,
+    /// Relabellings that leave the data unchanged, from blank node to blank
+    /// node.
+    automorphisms: Vec<BTreeMap<&'a str, &'a str>>,
+}
+
+impl<'a> Labelling<'a, '_> {
+    /// Explores the labellings below `digests`, reached along `path`.
+    /// Returns the depth to go back to when the rest of the subtree mirrors
+    /// one already explored.
+    fn explore(
+        &mut self,
+        digests: BTreeMap<&'a str, Uuid>,
+        path: &mut Vec<&'a str>,
+    ) -> Result<Option<usize>> {
+        let Some(tied) = first_tie(&digests) else {
+            return self.leaf(digests, path);
+        };
+        if self.graph.twins(&tied) {
+            // Blank nodes with the same neighbours trade places without
+            // changing the data, so numbering them in label order labels
+            // the data alike whatever the parser called them.
+            let mut next = digests.clone();
+            for (index, label) in tied.iter().enumerate() {
+                next.insert(
+                    label,
+                    hash(&format!("{}{SEPARATOR}{index}", digests[label])),
+                );
+            }
+            return self.explore(self.graph.refine(next), path);
+        }
+        let depth = path.len();
+        let known = self.automorphisms.len();
+        let mut singled: Vec<&str> = Vec::new();
+        for label in tied {
+            // Automorphisms found below this point fix `path`, so one
+            // mapping the label onto a blank node singled out already
+            // leads to the same labellings.
+            if same_orbit(&self.automorphisms[known..], label, &singled) {
+                continue;
+            }
+            singled.push(label);
+            let mut next = digests.clone();
+            next.insert(label, hash(&format!("{}{SEPARATOR}!", digests[label])));
+            path.push(label);
+            let back = self.explore(self.graph.refine(next), path)?;
+            path.pop();
+            if let Some(back) = back
+                && back < depth
+            {
+                return Ok(Some(back));
+            }
+        }
+        Ok(None)
+    }
+
+    fn leaf(
+        &mut self,
+        digests: BTreeMap<&'a str, Uuid>,
+        path: &[&'a str],
+    ) -> Result<Option<usize>> {
+        if self.budget == 0 {
+            return Err(ToolError::SymmetricBlankNodes {
+                count: digests.len(),
+                budget: SEARCH_BUDGET,
+            });
+        }
+        self.budget -= 1;
+        let leaf = Leaf {
+            path: path.to_vec(),
+            certificate: self.certificate(&digests),
+            digests,
+        };
+        let (Some(first), Some(best)) = (&self.first, &self.best) else {
+            self.first = Some(leaf.clone());
+            self.best = Some(leaf);
+            return Ok(None);
+        };
+        for reference in [first, best] {
+            if reference.certificate == leaf.certificate {
+                let back = common_prefix(&leaf.path, &reference.path);
+                self.automorphisms.push(automorphism(&leaf, reference));
+                return Ok(Some(back));
+            }
+        }
+        if leaf.certificate < best.certificate {
+            self.best = Some(leaf);
+        }
+        Ok(None)
+    }
+
+    /// Writes the data under `digests`: each blank node's content with its
+    /// digest, and the edges between blank nodes.
+    fn certificate(&self, digests: &BTreeMap<&'a str, Uuid>) -> Vec<String> {
+        let mut lines: Vec<String> = digests
+            .iter()
+            .map(|(label, digest)| format!("{digest}{SEPARATOR}{}", self.initial[label]))
+            .collect();
+        for (source, edges) in &self.graph.outgoing {
+            lines.extend(edges.iter().map(|(graph, predicate, target)| {
+                format!(
+                    "{}{SEPARATOR}{}{SEPARATOR}{predicate}{SEPARATOR}{}",
+                    digests[source],
+                    graph.unwrap_or_default(),
+                    digests[target]
+                )
+            }));
+        }
+        lines.sort();
+        lines
+    }
+}
+
+/// Maps each blank node of `leaf` onto the one `reference` labels alike.
+fn automorphism<'a>(leaf: &Leaf<'a>, reference: &Leaf<'a>) -> BTreeMap<&'a str, &'a str> {
+    let labels: BTreeMap<Uuid, &str> = reference
+        .digests
+        .iter()
+        .map(|(&label, &digest)| (digest, label))
+        .collect();
+    leaf.digests
+        .iter()
+        .map(|(&label, digest)| (label, labels[digest]))
+        .collect()
+}
+
+fn common_prefix(lhs: &[&str], rhs: &[&str]) -> usize {
+    lhs.iter()
+        .zip(rhs)
+        .take_while(|(lhs, rhs)| lhs == rhs)
+        .count()
+}
+
+/// Returns whether some of `automorphisms`, applied in turn, map `label`
+/// onto one of `others`.
+fn same_orbit(automorphisms: &[BTreeMap<&str, &str>], label: &str, others: &[&str]) -> bool {
+    let mut orbit = BTreeSet::from([label]);
+    let mut pending = vec![label];
+    while let Some(next) = pending.pop() {
+        for automorphism in automorphisms {
+            if let Some(&image) = automorphism.get(next)
+                && orbit.insert(image)
+            {
+                pending.push(image);
+            }
+        }
+    }
+    others.iter().any(|other| orbit.contains(other))
+}
+
+fn hash(text: &str) -> Uuid {
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, text.as_bytes())
+}
+
+/// Graph, predicate, and the node at the other end of a blank node edge.
+type Edge<'a> = (Option<&'a str>, &'a str, &'a str);
+
+/// The edges between blank nodes, and those from named nodes to them.
+struct BlankEdges<'a> {
+    labels: BTreeSet<&'a str>,
+    outgoing: BTreeMap<&'a str, Vec<Edge<'a>>>,
+    incoming: BTreeMap<&'a str, Vec<Edge<'a>>>,
+}
+
+impl<'a> BlankEdges<'a> {
+    fn new(nodes: &'a [Node]) -> Self {
+        let mut edges = BlankEdges {
+            labels: BTreeSet::new(),
+            outgoing: BTreeMap::new(),
+            incoming: BTreeMap::new(),
+        };
+        for node in nodes {
+            if is_blank(&node.id) {
+                edges.labels.insert(&node.id);
+            }
+            for (predicate, value) in &node.properties {
+                for target in targets(value).into_iter().filter(|target| is_blank(target)) {
+                    let graph = node.graph.as_deref();
+                    edges.labels.insert(target);
+                    edges
+                        .incoming
+                        .entry(target)
+                        .or_default()
+                        .push((graph, predicate, &node.id));
+                    if is_blank(&node.id) {
+                        edges
+                            .outgoing
+                            .entry(&node.id)
+                            .or_default()
+                            .push((graph, predicate, target));
+                    }
+                }
+            }
+        }
+        edges
+    }
+
+    /// Returns whether the blank nodes `labels` have the same edges to the
+    /// same nodes.
+    fn twins(&self, labels: &[&'a str]) -> bool {
+        let neighbours = |label: &str| {
+            let mut edges: Vec<(char, &Edge<'a>)> = self
+                .outgoing
+                .get(label)
+                .into_iter()
+                .flatten()
+                .map(|edge| ('>', edge))
+                .chain(
+                    self.incoming
+                        .get(label)
+                        .into_iter()
+                        .flatten()
+                        .map(|edge| ('<', edge)),
+                )
+                .collect();
+            edges.sort();
+            edges
+        };
+        let first = neighbours(labels[0]);
+        labels[1..].iter().all(|&label| neighbours(label) == first)
+    }
+
+    /// Hashes each blank node's records, with blank references anonymised,
+    /// and the named nodes referring to it.
+    fn initial_digests(&self, nodes: &'a [Node]) -> BTreeMap<&'a str, Uuid> {
+        let mut records: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for node in nodes.iter().filter(|node| is_blank(&node.id)) {
+            records.entry(&node.id).or_default().push(record(node));
+        }
+        self.labels
+            .iter()
+            .map(|&label| {
+                let mut fields = records.remove(label).unwrap_or_default();
+                fields.sort();
+                let mut named: Vec<String> = self
+                    .incoming
+                    .get(label)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, _, source)| !is_blank(source))
+                    .map(|(graph, predicate, source)| {
+                        format!("{}<{predicate}<{source}", graph.unwrap_or_default())
+                    })
+                    .collect();
+                named.sort();
+                fields.extend(named);
+                (label, hash(&fields.join(&SEPARATOR.to_string())))
+            })
+            .collect()
+    }
+
+    /// Folds the digests of neighbouring blank nodes into each digest until
+    /// no class of equal digests splits any more.
+    fn refine(&self, mut digests: BTreeMap<&'a str, Uuid>) -> BTreeMap<&'a str, Uuid> {
+        let mut classes = distinct(&digests);
+        loop {
+            let next: BTreeMap<&str, Uuid> = digests
+                .iter()
+                .map(|(&label, digest)| {
+                    let neighbours = |edges: Option<&Vec<Edge<'a>>>, direction: char| {
+                        let mut keyed: Vec<String> = edges
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|(graph, predicate, other)| {
+                                digests.get(other).map(|other| {
+                                    format!(
+                                        "{direction}{}{direction}{predicate}{direction}{other}",
+                                        graph.unwrap_or_default()
+                                    )
+                                })
+                            })
+                            .collect();
+                        keyed.sort();
+                        keyed
+                    };
+                    let mut fields = vec![digest.to_string()];
+                    fields.extend(neighbours(self.outgoing.get(label), '>'));
+                    fields.extend(neighbours(self.incoming.get(label), '<'));
+                    (label, hash(&fields.join(&SEPARATOR.to_string())))
+                })
+                .collect();
+            let count = distinct(&next);
+            digests = next;
+            if count == classes {
+                return digests;
+            }
+            classes = count;
+        }
+    }
+}
+
+fn distinct(digests: &BTreeMap<&str, Uuid>) -> usize {
+    digests.values().collect::<BTreeSet<_>>().len()
+}
+
+/// Text of a blank node's record, with blank references written as `_`.
+fn record(node: &Node) -> String {
+    let mut fields = vec![node.graph.as_deref().unwrap_or_default().to_string()];
+    fields.extend(node.types.iter().map(|type_name| format!("a<{type_name}")));
+    let anonymise = |id: &str| {
+        if is_blank(id) {
+            "_".to_string()
+        } else {
+            format!("<{id}>")
+        }
+    };
+    for (predicate, value) in &node.properties {
+        let items = sorted_items(value, anonymise);
+        fields.extend(items.into_iter().map(|item| format!("{predicate}<{item}")));
+    }
+    fields.join(&SEPARATOR.to_string())
+}
@@ -0,0 +1,162 @@
+//! Append-only change feeds for harvesters.
+//!
+//! A feed describes the node changes between two dataset versions, as found
+//! by [`diff_nodes`](crate::aideon::tools::diff::diff_nodes), so consumers
+//! can follow increments instead of downloading full dumps. Each change is
+//! one JSON-LD object of its own, and feeds are written one object per line
+//! and appended to, never rewritten:
+//!
+//! * [`FeedFormat::ActivityStreams`] writes an
+//!   [ActivityStreams](https://www.w3.org/TR/activitystreams-core/) `Create`,
+//!   `Update`, or `Delete` activity whose `object` is the node, a `Tombstone`
+//!   for deleted nodes, and whose `target` is the node's graph;
+//! * [`FeedFormat::Ldes`] adds a version of the node to a
+//!   [Linked Data Event Stream](https://w3id.org/ldes/specification): a
+//!   member with the node's content, `dcterms:isVersionOf` the node, and
+//!   `prov:generatedAtTime` the time of the change, or an `as:Tombstone` for
+//!   deleted nodes. Members drop the node's graph.
+//!
+//! Activity and version ids are `urn:uuid:` UUIDs derived from the change
+//! and its time.
+
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use std::path::Path;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{Map, Value, json};
+use uuid::Uuid;
+
+use crate::aideon::tools::diff::{self, ChangeKind, NodeChange};
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::io::jsonld;
+use crate::aideon::tools::model::XSD_DATE_TIME;
+
+/// Context of ActivityStreams 2.0 documents.
+const ACTIVITY_STREAMS_CONTEXT: &str = "https://www.w3.org/ns/activitystreams";
+
+/// Vocabulary of the changes a feed describes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedFormat {
+    /// ActivityStreams 2.0 activities.
+    ActivityStreams,
+    /// Members of the Linked Data Event Stream with this IRI.
+    Ldes {
+        /// IRI of the `ldes:EventStream`.
+        stream: String,
+    },
+}
+
+/// Describes each of `changes` in `format`, as happening at `published`.
+pub fn feed_events(
+    changes: &[NodeChange],
+    format: &FeedFormat,
+    published: DateTime<Utc>,
+) -> Vec<Value> {
+    let timestamp = published.to_rfc3339_opts(SecondsFormat::Secs, true);
+    changes
+        .iter()
+        .map(|change| {
+            let event_id = event_id(change, &timestamp);
+            match format {
+                FeedFormat::ActivityStreams => activity(change, event_id, &timestamp),
+                FeedFormat::Ldes { stream } => ldes_member(change, event_id, &timestamp, stream),
+            }
+        })
+        .collect()
+}
+
+/// Appends `events` to the feed at `path`, one per line, creating the file
+/// if needed; `-` writes them to stdout.
+pub fn append_feed(path: &Path, events: &[Value]) -> Result<()> {
+    if path.as_os_str() == "-" {
+        return diff::write_ndjson(std::io::stdout().lock(), events);
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    diff::write_ndjson(BufWriter::new(file), events)
+}
+
+fn event_id(change: &NodeChange, timestamp: &str) -> String {
+    let name = format!(
+        "{timestamp}\u{1f}{}\u{1f}{}\u{1f}{}",
+        kind_name(change.change),
+        change.graph.as_deref().unwrap_or_default(),
+        change.id
+    );
+    format!(
+        "urn:uuid:{}",
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes())
+    )
+}
+
+fn kind_name(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Created => "Create",
+        ChangeKind::Updated => "Update",
+        ChangeKind::Deleted => "Delete",
+    }
+}
+
+/// The expanded node object of the changed node after the change, or a
+/// tombstone listing its former types when it was deleted.
+fn changed_object(change: &NodeChange, tombstone: &str, former_type: &str) -> Map<String, Value> {
+    match &change.after {
+        Some(node) => match jsonld::node_to_json(node) {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        },
+        None => {
+            let mut map = Map::new();
+            map.insert("@id".into(), Value::String(change.id.to_string()));
+            map.insert("@type".into(), Value::String(tombstone.into()));
+            let types: Vec<Value> = change
+                .before
+                .iter()
+                .flat_map(|node| &node.types)
+                .map(|type_name| json!({ "@id": type_name }))
+                .collect();
+            if !types.is_empty() {
+                map.insert(former_type.into(), Value::Array(types));
+            }
+            map
+        }
+    }
+}
+
+fn activity(change: &NodeChange, id: String, timestamp: &str) -> Value {
+    let mut activity = Map::new();
+    activity.insert("@context".into(), ACTIVITY_STREAMS_CONTEXT.into());
+    activity.insert("id".into(), Value::String(id));
+    activity.insert("type".into(), kind_name(change.change).into());
+    activity.insert("published".into(), timestamp.into());
+    let object = changed_object(change, "Tombstone", "formerType");
+    activity.insert("object".into(), Value::Object(object));
+    if let Some(graph) = &change.graph {
+        activity.insert("target".into(), json!({ "@id": graph }));
+    }
+    Value::Object(activity)
+}
+
+fn ldes_member(change: &NodeChange, id: String, timestamp: &str, stream: &str) -> Value {
+    let mut member = changed_object(change, "as:Tombstone", "as:formerType");
+    member.insert("@id".into(), Value::String(id));
+    member.insert("dcterms:isVersionOf".into(), json!({ "@id": change.id }));
+    member.insert(
+        "prov:generatedAtTime".into(),
+        json!({ "@value": timestamp, "@type": XSD_DATE_TIME }),
+    );
+    json!({
+        "@context": {
+            "as": "https://www.w3.org/ns/activitystreams#",
+            "dcterms": "http://purl.org/dc/terms/",
+            "ldes": "https://w3id.org/ldes#",
+            "prov": "http://www.w3.org/ns/prov#",
+            "tree": "https://w3id.org/tree#"
+        },
+        "@id": stream,
+        "@type": "ldes:EventStream",
+        "ldes:versionOfPath": { "@id": "dcterms:isVersionOf" },
+        "ldes:timestampPath": { "@id": "prov:generatedAtTime" },
+        "tree:member": Value::Object(member)
+    })
+}
@@ -0,0 +1,277 @@
+//! Conversion profiles read from a configuration file.
+//!
+//! Teams converting the same data repeatedly can keep their conventions in
+//! an [`CONFIG_FILE`] next to the data instead of repeating flags on every
+//! invocation:
+//!
+//! ```toml
+//! context = "context.json"
+//! rdf-format = "n-quads"
+//! skip-sheets = ["Lookups"]
+//! input-encoding = "latin-1"
+//! language = "fr"
+//! rdf-lists = ["schema:itemListElement"]
+//! ignored-predicates = ["schema:dateModified"]
+//! same-as = "keep"
+//!
+//! [graph-contexts]
+//! "https://example.com/graphs/tenant-a" = "tenant-a.json"
+//!
+//! [prefixes]
+//! schema = "https://schema.org/"
+//!
+//! [column-order]
+//! "schema:Person" = ["schema:name", "schema:email"]
+//!
+//! [[column-groups."schema:Person"]]
+//! label = "Identification"
+//! predicates = ["schema:name"]
+//!
+//! [id-properties]
+//! "schema:Person" = "schema:identifier"
+//!
+//! [natural-keys]
+//! "schema:Order" = ["schema:orderNumber", "schema:seller"]
+//!
+//! [csv]
+//! delimiter = ";"
+//! encoding = "windows-1252"
+//!
+//! [labels.fr]
+//! "schema:Person" = "Personne"
+//! "schema:name" = "Nom"
+//!
+//! [cardinality]
+//! "schema:knows" = "many"
+//! "schema:email" = "one"
+//! ```
+//!
+//! Types and predicates may be written as compact IRIs using the declared
+//! prefixes, and paths are relative to the file. The contexts they name
+//! stay JSON-LD documents.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::filter::IriPattern;
+use crate::aideon::tools::flatten::{self, Cardinality, ColumnGroup};
+use crate::aideon::tools::io::csv_dialect::CsvDialect;
+use crate::aideon::tools::io::rdf::{JsonLdProfileSet, RdfFormat};
+use crate::aideon::tools::io::text_encoding::TextEncoding;
+use crate::aideon::tools::model::Iri;
+use crate::aideon::tools::same_as::SameAs;
+use crate::aideon::tools::sync::SyncOptions;
+
+/// Name of the configuration file looked up in the working directory.
+pub const CONFIG_FILE: &str = "aideon.toml";
+
+/// Defaults applied to conversions, as declared in a configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Profile {
+    /// JSON-LD context compacting written JSON-LD documents.
+    pub context: Option<PathBuf>,
+    /// JSON-LD context compacting each named graph of written JSON-LD
+    /// documents, see [`SyncOptions::graph_contexts`]: a path relative to
+    /// the file, read by [`Self::load`], or the context itself as a table.
+    pub graph_contexts: BTreeMap<String, Value>,
+    /// Serialisation of written RDF files, named like the `--rdf-format`
+    /// values (`turtle`, `n-triples`, `n-quads`, `tri-g`, `json-ld`, `n3`).
+    pub rdf_format: Option<String>,
+    /// Prefixes mapped to the namespace they abbreviate, see
+    /// [`SyncOptions::prefixes`].
+    pub prefixes: BTreeMap<String, String>,
+    /// Predicates whose columns lead the sheet of each type, see
+    /// [`SyncOptions::column_order`].
+    pub column_order: BTreeMap<String, Vec<String>>,
+    /// Labelled bands of columns leading the sheet of each type, see
+    /// [`SyncOptions::column_groups`].
+    pub column_groups: BTreeMap<String, Vec<ColumnGroup>>,
+    /// Sheets left out when workbooks and CSV files are read, see
+    /// [`SyncOptions::skip_sheets`].
+    pub skip_sheets: BTreeSet<String>,
+    /// Predicate holding the id of the nodes of each type, see
+    /// [`SyncOptions::id_properties`].
+    pub id_properties: BTreeMap<String, String>,
+    /// Predicates identifying the nodes of each type that have a surrogate
+    /// id, see [`SyncOptions::natural_keys`].
+    pub natural_keys: BTreeMap<String, Vec<String>>,
+    /// Dialect of CSV files read and written, see
+    /// [`SyncOptions::csv_dialect`].
+    pub csv: Option<CsvDialect>,
+    /// Character encoding of JSON-LD and RDF inputs, see
+    /// [`SyncOptions::input_encoding`].
+    pub input_encoding: Option<TextEncoding>,
+    /// Labels of types and predicates keyed by language, such as `fr`, see
+    /// [`SyncOptions::labels`].
+    pub labels: BTreeMap<String, BTreeMap<String, String>>,
+    /// Language whose `labels` name the sheets and columns of written
+    /// workbooks and CSV files.
+    pub language: Option<String>,
+    /// Cardinality of predicates, `one` or `many`, see
+    /// [`SyncOptions::cardinalities`].
+    pub cardinality: BTreeMap<String, Cardinality>,
+    /// Predicates written to RDF as ordered collections, see
+    /// [`SyncOptions::rdf_lists`].
+    pub rdf_lists: BTreeSet<String>,
+    /// Predicate patterns left out of written workbooks and kept from the
+    /// existing output when they are read back, see
+    /// [`SyncOptions::ignored_predicates`].
+    pub ignored_predicates: Vec<String>,
+    /// What becomes of nodes linked by `owl:sameAs` when `--same-as` is not
+    /// given, see [`SyncOptions::same_as`].
+    pub same_as: Option<SameAs>,
+}
+
+impl Profile {
+    /// Reads the TOML profile stored at `path`, failing with
+    /// [`ToolError::InvalidConfig`] on malformed TOML, unknown keys, RDF
+    /// formats, or languages, and with [`ToolError::InvalidLabels`] on clashing labels.
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        let mut profile: Self = toml::from_str(&data)
+            .map_err(|err| ToolError::InvalidConfig(format!("{}: {err}", path.display())))?;
+        profile.rdf_format()?;
+        for language in profile.labels.keys().chain(&profile.language) {
+            profile.labels(language)?;
+        }
+        if let Some(context) = &mut profile.context {
+            let base = path.parent().unwrap_or(Path::new(""));
+            *context = base.join(&*context);
+        }
+        for context in profile.graph_contexts.values_mut() {
+            if let Value::String(file) = context {
+                let base = path.parent().unwrap_or(Path::new(""));
+                let file = base.join(&*file);
+                let data = std::fs::read_to_string(&file)?;
+                *context = serde_json::from_str(&data).map_err(|err| {
+                    ToolError::InvalidConfig(format!("{}: {err}", file.display()))
+                })?;
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Reads the [`CONFIG_FILE`] in `dir` when there is one.
+    pub fn discover(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Self::load(&path).map(Some)
+    }
+
+    /// Returns the serialisation named by `rdf_format`.
+    pub fn rdf_format(&self) -> Result<Option<RdfFormat>> {
+        let Some(name) = &self.rdf_format else {
+            return Ok(None);
+        };
+        let format = match name.as_str() {
+            "turtle" => RdfFormat::Turtle,
+            "n-triples" => RdfFormat::NTriples,
+            "n-quads" => RdfFormat::NQuads,
+            "tri-g" => RdfFormat::TriG,
+            "n3" => RdfFormat::N3,
+            "json-ld" => RdfFormat::JsonLd {
+                profile: JsonLdProfileSet::empty(),
+            },
+            other => {
+                return Err(ToolError::InvalidConfig(format!(
+                    "unknown RDF format '{other}'"
+                )));
+            }
+        };
+        Ok(Some(format))
+    }
+
+    /// Returns the labels of `language`, keyed by expanded IRIs. Fails with
+    /// [`ToolError::InvalidConfig`] when the profile has none for it, and
+    /// as [`flatten::check_labels`] does.
+    pub fn labels(&self, language: &str) -> Result<BTreeMap<Iri, String>> {
+        let labels = self.labels.get(language).ok_or_else(|| {
+            ToolError::InvalidConfig(format!("no labels for language '{language}'"))
+        })?;
+        let labels = labels
+            .iter()
+            .map(|(iri, label)| (Iri::expand(iri, &self.prefixes), label.clone()))
+            .collect();
+        flatten::check_labels(&labels)?;
+        Ok(labels)
+    }
+
+    /// Copies the prefixes, column order and groups, skipped sheets, id properties, CSV
+    /// dialect, input encoding, labels of `language`, cardinalities, RDF
+    /// list predicates, and ignored predicates into `options`, expanding
+    /// compact IRIs.
+    pub fn apply(&self, options: &mut SyncOptions) {
+        let expand = |value: &String| Iri::expand(value, &self.prefixes);
+        options.prefixes.extend(self.prefixes.clone());
+        options
+            .column_order
+            .extend(self.column_order.iter().map(|(type_name, predicates)| {
+                (expand(type_name), predicates.iter().map(expand).collect())
+            }));
+        options
+            .column_groups
+            .extend(self.column_groups.iter().map(|(type_name, groups)| {
+                let groups = groups
+                    .iter()
+                    .map(|group| ColumnGroup {
+                        label: group.label.clone(),
+                        predicates: group
+                            .predicates
+                            .iter()
+                            .map(|predicate| Iri::expand(predicate, &self.prefixes))
+                            .collect(),
+                    })
+                    .collect();
+                (expand(type_name), groups)
+            }));
+        options.skip_sheets.extend(self.skip_sheets.iter().cloned());
+        options.id_properties.extend(
+            self.id_properties
+                .iter()
+                .map(|(type_name, predicate)| (expand(type_name), expand(predicate))),
+        );
+        options
+            .natural_keys
+            .extend(self.natural_keys.iter().map(|(type_name, predicates)| {
+                (expand(type_name), predicates.iter().map(expand).collect())
+            }));
+        if let Some(dialect) = self.csv {
+            options.csv_dialect = dialect;
+        }
+        if let Some(encoding) = self.input_encoding {
+            options.input_encoding = encoding;
+        }
+        options.cardinalities.extend(
+            self.cardinality
+                .iter()
+                .map(|(predicate, cardinality)| (expand(predicate), *cardinality)),
+        );
+        options.rdf_lists.extend(self.rdf_lists.iter().map(expand));
+        options.ignored_predicates.extend(
+            self.ignored_predicates
+                .iter()
+                .map(|pattern| IriPattern::new(pattern, &self.prefixes)),
+        );
+        options.same_as = options.same_as.or(self.same_as);
+        options.graph_contexts.extend(
+            self.graph_contexts
+                .iter()
+                .map(|(graph, context)| (expand(graph), context.clone())),
+        );
+        // `load` rejects languages without valid labels.
+        if let Some(Ok(labels)) = self
+            .language
+            .as_deref()
+            .map(|language| self.labels(language))
+        {
+            options.labels = labels;
+        }
+    }
+}
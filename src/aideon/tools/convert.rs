@@ -0,0 +1,198 @@
+//! Conversions between in-memory buffers and streams.
+//!
+//! The [`sync`](crate::aideon::tools::sync) routines read and write files.
+//! Applications embedding the crate can convert data they already hold, such
+//! as request bodies, with a [`Converter`] instead:
+//!
+//! ```
+//! use aideon_tools::convert::Converter;
+//! use serde_json::json;
+//!
+//! let document = br#"{"@id": "https://example.com/ada", "https://schema.org/name": "Ada"}"#;
+//! let mut workbook = std::io::Cursor::new(Vec::new());
+//! Converter::new()
+//!     .with_context(json!({ "@vocab": "https://schema.org/" }))
+//!     .read_jsonld(&document[..])?
+//!     .write_excel(&mut workbook)?;
+//! # Ok::<(), aideon_tools::ToolError>(())
+//! ```
+
+use std::io::{Read, Seek, Write};
+
+use serde_json::Value;
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::flatten::{WorkbookData, build_workbook_with_options};
+use crate::aideon::tools::io::rdf::{self, RdfFormat};
+use crate::aideon::tools::io::{excel_read, excel_write, tabular};
+use crate::aideon::tools::model::Node;
+use crate::aideon::tools::sync::{self, SyncOptions};
+
+/// Settings shared by the reading and writing steps of a conversion.
+#[derive(Debug, Clone)]
+pub struct Converter {
+    options: SyncOptions,
+    context: Option<Value>,
+    rdf_format: RdfFormat,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Self {
+            options: SyncOptions::default(),
+            context: None,
+            rdf_format: RdfFormat::Turtle,
+        }
+    }
+}
+
+impl Converter {
+    /// Creates a converter with the default [`SyncOptions`], no context, and
+    /// Turtle as the RDF serialisation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the options applied when reading and writing.
+    pub fn with_options(mut self, options: SyncOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the JSON-LD context compacting written JSON-LD. Its prefixes
+    /// also abbreviate written RDF.
+    pub fn with_context(mut self, context: Value) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Sets the serialisation of written RDF.
+    pub fn with_rdf_format(mut self, format: RdfFormat) -> Self {
+        self.rdf_format = format;
+        self
+    }
+
+    /// Reads a JSON-LD document from `reader`.
+    pub fn read_jsonld<R: Read>(self, mut reader: R) -> Result<Conversion> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let source = self.options.input_encoding.decode(&bytes)?;
+        let nodes = sync::parse_jsonld(&source, &self.options)?;
+        Ok(self.converted(nodes))
+    }
+
+    /// Reads an RDF graph serialised as `format` from `reader`.
+    pub fn read_rdf<R: Read>(self, reader: R, format: RdfFormat) -> Result<Conversion> {
+        let reader = self.options.input_encoding.reader(reader);
+        let nodes = sync::fold_reifications(rdf::read_rdf_from(reader, format)?, &self.options);
+        let nodes = sync::accept(nodes, &self.options)?;
+        Ok(self.converted(nodes))
+    }
+
+    /// Reads an Excel workbook from `reader`. Without a context of its own,
+    /// the converter takes the one embedded in the workbook.
+    pub fn read_excel<R: Read + Seek>(mut self, mut reader: R) -> Result<Conversion> {
+        self.context = sync::context_or_embedded(self.context, &self.options, || {
+            excel_read::read_context_from(&mut reader)
+        })?;
+        reader.rewind()?;
+        let nodes = excel_read::read_nodes_from(
+            &mut reader,
+            &self.options.coercions,
+            &self.options.skip_sheets,
+        )?;
+        let nodes = sync::accept(nodes, &self.options)?;
+        Ok(self.converted(nodes))
+    }
+
+    /// Reads the tables of a workbook held in memory, such as one built by
+    /// [`Conversion::to_workbook`], taking its embedded context like
+    /// [`Self::read_excel`].
+    pub fn read_workbook(mut self, workbook: &WorkbookData) -> Result<Conversion> {
+        self.context = sync::context_or_embedded(self.context, &self.options, || {
+            tabular::workbook_context(workbook)
+        })?;
+        let nodes = tabular::read_workbook_with(
+            workbook,
+            &self.options.coercions,
+            &self.options.skip_sheets,
+        )?;
+        let nodes = sync::accept(nodes, &self.options)?;
+        Ok(self.converted(nodes))
+    }
+
+    /// Starts from `nodes` already in memory, checked and renamed like the
+    /// nodes of any other input.
+    pub fn read_nodes(self, nodes: Vec<Node>) -> Result<Conversion> {
+        let nodes = sync::accept(nodes, &self.options)?;
+        Ok(self.converted(nodes))
+    }
+
+    fn converted(self, nodes: Vec<Node>) -> Conversion {
+        Conversion {
+            converter: self,
+            nodes,
+        }
+    }
+}
+
+/// Nodes read by a [`Converter`], ready to be written in any representation.
+#[derive(Debug, Clone)]
+pub struct Conversion {
+    converter: Converter,
+    nodes: Vec<Node>,
+}
+
+impl Conversion {
+    /// Returns the nodes read.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the nodes read, giving up the conversion.
+    pub fn into_nodes(self) -> Vec<Node> {
+        self.nodes
+    }
+
+    /// Builds the JSON-LD document of the nodes, compacted with the context
+    /// and shaped by the frame of the options, if any.
+    pub fn to_jsonld(&self) -> Result<Value> {
+        let converter = &self.converter;
+        sync::jsonld_document(&self.nodes, converter.context.clone(), &converter.options)
+    }
+
+    /// Writes the JSON-LD document of [`Self::to_jsonld`] to `writer`.
+    pub fn write_jsonld<W: Write>(&self, writer: W) -> Result<()> {
+        let converter = &self.converter;
+        sync::write_jsonld_to(
+            &self.nodes,
+            writer,
+            converter.context.clone(),
+            &converter.options,
+        )
+    }
+
+    /// Writes the nodes as RDF in the converter's serialisation, like
+    /// [`sync::write_rdf_to`].
+    pub fn write_rdf<W: Write>(&self, writer: W) -> Result<()> {
+        let converter = &self.converter;
+        sync::write_rdf_to(
+            &self.nodes,
+            writer,
+            converter.rdf_format,
+            converter.context.as_ref(),
+            &converter.options,
+        )
+    }
+
+    /// Flattens the nodes into workbook tables laid out as the options ask.
+    pub fn to_workbook(&self) -> Result<WorkbookData> {
+        build_workbook_with_options(&self.nodes, &self.converter.options.workbook_options())
+    }
+
+    /// Writes the tables of [`Self::to_workbook`] to `writer` as an Excel
+    /// workbook.
+    pub fn write_excel<W: Write + Seek + Send>(&self, writer: W) -> Result<()> {
+        excel_write::write_workbook_to(writer, &self.to_workbook()?)
+    }
+}
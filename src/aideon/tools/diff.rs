@@ -0,0 +1,155 @@
+//! Node-level comparison of two dataset versions.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::aideon::tools::equivalence;
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::model::{Iri, Node, NodeId};
+
+/// Kind of change observed for a node between two dataset versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// The node only exists in the newer version.
+    Created,
+    /// The node exists in both versions with different content.
+    Updated,
+    /// The node only exists in the older version.
+    Deleted,
+}
+
+/// Describes how a single node changed between two dataset versions.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeChange {
+    /// Kind of change.
+    pub change: ChangeKind,
+    /// Identifier of the changed node.
+    pub id: NodeId,
+    /// Graph the node belongs to.
+    pub graph: Option<Iri>,
+    /// Node content in the older version, if present.
+    pub before: Option<Node>,
+    /// Node content in the newer version, if present.
+    pub after: Option<Node>,
+}
+
+/// Compares two node sets keyed by `(graph, id)` and returns one entry per
+/// created, updated, or deleted node, ordered by graph and id.
+///
+/// Blank nodes are matched by structure rather than label and array order is
+/// ignored (see [`equivalence`]), so re-reading unchanged data reports no
+/// changes. Matched blank nodes are keyed by their label in `before`; the
+/// reported nodes are the unmodified inputs.
+pub fn diff_nodes(before: &[Node], after: &[Node]) -> Vec<NodeChange> {
+    let aligned = equivalence::align_blank_nodes(before, after);
+    let mut before: BTreeMap<(Option<Iri>, NodeId), Node> = before
+        .iter()
+        .map(|node| ((node.graph.clone(), node.id.clone()), node.clone()))
+        .collect();
+    let after: BTreeMap<(Option<Iri>, NodeId), (Node, &Node)> = aligned
+        .into_iter()
+        .zip(after)
+        .map(|(aligned, original)| {
+            let key = (aligned.graph.clone(), aligned.id.clone());
+            (key, (aligned, original))
+        })
+        .collect();
+    let mut changes = Vec::new();
+
+    for (key, (aligned, new_node)) in after {
+        let old_node = before.remove(&key);
+        if old_node
+            .as_ref()
+            .is_some_and(|old_node| equivalence::same_node(old_node, &aligned))
+        {
+            continue;
+        }
+        let (graph, id) = key;
+        let change = if old_node.is_some() {
+            ChangeKind::Updated
+        } else {
+            ChangeKind::Created
+        };
+        changes.push(NodeChange {
+            change,
+            id,
+            graph,
+            before: old_node,
+            after: Some(new_node.clone()),
+        });
+    }
+
+    for ((graph, id), old_node) in before {
+        changes.push(NodeChange {
+            change: ChangeKind::Deleted,
+            id,
+            graph,
+            before: Some(old_node),
+            after: None,
+        });
+    }
+
+    changes.sort_by(|lhs, rhs| lhs.graph.cmp(&rhs.graph).then_with(|| lhs.id.cmp(&rhs.id)));
+    changes
+}
+
+/// A row of a workbook sheet, numbered as spreadsheet applications show it,
+/// with the header as row 1.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct RowLocation {
+    /// Name of the sheet.
+    pub sheet: String,
+    /// Row number on the sheet.
+    pub row: usize,
+}
+
+/// Rows describing each node of a workbook, keyed by the node id and the
+/// text of the row's graph cell, empty when the row leaves it blank.
+pub type RowLocations = BTreeMap<(String, NodeId), Vec<RowLocation>>;
+
+/// A node change with the rows of the newer workbook describing the node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocatedChange {
+    /// The change itself.
+    #[serde(flatten)]
+    pub change: NodeChange,
+    /// Rows holding the node in the newer workbook; empty for deleted nodes.
+    pub rows: Vec<RowLocation>,
+}
+
+/// Pairs each of `changes` with the rows `locations` lists for the changed
+/// node, including the rows leaving its graph blank.
+pub fn locate_changes(changes: Vec<NodeChange>, locations: &RowLocations) -> Vec<LocatedChange> {
+    changes
+        .into_iter()
+        .map(|change| {
+            let mut rows = Vec::new();
+            if change.after.is_some() {
+                let graph = change.graph.as_deref().unwrap_or_default();
+                for graph in [graph, ""] {
+                    if let Some(found) = locations.get(&(graph.to_string(), change.id.clone())) {
+                        rows.extend(found.iter().cloned());
+                    }
+                    if graph.is_empty() {
+                        break;
+                    }
+                }
+            }
+            rows.sort();
+            LocatedChange { change, rows }
+        })
+        .collect()
+}
+
+/// Writes `changes` as newline-delimited JSON, one event per line.
+pub fn write_ndjson<W: Write, T: Serialize>(mut writer: W, changes: &[T]) -> Result<()> {
+    for change in changes {
+        serde_json::to_writer(&mut writer, change)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,452 @@
+//! Semantic comparison of node sets.
+//!
+//! Blank node labels (`_:b0`) are local to the document they were read from,
+//! so two readings of the same data may label them differently, and the order
+//! of multi-valued properties carries no meaning in RDF. This module matches
+//! the blank nodes of two node sets by their structure and compares values
+//! regardless of array order. Blank graph names are compared by label.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::aideon::tools::model::{ArrayItem, ArrayValue, Iri, Node, NodeId, PropertyValue};
+
+/// Upper bound on the candidate bijections tried for symmetric blank nodes
+/// before falling back to matching only structurally unique ones.
+const SEARCH_BUDGET: usize = 1024;
+
+type Color = u64;
+type Colors<'a> = BTreeMap<&'a str, Color>;
+/// Graph, predicate, and the node at the other end of a blank node edge.
+type Edge<'a> = (Option<&'a str>, &'a str, &'a str);
+
+/// Returns whether `id` is a blank node label.
+pub fn is_blank(id: &str) -> bool {
+    id.starts_with("_:")
+}
+
+/// Returns whether the two node sets describe the same data up to blank node
+/// labels and array order.
+pub fn equivalent(before: &[Node], after: &[Node]) -> bool {
+    let after = align_blank_nodes(before, after);
+    same_nodes(before, &after)
+}
+
+/// Returns `after` with its blank nodes relabelled to match the structurally
+/// equivalent blank nodes of `before`.
+///
+/// When the two sets are isomorphic every blank node is matched. Otherwise
+/// only blank nodes whose surroundings are unique on both sides are matched;
+/// the others keep their label unless a matched node took it, in which case
+/// they receive a fresh one.
+pub fn align_blank_nodes(before: &[Node], after: &[Node]) -> Vec<Node> {
+    let mapping = blank_node_mapping(before, after);
+    if mapping.iter().all(|(from, to)| from == to) {
+        return after.to_vec();
+    }
+
+    let claimed: HashSet<&str> = mapping.values().map(Iri::as_str).collect();
+    let mut taken: HashSet<String> = BlankGraph::new(before)
+        .labels
+        .iter()
+        .chain(claimed.iter())
+        .map(|label| label.to_string())
+        .collect();
+    let mut labels: BTreeMap<NodeId, NodeId> = mapping.clone();
+    for label in BlankGraph::new(after).labels {
+        if mapping.contains_key(label) || !claimed.contains(label) {
+            continue;
+        }
+        let fresh = (1..)
+            .map(|counter| format!("{label}~{counter}"))
+            .find(|candidate| !taken.contains(candidate))
+            .expect("unbounded counter");
+        taken.insert(fresh.clone());
+        labels.insert(label.into(), fresh.into());
+    }
+
+    after.iter().map(|node| relabel(node, &labels)).collect()
+}
+
+/// Maps blank node labels of `after` onto the labels of the blank nodes of
+/// `before` they correspond to.
+pub fn blank_node_mapping(before: &[Node], after: &[Node]) -> BTreeMap<NodeId, NodeId> {
+    let before_graph = BlankGraph::new(before);
+    let after_graph = BlankGraph::new(after);
+    let (before_colors, after_colors) = refine(
+        (&before_graph, &after_graph),
+        (before_graph.initial_colors(), after_graph.initial_colors()),
+    );
+
+    let mut search = Search {
+        before: &before_graph,
+        after: &after_graph,
+        before_nodes: touching_blanks(before),
+        after_nodes: touching_blanks(after),
+        budget: SEARCH_BUDGET,
+    };
+    if let Some(mapping) = search.run(before_colors.clone(), after_colors.clone(), 0) {
+        return mapping;
+    }
+
+    classes(&before_colors, &after_colors)
+        .into_values()
+        .filter(|(lhs, rhs)| lhs.len() == 1 && rhs.len() == 1)
+        .map(|(lhs, rhs)| (rhs[0].into(), lhs[0].into()))
+        .collect()
+}
+
+/// Compares two property values, ignoring the order of array items.
+pub fn same_value(lhs: &PropertyValue, rhs: &PropertyValue) -> bool {
+    match (lhs, rhs) {
+        (PropertyValue::Array(_), PropertyValue::Array(_)) => {
+            sorted_items(lhs, |id| id.to_string()) == sorted_items(rhs, |id| id.to_string())
+        }
+        _ => lhs == rhs,
+    }
+}
+
+/// Compares two nodes, ignoring the order of array items.
+pub fn same_node(lhs: &Node, rhs: &Node) -> bool {
+    lhs.id == rhs.id
+        && lhs.graph == rhs.graph
+        && lhs.types == rhs.types
+        && lhs.properties.len() == rhs.properties.len()
+        && lhs.properties.iter().all(|(predicate, value)| {
+            rhs.properties
+                .get(predicate)
+                .is_some_and(|other| same_value(value, other))
+        })
+}
+
+fn same_nodes(lhs: &[Node], rhs: &[Node]) -> bool {
+    let index = |nodes: &[Node]| -> BTreeMap<(Option<Iri>, NodeId), Node> {
+        nodes
+            .iter()
+            .map(|node| ((node.graph.clone(), node.id.clone()), node.clone()))
+            .collect()
+    };
+    let lhs = index(lhs);
+    let rhs = index(rhs);
+    lhs.len() == rhs.len()
+        && lhs
+            .iter()
+            .all(|(key, node)| rhs.get(key).is_some_and(|other| same_node(node, other)))
+}
+
+/// Returns the blank nodes and the nodes referring to a blank node.
+fn touching_blanks(nodes: &[Node]) -> Vec<Node> {
+    nodes
+        .iter()
+        .filter(|node| {
+            is_blank(&node.id)
+                || node
+                    .properties
+                    .values()
+                    .any(|value| targets(value).into_iter().any(|target| is_blank(target)))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Returns `node` with its id and reference targets renamed by `labels`.
+pub(crate) fn relabel(node: &Node, labels: &BTreeMap<NodeId, NodeId>) -> Node {
+    let rename = |id: &NodeId| labels.get(id).unwrap_or(id).clone();
+    let properties = node
+        .properties
+        .iter()
+        .map(|(predicate, value)| {
+            let value = match value {
+                PropertyValue::ObjectRef(target) => PropertyValue::ObjectRef(rename(target)),
+                PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => PropertyValue::Array(
+                    ArrayValue::ObjectRefs(targets.iter().map(rename).collect()),
+                ),
+                PropertyValue::Array(ArrayValue::Mixed(items)) => {
+                    PropertyValue::Array(ArrayValue::Mixed(
+                        items
+                            .iter()
+                            .map(|item| match item {
+                                ArrayItem::ObjectRef(target) => {
+                                    ArrayItem::ObjectRef(rename(target))
+                                }
+                                scalar => scalar.clone(),
+                            })
+                            .collect(),
+                    ))
+                }
+                other => other.clone(),
+            };
+            (predicate.clone(), value)
+        })
+        .collect();
+    Node {
+        id: rename(&node.id),
+        graph: node.graph.clone(),
+        types: node.types.clone(),
+        properties,
+        lists: node.lists.clone(),
+    }
+}
+
+/// Array items (or the single value) as sorted JSON text, with references
+/// rendered by `reference`.
+pub(crate) fn sorted_items(
+    value: &PropertyValue,
+    reference: impl Fn(&str) -> String,
+) -> Vec<String> {
+    let mut items: Vec<String> = match value {
+        PropertyValue::Scalar(scalar) => vec![scalar.to_json().to_string()],
+        PropertyValue::ObjectRef(target) => vec![reference(target)],
+        PropertyValue::Array(ArrayValue::Scalars(items)) => items
+            .iter()
+            .map(|item| item.to_json().to_string())
+            .collect(),
+        PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => {
+            targets.iter().map(|target| reference(target)).collect()
+        }
+        PropertyValue::Array(ArrayValue::Mixed(items)) => items
+            .iter()
+            .map(|item| match item {
+                ArrayItem::Scalar(scalar) => scalar.to_json().to_string(),
+                ArrayItem::ObjectRef(target) => reference(target),
+            })
+            .collect(),
+    };
+    items.sort();
+    items
+}
+
+/// Node references held by a value.
+pub(crate) fn targets(value: &PropertyValue) -> Vec<&NodeId> {
+    match value {
+        PropertyValue::ObjectRef(target) => vec![target],
+        PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => targets.iter().collect(),
+        PropertyValue::Array(ArrayValue::Mixed(items)) => items
+            .iter()
+            .filter_map(|item| match item {
+                ArrayItem::ObjectRef(target) => Some(target),
+                ArrayItem::Scalar(_) => None,
+            })
+            .collect(),
+        PropertyValue::Scalar(_) | PropertyValue::Array(ArrayValue::Scalars(_)) => Vec::new(),
+    }
+}
+
+fn hash_of<T: Hash>(value: &T) -> Color {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Blank nodes of a node set together with the edges touching them.
+struct BlankGraph<'a> {
+    labels: BTreeSet<&'a str>,
+    records: BTreeMap<&'a str, Vec<&'a Node>>,
+    outgoing: BTreeMap<&'a str, Vec<Edge<'a>>>,
+    incoming: BTreeMap<&'a str, Vec<Edge<'a>>>,
+}
+
+impl<'a> BlankGraph<'a> {
+    fn new(nodes: &'a [Node]) -> Self {
+        let mut graph = BlankGraph {
+            labels: BTreeSet::new(),
+            records: BTreeMap::new(),
+            outgoing: BTreeMap::new(),
+            incoming: BTreeMap::new(),
+        };
+        for node in nodes {
+            if is_blank(&node.id) {
+                graph.labels.insert(&node.id);
+                graph.records.entry(&node.id).or_default().push(node);
+            }
+            for (predicate, value) in &node.properties {
+                for target in targets(value).into_iter().filter(|target| is_blank(target)) {
+                    let graph_name = node.graph.as_deref();
+                    graph.labels.insert(target);
+                    graph
+                        .incoming
+                        .entry(target)
+                        .or_default()
+                        .push((graph_name, predicate, &node.id));
+                    if is_blank(&node.id) {
+                        graph
+                            .outgoing
+                            .entry(&node.id)
+                            .or_default()
+                            .push((graph_name, predicate, target));
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    /// Colours each blank node by its own content, with blank references
+    /// anonymised, and by the named nodes referring to it.
+    fn initial_colors(&self) -> Colors<'a> {
+        self.labels
+            .iter()
+            .map(|label| {
+                let mut records: Vec<String> = self
+                    .records
+                    .get(label)
+                    .into_iter()
+                    .flatten()
+                    .map(|node| record_signature(node))
+                    .collect();
+                records.sort();
+                let mut named_sources: Vec<&Edge> = self
+                    .incoming
+                    .get(label)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, _, source)| !is_blank(source))
+                    .collect();
+                named_sources.sort();
+                (*label, hash_of(&(records, named_sources)))
+            })
+            .collect()
+    }
+
+    /// Folds the colours of neighbouring blank nodes into each colour.
+    fn step(&self, colors: &Colors<'a>) -> Colors<'a> {
+        colors
+            .iter()
+            .map(|(label, color)| {
+                let neighbours = |edges: Option<&Vec<Edge<'a>>>| {
+                    let mut keyed: Vec<(Option<&str>, &str, Color)> = edges
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|(graph, predicate, other)| {
+                            colors.get(other).map(|color| (*graph, *predicate, *color))
+                        })
+                        .collect();
+                    keyed.sort();
+                    keyed
+                };
+                let outgoing = neighbours(self.outgoing.get(label));
+                let incoming = neighbours(self.incoming.get(label));
+                (*label, hash_of(&(color, outgoing, incoming)))
+            })
+            .collect()
+    }
+}
+
+/// Refines the colours of both sides in lockstep, so equal colours keep
+/// denoting equal surroundings, until no colour class splits any more.
+fn refine<'a>(
+    graphs: (&BlankGraph<'a>, &BlankGraph<'a>),
+    mut colors: (Colors<'a>, Colors<'a>),
+) -> (Colors<'a>, Colors<'a>) {
+    let mut distinct = distinct_colors(&colors);
+    loop {
+        colors = (graphs.0.step(&colors.0), graphs.1.step(&colors.1));
+        let next = distinct_colors(&colors);
+        if next == distinct {
+            return colors;
+        }
+        distinct = next;
+    }
+}
+
+fn record_signature(node: &Node) -> String {
+    let anonymise = |id: &str| {
+        if is_blank(id) {
+            "_".to_string()
+        } else {
+            format!("<{id}>")
+        }
+    };
+    let properties: Vec<(&Iri, Vec<String>)> = node
+        .properties
+        .iter()
+        .map(|(predicate, value)| (predicate, sorted_items(value, anonymise)))
+        .collect();
+    format!("{:?}|{:?}|{:?}", node.graph, node.types, properties)
+}
+
+fn distinct_colors((before, after): &(Colors, Colors)) -> usize {
+    before
+        .values()
+        .chain(after.values())
+        .collect::<BTreeSet<_>>()
+        .len()
+}
+
+/// Groups blank nodes of both sides by colour.
+fn classes<'a>(
+    before: &Colors<'a>,
+    after: &Colors<'a>,
+) -> BTreeMap<Color, (Vec<&'a str>, Vec<&'a str>)> {
+    let mut classes: BTreeMap<Color, (Vec<&str>, Vec<&str>)> = BTreeMap::new();
+    for (label, color) in before {
+        classes.entry(*color).or_default().0.push(label);
+    }
+    for (label, color) in after {
+        classes.entry(*color).or_default().1.push(label);
+    }
+    classes
+}
+
+/// Individualisation-refinement search for a blank node bijection.
+struct Search<'s, 'a> {
+    before: &'s BlankGraph<'a>,
+    after: &'s BlankGraph<'a>,
+    /// Nodes the blank node mapping can affect.
+    before_nodes: Vec<Node>,
+    after_nodes: Vec<Node>,
+    budget: usize,
+}
+
+impl<'a> Search<'_, 'a> {
+    fn run(
+        &mut self,
+        before: Colors<'a>,
+        after: Colors<'a>,
+        depth: u64,
+    ) -> Option<BTreeMap<NodeId, NodeId>> {
+        let classes = classes(&before, &after);
+        if classes.values().any(|(lhs, rhs)| lhs.len() != rhs.len()) {
+            return None;
+        }
+
+        let ambiguous = classes
+            .iter()
+            .filter(|(_, (lhs, _))| lhs.len() > 1)
+            .min_by_key(|(color, (lhs, _))| (lhs.len(), **color));
+        let Some((color, (lhs, rhs))) = ambiguous else {
+            if self.budget == 0 {
+                return None;
+            }
+            self.budget -= 1;
+            let mapping: BTreeMap<NodeId, NodeId> = classes
+                .into_values()
+                .map(|(lhs, rhs)| (rhs[0].into(), lhs[0].into()))
+                .collect();
+            let relabelled: Vec<Node> = self
+                .after_nodes
+                .iter()
+                .map(|node| relabel(node, &mapping))
+                .collect();
+            return same_nodes(&self.before_nodes, &relabelled).then_some(mapping);
+        };
+
+        let chosen = lhs[0];
+        let marker = hash_of(&(color, depth, "individualised"));
+        for candidate in rhs {
+            if self.budget == 0 {
+                return None;
+            }
+            let mut before = before.clone();
+            let mut after = after.clone();
+            before.insert(chosen, marker);
+            after.insert(candidate, marker);
+            let (before, after) = refine((self.before, self.after), (before, after));
+            if let Some(mapping) = self.run(before, after, depth + 1) {
+                return Some(mapping);
+            }
+        }
+        None
+    }
+}
@@ -6,6 +6,24 @@ use thiserror::Error;
 /// Convenient alias for fallible results returned throughout the crate.
 pub type Result<T> = std::result::Result<T, ToolError>;
 
+/// A workbook or CSV cell whose text could not be read.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("sheet '{sheet}', row {row}, column '{column}': '{text}': {reason}")]
+pub struct CellError {
+    pub sheet: String,
+    /// Row number users see, counting the header as row 1.
+    pub row: usize,
+    /// Header of the cell's column.
+    pub column: String,
+    /// Text of the cell.
+    pub text: String,
+    pub reason: String,
+}
+
+fn list_cells(cells: &[CellError]) -> String {
+    cells.iter().map(|cell| format!("\n  {cell}")).collect()
+}
+
 /// Error type covering the different failure cases that can occur when the
 /// tool ingests, transforms, or emits data.
 #[derive(Debug, Error)]
@@ -26,6 +44,28 @@ pub enum ToolError {
     #[error("Excel read error: {0}")]
     ExcelRead(#[from] calamine::XlsxError),
 
+    /// Errors raised while reading or rewriting a workbook's ZIP package.
+    #[error("workbook archive error: {0}")]
+    Archive(#[from] zip::result::ZipError),
+
+    /// Errors bubbled up from the CSV reader or writer.
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    /// Raised when CSV text cannot be decoded or encoded in the requested
+    /// encoding.
+    #[error("CSV encoding error: {0}")]
+    CsvEncoding(String),
+
+    /// Raised when a JSON-LD or RDF input is not valid in its character
+    /// encoding.
+    #[error("text encoding error: {0}")]
+    TextEncoding(String),
+
+    /// Raised when traces and metrics cannot be exported.
+    #[error("telemetry error: {0}")]
+    Telemetry(String),
+
     /// Raised when a sheet does not follow the expected conventions.
     #[error("invalid workbook structure: {0}")]
     InvalidWorkbook(String),
@@ -34,10 +74,35 @@ pub enum ToolError {
     #[error("JSON-LD normalization error: {0}")]
     JsonLd(String),
 
+    /// Raised when a remote JSON-LD context cannot be resolved.
+    #[error("remote context error: {0}")]
+    RemoteContext(String),
+
     /// Raised when RDF parsing or serialization fails.
     #[error("RDF error: {0}")]
     Rdf(String),
 
+    /// Raised when a SPARQL query cannot be parsed or evaluated.
+    #[error("SPARQL error: {0}")]
+    Sparql(String),
+
+    /// Raised when a SHACL shapes graph cannot be interpreted.
+    #[error("SHACL error: {0}")]
+    Shacl(String),
+
+    /// Raised when a SQL database cannot be reached or queried.
+    #[error("SQL error: {0}")]
+    Sql(String),
+
+    /// Raised when a Parquet file cannot be written, or Parquet output is
+    /// unavailable.
+    #[error("Parquet error: {0}")]
+    Parquet(String),
+
+    /// Raised when a transform script cannot be loaded or fails on a node.
+    #[error("script error: {0}")]
+    Script(String),
+
     /// Raised when an invalid IRI is encountered while building RDF nodes.
     #[error("invalid IRI: {0}")]
     InvalidIri(#[from] IriParseError),
@@ -50,18 +115,116 @@ pub enum ToolError {
     #[error("unsupported conversion from {from:?} to {to:?}")]
     UnsupportedConversion { from: String, to: String },
 
+    /// Raised when stdin or stdout is asked to carry a representation that
+    /// needs a file.
+    #[error("{format} cannot be read from stdin or written to stdout")]
+    UnsupportedStream { format: String },
+
     /// Raised when a required sheet or mapping entry is missing.
     #[error("missing metadata entry for sheet {0}")]
     MissingMetadata(String),
 
+    /// Raised by strict reads of workbooks and CSV directories when a cell
+    /// gives a property of a node another value than an earlier row
+    /// describing the same node.
+    #[error(
+        "cell in column {column} of row {row} on sheet {sheet} conflicts with an earlier row for the same node"
+    )]
+    ConflictingCell {
+        sheet: String,
+        row: usize,
+        column: String,
+    },
+
+    /// Raised when cells of a workbook or CSV directory cannot be read. The
+    /// whole workbook is read first, so every such cell is listed.
+    #[error("{} cell(s) could not be read:{}", .0.len(), list_cells(.0))]
+    InvalidCells(Vec<CellError>),
+
     /// Raised when numeric parsing fails when rebuilding typed values.
     #[error("invalid literal value '{value}' in column {column}")]
     InvalidLiteral { column: String, value: String },
 
+    /// Raised when a scalar coercion table cannot be interpreted.
+    #[error("invalid scalar coercion table: {0}")]
+    InvalidCoercions(String),
+
+    /// Raised when sheet and column labels would make workbook columns
+    /// ambiguous.
+    #[error("invalid label translations: {0}")]
+    InvalidLabels(String),
+
+    /// Raised when a configuration file cannot be interpreted or asks for
+    /// something the conversion cannot do.
+    #[error("invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    /// Raised when a conversion outgrows one of its size limits, before the
+    /// output is written.
+    #[error("conversion stopped: {found} {what} exceed the limit of {limit}")]
+    LimitExceeded {
+        what: &'static str,
+        found: usize,
+        limit: usize,
+    },
+
+    /// Raised when a conversion stops because cancellation was requested,
+    /// see [`interrupt`](crate::aideon::tools::interrupt).
+    #[error("interrupted after processing {nodes} node(s)")]
+    Interrupted { nodes: usize },
+
     /// Raised when the user provides a path that does not exist.
     #[error("input file not found: {0}")]
     MissingInput(PathBuf),
 
+    /// Raised when blank nodes are too symmetric to be labelled canonically
+    /// within the labellings the search may compare.
+    #[error(
+        "cannot label {count} blank node(s) canonically: their structure is too symmetric to settle within {budget} labellings"
+    )]
+    SymmetricBlankNodes { count: usize, budget: usize },
+
+    /// Raised when two merged datasets disagree on a property value.
+    #[error("merge conflict on {id} [{predicate}]: {reason}")]
+    MergeConflict {
+        id: String,
+        predicate: String,
+        reason: String,
+    },
+
+    /// Raised when a node cannot be read as a typed
+    /// [`View`](crate::aideon::tools::view::View).
+    #[error("node {id} does not fit the view: {reason}")]
+    View { id: String, reason: String },
+
+    /// Raised under a strict context check when the output context leaves
+    /// predicates or types of the dataset unmapped.
+    #[error("the JSON-LD context does not map {count} predicate or type IRI(s)")]
+    UnmappedIris { count: usize },
+
+    /// Raised when a dataset does not survive the workbook round trip.
+    #[error("round trip through the workbook alters {losses} value(s)")]
+    LossyRoundTrip { losses: usize },
+
+    /// Raised when some files of a batch conversion fail; the others are
+    /// still converted.
+    #[error("{failed} of {total} file(s) failed to convert")]
+    BatchFailed { failed: usize, total: usize },
+
+    /// Raised when a signature or signing key cannot be read, or signing is
+    /// unavailable.
+    #[error("invalid signature: {0}")]
+    Signature(String),
+
+    /// Raised when a signature does not match the dataset it is checked
+    /// against.
+    #[error("the signature does not match the dataset")]
+    InvalidSignature,
+
+    /// Raised when validation finds one or more errors in a dataset.
+    #[error("validation failed with {errors} error(s)")]
+    ValidationFailed { errors: usize },
+
     /// Raised when the tracing subscriber fails to initialise.
     #[error("failed to initialise logging: {0}")]
     Logging(String),
@@ -0,0 +1,179 @@
+//! Selection of the nodes and properties a conversion keeps.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::aideon::tools::model::{Iri, Node};
+
+/// Graphs, types, and predicates kept by the conversions given it in
+/// [`SyncOptions::node_filter`](crate::aideon::tools::sync::SyncOptions::node_filter).
+///
+/// The default filter keeps every node. Naming graphs, the default graph, or
+/// both keeps only the nodes in one of them. Type patterns keep the nodes
+/// with a matching type, untyped nodes included only when no includes are
+/// given, and predicate patterns keep the matching properties of the nodes
+/// kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeFilter {
+    /// Named graphs whose nodes are kept.
+    pub graphs: BTreeSet<Iri>,
+    /// Keeps the nodes of the default graph.
+    pub default_graph: bool,
+    /// Types of the nodes kept; every type when empty.
+    pub include_types: Vec<IriPattern>,
+    /// Types whose nodes are dropped, even when included.
+    pub exclude_types: Vec<IriPattern>,
+    /// Predicates of the properties kept; every predicate when empty.
+    pub include_predicates: Vec<IriPattern>,
+    /// Predicates whose properties are dropped, even when included.
+    pub exclude_predicates: Vec<IriPattern>,
+}
+
+impl NodeFilter {
+    /// Creates a filter keeping every node.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps the nodes of the named graph `graph`.
+    pub fn with_graph(mut self, graph: impl Into<Iri>) -> Self {
+        self.graphs.insert(graph.into());
+        self
+    }
+
+    /// Keeps the nodes of the default graph.
+    pub fn with_default_graph(mut self) -> Self {
+        self.default_graph = true;
+        self
+    }
+
+    /// Keeps only the nodes with a type matching `pattern` or another
+    /// included type.
+    pub fn including_type(mut self, pattern: IriPattern) -> Self {
+        self.include_types.push(pattern);
+        self
+    }
+
+    /// Drops the nodes with a type matching `pattern`.
+    pub fn excluding_type(mut self, pattern: IriPattern) -> Self {
+        self.exclude_types.push(pattern);
+        self
+    }
+
+    /// Keeps only the properties whose predicate matches `pattern` or
+    /// another included predicate.
+    pub fn including_predicate(mut self, pattern: IriPattern) -> Self {
+        self.include_predicates.push(pattern);
+        self
+    }
+
+    /// Drops the properties whose predicate matches `pattern`.
+    pub fn excluding_predicate(mut self, pattern: IriPattern) -> Self {
+        self.exclude_predicates.push(pattern);
+        self
+    }
+
+    /// Returns whether the filter keeps every node and property.
+    pub fn keeps_all(&self) -> bool {
+        self.graphs.is_empty()
+            && !self.default_graph
+            && self.include_types.is_empty()
+            && self.exclude_types.is_empty()
+            && self.include_predicates.is_empty()
+            && self.exclude_predicates.is_empty()
+    }
+
+    /// Returns whether `node` is kept.
+    pub fn matches(&self, node: &Node) -> bool {
+        let in_graph = match &node.graph {
+            _ if self.graphs.is_empty() && !self.default_graph => true,
+            Some(graph) => self.graphs.contains(graph),
+            None => self.default_graph,
+        };
+        let typed = |patterns: &[IriPattern]| {
+            node.types
+                .iter()
+                .any(|type_name| patterns.iter().any(|pattern| pattern.matches(type_name)))
+        };
+        in_graph
+            && (self.include_types.is_empty() || typed(&self.include_types))
+            && !typed(&self.exclude_types)
+    }
+
+    /// Returns whether properties with `predicate` are kept.
+    pub fn keeps_predicate(&self, predicate: &str) -> bool {
+        let matched = |patterns: &[IriPattern]| patterns.iter().any(|p| p.matches(predicate));
+        (self.include_predicates.is_empty() || matched(&self.include_predicates))
+            && !matched(&self.exclude_predicates)
+    }
+
+    /// Returns the nodes of `nodes` that are kept, in order, without the
+    /// properties that are not.
+    pub fn apply(&self, nodes: Vec<Node>) -> Vec<Node> {
+        if self.keeps_all() {
+            return nodes;
+        }
+        let filters_predicates =
+            !self.include_predicates.is_empty() || !self.exclude_predicates.is_empty();
+        nodes
+            .into_iter()
+            .filter(|node| self.matches(node))
+            .map(|mut node| {
+                if filters_predicates {
+                    node.properties
+                        .retain(|predicate, _| self.keeps_predicate(predicate));
+                }
+                node
+            })
+            .collect()
+    }
+}
+
+/// An IRI, or a pattern where `*` stands for any run of characters, such as
+/// `https://schema.org/*` or `*Event`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IriPattern {
+    pattern: String,
+}
+
+impl IriPattern {
+    /// Reads `pattern`, expanding a leading compact IRI prefix such as
+    /// `schema:` with `prefixes`.
+    pub fn new(pattern: &str, prefixes: &BTreeMap<String, String>) -> Self {
+        let expanded = pattern
+            .split_once(':')
+            .filter(|(_, rest)| !rest.starts_with("//"))
+            .and_then(|(prefix, rest)| {
+                prefixes
+                    .get(prefix)
+                    .map(|namespace| format!("{namespace}{rest}"))
+            });
+        IriPattern {
+            pattern: expanded.unwrap_or_else(|| pattern.to_string()),
+        }
+    }
+
+    /// The pattern with its prefix expanded.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns whether `iri` matches the pattern.
+    pub fn matches(&self, iri: &str) -> bool {
+        let mut parts = self.pattern.split('*');
+        let first = parts.next().unwrap_or_default();
+        let Some(mut rest) = iri.strip_prefix(first) else {
+            return false;
+        };
+        let mut parts: Vec<&str> = parts.collect();
+        let Some(last) = parts.pop() else {
+            return rest.is_empty();
+        };
+        for part in parts {
+            match rest.find(part) {
+                Some(index) => rest = &rest[index + part.len()..],
+                None => return false,
+            }
+        }
+        rest.len() >= last.len() && rest.ends_with(last)
+    }
+}
@@ -1,9 +1,16 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 
+use serde::Deserialize;
 use serde_json::Value;
 
-use crate::aideon::tools::error::Result;
-use crate::aideon::tools::model::{ArrayValue, Node, PropertyValue, ScalarValue};
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::filter::IriPattern;
+use crate::aideon::tools::model::{
+    ArrayItem, ArrayValue, Iri, Node, NodeId, PropertyValue, ScalarValue, XSD_BOOLEAN, XSD_DATE,
+    XSD_DATE_TIME, XSD_DOUBLE, XSD_INTEGER, XSD_STRING,
+};
+use crate::aideon::tools::same_as;
 
 /// Name used for nodes that do not declare a type.
 pub const UNTYPED_MARKER: &str = "__untyped__";
@@ -11,6 +18,34 @@ pub const UNTYPED_MARKER: &str = "__untyped__";
 pub const ENTITIES_SHEET: &str = "Entities";
 /// Sheet name storing metadata such as sheet → type mappings.
 pub const METADATA_SHEET: &str = "Metadata";
+/// Sheet name storing the JSON-LD context embedded in the workbook.
+pub const CONTEXT_SHEET: &str = "Context";
+/// Term of the only `Context` row of contexts that are not a term map.
+pub const WHOLE_CONTEXT_TERM: &str = "@context";
+/// Cell text marking a link on matrix sheets.
+pub const MATRIX_MARK: &str = "X";
+/// Header of the column giving the types of the rows of a sheet holding
+/// consolidated subtypes, see [`WorkbookOptions::consolidated_types`].
+pub const SUBTYPE_COLUMN: &str = "subtype";
+/// Sheet name storing the dataset-level metadata of the dataset node, see
+/// [`build_dataset_table`].
+pub const DATASET_SHEET: &str = "Dataset";
+/// Type of the dataset node described by the `Dataset` sheet.
+pub const DATASET_TYPE: &str = "http://www.w3.org/ns/dcat#Dataset";
+/// Keys of the `Dataset` sheet standing for the predicates of common
+/// dataset-level metadata.
+pub const DATASET_KEYS: [(&str, &str); 5] = [
+    ("title", "http://purl.org/dc/terms/title"),
+    ("description", "http://purl.org/dc/terms/description"),
+    ("version", "http://www.w3.org/ns/dcat#version"),
+    ("license", "http://purl.org/dc/terms/license"),
+    (
+        "defaultGraph",
+        "http://www.w3.org/ns/sparql-service-description#defaultGraph",
+    ),
+];
+
+const RDFS_SUB_CLASS_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
 
 /// A table that will be materialised as an Excel sheet.
 #[derive(Debug, Clone, PartialEq)]
@@ -20,147 +55,1339 @@ pub struct SheetTable {
     pub rows: Vec<Vec<String>>,
 }
 
+/// How literal cells are encoded in type sheets.
+///
+/// Excel workbooks store single literals as typed cells under either
+/// encoding, see [`excel_write`](crate::aideon::tools::io::excel_write); the
+/// encoding decides the text of every other literal cell and of CSV files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CellEncoding {
+    /// Every literal is written as JSON, so strings are quoted (`"Alice"`).
+    #[default]
+    Json,
+    /// Strings are written as bare text (`Alice`). Text that would otherwise
+    /// read back as JSON, is blank, or starts with an apostrophe is prefixed
+    /// with `'` (`'30`, `'true`, `''quoted`). Other literals stay JSON.
+    Bare,
+}
+
+/// Marker that forces a bare cell to be read as text.
+pub const TEXT_PREFIX: char = '\'';
+
+/// Kind of value held by a type sheet column, recorded in `Metadata`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// Single literals.
+    Scalar,
+    /// JSON arrays of literals.
+    ScalarArray,
+    /// Identifier of a single referenced node.
+    Ref,
+    /// JSON arrays mixing literals and `{"@id": ...}` references.
+    Mixed,
+}
+
+impl ColumnKind {
+    /// Name used for the kind in the `Metadata` sheet.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ColumnKind::Scalar => "scalar",
+            ColumnKind::ScalarArray => "scalar-array",
+            ColumnKind::Ref => "ref",
+            ColumnKind::Mixed => "mixed",
+        }
+    }
+
+    /// Parses a kind name written by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "scalar" => Some(ColumnKind::Scalar),
+            "scalar-array" => Some(ColumnKind::ScalarArray),
+            "ref" => Some(ColumnKind::Ref),
+            "mixed" => Some(ColumnKind::Mixed),
+            _ => None,
+        }
+    }
+}
+
+/// Predicate, kind, and literal datatype of one type sheet column.
+#[derive(Debug, Clone, PartialEq)]
+struct ColumnSpec {
+    predicate: String,
+    kind: ColumnKind,
+    datatype: DatatypeUse,
+}
+
+/// Datatypes seen in a column: a single shared one is recorded in `Metadata`.
+/// Plain literals count as the XML Schema datatype they stand for, so
+/// columns mixing integers and doubles declare no datatype and each cell
+/// keeps its own.
+#[derive(Debug, Clone, PartialEq)]
+enum DatatypeUse {
+    Unseen,
+    Uniform(String),
+    Mixed,
+}
+
+impl ColumnSpec {
+    fn new(predicate: &str, kind: ColumnKind) -> Self {
+        Self {
+            predicate: predicate.to_string(),
+            kind,
+            datatype: DatatypeUse::Unseen,
+        }
+    }
+
+    /// Folds another use of the column into the spec. A column holding both
+    /// single literals and arrays is declared `scalar` so neither is coerced
+    /// on read, and one holding literal and mixed arrays is declared `mixed`;
+    /// a header shared by a literal and a reference keeps the kind seen
+    /// first. Mixed arrays never declare a datatype.
+    fn observe(&mut self, kind: ColumnKind, scalars: &[ScalarValue]) {
+        match (self.kind, kind) {
+            (ColumnKind::ScalarArray | ColumnKind::Mixed, ColumnKind::Scalar) => {
+                self.kind = ColumnKind::Scalar;
+            }
+            (ColumnKind::ScalarArray, ColumnKind::Mixed) => self.kind = ColumnKind::Mixed,
+            _ => {}
+        }
+        if kind == ColumnKind::Mixed {
+            self.datatype = DatatypeUse::Mixed;
+        }
+        for scalar in scalars {
+            let datatype = match scalar {
+                ScalarValue::Null => continue,
+                ScalarValue::String(_) => Some(XSD_STRING),
+                ScalarValue::Integer(_) => Some(XSD_INTEGER),
+                ScalarValue::Double(_) => Some(XSD_DOUBLE),
+                ScalarValue::Boolean(_) => Some(XSD_BOOLEAN),
+                ScalarValue::Date(_) => Some(XSD_DATE),
+                ScalarValue::DateTime(_) => Some(XSD_DATE_TIME),
+                ScalarValue::TypedLiteral { datatype, .. } => Some(datatype.as_str()),
+                ScalarValue::LangString { .. } => None,
+            };
+            match (&self.datatype, datatype) {
+                (DatatypeUse::Unseen, Some(datatype)) => {
+                    self.datatype = DatatypeUse::Uniform(datatype.to_string());
+                }
+                (DatatypeUse::Uniform(seen), Some(datatype)) if seen == datatype => {}
+                _ => self.datatype = DatatypeUse::Mixed,
+            }
+        }
+    }
+
+    fn datatype(&self) -> &str {
+        match &self.datatype {
+            DatatypeUse::Uniform(datatype) => datatype,
+            DatatypeUse::Unseen | DatatypeUse::Mixed => "",
+        }
+    }
+}
+
 /// Represents all tables required to materialise the Excel workbook.
 #[derive(Debug, Clone, PartialEq)]
 pub struct WorkbookData {
     pub tables: Vec<SheetTable>,
 }
 
+impl WorkbookData {
+    /// Returns the number of cells of all tables, header rows included, like
+    /// [`WorkbookLayout::cell_count`].
+    pub fn cell_count(&self) -> usize {
+        self.tables
+            .iter()
+            .map(|table| (table.rows.len() + 1) * table.columns.len())
+            .sum()
+    }
+}
+
 /// Flattens the provided nodes into a set of tables following the spreadsheet
 /// conventions described in the project documentation.
 pub fn build_workbook(nodes: &[Node]) -> Result<WorkbookData> {
-    let mut sheet_names = SheetNameRegistry::default();
+    build_workbook_with(nodes, CellEncoding::default())
+}
+
+/// Flattens `nodes` like [`build_workbook`], encoding literal cells with
+/// `encoding`.
+pub fn build_workbook_with(nodes: &[Node], encoding: CellEncoding) -> Result<WorkbookData> {
+    build_workbook_with_options(
+        nodes,
+        &WorkbookOptions {
+            encoding,
+            ..WorkbookOptions::default()
+        },
+    )
+}
+
+/// Choices shaping the workbooks written by [`build_workbook_with_options`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkbookOptions {
+    /// Encoding of literal cells.
+    pub encoding: CellEncoding,
+    /// Predicates whose multi-valued references are written as matrix
+    /// sheets, with one row per subject, one column per target, and
+    /// [`MATRIX_MARK`] where they are linked, instead of as child sheets.
+    pub matrix_predicates: BTreeSet<Iri>,
+    /// Predicates whose columns lead the sheet of each type, in the given
+    /// order and ahead of the remaining columns in their usual sorted order.
+    /// A predicate names its literal column and its `Id` reference column.
+    pub column_order: BTreeMap<Iri, Vec<Iri>>,
+    /// Labelled bands of columns leading the sheet of each type, in the
+    /// given order and ahead of `column_order`. `Metadata` declares each
+    /// band with a `group` row; Excel workbooks show the labels in a row
+    /// above the headers and outline the bands so they can be collapsed.
+    pub column_groups: BTreeMap<Iri, Vec<ColumnGroup>>,
+    /// JSON-LD context embedded as the `Context` sheet, see
+    /// [`WorkbookLayout::context_table`].
+    pub context: Option<Value>,
+    /// Most cells the workbook may hold, see [`WorkbookLayout::check_cells`].
+    pub max_cells: Option<usize>,
+    /// Prefixes abbreviating the predicates that head type and child sheet
+    /// columns, such as `schema:name` for `https://schema.org/name`. The
+    /// prefixes used are declared by `prefix` rows in `Metadata`.
+    pub prefixes: BTreeMap<String, String>,
+    /// Context terms heading the columns of their predicates ahead of
+    /// `prefixes`, such as `name` for `https://schema.org/name` and
+    /// `knowsId` for references. The terms used are declared by `term` rows
+    /// in `Metadata`; terms that would head a column like a fixed one are
+    /// not used.
+    pub header_terms: BTreeMap<Iri, String>,
+    /// Labels naming the sheets of types and the columns of predicates in
+    /// place of their IRIs, such as translations for the team editing the
+    /// workbook. `Metadata` maps the labelled names back to the IRIs, see
+    /// [`check_labels`] for the labels allowed.
+    pub labels: BTreeMap<Iri, String>,
+    /// Cardinality of predicates, deciding their layout whatever the values
+    /// of the nodes at hand, see [`Cardinality`]. Other predicates are laid
+    /// out by the shape of their values.
+    pub cardinalities: BTreeMap<Iri, Cardinality>,
+    /// How sheets are named after the types and predicates they hold when
+    /// neither `labels` nor `sheet_names` name them.
+    pub sheet_naming: SheetNaming,
+    /// Names of the sheets of types, and of the predicates in child and
+    /// matrix sheet names, such as `Person` for `https://schema.org/Person`.
+    /// Unlike `labels` they leave column headers alone. `Metadata` maps the
+    /// sheets back to their IRIs.
+    pub sheet_names: BTreeMap<Iri, String>,
+    /// Types laid out on the sheet of another type, such as thin subclasses
+    /// on the sheet of their superclass, see [`consolidate_subclasses`].
+    /// Such sheets get a [`SUBTYPE_COLUMN`] listing the types of rows whose
+    /// node has any other than the sheet's own, so they read back unchanged.
+    pub consolidated_types: BTreeMap<Iri, Iri>,
+    /// Predicates whose values are left out of the workbook, such as
+    /// volatile audit metadata, see [`without_ignored`].
+    pub ignored_predicates: Vec<IriPattern>,
+    /// Sorts the rows of nodes linked by `owl:sameAs` next to those of the
+    /// canonical node of their cluster, see [`same_as::row_anchors`].
+    pub colocate_same_as: bool,
+}
+
+/// A labelled band of adjacent columns on the sheet of a type, such as
+/// `Identification` over the name and identifier columns.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ColumnGroup {
+    /// Label shown above the band.
+    pub label: String,
+    /// Predicates whose columns make up the band, in order. A predicate
+    /// names its literal column and its `Id` reference column.
+    pub predicates: Vec<Iri>,
+}
+
+/// How sheets are named after the IRIs of their types and predicates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SheetNaming {
+    /// The whole IRI, with the characters Excel forbids replaced, such as
+    /// `https___schema.org_Person`.
+    #[default]
+    Iri,
+    /// The local name after the IRI's last `/`, `#`, or `:`, such as
+    /// `Person`. Local names shared by several IRIs get a numbered suffix.
+    LocalName,
+}
+
+/// How many values a predicate takes, as declared by a schema.
+///
+/// Without one, a predicate holding a single value on every node becomes a
+/// plain column and one holding several an array cell or child sheet, so
+/// the layout flips as soon as the data does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Cardinality {
+    /// Functional predicates, with one value per node, get a literal or `Id`
+    /// column; one-item arrays are written as their item. Nodes holding
+    /// several values still get an array cell or child sheet.
+    One,
+    /// Multi-valued predicates get an array cell for literals and a child or
+    /// matrix sheet for references, even on nodes holding a single value.
+    Many,
+}
+
+/// Returns `value` in the shape `cardinality` lays it out in, see
+/// [`Cardinality`].
+fn shaped(value: &PropertyValue, cardinality: Option<Cardinality>) -> Cow<'_, PropertyValue> {
+    match (cardinality, value) {
+        (Some(Cardinality::Many), PropertyValue::Scalar(scalar)) => {
+            Cow::Owned(PropertyValue::Array(ArrayValue::Scalars(vec![
+                scalar.clone(),
+            ])))
+        }
+        (Some(Cardinality::Many), PropertyValue::ObjectRef(target)) => {
+            Cow::Owned(PropertyValue::Array(ArrayValue::ObjectRefs(vec![
+                target.clone(),
+            ])))
+        }
+        (Some(Cardinality::One), PropertyValue::Array(ArrayValue::Scalars(items)))
+            if items.len() == 1 =>
+        {
+            Cow::Owned(PropertyValue::Scalar(items[0].clone()))
+        }
+        (Some(Cardinality::One), PropertyValue::Array(ArrayValue::ObjectRefs(targets)))
+            if targets.len() == 1 =>
+        {
+            Cow::Owned(PropertyValue::ObjectRef(targets[0].clone()))
+        }
+        _ => Cow::Borrowed(value),
+    }
+}
+
+/// Headers of the fixed columns of type and child sheets.
+const FIXED_COLUMNS: [&str; 5] = ["id", "graph", SUBTYPE_COLUMN, "ParentId", "ParentGraph"];
+
+/// Fails with [`ToolError::InvalidLabels`] when two types or predicates
+/// share a label, or a label heads a column like a fixed one, since the
+/// workbook could not tell their columns apart.
+pub fn check_labels(labels: &BTreeMap<Iri, String>) -> Result<()> {
+    let mut named: BTreeMap<&str, &Iri> = BTreeMap::new();
+    for (iri, label) in labels {
+        if label.trim().is_empty() {
+            return Err(ToolError::InvalidLabels(format!("{iri} has a blank label")));
+        }
+        if FIXED_COLUMNS.contains(&label.as_str())
+            || FIXED_COLUMNS.contains(&format!("{label}Id").as_str())
+        {
+            return Err(ToolError::InvalidLabels(format!(
+                "label '{label}' of {iri} clashes with a fixed column"
+            )));
+        }
+        if let Some(other) = named.insert(label, iri) {
+            return Err(ToolError::InvalidLabels(format!(
+                "label '{label}' is given to both {other} and {iri}"
+            )));
+        }
+    }
+    Ok(())
+}
 
-    let mut type_builders: BTreeMap<String, TypeTableBuilder> = BTreeMap::new();
-    let mut child_builders: BTreeMap<(String, String), ChildTableBuilder> = BTreeMap::new();
-    let mut entities: Vec<(String, String, Option<String>)> = Vec::new();
+/// Returns `nodes` without the properties whose predicate matches one of
+/// the `ignored` patterns.
+pub fn without_ignored<'a>(nodes: &'a [Node], ignored: &[IriPattern]) -> Cow<'a, [Node]> {
+    let ignores = |predicate: &Iri| ignored.iter().any(|pattern| pattern.matches(predicate));
+    if !nodes.iter().any(|node| node.properties.keys().any(ignores)) {
+        return Cow::Borrowed(nodes);
+    }
+    Cow::Owned(
+        nodes
+            .iter()
+            .map(|node| {
+                let mut node = node.clone();
+                node.properties.retain(|predicate, _| !ignores(predicate));
+                node
+            })
+            .collect(),
+    )
+}
 
-    for node in nodes {
-        let node_types: Vec<String> = if node.types.is_empty() {
-            vec![UNTYPED_MARKER.to_string()]
+/// Flattens `nodes` like [`build_workbook`], laid out as `options` asks.
+pub fn build_workbook_with_options(
+    nodes: &[Node],
+    options: &WorkbookOptions,
+) -> Result<WorkbookData> {
+    let nodes = &*without_ignored(nodes, &options.ignored_predicates);
+    let (dataset, nodes) = split_dataset(nodes);
+    let dataset = dataset
+        .map(|node| build_dataset_table(node, options.encoding))
+        .transpose()?;
+    let mut layout = LayoutBuilder::default()
+        .with_matrix_predicates(options.matrix_predicates.iter().cloned())
+        .with_column_order(options.column_order.clone())
+        .with_column_groups(options.column_groups.clone())
+        .with_prefixes(options.prefixes.clone())
+        .with_header_terms(options.header_terms.clone())
+        .with_labels(options.labels.clone())
+        .with_sheet_names(options.sheet_naming, options.sheet_names.clone())
+        .with_cardinalities(options.cardinalities.clone())
+        .with_consolidated_types(options.consolidated_types.clone());
+    check_labels(&options.labels)?;
+    layout.observe(&nodes);
+    let layout = layout
+        .build()
+        .with_encoding(options.encoding)
+        .with_context(options.context.clone())
+        .with_dataset(dataset)
+        .with_row_anchors(if options.colocate_same_as {
+            same_as::row_anchors(&nodes)
         } else {
-            node.types.iter().cloned().collect()
-        };
+            BTreeMap::new()
+        });
+    layout.check_cells(options.max_cells)?;
 
-        for (type_index, type_name) in node_types.iter().enumerate() {
-            entities.push((node.id.clone(), type_name.clone(), node.graph.clone()));
+    let mut tables = layout.tables(&nodes)?;
+    tables.insert(1, layout.metadata_table());
+    let mut next = 2;
+    if let Some(context) = layout.context_table() {
+        tables.insert(next, context);
+        next += 1;
+    }
+    if let Some(dataset) = layout.dataset_table() {
+        tables.insert(next, dataset.clone());
+    }
+    Ok(WorkbookData { tables })
+}
 
-            let builder = type_builders
-                .entry(type_name.clone())
-                .or_insert_with(TypeTableBuilder::new);
+/// Splits the dataset node off `nodes`: the only node of type
+/// [`DATASET_TYPE`], if there is exactly one. Several dataset nodes stay on
+/// the sheet of their type.
+pub fn split_dataset(nodes: &[Node]) -> (Option<&Node>, Cow<'_, [Node]>) {
+    let mut datasets = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.types.contains(DATASET_TYPE));
+    let (Some((index, dataset)), None) = (datasets.next(), datasets.next()) else {
+        return (None, Cow::Borrowed(nodes));
+    };
+    let mut rest = nodes.to_vec();
+    rest.remove(index);
+    (Some(dataset), Cow::Owned(rest))
+}
 
-            let mut row_values: BTreeMap<String, String> = BTreeMap::new();
+/// Builds the `Dataset` sheet describing `node` as `key` and `value` rows:
+/// its `id`, its `graph` when it has one, a `type` row per type besides
+/// [`DATASET_TYPE`], and a row per value of each property. Properties are
+/// keyed by their [`DATASET_KEYS`] name, such as `title` for `dct:title`,
+/// or else by their predicate IRI, with `Id` appended for references like
+/// column headers. Literals are encoded like type sheet cells.
+pub fn build_dataset_table(node: &Node, encoding: CellEncoding) -> Result<SheetTable> {
+    let mut rows = vec![vec!["id".to_string(), node.id.to_string()]];
+    if let Some(graph) = &node.graph {
+        rows.push(vec!["graph".to_string(), graph.to_string()]);
+    }
+    for type_name in &node.types {
+        if type_name.as_str() != DATASET_TYPE {
+            rows.push(vec!["type".to_string(), type_name.to_string()]);
+        }
+    }
+    for (predicate, value) in &node.properties {
+        let key = DATASET_KEYS
+            .iter()
+            .find(|(_, iri)| *iri == predicate.as_str())
+            .map_or(predicate.as_str(), |(key, _)| key);
+        for item in value.items() {
+            rows.push(match item {
+                ArrayItem::Scalar(scalar) => {
+                    vec![key.to_string(), scalar_to_cell_value(&scalar, encoding)?]
+                }
+                ArrayItem::ObjectRef(target) => vec![format!("{key}Id"), target.to_string()],
+            });
+        }
+    }
+    Ok(SheetTable {
+        sheet_name: DATASET_SHEET.to_string(),
+        columns: vec!["key".to_string(), "value".to_string()],
+        rows,
+    })
+}
 
-            for (predicate, value) in &node.properties {
-                match value {
-                    PropertyValue::Scalar(scalar) => {
-                        builder.columns.insert(predicate.clone());
-                        row_values.insert(predicate.clone(), scalar_to_cell_value(scalar)?);
-                    }
-                    PropertyValue::ObjectRef(target) => {
-                        let column_name = format!("{predicate}Id");
-                        builder.columns.insert(column_name.clone());
-                        row_values.insert(column_name, target.clone());
-                    }
-                    PropertyValue::Array(ArrayValue::Scalars(items)) => {
-                        builder.columns.insert(predicate.clone());
-                        let json_items: Vec<Value> =
-                            items.iter().map(ScalarValue::to_json).collect();
-                        let json_string = serde_json::to_string(&Value::Array(json_items))?;
-                        row_values.insert(predicate.clone(), json_string);
-                    }
-                    PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => {
-                        if type_index == 0 {
-                            let child_builder = child_builders
-                                .entry((type_name.clone(), predicate.clone()))
-                                .or_insert_with(|| ChildTableBuilder::new(predicate.clone()));
-                            for target in targets {
-                                child_builder.rows.push((
-                                    node.id.clone(),
-                                    node.graph.clone(),
-                                    target.clone(),
-                                ));
+/// Accumulates the sheet structure of a dataset that is read in chunks.
+#[derive(Debug, Default)]
+pub struct LayoutBuilder {
+    type_columns: BTreeMap<Iri, BTreeMap<String, ColumnSpec>>,
+    type_rows: BTreeMap<Iri, usize>,
+    child_rows: BTreeMap<(Iri, Iri), usize>,
+    matrix_predicates: BTreeSet<Iri>,
+    /// Targets and row count of each matrix sheet.
+    matrix_targets: BTreeMap<(Iri, Iri), (BTreeSet<NodeId>, usize)>,
+    column_order: BTreeMap<Iri, Vec<Iri>>,
+    column_groups: BTreeMap<Iri, Vec<ColumnGroup>>,
+    headers: Headers,
+    cardinalities: BTreeMap<Iri, Cardinality>,
+    consolidated_types: BTreeMap<Iri, Iri>,
+    /// Sheet types holding rows of other types, which get a subtype column.
+    subtyped: BTreeSet<Iri>,
+    entity_rows: usize,
+}
+
+impl LayoutBuilder {
+    /// Lays out the multi-valued references of `predicates` as matrix
+    /// sheets, see [`WorkbookOptions::matrix_predicates`].
+    pub fn with_matrix_predicates(mut self, predicates: impl IntoIterator<Item = Iri>) -> Self {
+        self.matrix_predicates.extend(predicates);
+        self
+    }
+
+    /// Puts the columns of the given predicates first on each type sheet,
+    /// see [`WorkbookOptions::column_order`].
+    pub fn with_column_order(mut self, column_order: BTreeMap<Iri, Vec<Iri>>) -> Self {
+        self.column_order = column_order;
+        self
+    }
+
+    /// Bands the columns of the given predicates on each type sheet, see
+    /// [`WorkbookOptions::column_groups`].
+    pub fn with_column_groups(mut self, column_groups: BTreeMap<Iri, Vec<ColumnGroup>>) -> Self {
+        self.column_groups = column_groups;
+        self
+    }
+
+    /// Abbreviates column headers with `prefixes`, see
+    /// [`WorkbookOptions::prefixes`].
+    pub fn with_prefixes(mut self, prefixes: BTreeMap<String, String>) -> Self {
+        self.headers.prefixes = prefixes;
+        self
+    }
+
+    /// Heads columns with context `terms`, see
+    /// [`WorkbookOptions::header_terms`].
+    pub fn with_header_terms(mut self, mut terms: BTreeMap<Iri, String>) -> Self {
+        terms.retain(|_, term| {
+            !FIXED_COLUMNS.contains(&term.as_str())
+                && !FIXED_COLUMNS.contains(&format!("{term}Id").as_str())
+        });
+        self.headers.terms = terms;
+        self
+    }
+
+    /// Names sheets and columns with `labels`, see
+    /// [`WorkbookOptions::labels`].
+    pub fn with_labels(mut self, labels: BTreeMap<Iri, String>) -> Self {
+        self.headers.labels = labels;
+        self
+    }
+
+    /// Names sheets with `names`, and the others as `naming` asks, see
+    /// [`WorkbookOptions::sheet_names`].
+    pub fn with_sheet_names(mut self, naming: SheetNaming, names: BTreeMap<Iri, String>) -> Self {
+        self.headers.sheet_naming = naming;
+        self.headers.sheet_names = names;
+        self
+    }
+
+    /// Lays out predicates by their cardinality, see
+    /// [`WorkbookOptions::cardinalities`].
+    pub fn with_cardinalities(mut self, cardinalities: BTreeMap<Iri, Cardinality>) -> Self {
+        self.cardinalities = cardinalities;
+        self
+    }
+
+    /// Lays out types on the sheets of others, see
+    /// [`WorkbookOptions::consolidated_types`].
+    pub fn with_consolidated_types(mut self, consolidated_types: BTreeMap<Iri, Iri>) -> Self {
+        self.consolidated_types = consolidated_types;
+        self
+    }
+
+    /// Records the types, columns, and row counts contributed by `nodes`.
+    pub fn observe(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            self.entity_rows += node_types(node).len();
+            for (type_name, types) in sheet_types(node, &self.consolidated_types) {
+                if types.iter().any(|subtype| **subtype != type_name) {
+                    self.subtyped.insert(type_name.clone());
+                }
+                let columns = self.type_columns.entry(type_name.clone()).or_default();
+                *self.type_rows.entry(type_name.clone()).or_default() += 1;
+
+                for (predicate, value) in &node.properties {
+                    let value = shaped(value, self.cardinalities.get(predicate).copied());
+                    let (header, kind, scalars) = match value.as_ref() {
+                        PropertyValue::Scalar(scalar) => (
+                            self.headers.literal(predicate),
+                            ColumnKind::Scalar,
+                            std::slice::from_ref(scalar),
+                        ),
+                        PropertyValue::Array(ArrayValue::Scalars(items)) => (
+                            self.headers.literal(predicate),
+                            ColumnKind::ScalarArray,
+                            items.as_slice(),
+                        ),
+                        PropertyValue::ObjectRef(_) => {
+                            (self.headers.reference(predicate), ColumnKind::Ref, &[][..])
+                        }
+                        PropertyValue::Array(ArrayValue::Mixed(_)) => {
+                            (self.headers.literal(predicate), ColumnKind::Mixed, &[][..])
+                        }
+                        PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => {
+                            let key = (type_name.clone(), predicate.clone());
+                            if self.matrix_predicates.contains(predicate) {
+                                let (columns, rows) = self.matrix_targets.entry(key).or_default();
+                                columns.extend(targets.iter().cloned());
+                                *rows += 1;
+                            } else {
+                                *self.child_rows.entry(key).or_default() += targets.len();
                             }
+                            continue;
                         }
-                    }
+                    };
+                    columns
+                        .entry(header)
+                        .or_insert_with(|| ColumnSpec::new(predicate, kind))
+                        .observe(kind, scalars);
                 }
             }
+        }
+    }
+
+    /// Assigns sheet names and fixes the column order of every sheet.
+    pub fn build(self) -> WorkbookLayout {
+        let mut sheet_names = SheetNameRegistry::default();
+        // Reserve names for Entities and Metadata to avoid collisions.
+        sheet_names.claim(ENTITIES_SHEET.to_string());
+        sheet_names.claim(METADATA_SHEET.to_string());
+        sheet_names.claim(CONTEXT_SHEET.to_string());
+        sheet_names.claim(DATASET_SHEET.to_string());
+
+        let mut type_rows = self.type_rows;
+        let mut column_bands = BTreeMap::new();
+        let type_sheets = self
+            .type_columns
+            .iter()
+            .map(|(type_name, specs)| {
+                let mut columns = Vec::with_capacity(specs.len() + 3);
+                columns.push("id".to_string());
+                columns.push("graph".to_string());
+                if self.subtyped.contains(type_name) {
+                    columns.push(SUBTYPE_COLUMN.to_string());
+                }
+                let fixed = columns.len();
+                columns.extend(specs.keys().cloned());
+                let groups = self.column_groups.get(type_name).map(Vec::as_slice);
+                let grouped = groups
+                    .into_iter()
+                    .flatten()
+                    .map(|group| group.predicates.as_slice());
+                let leading: Vec<String> = grouped
+                    .chain(self.column_order.get(type_name).map(Vec::as_slice))
+                    .flatten()
+                    .map(|predicate| self.headers.literal(predicate))
+                    .collect();
+                order_columns(&mut columns[fixed..], &leading);
+                if let Some(groups) = groups {
+                    let bands = band_columns(&columns[fixed..], groups, &self.headers);
+                    column_bands.insert(type_name.clone(), bands);
+                }
+                let sheet = SheetLayout {
+                    sheet_name: sheet_names.assign(&self.headers.sheet(type_name)),
+                    row_count: type_rows.remove(type_name).unwrap_or_default(),
+                    columns,
+                };
+                (type_name.clone(), sheet)
+            })
+            .collect();
+
+        let child_sheets: BTreeMap<(Iri, Iri), SheetLayout> = self
+            .child_rows
+            .into_iter()
+            .map(|((type_name, predicate), row_count)| {
+                let sheet = SheetLayout {
+                    sheet_name: sheet_names.assign(&self.headers.pair_name(&type_name, &predicate)),
+                    columns: vec![
+                        "ParentId".to_string(),
+                        "ParentGraph".to_string(),
+                        self.headers.reference(&predicate),
+                    ],
+                    row_count,
+                };
+                ((type_name, predicate), sheet)
+            })
+            .collect();
+
+        let matrix_sheets = self
+            .matrix_targets
+            .into_iter()
+            .map(|((type_name, predicate), (targets, row_count))| {
+                let mut columns = Vec::with_capacity(targets.len() + 2);
+                columns.push("id".to_string());
+                columns.push("graph".to_string());
+                columns.extend(targets.into_iter().map(String::from));
+                let sheet = SheetLayout {
+                    sheet_name: sheet_names.assign(&self.headers.pair_name(&type_name, &predicate)),
+                    columns,
+                    row_count,
+                };
+                ((type_name, predicate), sheet)
+            })
+            .collect();
+
+        // Only the prefixes that shortened a header are declared.
+        let headers = self
+            .type_columns
+            .values()
+            .flat_map(BTreeMap::keys)
+            .chain(child_sheets.values().map(|sheet| &sheet.columns[2]));
+        let headers: BTreeSet<&String> = headers.collect();
+        let prefixes = used_prefixes(headers.iter().copied(), &self.headers.prefixes);
+        let terms = self
+            .headers
+            .terms
+            .iter()
+            .filter(|(_, term)| headers.contains(term) || headers.contains(&format!("{term}Id")))
+            .map(|(iri, term)| (term.clone(), iri.clone()))
+            .collect();
+
+        WorkbookLayout {
+            type_sheets,
+            type_columns: self.type_columns,
+            child_sheets,
+            matrix_sheets,
+            encoding: CellEncoding::default(),
+            context: None,
+            dataset: None,
+            row_anchors: BTreeMap::new(),
+            prefixes,
+            terms,
+            headers: self.headers,
+            cardinalities: self.cardinalities,
+            consolidated_types: self.consolidated_types,
+            column_bands,
+            entity_rows: self.entity_rows,
+        }
+    }
+}
+
+/// Naming of the sheets and columns of types and predicates.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Headers {
+    prefixes: BTreeMap<String, String>,
+    terms: BTreeMap<Iri, String>,
+    labels: BTreeMap<Iri, String>,
+    sheet_naming: SheetNaming,
+    sheet_names: BTreeMap<Iri, String>,
+}
+
+impl Headers {
+    /// Name a type or predicate gives the sheets holding it: its label, its
+    /// sheet name, or else its IRI as the sheet naming asks.
+    fn sheet<'a>(&'a self, iri: &'a str) -> Cow<'a, str> {
+        if let Some(name) = self.labels.get(iri).or_else(|| self.sheet_names.get(iri)) {
+            return Cow::Borrowed(name);
+        }
+        match self.sheet_naming {
+            SheetNaming::Iri => Cow::Borrowed(iri),
+            SheetNaming::LocalName => Cow::Borrowed(local_name(iri)),
+        }
+    }
+
+    /// Name of the child or matrix sheet of `predicate` on `type_name`.
+    fn pair_name(&self, type_name: &str, predicate: &str) -> String {
+        format!("{}__{}", self.sheet(type_name), self.sheet(predicate))
+    }
 
-            builder.rows.push(RowData {
-                id: node.id.clone(),
-                graph: node.graph.clone(),
-                values: row_values,
+    /// Header of the literal column holding `predicate`: its label or
+    /// term, or else the predicate abbreviated with a prefix when one
+    /// applies.
+    fn literal(&self, predicate: &str) -> String {
+        match self
+            .labels
+            .get(predicate)
+            .or_else(|| self.terms.get(predicate))
+        {
+            Some(name) => name.clone(),
+            None => self.compact(predicate),
+        }
+    }
+
+    /// Header of the reference column holding `predicate`, ending in `Id`.
+    fn reference(&self, predicate: &str) -> String {
+        match self
+            .labels
+            .get(predicate)
+            .or_else(|| self.terms.get(predicate))
+        {
+            Some(name) => format!("{name}Id"),
+            None => self.compact(&format!("{predicate}Id")),
+        }
+    }
+
+    fn compact(&self, iri: &str) -> String {
+        Iri::compact(iri, &self.prefixes).unwrap_or_else(|| iri.to_string())
+    }
+}
+
+/// Returns the part of `iri` after its last `/`, `#`, or `:`, or the whole
+/// IRI when nothing follows them.
+fn local_name(iri: &str) -> &str {
+    match iri.rsplit(['/', '#', ':']).next() {
+        Some(local) if !local.is_empty() => local,
+        _ => iri,
+    }
+}
+
+/// Returns the `prefixes` that abbreviate one of `headers`.
+fn used_prefixes<'a>(
+    headers: impl Iterator<Item = &'a String>,
+    prefixes: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut used = BTreeMap::new();
+    for header in headers {
+        if let Some((prefix, local)) = header.split_once(':')
+            && !local.starts_with("//")
+            && let Some(namespace) = prefixes.get(prefix)
+        {
+            used.insert(prefix.to_string(), namespace.clone());
+        }
+    }
+    used
+}
+
+/// Moves the columns of the `leading` headers to the front of `columns`,
+/// keeping the order of the others.
+fn order_columns(columns: &mut [String], leading: &[String]) {
+    let rank = |column: &String| {
+        leading
+            .iter()
+            .position(|header| {
+                column == header || column.strip_suffix("Id") == Some(header.as_str())
+            })
+            .unwrap_or(leading.len())
+    };
+    columns.sort_by_key(rank);
+}
+
+/// Label, first column, and last column of a band of type sheet columns.
+#[derive(Debug, Clone, PartialEq)]
+struct ColumnBand {
+    label: String,
+    first: String,
+    last: String,
+}
+
+/// Returns the bands `groups` form among `columns`, sorted with the columns
+/// of each group leading in group order, leaving out groups without any.
+fn band_columns(columns: &[String], groups: &[ColumnGroup], headers: &Headers) -> Vec<ColumnBand> {
+    let group_of = |column: &String| {
+        groups.iter().position(|group| {
+            group.predicates.iter().any(|predicate| {
+                let header = headers.literal(predicate);
+                *column == header || column.strip_suffix("Id") == Some(header.as_str())
+            })
+        })
+    };
+    let mut bands: Vec<(usize, ColumnBand)> = Vec::new();
+    for column in columns {
+        let Some(group) = group_of(column) else {
+            break;
+        };
+        match bands.last_mut() {
+            Some((last, band)) if *last == group => band.last = column.clone(),
+            _ => bands.push((
+                group,
+                ColumnBand {
+                    label: groups[group].label.clone(),
+                    first: column.clone(),
+                    last: column.clone(),
+                },
+            )),
+        }
+    }
+    bands.into_iter().map(|(_, band)| band).collect()
+}
+
+/// Name, columns, and total row count of one sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetLayout {
+    pub sheet_name: String,
+    pub columns: Vec<String>,
+    pub row_count: usize,
+}
+
+/// Fixed sheet structure of a dataset, used to emit its rows chunk by chunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkbookLayout {
+    type_sheets: BTreeMap<Iri, SheetLayout>,
+    type_columns: BTreeMap<Iri, BTreeMap<String, ColumnSpec>>,
+    child_sheets: BTreeMap<(Iri, Iri), SheetLayout>,
+    matrix_sheets: BTreeMap<(Iri, Iri), SheetLayout>,
+    encoding: CellEncoding,
+    context: Option<Value>,
+    dataset: Option<SheetTable>,
+    /// Id each node's rows are sorted by ahead of its own, see
+    /// [`Self::with_row_anchors`].
+    row_anchors: BTreeMap<(Option<Iri>, NodeId), NodeId>,
+    /// Prefixes abbreviating the headers, declared in `Metadata`.
+    prefixes: BTreeMap<String, String>,
+    /// Context terms heading columns, declared in `Metadata`.
+    terms: BTreeMap<String, Iri>,
+    headers: Headers,
+    cardinalities: BTreeMap<Iri, Cardinality>,
+    consolidated_types: BTreeMap<Iri, Iri>,
+    /// Bands of the columns of each type sheet, declared in `Metadata`.
+    column_bands: BTreeMap<Iri, Vec<ColumnBand>>,
+    entity_rows: usize,
+}
+
+impl WorkbookLayout {
+    /// Sets the encoding used for literal cells by [`Self::tables`].
+    pub fn with_encoding(mut self, encoding: CellEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets the JSON-LD context written by [`Self::context_table`].
+    pub fn with_context(mut self, context: Option<Value>) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Sets the `Dataset` sheet returned by [`Self::dataset_table`], built
+    /// by [`build_dataset_table`].
+    pub fn with_dataset(mut self, dataset: Option<SheetTable>) -> Self {
+        self.dataset = dataset;
+        self
+    }
+
+    /// Sorts the rows of type, child, and matrix sheets describing the nodes
+    /// in `anchors` by their anchor id ahead of their own, so that the rows
+    /// of related nodes, such as those linked by `owl:sameAs`, are listed
+    /// together.
+    pub fn with_row_anchors(mut self, anchors: BTreeMap<(Option<Iri>, NodeId), NodeId>) -> Self {
+        self.row_anchors = anchors;
+        self
+    }
+
+    /// Layout of every sheet in workbook order: `Entities`, `Metadata`, the
+    /// `Context` sheet when a context is set, the `Dataset` sheet when a
+    /// dataset node is, then the type and child sheets sorted by name.
+    pub fn sheets(&self) -> Vec<SheetLayout> {
+        let mut sheets = vec![
+            SheetLayout {
+                sheet_name: ENTITIES_SHEET.to_string(),
+                columns: entities_columns(),
+                row_count: self.entity_rows,
+            },
+            SheetLayout {
+                sheet_name: METADATA_SHEET.to_string(),
+                columns: metadata_columns(),
+                row_count: self.type_sheets.len()
+                    + self.child_sheets.len()
+                    + self.matrix_sheets.len()
+                    + self.type_columns.values().map(BTreeMap::len).sum::<usize>()
+                    + self.prefixes.len()
+                    + self.terms.len()
+                    + self.column_bands.values().map(Vec::len).sum::<usize>(),
+            },
+        ];
+        if let Some(context) = self.context_table() {
+            sheets.push(SheetLayout {
+                sheet_name: context.sheet_name,
+                columns: context.columns,
+                row_count: context.rows.len(),
+            });
+        }
+        if let Some(dataset) = &self.dataset {
+            sheets.push(SheetLayout {
+                sheet_name: dataset.sheet_name.clone(),
+                columns: dataset.columns.clone(),
+                row_count: dataset.rows.len(),
+            });
+        }
+        sheets.extend(self.data_sheets().into_iter().cloned());
+        sheets
+    }
+
+    /// Returns the number of cells of all sheets, header rows included.
+    pub fn cell_count(&self) -> usize {
+        self.sheets()
+            .iter()
+            .map(|sheet| (sheet.row_count + 1) * sheet.columns.len())
+            .sum()
+    }
+
+    /// Fails with [`ToolError::LimitExceeded`] when the sheets hold more than
+    /// `max_cells` cells, so that oversized workbooks are refused before
+    /// their rows are built.
+    pub fn check_cells(&self, max_cells: Option<usize>) -> Result<()> {
+        let Some(limit) = max_cells else {
+            return Ok(());
+        };
+        let found = self.cell_count();
+        if found > limit {
+            return Err(ToolError::LimitExceeded {
+                what: "cells",
+                found,
+                limit,
             });
         }
+        Ok(())
+    }
+
+    /// Builds the `Context` sheet embedding the JSON-LD context, if one is
+    /// set. A term map is written one `term` per row with its `definition`,
+    /// strings as they are and other values as JSON; any other context is
+    /// written as JSON in a single [`WHOLE_CONTEXT_TERM`] row.
+    pub fn context_table(&self) -> Option<SheetTable> {
+        let rows = match self.context.as_ref()? {
+            Value::Object(terms) => terms
+                .iter()
+                .map(|(term, definition)| {
+                    let definition = match definition {
+                        Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    };
+                    vec![term.clone(), definition]
+                })
+                .collect(),
+            other => vec![vec![WHOLE_CONTEXT_TERM.to_string(), other.to_string()]],
+        };
+        Some(SheetTable {
+            sheet_name: CONTEXT_SHEET.to_string(),
+            columns: vec!["term".to_string(), "definition".to_string()],
+            rows,
+        })
+    }
+
+    /// Returns the `Dataset` sheet, if a dataset node is set.
+    pub fn dataset_table(&self) -> Option<&SheetTable> {
+        self.dataset.as_ref()
+    }
+
+    /// Builds the `Metadata` sheet mapping sheets to types and predicates,
+    /// with `child` rows for child sheets and `matrix` rows for matrix
+    /// sheets, followed by one `column` row per type sheet column giving its
+    /// predicate, [`ColumnKind`], and shared literal datatype, if any, and
+    /// one `prefix` row per prefix abbreviating headers, with the prefix in
+    /// `column` and its namespace in `predicate`. Bands of columns get a
+    /// `group` row each, with the label in `column` and the band's first and
+    /// last column in `value_kind` and `datatype`.
+    pub fn metadata_table(&self) -> SheetTable {
+        let mut rows: Vec<Vec<String>> = self
+            .type_sheets
+            .iter()
+            .map(|(type_name, sheet)| {
+                metadata_row("type", &sheet.sheet_name, type_name, "", ["", "", ""])
+            })
+            .collect();
+        rows.extend(
+            self.child_sheets
+                .iter()
+                .map(|((type_name, predicate), sheet)| {
+                    metadata_row(
+                        "child",
+                        &sheet.sheet_name,
+                        type_name,
+                        predicate,
+                        ["", "", ""],
+                    )
+                }),
+        );
+        rows.extend(
+            self.matrix_sheets
+                .iter()
+                .map(|((type_name, predicate), sheet)| {
+                    metadata_row(
+                        "matrix",
+                        &sheet.sheet_name,
+                        type_name,
+                        predicate,
+                        ["", "", ""],
+                    )
+                }),
+        );
+        for (type_name, specs) in &self.type_columns {
+            let Some(sheet) = self.type_sheets.get(type_name) else {
+                continue;
+            };
+            rows.extend(specs.iter().map(|(column, spec)| {
+                metadata_row(
+                    "column",
+                    &sheet.sheet_name,
+                    type_name,
+                    &spec.predicate,
+                    [column, spec.kind.as_str(), spec.datatype()],
+                )
+            }));
+        }
+        for (type_name, bands) in &self.column_bands {
+            let Some(sheet) = self.type_sheets.get(type_name) else {
+                continue;
+            };
+            rows.extend(bands.iter().map(|band| {
+                metadata_row(
+                    "group",
+                    &sheet.sheet_name,
+                    type_name,
+                    "",
+                    [&band.label, &band.first, &band.last],
+                )
+            }));
+        }
+        rows.extend(self.prefixes.iter().map(|(prefix, namespace)| {
+            metadata_row("prefix", "", "", namespace, [prefix, "", ""])
+        }));
+        rows.extend(
+            self.terms
+                .iter()
+                .map(|(term, iri)| metadata_row("term", "", "", iri, [term, "", ""])),
+        );
+
+        SheetTable {
+            sheet_name: METADATA_SHEET.to_string(),
+            columns: metadata_columns(),
+            rows,
+        }
     }
 
-    entities.sort();
+    /// Flattens `nodes` into rows of the `Entities`, type, child, and matrix
+    /// sheets,
+    /// leaving out `Metadata`. Every sheet of the layout is returned, so
+    /// chunks that do not touch a sheet yield it without rows. Rows are
+    /// sorted within the chunk.
+    pub fn tables(&self, nodes: &[Node]) -> Result<Vec<SheetTable>> {
+        let mut entities: Vec<(NodeId, Iri, Option<Iri>)> = Vec::new();
+        let mut type_rows: BTreeMap<&str, Vec<RowData>> = BTreeMap::new();
+        let mut child_rows: BTreeMap<(&str, &str), Vec<ChildRow>> = BTreeMap::new();
+        let mut matrix_rows: BTreeMap<(&str, &str), Vec<RowData>> = BTreeMap::new();
+
+        for node in nodes {
+            for type_name in node_types(node) {
+                entities.push((node.id.clone(), type_name, node.graph.clone()));
+            }
+            for (type_name, types) in sheet_types(node, &self.consolidated_types) {
+                let Some((type_key, _)) = self.type_sheets.get_key_value(&type_name) else {
+                    continue;
+                };
+                let mut row_values: BTreeMap<String, String> = BTreeMap::new();
+                if types.iter().any(|subtype| **subtype != type_name) {
+                    let types: Vec<&str> =
+                        types.iter().map(|type_name| type_name.as_str()).collect();
+                    row_values.insert(SUBTYPE_COLUMN.to_string(), types.join(" "));
+                }
+
+                for (predicate, value) in &node.properties {
+                    let value = shaped(value, self.cardinalities.get(predicate).copied());
+                    match value.as_ref() {
+                        PropertyValue::Scalar(scalar) => {
+                            row_values.insert(
+                                self.headers.literal(predicate),
+                                scalar_to_cell_value(scalar, self.encoding)?,
+                            );
+                        }
+                        PropertyValue::ObjectRef(target) => {
+                            row_values
+                                .insert(self.headers.reference(predicate), target.to_string());
+                        }
+                        PropertyValue::Array(ArrayValue::Scalars(_) | ArrayValue::Mixed(_)) => {
+                            let json_string = serde_json::to_string(&value.to_json())?;
+                            row_values.insert(self.headers.literal(predicate), json_string);
+                        }
+                        PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => {
+                            let key = (type_name.clone(), predicate.clone());
+                            if let Some(((child_type, child_predicate), _)) =
+                                self.child_sheets.get_key_value(&key)
+                            {
+                                let rows = child_rows
+                                    .entry((child_type.as_str(), child_predicate.as_str()))
+                                    .or_default();
+                                for target in targets {
+                                    rows.push((
+                                        node.id.clone(),
+                                        node.graph.clone(),
+                                        target.clone(),
+                                    ));
+                                }
+                            } else if let Some(((matrix_type, matrix_predicate), _)) =
+                                self.matrix_sheets.get_key_value(&key)
+                            {
+                                matrix_rows
+                                    .entry((matrix_type.as_str(), matrix_predicate.as_str()))
+                                    .or_default()
+                                    .push(RowData {
+                                        id: node.id.clone(),
+                                        graph: node.graph.clone(),
+                                        values: targets
+                                            .iter()
+                                            .map(|target| {
+                                                (target.to_string(), MATRIX_MARK.to_string())
+                                            })
+                                            .collect(),
+                                    });
+                            }
+                        }
+                    }
+                }
+
+                type_rows
+                    .entry(type_key.as_str())
+                    .or_default()
+                    .push(RowData {
+                        id: node.id.clone(),
+                        graph: node.graph.clone(),
+                        values: row_values,
+                    });
+            }
+        }
 
-    let mut tables: Vec<SheetTable> = Vec::new();
-    let mut metadata_rows: Vec<Vec<String>> = Vec::new();
+        entities.sort();
 
-    // Reserve names for Entities and Metadata to avoid collisions.
-    sheet_names.claim(ENTITIES_SHEET.to_string());
-    sheet_names.claim(METADATA_SHEET.to_string());
+        let mut tables: Vec<SheetTable> = Vec::new();
+        for (type_name, sheet) in &self.type_sheets {
+            let mut rows = type_rows.remove(type_name.as_str()).unwrap_or_default();
+            rows.sort_by(|lhs, rhs| self.row_key(lhs).cmp(&self.row_key(rhs)));
+            tables.push(type_table(sheet, rows));
+        }
+        for ((type_name, predicate), sheet) in &self.child_sheets {
+            let mut rows = child_rows
+                .remove(&(type_name.as_str(), predicate.as_str()))
+                .unwrap_or_default();
+            rows.sort();
+            if !self.row_anchors.is_empty() {
+                rows.sort_by(|lhs, rhs| {
+                    self.anchor(&lhs.1, &lhs.0).cmp(self.anchor(&rhs.1, &rhs.0))
+                });
+            }
+            tables.push(child_table(sheet, rows));
+        }
+        for ((type_name, predicate), sheet) in &self.matrix_sheets {
+            let mut rows = matrix_rows
+                .remove(&(type_name.as_str(), predicate.as_str()))
+                .unwrap_or_default();
+            rows.sort_by(|lhs, rhs| self.row_key(lhs).cmp(&self.row_key(rhs)));
+            tables.push(type_table(sheet, rows));
+        }
+        tables.sort_by(|lhs, rhs| lhs.sheet_name.cmp(&rhs.sheet_name));
 
-    for (type_name, mut builder) in type_builders {
-        builder.rows.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
-        let sheet_name = sheet_names.assign(&type_name);
+        let mut all_tables = vec![build_entities_table(entities)];
+        all_tables.extend(tables);
+        Ok(all_tables)
+    }
 
-        metadata_rows.push(vec![
-            "type".to_string(),
-            sheet_name.clone(),
-            type_name.clone(),
-            String::new(),
-        ]);
+    /// Returns the id the rows of the node `id` of `graph` are sorted by
+    /// first: its anchor, if it has one, or else its own id.
+    fn anchor<'a>(&'a self, graph: &Option<Iri>, id: &'a NodeId) -> &'a NodeId {
+        if self.row_anchors.is_empty() {
+            return id;
+        }
+        self.row_anchors
+            .get(&(graph.clone(), id.clone()))
+            .unwrap_or(id)
+    }
 
-        tables.push(builder.into_table(sheet_name));
+    /// Returns the key type and matrix rows are sorted by.
+    fn row_key<'a>(&'a self, row: &'a RowData) -> (&'a NodeId, &'a NodeId) {
+        (self.anchor(&row.graph, &row.id), &row.id)
     }
 
-    for ((type_name, predicate), mut builder) in child_builders {
-        builder.rows.sort();
-        let raw_sheet = format!("{type_name}__{predicate}");
-        let sheet_name = sheet_names.assign(&raw_sheet);
+    fn data_sheets(&self) -> Vec<&SheetLayout> {
+        let mut sheets: Vec<&SheetLayout> = self
+            .type_sheets
+            .values()
+            .chain(self.child_sheets.values())
+            .chain(self.matrix_sheets.values())
+            .collect();
+        sheets.sort_by(|lhs, rhs| lhs.sheet_name.cmp(&rhs.sheet_name));
+        sheets
+    }
+}
 
-        metadata_rows.push(vec![
-            "child".to_string(),
-            sheet_name.clone(),
-            type_name,
-            predicate,
-        ]);
+fn node_types(node: &Node) -> Vec<Iri> {
+    if node.types.is_empty() {
+        vec![Iri::from(UNTYPED_MARKER)]
+    } else {
+        node.types.iter().cloned().collect()
+    }
+}
 
-        tables.push(builder.into_table(sheet_name));
+/// Groups the types of `node` by the type whose sheet holds them, each type
+/// on its own sheet unless `consolidated_types` moves it.
+fn sheet_types<'a>(
+    node: &'a Node,
+    consolidated_types: &'a BTreeMap<Iri, Iri>,
+) -> BTreeMap<Iri, Vec<&'a Iri>> {
+    let mut sheets: BTreeMap<Iri, Vec<&Iri>> = BTreeMap::new();
+    if node.types.is_empty() {
+        sheets.insert(Iri::from(UNTYPED_MARKER), Vec::new());
+        return sheets;
     }
+    for type_name in &node.types {
+        let sheet = consolidated_types.get(type_name).unwrap_or(type_name);
+        sheets.entry(sheet.clone()).or_default().push(type_name);
+    }
+    sheets
+}
 
-    tables.sort_by(|lhs, rhs| lhs.sheet_name.cmp(&rhs.sheet_name));
+/// Maps every class given a superclass by the `rdfs:subClassOf` references
+/// of `hierarchy` to its topmost superclass, which consolidates each class
+/// tree onto the sheet of its root as
+/// [`WorkbookOptions::consolidated_types`]. Classes with several
+/// superclasses follow the first in IRI order, and cycles stop at the last
+/// class not seen yet.
+pub fn consolidate_subclasses(hierarchy: &[Node]) -> BTreeMap<Iri, Iri> {
+    let mut superclasses: BTreeMap<&Iri, &Iri> = BTreeMap::new();
+    for node in hierarchy {
+        let superclass = match node.properties.get(RDFS_SUB_CLASS_OF) {
+            Some(PropertyValue::ObjectRef(target)) => Some(target),
+            Some(PropertyValue::Array(ArrayValue::ObjectRefs(targets))) => targets.iter().min(),
+            _ => None,
+        };
+        if let Some(superclass) = superclass.filter(|superclass| **superclass != node.id) {
+            superclasses.insert(&node.id, superclass);
+        }
+    }
+    superclasses
+        .keys()
+        .map(|&class| {
+            let mut seen = BTreeSet::from([class]);
+            let mut root = class;
+            while let Some(&superclass) = superclasses.get(root) {
+                if !seen.insert(superclass) {
+                    break;
+                }
+                root = superclass;
+            }
+            (class.clone(), root.clone())
+        })
+        .filter(|(class, root)| class != root)
+        .collect()
+}
 
-    let entities_table = build_entities_table(entities);
-    let metadata_table = SheetTable {
-        sheet_name: METADATA_SHEET.to_string(),
-        columns: vec![
-            "kind".to_string(),
-            "sheet".to_string(),
-            "type".to_string(),
-            "predicate".to_string(),
-        ],
-        rows: metadata_rows,
-    };
+fn entities_columns() -> Vec<String> {
+    vec!["id".to_string(), "type".to_string(), "graph".to_string()]
+}
 
-    let mut all_tables = vec![entities_table, metadata_table];
-    all_tables.extend(tables);
+fn metadata_columns() -> Vec<String> {
+    vec![
+        "kind".to_string(),
+        "sheet".to_string(),
+        "type".to_string(),
+        "predicate".to_string(),
+        "column".to_string(),
+        "value_kind".to_string(),
+        "datatype".to_string(),
+    ]
+}
 
-    Ok(WorkbookData { tables: all_tables })
+fn metadata_row(
+    kind: &str,
+    sheet: &str,
+    type_name: &str,
+    predicate: &str,
+    column: [&str; 3],
+) -> Vec<String> {
+    [kind, sheet, type_name, predicate]
+        .into_iter()
+        .chain(column)
+        .map(str::to_string)
+        .collect()
 }
 
-fn build_entities_table(entries: Vec<(String, String, Option<String>)>) -> SheetTable {
+fn build_entities_table(entries: Vec<(NodeId, Iri, Option<Iri>)>) -> SheetTable {
     let rows = entries
         .into_iter()
-        .map(|(id, type_name, graph)| vec![id, type_name, graph.unwrap_or_default()])
+        .map(|(id, type_name, graph)| vec![id.into(), type_name.into(), graph_cell(graph)])
         .collect();
 
     SheetTable {
         sheet_name: ENTITIES_SHEET.to_string(),
-        columns: vec!["id".to_string(), "type".to_string(), "graph".to_string()],
+        columns: entities_columns(),
         rows,
     }
 }
@@ -187,9 +1414,7 @@ impl SheetNameRegistry {
             let suffix = format!("_{counter}");
             let max_len = 31 - suffix.len();
             let mut prefix = base.clone();
-            if prefix.len() > max_len {
-                prefix.truncate(max_len);
-            }
+            truncate_chars(&mut prefix, max_len);
             let candidate = format!("{prefix}{suffix}");
             if !self.used.contains(&candidate) {
                 self.used.insert(candidate.clone());
@@ -218,91 +1443,117 @@ fn sanitize_sheet_name(raw: &str) -> String {
         sanitized = "Sheet".to_string();
     }
 
-    if sanitized.len() > 31 {
-        sanitized.truncate(31);
-    }
-
+    truncate_chars(&mut sanitized, 31);
     sanitized
 }
 
-struct TypeTableBuilder {
-    columns: BTreeSet<String>,
-    rows: Vec<RowData>,
-}
-
-impl TypeTableBuilder {
-    fn new() -> Self {
-        Self {
-            columns: BTreeSet::new(),
-            rows: Vec::new(),
-        }
+/// Shortens `text` to at most `max_len` characters, the unit of Excel's
+/// limit on sheet names, so labels outside ASCII are not cut mid-character.
+fn truncate_chars(text: &mut String, max_len: usize) {
+    if let Some((index, _)) = text.char_indices().nth(max_len) {
+        text.truncate(index);
     }
+}
 
-    fn into_table(self, sheet_name: String) -> SheetTable {
-        let mut columns = Vec::with_capacity(self.columns.len() + 2);
-        columns.push("id".to_string());
-        columns.push("graph".to_string());
-        columns.extend(self.columns);
-
-        let mut rows = Vec::with_capacity(self.rows.len());
-        for row in self.rows {
-            let mut cells = Vec::with_capacity(columns.len());
-            cells.push(row.id);
-            cells.push(row.graph.unwrap_or_default());
-            for column in columns.iter().skip(2) {
+fn type_table(sheet: &SheetLayout, rows: Vec<RowData>) -> SheetTable {
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            let mut cells = Vec::with_capacity(sheet.columns.len());
+            cells.push(row.id.into());
+            cells.push(graph_cell(row.graph));
+            for column in sheet.columns.iter().skip(2) {
                 cells.push(row.values.get(column).cloned().unwrap_or_default());
             }
-            rows.push(cells);
-        }
+            cells
+        })
+        .collect();
 
-        SheetTable {
-            sheet_name,
-            columns,
-            rows,
-        }
+    SheetTable {
+        sheet_name: sheet.sheet_name.clone(),
+        columns: sheet.columns.clone(),
+        rows,
     }
 }
 
-struct RowData {
-    id: String,
-    graph: Option<String>,
-    values: BTreeMap<String, String>,
+fn child_table(sheet: &SheetLayout, rows: Vec<ChildRow>) -> SheetTable {
+    let rows = rows
+        .into_iter()
+        .map(|(parent, graph, target)| vec![parent.into(), graph_cell(graph), target.into()])
+        .collect();
+
+    SheetTable {
+        sheet_name: sheet.sheet_name.clone(),
+        columns: sheet.columns.clone(),
+        rows,
+    }
 }
 
-struct ChildTableBuilder {
-    predicate: String,
-    rows: Vec<(String, Option<String>, String)>,
+/// Cell text of a graph column; the default graph is left blank.
+fn graph_cell(graph: Option<Iri>) -> String {
+    graph.map(String::from).unwrap_or_default()
 }
 
-impl ChildTableBuilder {
-    fn new(predicate: String) -> Self {
-        Self {
-            predicate,
-            rows: Vec::new(),
-        }
-    }
+/// Parent id, parent graph, and target id of a child sheet row.
+type ChildRow = (NodeId, Option<Iri>, NodeId);
 
-    fn into_table(self, sheet_name: String) -> SheetTable {
-        let column_name = format!("{}Id", self.predicate);
-        let rows = self
-            .rows
-            .into_iter()
-            .map(|(parent, graph, target)| vec![parent, graph.unwrap_or_default(), target])
-            .collect();
+struct RowData {
+    id: NodeId,
+    graph: Option<Iri>,
+    values: BTreeMap<String, String>,
+}
 
-        SheetTable {
-            sheet_name,
-            columns: vec![
-                "ParentId".to_string(),
-                "ParentGraph".to_string(),
-                column_name,
-            ],
-            rows,
+/// Returns whether bare cell text `text` would not read back as the same
+/// string: text that parses as JSON, is blank, or starts with the text
+/// marker.
+pub(crate) fn needs_text_prefix(text: &str) -> bool {
+    let Some(first) = text.trim_start().bytes().next() else {
+        return true;
+    };
+    // Most text cannot start a JSON value, which saves parsing it.
+    text.starts_with(TEXT_PREFIX)
+        || (matches!(
+            first,
+            b'"' | b'{' | b'[' | b'-' | b'0'..=b'9' | b't' | b'f' | b'n'
+        ) && serde_json::from_str::<Value>(text).is_ok())
+}
+
+/// Encodes a literal cell. Strings, numbers, and booleans, which make up
+/// most cells, are written directly instead of through a JSON value, with
+/// the same text serde_json gives them.
+fn scalar_to_cell_value(value: &ScalarValue, encoding: CellEncoding) -> Result<String> {
+    Ok(match value {
+        ScalarValue::String(text) => match encoding {
+            CellEncoding::Bare if needs_text_prefix(text) => {
+                let mut cell = String::with_capacity(text.len() + 1);
+                cell.push(TEXT_PREFIX);
+                cell.push_str(text);
+                cell
+            }
+            CellEncoding::Bare => text.clone(),
+            CellEncoding::Json => json_string(text)?,
+        },
+        ScalarValue::Integer(number) => itoa::Buffer::new().format(*number).to_owned(),
+        ScalarValue::Double(number) if number.is_finite() => {
+            ryu::Buffer::new().format_finite(*number).to_owned()
         }
-    }
+        ScalarValue::Boolean(flag) => flag.to_string(),
+        other => serde_json::to_string(&other.to_json())?,
+    })
 }
 
-fn scalar_to_cell_value(value: &ScalarValue) -> Result<String> {
-    let json_value = value.to_json();
-    Ok(serde_json::to_string(&json_value)?)
+/// Quotes `text` as a JSON string, leaving text without characters to
+/// escape to a copy.
+fn json_string(text: &str) -> Result<String> {
+    if text
+        .bytes()
+        .any(|byte| byte == b'"' || byte == b'\\' || byte < 0x20)
+    {
+        return Ok(serde_json::to_string(text)?);
+    }
+    let mut cell = String::with_capacity(text.len() + 2);
+    cell.push('"');
+    cell.push_str(text);
+    cell.push('"');
+    Ok(cell)
 }
@@ -0,0 +1,206 @@
+//! Lightweight version control for converted datasets.
+//!
+//! A history directory keeps successive snapshots of a dataset without a
+//! triple store:
+//!
+//! * `snapshots/<hash>.nq` holds each distinct snapshot as canonical
+//!   N-Quads, see [`rdf::canonical_nquads`], named after a hash of its
+//!   content, so committing a dataset that did not change stores nothing
+//!   new;
+//! * `index.json` lists the versions in the order they were committed, each
+//!   with its number, counting from 1, snapshot hash, commit time, and node
+//!   count.
+//!
+//! Versions are picked with [`History::resolve`] by number, by a prefix of
+//! their hash, or by the time they were current, and compared with
+//! [`diff_nodes`](crate::aideon::tools::diff::diff_nodes).
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use oxigraph::io::RdfFormat;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::io::{atomic, rdf};
+use crate::aideon::tools::model::Node;
+
+/// Name of the file listing the versions of a history directory.
+pub const INDEX_FILE: &str = "index.json";
+
+/// Directory holding the snapshots of a history directory.
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// Shortest hash prefix [`History::resolve`] accepts.
+const MIN_HASH_PREFIX: usize = 4;
+
+/// A committed snapshot of the dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Version {
+    /// Position of the version in the history, counting from 1.
+    pub number: usize,
+    /// Hash of the snapshot's content, naming its file.
+    pub hash: String,
+    /// When the version was committed.
+    pub committed: DateTime<Utc>,
+    /// Number of nodes in the snapshot.
+    pub nodes: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryIndex {
+    versions: Vec<Version>,
+}
+
+/// The versions stored in a history directory.
+#[derive(Debug)]
+pub struct History {
+    dir: PathBuf,
+    index: HistoryIndex,
+}
+
+impl History {
+    /// Opens the history kept in `dir`. A directory that does not exist yet
+    /// has no versions and is created by the first [`commit`](Self::commit).
+    pub fn open(dir: &Path) -> Result<Self> {
+        let index_path = dir.join(INDEX_FILE);
+        let index = if index_path.exists() {
+            serde_json::from_str(&fs::read_to_string(&index_path)?).map_err(|err| {
+                ToolError::InvalidConfig(format!("{}: {err}", index_path.display()))
+            })?
+        } else {
+            HistoryIndex::default()
+        };
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            index,
+        })
+    }
+
+    /// The versions, oldest first.
+    pub fn versions(&self) -> &[Version] {
+        &self.index.versions
+    }
+
+    /// The most recent version, if any.
+    pub fn latest(&self) -> Option<&Version> {
+        self.index.versions.last()
+    }
+
+    /// Stores `nodes` as a new version committed at `committed`, returning
+    /// it, or `None` when they have the content of the latest version.
+    pub fn commit(&mut self, nodes: &[Node], committed: DateTime<Utc>) -> Result<Option<Version>> {
+        let snapshot = rdf::canonical_nquads(nodes)?;
+        let hash = Uuid::new_v5(&Uuid::NAMESPACE_OID, snapshot.as_bytes())
+            .simple()
+            .to_string();
+        if self.latest().is_some_and(|latest| latest.hash == hash) {
+            return Ok(None);
+        }
+
+        let snapshots = self.dir.join(SNAPSHOTS_DIR);
+        fs::create_dir_all(&snapshots)?;
+        let path = snapshots.join(format!("{hash}.nq"));
+        if !path.exists() {
+            atomic::write_atomically(&path, |partial| Ok(fs::write(partial, &snapshot)?))?;
+        }
+        let version = Version {
+            number: self.index.versions.len() + 1,
+            hash,
+            committed,
+            nodes: nodes.len(),
+        };
+        self.index.versions.push(version.clone());
+        atomic::write_atomically(&self.dir.join(INDEX_FILE), |partial| {
+            serde_json::to_writer_pretty(BufWriter::new(File::create(partial)?), &self.index)?;
+            Ok(())
+        })?;
+        Ok(Some(version))
+    }
+
+    /// Finds the version `spec` names: a version number, a prefix of at
+    /// least four characters of a snapshot hash, or an RFC 3339 time or
+    /// `YYYY-MM-DD` date (the end of that day, UTC), which picks the last
+    /// version committed by then.
+    pub fn resolve(&self, spec: &str) -> Result<&Version> {
+        let versions = &self.index.versions;
+        // Hash prefixes made of digits only are read as hashes when no
+        // version has that number.
+        if let Ok(number) = spec.parse::<usize>()
+            && (spec.len() < MIN_HASH_PREFIX || number <= versions.len())
+        {
+            return versions
+                .iter()
+                .find(|version| version.number == number)
+                .ok_or_else(|| unknown_version(spec, "no version has that number"));
+        }
+        if let Some(time) = parse_time(spec) {
+            return versions
+                .iter()
+                .rfind(|version| version.committed <= time)
+                .ok_or_else(|| unknown_version(spec, "no version was committed by then"));
+        }
+        if spec.len() < MIN_HASH_PREFIX {
+            return Err(unknown_version(
+                spec,
+                "hash prefixes need at least four characters",
+            ));
+        }
+        let hashes: BTreeMap<&str, &Version> = versions
+            .iter()
+            .filter(|version| version.hash.starts_with(spec))
+            .map(|version| (version.hash.as_str(), version))
+            .collect();
+        match hashes.len() {
+            0 => Err(unknown_version(spec, "no snapshot hash starts with it")),
+            // A snapshot committed again names its latest version.
+            1 => Ok(versions
+                .iter()
+                .rfind(|version| version.hash.starts_with(spec))
+                .expect("a version has the hash")),
+            _ => Err(unknown_version(
+                spec,
+                "several snapshot hashes start with it",
+            )),
+        }
+    }
+
+    /// Reads the nodes of `version`.
+    pub fn load(&self, version: &Version) -> Result<Vec<Node>> {
+        let path = self
+            .dir
+            .join(SNAPSHOTS_DIR)
+            .join(format!("{}.nq", version.hash));
+        if !path.exists() {
+            return Err(ToolError::MissingInput(path));
+        }
+        rdf::read_rdf(&path, Some(RdfFormat::NQuads))
+    }
+}
+
+/// Describes `version` on one line, as `history log` prints it.
+pub fn describe(version: &Version) -> String {
+    format!(
+        "{} {} {} {} node(s)",
+        version.number,
+        &version.hash[..version.hash.len().min(12)],
+        version.committed.to_rfc3339_opts(SecondsFormat::Secs, true),
+        version.nodes
+    )
+}
+
+fn parse_time(spec: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(spec) {
+        return Some(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(spec, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_nano_opt(23, 59, 59, 999_999_999)?.and_utc())
+}
+
+fn unknown_version(spec: &str, reason: &str) -> ToolError {
+    ToolError::InvalidConfig(format!("no version '{spec}' in the history: {reason}"))
+}
@@ -0,0 +1,187 @@
+//! Statistics describing a dataset, for judging inputs of unknown quality.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::flatten::{
+    CONTEXT_SHEET, DATASET_SHEET, ENTITIES_SHEET, METADATA_SHEET, WorkbookOptions,
+    build_workbook_with_options,
+};
+use crate::aideon::tools::model::{ArrayItem, Iri, Node, NodeId};
+
+/// Label of null literals in [`DatasetStatistics::datatype_counts`].
+pub const NULL_LABEL: &str = "(null)";
+
+/// Counts and problems found in a node set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DatasetStatistics {
+    /// Total number of nodes.
+    pub node_count: usize,
+    /// Number of nodes per type IRI.
+    pub type_counts: BTreeMap<String, usize>,
+    /// Number of nodes without a type.
+    pub untyped_count: usize,
+    /// Number of nodes per graph, the default graph first.
+    pub graphs: Vec<GraphUsage>,
+    /// Use of each predicate.
+    pub predicates: BTreeMap<String, PredicateUsage>,
+    /// Number of literal values per datatype IRI, with plain strings,
+    /// numbers, and booleans counting as the XML Schema datatype they stand
+    /// for and nulls as [`NULL_LABEL`].
+    pub datatype_counts: BTreeMap<String, usize>,
+    /// References to ids no node has.
+    pub orphan_references: Vec<OrphanReference>,
+    /// Type, child, and matrix sheets of the dataset's workbook layout, the
+    /// most rows first. Empty unless computed with
+    /// [`DatasetStatistics::with_sheets`].
+    pub sheets: Vec<SheetSize>,
+}
+
+/// Number of nodes in one graph.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphUsage {
+    /// Graph name; `None` is the default graph.
+    pub graph: Option<String>,
+    /// Number of nodes in the graph.
+    pub node_count: usize,
+}
+
+/// How often one predicate is used.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PredicateUsage {
+    /// Number of nodes stating the predicate.
+    pub node_count: usize,
+    /// Number of values, counting each array item.
+    pub value_count: usize,
+}
+
+/// Reference whose target is not a node of the dataset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrphanReference {
+    /// Node holding the reference.
+    pub node: NodeId,
+    /// Graph of the node holding the reference.
+    pub graph: Option<Iri>,
+    /// Predicate of the reference.
+    pub predicate: Iri,
+    /// Id the reference points to.
+    pub target: NodeId,
+}
+
+/// Size of one sheet.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SheetSize {
+    /// Sheet name.
+    pub sheet_name: String,
+    /// Number of data rows.
+    pub row_count: usize,
+    /// Number of columns.
+    pub column_count: usize,
+}
+
+impl DatasetStatistics {
+    /// Computes the statistics of `nodes`, without sheets. References count
+    /// as orphans when no node in any graph has their id, as in
+    /// [`validate_nodes`](crate::aideon::tools::validate::validate_nodes).
+    pub fn from_nodes(nodes: &[Node]) -> Self {
+        let known_ids: HashSet<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+        let mut graph_counts: BTreeMap<Option<&str>, usize> = BTreeMap::new();
+        let mut statistics = Self {
+            node_count: nodes.len(),
+            ..Self::default()
+        };
+        for node in nodes {
+            for type_name in &node.types {
+                *statistics
+                    .type_counts
+                    .entry(type_name.to_string())
+                    .or_default() += 1;
+            }
+            if node.types.is_empty() {
+                statistics.untyped_count += 1;
+            }
+            *graph_counts.entry(node.graph.as_deref()).or_default() += 1;
+
+            for (predicate, value) in &node.properties {
+                let items = value.items();
+                let usage = statistics
+                    .predicates
+                    .entry(predicate.to_string())
+                    .or_default();
+                usage.node_count += 1;
+                usage.value_count += items.len();
+                for item in items {
+                    match item {
+                        ArrayItem::Scalar(scalar) => {
+                            let datatype = scalar.datatype().unwrap_or(NULL_LABEL);
+                            *statistics
+                                .datatype_counts
+                                .entry(datatype.to_string())
+                                .or_default() += 1;
+                        }
+                        ArrayItem::ObjectRef(target) if !known_ids.contains(target.as_str()) => {
+                            statistics.orphan_references.push(OrphanReference {
+                                node: node.id.clone(),
+                                graph: node.graph.clone(),
+                                predicate: predicate.clone(),
+                                target,
+                            });
+                        }
+                        ArrayItem::ObjectRef(_) => {}
+                    }
+                }
+            }
+        }
+        statistics.graphs = graph_counts
+            .into_iter()
+            .map(|(graph, node_count)| GraphUsage {
+                graph: graph.map(str::to_string),
+                node_count,
+            })
+            .collect();
+        statistics
+    }
+
+    /// Computes the statistics of `nodes` like [`from_nodes`](Self::from_nodes),
+    /// adding the sheets of the workbook they lay out as with `options`.
+    pub fn with_sheets(nodes: &[Node], options: &WorkbookOptions) -> Result<Self> {
+        let workbook = build_workbook_with_options(nodes, options)?;
+        let mut sheets: Vec<SheetSize> = workbook
+            .tables
+            .into_iter()
+            .filter(|table| {
+                ![ENTITIES_SHEET, METADATA_SHEET, CONTEXT_SHEET, DATASET_SHEET]
+                    .contains(&table.sheet_name.as_str())
+            })
+            .map(|table| SheetSize {
+                sheet_name: table.sheet_name,
+                row_count: table.rows.len(),
+                column_count: table.columns.len(),
+            })
+            .collect();
+        sheets.sort_by(|left, right| {
+            right
+                .row_count
+                .cmp(&left.row_count)
+                .then_with(|| left.sheet_name.cmp(&right.sheet_name))
+        });
+        Ok(Self {
+            sheets,
+            ..Self::from_nodes(nodes)
+        })
+    }
+
+    /// Returns the predicates by descending number of values.
+    pub fn predicates_by_use(&self) -> Vec<(&str, &PredicateUsage)> {
+        let mut predicates: Vec<(&str, &PredicateUsage)> = self
+            .predicates
+            .iter()
+            .map(|(predicate, usage)| (predicate.as_str(), usage))
+            .collect();
+        predicates.sort_by_key(|(_, usage)| Reverse(usage.value_count));
+        predicates
+    }
+}
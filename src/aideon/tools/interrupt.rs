@@ -0,0 +1,74 @@
+//! Cooperative cancellation of long conversions.
+//!
+//! Readers and writers of large node sets poll [`check`] as they go and fail
+//! with [`ToolError::Interrupted`] once cancellation has been requested.
+//! Outputs are written through [`atomic`](crate::io::atomic) files, so the
+//! error unwinds through the write and removes the partial output. The CLI
+//! requests cancellation on `SIGINT` and `SIGTERM` with
+//! [`install_signal_handlers`]; applications embedding the crate can call
+//! [`request`] from their own handlers or from another thread.
+
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+use crate::aideon::tools::error::{Result, ToolError};
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+/// Asks running conversions to stop at their next [`check`].
+pub fn request() {
+    REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Returns whether cancellation has been requested.
+pub fn is_requested() -> bool {
+    REQUESTED.load(Ordering::Relaxed)
+}
+
+/// Withdraws a request, so that later conversions run to completion.
+pub fn reset() {
+    REQUESTED.store(false, Ordering::SeqCst);
+    SIGNAL.store(0, Ordering::SeqCst);
+}
+
+/// Returns the number of the signal that requested cancellation, if one did.
+pub fn signal() -> Option<i32> {
+    match SIGNAL.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
+
+/// Fails with [`ToolError::Interrupted`], reporting `nodes` as the progress
+/// made so far, when cancellation has been requested.
+pub fn check(nodes: usize) -> Result<()> {
+    if is_requested() {
+        return Err(ToolError::Interrupted { nodes });
+    }
+    Ok(())
+}
+
+/// Requests cancellation when the process receives `SIGINT` or `SIGTERM`.
+/// A second signal ends the process at once, for conversions stuck outside
+/// a [`check`]. Does nothing on platforms without POSIX signals.
+pub fn install_signal_handlers() -> Result<()> {
+    #[cfg(unix)]
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only touches atomics and calls `_exit`, both of
+        // which are async-signal-safe.
+        if unsafe { libc::signal(signal, handler) } == libc::SIG_ERR {
+            return Err(std::io::Error::last_os_error().into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    if REQUESTED.swap(true, Ordering::SeqCst) {
+        // SAFETY: `_exit` is async-signal-safe and skips destructors.
+        unsafe { libc::_exit(128 + signal) };
+    }
+    SIGNAL.store(signal, Ordering::SeqCst);
+}
@@ -0,0 +1,40 @@
+//! Output files that appear whole or not at all.
+//!
+//! Writers fill a sibling file in the destination directory, named after the
+//! output and the running process, and rename it over the output once it is
+//! complete. The rename replaces the output in one step on the same
+//! filesystem, so jobs reading the output see either the previous file or
+//! the new one, never a truncated file, and a failed write leaves the
+//! previous file in place.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::aideon::tools::error::Result;
+
+/// Suffix of the files that outputs are written to before they are renamed.
+pub const PARTIAL_SUFFIX: &str = ".partial";
+
+/// Writes `path` by passing [`partial_path`] to `write` and renaming it over
+/// `path` when `write` succeeds. When it fails, whatever it wrote is removed.
+pub fn write_atomically<T>(path: &Path, write: impl FnOnce(&Path) -> Result<T>) -> Result<T> {
+    let partial = partial_path(path);
+    let written = write(&partial).and_then(|value| {
+        fs::rename(&partial, path)?;
+        Ok(value)
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    written
+}
+
+/// Returns the sibling of `path` that [`write_atomically`] writes to, such
+/// as `.data.xlsx.4242.partial` for `data.xlsx` written by process 4242.
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}{PARTIAL_SUFFIX}", std::process::id()));
+    path.with_file_name(name)
+}
@@ -0,0 +1,101 @@
+//! User-declared readings of literal cell text.
+//!
+//! Spreadsheets maintained by hand follow their own conventions, such as
+//! `yes`/`no` for booleans or `N/A` for a missing value. A
+//! [`ScalarCoercions`] table maps such text to the scalar it stands for, or
+//! marks it to be skipped, and is applied by the workbook and CSV readers.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::io::tabular::value_to_scalar;
+use crate::aideon::tools::model::ScalarValue;
+
+/// Keyword that marks a text as skipped in a coercion table file.
+pub const SKIP_KEYWORD: &str = "@skip";
+
+/// How cell text matching a coercion rule is read.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Coercion {
+    /// The text reads as this scalar.
+    Scalar(ScalarValue),
+    /// The text is dropped as if the cell, or the array item, were empty.
+    Skip,
+}
+
+/// Table of cell texts and the coercion applied to each.
+///
+/// Rules match literal cells whose whole trimmed text, or whose string
+/// value, equals the rule's text exactly; in array cells each string item is
+/// matched on its own. Cells starting with the `'` text marker are never
+/// coerced. Reference cells only honour rules that skip the cell or read it
+/// as null, both of which leave the reference out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScalarCoercions {
+    rules: HashMap<String, Coercion>,
+}
+
+impl ScalarCoercions {
+    /// Creates an empty table, which leaves every cell as written.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule reading `text` as `coercion`, replacing any earlier rule
+    /// for the same text.
+    pub fn with_rule(mut self, text: impl Into<String>, coercion: Coercion) -> Self {
+        self.rules.insert(text.into(), coercion);
+        self
+    }
+
+    /// Reads a table from a JSON object mapping each text to the literal it
+    /// stands for, written like a JSON-encoded cell (`true`, `null`, `3`,
+    /// `"text"`, or a `{"@value": ...}` object), or to `"@skip"`:
+    ///
+    /// ```json
+    /// { "yes": true, "no": false, "N/A": null, "∞": "@skip" }
+    /// ```
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let Value::Object(entries) = value else {
+            return Err(ToolError::InvalidCoercions(format!(
+                "expected an object mapping cell text to values, found {value}"
+            )));
+        };
+        let mut coercions = Self::new();
+        for (text, target) in entries {
+            let coercion = match target {
+                Value::String(keyword) if keyword == SKIP_KEYWORD => Coercion::Skip,
+                Value::Array(_) => {
+                    return Err(ToolError::InvalidCoercions(format!(
+                        "'{text}' maps to an array; rules map text to a single literal"
+                    )));
+                }
+                Value::Object(map) if !map.contains_key("@value") => {
+                    return Err(ToolError::InvalidCoercions(format!(
+                        "'{text}' maps to an object without '@value'"
+                    )));
+                }
+                other => Coercion::Scalar(value_to_scalar(other.clone())?),
+            };
+            coercions.rules.insert(text.clone(), coercion);
+        }
+        Ok(coercions)
+    }
+
+    /// Returns the coercion for `text`, if any.
+    pub fn get(&self, text: &str) -> Option<&Coercion> {
+        self.rules.get(text)
+    }
+
+    /// Returns the number of rules.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns whether the table has no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
@@ -0,0 +1,186 @@
+//! Delimiters, quoting, and text encodings of CSV files.
+//!
+//! CSV files are written as comma-separated UTF-8 by default. Partner
+//! systems expecting semicolons, tabs, UTF-16, or Windows-1252 get files in
+//! their dialect from a [`CsvDialect`], and reading with the same dialect
+//! loads them back.
+
+use serde::Deserialize;
+
+use crate::aideon::tools::error::{Result, ToolError};
+
+/// How CSV files are delimited, quoted, and encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CsvDialect {
+    /// Character separating the fields of a record, `,` by default.
+    pub delimiter: char,
+    /// Character enclosing quoted fields, `"` by default.
+    pub quote: char,
+    /// Which fields written files quote. Readers accept quoted and unquoted
+    /// fields alike.
+    pub quoting: CsvQuoting,
+    /// Text encoding of the files.
+    pub encoding: CsvEncoding,
+    /// Starts written files with a byte order mark. Readers skip a byte
+    /// order mark either way and let it decide between UTF-8 and UTF-16.
+    pub bom: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            quoting: CsvQuoting::default(),
+            encoding: CsvEncoding::default(),
+            bom: false,
+        }
+    }
+}
+
+/// Fields quoted in written CSV files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CsvQuoting {
+    /// Quotes fields holding the delimiter, the quote, or a line break.
+    #[default]
+    Necessary,
+    /// Quotes every field.
+    Always,
+    /// Quotes every field that is not a number.
+    NonNumeric,
+    /// Quotes no field, failing on fields that would need it.
+    Never,
+}
+
+/// Text encodings of CSV files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CsvEncoding {
+    /// UTF-8.
+    #[default]
+    #[serde(rename = "utf-8")]
+    Utf8,
+    /// UTF-16, little-endian.
+    #[serde(rename = "utf-16le")]
+    Utf16Le,
+    /// UTF-16, big-endian.
+    #[serde(rename = "utf-16be")]
+    Utf16Be,
+    /// Windows-1252, the Western European code page used by Excel on
+    /// Windows. Writing fails on characters it cannot represent.
+    #[serde(rename = "windows-1252")]
+    Windows1252,
+}
+
+impl CsvDialect {
+    /// Returns a reader configuration for records in this dialect, whose
+    /// text has already been decoded with [`Self::decode`].
+    pub(crate) fn reader(&self) -> Result<csv::ReaderBuilder> {
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(ascii(self.delimiter, "delimiter")?)
+            .quote(ascii(self.quote, "quote")?);
+        Ok(builder)
+    }
+
+    /// Returns a writer configuration for records in this dialect, whose
+    /// text is encoded with [`Self::encode`] afterwards.
+    pub(crate) fn writer(&self) -> Result<csv::WriterBuilder> {
+        let mut builder = csv::WriterBuilder::new();
+        builder
+            .delimiter(ascii(self.delimiter, "delimiter")?)
+            .quote(ascii(self.quote, "quote")?)
+            .quote_style(match self.quoting {
+                CsvQuoting::Necessary => csv::QuoteStyle::Necessary,
+                CsvQuoting::Always => csv::QuoteStyle::Always,
+                CsvQuoting::NonNumeric => csv::QuoteStyle::NonNumeric,
+                CsvQuoting::Never => csv::QuoteStyle::Never,
+            });
+        Ok(builder)
+    }
+
+    /// Decodes the bytes of a file in this dialect, following its byte order
+    /// mark when it has one.
+    pub(crate) fn decode(&self, bytes: &[u8]) -> Result<String> {
+        let encoding = match self.encoding {
+            CsvEncoding::Utf8 => encoding_rs::UTF_8,
+            CsvEncoding::Utf16Le => encoding_rs::UTF_16LE,
+            CsvEncoding::Utf16Be => encoding_rs::UTF_16BE,
+            CsvEncoding::Windows1252 => encoding_rs::WINDOWS_1252,
+        };
+        let (text, used, malformed) = encoding.decode(bytes);
+        if malformed {
+            return Err(ToolError::CsvEncoding(format!(
+                "the file is not valid {}",
+                used.name()
+            )));
+        }
+        Ok(text.into_owned())
+    }
+
+    /// Encodes UTF-8 CSV text in this dialect.
+    pub(crate) fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        match self.encoding {
+            CsvEncoding::Utf8 => {
+                if self.bom {
+                    bytes.extend_from_slice(b"\xEF\xBB\xBF");
+                }
+                bytes.extend_from_slice(text.as_bytes());
+            }
+            CsvEncoding::Utf16Le => {
+                let bom = self.bom.then_some('\u{FEFF}');
+                for unit in bom.into_iter().chain(text.chars()).flat_map(utf16) {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            CsvEncoding::Utf16Be => {
+                let bom = self.bom.then_some('\u{FEFF}');
+                for unit in bom.into_iter().chain(text.chars()).flat_map(utf16) {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+            CsvEncoding::Windows1252 => {
+                // The code page has no byte order mark.
+                let (encoded, _, unmappable) = encoding_rs::WINDOWS_1252.encode(text);
+                if unmappable {
+                    let character = text
+                        .chars()
+                        .find(|character| {
+                            let mut buffer = [0; 4];
+                            encoding_rs::WINDOWS_1252
+                                .encode(character.encode_utf8(&mut buffer))
+                                .2
+                        })
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    return Err(ToolError::CsvEncoding(format!(
+                        "'{character}' cannot be written in windows-1252"
+                    )));
+                }
+                bytes.extend_from_slice(&encoded);
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+fn utf16(character: char) -> impl Iterator<Item = u16> {
+    let mut buffer = [0; 2];
+    let units = character.encode_utf16(&mut buffer).len();
+    buffer.into_iter().take(units)
+}
+
+fn ascii(character: char, what: &str) -> Result<u8> {
+    u8::try_from(character)
+        .ok()
+        .filter(u8::is_ascii)
+        .ok_or_else(|| {
+            ToolError::InvalidConfig(format!(
+                "CSV {what} '{character}' is not a single ASCII character"
+            ))
+        })
+}
@@ -0,0 +1,223 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::io::coercion::ScalarCoercions;
+use crate::aideon::tools::io::csv_dialect::CsvDialect;
+use crate::aideon::tools::io::csv_write::{table_file_name, table_path};
+use crate::aideon::tools::io::tabular::{self, PreviewSource, SheetRows, SheetSource};
+use crate::aideon::tools::model::Node;
+use crate::aideon::tools::provenance::CellProvenance;
+use crate::aideon::tools::validate::ValidationReport;
+
+/// Reads nodes from a directory of CSV files following the conventions
+/// produced by the [`csv_write`](crate::io::csv_write) module.
+pub fn read_nodes(dir: &Path) -> Result<Vec<Node>> {
+    read_nodes_with(dir, &ScalarCoercions::default())
+}
+
+/// Reads nodes like [`read_nodes`], reading literal cells through
+/// `coercions`.
+pub fn read_nodes_with(dir: &Path, coercions: &ScalarCoercions) -> Result<Vec<Node>> {
+    read_nodes_skipping(dir, coercions, &BTreeSet::new())
+}
+
+/// Reads nodes like [`read_nodes_with`], leaving out the tables whose sheet
+/// names are in `skipped`. Skipping every table of a type also leaves out
+/// the nodes known only under that type.
+pub fn read_nodes_skipping(
+    dir: &Path,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>> {
+    read_nodes_in_dialect(dir, &CsvDialect::default(), coercions, skipped)
+}
+
+/// Reads nodes like [`read_nodes_skipping`], from files written in
+/// `dialect`.
+pub fn read_nodes_in_dialect(
+    dir: &Path,
+    dialect: &CsvDialect,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>> {
+    tabular::read_nodes(&mut DirectorySource::new(dir, dialect), coercions, skipped)
+}
+
+/// Reads nodes like [`read_nodes_in_dialect`] from a sample of the
+/// tables, see [`excel_read::read_nodes_preview`](crate::aideon::tools::io::excel_read::read_nodes_preview).
+pub fn read_nodes_preview_in_dialect(
+    dir: &Path,
+    dialect: &CsvDialect,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+    rows: usize,
+) -> Result<Vec<Node>> {
+    let mut source = PreviewSource::new(DirectorySource::new(dir, dialect), rows);
+    tabular::read_nodes(&mut source, coercions, skipped)
+}
+
+/// Reads nodes like [`read_nodes_in_dialect`], failing with
+/// [`ToolError::ConflictingCell`](crate::aideon::tools::ToolError::ConflictingCell)
+/// where two rows describing the same node give one of its properties
+/// different values, instead of keeping the later value.
+pub fn read_nodes_strict_in_dialect(
+    dir: &Path,
+    dialect: &CsvDialect,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>> {
+    tabular::read_nodes_strict(&mut DirectorySource::new(dir, dialect), coercions, skipped)
+}
+
+/// Reads nodes like [`read_nodes_skipping`], returning the disagreements
+/// between `entities.csv` and the type tables instead of logging them.
+pub fn read_nodes_with_report(
+    dir: &Path,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    read_nodes_with_report_in_dialect(dir, &CsvDialect::default(), coercions, skipped)
+}
+
+/// Reads nodes like [`read_nodes_with_report`], from files written in
+/// `dialect`.
+pub fn read_nodes_with_report_in_dialect(
+    dir: &Path,
+    dialect: &CsvDialect,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    tabular::read_nodes_reporting(&mut DirectorySource::new(dir, dialect), coercions, skipped)
+}
+
+/// Reads nodes like [`read_nodes_with_report_in_dialect`], leaving out the
+/// cells whose text cannot be read instead of failing at them and reporting
+/// each as an [`IssueKind::InvalidCell`](crate::aideon::tools::validate::IssueKind::InvalidCell)
+/// error.
+pub fn read_nodes_keep_going_in_dialect(
+    dir: &Path,
+    dialect: &CsvDialect,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    tabular::read_nodes_keep_going(&mut DirectorySource::new(dir, dialect), coercions, skipped)
+}
+
+/// Reads nodes like [`read_nodes_skipping`], from files written in
+/// `dialect`, also returning the cell each value was read from. Cells are
+/// located on the tables named like workbook sheets.
+pub fn read_nodes_with_provenance_in_dialect(
+    dir: &Path,
+    dialect: &CsvDialect,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, Vec<CellProvenance>)> {
+    tabular::read_nodes_with_provenance(&mut DirectorySource::new(dir, dialect), coercions, skipped)
+}
+
+/// Reads the JSON-LD context embedded in `context.csv`, or `None` when the
+/// directory has none.
+pub fn read_context(dir: &Path) -> Result<Option<Value>> {
+    read_context_in_dialect(dir, &CsvDialect::default())
+}
+
+/// Reads the embedded JSON-LD context like [`read_context`], from files
+/// written in `dialect`.
+pub fn read_context_in_dialect(dir: &Path, dialect: &CsvDialect) -> Result<Option<Value>> {
+    tabular::read_context(&mut DirectorySource::new(dir, dialect))
+}
+
+/// Reads nodes like [`read_nodes_in_dialect`], from CSV files that `open`
+/// returns a reader for, or `None` when there is no such file, by file name
+/// such as `entities.csv`. This reads tables kept outside a directory, such
+/// as the entries of a zip archive.
+pub fn read_nodes_from<R, F>(
+    open: F,
+    dialect: &CsvDialect,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>>
+where
+    R: Read,
+    F: FnMut(&str) -> Result<Option<R>>,
+{
+    let mut source = FileSource { open, dialect };
+    tabular::read_nodes(&mut source, coercions, skipped)
+}
+
+/// Reads the embedded JSON-LD context like [`read_context`], from the CSV
+/// files that `open` returns, see [`read_nodes_from`].
+pub fn read_context_from<R, F>(open: F, dialect: &CsvDialect) -> Result<Option<Value>>
+where
+    R: Read,
+    F: FnMut(&str) -> Result<Option<R>>,
+{
+    tabular::read_context(&mut FileSource { open, dialect })
+}
+
+struct DirectorySource<'a> {
+    dir: PathBuf,
+    dialect: &'a CsvDialect,
+}
+
+impl<'a> DirectorySource<'a> {
+    fn new(dir: &Path, dialect: &'a CsvDialect) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            dialect,
+        }
+    }
+}
+
+impl SheetSource for DirectorySource<'_> {
+    fn sheet(&mut self, name: &str) -> Result<SheetRows> {
+        let path = table_path(&self.dir, name);
+        if !path.is_file() {
+            return Err(tabular::missing_sheet(name));
+        }
+        read_rows(self.dialect, &fs::read(path)?)
+    }
+
+    fn has_sheet(&mut self, name: &str) -> bool {
+        table_path(&self.dir, name).is_file()
+    }
+}
+
+struct FileSource<'a, F> {
+    open: F,
+    dialect: &'a CsvDialect,
+}
+
+impl<R, F> SheetSource for FileSource<'_, F>
+where
+    R: Read,
+    F: FnMut(&str) -> Result<Option<R>>,
+{
+    fn sheet(&mut self, name: &str) -> Result<SheetRows> {
+        let Some(mut file) = (self.open)(&table_file_name(name))? else {
+            return Err(tabular::missing_sheet(name));
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        read_rows(self.dialect, &bytes)
+    }
+
+    fn has_sheet(&mut self, name: &str) -> bool {
+        matches!((self.open)(&table_file_name(name)), Ok(Some(_)))
+    }
+}
+
+fn read_rows(dialect: &CsvDialect, bytes: &[u8]) -> Result<SheetRows> {
+    let text = dialect.decode(bytes)?;
+    dialect
+        .reader()?
+        .from_reader(text.as_bytes())
+        .records()
+        .map(|record| Ok(record?.iter().map(str::to_string).collect()))
+        .collect()
+}
@@ -0,0 +1,72 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::flatten::{
+    CONTEXT_SHEET, DATASET_SHEET, ENTITIES_SHEET, METADATA_SHEET, SheetTable, WorkbookData,
+};
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::io::csv_dialect::CsvDialect;
+
+/// Writes every table of `workbook` as a CSV file inside `dir`, creating the
+/// directory when necessary. The index and metadata tables are written as
+/// `entities.csv` and `metadata.csv`; every other table uses its sheet name.
+/// Each file is replaced whole, see [`atomic`].
+pub fn write_workbook(dir: &Path, workbook: &WorkbookData) -> Result<()> {
+    write_workbook_in_dialect(dir, workbook, &CsvDialect::default())
+}
+
+/// Writes every table of `workbook` like [`write_workbook`], in `dialect`.
+pub fn write_workbook_in_dialect(
+    dir: &Path,
+    workbook: &WorkbookData,
+    dialect: &CsvDialect,
+) -> Result<()> {
+    fs::create_dir_all(dir)?;
+
+    for table in &workbook.tables {
+        atomic::write_atomically(&table_path(dir, &table.sheet_name), |partial| {
+            write_table_to(File::create(partial)?, table, dialect)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Writes `table` as CSV in `dialect` to `writer`, such as an entry of a zip
+/// archive named by [`table_file_name`], its header row first.
+pub fn write_table_to<W: Write>(
+    mut writer: W,
+    table: &SheetTable,
+    dialect: &CsvDialect,
+) -> Result<()> {
+    let mut records = dialect.writer()?.from_writer(Vec::new());
+    records.write_record(&table.columns)?;
+    for row in &table.rows {
+        records.write_record(row)?;
+    }
+    let text = records.into_inner().map_err(|err| err.into_error())?;
+    let text = String::from_utf8(text).map_err(|err| ToolError::CsvEncoding(err.to_string()))?;
+    writer.write_all(&dialect.encode(&text)?)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Returns the CSV file that stores the sheet called `sheet_name`.
+pub fn table_path(dir: &Path, sheet_name: &str) -> PathBuf {
+    dir.join(table_file_name(sheet_name))
+}
+
+/// Returns the name of the CSV file that stores the sheet called
+/// `sheet_name`, such as `entities.csv` for the index sheet.
+pub fn table_file_name(sheet_name: &str) -> String {
+    let file_name = match sheet_name {
+        ENTITIES_SHEET => "entities",
+        METADATA_SHEET => "metadata",
+        CONTEXT_SHEET => "context",
+        DATASET_SHEET => "dataset",
+        other => other,
+    };
+    format!("{file_name}.csv")
+}
@@ -1,244 +1,170 @@
-use std::collections::{BTreeMap, HashMap, btree_map::Entry};
+use std::collections::BTreeSet;
+use std::io::{Read, Seek};
 use std::path::Path;
 
 use calamine::{Data, Reader, Xlsx, open_workbook};
+use chrono::NaiveTime;
 use serde_json::Value;
 
-use crate::aideon::tools::error::{Result, ToolError};
-use crate::aideon::tools::flatten::{ENTITIES_SHEET, METADATA_SHEET, UNTYPED_MARKER};
-use crate::aideon::tools::model::{ArrayValue, Node, PropertyValue, ScalarValue};
-
-type NodeKey = (Option<String>, String);
-
-type TypeSheetMap = HashMap<String, String>;
-type ChildSheetMap = HashMap<String, (String, String)>;
+use crate::aideon::tools::diff::RowLocations;
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::io::coercion::ScalarCoercions;
+use crate::aideon::tools::io::tabular::{self, PreviewSource, SheetRows, SheetSource};
+use crate::aideon::tools::model::Node;
+use crate::aideon::tools::provenance::CellProvenance;
+use crate::aideon::tools::validate::ValidationReport;
 
 /// Reads nodes from an Excel workbook following the conventions produced by the
 /// [`excel_write`](crate::io::excel_write) module.
 pub fn read_nodes(path: &Path) -> Result<Vec<Node>> {
-    let mut workbook: Xlsx<_> = open_workbook(path)?;
-
-    let metadata_range = read_required_sheet(&mut workbook, METADATA_SHEET)?;
-    let entities_range = read_required_sheet(&mut workbook, ENTITIES_SHEET)?;
-
-    let (type_sheets, child_sheets) = parse_metadata(&metadata_range)?;
-    let mut nodes = initialize_nodes(&entities_range)?;
-
-    for (sheet_name, type_name) in &type_sheets {
-        let range = read_required_sheet(&mut workbook, sheet_name)?;
-        ingest_type_sheet(&range, type_name, &mut nodes)?;
-    }
-
-    for (sheet_name, (_type_name, predicate)) in &child_sheets {
-        let range = read_required_sheet(&mut workbook, sheet_name)?;
-        ingest_child_sheet(&range, predicate, &mut nodes)?;
-    }
-
-    let mut nodes: Vec<Node> = nodes.into_values().collect();
-    nodes.sort_by(|lhs, rhs| lhs.graph.cmp(&rhs.graph).then_with(|| lhs.id.cmp(&rhs.id)));
-    Ok(nodes)
+    read_nodes_with(path, &ScalarCoercions::default())
 }
 
-fn read_required_sheet<R: std::io::Read + std::io::Seek>(
-    workbook: &mut Xlsx<R>,
-    name: &str,
-) -> Result<calamine::Range<Data>> {
-    match workbook.worksheet_range(name) {
-        Ok(range) => Ok(range),
-        Err(calamine::XlsxError::WorksheetNotFound(_)) => Err(ToolError::InvalidWorkbook(format!(
-            "missing sheet '{name}'"
-        ))),
-        Err(err) => Err(err.into()),
-    }
+/// Reads nodes like [`read_nodes`], reading literal cells through
+/// `coercions`.
+pub fn read_nodes_with(path: &Path, coercions: &ScalarCoercions) -> Result<Vec<Node>> {
+    read_nodes_skipping(path, coercions, &BTreeSet::new())
 }
 
-fn parse_metadata(range: &calamine::Range<Data>) -> Result<(TypeSheetMap, ChildSheetMap)> {
-    let mut type_sheets: TypeSheetMap = HashMap::new();
-    let mut child_sheets: ChildSheetMap = HashMap::new();
-
-    for row in range.rows().skip(1) {
-        let kind = string_at(row, 0);
-        if kind.is_empty() {
-            continue;
-        }
-        let sheet = string_at(row, 1);
-        let type_name = string_at(row, 2);
-        let predicate = string_at(row, 3);
-
-        match kind.as_str() {
-            "type" => {
-                type_sheets.insert(sheet, type_name);
-            }
-            "child" => {
-                child_sheets.insert(sheet, (type_name, predicate));
-            }
-            other => {
-                return Err(ToolError::InvalidWorkbook(format!(
-                    "unknown metadata kind '{other}'"
-                )));
-            }
-        }
-    }
-
-    Ok((type_sheets, child_sheets))
+/// Reads nodes like [`read_nodes_with`], leaving out the sheets named in
+/// `skipped`. Skipping every sheet of a type also leaves out the nodes known
+/// only under that type.
+pub fn read_nodes_skipping(
+    path: &Path,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>> {
+    let workbook: Xlsx<_> = open_workbook(path)?;
+    tabular::read_nodes(&mut WorkbookSource { workbook }, coercions, skipped)
 }
 
-fn initialize_nodes(range: &calamine::Range<Data>) -> Result<BTreeMap<NodeKey, Node>> {
-    let mut nodes = BTreeMap::new();
-
-    for row in range.rows().skip(1) {
-        let id = string_at(row, 0);
-        if id.is_empty() {
-            continue;
-        }
-        let type_name = string_at(row, 1);
-        let node = ensure_node(&mut nodes, &id, string_at(row, 2));
-        if !type_name.is_empty() && type_name != UNTYPED_MARKER {
-            node.types.insert(type_name);
-        }
-    }
-
-    Ok(nodes)
+/// Reads nodes like [`read_nodes_skipping`] from a sample of the workbook:
+/// the first `rows` rows of each type sheet, with the rows of the other
+/// sheets describing the nodes those hold.
+pub fn read_nodes_preview(
+    path: &Path,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+    rows: usize,
+) -> Result<Vec<Node>> {
+    let workbook: Xlsx<_> = open_workbook(path)?;
+    let mut source = PreviewSource::new(WorkbookSource { workbook }, rows);
+    tabular::read_nodes(&mut source, coercions, skipped)
 }
 
-fn ingest_type_sheet(
-    range: &calamine::Range<Data>,
-    type_name: &str,
-    nodes: &mut BTreeMap<NodeKey, Node>,
-) -> Result<()> {
-    let headers = read_headers(range);
-    if headers.is_empty() {
-        return Ok(());
-    }
-
-    for row in range.rows().skip(1) {
-        let id = string_at(row, 0);
-        if id.is_empty() {
-            continue;
-        }
-
-        let node = ensure_node(nodes, &id, string_at(row, 1));
-        if !type_name.is_empty() && type_name != UNTYPED_MARKER {
-            node.types.insert(type_name.to_owned());
-        }
-
-        for (col_idx, cell) in row.iter().enumerate().skip(2) {
-            let Some(header) = headers.get(col_idx) else {
-                continue;
-            };
-            if header.is_empty() {
-                continue;
-            }
-
-            let raw_value = cell_to_string(Some(cell));
-            if raw_value.trim().is_empty() {
-                continue;
-            }
-
-            let (predicate, property) = parse_property_entry(header, &raw_value)?;
-            node.insert_property(predicate, property);
-        }
-    }
-
-    Ok(())
+/// Reads nodes like [`read_nodes_skipping`], failing with
+/// [`ToolError::ConflictingCell`](crate::aideon::tools::ToolError::ConflictingCell)
+/// where two rows describing the same node give one of its properties
+/// different values, instead of keeping the later value.
+pub fn read_nodes_strict(
+    path: &Path,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>> {
+    let workbook: Xlsx<_> = open_workbook(path)?;
+    tabular::read_nodes_strict(&mut WorkbookSource { workbook }, coercions, skipped)
 }
 
-fn ingest_child_sheet(
-    range: &calamine::Range<Data>,
-    predicate: &str,
-    nodes: &mut BTreeMap<NodeKey, Node>,
-) -> Result<()> {
-    let header_width = range.rows().next().map(|row| row.len()).unwrap_or(0);
-    let has_graph_column = header_width >= 3;
+/// Reads nodes like [`read_nodes_skipping`], from a workbook held by
+/// `reader` instead of a file.
+pub fn read_nodes_from<R: Read + Seek>(
+    reader: R,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>> {
+    let workbook = Xlsx::new(reader)?;
+    tabular::read_nodes(&mut WorkbookSource { workbook }, coercions, skipped)
+}
 
-    for row in range.rows().skip(1) {
-        let parent = string_at(row, 0);
-        let target_index = if has_graph_column { 2 } else { 1 };
-        let target = string_at(row, target_index);
-        if parent.is_empty() || target.is_empty() {
-            continue;
-        }
+/// Reads nodes like [`read_nodes_skipping`], returning the disagreements
+/// between the `Entities` sheet and the type sheets instead of logging them.
+pub fn read_nodes_with_report(
+    path: &Path,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    let workbook: Xlsx<_> = open_workbook(path)?;
+    tabular::read_nodes_reporting(&mut WorkbookSource { workbook }, coercions, skipped)
+}
 
-        let raw_graph = if has_graph_column {
-            string_at(row, 1)
-        } else {
-            String::new()
-        };
-        let node = ensure_node(nodes, &parent, raw_graph);
-        let predicate_key = predicate.to_string();
+/// Reads nodes like [`read_nodes_with_report`], leaving out the cells
+/// whose text cannot be read instead of failing at them and reporting each
+/// as an [`IssueKind::InvalidCell`](crate::aideon::tools::validate::IssueKind::InvalidCell)
+/// error, so every problem of a hand-edited workbook is found in one pass.
+pub fn read_nodes_keep_going(
+    path: &Path,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    let workbook: Xlsx<_> = open_workbook(path)?;
+    tabular::read_nodes_keep_going(&mut WorkbookSource { workbook }, coercions, skipped)
+}
 
-        match node.properties.entry(predicate_key) {
-            Entry::Occupied(mut entry) => match entry.get_mut() {
-                PropertyValue::Array(ArrayValue::ObjectRefs(ids)) => {
-                    ids.push(target);
-                }
-                _ => {
-                    return Err(ToolError::InvalidWorkbook(format!(
-                        "predicate '{predicate}' is not an object reference array"
-                    )));
-                }
-            },
-            Entry::Vacant(entry) => {
-                entry.insert(PropertyValue::Array(ArrayValue::ObjectRefs(vec![target])));
-            }
-        }
-    }
+/// Reads nodes like [`read_nodes_skipping`], also returning the cell each
+/// value was read from.
+pub fn read_nodes_with_provenance(
+    path: &Path,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, Vec<CellProvenance>)> {
+    let workbook: Xlsx<_> = open_workbook(path)?;
+    tabular::read_nodes_with_provenance(&mut WorkbookSource { workbook }, coercions, skipped)
+}
 
-    Ok(())
+/// Reads nodes like [`read_nodes_with_report`], from a workbook held by
+/// `reader` instead of a file.
+pub fn read_nodes_with_report_from<R: Read + Seek>(
+    reader: R,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    let workbook = Xlsx::new(reader)?;
+    tabular::read_nodes_reporting(&mut WorkbookSource { workbook }, coercions, skipped)
 }
 
-/// Extracts the header row as owned strings, returning an empty collection when absent.
-fn read_headers(range: &calamine::Range<Data>) -> Vec<String> {
-    range
-        .rows()
-        .next()
-        .map(|row| row.iter().map(|cell| cell_to_string(Some(cell))).collect())
-        .unwrap_or_default()
+/// Reads the JSON-LD context embedded in the workbook's `Context` sheet, or
+/// `None` when it has none.
+pub fn read_context(path: &Path) -> Result<Option<Value>> {
+    let workbook: Xlsx<_> = open_workbook(path)?;
+    tabular::read_context(&mut WorkbookSource { workbook })
 }
 
-/// Converts the cell at `index` into a `String`, returning an empty string when missing.
-fn string_at(row: &[Data], index: usize) -> String {
-    cell_to_string(row.get(index))
+/// Reads the embedded JSON-LD context like [`read_context`], from a workbook
+/// held by `reader` instead of a file.
+pub fn read_context_from<R: Read + Seek>(reader: R) -> Result<Option<Value>> {
+    let workbook = Xlsx::new(reader)?;
+    tabular::read_context(&mut WorkbookSource { workbook })
 }
 
-/// Returns the node matching `id` and `raw_graph`, normalising the graph identifier in the process.
-fn ensure_node<'a>(
-    nodes: &'a mut BTreeMap<NodeKey, Node>,
-    id: &str,
-    raw_graph: String,
-) -> &'a mut Node {
-    let graph = normalize_optional(raw_graph);
-    let id_key = id.to_owned();
-    let key = (graph.clone(), id_key.clone());
-    let node = nodes
-        .entry(key)
-        .or_insert_with(|| Node::with_graph(id_key.clone(), graph.clone()));
-    node.set_graph(graph);
-    node
+/// Lists the rows of the workbook's type, child, and matrix sheets that
+/// describe each node.
+pub fn locate_rows(path: &Path) -> Result<RowLocations> {
+    let workbook: Xlsx<_> = open_workbook(path)?;
+    tabular::locate_rows(&mut WorkbookSource { workbook })
 }
 
-/// Converts a header/value pair coming from a type sheet row into a property entry.
-fn parse_property_entry(header: &str, raw_value: &str) -> Result<(String, PropertyValue)> {
-    if let Some(predicate) = header.strip_suffix("Id") {
-        return Ok((
-            predicate.to_string(),
-            PropertyValue::ObjectRef(raw_value.to_string()),
-        ));
-    }
+struct WorkbookSource<R> {
+    workbook: Xlsx<R>,
+}
 
-    let parsed = serde_json::from_str::<Value>(raw_value)?;
-    let property = match parsed {
-        Value::Array(items) => {
-            let scalars = items
-                .into_iter()
-                .map(value_to_scalar)
-                .collect::<Result<Vec<_>>>()?;
-            PropertyValue::Array(ArrayValue::Scalars(scalars))
+impl<R: Read + Seek> SheetSource for WorkbookSource<R> {
+    fn sheet(&mut self, name: &str) -> Result<SheetRows> {
+        match self.workbook.worksheet_range(name) {
+            Ok(range) => Ok(range
+                .rows()
+                .map(|row| row.iter().map(|cell| cell_to_string(Some(cell))).collect())
+                .collect()),
+            Err(calamine::XlsxError::WorksheetNotFound(_)) => Err(tabular::missing_sheet(name)),
+            Err(err) => Err(err.into()),
         }
-        other => PropertyValue::Scalar(value_to_scalar(other)?),
-    };
+    }
 
-    Ok((header.to_string(), property))
+    fn has_sheet(&mut self, name: &str) -> bool {
+        self.workbook
+            .sheet_names()
+            .iter()
+            .any(|sheet_name| sheet_name == name)
+    }
 }
 
 fn cell_to_string(cell: Option<&Data>) -> String {
@@ -247,32 +173,21 @@ fn cell_to_string(cell: Option<&Data>) -> String {
         Some(Data::Float(value)) => value.to_string(),
         Some(Data::Int(value)) => value.to_string(),
         Some(Data::Bool(value)) => value.to_string(),
-        Some(Data::DateTime(value)) => value.to_string(),
+        Some(Data::DateTime(value)) => match value.as_datetime() {
+            // Dates entered in Excel read as their ISO 8601 form, so that
+            // `xsd:date` and `xsd:dateTime` columns parse them.
+            Some(datetime) if !value.is_duration() => {
+                if datetime.time() == NaiveTime::MIN {
+                    datetime.format("%Y-%m-%d").to_string()
+                } else {
+                    datetime.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+                }
+            }
+            _ => value.to_string(),
+        },
         Some(Data::DateTimeIso(value)) => value.clone(),
         Some(Data::DurationIso(value)) => value.clone(),
         Some(Data::Error(value)) => value.to_string(),
         Some(Data::Empty) | None => String::new(),
     }
 }
-
-fn value_to_scalar(value: Value) -> Result<ScalarValue> {
-    Ok(match value {
-        Value::Null => ScalarValue::Null,
-        Value::Bool(value) => ScalarValue::Boolean(value),
-        Value::Number(number) => ScalarValue::Number(
-            number
-                .as_f64()
-                .ok_or_else(|| ToolError::InvalidWorkbook("invalid number literal".into()))?,
-        ),
-        Value::String(value) => ScalarValue::String(value),
-        other => ScalarValue::String(serde_json::to_string(&other)?),
-    })
-}
-fn normalize_optional(value: String) -> Option<String> {
-    let trimmed = value.trim();
-    if trimmed.is_empty() {
-        None
-    } else {
-        Some(trimmed.to_string())
-    }
-}
@@ -0,0 +1,1430 @@
+//! In-place updates of workbooks written by
+//! [`excel_write`](crate::aideon::tools::io::excel_write).
+//!
+//! Writing a workbook from scratch discards everything users add to it in
+//! Excel: formatting, comments, extra columns, and sheets of their own.
+//! [`update_workbook`] edits the sheet XML of the existing file instead. Rows
+//! are matched by key, which is `id` and `graph` on type and matrix sheets
+//! and the whole row on the other sheets, and only cells whose text changed
+//! are rewritten, typed the way `excel_write` types them.
+//! Untouched rows, sheets, and every other part of the package are copied
+//! unchanged.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use quick_xml::Reader;
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::{BytesStart, Event};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::flatten::{METADATA_SHEET, SheetTable, WorkbookData};
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::io::excel_write::{
+    CellValue, cell_value, literal_columns, literal_datatypes,
+};
+
+const CONTENT_TYPES_PART: &str = "[Content_Types].xml";
+const WORKBOOK_PART: &str = "xl/workbook.xml";
+
+const RELATIONSHIPS_NS: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const PACKAGE_RELATIONSHIPS_NS: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships";
+const SPREADSHEET_NS: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const WORKSHEET_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml";
+const TABLE_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.table+xml";
+
+/// Rows changed by [`update_workbook`], summed over all sheets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkbookUpdate {
+    /// Rows added, first into rows freed by deletions, then below the last
+    /// row of the sheet.
+    pub inserted_rows: usize,
+    /// Existing rows with at least one rewritten cell.
+    pub updated_rows: usize,
+    /// Rows removed because their key is no longer written.
+    pub deleted_rows: usize,
+    /// Sheets that did not exist in the workbook and were appended to it.
+    pub added_sheets: Vec<String>,
+}
+
+impl WorkbookUpdate {
+    /// Returns whether the update left the workbook unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.inserted_rows == 0
+            && self.updated_rows == 0
+            && self.deleted_rows == 0
+            && self.added_sheets.is_empty()
+    }
+}
+
+/// Updates the workbook at `path` in place so that it holds the tables of
+/// `workbook`.
+///
+/// Rows keep their position: deleted rows are removed without moving the
+/// rows below them, so comments, formatting, and formulas stay attached to
+/// the cells they belong to. Columns missing from a sheet are appended after
+/// its last header, and Excel tables starting at the header row are widened
+/// and lengthened to cover new columns and rows. Columns the sheet's previous
+/// `Metadata` declared but the new tables no longer write are cleared; other
+/// headers are treated as user columns and left alone. Sheets that are
+/// missing are appended to the workbook.
+pub fn update_workbook(path: &Path, workbook: &WorkbookData) -> Result<WorkbookUpdate> {
+    let mut package = Package::open(path)?;
+    let previous = package.previous_layout()?;
+    let mut update = WorkbookUpdate::default();
+    let literals = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == METADATA_SHEET)
+        .map(literal_columns)
+        .unwrap_or_default();
+
+    let mut written = HashSet::new();
+    for table in &workbook.tables {
+        written.insert(table.sheet_name.as_str());
+        let datatypes = literal_datatypes(table, &literals);
+        let part = package.sheets.get(&table.sheet_name).cloned();
+        // Every target column of a matrix sheet is written, so targets no
+        // longer linked have their marks cleared.
+        let matrix_columns = match &part {
+            Some(part) if previous.matrices.contains(&table.sheet_name) => {
+                package.header_texts(part)?
+            }
+            _ => Vec::new(),
+        };
+        let retired: Vec<&str> = previous
+            .columns
+            .get(&table.sheet_name)
+            .into_iter()
+            .flatten()
+            .chain(matrix_columns.iter().skip(2))
+            .map(String::as_str)
+            .filter(|column| !table.columns.iter().any(|written| written == column))
+            .collect();
+        match part {
+            Some(part) => package.update_sheet(&part, table, &datatypes, &retired, &mut update)?,
+            None => {
+                package.add_sheet(table, &datatypes)?;
+                update.inserted_rows += table.rows.len();
+                update.added_sheets.push(table.sheet_name.clone());
+            }
+        }
+    }
+
+    // Sheets of types or relationships that no longer occur lose their rows
+    // but stay in the workbook, like any other sheet the tables do not name.
+    for sheet_name in &previous.sheets {
+        if written.contains(sheet_name.as_str()) {
+            continue;
+        }
+        let Some(part) = package.sheets.get(sheet_name).cloned() else {
+            continue;
+        };
+        let columns = package.header_texts(&part)?;
+        let table = SheetTable {
+            sheet_name: sheet_name.clone(),
+            columns,
+            rows: Vec::new(),
+        };
+        package.update_sheet(&part, &table, &[], &[], &mut update)?;
+    }
+
+    if !update.is_empty() {
+        package.save(path)?;
+    }
+    Ok(update)
+}
+
+/// Sheets and columns described by the `Metadata` sheet of the workbook
+/// being updated.
+#[derive(Default)]
+struct PreviousLayout {
+    /// Type, child, and matrix sheets.
+    sheets: Vec<String>,
+    /// Matrix sheets, whose columns are headed by targets.
+    matrices: HashSet<String>,
+    /// Declared columns of each type sheet.
+    columns: HashMap<String, Vec<String>>,
+}
+
+/// Relationship read from a `.rels` part, with its target resolved to a part
+/// name.
+struct Relationship {
+    id: String,
+    kind: String,
+    target: String,
+}
+
+/// An opened workbook package and the parts rewritten so far.
+struct Package {
+    archive: ZipArchive<File>,
+    /// Parts rewritten or added by the update, by name.
+    parts: BTreeMap<String, String>,
+    shared_strings: Vec<String>,
+    /// Worksheet part of each sheet, by sheet name.
+    sheets: HashMap<String, String>,
+}
+
+impl Package {
+    fn open(path: &Path) -> Result<Self> {
+        let archive = ZipArchive::new(File::open(path)?)?;
+        let mut package = Self {
+            archive,
+            parts: BTreeMap::new(),
+            shared_strings: Vec::new(),
+            sheets: HashMap::new(),
+        };
+
+        let relationships = package.relationships(WORKBOOK_PART)?;
+        let workbook = package.required_part(WORKBOOK_PART)?;
+        for tag in tags(&workbook, "sheet")? {
+            let (Some(name), Some(id)) = (tag.attribute("name"), tag.attribute("id")) else {
+                continue;
+            };
+            if let Some(relationship) = relationships.iter().find(|rel| rel.id == id) {
+                package
+                    .sheets
+                    .insert(name.to_string(), relationship.target.clone());
+            }
+        }
+
+        if let Some(relationship) = relationships
+            .iter()
+            .find(|rel| rel.kind.ends_with("/sharedStrings"))
+        {
+            let xml = package.required_part(&relationship.target)?;
+            package.shared_strings = parse_shared_strings(&xml)?;
+        }
+        Ok(package)
+    }
+
+    /// Returns the content of part `name`, including rewrites made so far.
+    fn part(&mut self, name: &str) -> Result<Option<String>> {
+        if let Some(content) = self.parts.get(name) {
+            return Ok(Some(content.clone()));
+        }
+        match self.archive.by_name(name) {
+            Ok(mut file) => {
+                let mut content = String::new();
+                file.read_to_string(&mut content)?;
+                Ok(Some(content))
+            }
+            Err(ZipError::FileNotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn required_part(&mut self, name: &str) -> Result<String> {
+        self.part(name)?.ok_or_else(|| {
+            ToolError::InvalidWorkbook(format!("workbook package has no part '{name}'"))
+        })
+    }
+
+    fn has_part(&self, name: &str) -> bool {
+        self.parts.contains_key(name) || self.archive.file_names().any(|file| file == name)
+    }
+
+    /// Returns the internal relationships of part `name`.
+    fn relationships(&mut self, name: &str) -> Result<Vec<Relationship>> {
+        let Some(xml) = self.part(&relationships_part(name))? else {
+            return Ok(Vec::new());
+        };
+        Ok(tags(&xml, "Relationship")?
+            .into_iter()
+            .filter(|tag| tag.attribute("TargetMode") != Some("External"))
+            .filter_map(|tag| {
+                Some(Relationship {
+                    id: tag.attribute("Id")?.to_string(),
+                    kind: tag.attribute("Type")?.to_string(),
+                    target: resolve_target(name, tag.attribute("Target")?),
+                })
+            })
+            .collect())
+    }
+
+    fn previous_layout(&mut self) -> Result<PreviousLayout> {
+        let mut layout = PreviousLayout::default();
+        let Some(part) = self.sheets.get(METADATA_SHEET).cloned() else {
+            return Ok(layout);
+        };
+        let sheet = SheetXml::parse(&self.required_part(&part)?, &self.shared_strings)?;
+        let Some(header) = sheet.header_index() else {
+            return Ok(layout);
+        };
+        let position = |name: &str| sheet.rows[header].column_of(name);
+        let (Some(kind), Some(sheet_column)) = (position("kind"), position("sheet")) else {
+            return Ok(layout);
+        };
+        let column = position("column");
+
+        for row in &sheet.rows[header + 1..] {
+            let sheet_name = row.text(sheet_column).to_string();
+            match row.text(kind) {
+                "type" | "child" => layout.sheets.push(sheet_name),
+                "matrix" => {
+                    layout.matrices.insert(sheet_name.clone());
+                    layout.sheets.push(sheet_name);
+                }
+                "column" => {
+                    if let Some(column) = column {
+                        let header = row.text(column).to_string();
+                        layout.columns.entry(sheet_name).or_default().push(header);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(layout)
+    }
+
+    /// Header texts of the worksheet `part`.
+    fn header_texts(&mut self, part: &str) -> Result<Vec<String>> {
+        let xml = self.required_part(part)?;
+        Ok(SheetXml::parse(&xml, &self.shared_strings)?.header_texts())
+    }
+
+    fn update_sheet(
+        &mut self,
+        part: &str,
+        table: &SheetTable,
+        datatypes: &[Option<&str>],
+        retired: &[&str],
+        update: &mut WorkbookUpdate,
+    ) -> Result<()> {
+        let mut sheet = SheetXml::parse(&self.required_part(part)?, &self.shared_strings)?;
+        let changes = sheet.apply(table, datatypes, retired);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        update.inserted_rows += changes.inserted;
+        update.updated_rows += changes.updated;
+        update.deleted_rows += changes.deleted;
+        self.parts.insert(part.to_string(), sheet.render()?);
+
+        for relationship in self.relationships(part)? {
+            if !relationship.kind.ends_with("/table") {
+                continue;
+            }
+            let xml = self.required_part(&relationship.target)?;
+            if let Some(xml) = extend_table(&xml, &sheet, &changes.added_columns)? {
+                self.parts.insert(relationship.target, xml);
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends a sheet holding `table` as an Excel table, like
+    /// [`write_workbook`](crate::aideon::tools::io::excel_write::write_workbook)
+    /// writes it.
+    fn add_sheet(&mut self, table: &SheetTable, datatypes: &[Option<&str>]) -> Result<()> {
+        let mut sheet = SheetXml::new(
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+                 <worksheet xmlns=\"{SPREADSHEET_NS}\" xmlns:r=\"{RELATIONSHIPS_NS}\">\
+                 <dimension ref=\"A1\"/><sheetViews><sheetView workbookViewId=\"0\"/>\
+                 </sheetViews><sheetFormatPr defaultRowHeight=\"15\"/><sheetData>"
+            ),
+            "</sheetData><pageMargins left=\"0.7\" right=\"0.7\" top=\"0.75\" \
+             bottom=\"0.75\" header=\"0.3\" footer=\"0.3\"/><tableParts count=\"1\">\
+             <tablePart r:id=\"rId1\"/></tableParts></worksheet>"
+                .to_string(),
+        );
+        sheet.apply(table, datatypes, &[]);
+
+        let sheet_part = self.free_part("xl/worksheets/sheet");
+        let table_part = self.free_part("xl/tables/table");
+        let table_id = self.free_table_id()?;
+        let last_column = table.columns.len().saturating_sub(1) as u32;
+        let reference = format!(
+            "A1:{}",
+            cell_reference(last_column, sheet.last_row().max(2))
+        );
+        let columns: String = table
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                format!(
+                    "<tableColumn id=\"{}\" name=\"{}\"/>",
+                    index + 1,
+                    escape(name.as_str())
+                )
+            })
+            .collect();
+        let table_xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <table xmlns=\"{SPREADSHEET_NS}\" id=\"{table_id}\" name=\"Table{table_id}\" \
+             displayName=\"Table{table_id}\" ref=\"{reference}\" totalsRowShown=\"0\">\
+             <autoFilter ref=\"{reference}\"/><tableColumns count=\"{}\">{columns}\
+             </tableColumns><tableStyleInfo name=\"TableStyleMedium9\" \
+             showFirstColumn=\"0\" showLastColumn=\"0\" showRowStripes=\"1\" \
+             showColumnStripes=\"0\"/></table>",
+            table.columns.len()
+        );
+        let sheet_relationships = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <Relationships xmlns=\"{PACKAGE_RELATIONSHIPS_NS}\"><Relationship Id=\"rId1\" \
+             Type=\"{RELATIONSHIPS_NS}/table\" Target=\"../tables/{}\"/></Relationships>",
+            file_name(&table_part)
+        );
+
+        let relationship_id = self.add_workbook_relationship(&sheet_part)?;
+        let workbook = self.required_part(WORKBOOK_PART)?;
+        let prefix = relationships_prefix(&workbook)?;
+        let sheet_id = tags(&workbook, "sheet")?
+            .iter()
+            .filter_map(|tag| tag.attribute("sheetId")?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let workbook = insert_before_close(
+            &workbook,
+            "sheets",
+            &format!(
+                "<sheet name=\"{}\" sheetId=\"{sheet_id}\" {prefix}:id=\"{relationship_id}\"/>",
+                escape(table.sheet_name.as_str())
+            ),
+        )?;
+        self.parts.insert(WORKBOOK_PART.to_string(), workbook);
+
+        let content_types = self.required_part(CONTENT_TYPES_PART)?;
+        let overrides = format!(
+            "<Override PartName=\"/{sheet_part}\" ContentType=\"{WORKSHEET_CONTENT_TYPE}\"/>\
+             <Override PartName=\"/{table_part}\" ContentType=\"{TABLE_CONTENT_TYPE}\"/>"
+        );
+        let content_types = insert_before_close(&content_types, "Types", &overrides)?;
+        self.parts
+            .insert(CONTENT_TYPES_PART.to_string(), content_types);
+
+        self.parts
+            .insert(relationships_part(&sheet_part), sheet_relationships);
+        self.parts.insert(sheet_part.clone(), sheet.render()?);
+        self.parts.insert(table_part, table_xml);
+        self.sheets.insert(table.sheet_name.clone(), sheet_part);
+        Ok(())
+    }
+
+    /// Registers `part` as a worksheet of the workbook and returns the id of
+    /// the new relationship.
+    fn add_workbook_relationship(&mut self, part: &str) -> Result<String> {
+        let name = relationships_part(WORKBOOK_PART);
+        let xml = self.required_part(&name)?;
+        let used: HashSet<String> = self
+            .relationships(WORKBOOK_PART)?
+            .into_iter()
+            .map(|relationship| relationship.id)
+            .collect();
+        let id = (1..)
+            .map(|number| format!("rId{number}"))
+            .find(|id| !used.contains(id))
+            .unwrap_or_default();
+        let target = part.strip_prefix("xl/").unwrap_or(part);
+        let xml = insert_before_close(
+            &xml,
+            "Relationships",
+            &format!(
+                "<Relationship Id=\"{id}\" Type=\"{RELATIONSHIPS_NS}/worksheet\" \
+                 Target=\"{target}\"/>"
+            ),
+        )?;
+        self.parts.insert(name, xml);
+        Ok(id)
+    }
+
+    /// Returns the first unused part name `{stem}{n}.xml`.
+    fn free_part(&self, stem: &str) -> String {
+        (1..)
+            .map(|number| format!("{stem}{number}.xml"))
+            .find(|name| !self.has_part(name))
+            .unwrap_or_default()
+    }
+
+    /// Returns an id above those of every table in the workbook whose
+    /// default name `Table{id}` is not taken.
+    fn free_table_id(&mut self) -> Result<u32> {
+        let table_parts: Vec<String> = self
+            .archive
+            .file_names()
+            .chain(self.parts.keys().map(String::as_str))
+            .filter(|name| name.starts_with("xl/tables/") && name.ends_with(".xml"))
+            .map(str::to_string)
+            .collect();
+        let mut highest = 0;
+        let mut names = HashSet::new();
+        for part in table_parts {
+            let xml = self.required_part(&part)?;
+            if let Some(tag) = first_tag(&xml, "table")? {
+                if let Some(id) = tag.attribute("id").and_then(|id| id.parse::<u32>().ok()) {
+                    highest = highest.max(id);
+                }
+                names.extend(tag.attribute("name").map(str::to_string));
+                names.extend(tag.attribute("displayName").map(str::to_string));
+            }
+        }
+        Ok((highest + 1..)
+            .find(|id| !names.contains(&format!("Table{id}")))
+            .unwrap_or(highest + 1))
+    }
+
+    /// Writes the package to a sibling file and moves it over `path`.
+    fn save(mut self, path: &Path) -> Result<()> {
+        atomic::write_atomically(path, |partial| self.write_to(partial))
+    }
+
+    fn write_to(&mut self, path: &Path) -> Result<()> {
+        let mut writer = ZipWriter::new(BufWriter::new(File::create(path)?));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut parts = std::mem::take(&mut self.parts);
+        for index in 0..self.archive.len() {
+            let file = self.archive.by_index_raw(index)?;
+            match parts.remove(file.name()) {
+                Some(content) => {
+                    let name = file.name().to_string();
+                    drop(file);
+                    writer.start_file(name, options)?;
+                    writer.write_all(content.as_bytes())?;
+                }
+                None => writer.raw_copy_file(file)?,
+            }
+        }
+        for (name, content) in parts {
+            writer.start_file(name, options)?;
+            writer.write_all(content.as_bytes())?;
+        }
+        writer.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+/// Rows added, rewritten, and removed in one sheet.
+#[derive(Default)]
+struct SheetChanges {
+    inserted: usize,
+    updated: usize,
+    deleted: usize,
+    /// Header columns appended to the sheet.
+    added_columns: Vec<u32>,
+}
+
+impl SheetChanges {
+    fn is_empty(&self) -> bool {
+        self.inserted == 0
+            && self.updated == 0
+            && self.deleted == 0
+            && self.added_columns.is_empty()
+    }
+}
+
+/// Worksheet XML split around its rows.
+struct SheetXml {
+    /// Markup up to and including `<sheetData>`.
+    head: String,
+    rows: Vec<RowXml>,
+    /// Markup from `</sheetData>` on.
+    tail: String,
+}
+
+struct RowXml {
+    number: u32,
+    /// Original markup, written back unless the row changed.
+    markup: Option<String>,
+    /// Attributes of the `<row>` element other than `r` and `spans`.
+    attributes: String,
+    cells: Vec<CellXml>,
+}
+
+struct CellXml {
+    column: u32,
+    markup: String,
+    /// Raw `s` attribute, the cell's style index.
+    style: Option<String>,
+    text: String,
+}
+
+impl SheetXml {
+    fn new(head: String, tail: String) -> Self {
+        Self {
+            head,
+            rows: Vec::new(),
+            tail,
+        }
+    }
+
+    fn parse(xml: &str, shared_strings: &[String]) -> Result<Self> {
+        let mut reader = Reader::from_str(xml);
+        loop {
+            let start = position(&reader);
+            match reader.read_event().map_err(xml_error)? {
+                Event::Start(element) if element.local_name().as_ref() == b"sheetData" => {
+                    let head = xml[..position(&reader)].to_string();
+                    let (rows, tail_start) = parse_rows(&mut reader, xml, shared_strings)?;
+                    return Ok(Self {
+                        head,
+                        rows,
+                        tail: xml[tail_start..].to_string(),
+                    });
+                }
+                Event::Empty(element) if element.local_name().as_ref() == b"sheetData" => {
+                    return Ok(Self::new(
+                        format!("{}<sheetData>", &xml[..start]),
+                        format!("</sheetData>{}", &xml[position(&reader)..]),
+                    ));
+                }
+                Event::Eof => {
+                    return Err(ToolError::InvalidWorkbook(
+                        "worksheet has no sheetData element".to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Index of the header row, the first row holding any cell unless that
+    /// only labels bands of columns above a header, leaving its first
+    /// column empty.
+    fn header_index(&self) -> Option<usize> {
+        let first = self.rows.iter().position(|row| !row.cells.is_empty())?;
+        let bands = self.rows[first].text(0).is_empty()
+            && self
+                .rows
+                .get(first + 1)
+                .is_some_and(|row| !row.text(0).is_empty());
+        Some(first + usize::from(bands))
+    }
+
+    fn header_texts(&self) -> Vec<String> {
+        let Some(header) = self.header_index() else {
+            return Vec::new();
+        };
+        let row = &self.rows[header];
+        let last = row.cells.iter().map(|cell| cell.column).max().unwrap_or(0);
+        (0..=last)
+            .map(|column| row.text(column).to_string())
+            .collect()
+    }
+
+    fn last_row(&self) -> u32 {
+        self.rows.iter().map(|row| row.number).max().unwrap_or(1)
+    }
+
+    /// Brings the rows of the sheet in line with `table` and clears the
+    /// `retired` columns; `datatypes` gives the table columns written as
+    /// typed cells, as for
+    /// [`write_workbook`](crate::aideon::tools::io::excel_write::write_workbook).
+    fn apply(
+        &mut self,
+        table: &SheetTable,
+        datatypes: &[Option<&str>],
+        retired: &[&str],
+    ) -> SheetChanges {
+        let mut changes = SheetChanges::default();
+        let header = match self.header_index() {
+            Some(header) => header,
+            None => match self.rows.iter().position(|row| row.number == 1) {
+                Some(header) => header,
+                None => {
+                    self.rows.insert(0, RowXml::new(1));
+                    0
+                }
+            },
+        };
+
+        let mut next_column = self.rows[header]
+            .cells
+            .iter()
+            .map(|cell| cell.column + 1)
+            .max()
+            .unwrap_or(0);
+        let mut positions = Vec::with_capacity(table.columns.len());
+        for name in &table.columns {
+            let header_row = &mut self.rows[header];
+            let column = match header_row.column_of(name) {
+                Some(column) => column,
+                None => {
+                    let column = next_column;
+                    next_column += 1;
+                    header_row.set_cells([(column, CellValue::Text(name.into()))]);
+                    changes.added_columns.push(column);
+                    column
+                }
+            };
+            positions.push(column);
+        }
+        let retired: Vec<u32> = retired
+            .iter()
+            .filter_map(|name| self.rows[header].column_of(name))
+            .collect();
+
+        let key_width = if table
+            .columns
+            .starts_with(&["id".to_string(), "graph".to_string()])
+        {
+            2
+        } else {
+            table.columns.len()
+        };
+        let key_positions = &positions[..key_width];
+        let mut existing: HashMap<Vec<String>, VecDeque<usize>> = HashMap::new();
+        for (index, row) in self.rows.iter().enumerate().skip(header + 1) {
+            let key: Vec<String> = key_positions
+                .iter()
+                .map(|column| row.text(*column).to_string())
+                .collect();
+            if key.iter().all(String::is_empty) {
+                continue;
+            }
+            existing.entry(key).or_default().push_back(index);
+        }
+
+        let mut inserts = Vec::new();
+        for cells in &table.rows {
+            let key: Vec<String> = (0..key_width)
+                .map(|index| cells.get(index).cloned().unwrap_or_default())
+                .collect();
+            let values = positions
+                .iter()
+                .zip(
+                    cells
+                        .iter()
+                        .map(String::as_str)
+                        .chain(std::iter::repeat("")),
+                )
+                .zip(datatypes.iter().copied().chain(std::iter::repeat(None)))
+                .map(|((column, text), datatype)| (*column, cell_value(text, datatype)))
+                .chain(
+                    retired
+                        .iter()
+                        .map(|column| (*column, CellValue::Text("".into()))),
+                );
+            match existing.get_mut(&key).and_then(VecDeque::pop_front) {
+                Some(index) => {
+                    if self.rows[index].set_cells(values) {
+                        changes.updated += 1;
+                    }
+                }
+                None => inserts.push(values.collect::<Vec<_>>()),
+            }
+        }
+
+        let deleted: HashSet<usize> = existing.into_values().flatten().collect();
+        let mut free: Vec<u32> = deleted
+            .iter()
+            .map(|index| self.rows[*index].number)
+            .collect();
+        free.sort_unstable();
+        let mut free = VecDeque::from(free);
+        changes.deleted = deleted.len();
+        let mut index = 0;
+        self.rows.retain(|_| {
+            index += 1;
+            !deleted.contains(&(index - 1))
+        });
+
+        let mut next_row = self.last_row() + 1;
+        changes.inserted = inserts.len();
+        for values in inserts {
+            let number = free.pop_front().unwrap_or_else(|| {
+                next_row += 1;
+                next_row - 1
+            });
+            let mut row = RowXml::new(number);
+            row.set_cells(values);
+            self.rows.push(row);
+        }
+        self.rows.sort_by_key(|row| row.number);
+        changes
+    }
+
+    fn render(&self) -> Result<String> {
+        let mut xml = String::with_capacity(self.head.len() + self.tail.len());
+        xml.push_str(&self.head);
+        for row in &self.rows {
+            row.render(&mut xml);
+        }
+        xml.push_str(&self.tail);
+
+        let cells = self
+            .rows
+            .iter()
+            .flat_map(|row| row.cells.iter().map(move |cell| (cell.column, row.number)));
+        let dimension = match cells.clone().next() {
+            Some(_) => {
+                let (first_column, last_column) = cells
+                    .clone()
+                    .fold((u32::MAX, 0), |(min, max), (column, _)| {
+                        (min.min(column), max.max(column))
+                    });
+                let (first_row, last_row) = cells.fold((u32::MAX, 0), |(min, max), (_, row)| {
+                    (min.min(row), max.max(row))
+                });
+                format!(
+                    "{}:{}",
+                    cell_reference(first_column, first_row),
+                    cell_reference(last_column, last_row)
+                )
+            }
+            None => "A1".to_string(),
+        };
+        set_attribute(&xml, "dimension", "ref", &dimension)
+    }
+}
+
+impl RowXml {
+    fn new(number: u32) -> Self {
+        Self {
+            number,
+            markup: None,
+            attributes: String::new(),
+            cells: Vec::new(),
+        }
+    }
+
+    /// Returns the text of the cell in `column`, empty when there is none.
+    fn text(&self, column: u32) -> &str {
+        self.cells
+            .iter()
+            .find(|cell| cell.column == column)
+            .map_or("", |cell| cell.text.as_str())
+    }
+
+    /// Returns the column of the first cell whose text is `text`.
+    fn column_of(&self, text: &str) -> Option<u32> {
+        self.cells
+            .iter()
+            .find(|cell| cell.text == text)
+            .map(|cell| cell.column)
+    }
+
+    /// Sets the given cells, keeping their style, and returns whether any of
+    /// them changed. Cells already reading as the new value are kept, so a
+    /// number typed as text is not rewritten, nor is a date cell holding the
+    /// new date.
+    fn set_cells<'a>(&mut self, values: impl IntoIterator<Item = (u32, CellValue<'a>)>) -> bool {
+        let mut changed = false;
+        for (column, value) in values {
+            let text = value.text();
+            match self.cells.binary_search_by_key(&column, |cell| cell.column) {
+                Ok(index)
+                    if self.cells[index].text == text
+                        || value
+                            .serial()
+                            .is_some_and(|serial| self.cells[index].text == serial.to_string()) => {
+                }
+                Ok(index) => {
+                    let style = self.cells[index].style.take();
+                    if text.is_empty() && style.is_none() {
+                        self.cells.remove(index);
+                    } else {
+                        self.cells[index] = CellXml::new(column, self.number, &value, style);
+                    }
+                    changed = true;
+                }
+                Err(_) if text.is_empty() => {}
+                Err(index) => {
+                    self.cells
+                        .insert(index, CellXml::new(column, self.number, &value, None));
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            self.markup = None;
+        }
+        changed
+    }
+
+    fn render(&self, xml: &mut String) {
+        if let Some(markup) = &self.markup {
+            xml.push_str(markup);
+            return;
+        }
+        xml.push_str(&format!("<row r=\"{}\"{}>", self.number, self.attributes));
+        for cell in &self.cells {
+            xml.push_str(&cell.markup);
+        }
+        xml.push_str("</row>");
+    }
+}
+
+impl CellXml {
+    /// Creates a cell holding `value`, with text as an inline string. Dates
+    /// are only written as numbers when the cell keeps a style, which the
+    /// user may have given a date format; otherwise they are written as the
+    /// text a date cell reads as.
+    fn new(column: u32, row: u32, value: &CellValue, style: Option<String>) -> Self {
+        let reference = cell_reference(column, row);
+        let style_attribute = style
+            .as_ref()
+            .map(|style| format!(" s=\"{style}\""))
+            .unwrap_or_default();
+        let serial = value.serial().filter(|_| style.is_some());
+        let markup = match value {
+            CellValue::Number(number) => {
+                format!("<c r=\"{reference}\"{style_attribute}><v>{number}</v></c>")
+            }
+            CellValue::Date(_) | CellValue::DateTime(_) => match serial {
+                Some(serial) => {
+                    format!("<c r=\"{reference}\"{style_attribute}><v>{serial}</v></c>")
+                }
+                None => format!(
+                    "<c r=\"{reference}\"{style_attribute} t=\"inlineStr\"><is>\
+                     <t xml:space=\"preserve\">{}</t></is></c>",
+                    escape_cell_text(&value.text())
+                ),
+            },
+            CellValue::Boolean(flag) => format!(
+                "<c r=\"{reference}\"{style_attribute} t=\"b\"><v>{}</v></c>",
+                u8::from(*flag)
+            ),
+            CellValue::Text(text) if text.is_empty() => {
+                format!("<c r=\"{reference}\"{style_attribute}/>")
+            }
+            CellValue::Text(text) => format!(
+                "<c r=\"{reference}\"{style_attribute} t=\"inlineStr\"><is>\
+                 <t xml:space=\"preserve\">{}</t></is></c>",
+                escape_cell_text(text)
+            ),
+        };
+        Self {
+            column,
+            markup,
+            style,
+            text: serial.map_or_else(|| value.text().into_owned(), |serial| serial.to_string()),
+        }
+    }
+}
+
+/// Reads the rows of `sheetData`, returning them with the position of
+/// `</sheetData>`.
+fn parse_rows(
+    reader: &mut Reader<&[u8]>,
+    xml: &str,
+    shared_strings: &[String],
+) -> Result<(Vec<RowXml>, usize)> {
+    let mut rows: Vec<RowXml> = Vec::new();
+    loop {
+        let start = position(reader);
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) if element.local_name().as_ref() == b"row" => {
+                let mut row = row_start(reader, &element, &rows)?;
+                row.cells = parse_cells(reader, xml, row.number, shared_strings)?;
+                row.markup = Some(xml[start..position(reader)].to_string());
+                rows.push(row);
+            }
+            Event::Empty(element) if element.local_name().as_ref() == b"row" => {
+                let mut row = row_start(reader, &element, &rows)?;
+                row.markup = Some(xml[start..position(reader)].to_string());
+                rows.push(row);
+            }
+            Event::End(element) if element.local_name().as_ref() == b"sheetData" => {
+                return Ok((rows, start));
+            }
+            Event::Eof => {
+                return Err(ToolError::InvalidWorkbook(
+                    "worksheet sheetData is not closed".to_string(),
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn row_start(reader: &Reader<&[u8]>, element: &BytesStart, rows: &[RowXml]) -> Result<RowXml> {
+    let mut row = RowXml::new(rows.last().map_or(1, |row| row.number + 1));
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(xml_error)?;
+        match attribute.key.as_ref() {
+            b"r" => {
+                let value = attribute
+                    .decode_and_unescape_value(reader.decoder())
+                    .map_err(xml_error)?;
+                row.number = value.parse().map_err(|_| {
+                    ToolError::InvalidWorkbook(format!("invalid row number '{value}'"))
+                })?;
+            }
+            b"spans" => {}
+            key => row.attributes.push_str(&format!(
+                " {}=\"{}\"",
+                String::from_utf8_lossy(key),
+                String::from_utf8_lossy(&attribute.value)
+            )),
+        }
+    }
+    Ok(row)
+}
+
+fn parse_cells(
+    reader: &mut Reader<&[u8]>,
+    xml: &str,
+    row: u32,
+    shared_strings: &[String],
+) -> Result<Vec<CellXml>> {
+    let mut cells: Vec<CellXml> = Vec::new();
+    loop {
+        let start = position(reader);
+        let (element, empty) = match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) if element.local_name().as_ref() == b"c" => (element, false),
+            Event::Empty(element) if element.local_name().as_ref() == b"c" => (element, true),
+            Event::End(element) if element.local_name().as_ref() == b"row" => break,
+            Event::Eof => {
+                return Err(ToolError::InvalidWorkbook(format!(
+                    "row {row} is not closed"
+                )));
+            }
+            _ => continue,
+        };
+
+        let mut column = cells.last().map_or(0, |cell| cell.column + 1);
+        let mut kind = None;
+        let mut style = None;
+        for attribute in element.attributes() {
+            let attribute = attribute.map_err(xml_error)?;
+            let value = attribute
+                .decode_and_unescape_value(reader.decoder())
+                .map_err(xml_error)?;
+            match attribute.key.as_ref() {
+                b"r" => {
+                    column = parse_reference(&value)
+                        .map(|(column, _)| column)
+                        .ok_or_else(|| {
+                            ToolError::InvalidWorkbook(format!("invalid cell reference '{value}'"))
+                        })?;
+                }
+                b"t" => kind = Some(value.into_owned()),
+                b"s" => style = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+
+        let mut value = String::new();
+        let mut inline = String::new();
+        if !empty {
+            loop {
+                match reader.read_event().map_err(xml_error)? {
+                    Event::Start(child) if child.local_name().as_ref() == b"v" => {
+                        value = element_text(reader, &child)?;
+                    }
+                    Event::Start(child) if child.local_name().as_ref() == b"is" => {
+                        inline = string_item(reader, &child)?;
+                    }
+                    Event::Start(child) => {
+                        reader.read_to_end(child.name()).map_err(xml_error)?;
+                    }
+                    Event::End(child) if child.local_name().as_ref() == b"c" => break,
+                    Event::Eof => {
+                        return Err(ToolError::InvalidWorkbook(format!(
+                            "cell in row {row} is not closed"
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let text = match kind.as_deref() {
+            Some("s") => value
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| shared_strings.get(index))
+                .cloned()
+                .unwrap_or_default(),
+            Some("inlineStr") => inline,
+            Some("b") => (value.trim() == "1").to_string(),
+            Some(_) => value,
+            None => match value.trim().parse::<f64>() {
+                Ok(number) => number.to_string(),
+                Err(_) => value,
+            },
+        };
+        cells.push(CellXml {
+            column,
+            markup: xml[start..position(reader)].to_string(),
+            style,
+            text,
+        });
+    }
+    cells.sort_by_key(|cell| cell.column);
+    Ok(cells)
+}
+
+fn parse_shared_strings(xml: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut strings = Vec::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) if element.local_name().as_ref() == b"si" => {
+                strings.push(string_item(&mut reader, &element)?);
+            }
+            Event::Empty(element) if element.local_name().as_ref() == b"si" => {
+                strings.push(String::new());
+            }
+            Event::Eof => return Ok(strings),
+            _ => {}
+        }
+    }
+}
+
+/// Reads the text of a string item (`<si>` or `<is>`), joining its rich text
+/// runs and leaving out phonetic hints.
+fn string_item(reader: &mut Reader<&[u8]>, element: &BytesStart) -> Result<String> {
+    let mut text = String::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(child) if child.local_name().as_ref() == b"t" => {
+                text.push_str(&element_text(reader, &child)?);
+            }
+            Event::Start(child) if child.local_name().as_ref() == b"rPh" => {
+                reader.read_to_end(child.name()).map_err(xml_error)?;
+            }
+            Event::End(end) if end.name() == element.name() => return Ok(text),
+            Event::Eof => {
+                return Err(ToolError::InvalidWorkbook(
+                    "string item is not closed".to_string(),
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reads the unescaped text content of `element`, whose start tag was just
+/// read.
+fn element_text(reader: &mut Reader<&[u8]>, element: &BytesStart) -> Result<String> {
+    let raw = reader.read_text(element.name()).map_err(xml_error)?;
+    Ok(unescape(&raw).map_err(xml_error)?.into_owned())
+}
+
+/// Widens and lengthens an Excel table whose header is the sheet's header
+/// row so it covers the sheet's rows and `added_columns`. Returns `None` when
+/// the table already does or starts elsewhere.
+fn extend_table(xml: &str, sheet: &SheetXml, added_columns: &[u32]) -> Result<Option<String>> {
+    let Some(table) = first_tag(xml, "table")? else {
+        return Ok(None);
+    };
+    let Some(((first_column, first_row), (last_column, last_row))) =
+        table.attribute("ref").and_then(parse_range)
+    else {
+        return Ok(None);
+    };
+    let Some(header) = sheet.header_index() else {
+        return Ok(None);
+    };
+    if first_row != sheet.rows[header].number {
+        return Ok(None);
+    }
+
+    let new_last_row = last_row.max(sheet.last_row());
+    let new_last_column = added_columns.iter().copied().fold(last_column, u32::max);
+    if new_last_row == last_row && new_last_column == last_column {
+        return Ok(None);
+    }
+    let reference = format!(
+        "{}:{}",
+        cell_reference(first_column, first_row),
+        cell_reference(new_last_column, new_last_row)
+    );
+    let mut xml = set_attribute(xml, "table", "ref", &reference)?;
+    if first_tag(&xml, "autoFilter")?.is_some() {
+        xml = set_attribute(&xml, "autoFilter", "ref", &reference)?;
+    }
+
+    if new_last_column > last_column {
+        let table_columns = tags(&xml, "tableColumn")?;
+        let next_id = table_columns
+            .iter()
+            .filter_map(|tag| tag.attribute("id")?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let mut markup = String::new();
+        for (id, column) in (next_id..).zip(last_column + 1..=new_last_column) {
+            let header_text = sheet.rows[header].text(column);
+            let name = if header_text.is_empty() {
+                format!("Column{}", column - first_column + 1)
+            } else {
+                header_text.to_string()
+            };
+            markup.push_str(&format!(
+                "<tableColumn id=\"{id}\" name=\"{}\"/>",
+                escape(name.as_str())
+            ));
+        }
+        let count = table_columns.len() as u32 + new_last_column - last_column;
+        xml = insert_before_close(&xml, "tableColumns", &markup)?;
+        xml = set_attribute(&xml, "tableColumns", "count", &count.to_string())?;
+    }
+    Ok(Some(xml))
+}
+
+/// Start tag of an XML element.
+struct Tag {
+    /// Span of the tag in the document.
+    span: Range<usize>,
+    empty: bool,
+    /// Qualified name of the element.
+    name: String,
+    /// Attribute names and unescaped values in document order.
+    attributes: Vec<(String, String)>,
+}
+
+impl Tag {
+    /// Reads the tag of `element`, which `reader` just read from `start` on.
+    fn read(
+        reader: &Reader<&[u8]>,
+        element: &BytesStart,
+        start: usize,
+        empty: bool,
+    ) -> Result<Self> {
+        let mut attributes = Vec::new();
+        for attribute in element.attributes() {
+            let attribute = attribute.map_err(xml_error)?;
+            let value = attribute
+                .decode_and_unescape_value(reader.decoder())
+                .map_err(xml_error)?;
+            attributes.push((
+                String::from_utf8_lossy(attribute.key.as_ref()).into_owned(),
+                value.into_owned(),
+            ));
+        }
+        Ok(Self {
+            span: start..position(reader),
+            empty,
+            name: String::from_utf8_lossy(element.name().as_ref()).into_owned(),
+            attributes,
+        })
+    }
+
+    /// Returns the value of the attribute with local name `name`.
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| local_name(key) == name && !key.starts_with("xmlns"))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// Returns the start tags of every element with local name `name`.
+fn tags(xml: &str, name: &str) -> Result<Vec<Tag>> {
+    let mut reader = Reader::from_str(xml);
+    let mut found = Vec::new();
+    loop {
+        let start = position(&reader);
+        let (element, empty) = match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) => (element, false),
+            Event::Empty(element) => (element, true),
+            Event::Eof => return Ok(found),
+            _ => continue,
+        };
+        if element.local_name().as_ref() == name.as_bytes() {
+            found.push(Tag::read(&reader, &element, start, empty)?);
+        }
+    }
+}
+
+/// Sets attribute `attribute` on the first `element` start tag, adding it
+/// when missing. Documents without such an element are returned unchanged.
+fn set_attribute(xml: &str, element: &str, attribute: &str, value: &str) -> Result<String> {
+    let Some(mut tag) = first_tag(xml, element)? else {
+        return Ok(xml.to_string());
+    };
+    match tag.attributes.iter_mut().find(|(key, _)| key == attribute) {
+        Some((_, existing)) => *existing = value.to_string(),
+        None => tag
+            .attributes
+            .push((attribute.to_string(), value.to_string())),
+    }
+    let mut markup = format!("<{}", tag.name);
+    for (key, value) in &tag.attributes {
+        markup.push_str(&format!(" {key}=\"{}\"", escape(value.as_str())));
+    }
+    markup.push_str(if tag.empty { "/>" } else { ">" });
+    Ok(format!(
+        "{}{markup}{}",
+        &xml[..tag.span.start],
+        &xml[tag.span.end..]
+    ))
+}
+
+/// Returns the first start tag of an element with local name `name`.
+fn first_tag(xml: &str, name: &str) -> Result<Option<Tag>> {
+    // Scanning stops at the first match, which keeps lookups near the top of
+    // large worksheets cheap.
+    let mut reader = Reader::from_str(xml);
+    loop {
+        let start = position(&reader);
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) if element.local_name().as_ref() == name.as_bytes() => {
+                return Tag::read(&reader, &element, start, false).map(Some);
+            }
+            Event::Empty(element) if element.local_name().as_ref() == name.as_bytes() => {
+                return Tag::read(&reader, &element, start, true).map(Some);
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Inserts `markup` before the end tag of the first `element`.
+fn insert_before_close(xml: &str, element: &str, markup: &str) -> Result<String> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        let start = position(&reader);
+        match reader.read_event().map_err(xml_error)? {
+            Event::End(end) if end.local_name().as_ref() == element.as_bytes() => {
+                return Ok(format!("{}{markup}{}", &xml[..start], &xml[start..]));
+            }
+            Event::Empty(empty) if empty.local_name().as_ref() == element.as_bytes() => {
+                let open = &xml[start..position(&reader)];
+                let open = open.trim_end_matches("/>").trim_end();
+                let name = String::from_utf8_lossy(empty.name().as_ref()).into_owned();
+                return Ok(format!(
+                    "{}{open}>{markup}</{name}>{}",
+                    &xml[..start],
+                    &xml[position(&reader)..]
+                ));
+            }
+            Event::Eof => {
+                return Err(ToolError::InvalidWorkbook(format!(
+                    "workbook part has no {element} element"
+                )));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the prefix bound to the relationships namespace on the root of
+/// `workbook.xml`.
+fn relationships_prefix(workbook: &str) -> Result<String> {
+    first_tag(workbook, "workbook")?
+        .and_then(|tag| {
+            tag.attributes.into_iter().find_map(|(key, value)| {
+                let prefix = key.strip_prefix("xmlns:")?;
+                (value == RELATIONSHIPS_NS).then(|| prefix.to_string())
+            })
+        })
+        .ok_or_else(|| {
+            ToolError::InvalidWorkbook(
+                "workbook does not declare the relationships namespace".to_string(),
+            )
+        })
+}
+
+/// Name of the part holding the relationships of `part`.
+fn relationships_part(part: &str) -> String {
+    match part.rsplit_once('/') {
+        Some((directory, file)) => format!("{directory}/_rels/{file}.rels"),
+        None => format!("_rels/{part}.rels"),
+    }
+}
+
+/// Resolves a relationship target against the part the relationship belongs
+/// to.
+fn resolve_target(part: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut segments: Vec<&str> = match part.rsplit_once('/') {
+        Some((directory, _)) => directory.split('/').collect(),
+        None => Vec::new(),
+    };
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn file_name(part: &str) -> &str {
+    part.rsplit_once('/').map_or(part, |(_, file)| file)
+}
+
+/// Formats a zero-based column and one-based row as an `A1` reference.
+fn cell_reference(column: u32, row: u32) -> String {
+    let mut letters = Vec::new();
+    let mut remaining = column + 1;
+    while remaining > 0 {
+        letters.push(char::from(b'A' + ((remaining - 1) % 26) as u8));
+        remaining = (remaining - 1) / 26;
+    }
+    let column: String = letters.iter().rev().collect();
+    format!("{column}{row}")
+}
+
+/// Parses an `A1` reference into a zero-based column and one-based row.
+fn parse_reference(reference: &str) -> Option<(u32, u32)> {
+    let split = reference.find(|ch: char| ch.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    if letters.is_empty() {
+        return None;
+    }
+    let mut column = 0u32;
+    for letter in letters.bytes() {
+        if !letter.is_ascii_uppercase() {
+            return None;
+        }
+        column = column.checked_mul(26)? + u32::from(letter - b'A' + 1);
+    }
+    Some((column - 1, digits.parse().ok()?))
+}
+
+/// Parses an `A1:D5` range, or a single reference, into its corners.
+fn parse_range(range: &str) -> Option<((u32, u32), (u32, u32))> {
+    match range.split_once(':') {
+        Some((first, last)) => Some((parse_reference(first)?, parse_reference(last)?)),
+        None => {
+            let corner = parse_reference(range)?;
+            Some((corner, corner))
+        }
+    }
+}
+
+/// Escapes cell text for XML. Control characters, which XML cannot hold,
+/// are written in Excel's `_xHHHH_` notation.
+fn escape_cell_text(text: &str) -> String {
+    let escaped = escape(text);
+    if !escaped
+        .chars()
+        .any(|ch| ch.is_control() && !matches!(ch, '\t' | '\n' | '\r'))
+    {
+        return escaped.into_owned();
+    }
+    escaped
+        .chars()
+        .map(|ch| match ch {
+            '\t' | '\n' | '\r' => ch.to_string(),
+            ch if ch.is_control() => format!("_x{:04X}_", u32::from(ch)),
+            ch => ch.to_string(),
+        })
+        .collect()
+}
+
+fn position(reader: &Reader<&[u8]>) -> usize {
+    reader.buffer_position() as usize
+}
+
+fn xml_error(err: impl std::fmt::Display) -> ToolError {
+    ToolError::InvalidWorkbook(format!("malformed workbook XML: {err}"))
+}
@@ -1,39 +1,763 @@
+//! Excel workbook output.
+//!
+//! Cells are written as text, except for the cells of `scalar` columns
+//! declared in `Metadata`, which keep their Excel type: JSON numbers and
+//! booleans become numeric and boolean cells, JSON strings that read back
+//! unchanged without their quotes are written as plain text, and dates in
+//! `xsd:date` and `xsd:dateTime` columns become date cells. Cells of
+//! `xsd:boolean` columns are formatted as checkboxes where Excel supports
+//! them, and show as `TRUE` or `FALSE` elsewhere. Readers turn
+//! these cells back into text the column reads the same way, so the encoding
+//! is lossless.
+//!
+//! Columns holding node ids, the `ref` columns declared in `Metadata` and
+//! the parent and target columns of child sheets, offer the ids of the
+//! `Entities` sheet as a dropdown and warn about any other value, so editors
+//! do not leave dangling references by accident; `xsd:boolean` columns only
+//! accept `TRUE` and `FALSE`.
+//!
+//! Reference cells link to the row of the node they name on its type sheet,
+//! and the ids of the `Entities` sheet to the row of their node on the sheet
+//! of the type they list, so a flattened model can be navigated by
+//! clicking. Chunked exports, whose rows arrive in no known order, are
+//! written without links.
+//!
+//! The value cells of the `Dataset` sheet get workbook-level names after
+//! their key, such as `dataset_title`, so formulas and templates can refer
+//! to the dataset's metadata wherever its row ends up.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::{Seek, Write};
 use std::path::Path;
 
-use rust_xlsxwriter::Workbook;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use rust_xlsxwriter::{
+    DataValidation, DataValidationErrorStyle, Format, FormatAlign, Formula, Table, TableColumn,
+    Url, Workbook, Worksheet,
+};
+use serde_json::Value;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::flatten::{
+    ColumnKind, DATASET_SHEET, ENTITIES_SHEET, METADATA_SHEET, SheetTable, WorkbookData,
+    WorkbookLayout, needs_text_prefix,
+};
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::model::{ScalarValue, XSD_BOOLEAN, XSD_DATE, XSD_DATE_TIME, XSD_DOUBLE};
+
+/// Datatype of each `scalar` column, empty when it has none, keyed by sheet
+/// and then header.
+pub(crate) type LiteralColumns = HashMap<String, HashMap<String, String>>;
+
+/// Headers of the columns holding node ids, keyed by sheet.
+type ReferenceColumns = HashMap<String, HashSet<String>>;
+
+/// Label, first column, and last column of each band of columns, keyed by
+/// sheet.
+pub(crate) type ColumnBands = HashMap<String, Vec<[String; 3]>>;
+
+/// Value stored in an Excel cell.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CellValue<'a> {
+    Number(f64),
+    Boolean(bool),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    Text(Cow<'a, str>),
+}
+
+impl CellValue<'_> {
+    /// Text a reader sees for the cell.
+    pub(crate) fn text(&self) -> Cow<'_, str> {
+        match self {
+            CellValue::Number(number) => Cow::Owned(number.to_string()),
+            CellValue::Boolean(value) => Cow::Borrowed(if *value { "true" } else { "false" }),
+            CellValue::Date(date) => Cow::Owned(date.format("%Y-%m-%d").to_string()),
+            CellValue::DateTime(datetime) => {
+                Cow::Owned(datetime.format("%Y-%m-%dT%H:%M:%S").to_string())
+            }
+            CellValue::Text(text) => Cow::Borrowed(text),
+        }
+    }
+
+    /// Excel serial number of date cells.
+    pub(crate) fn serial(&self) -> Option<f64> {
+        match self {
+            CellValue::Date(date) => excel_serial(date.and_time(NaiveTime::MIN)),
+            CellValue::DateTime(datetime) => excel_serial(*datetime),
+            _ => None,
+        }
+    }
+}
+
+/// Returns how cell `text` is stored. `datatype` is `None` for cells outside
+/// `scalar` columns, and the column's datatype, possibly empty, otherwise.
+pub(crate) fn cell_value<'a>(text: &'a str, datatype: Option<&str>) -> CellValue<'a> {
+    let Some(datatype) = datatype else {
+        return CellValue::Text(Cow::Borrowed(text));
+    };
+    match text {
+        "true" => return CellValue::Boolean(true),
+        "false" => return CellValue::Boolean(false),
+        _ => {}
+    }
+    if text.starts_with('"') {
+        if let Ok(unquoted) = serde_json::from_str::<String>(text)
+            && !needs_text_prefix(&unquoted)
+        {
+            return CellValue::Text(Cow::Owned(unquoted));
+        }
+    } else if text.starts_with('{') {
+        if (datatype == XSD_DATE || datatype == XSD_DATE_TIME)
+            && let Some(value) = date_cell(text, datatype)
+        {
+            return value;
+        }
+    } else if let Ok(number) = serde_json::from_str::<serde_json::Number>(text)
+        && let Some(number) = number.as_f64().filter(|number| number.is_finite())
+        // Excel stores every number as a double: integers beyond its
+        // precision, and doubles such as `30.0` that would read back as
+        // integers outside an `xsd:double` column, stay text.
+        && (number.to_string() == text || datatype == XSD_DOUBLE)
+    {
+        return CellValue::Number(number);
+    }
+    CellValue::Text(Cow::Borrowed(text))
+}
+
+/// Reads a value object of the column's date `datatype` as a date cell.
+/// Dates Excel cannot show, and date-times with fractional seconds, stay
+/// text.
+fn date_cell(text: &str, datatype: &str) -> Option<CellValue<'static>> {
+    let Ok(Value::Object(map)) = serde_json::from_str::<Value>(text) else {
+        return None;
+    };
+    if map.len() != 2 || map.get("@type").and_then(Value::as_str) != Some(datatype) {
+        return None;
+    }
+    let value = match ScalarValue::from_lexical(map.get("@value")?.as_str()?, datatype) {
+        ScalarValue::Date(date) => CellValue::Date(date),
+        ScalarValue::DateTime(datetime) if datetime.nanosecond() == 0 => {
+            CellValue::DateTime(datetime)
+        }
+        _ => return None,
+    };
+    value.serial().map(|_| value)
+}
+
+/// Returns the Excel serial number of `datetime`: days since 1899-12-30,
+/// with the time of day as fraction. Dates before March 1900, where Excel
+/// counts a nonexistent leap day, and after 9999 have none.
+pub(crate) fn excel_serial(datetime: NaiveDateTime) -> Option<f64> {
+    let first = NaiveDate::from_ymd_opt(1900, 3, 1)?.and_time(NaiveTime::MIN);
+    let last = NaiveDate::from_ymd_opt(10000, 1, 1)?.and_time(NaiveTime::MIN);
+    if datetime < first || datetime >= last {
+        return None;
+    }
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_time(NaiveTime::MIN);
+    let elapsed = datetime - epoch;
+    Some(elapsed.num_days() as f64 + f64::from(datetime.num_seconds_from_midnight()) / 86_400.0)
+}
+
+/// Returns the datatype of each `scalar` column as declared by the `column`
+/// rows of `metadata`.
+pub(crate) fn literal_columns(metadata: &SheetTable) -> LiteralColumns {
+    let position = |name: &str| metadata.columns.iter().position(|column| column == name);
+    let mut columns = LiteralColumns::new();
+    let (Some(kind), Some(sheet), Some(column), Some(value_kind)) = (
+        position("kind"),
+        position("sheet"),
+        position("column"),
+        position("value_kind"),
+    ) else {
+        return columns;
+    };
+    let datatype = position("datatype");
+    for row in &metadata.rows {
+        let cell = |index: usize| row.get(index).map(String::as_str).unwrap_or_default();
+        if cell(kind) == "column" && cell(value_kind) == ColumnKind::Scalar.as_str() {
+            columns.entry(cell(sheet).to_string()).or_default().insert(
+                cell(column).to_string(),
+                datatype.map(cell).unwrap_or_default().to_string(),
+            );
+        }
+    }
+    columns
+}
+
+/// Returns the bands of columns declared by the `group` rows of `metadata`.
+pub(crate) fn column_bands(metadata: &SheetTable) -> ColumnBands {
+    let position = |name: &str| metadata.columns.iter().position(|column| column == name);
+    let mut bands = ColumnBands::new();
+    let (Some(kind), Some(sheet), Some(column), Some(value_kind), Some(datatype)) = (
+        position("kind"),
+        position("sheet"),
+        position("column"),
+        position("value_kind"),
+        position("datatype"),
+    ) else {
+        return bands;
+    };
+    for row in &metadata.rows {
+        let cell = |index: usize| row.get(index).cloned().unwrap_or_default();
+        if cell(kind) == "group" {
+            bands.entry(cell(sheet)).or_default().push([
+                cell(column),
+                cell(value_kind),
+                cell(datatype),
+            ]);
+        }
+    }
+    bands
+}
+
+/// Headers of the columns holding node ids, keyed by sheet: the `ref`
+/// columns declared by `metadata` and the parent and target columns, first
+/// and third, of its child sheets.
+fn reference_columns(metadata: &SheetTable, sheets: &[(&str, &[String])]) -> ReferenceColumns {
+    let position = |name: &str| metadata.columns.iter().position(|column| column == name);
+    let mut references = ReferenceColumns::new();
+    let (Some(kind), Some(sheet), Some(column), Some(value_kind)) = (
+        position("kind"),
+        position("sheet"),
+        position("column"),
+        position("value_kind"),
+    ) else {
+        return references;
+    };
+    for row in &metadata.rows {
+        let cell = |index: usize| row.get(index).map(String::as_str).unwrap_or_default();
+        let headers: Vec<&str> = match cell(kind) {
+            "column" if cell(value_kind) == ColumnKind::Ref.as_str() => vec![cell(column)],
+            "child" => sheets
+                .iter()
+                .find(|(name, _)| *name == cell(sheet))
+                .map(|(_, columns)| {
+                    [0, 2]
+                        .iter()
+                        .filter_map(|index| columns.get(*index))
+                        .map(String::as_str)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => continue,
+        };
+        references
+            .entry(cell(sheet).to_string())
+            .or_default()
+            .extend(headers.into_iter().map(str::to_string));
+    }
+    references
+}
+
+/// Returns the type of each type sheet declared by `metadata`, by sheet.
+fn sheet_types(metadata: &SheetTable) -> HashMap<&str, &str> {
+    let position = |name: &str| metadata.columns.iter().position(|column| column == name);
+    let (Some(kind), Some(sheet), Some(type_name)) =
+        (position("kind"), position("sheet"), position("type"))
+    else {
+        return HashMap::new();
+    };
+    metadata
+        .rows
+        .iter()
+        .filter(|row| row.get(kind).is_some_and(|kind| kind == "type"))
+        .filter_map(|row| Some((row.get(sheet)?.as_str(), row.get(type_name)?.as_str())))
+        .collect()
+}
+
+/// Returns the datatype of each column of `table` holding single literals,
+/// and `None` for the other columns.
+pub(crate) fn literal_datatypes<'a>(
+    table: &SheetTable,
+    literals: &'a LiteralColumns,
+) -> Vec<Option<&'a str>> {
+    let declared = literals.get(&table.sheet_name);
+    table
+        .columns
+        .iter()
+        .map(|column| {
+            declared
+                .and_then(|declared| declared.get(column))
+                .map(String::as_str)
+        })
+        .collect()
+}
+
+/// Number formats of date cells, the format of boolean column cells, and
+/// that of band labels.
+struct CellFormats {
+    date: Format,
+    date_time: Format,
+    checkbox: Format,
+    band: Format,
+}
 
-use crate::aideon::tools::error::Result;
-use crate::aideon::tools::flatten::WorkbookData;
+impl CellFormats {
+    fn new() -> Self {
+        Self {
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            date_time: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+            checkbox: Format::new().set_checkbox(),
+            band: Format::new().set_bold().set_align(FormatAlign::Center),
+        }
+    }
+}
 
 /// Writes the provided workbook data to the given path.
 pub fn write_workbook(path: &Path, workbook: &WorkbookData) -> Result<()> {
+    let mut workbook = excel_workbook(workbook)?;
+    atomic::write_atomically(path, |partial| Ok(workbook.save(partial)?))
+}
+
+/// Writes `workbook` like [`write_workbook`], to `writer` instead of a file.
+pub fn write_workbook_to<W: Write + Seek + Send>(writer: W, workbook: &WorkbookData) -> Result<()> {
+    excel_workbook(workbook)?.save_to_writer(writer)?;
+    Ok(())
+}
+
+fn excel_workbook(workbook: &WorkbookData) -> Result<Workbook> {
     let mut workbook_writer = Workbook::new();
+    let metadata = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == METADATA_SHEET);
+    let literals = metadata.map(literal_columns).unwrap_or_default();
+    let bands = metadata.map(column_bands).unwrap_or_default();
+    let sheets: Vec<(&str, &[String])> = workbook
+        .tables
+        .iter()
+        .map(|table| (table.sheet_name.as_str(), table.columns.as_slice()))
+        .collect();
+    let references = metadata
+        .map(|metadata| reference_columns(metadata, &sheets))
+        .unwrap_or_default();
+    let entity_rows = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == ENTITIES_SHEET)
+        .map_or(0, |table| table.rows.len());
+    let node_rows = metadata
+        .map(|metadata| NodeRows::new(workbook, metadata, &bands))
+        .unwrap_or_default();
+    let formats = CellFormats::new();
 
     for table in &workbook.tables {
         let worksheet = workbook_writer.add_worksheet();
         worksheet.set_name(&table.sheet_name)?;
+        let sheet_bands = bands.get(&table.sheet_name).map(Vec::as_slice);
+        let first_row = write_header(
+            worksheet,
+            &table.columns,
+            table.rows.len(),
+            sheet_bands.unwrap_or_default(),
+            &formats,
+        )?;
+        add_validations(
+            worksheet,
+            &table.columns,
+            first_row,
+            table.rows.len(),
+            &SheetValidations {
+                references: references.get(&table.sheet_name),
+                literals: literals.get(&table.sheet_name),
+                entity_rows,
+            },
+        )?;
+
+        let datatypes = literal_datatypes(table, &literals);
+        for (row_idx, row) in table.rows.iter().enumerate() {
+            write_row(
+                worksheet,
+                first_row + row_idx as u32,
+                row,
+                &datatypes,
+                &formats,
+            )?;
+        }
+        link_rows(
+            worksheet,
+            table,
+            first_row,
+            references.get(&table.sheet_name),
+            &node_rows,
+            workbook,
+        )?;
+        if table.sheet_name == DATASET_SHEET {
+            define_dataset_names(&mut workbook_writer, table, first_row)?;
+        }
+    }
+    Ok(workbook_writer)
+}
+
+/// Most hyperlinks Excel keeps on a worksheet; cells past them stay plain.
+const MAX_SHEET_LINKS: usize = 65_530;
+
+/// Where each node has a row on the type sheets of a workbook, as the index
+/// of the sheet's table and the zero-based row.
+#[derive(Default)]
+struct NodeRows {
+    /// The first row of each id.
+    by_id: HashMap<String, (usize, u32)>,
+    /// The row of each id on the sheet of each type, by type, id, and graph.
+    by_type: HashMap<(String, String, String), (usize, u32)>,
+}
 
-        for (col_idx, header) in table.columns.iter().enumerate() {
-            worksheet.write_string(0, col_idx as u16, header)?;
+impl NodeRows {
+    fn new(workbook: &WorkbookData, metadata: &SheetTable, bands: &ColumnBands) -> Self {
+        let types = sheet_types(metadata);
+        let mut rows = Self::default();
+        for (index, table) in workbook.tables.iter().enumerate() {
+            let Some(type_name) = types.get(table.sheet_name.as_str()) else {
+                continue;
+            };
+            let position = |name: &str| table.columns.iter().position(|column| column == name);
+            let (Some(id), graph) = (position("id"), position("graph")) else {
+                continue;
+            };
+            let first_row = if bands.get(&table.sheet_name).is_some_and(|b| !b.is_empty()) {
+                2
+            } else {
+                1
+            };
+            for (row_idx, row) in table.rows.iter().enumerate() {
+                let cell = |index: usize| row.get(index).cloned().unwrap_or_default();
+                let at = (index, first_row + row_idx as u32);
+                rows.by_id.entry(cell(id)).or_insert(at);
+                let graph = graph.map(cell).unwrap_or_default();
+                rows.by_type
+                    .insert((type_name.to_string(), cell(id), graph), at);
+            }
         }
+        rows
+    }
+}
 
-        for (row_idx, row) in table.rows.iter().enumerate() {
-            for (col_idx, cell) in row.iter().enumerate() {
-                worksheet.write_string((row_idx + 1) as u32, col_idx as u16, cell)?;
+/// Turns the cells of `table` naming a node into hyperlinks to its row:
+/// those of `references` link to the node's first row, and the ids of the
+/// `Entities` sheet to its row on the sheet of the type listed beside it.
+fn link_rows(
+    worksheet: &mut Worksheet,
+    table: &SheetTable,
+    first_row: u32,
+    references: Option<&HashSet<String>>,
+    node_rows: &NodeRows,
+    workbook: &WorkbookData,
+) -> Result<()> {
+    let entities = table.sheet_name == ENTITIES_SHEET;
+    let columns: Vec<usize> = if entities {
+        vec![0]
+    } else {
+        table
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(_, header)| references.is_some_and(|references| references.contains(*header)))
+            .map(|(index, _)| index)
+            .collect()
+    };
+    let mut links = 0;
+    for (row_idx, row) in table.rows.iter().enumerate() {
+        for &col_idx in &columns {
+            let Some(cell) = row.get(col_idx).filter(|cell| !cell.is_empty()) else {
+                continue;
+            };
+            let target = if entities {
+                let key = |index: usize| row.get(index).cloned().unwrap_or_default();
+                node_rows
+                    .by_type
+                    .get(&(key(1), cell.clone(), key(2)))
+                    .or_else(|| node_rows.by_id.get(cell))
+            } else {
+                node_rows.by_id.get(cell)
+            };
+            let Some(&(sheet, target_row)) = target else {
+                continue;
+            };
+            if links == MAX_SHEET_LINKS {
+                return Ok(());
             }
+            links += 1;
+            let sheet_name = workbook.tables[sheet].sheet_name.replace('\'', "''");
+            let link = Url::new(format!("internal:'{sheet_name}'!A{}", target_row + 1));
+            worksheet.write_url_with_text(
+                first_row + row_idx as u32,
+                col_idx as u16,
+                link,
+                cell.as_str(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Names the value cell of each `Dataset` sheet row after its key, such as
+/// `dataset_title`, for rows from `first_row` on. Keys that cannot name a
+/// cell, such as predicate IRIs, and repeated keys after their first row
+/// are left unnamed.
+fn define_dataset_names(workbook: &mut Workbook, table: &SheetTable, first_row: u32) -> Result<()> {
+    let mut named = HashSet::new();
+    for (row_idx, row) in table.rows.iter().enumerate() {
+        let Some(key) = row.first() else {
+            continue;
+        };
+        if key.is_empty()
+            || !key.chars().all(|ch| ch.is_ascii_alphanumeric())
+            || !named.insert(key.to_ascii_lowercase())
+        {
+            continue;
+        }
+        let cell = first_row + row_idx as u32 + 1;
+        workbook.define_name(
+            format!("dataset_{key}"),
+            &format!("={DATASET_SHEET}!$B${cell}"),
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a workbook chunk by chunk using constant-memory worksheets.
+///
+/// The layout must come from a full pass over the data so that every sheet,
+/// column, and table range is known before the first row is written. Rows of
+/// each sheet are appended in the order the chunks arrive.
+pub struct StreamingWorkbookWriter {
+    workbook: Workbook,
+    /// Worksheet index and next free row per sheet name.
+    cursors: HashMap<String, (usize, u32)>,
+    literals: LiteralColumns,
+    formats: CellFormats,
+}
+
+impl StreamingWorkbookWriter {
+    /// Creates every sheet of `layout` and writes the headers and the
+    /// `Metadata`, `Context`, and `Dataset` sheets.
+    pub fn new(layout: &WorkbookLayout) -> Result<Self> {
+        let mut workbook = Workbook::new();
+        let mut cursors = HashMap::new();
+        let metadata = layout.metadata_table();
+        let bands = column_bands(&metadata);
+        let literals = literal_columns(&metadata);
+        let layout_sheets = layout.sheets();
+        let sheets: Vec<(&str, &[String])> = layout_sheets
+            .iter()
+            .map(|sheet| (sheet.sheet_name.as_str(), sheet.columns.as_slice()))
+            .collect();
+        let references = reference_columns(&metadata, &sheets);
+        let entity_rows = layout_sheets
+            .iter()
+            .find(|sheet| sheet.sheet_name == ENTITIES_SHEET)
+            .map_or(0, |sheet| sheet.row_count);
+        let formats = CellFormats::new();
+
+        for (index, sheet) in layout_sheets.iter().enumerate() {
+            let worksheet = workbook.add_worksheet_with_constant_memory();
+            worksheet.set_name(&sheet.sheet_name)?;
+            let first_row = write_header(
+                worksheet,
+                &sheet.columns,
+                sheet.row_count,
+                bands
+                    .get(&sheet.sheet_name)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+                &formats,
+            )?;
+            add_validations(
+                worksheet,
+                &sheet.columns,
+                first_row,
+                sheet.row_count,
+                &SheetValidations {
+                    references: references.get(&sheet.sheet_name),
+                    literals: literals.get(&sheet.sheet_name),
+                    entity_rows,
+                },
+            )?;
+            cursors.insert(sheet.sheet_name.clone(), (index, first_row));
         }
 
-        let excel_table = rust_xlsxwriter::Table::new().set_autofilter(true);
+        let mut writer = Self {
+            workbook,
+            cursors,
+            literals,
+            formats,
+        };
+        writer.append(&[metadata])?;
+        writer.append(&layout.context_table().into_iter().collect::<Vec<_>>())?;
+        writer.append(
+            &layout
+                .dataset_table()
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+        )?;
+        Ok(writer)
+    }
 
-        let col_end = (table.columns.len() as u16).saturating_sub(1);
-        let row_end = if table.rows.is_empty() {
-            0
-        } else {
-            table.rows.len() as u32
+    /// Appends the rows of `tables`, as produced by
+    /// [`WorkbookLayout::tables`], to their sheets.
+    pub fn append(&mut self, tables: &[SheetTable]) -> Result<()> {
+        for table in tables {
+            let (index, next_row) = self.cursors.get_mut(&table.sheet_name).ok_or_else(|| {
+                ToolError::InvalidWorkbook(format!(
+                    "sheet '{}' is not part of the workbook layout",
+                    table.sheet_name
+                ))
+            })?;
+            let worksheet = self.workbook.worksheet_from_index(*index)?;
+            let datatypes = literal_datatypes(table, &self.literals);
+            let first_row = *next_row;
+            for row in &table.rows {
+                write_row(worksheet, *next_row, row, &datatypes, &self.formats)?;
+                *next_row += 1;
+            }
+            if table.sheet_name == DATASET_SHEET {
+                define_dataset_names(&mut self.workbook, table, first_row)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the workbook to `path`.
+    pub fn finish(mut self, path: &Path) -> Result<()> {
+        atomic::write_atomically(path, |partial| Ok(self.workbook.save(partial)?))
+    }
+}
+
+/// Writes the cells of one row, storing those of columns with an entry in
+/// `datatypes` as typed cells and the booleans of `xsd:boolean` columns as
+/// checkboxes.
+fn write_row(
+    worksheet: &mut Worksheet,
+    row: u32,
+    cells: &[String],
+    datatypes: &[Option<&str>],
+    formats: &CellFormats,
+) -> Result<()> {
+    for (col_idx, cell) in cells.iter().enumerate() {
+        let col = col_idx as u16;
+        let datatype = datatypes.get(col_idx).copied().flatten();
+        let value = cell_value(cell, datatype);
+        match (&value, value.serial()) {
+            (CellValue::Number(number), _) => worksheet.write_number(row, col, *number)?,
+            (CellValue::Boolean(flag), _) if datatype == Some(XSD_BOOLEAN) => {
+                worksheet.write_boolean_with_format(row, col, *flag, &formats.checkbox)?
+            }
+            (CellValue::Boolean(flag), _) => worksheet.write_boolean(row, col, *flag)?,
+            (CellValue::Date(_), Some(serial)) => {
+                worksheet.write_number_with_format(row, col, serial, &formats.date)?
+            }
+            (CellValue::DateTime(_), Some(serial)) => {
+                worksheet.write_number_with_format(row, col, serial, &formats.date_time)?
+            }
+            (CellValue::Text(text), _) => worksheet.write_string(row, col, text.as_ref())?,
+            (CellValue::Date(_) | CellValue::DateTime(_), None) => {
+                worksheet.write_string(row, col, cell)?
+            }
         };
-        worksheet.add_table(0, 0, row_end, col_end, &excel_table)?;
     }
+    Ok(())
+}
 
-    workbook_writer.save(path)?;
+/// What the cells of one sheet are validated against.
+struct SheetValidations<'a> {
+    /// Headers of the columns holding node ids.
+    references: Option<&'a HashSet<String>>,
+    /// Datatype of each `scalar` column, by header.
+    literals: Option<&'a HashMap<String, String>>,
+    /// Data rows of the `Entities` sheet.
+    entity_rows: usize,
+}
+
+/// Adds data validations to the `row_count` data rows, from `first_row`
+/// on, of a sheet with `columns`: reference columns list the ids of the
+/// `Entities` sheet and warn about other values, and `xsd:boolean` columns
+/// only accept `TRUE` and `FALSE`. Reference columns are left alone when
+/// the `Entities` sheet is empty.
+fn add_validations(
+    worksheet: &mut Worksheet,
+    columns: &[String],
+    first_row: u32,
+    row_count: usize,
+    validations: &SheetValidations<'_>,
+) -> Result<()> {
+    let last_row = first_row + row_count.max(1) as u32 - 1;
+    let entity_ids = DataValidation::new()
+        .allow_list_formula(Formula::new(format!(
+            "={ENTITIES_SHEET}!$A$2:$A${}",
+            validations.entity_rows + 1
+        )))
+        .set_error_style(DataValidationErrorStyle::Warning)
+        .set_error_title("Unknown node")?
+        .set_error_message(format!(
+            "The id is not listed on the {ENTITIES_SHEET} sheet."
+        ))?;
+    let booleans = DataValidation::new().allow_list_strings(&["TRUE", "FALSE"])?;
+    for (col_idx, header) in columns.iter().enumerate() {
+        let validation = if validations.entity_rows > 0
+            && validations
+                .references
+                .is_some_and(|references| references.contains(header))
+        {
+            &entity_ids
+        } else if validations
+            .literals
+            .and_then(|literals| literals.get(header))
+            .is_some_and(|datatype| datatype == XSD_BOOLEAN)
+        {
+            &booleans
+        } else {
+            continue;
+        };
+        let col = col_idx as u16;
+        worksheet.add_data_validation(first_row, col, last_row, col, validation)?;
+    }
     Ok(())
 }
+
+/// Writes the header row and registers an auto-filtered table spanning
+/// `row_count` data rows, returning the row of the first. `bands` get their
+/// labels merged across their columns in a row above the header, and an
+/// outline level so Excel can collapse them.
+fn write_header(
+    worksheet: &mut Worksheet,
+    columns: &[String],
+    row_count: usize,
+    bands: &[[String; 3]],
+    formats: &CellFormats,
+) -> Result<u32> {
+    let position = |name: &str| columns.iter().position(|column| column == name);
+    let header_row = u32::from(!bands.is_empty());
+    for [label, first, last] in bands {
+        let (Some(first), Some(last)) = (position(first), position(last)) else {
+            continue;
+        };
+        let (first, last) = (first as u16, last as u16);
+        if first < last {
+            worksheet.merge_range(0, first, 0, last, label, &formats.band)?;
+        } else {
+            worksheet.write_string_with_format(0, first, label, &formats.band)?;
+        }
+        worksheet.group_columns(first, last)?;
+    }
+    for (col_idx, header) in columns.iter().enumerate() {
+        worksheet.write_string(header_row, col_idx as u16, header)?;
+    }
+
+    let table_columns: Vec<TableColumn> = columns
+        .iter()
+        .map(|header| TableColumn::new().set_header(header))
+        .collect();
+    let excel_table = Table::new()
+        .set_autofilter(true)
+        .set_columns(&table_columns);
+
+    // Excel tables need at least one data row; readers skip the blank one
+    // an empty sheet gets.
+    let col_end = (columns.len() as u16).saturating_sub(1);
+    let row_end = header_row + row_count.max(1) as u32;
+    worksheet.add_table(header_row, 0, row_end, col_end, &excel_table)?;
+    Ok(header_row + 1)
+}
@@ -0,0 +1,181 @@
+//! JSON tree borrowing its strings from the text or [`Value`] it was read
+//! from.
+//!
+//! The JSON-LD reader only copies the strings it keeps, so parsing into this
+//! tree instead of a [`Value`] saves allocating every string twice. Strings
+//! containing escapes cannot be borrowed and are owned instead.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use serde_json::{Number, Value};
+
+/// Object members, ordered by key like [`serde_json::Map`] so iteration,
+/// duplicate keys, and serialisation match the [`Value`] representation.
+pub(crate) type JsonObject<'a> = BTreeMap<Cow<'a, str>, JsonRef<'a>>;
+
+/// A JSON value whose strings may borrow from their source.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonRef<'a> {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Array(Vec<JsonRef<'a>>),
+    Object(JsonObject<'a>),
+}
+
+impl JsonRef<'_> {
+    /// Returns the string if the value is one.
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonRef::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Converts the value into an owned [`Value`].
+    pub(crate) fn to_value(&self) -> Value {
+        match self {
+            JsonRef::Null => Value::Null,
+            JsonRef::Bool(value) => Value::Bool(*value),
+            JsonRef::Number(number) => Value::Number(number.clone()),
+            JsonRef::String(value) => Value::String(value.to_string()),
+            JsonRef::Array(values) => Value::Array(values.iter().map(JsonRef::to_value).collect()),
+            JsonRef::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Writes the value as compact JSON, like [`Value`]'s `Display`.
+impl fmt::Display for JsonRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&text)
+    }
+}
+
+impl Serialize for JsonRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonRef::Null => serializer.serialize_unit(),
+            JsonRef::Bool(value) => serializer.serialize_bool(*value),
+            JsonRef::Number(number) => number.serialize(serializer),
+            JsonRef::String(value) => serializer.serialize_str(value),
+            JsonRef::Array(values) => serializer.collect_seq(values),
+            JsonRef::Object(object) => serializer.collect_map(object),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonRef<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonRefVisitor)
+    }
+}
+
+struct JsonRefVisitor;
+
+impl<'de> Visitor<'de> for JsonRefVisitor {
+    type Value = JsonRef<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(JsonRef::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(JsonRef::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        JsonRef::deserialize(deserializer)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(JsonRef::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(JsonRef::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(JsonRef::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(Number::from_f64(value).map_or(JsonRef::Null, JsonRef::Number))
+    }
+
+    fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E> {
+        Ok(JsonRef::String(Cow::Borrowed(value)))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(JsonRef::String(Cow::Owned(value.to_string())))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+        Ok(JsonRef::String(Cow::Owned(value)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(JsonRef::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut object = JsonObject::new();
+        while let Some((Key(key), value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(JsonRef::Object(object))
+    }
+}
+
+/// Object key borrowing from the source where possible; `Cow<str>`'s own
+/// `Deserialize` always copies.
+struct Key<'a>(Cow<'a, str>);
+
+impl<'de> Deserialize<'de> for Key<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a JSON object key")
+            }
+
+            fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E> {
+                Ok(Key(Cow::Borrowed(value)))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(Key(Cow::Owned(value.to_string())))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+                Ok(Key(Cow::Owned(value)))
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
@@ -1,38 +1,323 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{Read, Write};
 
 use futures::executor::block_on;
-use iref::Iri;
-use json_ld::{JsonLdProcessor, NoLoader, Options, RemoteContextReference, RemoteDocument};
+use iref::Iri as IriRef;
+use json_ld::{
+    JsonLdProcessor, NoLoader, Options, ProcessingMode, RemoteContextReference, RemoteDocument,
+};
 use json_ld_syntax::TryFromJson;
 use json_ld_syntax::context::Context as JsonLdContext;
 use json_syntax::Value as JsonSyntaxValue;
-use serde_json::{Map, Value};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{
+    self, Deserialize, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor,
+};
+use serde_json::{Map, Number, Value};
 use uuid::Uuid;
 
 use crate::aideon::tools::error::{Result, ToolError};
-use crate::aideon::tools::model::{ArrayValue, Node, PropertyValue, ScalarValue};
+use crate::aideon::tools::interrupt;
+use crate::aideon::tools::io::json_ref::{JsonObject, JsonRef};
+use crate::aideon::tools::model::{
+    ArrayItem, ArrayValue, Interner, Iri, Node, NodeId, PropertyValue, ScalarValue,
+};
 
-type NodeKey = (Option<String>, String);
+type NodeKey = (Option<Iri>, NodeId);
 
 #[derive(Clone, Default)]
 struct ActiveContext {
     vocab: Option<String>,
     term_map: BTreeMap<String, String>,
     id_properties: BTreeSet<String>,
+    language_maps: BTreeSet<String>,
+    default_language: Option<String>,
+}
+
+/// How node objects without an `@id` are given one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// A `urn:uuid:` name-based (version 5) UUID hashed from the node
+    /// object, the same whenever the object is.
+    #[default]
+    UuidV5,
+    /// A random (version 4) `urn:uuid:` UUID, different on every read.
+    UuidV4,
+    /// A `urn:uuid:` name-based UUID hashed from the node's types and the
+    /// values of these properties, so objects agreeing on them share an id
+    /// whatever else they hold. Properties are named as the object's keys
+    /// or as expanded IRIs. Objects holding none of them are hashed whole,
+    /// as [`IdStrategy::UuidV5`] does.
+    KeyHash(Vec<String>),
+    /// An IRI filled in from a template such as
+    /// `https://example.org/{type}/{name}`: `{type}` stands for the local
+    /// name of the node's first type, and other placeholders for the value
+    /// of the property they name, like [`IdStrategy::KeyHash`] keys, percent
+    /// encoded. Parsing fails for objects lacking a named value.
+    Template(String),
+}
+
+/// Nodes parsed so far, keyed by graph and id, together with the pool their
+/// ids, types, and predicates are interned in and the strategy minting ids
+/// for node objects without one.
+#[derive(Default)]
+struct NodeSet {
+    nodes: BTreeMap<NodeKey, Node>,
+    interner: Interner,
+    ids: IdStrategy,
+}
+
+impl NodeSet {
+    fn new(ids: &IdStrategy) -> Self {
+        NodeSet {
+            ids: ids.clone(),
+            ..NodeSet::default()
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn into_nodes(self) -> Vec<Node> {
+        self.nodes.into_values().collect()
+    }
 }
 
 /// Parses a JSON-LD document into a vector of [`Node`]s.
 pub fn parse_jsonld_document(document: &Value) -> Result<Vec<Node>> {
-    let mut nodes: BTreeMap<NodeKey, Node> = BTreeMap::new();
+    parse_jsonld_document_with_ids(document, &IdStrategy::default())
+}
+
+/// Parses a JSON-LD document like [`parse_jsonld_document`], minting the ids
+/// of node objects without one with `ids`.
+pub fn parse_jsonld_document_with_ids(document: &Value, ids: &IdStrategy) -> Result<Vec<Node>> {
+    parse_document(&JsonRef::deserialize(document)?, ids)
+}
+
+/// Parses the JSON-LD text held by `reader` like [`parse_jsonld_str`].
+pub fn read_jsonld_from<R: Read>(mut reader: R) -> Result<Vec<Node>> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+    parse_jsonld_str(&source)
+}
+
+/// Parses JSON-LD text into a vector of [`Node`]s.
+///
+/// Produces the same nodes as [`parse_jsonld_document`] without building a
+/// [`Value`] first: strings are borrowed from `source` while parsing and only
+/// the literals and pooled ids that end up in the nodes are copied. Top-level
+/// entries of an array or `@graph` are parsed one at a time, so only one
+/// entry's JSON is held in memory alongside the text and the nodes.
+pub fn parse_jsonld_str(source: &str) -> Result<Vec<Node>> {
+    parse_jsonld_str_with_ids(source, &IdStrategy::default())
+}
+
+/// Parses JSON-LD text like [`parse_jsonld_str`], minting the ids of node
+/// objects without one with `ids`.
+pub fn parse_jsonld_str_with_ids(source: &str, ids: &IdStrategy) -> Result<Vec<Node>> {
+    let mut nodes = NodeSet::new(ids);
+    let mut failure = None;
+    let mut stream = EntryStream {
+        nodes: &mut nodes,
+        failure: &mut failure,
+    };
+    let mut deserializer = serde_json::Deserializer::from_str(source);
+    let parsed = match source.trim_start().chars().next() {
+        Some('[') => GraphSeed {
+            stream: &mut stream,
+            context: None,
+        }
+        .deserialize(&mut deserializer),
+        Some('{') => {
+            // Only a root holding nothing but a context and a graph is read
+            // entry by entry; other roots are node objects.
+            let keys: BTreeMap<String, IgnoredAny> = serde_json::from_str(source)?;
+            if !keys.contains_key("@graph")
+                || keys.keys().any(|key| key != "@graph" && key != "@context")
+            {
+                return parse_document(&serde_json::from_str(source)?, ids);
+            }
+            RootSeed {
+                stream: &mut stream,
+                has_context: keys.contains_key("@context"),
+            }
+            .deserialize(&mut deserializer)
+        }
+        _ => return parse_document(&serde_json::from_str(source)?, ids),
+    };
+    if let Some(err) = failure {
+        return Err(err);
+    }
+    parsed.and_then(|()| deserializer.end())?;
+    Ok(nodes.into_nodes())
+}
+
+/// The node set entries are parsed into while the text is deserialised,
+/// with the first error doing so, which serde cannot carry.
+struct EntryStream<'s> {
+    nodes: &'s mut NodeSet,
+    failure: &'s mut Option<ToolError>,
+}
+
+impl EntryStream<'_> {
+    /// Runs `parse` on the node set, keeping its error and stopping the
+    /// deserialisation with a placeholder.
+    fn run<T, E: de::Error>(
+        &mut self,
+        parse: impl FnOnce(&mut NodeSet) -> Result<T>,
+    ) -> std::result::Result<T, E> {
+        parse(self.nodes).map_err(|err| {
+            *self.failure = Some(err);
+            E::custom("invalid JSON-LD entry")
+        })
+    }
+}
+
+/// Parses a root object of `@context` and `@graph`, reading the graph entry
+/// by entry once the context is known. A graph before its context is held
+/// whole until the context is read.
+struct RootSeed<'a, 's> {
+    stream: &'a mut EntryStream<'s>,
+    has_context: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for RootSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for RootSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a JSON-LD document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        let mut context = None;
+        let mut pending = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "@context" {
+                let value: JsonRef<'de> = map.next_value()?;
+                context = Some(
+                    self.stream
+                        .run(|_| parse_context_value(&value.to_value(), None))?,
+                );
+            } else if self.has_context && context.is_none() {
+                pending = Some(map.next_value::<JsonRef<'de>>()?);
+            } else {
+                map.next_value_seed(GraphSeed {
+                    stream: &mut *self.stream,
+                    context: context.as_ref(),
+                })?;
+            }
+        }
+        match pending {
+            Some(graph) => GraphSeed {
+                stream: self.stream,
+                context: context.as_ref(),
+            }
+            .parse(graph),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses a `@graph` value or a top-level array, an array one entry at a
+/// time.
+struct GraphSeed<'a, 's> {
+    stream: &'a mut EntryStream<'s>,
+    context: Option<&'a ActiveContext>,
+}
+
+impl GraphSeed<'_, '_> {
+    fn parse<E: de::Error>(self, graph: JsonRef<'_>) -> std::result::Result<(), E> {
+        let context = self.context;
+        self.stream
+            .run(|nodes| parse_graph(&graph, None, context, nodes))
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for GraphSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for GraphSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a JSON-LD graph")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        let context = self.context;
+        while let Some(entry) = seq.next_element::<JsonRef<'de>>()? {
+            self.stream.run(|nodes| {
+                interrupt::check(nodes.len())?;
+                parse_entry(&entry, None, context, nodes)
+            })?;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> std::result::Result<(), A::Error> {
+        let graph = JsonRef::deserialize(MapAccessDeserializer::new(map))?;
+        self.parse(graph)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<(), E> {
+        self.parse(JsonRef::Null)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> std::result::Result<(), E> {
+        self.parse(JsonRef::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<(), E> {
+        self.parse(JsonRef::Number(value.into()))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<(), E> {
+        self.parse(JsonRef::Number(value.into()))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> std::result::Result<(), E> {
+        self.parse(Number::from_f64(value).map_or(JsonRef::Null, JsonRef::Number))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<(), E> {
+        self.parse(JsonRef::String(Cow::Borrowed(value)))
+    }
+}
+
+fn parse_document(document: &JsonRef<'_>, ids: &IdStrategy) -> Result<Vec<Node>> {
+    let mut nodes = NodeSet::new(ids);
     match document {
-        Value::Array(items) => {
+        JsonRef::Array(items) => {
             for value in items {
+                interrupt::check(nodes.len())?;
                 parse_entry(value, None, None, &mut nodes)?;
             }
         }
-        Value::Object(map) => {
+        JsonRef::Object(map) => {
             let base_context = if let Some(context) = map.get("@context") {
-                Some(parse_context_value(context, None)?)
+                Some(parse_context_value(&context.to_value(), None)?)
             } else {
                 None
             };
@@ -45,25 +330,26 @@ pub fn parse_jsonld_document(document: &Value) -> Result<Vec<Node>> {
         }
     }
 
-    Ok(nodes.into_values().collect())
+    Ok(nodes.into_nodes())
 }
 
 fn parse_graph(
-    value: &Value,
+    value: &JsonRef<'_>,
     active_graph: Option<&str>,
     context: Option<&ActiveContext>,
-    nodes: &mut BTreeMap<NodeKey, Node>,
+    nodes: &mut NodeSet,
 ) -> Result<()> {
     match value {
-        Value::Array(items) => {
+        JsonRef::Array(items) => {
             for entry in items {
+                interrupt::check(nodes.len())?;
                 parse_entry(entry, active_graph, context, nodes)?;
             }
         }
-        Value::Object(_) => {
+        JsonRef::Object(_) => {
             parse_entry(value, active_graph, context, nodes)?;
         }
-        Value::Null => {}
+        JsonRef::Null => {}
         other => {
             return Err(ToolError::JsonLd(format!(
                 "invalid @graph entry: expected array or object, found {other}"
@@ -74,23 +360,23 @@ fn parse_graph(
 }
 
 fn parse_entry(
-    value: &Value,
+    value: &JsonRef<'_>,
     active_graph: Option<&str>,
     context: Option<&ActiveContext>,
-    nodes: &mut BTreeMap<NodeKey, Node>,
+    nodes: &mut NodeSet,
 ) -> Result<()> {
     match value {
-        Value::Object(object) => {
+        JsonRef::Object(object) => {
             let local_context_storage;
             let context_to_use = if let Some(context_value) = object.get("@context") {
-                local_context_storage = parse_context_value(context_value, context)?;
+                local_context_storage = parse_context_value(&context_value.to_value(), context)?;
                 Some(&local_context_storage)
             } else {
                 context
             };
 
             if let Some(graph_value) = object.get("@graph") {
-                let next_graph = object.get("@id").and_then(Value::as_str);
+                let next_graph = object.get("@id").and_then(JsonRef::as_str);
                 parse_graph(graph_value, next_graph, context_to_use, nodes)?;
                 if has_node_properties(object) {
                     parse_node_object(object, active_graph, context_to_use, nodes)?;
@@ -100,7 +386,7 @@ fn parse_entry(
             }
             Ok(())
         }
-        Value::Array(values) => {
+        JsonRef::Array(values) => {
             for item in values {
                 parse_entry(item, active_graph, context, nodes)?;
             }
@@ -112,46 +398,35 @@ fn parse_entry(
     }
 }
 
-fn has_node_properties(object: &Map<String, Value>) -> bool {
+fn has_node_properties(object: &JsonObject<'_>) -> bool {
     object
         .keys()
         .any(|key| key != "@id" && key != "@graph" && key != "@context")
 }
 
 fn parse_node_object(
-    object: &Map<String, Value>,
+    object: &JsonObject<'_>,
     active_graph: Option<&str>,
     context: Option<&ActiveContext>,
-    nodes: &mut BTreeMap<NodeKey, Node>,
-) -> Result<()> {
-    let mut id = object
-        .get("@id")
-        .and_then(Value::as_str)
-        .map(str::to_string)
-        .unwrap_or_else(|| generate_surrogate_id(object));
-
-    if id.is_empty() {
-        id = generate_surrogate_id(object);
-    }
-
-    let graph = active_graph.map(str::to_string);
-    let key = (graph.clone(), id.clone());
-    let node = nodes
-        .entry(key)
-        .or_insert_with(|| Node::with_graph(id.clone(), graph.clone()));
-    node.set_graph(graph);
-
-    if let Some(types) = object.get("@type") {
-        match types {
-            Value::Array(entries) => {
+    nodes: &mut NodeSet,
+) -> Result<NodeId> {
+    let id = match object.get("@id").and_then(JsonRef::as_str) {
+        Some(id) if !id.is_empty() => Cow::Borrowed(id),
+        _ => Cow::Owned(generate_surrogate_id(object, context, &nodes.ids)?),
+    };
+
+    let mut types = BTreeSet::new();
+    if let Some(type_value) = object.get("@type") {
+        match type_value {
+            JsonRef::Array(entries) => {
                 for entry in entries {
                     if let Some(value) = entry.as_str() {
-                        node.types.insert(expand_term(context, value));
+                        types.insert(expand_term(context, value));
                     }
                 }
             }
-            Value::String(value) => {
-                node.types.insert(expand_term(context, value));
+            JsonRef::String(value) => {
+                types.insert(expand_term(context, value));
             }
             other => {
                 return Err(ToolError::JsonLd(format!(
@@ -161,8 +436,11 @@ fn parse_node_object(
         }
     }
 
+    // Properties are parsed before the node is looked up because embedded
+    // node objects add nodes of their own.
+    let mut properties = Vec::new();
     for (key, value) in object {
-        if matches!(key.as_str(), "@id" | "@type" | "@context" | "@graph") {
+        if matches!(key.as_ref(), "@id" | "@type" | "@context" | "@graph") {
             continue;
         }
 
@@ -171,143 +449,212 @@ fn parse_node_object(
             .map(|ctx| ctx.id_properties.contains(&expanded_key))
             .unwrap_or(false);
 
-        let property_value = parse_property_value(value, context, treat_as_id).map_err(|err| {
-            ToolError::JsonLd(format!("failed to parse property '{expanded_key}': {err}"))
+        let is_language_map = context
+            .map(|ctx| ctx.language_maps.contains(&expanded_key))
+            .unwrap_or(false);
+
+        let mut scope = Scope {
+            graph: active_graph,
+            context,
+            nodes,
+        };
+        let is_list = matches!(value, JsonRef::Object(map) if map.contains_key("@list"));
+        let parsed = match value {
+            JsonRef::Object(map) if is_language_map => parse_language_map(map),
+            _ => scope.parse_property_value(value, treat_as_id),
+        };
+        let property_value = parsed.map_err(|err| {
+            ToolError::JsonLd(format!(
+                "failed to parse property '{expanded_key}' of node '{id}': {err}"
+            ))
         })?;
-        node.insert_property(expanded_key, property_value);
+        properties.push((expanded_key, property_value, is_list));
     }
 
-    Ok(())
-}
-
-fn parse_property_value(
-    value: &Value,
-    context: Option<&ActiveContext>,
-    treat_as_id: bool,
-) -> Result<PropertyValue> {
-    match value {
-        Value::Null => Ok(PropertyValue::Scalar(ScalarValue::Null)),
-        Value::Bool(value) => Ok(PropertyValue::Scalar(ScalarValue::Boolean(*value))),
-        Value::Number(number) => Ok(PropertyValue::Scalar(ScalarValue::Number(
-            number
-                .as_f64()
-                .ok_or_else(|| ToolError::JsonLd("invalid number literal".into()))?,
-        ))),
-        Value::String(value) => {
-            if treat_as_id {
-                Ok(PropertyValue::ObjectRef(expand_term(context, value)))
-            } else if looks_like_iri(value) {
-                Ok(PropertyValue::ObjectRef(value.clone()))
-            } else {
-                Ok(PropertyValue::Scalar(ScalarValue::String(value.clone())))
-            }
+    let NodeSet {
+        nodes, interner, ..
+    } = nodes;
+    let id = interner.intern(&id);
+    let graph = active_graph.map(|graph| interner.intern(graph));
+    let key = (graph.clone(), id.clone());
+    let node = nodes
+        .entry(key)
+        .or_insert_with(|| Node::with_graph(id.clone(), graph.clone()));
+    node.set_graph(graph);
+    node.types
+        .extend(types.iter().map(|type_name| interner.intern(type_name)));
+    for (predicate, value, is_list) in properties {
+        let predicate = interner.intern(&predicate);
+        if is_list {
+            node.lists.insert(predicate.clone());
         }
-        Value::Array(values) => parse_array(values, context, treat_as_id),
-        Value::Object(map) => {
-            if let Some(set) = map.get("@set") {
-                return parse_property_value(set, context, treat_as_id);
-            }
+        node.insert_property(predicate, value);
+    }
 
-            if let Some(list) = map.get("@list") {
-                return parse_property_value(list, context, treat_as_id);
-            }
+    Ok(id)
+}
 
-            if let Some(id) = map.get("@id").and_then(Value::as_str) {
-                let reference = if treat_as_id {
-                    expand_term(context, id)
+/// Parsing state for property values, which may contain embedded node
+/// objects that become nodes of the enclosing graph.
+struct Scope<'a, 'c> {
+    graph: Option<&'a str>,
+    context: Option<&'c ActiveContext>,
+    nodes: &'a mut NodeSet,
+}
+
+impl Scope<'_, '_> {
+    fn parse_property_value(
+        &mut self,
+        value: &JsonRef<'_>,
+        treat_as_id: bool,
+    ) -> Result<PropertyValue> {
+        let context = self.context;
+        match value {
+            JsonRef::Null => Ok(PropertyValue::Scalar(ScalarValue::Null)),
+            JsonRef::Bool(value) => Ok(PropertyValue::Scalar(ScalarValue::Boolean(*value))),
+            JsonRef::Number(number) => Ok(PropertyValue::Scalar(
+                ScalarValue::from_json_number(number)
+                    .ok_or_else(|| ToolError::JsonLd("invalid number literal".into()))?,
+            )),
+            JsonRef::String(value) => {
+                if treat_as_id {
+                    Ok(PropertyValue::ObjectRef(
+                        self.intern(&expand_term(context, value)),
+                    ))
+                } else if looks_like_iri(value) {
+                    Ok(PropertyValue::ObjectRef(self.intern(value)))
                 } else {
-                    id.to_string()
-                };
-                return Ok(PropertyValue::ObjectRef(reference));
+                    Ok(PropertyValue::Scalar(string_literal(context, value)))
+                }
             }
+            JsonRef::Array(values) => self.parse_array(values, treat_as_id),
+            JsonRef::Object(map) => {
+                if let Some(set) = map.get("@set") {
+                    return self.parse_property_value(set, treat_as_id);
+                }
 
-            if let Some(literal) = map.get("@value") {
-                return parse_property_value(literal, context, treat_as_id);
-            }
+                if let Some(list) = map.get("@list") {
+                    return self.parse_property_value(list, treat_as_id);
+                }
+
+                if map.contains_key("@value") {
+                    return Ok(PropertyValue::Scalar(parse_value_object(map, context)?));
+                }
 
-            Ok(PropertyValue::Scalar(ScalarValue::String(
-                serde_json::to_string(map).map_err(|err| ToolError::JsonLd(err.to_string()))?,
-            )))
+                Ok(PropertyValue::ObjectRef(self.reference(map, treat_as_id)?))
+            }
         }
     }
-}
 
-fn parse_array(
-    values: &[Value],
-    context: Option<&ActiveContext>,
-    treat_as_id: bool,
-) -> Result<PropertyValue> {
-    let mut scalars = Vec::new();
-    let mut refs = Vec::new();
-
-    for entry in values {
-        match entry {
-            Value::Array(items) => {
-                let nested = parse_array(items, context, treat_as_id)?;
-                collect_array_entry(nested, &mut scalars, &mut refs)?;
-            }
-            Value::Object(map) if map.contains_key("@set") => {
-                let nested = parse_property_value(map.get("@set").unwrap(), context, treat_as_id)?;
-                collect_array_entry(nested, &mut scalars, &mut refs)?;
-            }
-            Value::Object(map) if map.contains_key("@list") => {
-                let nested = parse_property_value(map.get("@list").unwrap(), context, treat_as_id)?;
-                collect_array_entry(nested, &mut scalars, &mut refs)?;
-            }
-            Value::Object(map) if map.contains_key("@id") => {
-                if let Some(id) = map.get("@id").and_then(Value::as_str) {
-                    let reference = if treat_as_id {
-                        expand_term(context, id)
-                    } else {
-                        id.to_string()
-                    };
-                    refs.push(reference);
-                } else {
-                    return Err(ToolError::JsonLd("object reference missing @id".into()));
+    fn parse_array(&mut self, values: &[JsonRef<'_>], treat_as_id: bool) -> Result<PropertyValue> {
+        let context = self.context;
+        let mut items = Vec::new();
+
+        for entry in values {
+            match entry {
+                JsonRef::Array(nested) => {
+                    items.extend(self.parse_array(nested, treat_as_id)?.items());
                 }
+                JsonRef::Object(map) if map.contains_key("@set") => {
+                    let nested = self.parse_property_value(&map["@set"], treat_as_id)?;
+                    items.extend(nested.items());
+                }
+                JsonRef::Object(map) if map.contains_key("@list") => {
+                    let nested = self.parse_property_value(&map["@list"], treat_as_id)?;
+                    items.extend(nested.items());
+                }
+                JsonRef::Object(map) if map.contains_key("@value") => {
+                    items.push(ArrayItem::Scalar(parse_value_object(map, context)?));
+                }
+                JsonRef::Object(map) => {
+                    items.push(ArrayItem::ObjectRef(self.reference(map, treat_as_id)?));
+                }
+                JsonRef::String(value) if treat_as_id => {
+                    let reference = self.intern(&expand_term(context, value));
+                    items.push(ArrayItem::ObjectRef(reference));
+                }
+                JsonRef::String(value) if looks_like_iri(value) => {
+                    items.push(ArrayItem::ObjectRef(self.intern(value)));
+                }
+                JsonRef::String(value) => {
+                    items.push(ArrayItem::Scalar(string_literal(context, value)));
+                }
+                other => items.push(ArrayItem::Scalar(extract_scalar(other)?)),
             }
-            Value::Object(map) if map.contains_key("@value") => {
-                scalars.push(extract_scalar(map.get("@value").unwrap())?);
-            }
-            Value::Object(map) => {
-                scalars.push(ScalarValue::String(
-                    serde_json::to_string(map).map_err(|err| ToolError::JsonLd(err.to_string()))?,
-                ));
+        }
+
+        Ok(PropertyValue::Array(ArrayValue::from_items(items)))
+    }
+
+    fn intern(&mut self, value: &str) -> NodeId {
+        self.nodes.interner.intern(value)
+    }
+
+    /// Returns the id a node object in value position refers to. Objects
+    /// carrying properties are embedded nodes: they are added to the node
+    /// set, recursively, under their `@id` or a surrogate id.
+    fn reference(&mut self, map: &JsonObject<'_>, treat_as_id: bool) -> Result<NodeId> {
+        let embedded = map.keys().any(|key| key != "@id");
+        match map.get("@id") {
+            Some(JsonRef::String(id)) if !embedded => Ok(if treat_as_id {
+                self.intern(&expand_term(self.context, id))
+            } else {
+                self.intern(id)
+            }),
+            Some(id) if id.as_str().is_none() => {
+                Err(ToolError::JsonLd("object reference missing @id".into()))
             }
-            Value::String(value) if treat_as_id || looks_like_iri(value) => {
-                let reference = if treat_as_id {
-                    expand_term(context, value)
+            _ => {
+                let local_context_storage;
+                let context = if let Some(context_value) = map.get("@context") {
+                    local_context_storage =
+                        parse_context_value(&context_value.to_value(), self.context)?;
+                    Some(&local_context_storage)
                 } else {
-                    value.clone()
+                    self.context
                 };
-                refs.push(reference);
+                parse_node_object(map, self.graph, context, self.nodes)
             }
-            other => scalars.push(extract_scalar(other)?),
         }
     }
+}
 
-    match (scalars.is_empty(), refs.is_empty()) {
-        (false, true) => Ok(PropertyValue::Array(ArrayValue::Scalars(scalars))),
-        (true, false) => Ok(PropertyValue::Array(ArrayValue::ObjectRefs(refs))),
-        (true, true) => Ok(PropertyValue::Array(ArrayValue::Scalars(vec![]))),
-        (false, false) => Err(ToolError::JsonLd(
-            "mixed arrays of literals and object references are not supported".into(),
-        )),
+/// Expands a JSON-LD language map (`{"en": "...", "de": [...]}`) into
+/// language-tagged literals.
+fn parse_language_map(map: &JsonObject<'_>) -> Result<PropertyValue> {
+    let mut scalars = Vec::new();
+    for (language, entry) in map {
+        let texts = match entry {
+            JsonRef::Array(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        for text in texts {
+            match text {
+                JsonRef::String(value) => {
+                    scalars.push(ScalarValue::lang_tagged(value.as_ref(), language.as_ref()))
+                }
+                JsonRef::Null => {}
+                other => {
+                    return Err(ToolError::JsonLd(format!(
+                        "invalid language map entry for '{language}': expected string, found {other}"
+                    )));
+                }
+            }
+        }
     }
+
+    Ok(match scalars.len() {
+        1 => PropertyValue::Scalar(scalars.remove(0)),
+        _ => PropertyValue::Array(ArrayValue::Scalars(scalars)),
+    })
 }
 
-fn collect_array_entry(
-    value: PropertyValue,
-    scalars: &mut Vec<ScalarValue>,
-    refs: &mut Vec<String>,
-) -> Result<()> {
-    match value {
-        PropertyValue::Scalar(scalar) => scalars.push(scalar),
-        PropertyValue::ObjectRef(reference) => refs.push(reference),
-        PropertyValue::Array(ArrayValue::Scalars(mut nested)) => scalars.append(&mut nested),
-        PropertyValue::Array(ArrayValue::ObjectRefs(mut nested)) => refs.append(&mut nested),
+/// Builds a string literal, applying the context's default `@language` when set.
+fn string_literal(context: Option<&ActiveContext>, value: &str) -> ScalarValue {
+    match context.and_then(|ctx| ctx.default_language.as_ref()) {
+        Some(language) => ScalarValue::lang_tagged(value, language),
+        None => ScalarValue::String(value.to_string()),
     }
-    Ok(())
 }
 
 fn parse_context_value(value: &Value, parent: Option<&ActiveContext>) -> Result<ActiveContext> {
@@ -322,7 +669,7 @@ fn parse_context_value(value: &Value, parent: Option<&ActiveContext>) -> Result<
         }
         Value::Object(object) => parse_context_object(object, parent),
         Value::String(reference) => Err(ToolError::JsonLd(format!(
-            "remote context references are not supported: {reference}"
+            "remote context reference '{reference}' was not resolved; enable remote context loading to process it"
         ))),
         other => Err(ToolError::JsonLd(format!(
             "invalid @context entry: expected object, array, null, or string, found {other}"
@@ -348,6 +695,18 @@ fn parse_context_object(
         }
     }
 
+    if let Some(language) = object.get("@language") {
+        match language {
+            Value::Null => context.default_language = None,
+            Value::String(value) => context.default_language = Some(value.clone()),
+            other => {
+                return Err(ToolError::JsonLd(format!(
+                    "invalid @language definition: expected string or null, found {other}"
+                )));
+            }
+        }
+    }
+
     for (term, definition) in object {
         if term.starts_with('@') {
             continue;
@@ -403,6 +762,16 @@ fn parse_context_term(term: &str, definition: &Value, context: &mut ActiveContex
                     update_term_definition(context, term, inferred, true);
                 }
             }
+
+            if is_language_container(object.get("@container"))
+                && let Some(expanded_iri) = context
+                    .term_map
+                    .get(term)
+                    .cloned()
+                    .or_else(|| default_vocab_expansion(context, term))
+            {
+                context.language_maps.insert(expanded_iri);
+            }
         }
         other => {
             return Err(ToolError::JsonLd(format!(
@@ -414,9 +783,18 @@ fn parse_context_term(term: &str, definition: &Value, context: &mut ActiveContex
     Ok(())
 }
 
+fn is_language_container(container: Option<&Value>) -> bool {
+    match container {
+        Some(Value::String(value)) => value == "@language",
+        Some(Value::Array(values)) => values.iter().any(|value| value == "@language"),
+        _ => false,
+    }
+}
+
 fn remove_term_definition(context: &mut ActiveContext, term: &str) {
     if let Some(previous) = context.term_map.remove(term) {
         context.id_properties.remove(&previous);
+        context.language_maps.remove(&previous);
     }
 }
 
@@ -451,7 +829,15 @@ fn expand_context_reference(context: &ActiveContext, value: &str) -> String {
 }
 
 fn expand_term(context: Option<&ActiveContext>, term: &str) -> String {
-    if term.starts_with('@') || looks_like_iri(term) {
+    if term.starts_with('@') {
+        return term.to_string();
+    }
+
+    if let Some(expanded) = context.and_then(|ctx| expand_compact_iri(ctx, term)) {
+        return expanded;
+    }
+
+    if looks_like_iri(term) {
         return term.to_string();
     }
 
@@ -460,10 +846,6 @@ fn expand_term(context: Option<&ActiveContext>, term: &str) -> String {
             return mapped.clone();
         }
 
-        if let Some(expanded) = expand_compact_iri(ctx, term) {
-            return expanded;
-        }
-
         if let Some(expanded) = default_vocab_expansion(ctx, term) {
             return expanded;
         }
@@ -474,40 +856,186 @@ fn expand_term(context: Option<&ActiveContext>, term: &str) -> String {
 
 fn expand_compact_iri(context: &ActiveContext, value: &str) -> Option<String> {
     let (prefix, suffix) = value.split_once(':')?;
+    if suffix.starts_with("//") {
+        return None;
+    }
     context
         .term_map
         .get(prefix)
         .map(|base| format!("{base}{suffix}"))
 }
 
-fn extract_scalar(value: &Value) -> Result<ScalarValue> {
+fn extract_scalar(value: &JsonRef<'_>) -> Result<ScalarValue> {
     match value {
-        Value::Null => Ok(ScalarValue::Null),
-        Value::Bool(value) => Ok(ScalarValue::Boolean(*value)),
-        Value::Number(number) => {
-            Ok(ScalarValue::Number(number.as_f64().ok_or_else(|| {
-                ToolError::JsonLd("invalid number literal".into())
-            })?))
-        }
-        Value::String(value) => Ok(ScalarValue::String(value.clone())),
+        JsonRef::Null => Ok(ScalarValue::Null),
+        JsonRef::Bool(value) => Ok(ScalarValue::Boolean(*value)),
+        JsonRef::Number(number) => ScalarValue::from_json_number(number)
+            .ok_or_else(|| ToolError::JsonLd("invalid number literal".into())),
+        JsonRef::String(value) => Ok(ScalarValue::String(value.to_string())),
         other => Ok(ScalarValue::String(serde_json::to_string(other)?)),
     }
 }
 
+/// Converts a JSON-LD value object into a scalar, keeping explicit datatypes
+/// and language tags so they survive re-serialisation.
+fn parse_value_object(
+    map: &JsonObject<'_>,
+    context: Option<&ActiveContext>,
+) -> Result<ScalarValue> {
+    let literal = map.get("@value").unwrap_or(&JsonRef::Null);
+
+    if let Some(language) = map.get("@language").and_then(JsonRef::as_str) {
+        return Ok(ScalarValue::lang_tagged(lexical_form(literal), language));
+    }
+
+    if let Some(datatype) = map.get("@type").and_then(JsonRef::as_str) {
+        let datatype = expand_term(context, datatype);
+        return Ok(ScalarValue::from_lexical(lexical_form(literal), &datatype));
+    }
+
+    extract_scalar(literal)
+}
+
+fn lexical_form(value: &JsonRef<'_>) -> String {
+    match value {
+        JsonRef::String(text) => text.to_string(),
+        other => other.to_string(),
+    }
+}
+
 fn looks_like_iri(value: &str) -> bool {
-    Iri::new(value).is_ok()
+    IriRef::new(value).is_ok()
 }
 
-fn generate_surrogate_id(object: &Map<String, Value>) -> String {
-    let canonical = canonicalise_object(object);
-    let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, canonical.as_bytes());
-    format!("urn:uuid:{uuid}")
+fn generate_surrogate_id(
+    object: &JsonObject<'_>,
+    context: Option<&ActiveContext>,
+    ids: &IdStrategy,
+) -> Result<String> {
+    let hashed = |canonical: String| {
+        let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, canonical.as_bytes());
+        format!("urn:uuid:{uuid}")
+    };
+    Ok(match ids {
+        IdStrategy::UuidV5 => hashed(canonicalise_object(object)),
+        IdStrategy::UuidV4 => format!("urn:uuid:{}", Uuid::new_v4()),
+        IdStrategy::KeyHash(keys) => {
+            let values: BTreeMap<&str, &JsonRef<'_>> = keys
+                .iter()
+                .filter_map(|key| Some((key.as_str(), key_value(object, context, key)?)))
+                .collect();
+            if values.is_empty() {
+                hashed(canonicalise_object(object))
+            } else {
+                let types = object_types(object, context);
+                hashed(serde_json::to_string(&(types, values)).unwrap_or_default())
+            }
+        }
+        IdStrategy::Template(template) => fill_id_template(template, object, context)?,
+    })
 }
 
-fn canonicalise_object(object: &Map<String, Value>) -> String {
+/// Returns the value of the property of `object` named `key`, as written or
+/// expanded.
+fn key_value<'o, 'a>(
+    object: &'o JsonObject<'a>,
+    context: Option<&ActiveContext>,
+    key: &str,
+) -> Option<&'o JsonRef<'a>> {
+    object.get(key).or_else(|| {
+        object
+            .iter()
+            .find(|(name, _)| !name.starts_with('@') && expand_term(context, name) == key)
+            .map(|(_, value)| value)
+    })
+}
+
+/// Returns the expanded types of `object`, sorted.
+fn object_types(object: &JsonObject<'_>, context: Option<&ActiveContext>) -> BTreeSet<String> {
+    match object.get("@type") {
+        Some(JsonRef::String(value)) => BTreeSet::from([expand_term(context, value)]),
+        Some(JsonRef::Array(entries)) => entries
+            .iter()
+            .filter_map(JsonRef::as_str)
+            .map(|value| expand_term(context, value))
+            .collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+/// Fills the placeholders of an [`IdStrategy::Template`] from `object`.
+fn fill_id_template(
+    template: &str,
+    object: &JsonObject<'_>,
+    context: Option<&ActiveContext>,
+) -> Result<String> {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| {
+            ToolError::JsonLd(format!("unclosed placeholder in id template '{template}'"))
+        })?;
+        let name = &rest[start + 1..start + end];
+        let value = if name == "type" {
+            object_types(object, context)
+                .into_iter()
+                .next()
+                .map(|type_name| {
+                    let local = type_name.rsplit(['/', '#', ':']).next().unwrap_or_default();
+                    local.to_string()
+                })
+        } else {
+            key_value(object, context, name).and_then(template_text)
+        };
+        let value = value.filter(|value| !value.is_empty()).ok_or_else(|| {
+            ToolError::JsonLd(format!(
+                "id template '{template}' needs '{name}', which a node object without @id lacks"
+            ))
+        })?;
+        filled.push_str(&rest[..start]);
+        filled.push_str(&percent_encode(&value));
+        rest = &rest[start + end + 1..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+/// Text of a property value filling a template placeholder: the first entry
+/// of arrays, and the `@value` or `@id` of objects.
+fn template_text(value: &JsonRef<'_>) -> Option<String> {
+    match value {
+        JsonRef::String(text) => Some(text.to_string()),
+        JsonRef::Number(_) | JsonRef::Bool(_) => Some(value.to_string()),
+        JsonRef::Array(items) => items.first().and_then(template_text),
+        JsonRef::Object(map) => map
+            .get("@value")
+            .or_else(|| map.get("@id"))
+            .and_then(template_text),
+        JsonRef::Null => None,
+    }
+}
+
+/// Percent-encodes the characters of `value` other than letters, digits,
+/// and `-._~`.
+pub(crate) fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for character in value.chars() {
+        if character.is_alphanumeric() || matches!(character, '-' | '.' | '_' | '~') {
+            encoded.push(character);
+        } else {
+            let mut bytes = [0; 4];
+            for byte in character.encode_utf8(&mut bytes).bytes() {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+    encoded
+}
+
+fn canonicalise_object(object: &JsonObject<'_>) -> String {
     let mut ordered = BTreeMap::new();
     for (key, value) in object {
-        if matches!(key.as_str(), "@context" | "@graph") {
+        if matches!(key.as_ref(), "@context" | "@graph") {
             continue;
         }
         ordered.insert(key, value);
@@ -515,10 +1043,32 @@ fn canonicalise_object(object: &Map<String, Value>) -> String {
     serde_json::to_string(&ordered).unwrap_or_default()
 }
 
+/// JSON-LD specification version targeted by the serialiser.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonLdVersion {
+    /// JSON-LD 1.0 for legacy consumers: the context loses its `@version`
+    /// entry, compaction runs in 1.0 processing mode (so contexts relying on
+    /// 1.1 features are rejected), and `@type` is always written as an array.
+    V1_0,
+    /// JSON-LD 1.1.
+    #[default]
+    V1_1,
+}
+
 /// Serialises a collection of nodes back into a JSON-LD document.
 pub fn nodes_to_jsonld(nodes: &[Node], context: Option<Value>) -> Result<Value> {
+    nodes_to_jsonld_versioned(nodes, context, JsonLdVersion::default())
+}
+
+/// Serialises a collection of nodes into a JSON-LD document restricted to
+/// the constructs of `version`.
+pub fn nodes_to_jsonld_versioned(
+    nodes: &[Node],
+    context: Option<Value>,
+    version: JsonLdVersion,
+) -> Result<Value> {
     let mut default_graph: Vec<Value> = Vec::new();
-    let mut named_graphs: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    let mut named_graphs: BTreeMap<Iri, Vec<Value>> = BTreeMap::new();
 
     for node in nodes {
         let entry = node_to_json(node);
@@ -532,70 +1082,718 @@ pub fn nodes_to_jsonld(nodes: &[Node], context: Option<Value>) -> Result<Value>
     let mut graph_entries = default_graph;
     for (graph, nodes) in named_graphs {
         let mut container = Map::new();
-        container.insert("@id".to_string(), Value::String(graph));
+        container.insert("@id".to_string(), Value::String(graph.into()));
         container.insert("@graph".to_string(), Value::Array(nodes));
         graph_entries.push(Value::Object(container));
     }
 
+    finish_document(graph_entries, context, version)
+}
+
+/// Serialises nodes like [`nodes_to_jsonld_versioned`], compacting each
+/// named graph in `graph_contexts` with its own context instead of
+/// `context`. Such graph objects declare their context, replacing the
+/// document's, so each is read back with the vocabulary it was written in.
+pub fn nodes_to_jsonld_with_graph_contexts(
+    nodes: &[Node],
+    context: Option<Value>,
+    graph_contexts: &BTreeMap<Iri, Value>,
+    version: JsonLdVersion,
+) -> Result<Value> {
+    let mut rest: Vec<Node> = Vec::new();
+    let mut graphs: BTreeMap<&Iri, Vec<Node>> = BTreeMap::new();
+    for node in nodes {
+        match node
+            .graph
+            .as_ref()
+            .filter(|graph| graph_contexts.contains_key(*graph))
+        {
+            // Compacted as the default graph of a document of their own.
+            Some(graph) => graphs.entry(graph).or_default().push(Node {
+                graph: None,
+                ..node.clone()
+            }),
+            None => rest.push(node.clone()),
+        }
+    }
+    let scoped = context.is_some();
+    let document = nodes_to_jsonld_versioned(&rest, context, version)?;
+    let document_context = document.get("@context").cloned();
+    let mut entries = top_level_entries(document);
+    for (graph, nodes) in graphs {
+        let mut graph_context = graph_contexts[graph].clone();
+        let members = top_level_entries(nodes_to_jsonld_versioned(
+            &nodes,
+            Some(graph_context.clone()),
+            version,
+        )?);
+        if version == JsonLdVersion::V1_0 {
+            strip_version(&mut graph_context);
+        }
+        let mut container = Map::new();
+        container.insert(
+            "@context".to_string(),
+            if scoped {
+                Value::Array(vec![Value::Null, graph_context])
+            } else {
+                graph_context
+            },
+        );
+        container.insert("@id".to_string(), Value::String(graph.to_string()));
+        container.insert("@graph".to_string(), Value::Array(members));
+        entries.push(Value::Object(container));
+    }
+
+    let mut document = Map::new();
+    if let Some(context) = document_context {
+        document.insert("@context".to_string(), context);
+    }
+    document.insert("@graph".to_string(), Value::Array(entries));
+    Ok(Value::Object(document))
+}
+
+/// Wraps top-level entries in a `@graph` document and compacts it with
+/// `context` according to `version`.
+fn finish_document(
+    graph_entries: Vec<Value>,
+    context: Option<Value>,
+    version: JsonLdVersion,
+) -> Result<Value> {
     let mut document = Map::new();
     document.insert("@graph".to_string(), Value::Array(graph_entries));
     let expanded = Value::Object(document);
 
-    if let Some(context) = context {
-        compact_with_context(expanded, context)
-    } else {
-        Ok(expanded)
+    match (context, version) {
+        (Some(context), version) => compact_versioned(expanded, context, version),
+        (None, JsonLdVersion::V1_1) => Ok(expanded),
+        (None, JsonLdVersion::V1_0) => {
+            let mut expanded = expanded;
+            wrap_types(&mut expanded, &["@type".to_string()]);
+            Ok(expanded)
+        }
     }
 }
 
-fn node_to_json(node: &Node) -> Value {
+/// Rewrites a JSON-LD document against a new context without reading it
+/// into nodes: the document is expanded with the contexts it declares and
+/// compacted again with `context`, restricted to `version`.
+///
+/// Remote contexts, in the document or in `context`, must have been
+/// resolved first.
+pub fn recompact(document: Value, context: Value, version: JsonLdVersion) -> Result<Value> {
+    compact_versioned(document, context, version)
+}
+
+/// Compacts `document` with `context` according to `version`.
+fn compact_versioned(document: Value, mut context: Value, version: JsonLdVersion) -> Result<Value> {
+    match version {
+        JsonLdVersion::V1_1 => compact_with_context(document, context, ProcessingMode::JsonLd1_1),
+        JsonLdVersion::V1_0 => {
+            strip_version(&mut context);
+            let type_keys = type_aliases(&context);
+            let mut compacted = compact_with_context(document, context, ProcessingMode::JsonLd1_0)?;
+            wrap_types(&mut compacted, &type_keys);
+            Ok(compacted)
+        }
+    }
+}
+
+/// Serialises `nodes` as a JSON-LD document shaped by a JSON-LD frame.
+///
+/// Nodes matching the frame become the top-level entries of their graph and
+/// referenced nodes are embedded into them. Supported frame features are
+/// `@id` and `@type` matching (`"@type": {}` matches any typed node),
+/// `@embed` (`@once`, the default, `@always`, or `@never`), `@explicit`, and
+/// nested frames per property; a frame without `@id` or `@type` matches every
+/// node. References whose target is unknown, already on the embedding path,
+/// or not matched by the nested frame stay `{"@id": ...}` references.
+///
+/// The output is compacted with `context` or, when it is absent, the frame's
+/// own `@context`.
+pub fn frame_nodes(
+    nodes: &[Node],
+    frame: &Value,
+    context: Option<Value>,
+    version: JsonLdVersion,
+) -> Result<Value> {
+    let Value::Object(frame_map) = frame else {
+        return Err(ToolError::JsonLd(
+            "a JSON-LD frame must be an object".into(),
+        ));
+    };
+    let frame_context = match frame_map.get("@context") {
+        Some(context) => Some(parse_context_value(context, None)?),
+        None => None,
+    };
+    let frame = Frame::parse(frame_map, frame_context.as_ref(), &Frame::default())?;
+    let context = context.or_else(|| frame_map.get("@context").cloned());
+
+    let mut graphs: BTreeMap<Option<&str>, Vec<&Node>> = BTreeMap::new();
+    for node in nodes {
+        graphs.entry(node.graph.as_deref()).or_default().push(node);
+    }
+
+    let mut graph_entries = Vec::new();
+    for (graph, members) in graphs {
+        let mut framer = Framer {
+            index: members
+                .iter()
+                .map(|node| (node.id.as_str(), *node))
+                .collect(),
+            embedded: BTreeSet::new(),
+        };
+        let entries: Vec<Value> = members
+            .iter()
+            .filter(|node| frame.matches(node))
+            .map(|node| framer.embed(node, &frame, &mut vec![node.id.as_str()]))
+            .collect();
+
+        match graph {
+            None => graph_entries.extend(entries),
+            Some(_) if entries.is_empty() => {}
+            Some(graph) => {
+                let mut container = Map::new();
+                container.insert("@id".to_string(), Value::String(graph.to_string()));
+                container.insert("@graph".to_string(), Value::Array(entries));
+                graph_entries.push(Value::Object(container));
+            }
+        }
+    }
+
+    finish_document(graph_entries, context, version)
+}
+
+/// How often a node matched by a frame is embedded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Embed {
+    /// Embed the first reference to a node and keep the others as references.
+    #[default]
+    Once,
+    /// Embed every reference to a node.
+    Always,
+    /// Keep every reference as a reference.
+    Never,
+}
+
+/// A parsed JSON-LD frame with expanded identifiers and predicates.
+#[derive(Debug, Default)]
+struct Frame {
+    ids: Option<BTreeSet<String>>,
+    /// Required types; an empty set matches any typed node.
+    types: Option<BTreeSet<String>>,
+    embed: Embed,
+    explicit: bool,
+    properties: BTreeMap<String, Frame>,
+}
+
+impl Frame {
+    /// Parses a frame object. `@embed` and `@explicit` are inherited from
+    /// `parent` unless the frame sets them.
+    fn parse(
+        map: &Map<String, Value>,
+        context: Option<&ActiveContext>,
+        parent: &Frame,
+    ) -> Result<Frame> {
+        let mut frame = parent.open();
+        for (key, value) in map {
+            match key.as_str() {
+                "@context" => {}
+                "@id" => {
+                    let ids = frame_strings(key, value)?;
+                    frame.ids = Some(
+                        ids.iter()
+                            .map(|id| {
+                                context
+                                    .and_then(|ctx| expand_compact_iri(ctx, id))
+                                    .unwrap_or_else(|| id.clone())
+                            })
+                            .collect(),
+                    );
+                }
+                "@type" => {
+                    let types = match value {
+                        Value::Object(wildcard) if wildcard.is_empty() => Vec::new(),
+                        other => frame_strings(key, other)?,
+                    };
+                    frame.types = Some(
+                        types
+                            .iter()
+                            .map(|type_name| expand_term(context, type_name))
+                            .collect(),
+                    );
+                }
+                "@embed" => {
+                    frame.embed = match value {
+                        Value::Bool(true) => Embed::Once,
+                        Value::Bool(false) => Embed::Never,
+                        Value::String(mode) if mode == "@once" => Embed::Once,
+                        Value::String(mode) if mode == "@always" => Embed::Always,
+                        Value::String(mode) if mode == "@never" => Embed::Never,
+                        other => {
+                            return Err(ToolError::JsonLd(format!("invalid @embed value {other}")));
+                        }
+                    };
+                }
+                "@explicit" => {
+                    frame.explicit = value.as_bool().ok_or_else(|| {
+                        ToolError::JsonLd(format!("invalid @explicit value {value}"))
+                    })?;
+                }
+                keyword if keyword.starts_with('@') => {
+                    return Err(ToolError::JsonLd(format!(
+                        "unsupported frame keyword '{keyword}'"
+                    )));
+                }
+                term => {
+                    let nested = match value {
+                        Value::Object(nested) => nested,
+                        Value::Array(items) if items.len() == 1 && items[0].is_object() => {
+                            items[0].as_object().expect("checked above")
+                        }
+                        other => {
+                            return Err(ToolError::JsonLd(format!(
+                                "frame property '{term}' must hold a frame object, found {other}"
+                            )));
+                        }
+                    };
+                    let nested = Frame::parse(nested, context, &frame)?;
+                    frame.properties.insert(expand_term(context, term), nested);
+                }
+            }
+        }
+        Ok(frame)
+    }
+
+    /// Frame matching any node, inheriting the embedding flags of `self`.
+    fn open(&self) -> Frame {
+        Frame {
+            embed: self.embed,
+            explicit: self.explicit,
+            ..Frame::default()
+        }
+    }
+
+    fn matches(&self, node: &Node) -> bool {
+        let id_matches = self
+            .ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(node.id.as_str()));
+        let type_matches = self.types.as_ref().is_none_or(|types| {
+            if types.is_empty() {
+                !node.types.is_empty()
+            } else {
+                node.types
+                    .iter()
+                    .any(|type_name| types.contains(type_name.as_str()))
+            }
+        });
+        id_matches && type_matches
+    }
+}
+
+fn frame_strings(key: &str, value: &Value) -> Result<Vec<String>> {
+    let invalid = || ToolError::JsonLd(format!("invalid {key} value {value} in frame"));
+    match value {
+        Value::String(text) => Ok(vec![text.clone()]),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_str().map(str::to_string).ok_or_else(invalid))
+            .collect(),
+        _ => Err(invalid()),
+    }
+}
+
+/// Embeds the nodes of one graph into the node objects of a framed document.
+struct Framer<'a> {
+    index: BTreeMap<&'a str, &'a Node>,
+    /// Nodes already embedded somewhere in the graph.
+    embedded: BTreeSet<&'a str>,
+}
+
+impl<'a> Framer<'a> {
+    fn embed(&mut self, node: &'a Node, frame: &Frame, path: &mut Vec<&'a str>) -> Value {
+        let mut map = node_header(node);
+
+        let open = frame.open();
+        for (predicate, value) in &node.properties {
+            let nested = match frame.properties.get(predicate.as_str()) {
+                Some(nested) => nested,
+                None if frame.explicit => continue,
+                None => &open,
+            };
+            let json = match value {
+                PropertyValue::ObjectRef(target) => self.reference(target, nested, path),
+                PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => Value::Array(
+                    targets
+                        .iter()
+                        .map(|target| self.reference(target, nested, path))
+                        .collect(),
+                ),
+                PropertyValue::Array(ArrayValue::Mixed(items)) => Value::Array(
+                    items
+                        .iter()
+                        .map(|item| match item {
+                            ArrayItem::ObjectRef(target) => self.reference(target, nested, path),
+                            scalar => scalar.to_json(),
+                        })
+                        .collect(),
+                ),
+                other => other.to_json(),
+            };
+            map.insert(predicate.to_string(), ordered(node, predicate, json));
+        }
+
+        Value::Object(map)
+    }
+
+    fn reference(&mut self, target: &str, frame: &Frame, path: &mut Vec<&'a str>) -> Value {
+        let reference = || serde_json::json!({ "@id": target });
+        let Some(&node) = self.index.get(target) else {
+            return reference();
+        };
+        let repeated = frame.embed == Embed::Once && self.embedded.contains(target);
+        if frame.embed == Embed::Never || repeated || path.contains(&target) || !frame.matches(node)
+        {
+            return reference();
+        }
+
+        self.embedded.insert(node.id.as_str());
+        path.push(node.id.as_str());
+        let embedded = self.embed(node, frame, path);
+        path.pop();
+        embedded
+    }
+}
+
+/// Removes `@version` declarations from a local context definition.
+pub(crate) fn strip_version(context: &mut Value) {
+    match context {
+        Value::Object(map) => {
+            map.remove("@version");
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_version),
+        _ => {}
+    }
+}
+
+/// Where an IRI left unmapped by a context appears in a dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IriRole {
+    /// The IRI is a predicate, written as a property key.
+    Predicate,
+    /// The IRI is a type, written as an `@type` value.
+    Type,
+}
+
+impl std::fmt::Display for IriRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            IriRole::Predicate => "predicate",
+            IriRole::Type => "type",
+        })
+    }
+}
+
+/// A predicate or type IRI that a context can neither shorten to a term nor
+/// to a compact IRI, so compaction leaves it absolute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmappedIri {
+    pub iri: Iri,
+    pub role: IriRole,
+    /// First node using the IRI.
+    pub node: NodeId,
+}
+
+/// The terms, prefixes, and `@vocab` of a local context, used to check that
+/// it covers the predicates and types of a dataset before compacting it.
+pub struct ContextCoverage {
+    terms: BTreeSet<String>,
+    prefixes: Vec<String>,
+    vocab: Option<String>,
+}
+
+impl ContextCoverage {
+    /// Reads the definitions of `context`. Remote references must have been
+    /// resolved first.
+    pub fn new(context: &Value) -> Result<Self> {
+        let active = parse_context_value(context, None)?;
+        let marked = context_prefixes(context);
+        let prefixes = active
+            .term_map
+            .iter()
+            .filter(|(term, iri)| {
+                marked.contains_key(term.as_str())
+                    || iri.ends_with([':', '/', '?', '#', '[', ']', '@'])
+            })
+            .map(|(_, iri)| iri.clone())
+            .collect();
+        Ok(ContextCoverage {
+            terms: active.term_map.into_values().collect(),
+            prefixes,
+            vocab: active.vocab,
+        })
+    }
+
+    /// Tells whether compaction shortens `iri`: a term maps to it, or it
+    /// extends a prefix or the `@vocab`.
+    pub fn maps(&self, iri: &str) -> bool {
+        let extends = |namespace: &str| iri.len() > namespace.len() && iri.starts_with(namespace);
+        self.terms.contains(iri)
+            || self.prefixes.iter().any(|prefix| extends(prefix))
+            || self.vocab.as_deref().is_some_and(extends)
+    }
+
+    /// Returns the predicates and types of `nodes` the context leaves
+    /// unmapped, each once, sorted by role and IRI.
+    pub fn unmapped(&self, nodes: &[Node]) -> Vec<UnmappedIri> {
+        let mut unmapped: BTreeMap<(IriRole, &Iri), &NodeId> = BTreeMap::new();
+        for node in nodes {
+            let predicates = node.properties.keys().map(|iri| (IriRole::Predicate, iri));
+            let types = node.types.iter().map(|iri| (IriRole::Type, iri));
+            for key in predicates.chain(types) {
+                if !self.maps(key.1) {
+                    unmapped.entry(key).or_insert(&node.id);
+                }
+            }
+        }
+        unmapped
+            .into_iter()
+            .map(|((role, iri), node)| UnmappedIri {
+                iri: iri.clone(),
+                role,
+                node: node.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Returns the prefixes a context defines: terms whose IRI ends in `/` or
+/// `#`, or that are marked with `"@prefix": true`. Later objects of an array
+/// context override earlier ones.
+pub fn context_prefixes(context: &Value) -> BTreeMap<String, String> {
+    let definitions: Vec<&Map<String, Value>> = match context {
+        Value::Object(map) => vec![map],
+        Value::Array(items) => items.iter().filter_map(Value::as_object).collect(),
+        _ => Vec::new(),
+    };
+    let mut prefixes = BTreeMap::new();
+    for map in definitions {
+        for (term, definition) in map {
+            if term.starts_with('@') || term.contains(':') {
+                continue;
+            }
+            let (namespace, marked) = match definition {
+                Value::String(namespace) => (namespace.as_str(), false),
+                Value::Object(definition) => match definition.get("@id").and_then(Value::as_str) {
+                    Some(namespace) => (
+                        namespace,
+                        definition.get("@prefix") == Some(&Value::Bool(true)),
+                    ),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if marked || namespace.ends_with('/') || namespace.ends_with('#') {
+                prefixes.insert(term.clone(), namespace.to_string());
+            }
+        }
+    }
+    prefixes
+}
+
+/// Returns the term a local context defines for each IRI, with compact IRIs
+/// such as `schema:name` expanded, the first in order when several terms
+/// name the same IRI.
+pub fn context_terms(context: &Value) -> Result<BTreeMap<Iri, String>> {
+    let active = parse_context_value(context, None)?;
+    let mut terms = BTreeMap::new();
+    for (term, iri) in &active.term_map {
+        if !iri.starts_with('@') {
+            let iri = expand_term(Some(&active), iri);
+            terms.entry(Iri::from(iri.as_str())).or_insert(term.clone());
+        }
+    }
+    Ok(terms)
+}
+
+/// Returns `@type` together with any terms the context defines as aliases
+/// for it.
+fn type_aliases(context: &Value) -> Vec<String> {
+    let mut keys = vec!["@type".to_string()];
+    let definitions: Vec<&Map<String, Value>> = match context {
+        Value::Object(map) => vec![map],
+        Value::Array(items) => items.iter().filter_map(Value::as_object).collect(),
+        _ => Vec::new(),
+    };
+    for map in definitions {
+        for (term, definition) in map {
+            let target = match definition {
+                Value::String(target) => Some(target.as_str()),
+                Value::Object(definition) => definition.get("@id").and_then(Value::as_str),
+                _ => None,
+            };
+            if target == Some("@type") {
+                keys.push(term.clone());
+            }
+        }
+    }
+    keys
+}
+
+/// Rewrites single `@type` values of node objects as one-element arrays.
+/// Value objects keep their datatype as a string.
+fn wrap_types(value: &mut Value, type_keys: &[String]) {
+    match value {
+        Value::Object(map) => {
+            let is_value_object = map.contains_key("@value");
+            for (key, entry) in map.iter_mut() {
+                if key == "@context" {
+                    continue;
+                }
+                if !is_value_object && type_keys.contains(key) && entry.is_string() {
+                    *entry = Value::Array(vec![entry.take()]);
+                } else {
+                    wrap_types(entry, type_keys);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                wrap_types(item, type_keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Writes nodes arriving in `chunks` as a single JSON-LD document without
+/// holding more than one chunk in memory, returning the number of nodes
+/// written.
+///
+/// Each chunk is serialised with [`nodes_to_jsonld`] and its top-level
+/// entries are appended to the document's `@graph`, so a named graph split
+/// across chunks appears as several graph objects sharing the same `@id`.
+pub fn write_jsonld_stream<W, I>(
+    mut writer: W,
+    chunks: I,
+    mut context: Option<Value>,
+    version: JsonLdVersion,
+) -> Result<usize>
+where
+    W: Write,
+    I: IntoIterator<Item = Result<Vec<Node>>>,
+{
+    if let (Some(context), JsonLdVersion::V1_0) = (&mut context, version) {
+        strip_version(context);
+    }
+    writer.write_all(b"{")?;
+    if let Some(context) = &context {
+        writer.write_all(b"\n  \"@context\": ")?;
+        serde_json::to_writer(&mut writer, context)?;
+        writer.write_all(b",")?;
+    }
+    writer.write_all(b"\n  \"@graph\": [")?;
+
+    let mut node_count = 0;
+    let mut first = true;
+    for chunk in chunks {
+        let chunk = chunk?;
+        node_count += chunk.len();
+        let document = nodes_to_jsonld_versioned(&chunk, context.clone(), version)?;
+        for entry in top_level_entries(document) {
+            writer.write_all(if first { b"\n    " } else { b",\n    " })?;
+            serde_json::to_writer(&mut writer, &entry)?;
+            first = false;
+        }
+    }
+
+    writer.write_all(b"\n  ]\n}\n")?;
+    writer.flush()?;
+    Ok(node_count)
+}
+
+/// Returns the node and graph objects of a document produced by
+/// [`nodes_to_jsonld`], whose compaction may have collapsed a single-entry
+/// `@graph` into the top-level object.
+fn top_level_entries(document: Value) -> Vec<Value> {
+    match document {
+        Value::Object(mut map) => {
+            map.remove("@context");
+            match map.remove("@graph") {
+                Some(Value::Array(entries)) if map.is_empty() => entries,
+                Some(entry) if map.is_empty() => vec![entry],
+                Some(graph) => {
+                    map.insert("@graph".to_string(), graph);
+                    vec![Value::Object(map)]
+                }
+                None if map.is_empty() => Vec::new(),
+                None => vec![Value::Object(map)],
+            }
+        }
+        Value::Array(entries) => entries,
+        other => vec![other],
+    }
+}
+
+pub(crate) fn node_to_json(node: &Node) -> Value {
+    let mut map = node_header(node);
+    for (predicate, value) in &node.properties {
+        map.insert(
+            predicate.to_string(),
+            ordered(node, predicate, value.to_json()),
+        );
+    }
+
+    Value::Object(map)
+}
+
+/// Wraps `json`, the value of `predicate`, in a `@list` object when it is
+/// one of the node's [`Node::lists`], so its order is kept.
+fn ordered(node: &Node, predicate: &Iri, json: Value) -> Value {
+    if !node.lists.contains(predicate) {
+        return json;
+    }
+    let items = match json {
+        Value::Array(items) => items,
+        single => vec![single],
+    };
+    let mut list = Map::new();
+    list.insert("@list".to_string(), Value::Array(items));
+    Value::Object(list)
+}
+
+/// Returns the `@id` and `@type` entries of a node object.
+fn node_header(node: &Node) -> Map<String, Value> {
     let mut map = Map::new();
-    map.insert("@id".to_string(), Value::String(node.id.clone()));
+    map.insert("@id".to_string(), Value::String(node.id.to_string()));
 
     if !node.types.is_empty() {
         if node.types.len() == 1 {
             map.insert(
                 "@type".to_string(),
-                Value::String(node.types.iter().next().unwrap().clone()),
+                Value::String(node.types.iter().next().unwrap().to_string()),
             );
         } else {
             map.insert(
                 "@type".to_string(),
-                Value::Array(node.types.iter().cloned().map(Value::String).collect()),
+                Value::Array(
+                    node.types
+                        .iter()
+                        .map(|type_name| Value::String(type_name.to_string()))
+                        .collect(),
+                ),
             );
         }
     }
 
-    for (predicate, value) in &node.properties {
-        let json_value = match value {
-            PropertyValue::Scalar(scalar) => scalar.to_json(),
-            PropertyValue::ObjectRef(target) => {
-                let mut ref_map = Map::new();
-                ref_map.insert("@id".to_string(), Value::String(target.clone()));
-                Value::Object(ref_map)
-            }
-            PropertyValue::Array(ArrayValue::Scalars(values)) => {
-                Value::Array(values.iter().map(ScalarValue::to_json).collect())
-            }
-            PropertyValue::Array(ArrayValue::ObjectRefs(values)) => Value::Array(
-                values
-                    .iter()
-                    .map(|target| {
-                        let mut ref_map = Map::new();
-                        ref_map.insert("@id".to_string(), Value::String(target.clone()));
-                        Value::Object(ref_map)
-                    })
-                    .collect(),
-            ),
-        };
-
-        map.insert(predicate.clone(), json_value);
-    }
-
-    Value::Object(map)
+    map
 }
 
-fn compact_with_context(expanded: Value, context: Value) -> Result<Value> {
+fn compact_with_context(
+    expanded: Value,
+    context: Value,
+    processing_mode: ProcessingMode,
+) -> Result<Value> {
     let document = JsonSyntaxValue::from_serde_json(expanded);
     let remote_document = RemoteDocument::new(None, None, document);
 
@@ -606,7 +1804,10 @@ fn compact_with_context(expanded: Value, context: Value) -> Result<Value> {
     let context_reference = RemoteContextReference::Loaded(remote_context);
 
     let loader = NoLoader;
-    let options = Options::default();
+    let options = Options {
+        processing_mode,
+        ..Options::default()
+    };
 
     let compacted = block_on(remote_document.compact_using(context_reference, &loader, options))
         .map_err(|err| ToolError::JsonLd(err.to_string()))?;
@@ -1,4 +1,18 @@
+pub mod atomic;
+pub mod coercion;
+pub mod csv_dialect;
+pub mod csv_read;
+pub mod csv_write;
 pub mod excel_read;
+pub mod excel_update;
 pub mod excel_write;
+pub(crate) mod json_ref;
 pub mod jsonld;
+pub mod nodes_json;
+pub mod parquet_write;
 pub mod rdf;
+pub mod remote_context;
+pub mod sql;
+pub(crate) mod tabular;
+pub mod text_encoding;
+pub(crate) mod typed_cells;
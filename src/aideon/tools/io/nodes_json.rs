@@ -0,0 +1,102 @@
+//! The node model itself as JSON.
+//!
+//! Every other representation converts nodes into its own shape and back;
+//! this one is the serde serialisation of [`Node`], so it keeps exactly what
+//! the converters read and gives downstream tooling a stable intermediate
+//! format to consume or produce:
+//!
+//! ```json
+//! {
+//!   "format": "aideon-nodes",
+//!   "version": 1,
+//!   "nodes": [
+//!     {
+//!       "id": "https://example.com/people/1",
+//!       "graph": null,
+//!       "types": ["https://schema.org/Person"],
+//!       "properties": {
+//!         "https://schema.org/name": {
+//!           "variant": "Scalar",
+//!           "value": { "type": "String", "value": "Ada" }
+//!         }
+//!       }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The layout only changes with [`VERSION`]; documents of another version
+//! are rejected rather than misread.
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::model::Node;
+
+/// Value of the `format` member of node documents.
+pub const FORMAT: &str = "aideon-nodes";
+
+/// Version of the node document layout written and understood.
+pub const VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct NodeDocument<'a> {
+    format: &'static str,
+    version: u32,
+    nodes: &'a [Node],
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OwnedNodeDocument {
+    format: String,
+    version: u32,
+    nodes: Vec<Node>,
+}
+
+/// Writes `nodes` to `path` as a node document.
+pub fn write_nodes_json(path: &Path, nodes: &[Node]) -> Result<()> {
+    atomic::write_atomically(path, |partial| {
+        write_nodes_json_to(
+            std::io::BufWriter::new(std::fs::File::create(partial)?),
+            nodes,
+        )
+    })
+}
+
+/// Writes `nodes` to `writer` as a node document.
+pub fn write_nodes_json_to<W: Write>(mut writer: W, nodes: &[Node]) -> Result<()> {
+    let document = NodeDocument {
+        format: FORMAT,
+        version: VERSION,
+        nodes,
+    };
+    serde_json::to_writer_pretty(&mut writer, &document)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the nodes of the node document at `path`.
+pub fn read_nodes_json(path: &Path) -> Result<Vec<Node>> {
+    read_nodes_json_from(std::io::BufReader::new(std::fs::File::open(path)?))
+}
+
+/// Reads the nodes of the node document held by `reader`, failing on
+/// documents of another format or version.
+pub fn read_nodes_json_from<R: Read>(reader: R) -> Result<Vec<Node>> {
+    let document: OwnedNodeDocument = serde_json::from_reader(reader)?;
+    if document.format != FORMAT || document.version != VERSION {
+        return Err(serde_json::Error::custom(format!(
+            "expected a {FORMAT} document of version {VERSION}, found {} version {}",
+            document.format, document.version
+        ))
+        .into());
+    }
+    Ok(document.nodes)
+}
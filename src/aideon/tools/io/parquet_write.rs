@@ -0,0 +1,209 @@
+//! Parquet files of flattened tables, for analytics tools such as DuckDB and
+//! pandas.
+//!
+//! Every table of a workbook is written as one `.parquet` file inside a
+//! directory, named like the files of
+//! [`csv_write`](crate::aideon::tools::io::csv_write). Each file holds a
+//! single row group of nullable columns named by the table's headers, and
+//! blank cells are nulls. The `scalar` columns declared in `Metadata` hold
+//! their literals, without the quotes and value objects of the cell
+//! encoding, and those of a single XML Schema datatype get an Arrow type:
+//! `Int64` for integers, `Float64` for doubles, `Boolean`, `Date32` for
+//! dates, and `Timestamp` in microseconds without a timezone for
+//! date-times, as long as every cell holds a literal of it. Other columns,
+//! `xsd:decimal` ones among them so no digit is lost, are UTF-8 strings;
+//! those holding ids, arrays, and references keep the text a CSV file would
+//! hold. Pages are Snappy-compressed, which every Parquet reader
+//! understands.
+//!
+//! Files are written with the Apache Arrow `parquet` crate, which needs the
+//! `parquet` feature; without it, [`write_workbook`] and [`write_table_to`]
+//! fail.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::flatten::{
+    CONTEXT_SHEET, DATASET_SHEET, ENTITIES_SHEET, METADATA_SHEET, SheetTable, WorkbookData,
+};
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::io::excel_write::{LiteralColumns, literal_columns};
+
+/// Writes every table of `workbook` as a Parquet file inside `dir`, creating
+/// the directory when necessary, named like the files of
+/// [`csv_write::write_workbook`](crate::aideon::tools::io::csv_write::write_workbook).
+/// Each file is replaced whole, see [`atomic`].
+pub fn write_workbook(dir: &Path, workbook: &WorkbookData) -> Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let literals = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == METADATA_SHEET)
+        .map(literal_columns)
+        .unwrap_or_default();
+    for table in &workbook.tables {
+        atomic::write_atomically(&table_path(dir, &table.sheet_name), |partial| {
+            arrow::write_table(File::create(partial)?, table, &literals)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Writes `table` as a Parquet file to `writer`. Without the workbook's
+/// `Metadata`, every column is a UTF-8 string column holding the text of
+/// its cells.
+pub fn write_table_to<W: Write + Send>(writer: W, table: &SheetTable) -> Result<()> {
+    arrow::write_table(writer, table, &LiteralColumns::new())
+}
+
+/// Returns the Parquet file that stores the sheet called `sheet_name`.
+pub fn table_path(dir: &Path, sheet_name: &str) -> PathBuf {
+    dir.join(table_file_name(sheet_name))
+}
+
+/// Returns the name of the Parquet file that stores the sheet called
+/// `sheet_name`, such as `entities.parquet` for the index sheet.
+pub fn table_file_name(sheet_name: &str) -> String {
+    let file_name = match sheet_name {
+        ENTITIES_SHEET => "entities",
+        METADATA_SHEET => "metadata",
+        CONTEXT_SHEET => "context",
+        DATASET_SHEET => "dataset",
+        other => other,
+    };
+    format!("{file_name}.parquet")
+}
+
+#[cfg(feature = "parquet")]
+mod arrow {
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use arrow_array::{
+        ArrayRef, BooleanArray, Date32Array, Float64Array, Int64Array, RecordBatch, StringArray,
+        TimestampMicrosecondArray,
+    };
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use chrono::NaiveDate;
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+
+    use crate::aideon::tools::error::{Result, ToolError};
+    use crate::aideon::tools::flatten::SheetTable;
+    use crate::aideon::tools::io::excel_write::LiteralColumns;
+    use crate::aideon::tools::io::typed_cells::{
+        ColumnType, TypedCell, TypedColumn, typed_columns,
+    };
+
+    pub(super) fn write_table<W: Write + Send>(
+        writer: W,
+        table: &SheetTable,
+        literals: &LiteralColumns,
+    ) -> Result<()> {
+        let typed = typed_columns(table, literals);
+        let schema = Arc::new(Schema::new(
+            table
+                .columns
+                .iter()
+                .zip(&typed)
+                .map(|(column, typed)| Field::new(column, data_type(typed.column_type), true))
+                .collect::<Vec<_>>(),
+        ));
+        let columns: Vec<ArrayRef> = typed.iter().map(array).collect();
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_created_by(format!(
+                "aideon-tools version {}",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .build();
+
+        let mut writer = ArrowWriter::try_new(writer, schema.clone(), Some(properties))
+            .map_err(parquet_error)?;
+        if !table.rows.is_empty() {
+            let batch = RecordBatch::try_new(schema, columns)
+                .map_err(|err| ToolError::Parquet(err.to_string()))?;
+            writer.write(&batch).map_err(parquet_error)?;
+        }
+        writer.close().map_err(parquet_error)?;
+        Ok(())
+    }
+
+    /// Arrow type of the columns of `column_type`.
+    fn data_type(column_type: ColumnType) -> DataType {
+        match column_type {
+            ColumnType::Integer => DataType::Int64,
+            ColumnType::Real => DataType::Float64,
+            ColumnType::Boolean => DataType::Boolean,
+            ColumnType::Date => DataType::Date32,
+            ColumnType::DateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+            ColumnType::Numeric | ColumnType::Text => DataType::Utf8,
+        }
+    }
+
+    /// Builds the Arrow array of `column`, whose cells all fit its type.
+    fn array(column: &TypedColumn) -> ArrayRef {
+        let cells = column.cells.iter();
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+        match column.column_type {
+            ColumnType::Integer => Arc::new(Int64Array::from_iter(cells.map(|cell| match cell {
+                TypedCell::Integer(number) => Some(*number),
+                _ => None,
+            }))),
+            ColumnType::Real => Arc::new(Float64Array::from_iter(cells.map(|cell| match cell {
+                TypedCell::Real(number) => Some(*number),
+                _ => None,
+            }))),
+            ColumnType::Boolean => {
+                Arc::new(BooleanArray::from_iter(cells.map(|cell| match cell {
+                    TypedCell::Boolean(flag) => Some(*flag),
+                    _ => None,
+                })))
+            }
+            ColumnType::Date => Arc::new(Date32Array::from_iter(cells.map(|cell| match cell {
+                TypedCell::Date(date) => i32::try_from((*date - epoch).num_days()).ok(),
+                _ => None,
+            }))),
+            ColumnType::DateTime => Arc::new(TimestampMicrosecondArray::from_iter(cells.map(
+                |cell| match cell {
+                    TypedCell::DateTime(datetime) => Some(datetime.and_utc().timestamp_micros()),
+                    _ => None,
+                },
+            ))),
+            ColumnType::Numeric | ColumnType::Text => {
+                Arc::new(StringArray::from_iter(cells.map(|cell| match cell {
+                    TypedCell::Text(text) => Some(text.as_str()),
+                    _ => None,
+                })))
+            }
+        }
+    }
+
+    fn parquet_error(err: parquet::errors::ParquetError) -> ToolError {
+        ToolError::Parquet(err.to_string())
+    }
+}
+
+#[cfg(not(feature = "parquet"))]
+mod arrow {
+    use std::io::Write;
+
+    use crate::aideon::tools::error::{Result, ToolError};
+    use crate::aideon::tools::flatten::SheetTable;
+    use crate::aideon::tools::io::excel_write::LiteralColumns;
+
+    pub(super) fn write_table<W: Write + Send>(
+        _writer: W,
+        _table: &SheetTable,
+        _literals: &LiteralColumns,
+    ) -> Result<()> {
+        Err(ToolError::Parquet(
+            "Parquet output requires the `parquet` feature".into(),
+        ))
+    }
+}
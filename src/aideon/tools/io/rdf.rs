@@ -1,143 +1,574 @@
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs::File;
+use std::io::{Read, Write};
 use std::path::Path;
 
 pub use oxigraph::io::{JsonLdProfileSet, RdfFormat};
-use oxigraph::io::{RdfParser, RdfSerializer};
-use oxigraph::model::{BlankNode, GraphName, Literal, NamedNode, NamedOrBlankNode, Quad, Term};
+use oxigraph::io::{RdfParseError, RdfParser, RdfSerializer, ReaderQuadParser};
+pub use oxigraph::model::Quad;
+use oxigraph::model::{BlankNode, GraphName, Literal, NamedNode, NamedOrBlankNode, Term};
+use tracing::{Level, Span, debug};
 
+use crate::aideon::tools::equivalence::is_blank;
 use crate::aideon::tools::error::{Result, ToolError};
-use crate::aideon::tools::model::{ArrayValue, Node, PropertyValue, ScalarValue};
+use crate::aideon::tools::interrupt;
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::model::{
+    ArrayItem, ArrayValue, Interner, Iri, Node, NodeId, PropertyValue, ScalarValue, XSD_BOOLEAN,
+    XSD_DOUBLE, XSD_INTEGER, XSD_STRING,
+};
+use crate::aideon::tools::rdfc;
+use crate::aideon::tools::report::NONE_LABEL;
+use crate::aideon::tools::telemetry::{self, Counter};
+use crate::aideon::tools::validate::{IssueKind, Severity, ValidationIssue, ValidationReport};
 
-const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
-const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
-const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
-const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
-const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+pub(crate) const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+/// Predicate holding the item of an RDF collection cell.
+pub const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+/// Predicate linking an RDF collection cell to the next one.
+pub const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+/// The empty RDF collection, ending every list.
+pub const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+const RDF_LIST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#List";
+/// Class of reified statements.
+pub const RDF_STATEMENT: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#Statement";
+/// Predicate naming the subject of a reified statement.
+pub const RDF_SUBJECT: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#subject";
+/// Predicate naming the predicate of a reified statement.
+pub const RDF_PREDICATE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate";
+/// Predicate naming the object of a reified statement.
+pub const RDF_OBJECT: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#object";
 
 /// Loads an RDF graph from the provided path and converts it into the internal
 /// node representation.
 pub fn read_rdf(path: &Path, format: Option<RdfFormat>) -> Result<Vec<Node>> {
-    let format = format.or_else(|| detect_format(path)).ok_or_else(|| {
-        ToolError::Rdf(format!(
-            "unable to infer RDF format from extension for file {}",
-            path.display()
-        ))
-    })?;
+    let format = resolve_format(path, format)?;
+    read_rdf_from(File::open(path)?, format)
+}
 
-    let file = File::open(path)?;
-    let parser = RdfParser::from_format(format);
-    let quad_parser = parser.for_reader(file);
+/// Loads an RDF graph serialised as `format` from `reader`, like
+/// [`read_rdf`]. RDF collections are read as ordered arrays, see
+/// [`collapse_lists`].
+pub fn read_rdf_from<R: Read>(reader: R, format: RdfFormat) -> Result<Vec<Node>> {
+    read_quads(reader, format, None)
+}
+
+/// Loads an RDF graph like [`read_rdf_from`], leaving out the statements
+/// that cannot be parsed instead of failing at the first and reporting each
+/// as an [`IssueKind::InvalidStatement`] error. Parsers resume at the next
+/// statement they recognise; RDF/XML and JSON-LD documents are left at
+/// their first error.
+pub fn read_rdf_keep_going_from<R: Read>(
+    reader: R,
+    format: RdfFormat,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    let mut report = ValidationReport::default();
+    let nodes = read_quads(reader, format, Some(&mut report))?;
+    Ok((nodes, report))
+}
 
-    let mut nodes: BTreeMap<(Option<String>, String), Node> = BTreeMap::new();
+/// Reads the quads of `reader`, adding the statements that cannot be parsed
+/// to `skipped`, when given, instead of failing at them.
+fn read_quads<R: Read>(
+    reader: R,
+    format: RdfFormat,
+    mut skipped: Option<&mut ValidationReport>,
+) -> Result<Vec<Node>> {
+    let quad_parser = RdfParser::from_format(format).for_reader(reader);
+
+    let mut nodes: BTreeMap<(Option<Iri>, NodeId), Node> = BTreeMap::new();
+    let mut interner = Interner::new();
 
     for quad_result in quad_parser {
-        let quad = quad_result.map_err(|err| ToolError::Rdf(err.to_string()))?;
+        interrupt::check(nodes.len())?;
+        let quad = match (quad_result, skipped.as_deref_mut()) {
+            (Ok(quad), _) => quad,
+            (Err(RdfParseError::Syntax(err)), Some(report)) => {
+                report.issues.push(ValidationIssue {
+                    severity: Severity::Error,
+                    kind: IssueKind::InvalidStatement,
+                    node: NodeId::from(""),
+                    graph: None,
+                    predicate: None,
+                    message: format!("statement left out: {err}"),
+                });
+                continue;
+            }
+            (Err(err), _) => return Err(ToolError::Rdf(err.to_string())),
+        };
 
-        let subject_id = subject_to_id(&quad.subject)?;
-        let predicate = quad.predicate.as_str().to_string();
-        let graph_name = graph_name_to_string(&quad.graph_name)?;
+        let subject_id = intern_subject(&mut interner, &quad.subject);
+        let graph_name = intern_graph_name(&mut interner, &quad.graph_name);
         let node = nodes
             .entry((graph_name.clone(), subject_id.clone()))
             .or_insert_with(|| Node::with_graph(subject_id.clone(), graph_name.clone()));
         node.set_graph(graph_name);
+        apply_quad(node, &quad, &mut interner)?;
+    }
+
+    Ok(collapse_lists(nodes.into_values().collect()))
+}
 
-        if predicate == RDF_TYPE {
-            if let Term::NamedNode(object) = &quad.object {
-                node.types.insert(object.as_str().to_string());
+/// Replaces the RDF collections of `nodes` with ordered arrays, recording
+/// their predicates in [`Node::lists`].
+///
+/// A property whose only value is the head of an `rdf:first`/`rdf:rest`
+/// chain of blank nodes, each referred to once and stating nothing else,
+/// takes the items of the list, and the chain is dropped. A property whose
+/// only value is `rdf:nil` becomes an empty list. Shared, cyclic, or
+/// otherwise malformed chains and lists of lists stay as they are.
+pub fn collapse_lists(mut nodes: Vec<Node>) -> Vec<Node> {
+    type Key = (Option<Iri>, NodeId);
+    let is_cell = |node: &Node| {
+        is_blank(&node.id)
+            && node
+                .types
+                .iter()
+                .all(|type_name| type_name.as_str() == RDF_LIST)
+            && node.properties.len() == 2
+            && matches!(
+                node.properties.get(RDF_FIRST),
+                Some(PropertyValue::Scalar(_) | PropertyValue::ObjectRef(_))
+            )
+            && matches!(
+                node.properties.get(RDF_REST),
+                Some(PropertyValue::ObjectRef(_))
+            )
+    };
+    let cells: HashMap<Key, &Node> = nodes
+        .iter()
+        .filter(|node| is_cell(node))
+        .map(|node| ((node.graph.clone(), node.id.clone()), node))
+        .collect();
+    let mut references: HashMap<Key, usize> = HashMap::new();
+    for node in &nodes {
+        for item in node.properties.values().flat_map(PropertyValue::items) {
+            if let ArrayItem::ObjectRef(target) = item {
+                let key = (node.graph.clone(), target);
+                if cells.contains_key(&key) {
+                    *references.entry(key).or_default() += 1;
+                }
             }
+        }
+    }
+
+    // A cell referred to once can only be reached from the cell before it,
+    // so following the chain cannot loop.
+    let list = |graph: &Option<Iri>, head: &NodeId| -> Option<(Vec<ArrayItem>, Vec<Key>)> {
+        let mut items = Vec::new();
+        let mut chain = Vec::new();
+        let mut current = head.clone();
+        while current.as_str() != RDF_NIL || chain.is_empty() {
+            let key = (graph.clone(), current);
+            let cell = cells.get(&key)?;
+            if references.get(&key) != Some(&1) {
+                return None;
+            }
+            items.extend(cell.properties[RDF_FIRST].items());
+            let PropertyValue::ObjectRef(rest) = &cell.properties[RDF_REST] else {
+                return None;
+            };
+            current = rest.clone();
+            chain.push(key);
+        }
+        Some((items, chain))
+    };
+
+    let mut collapsed = HashSet::new();
+    let mut replacements = Vec::new();
+    for (index, node) in nodes.iter().enumerate() {
+        if cells.contains_key(&(node.graph.clone(), node.id.clone())) {
+            continue;
+        }
+        for (predicate, value) in &node.properties {
+            let PropertyValue::ObjectRef(head) = value else {
+                continue;
+            };
+            if head.as_str() == RDF_NIL {
+                // Cells kept as they are keep their `rdf:first` and `rdf:rest`.
+                if predicate.as_str() != RDF_FIRST && predicate.as_str() != RDF_REST {
+                    replacements.push((index, predicate.clone(), Vec::new()));
+                }
+            } else if let Some((items, chain)) = list(&node.graph, head) {
+                replacements.push((index, predicate.clone(), items));
+                collapsed.extend(chain);
+            }
+        }
+    }
+    for (index, predicate, items) in replacements {
+        let node = &mut nodes[index];
+        node.properties.insert(
+            predicate.clone(),
+            PropertyValue::Array(ArrayValue::from_items(items)),
+        );
+        node.lists.insert(predicate);
+    }
+    nodes.retain(|node| !collapsed.contains(&(node.graph.clone(), node.id.clone())));
+    nodes
+}
+
+/// Folds the reified statements of `nodes` into relationship nodes.
+///
+/// A node stating one `rdf:subject`, `rdf:predicate`, and `rdf:object`,
+/// typed `rdf:Statement` or not at all, describes the statement `S P O`.
+/// When the graph also asserts it, `P` of `S` refers to the statement node
+/// instead of `O`, and the statement node states `P` `O` in place of its
+/// reification triples, keeping its other properties as attributes of the
+/// relationship, the shape relationships with child sheet attribute columns
+/// read into from workbooks. Statements that are not asserted, reify
+/// `rdf:type`, or already state `P` stay as they are, as do further
+/// reifications of a statement already folded.
+pub fn fold_reifications(mut nodes: Vec<Node>) -> Vec<Node> {
+    let statements: Vec<(usize, NodeId, Iri, ArrayItem)> = nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, node)| {
+            let (subject, predicate, object) = reified_statement(node)?;
+            Some((index, subject, predicate, object))
+        })
+        .collect();
+    if statements.is_empty() {
+        return nodes;
+    }
+    let positions: HashMap<(Option<Iri>, NodeId), usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| ((node.graph.clone(), node.id.clone()), index))
+        .collect();
+
+    for (index, subject, predicate, object) in statements {
+        let statement_id = nodes[index].id.clone();
+        let Some(&subject_index) = positions.get(&(nodes[index].graph.clone(), subject)) else {
+            continue;
+        };
+        let Some(value) = nodes[subject_index].properties.get_mut(&predicate) else {
             continue;
+        };
+        let mut items = value.items();
+        let Some(position) = items.iter().position(|item| *item == object) else {
+            continue;
+        };
+        *value = match value {
+            PropertyValue::Array(_) => {
+                items[position] = ArrayItem::ObjectRef(statement_id);
+                PropertyValue::Array(ArrayValue::from_items(items))
+            }
+            _ => PropertyValue::ObjectRef(statement_id),
+        };
+
+        let statement = &mut nodes[index];
+        statement.types.clear();
+        for reification in [RDF_SUBJECT, RDF_PREDICATE, RDF_OBJECT] {
+            statement.properties.remove(reification);
+        }
+        let object = match object {
+            ArrayItem::Scalar(scalar) => PropertyValue::Scalar(scalar),
+            ArrayItem::ObjectRef(target) => PropertyValue::ObjectRef(target),
+        };
+        statement.properties.insert(predicate, object);
+    }
+    nodes
+}
+
+/// Returns the subject, predicate, and object `node` reifies, if it is a
+/// statement [`fold_reifications`] can fold.
+fn reified_statement(node: &Node) -> Option<(NodeId, Iri, ArrayItem)> {
+    if node
+        .types
+        .iter()
+        .any(|type_name| type_name.as_str() != RDF_STATEMENT)
+    {
+        return None;
+    }
+    let Some(PropertyValue::ObjectRef(subject)) = node.properties.get(RDF_SUBJECT) else {
+        return None;
+    };
+    let Some(PropertyValue::ObjectRef(predicate)) = node.properties.get(RDF_PREDICATE) else {
+        return None;
+    };
+    let object = match node.properties.get(RDF_OBJECT)? {
+        PropertyValue::Scalar(scalar) => ArrayItem::Scalar(scalar.clone()),
+        PropertyValue::ObjectRef(target) => ArrayItem::ObjectRef(target.clone()),
+        PropertyValue::Array(_) => return None,
+    };
+    if is_blank(predicate)
+        || predicate.as_str() == RDF_TYPE
+        || node.properties.contains_key(predicate.as_str())
+    {
+        return None;
+    }
+    Some((subject.clone(), predicate.clone(), object))
+}
+
+/// Writes the values of `predicates`, and of the [`Node::lists`] of each
+/// node, as RDF collections, the inverse of [`collapse_lists`]: each such
+/// property refers to the head of a chain of new blank nodes holding its
+/// values in order, or to `rdf:nil` when it has none. Null values are left
+/// out, as they are from other statements.
+pub fn build_lists<'a>(nodes: &'a [Node], predicates: &BTreeSet<Iri>) -> Cow<'a, [Node]> {
+    if predicates.is_empty() && nodes.iter().all(|node| node.lists.is_empty()) {
+        return Cow::Borrowed(nodes);
+    }
+    let mut taken: HashSet<NodeId> = HashSet::new();
+    for node in nodes {
+        taken.insert(node.id.clone());
+        for item in node.properties.values().flat_map(PropertyValue::items) {
+            if let ArrayItem::ObjectRef(target) = item {
+                taken.insert(target);
+            }
+        }
+    }
+    let mut counter = 0;
+    let mut fresh = || {
+        loop {
+            counter += 1;
+            let id = NodeId::from(format!("_:list{counter}"));
+            if !taken.contains(&id) {
+                return id;
+            }
+        }
+    };
+
+    let mut written = Vec::with_capacity(nodes.len());
+    let mut cells = Vec::new();
+    for node in nodes {
+        let mut node = node.clone();
+        let lists = std::mem::take(&mut node.lists);
+        for (predicate, value) in &mut node.properties {
+            if !predicates.contains(predicate) && !lists.contains(predicate) {
+                continue;
+            }
+            let items: Vec<ArrayItem> = value
+                .items()
+                .into_iter()
+                .filter(|item| !matches!(item, ArrayItem::Scalar(ScalarValue::Null)))
+                .collect();
+            let ids: Vec<NodeId> = items.iter().map(|_| fresh()).collect();
+            for (index, item) in items.into_iter().enumerate() {
+                let mut cell = Node::with_graph(ids[index].clone(), node.graph.clone());
+                let first = match item {
+                    ArrayItem::Scalar(scalar) => PropertyValue::Scalar(scalar),
+                    ArrayItem::ObjectRef(target) => PropertyValue::ObjectRef(target),
+                };
+                let rest = ids.get(index + 1).cloned();
+                cell.properties.insert(Iri::from(RDF_FIRST), first);
+                cell.properties.insert(
+                    Iri::from(RDF_REST),
+                    PropertyValue::ObjectRef(rest.unwrap_or_else(|| NodeId::from(RDF_NIL))),
+                );
+                cells.push(cell);
+            }
+            let head = ids.first().cloned();
+            *value = PropertyValue::ObjectRef(head.unwrap_or_else(|| NodeId::from(RDF_NIL)));
+        }
+        written.push(node);
+    }
+    written.extend(cells);
+    Cow::Owned(written)
+}
+
+/// Streams the nodes of an RDF file in batches of at most `batch_size` nodes
+/// instead of materialising the whole graph.
+///
+/// Consecutive quads with the same subject and graph are merged into one
+/// node, so input sorted by subject (such as `sort`ed N-Quads) yields every
+/// node exactly once. Unsorted input yields one partial node per run of
+/// quads.
+pub fn stream_nodes(
+    path: &Path,
+    format: Option<RdfFormat>,
+    batch_size: usize,
+) -> Result<NodeBatches> {
+    let format = resolve_format(path, format)?;
+    Ok(stream_nodes_from(File::open(path)?, format, batch_size))
+}
+
+/// Streams the nodes of an RDF graph serialised as `format` from `reader`,
+/// like [`stream_nodes`].
+pub fn stream_nodes_from<R: Read>(
+    reader: R,
+    format: RdfFormat,
+    batch_size: usize,
+) -> NodeBatches<R> {
+    NodeBatches {
+        quads: RdfParser::from_format(format).for_reader(reader),
+        current: None,
+        batch_size: batch_size.max(1),
+        yielded: 0,
+        finished: false,
+    }
+}
+
+/// Iterator over the node batches produced by [`stream_nodes`] and
+/// [`stream_nodes_from`].
+pub struct NodeBatches<R: Read = File> {
+    quads: ReaderQuadParser<R>,
+    current: Option<Node>,
+    batch_size: usize,
+    /// Nodes in the batches returned so far, reported when interrupted.
+    yielded: usize,
+    finished: bool,
+}
+
+impl<R: Read> NodeBatches<R> {
+    /// Reads nodes into `batch` until it is full or the input ends. Values
+    /// are interned per batch, so memory stays bounded by the batch size
+    /// rather than growing with every distinct IRI of the file.
+    fn fill(&mut self, batch: &mut Vec<Node>) -> Result<()> {
+        let mut interner = Interner::new();
+        while batch.len() < self.batch_size {
+            interrupt::check(self.yielded + batch.len())?;
+            let Some(quad) = self.quads.next() else {
+                self.finished = true;
+                batch.extend(self.current.take());
+                return Ok(());
+            };
+            let quad = quad.map_err(|err| ToolError::Rdf(err.to_string()))?;
+            let subject_id = intern_subject(&mut interner, &quad.subject);
+            let graph_name = intern_graph_name(&mut interner, &quad.graph_name);
+
+            let continues = self
+                .current
+                .as_ref()
+                .is_some_and(|node| node.id == subject_id && node.graph == graph_name);
+            if !continues {
+                batch.extend(
+                    self.current
+                        .replace(Node::with_graph(subject_id, graph_name)),
+                );
+            }
+            if let Some(node) = self.current.as_mut() {
+                apply_quad(node, &quad, &mut interner)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for NodeBatches<R> {
+    type Item = Result<Vec<Node>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
         }
+        let mut batch = Vec::with_capacity(self.batch_size);
+        match self.fill(&mut batch) {
+            Ok(()) => {
+                self.yielded += batch.len();
+                (!batch.is_empty()).then_some(Ok(batch))
+            }
+            Err(err) => {
+                self.finished = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Prefixes declared in Turtle and TriG output unless overridden, see
+/// [`write_rdf_with_prefixes`].
+pub const WELL_KNOWN_PREFIXES: [(&str, &str); 4] = [
+    ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("schema", "https://schema.org/"),
+    ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+];
 
-        let property = term_to_property(&quad.object)?;
-        merge_property(node, predicate, property);
+/// Serialises `nodes` as canonical N-Quads, the same whenever they hold the
+/// same statements: their statements, see [`dataset_quads`], are
+/// canonicalized with RDFC-1.0, see [`rdfc`]. Datasets read from any format
+/// in any statement order compare, hash, and sign alike this way.
+pub fn canonical_nquads(nodes: &[Node]) -> Result<String> {
+    rdfc::canonical_nquads(&dataset_quads(nodes)?)
+}
+
+/// Returns the statements of `nodes`, with [`Node::lists`] as collections,
+/// as [`write_rdf_to`] writes them.
+pub fn dataset_quads(nodes: &[Node]) -> Result<Vec<Quad>> {
+    let nodes = build_lists(nodes, &BTreeSet::new());
+    let mut quads = Vec::new();
+    for node in nodes.iter() {
+        quads.extend(node_quads(node)?);
     }
+    Ok(quads)
+}
 
-    Ok(nodes.into_values().collect())
+/// Reads the statements of the RDF file at `path` as they are, without
+/// turning them into nodes, detecting the format from the extension when
+/// `format` is `None`.
+pub fn read_dataset(path: &Path, format: Option<RdfFormat>) -> Result<Vec<Quad>> {
+    let format = resolve_format(path, format)?;
+    read_dataset_from(File::open(path)?, format)
+}
+
+/// Reads the statements of `reader`, in `format`, as they are.
+pub fn read_dataset_from<R: Read>(reader: R, format: RdfFormat) -> Result<Vec<Quad>> {
+    RdfParser::from_format(format)
+        .for_reader(reader)
+        .enumerate()
+        .map(|(read, quad)| {
+            interrupt::check(read)?;
+            quad.map_err(|err| ToolError::Rdf(err.to_string()))
+        })
+        .collect()
 }
 
 /// Serialises the provided nodes into an RDF graph.
 pub fn write_rdf(path: &Path, nodes: &[Node], format: RdfFormat) -> Result<()> {
-    let file = File::create(path)?;
-    let mut serializer = RdfSerializer::from_format(format).for_writer(file);
+    write_rdf_with_prefixes(path, nodes, format, &BTreeMap::new())
+}
 
-    let rdf_type = NamedNode::new(RDF_TYPE).map_err(|err| ToolError::Rdf(err.to_string()))?;
+/// Serialises `nodes` like [`write_rdf`], abbreviating IRIs with the
+/// `prefixes` they use in formats that support prefixes (Turtle and TriG).
+/// Prefixes no statement uses are not declared.
+pub fn write_rdf_with_prefixes(
+    path: &Path,
+    nodes: &[Node],
+    format: RdfFormat,
+    prefixes: &BTreeMap<String, String>,
+) -> Result<()> {
+    atomic::write_atomically(path, |partial| {
+        write_rdf_to(File::create(partial)?, nodes, format, prefixes)
+    })
+}
 
-    for node in nodes {
-        let subject = id_to_subject(&node.id)?;
-        let graph_name = graph_to_name(node.graph.as_ref())?;
-
-        for type_name in &node.types {
-            let class = NamedNode::new(type_name).map_err(|err| ToolError::Rdf(err.to_string()))?;
-            let quad = Quad::new(
-                subject.clone(),
-                rdf_type.clone(),
-                class.clone(),
-                graph_name.clone(),
-            );
+/// Serialises `nodes` like [`write_rdf_with_prefixes`], to `writer` instead
+/// of a file. [`Node::lists`] are written as collections, see
+/// [`build_lists`].
+pub fn write_rdf_to<W: Write>(
+    writer: W,
+    nodes: &[Node],
+    format: RdfFormat,
+    prefixes: &BTreeMap<String, String>,
+) -> Result<()> {
+    let nodes = &*build_lists(nodes, &BTreeSet::new());
+    let mut serializer = RdfSerializer::from_format(format);
+    for (prefix, namespace) in used_prefixes(nodes, prefixes)? {
+        serializer = serializer
+            .with_prefix(prefix, namespace)
+            .map_err(|err| ToolError::Rdf(err.to_string()))?;
+    }
+    let mut serializer = serializer.for_writer(writer);
+
+    let per_type = tracing::enabled!(Level::DEBUG);
+    let mut type_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut triple_count = 0;
+    for (written, node) in nodes.iter().enumerate() {
+        interrupt::check(written)?;
+        let quads = node_quads(node)?;
+        for quad in &quads {
             serializer
-                .serialize_quad(&quad)
+                .serialize_quad(quad)
                 .map_err(|err| ToolError::Rdf(err.to_string()))?;
         }
-
-        for (predicate, value) in &node.properties {
-            let predicate_node =
-                NamedNode::new(predicate).map_err(|err| ToolError::Rdf(err.to_string()))?;
-            match value {
-                PropertyValue::Scalar(scalar) => {
-                    if let Some(term) = scalar_to_term(scalar)? {
-                        let quad = Quad::new(
-                            subject.clone(),
-                            predicate_node.clone(),
-                            term,
-                            graph_name.clone(),
-                        );
-                        serializer
-                            .serialize_quad(&quad)
-                            .map_err(|err| ToolError::Rdf(err.to_string()))?;
-                    }
-                }
-                PropertyValue::ObjectRef(target) => {
-                    let term = id_to_term(target)?;
-                    let quad = Quad::new(
-                        subject.clone(),
-                        predicate_node.clone(),
-                        term,
-                        graph_name.clone(),
-                    );
-                    serializer
-                        .serialize_quad(&quad)
-                        .map_err(|err| ToolError::Rdf(err.to_string()))?;
-                }
-                PropertyValue::Array(ArrayValue::Scalars(items)) => {
-                    for scalar in items {
-                        if let Some(term) = scalar_to_term(scalar)? {
-                            let quad = Quad::new(
-                                subject.clone(),
-                                predicate_node.clone(),
-                                term,
-                                graph_name.clone(),
-                            );
-                            serializer
-                                .serialize_quad(&quad)
-                                .map_err(|err| ToolError::Rdf(err.to_string()))?;
-                        }
-                    }
-                }
-                PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => {
-                    for target in targets {
-                        let term = id_to_term(target)?;
-                        let quad = Quad::new(
-                            subject.clone(),
-                            predicate_node.clone(),
-                            term,
-                            graph_name.clone(),
-                        );
-                        serializer
-                            .serialize_quad(&quad)
-                            .map_err(|err| ToolError::Rdf(err.to_string()))?;
-                    }
-                }
+        triple_count += quads.len();
+        if per_type {
+            let types = node.types.iter().map(Iri::as_str);
+            let types = types.chain(node.types.is_empty().then_some(NONE_LABEL));
+            for type_name in types {
+                *type_counts.entry(type_name).or_default() += quads.len();
             }
         }
     }
@@ -145,13 +576,115 @@ pub fn write_rdf(path: &Path, nodes: &[Node], format: RdfFormat) -> Result<()> {
     serializer
         .finish()
         .map_err(|err| ToolError::Rdf(err.to_string()))?;
+    Span::current().record("triple_count", triple_count);
+    telemetry::count(Counter::TriplesWritten, triple_count);
+    for (type_name, triple_count) in type_counts {
+        debug!(type_name, triple_count, "triples written for type");
+    }
     Ok(())
 }
 
+/// Returns the `prefixes` whose namespace starts an IRI in the statements
+/// about `nodes`.
+fn used_prefixes<'a>(
+    nodes: &[Node],
+    prefixes: &'a BTreeMap<String, String>,
+) -> Result<Vec<(&'a String, &'a String)>> {
+    let mut unused: Vec<(&String, &String)> = prefixes.iter().collect();
+    let mut used = Vec::new();
+    for node in nodes {
+        if unused.is_empty() {
+            break;
+        }
+        for quad in node_quads(node)? {
+            let object = match &quad.object {
+                Term::NamedNode(node) => Some(node.as_str()),
+                // Plain and language-tagged strings are written without
+                // their datatype.
+                Term::Literal(literal)
+                    if literal.language().is_some()
+                        || literal.datatype().as_str() == XSD_STRING =>
+                {
+                    None
+                }
+                Term::Literal(literal) => Some(literal.datatype().as_str()),
+                Term::BlankNode(_) => None,
+            };
+            let graph = match &quad.graph_name {
+                GraphName::NamedNode(node) => Some(node.as_str()),
+                _ => None,
+            };
+            let subject = match &quad.subject {
+                NamedOrBlankNode::NamedNode(node) => Some(node.as_str()),
+                NamedOrBlankNode::BlankNode(_) => None,
+            };
+            let iris = [subject, Some(quad.predicate.as_str()), object, graph];
+            unused.retain(|&(prefix, namespace)| {
+                let starts = iris
+                    .iter()
+                    .flatten()
+                    .any(|iri| iri.starts_with(namespace.as_str()));
+                if starts {
+                    used.push((prefix, namespace));
+                }
+                !starts
+            });
+        }
+    }
+    used.sort();
+    Ok(used)
+}
+
+/// Converts a node into the quads stating its types and properties. Null
+/// literals have no RDF form and are skipped.
+pub(crate) fn node_quads(node: &Node) -> Result<Vec<Quad>> {
+    let rdf_type = NamedNode::new(RDF_TYPE).map_err(|err| ToolError::Rdf(err.to_string()))?;
+    let subject = id_to_subject(&node.id)?;
+    let graph_name = graph_to_name(node.graph.as_ref())?;
+    let mut quads = Vec::new();
+
+    for type_name in &node.types {
+        let class =
+            NamedNode::new(type_name.as_str()).map_err(|err| ToolError::Rdf(err.to_string()))?;
+        quads.push(Quad::new(
+            subject.clone(),
+            rdf_type.clone(),
+            class,
+            graph_name.clone(),
+        ));
+    }
+
+    for (predicate, value) in &node.properties {
+        let predicate_node =
+            NamedNode::new(predicate.as_str()).map_err(|err| ToolError::Rdf(err.to_string()))?;
+        for item in value.items() {
+            let term = match item {
+                ArrayItem::Scalar(scalar) => scalar_to_term(&scalar)?,
+                ArrayItem::ObjectRef(target) => Some(id_to_term(&target)?),
+            };
+            if let Some(term) = term {
+                quads.push(Quad::new(
+                    subject.clone(),
+                    predicate_node.clone(),
+                    term,
+                    graph_name.clone(),
+                ));
+            }
+        }
+    }
+    Ok(quads)
+}
+
+/// Infers the RDF serialisation from a file extension.
+///
+/// `.n3` files are Notation3. Its Turtle-compatible subset reads as plain
+/// RDF, with formulas as blank-node graphs, and written N3 is Turtle.
+/// Variables and literal subjects have no RDF equivalent and fail the read.
 pub fn detect_format(path: &Path) -> Option<RdfFormat> {
     let extension = path.extension()?.to_ascii_lowercase();
     match extension.to_str()? {
         "ttl" | "turtle" => Some(RdfFormat::Turtle),
+        "n3" => Some(RdfFormat::N3),
         "nt" => Some(RdfFormat::NTriples),
         "nq" => Some(RdfFormat::NQuads),
         "trig" => Some(RdfFormat::TriG),
@@ -162,51 +695,77 @@ pub fn detect_format(path: &Path) -> Option<RdfFormat> {
     }
 }
 
-fn subject_to_id(subject: &NamedOrBlankNode) -> Result<String> {
+/// Returns `format` or the serialisation detected from the extension of `path`.
+pub(crate) fn resolve_format(path: &Path, format: Option<RdfFormat>) -> Result<RdfFormat> {
+    format.or_else(|| detect_format(path)).ok_or_else(|| {
+        ToolError::Rdf(format!(
+            "unable to infer RDF format from extension for file {}",
+            path.display()
+        ))
+    })
+}
+
+fn apply_quad(node: &mut Node, quad: &Quad, interner: &mut Interner) -> Result<()> {
+    let predicate = quad.predicate.as_str();
+    if predicate == RDF_TYPE {
+        if let Term::NamedNode(object) = &quad.object {
+            node.types.insert(interner.intern(object.as_str()));
+        }
+        return Ok(());
+    }
+
+    let property = term_to_property(&quad.object, interner)?;
+    merge_property(node, interner.intern(predicate), property);
+    Ok(())
+}
+
+fn intern_subject(interner: &mut Interner, subject: &NamedOrBlankNode) -> NodeId {
+    match subject {
+        NamedOrBlankNode::NamedNode(node) => interner.intern(node.as_str()),
+        NamedOrBlankNode::BlankNode(node) => interner.intern(&format!("_:{}", node.as_str())),
+    }
+}
+
+pub(crate) fn subject_to_id(subject: &NamedOrBlankNode) -> Result<String> {
     match subject {
         NamedOrBlankNode::NamedNode(node) => Ok(node.as_str().to_string()),
         NamedOrBlankNode::BlankNode(node) => Ok(format!("_:{}", node.as_str())),
     }
 }
 
-fn term_to_property(term: &Term) -> Result<PropertyValue> {
+fn term_to_property(term: &Term, interner: &mut Interner) -> Result<PropertyValue> {
     Ok(match term {
-        Term::NamedNode(node) => PropertyValue::ObjectRef(node.as_str().to_string()),
-        Term::BlankNode(node) => PropertyValue::ObjectRef(format!("_:{}", node.as_str())),
+        Term::NamedNode(node) => PropertyValue::ObjectRef(interner.intern(node.as_str())),
+        Term::BlankNode(node) => {
+            PropertyValue::ObjectRef(interner.intern(&format!("_:{}", node.as_str())))
+        }
         Term::Literal(literal) => PropertyValue::Scalar(literal_to_scalar(literal)?),
     })
 }
 
-fn literal_to_scalar(literal: &Literal) -> Result<ScalarValue> {
+pub(crate) fn literal_to_scalar(literal: &Literal) -> Result<ScalarValue> {
     if let Some(language) = literal.language() {
-        return Ok(ScalarValue::String(format!(
-            "{}@{}",
-            literal.value(),
-            language
-        )));
+        return Ok(ScalarValue::lang_tagged(literal.value(), language));
     }
 
-    match literal.datatype().as_str() {
-        XSD_BOOLEAN => Ok(ScalarValue::Boolean(matches!(
-            literal.value(),
-            "true" | "1"
-        ))),
-        XSD_INTEGER | XSD_DECIMAL | XSD_DOUBLE => literal
-            .value()
-            .parse::<f64>()
-            .map(ScalarValue::Number)
-            .map_err(|err| ToolError::Rdf(err.to_string())),
-        _ => Ok(ScalarValue::String(literal.value().to_string())),
-    }
+    Ok(ScalarValue::from_lexical(
+        literal.value(),
+        literal.datatype().as_str(),
+    ))
 }
 
-fn scalar_to_term(value: &ScalarValue) -> Result<Option<Term>> {
+pub(crate) fn scalar_to_term(value: &ScalarValue) -> Result<Option<Term>> {
     Ok(match value {
         ScalarValue::String(text) => {
             let literal = Literal::new_simple_literal(text);
             Some(Term::Literal(literal))
         }
-        ScalarValue::Number(number) => {
+        ScalarValue::Integer(number) => {
+            let datatype = NamedNode::new(XSD_INTEGER)?;
+            let literal = Literal::new_typed_literal(number.to_string(), datatype);
+            Some(Term::Literal(literal))
+        }
+        ScalarValue::Double(number) => {
             let datatype = NamedNode::new(XSD_DOUBLE)?;
             let literal = Literal::new_typed_literal(number.to_string(), datatype);
             Some(Term::Literal(literal))
@@ -217,10 +776,24 @@ fn scalar_to_term(value: &ScalarValue) -> Result<Option<Term>> {
             Some(Term::Literal(literal))
         }
         ScalarValue::Null => None,
+        ScalarValue::TypedLiteral { .. } | ScalarValue::Date(_) | ScalarValue::DateTime(_) => {
+            match value.typed_lexical() {
+                Some((lexical, datatype)) => {
+                    let literal = Literal::new_typed_literal(lexical, NamedNode::new(datatype)?);
+                    Some(Term::Literal(literal))
+                }
+                None => None,
+            }
+        }
+        ScalarValue::LangString { value, language } => {
+            let literal = Literal::new_language_tagged_literal(value, language)
+                .map_err(|err| ToolError::Rdf(err.to_string()))?;
+            Some(Term::Literal(literal))
+        }
     })
 }
 
-fn id_to_subject(id: &str) -> Result<NamedOrBlankNode> {
+pub(crate) fn id_to_subject(id: &str) -> Result<NamedOrBlankNode> {
     if let Some(rest) = id.strip_prefix("_:") {
         let blank = BlankNode::new(rest).map_err(|err| ToolError::Rdf(err.to_string()))?;
         Ok(NamedOrBlankNode::BlankNode(blank))
@@ -230,7 +803,7 @@ fn id_to_subject(id: &str) -> Result<NamedOrBlankNode> {
     }
 }
 
-fn id_to_term(id: &str) -> Result<Term> {
+pub(crate) fn id_to_term(id: &str) -> Result<Term> {
     if let Some(rest) = id.strip_prefix("_:") {
         let blank = BlankNode::new(rest).map_err(|err| ToolError::Rdf(err.to_string()))?;
         Ok(Term::from(blank))
@@ -240,15 +813,15 @@ fn id_to_term(id: &str) -> Result<Term> {
     }
 }
 
-fn graph_name_to_string(name: &GraphName) -> Result<Option<String>> {
-    Ok(match name {
+fn intern_graph_name(interner: &mut Interner, name: &GraphName) -> Option<Iri> {
+    match name {
         GraphName::DefaultGraph => None,
-        GraphName::NamedNode(node) => Some(node.as_str().to_string()),
-        GraphName::BlankNode(node) => Some(format!("_:{}", node.as_str())),
-    })
+        GraphName::NamedNode(node) => Some(interner.intern(node.as_str())),
+        GraphName::BlankNode(node) => Some(interner.intern(&format!("_:{}", node.as_str()))),
+    }
 }
 
-fn graph_to_name(graph: Option<&String>) -> Result<GraphName> {
+fn graph_to_name(graph: Option<&Iri>) -> Result<GraphName> {
     match graph {
         None => Ok(GraphName::DefaultGraph),
         Some(value) => {
@@ -256,14 +829,14 @@ fn graph_to_name(graph: Option<&String>) -> Result<GraphName> {
                 let blank = BlankNode::new(rest).map_err(|err| ToolError::Rdf(err.to_string()))?;
                 Ok(GraphName::BlankNode(blank))
             } else {
-                let named = NamedNode::new(value)?;
+                let named = NamedNode::new(value.as_str())?;
                 Ok(GraphName::NamedNode(named))
             }
         }
     }
 }
 
-fn merge_property(node: &mut Node, predicate: String, value: PropertyValue) {
+fn merge_property(node: &mut Node, predicate: Iri, value: PropertyValue) {
     use std::collections::btree_map::Entry;
 
     match node.properties.entry(predicate) {
@@ -303,8 +876,12 @@ fn merge_property(node: &mut Node, predicate: String, value: PropertyValue) {
             ) => {
                 existing.append(&mut incoming);
             }
-            (_, other) => {
-                entry.insert(other);
+            (existing, other) => {
+                // Literals and references for the same predicate form a
+                // mixed array.
+                let mut items = existing.items();
+                items.extend(other.items());
+                *existing = PropertyValue::Array(ArrayValue::from_items(items));
             }
         },
     }
@@ -0,0 +1,387 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::io::atomic;
+
+/// Upper bound on nested remote context references, guarding against cycles.
+const MAX_CONTEXT_DEPTH: usize = 8;
+
+/// Upper bound on the redirects and alternate links followed to fetch one
+/// context.
+#[cfg(feature = "remote-contexts")]
+const MAX_REDIRECTS: usize = 5;
+
+/// Resolves remote JSON-LD context references (`"@context": "https://..."`)
+/// into inline context definitions.
+///
+/// Fetched contexts are cached on disk so repeated conversions do not hit the
+/// network. Cached contexts are only trusted when they and their directory
+/// belong to the current user and are not writable by others. Fetching
+/// requires the `remote-contexts` feature; without it only previously cached
+/// contexts can be resolved.
+#[derive(Debug, Clone)]
+pub struct RemoteContextLoader {
+    allowlist: Vec<String>,
+    cache_dir: Option<PathBuf>,
+}
+
+impl Default for RemoteContextLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RemoteContextLoader {
+    /// Creates a loader that accepts any `https://` context and caches
+    /// contexts in the per-user cache directory, see [`default_cache_dir`].
+    pub fn new() -> Self {
+        Self {
+            allowlist: Vec::new(),
+            cache_dir: default_cache_dir(),
+        }
+    }
+
+    /// Restricts resolution to context IRIs below one of `prefixes`: with
+    /// the same scheme, host, and port, and a path equal to the prefix's or
+    /// continuing it after a `/`. An empty allowlist accepts any `https://`
+    /// IRI.
+    pub fn with_allowlist<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowlist = prefixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Stores cached contexts in `dir`, or disables caching when `None`.
+    pub fn with_cache_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.cache_dir = dir;
+        self
+    }
+
+    /// Returns the file used to cache the context published at `iri`, if
+    /// caching is enabled.
+    pub fn cache_path(&self, iri: &str) -> Option<PathBuf> {
+        let key = Uuid::new_v5(&Uuid::NAMESPACE_URL, iri.as_bytes());
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{key}.jsonld")))
+    }
+
+    /// Returns a copy of `document` where every remote `@context` reference
+    /// has been replaced by the context it points to.
+    pub fn resolve_document(&self, document: &Value) -> Result<Value> {
+        match document {
+            Value::Object(map) => {
+                let mut resolved = serde_json::Map::with_capacity(map.len());
+                for (key, value) in map {
+                    let value = if key == "@context" {
+                        self.resolve_context(value)?
+                    } else {
+                        self.resolve_document(value)?
+                    };
+                    resolved.insert(key.clone(), value);
+                }
+                Ok(Value::Object(resolved))
+            }
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.resolve_document(item))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array),
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// Resolves remote references inside a `@context` value.
+    pub fn resolve_context(&self, context: &Value) -> Result<Value> {
+        self.resolve_context_at_depth(context, 0)
+    }
+
+    fn resolve_context_at_depth(&self, context: &Value, depth: usize) -> Result<Value> {
+        if depth > MAX_CONTEXT_DEPTH {
+            return Err(ToolError::RemoteContext(format!(
+                "more than {MAX_CONTEXT_DEPTH} nested remote context references"
+            )));
+        }
+
+        match context {
+            Value::String(iri) => {
+                let loaded = self.load(iri)?;
+                self.resolve_context_at_depth(&loaded, depth + 1)
+            }
+            Value::Array(entries) => {
+                let mut resolved = Vec::with_capacity(entries.len());
+                for entry in entries {
+                    match self.resolve_context_at_depth(entry, depth)? {
+                        Value::Array(nested) => resolved.extend(nested),
+                        other => resolved.push(other),
+                    }
+                }
+                Ok(Value::Array(resolved))
+            }
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// Loads the `@context` published at `iri`, consulting the cache first.
+    fn load(&self, iri: &str) -> Result<Value> {
+        self.ensure_allowed(iri)?;
+
+        let cache_path = self.cache_path(iri);
+        if let Some(path) = cache_path.as_ref().filter(|path| path.exists()) {
+            if is_private(path)? && path.parent().map_or(Ok(false), is_private)? {
+                debug!(iri, cache = %path.display(), "using cached remote context");
+                let cached = fs::read_to_string(path)?;
+                return extract_context(iri, &cached);
+            }
+            warn!(
+                iri,
+                cache = %path.display(),
+                "ignoring a cached remote context that other users can write"
+            );
+        }
+
+        let body = self.fetch(iri)?;
+        let context = extract_context(iri, &body)?;
+
+        if let Some(path) = cache_path
+            && let Some(dir) = path.parent()
+        {
+            create_private_dir(dir)?;
+            if is_private(dir)? {
+                atomic::write_atomically(&path, |partial| Ok(fs::write(partial, &body)?))?;
+                debug!(iri, cache = %path.display(), "cached remote context");
+            } else {
+                warn!(
+                    iri,
+                    cache = %dir.display(),
+                    "not caching the remote context in a directory that other users can write"
+                );
+            }
+        }
+
+        Ok(context)
+    }
+
+    fn ensure_allowed(&self, iri: &str) -> Result<()> {
+        let allowed = match ContextLocation::parse(iri) {
+            Some(location) if self.allowlist.is_empty() => location.scheme == "https",
+            Some(location) => self.allowlist.iter().any(|prefix| {
+                ContextLocation::parse(prefix).is_some_and(|prefix| location.is_below(&prefix))
+            }),
+            None => false,
+        };
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(ToolError::RemoteContext(format!(
+                "context '{iri}' is not permitted by the remote context allowlist"
+            )))
+        }
+    }
+
+    /// Fetches the document published at `iri`. Redirects and alternate
+    /// JSON-LD links are followed only to IRIs the allowlist permits.
+    #[cfg(feature = "remote-contexts")]
+    fn fetch(&self, iri: &str) -> Result<String> {
+        const ACCEPT: &str = "application/ld+json, application/json;q=0.9";
+
+        let request_error =
+            |err: ureq::Error| ToolError::RemoteContext(format!("failed to fetch '{iri}': {err}"));
+        let mut target = iri.to_string();
+        for _ in 0..=MAX_REDIRECTS {
+            self.ensure_allowed(&target)?;
+            debug!(iri, target = %target, "fetching remote context");
+            // Redirects are followed here rather than by ureq, so that every
+            // hop is checked against the allowlist.
+            let mut response = ureq::get(&target)
+                .config()
+                .max_redirects(0)
+                .build()
+                .header("Accept", ACCEPT)
+                .call()
+                .map_err(request_error)?;
+
+            let header = |name: &str| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+            };
+            // Servers such as schema.org answer with HTML and advertise the
+            // context through a
+            // `Link: <...>; rel="alternate"; type="application/ld+json"` header.
+            let next = if response.status().is_redirection() {
+                header("location").map(str::to_string)
+            } else if header("content-type").is_some_and(|value| value.contains("json")) {
+                None
+            } else {
+                header("link").and_then(alternate_jsonld_link)
+            };
+            match next {
+                Some(next) => {
+                    target = resolve_link_target(&target, &next)?;
+                    debug!(iri, next = %target, "following redirect or alternate JSON-LD link");
+                }
+                None => return response.body_mut().read_to_string().map_err(request_error),
+            }
+        }
+        Err(ToolError::RemoteContext(format!(
+            "more than {MAX_REDIRECTS} redirects fetching '{iri}'"
+        )))
+    }
+
+    #[cfg(not(feature = "remote-contexts"))]
+    fn fetch(&self, iri: &str) -> Result<String> {
+        Err(ToolError::RemoteContext(format!(
+            "fetching '{iri}' requires the `remote-contexts` feature"
+        )))
+    }
+}
+
+/// Scheme, host, port, and path of a context IRI or allowlist entry,
+/// compared part by part so that `https://schema.org` does not admit
+/// `https://schema.org.example.com/`.
+#[derive(Debug, PartialEq)]
+struct ContextLocation {
+    scheme: String,
+    host: String,
+    port: Option<String>,
+    path: String,
+}
+
+impl ContextLocation {
+    /// Parses an absolute IRI with an authority, lowercasing its scheme and
+    /// host and leaving out the default port of `http` and `https`.
+    fn parse(iri: &str) -> Option<Self> {
+        let iri = iref::Iri::new(iri).ok()?;
+        let authority = iri.authority()?;
+        let scheme = iri.scheme().as_str().to_ascii_lowercase();
+        let default_port = match scheme.as_str() {
+            "https" => Some("443"),
+            "http" => Some("80"),
+            _ => None,
+        };
+        let port = authority
+            .port()
+            .map(|port| port.as_str())
+            .filter(|port| !port.is_empty() && Some(*port) != default_port)
+            .map(str::to_string);
+        Some(Self {
+            host: authority.host().as_str().to_ascii_lowercase(),
+            scheme,
+            port,
+            path: iri.path().as_str().to_string(),
+        })
+    }
+
+    /// Returns whether this location lies below `prefix`: the same origin,
+    /// and a path equal to the prefix's or continuing it after a `/`.
+    fn is_below(&self, prefix: &Self) -> bool {
+        if (&self.scheme, &self.host, &self.port) != (&prefix.scheme, &prefix.host, &prefix.port) {
+            return false;
+        }
+        let base = prefix.path.trim_end_matches('/');
+        match self.path.strip_prefix(base) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        }
+    }
+}
+
+/// Returns the directory [`RemoteContextLoader::new`] caches contexts in:
+/// `aideon-tools/contexts` below `$XDG_CACHE_HOME` or `~/.cache`, or below
+/// `%LOCALAPPDATA%` on Windows. Caching is off when none of them is set.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| {
+        std::env::var_os(name)
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+    };
+    let base = if cfg!(windows) {
+        env_dir("LOCALAPPDATA")
+    } else {
+        env_dir("XDG_CACHE_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".cache")))
+    };
+    base.map(|dir| dir.join("aideon-tools").join("contexts"))
+}
+
+/// Creates `dir` and its missing parents, readable only by the current user.
+fn create_private_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+    }
+    #[cfg(not(unix))]
+    fs::create_dir_all(dir)?;
+    Ok(())
+}
+
+/// Returns whether `path` is owned by the current user and not writable by
+/// other users, so no one else can have planted or changed it. Always true
+/// on platforms without Unix permissions.
+fn is_private(path: &Path) -> Result<bool> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = fs::metadata(path)?;
+        // SAFETY: `geteuid` has no preconditions and cannot fail.
+        let user = unsafe { libc::geteuid() };
+        Ok(metadata.uid() == user && metadata.mode() & 0o002 == 0)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(true)
+    }
+}
+
+/// Extracts the `@context` entry of a fetched context document.
+fn extract_context(iri: &str, body: &str) -> Result<Value> {
+    let document: Value = serde_json::from_str(body).map_err(|err| {
+        ToolError::RemoteContext(format!("context '{iri}' is not valid JSON: {err}"))
+    })?;
+    document.get("@context").cloned().ok_or_else(|| {
+        ToolError::RemoteContext(format!("document at '{iri}' has no @context entry"))
+    })
+}
+
+/// Finds the target of a `rel="alternate"` JSON-LD entry in a `Link` header.
+#[cfg(feature = "remote-contexts")]
+fn alternate_jsonld_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|entry| {
+        let (target, params) = entry.split_once(';')?;
+        let is_alternate = params.contains("rel=\"alternate\"") || params.contains("rel=alternate");
+        let is_jsonld = params.contains("application/ld+json");
+        (is_alternate && is_jsonld).then(|| {
+            target
+                .trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string()
+        })
+    })
+}
+
+/// Resolves the `Location` or `Link` target `target` against the IRI of the
+/// response it came with, as RFC 3986 section 5.2 describes.
+#[cfg(feature = "remote-contexts")]
+fn resolve_link_target(base: &str, target: &str) -> Result<String> {
+    let invalid =
+        || ToolError::RemoteContext(format!("cannot resolve '{target}' against '{base}'"));
+    let base = iref::Iri::new(base).map_err(|_| invalid())?;
+    let target = iref::IriRef::new(target).map_err(|_| invalid())?;
+    Ok(target.resolved(base).to_string())
+}
@@ -0,0 +1,550 @@
+//! Nodes read from SQL databases.
+//!
+//! A source file names the database and the queries to run against it, each
+//! with the [`RowMapping`] turning its result rows into nodes:
+//!
+//! ```json
+//! {
+//!   "url": "sqlite://people.db",
+//!   "prefixes": { "schema": "https://schema.org/" },
+//!   "queries": [
+//!     {
+//!       "sql": "SELECT id, name FROM person",
+//!       "type": "schema:Person",
+//!       "id": "https://example.com/people/{id}",
+//!       "columns": { "name": "schema:name" }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! A query may name a `table` instead of giving its `sql`, reading all of it,
+//! so a mapping file lifts a database table by table: each table becomes a
+//! type, its columns predicates, and its foreign keys references through
+//! `reference` templates naming the key columns. The `url` may be left out
+//! of files given the connection URL otherwise, such as by
+//! [`SqlSource::with_url`].
+//!
+//! SQLite and PostgreSQL URLs are understood. Text, integer, floating point,
+//! and boolean columns keep their type, and binary columns become
+//! `xsd:hexBinary` literals; other column types can be cast to text in the
+//! query and retyped by the mapping. Rows of every query that map to the
+//! same node add up to one node. Querying needs the `sql` feature; without
+//! it, [`read_nodes`] fails.
+//!
+//! [`write_sqlite`] goes the other way for analysts, materialising the
+//! flattened tables of a workbook as the tables of a SQLite database, with
+//! the column types and foreign keys described by [`create_statements`]. It
+//! needs the `sql` feature too.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::flatten::{ENTITIES_SHEET, METADATA_SHEET, SheetTable, WorkbookData};
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::io::excel_write::literal_columns;
+use crate::aideon::tools::io::typed_cells::{ColumnType, TypedColumn, typed_columns};
+use crate::aideon::tools::mapping::{RowMapper, RowMapping};
+use crate::aideon::tools::model::Node;
+
+/// A database and the queries reading nodes from it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SqlSource {
+    /// Connection URL, such as `sqlite://people.db` or
+    /// `postgres://user@host/database`; empty when left out.
+    #[serde(default)]
+    pub url: String,
+    /// Prefixes of the compact IRIs in the mappings, added to those the
+    /// conversion already knows.
+    #[serde(default)]
+    pub prefixes: BTreeMap<String, String>,
+    /// Queries run in order.
+    pub queries: Vec<SqlQuery>,
+}
+
+/// A query and how its rows become nodes.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SqlQuery {
+    /// SQL statement returning the rows.
+    #[serde(default)]
+    pub sql: Option<String>,
+    /// Table whose rows are all read, in place of `sql`.
+    #[serde(default)]
+    pub table: Option<String>,
+    /// Schema qualifying the `table`, such as `hr`.
+    #[serde(default)]
+    pub schema: Option<String>,
+    /// Mapping of the rows onto nodes.
+    #[serde(flatten)]
+    pub mapping: RowMapping,
+}
+
+impl SqlSource {
+    /// Reads the source file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        serde_json::from_str(&text)
+            .map_err(|err| ToolError::InvalidConfig(format!("{}: {err}", path.display())))
+    }
+
+    /// Connects to `url` instead of the database the file names.
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+}
+
+impl SqlQuery {
+    /// Returns the SQL statement run for the query: its `sql`, or a `SELECT`
+    /// of every row of its `table`.
+    pub fn statement(&self) -> Result<String> {
+        match (&self.sql, &self.table) {
+            (Some(sql), None) => Ok(sql.clone()),
+            (None, Some(table)) => match &self.schema {
+                Some(schema) => Ok(format!(
+                    "SELECT * FROM {}.{}",
+                    quote_identifier(schema),
+                    quote_identifier(table)
+                )),
+                None => Ok(format!("SELECT * FROM {}", quote_identifier(table))),
+            },
+            _ => Err(ToolError::InvalidConfig(
+                "each SQL query needs either `sql` or `table`".into(),
+            )),
+        }
+    }
+}
+
+/// Runs the queries of `source` and maps their rows onto nodes, expanding
+/// compact IRIs with `prefixes` and then the source's own.
+pub fn read_nodes(source: &SqlSource, prefixes: &BTreeMap<String, String>) -> Result<Vec<Node>> {
+    if source.url.is_empty() {
+        return Err(ToolError::InvalidConfig(
+            "the SQL source names no database `url`".into(),
+        ));
+    }
+    let statements: Vec<String> = source
+        .queries
+        .iter()
+        .map(SqlQuery::statement)
+        .collect::<Result<_>>()?;
+    let statements: Vec<&str> = statements.iter().map(String::as_str).collect();
+    let results = query::fetch(&source.url, &statements)?;
+    let mut prefixes = prefixes.clone();
+    prefixes.extend(source.prefixes.clone());
+    let mut mapper = RowMapper::new(prefixes);
+    for (query, rows) in source.queries.iter().zip(results) {
+        for row in &rows {
+            mapper.add(&query.mapping, row)?;
+        }
+    }
+    Ok(mapper.into_nodes())
+}
+
+/// Writes every table of `workbook` to a new SQLite database at `path`,
+/// replacing the file whole, see [`atomic`]. Blank cells are `NULL`, and the
+/// cells of typed columns, see [`create_statements`], are stored as values
+/// of their type.
+pub fn write_sqlite(path: &Path, workbook: &WorkbookData) -> Result<()> {
+    let tables = sql_tables(workbook);
+    let statements = table_statements(workbook, &tables);
+    atomic::write_atomically(path, |partial| {
+        let _ = std::fs::remove_file(partial);
+        sqlite::write(partial, &statements, &tables)
+    })
+}
+
+/// Returns the `CREATE TABLE` statement of every table of `workbook`: one
+/// table per sheet, named after the type or the type and predicate it
+/// holds, such as `https://schema.org/Person` and
+/// `https://schema.org/Person__https://schema.org/knows`, rather than after
+/// the sheet, whose name Excel shortens; the index sheets keep their name.
+///
+/// Each header gets a column. The `scalar` columns declared in `Metadata`
+/// hold their literals, without the quotes and value objects of the cell
+/// encoding, and those of a single XML Schema datatype get its type:
+/// `INTEGER`, `REAL`, `NUMERIC` for `xsd:decimal`, or `BOOLEAN`, as long as
+/// every cell holds a literal of it. Dates and date-times are `TEXT` in the
+/// ISO 8601 form SQLite's date functions read, like the other columns.
+///
+/// When every node has its own row in `Entities`, its `id` is the primary
+/// key and foreign keys point at it from the `id` columns of type and matrix
+/// sheets, the `ref` columns declared in `Metadata`, and the parent and
+/// target columns of child sheets. References to nodes outside the dataset
+/// are kept, so `PRAGMA foreign_key_check` lists them.
+pub fn create_statements(workbook: &WorkbookData) -> Vec<String> {
+    table_statements(workbook, &sql_tables(workbook))
+}
+
+/// A sheet as a table of the database.
+struct SqlTable<'w> {
+    name: String,
+    sheet: &'w SheetTable,
+    columns: Vec<TypedColumn>,
+}
+
+/// Returns the tables of `workbook`, with their names and typed columns.
+fn sql_tables(workbook: &WorkbookData) -> Vec<SqlTable<'_>> {
+    let literals = table(workbook, METADATA_SHEET)
+        .map(literal_columns)
+        .unwrap_or_default();
+    let mut names = table_names(workbook);
+    workbook
+        .tables
+        .iter()
+        .map(|sheet| SqlTable {
+            name: names
+                .remove(sheet.sheet_name.as_str())
+                .unwrap_or_else(|| sheet.sheet_name.clone()),
+            sheet,
+            columns: typed_columns(sheet, &literals),
+        })
+        .collect()
+}
+
+fn table_statements(workbook: &WorkbookData, tables: &[SqlTable]) -> Vec<String> {
+    let keyed = entities_are_keyed(workbook);
+    let references = if keyed {
+        reference_columns(workbook)
+    } else {
+        BTreeMap::new()
+    };
+    tables
+        .iter()
+        .map(|table| {
+            let sheet = table.sheet;
+            let mut definitions: Vec<String> = sheet
+                .columns
+                .iter()
+                .zip(&table.columns)
+                .map(|(column, typed)| {
+                    let key = keyed && sheet.sheet_name == ENTITIES_SHEET && column == "id";
+                    let key = if key { " PRIMARY KEY" } else { "" };
+                    let column_type = column_type(typed.column_type);
+                    format!("{} {column_type}{key}", quote_identifier(column))
+                })
+                .collect();
+            for column in references
+                .get(sheet.sheet_name.as_str())
+                .into_iter()
+                .flatten()
+            {
+                definitions.push(format!(
+                    "FOREIGN KEY ({}) REFERENCES {} (\"id\")",
+                    quote_identifier(column),
+                    quote_identifier(ENTITIES_SHEET)
+                ));
+            }
+            format!(
+                "CREATE TABLE {} (\n  {}\n)",
+                quote_identifier(&table.name),
+                definitions.join(",\n  ")
+            )
+        })
+        .collect()
+}
+
+/// Declared SQL type of the columns of `column_type`.
+fn column_type(column_type: ColumnType) -> &'static str {
+    match column_type {
+        ColumnType::Integer => "INTEGER",
+        ColumnType::Real => "REAL",
+        ColumnType::Numeric => "NUMERIC",
+        ColumnType::Boolean => "BOOLEAN",
+        ColumnType::Date | ColumnType::DateTime | ColumnType::Text => "TEXT",
+    }
+}
+
+/// Returns the table name of the type, child, and matrix sheets declared in
+/// `Metadata`, by sheet. Names another table already has are left out, so
+/// such sheets keep their own.
+fn table_names(workbook: &WorkbookData) -> HashMap<&str, String> {
+    let mut names = HashMap::new();
+    let Some(metadata) = table(workbook, METADATA_SHEET) else {
+        return names;
+    };
+    let position = |name: &str| metadata.columns.iter().position(|column| column == name);
+    let (Some(kind), Some(sheet), Some(type_name), Some(predicate)) = (
+        position("kind"),
+        position("sheet"),
+        position("type"),
+        position("predicate"),
+    ) else {
+        return names;
+    };
+    let mut taken: HashSet<String> = workbook
+        .tables
+        .iter()
+        .map(|table| table.sheet_name.clone())
+        .collect();
+    for row in &metadata.rows {
+        let name = match cell(row, kind) {
+            "type" => cell(row, type_name).to_string(),
+            "child" | "matrix" => format!("{}__{}", cell(row, type_name), cell(row, predicate)),
+            _ => continue,
+        };
+        let Some(target) = table(workbook, cell(row, sheet)) else {
+            continue;
+        };
+        if name == target.sheet_name || !taken.insert(name.clone()) {
+            continue;
+        }
+        names.insert(target.sheet_name.as_str(), name);
+    }
+    names
+}
+
+/// Whether the `Entities` table lists every node id once, so it can be
+/// referenced; a node in several graphs has a row per graph.
+fn entities_are_keyed(workbook: &WorkbookData) -> bool {
+    let Some(entities) = table(workbook, ENTITIES_SHEET) else {
+        return false;
+    };
+    let Some(index) = entities.columns.iter().position(|column| column == "id") else {
+        return false;
+    };
+    let mut seen = HashSet::new();
+    entities.rows.iter().all(|row| {
+        row.get(index)
+            .is_some_and(|id| !id.is_empty() && seen.insert(id.as_str()))
+    })
+}
+
+/// The columns of each sheet that hold node ids, found from the `Metadata`
+/// table.
+fn reference_columns(workbook: &WorkbookData) -> BTreeMap<&str, BTreeSet<&str>> {
+    let mut references: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let Some(metadata) = table(workbook, METADATA_SHEET) else {
+        return references;
+    };
+    let position = |name: &str| metadata.columns.iter().position(|column| column == name);
+    let (Some(kind), Some(sheet), Some(column), Some(value_kind)) = (
+        position("kind"),
+        position("sheet"),
+        position("column"),
+        position("value_kind"),
+    ) else {
+        return references;
+    };
+    for row in &metadata.rows {
+        let Some(target) = table(workbook, cell(row, sheet)) else {
+            continue;
+        };
+        let columns: Vec<&str> = match cell(row, kind) {
+            "type" | "matrix" => vec!["id"],
+            "column" if cell(row, value_kind) == "ref" => vec![cell(row, column)],
+            // Parent, parent graph, and target come first on child sheets.
+            "child" => [0, 2]
+                .iter()
+                .filter_map(|index| target.columns.get(*index))
+                .map(String::as_str)
+                .collect(),
+            _ => continue,
+        };
+        let entry = references.entry(target.sheet_name.as_str()).or_default();
+        for column in columns {
+            if let Some(column) = target.columns.iter().find(|header| *header == column) {
+                entry.insert(column.as_str());
+            }
+        }
+    }
+    references
+}
+
+fn cell(row: &[String], index: usize) -> &str {
+    row.get(index).map_or("", String::as_str)
+}
+
+fn table<'w>(workbook: &'w WorkbookData, sheet_name: &str) -> Option<&'w SheetTable> {
+    workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == sheet_name)
+}
+
+/// Quotes `name` as a SQL identifier.
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(feature = "sql")]
+mod sqlite {
+    use std::path::Path;
+
+    use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+    use sqlx::{Connection, Executor};
+
+    use super::{SqlTable, quote_identifier};
+    use crate::aideon::tools::error::{Result, ToolError};
+    use crate::aideon::tools::io::typed_cells::TypedCell;
+
+    /// Creates the database at `path` with `statements` and inserts the
+    /// rows of `tables`, in one transaction. Foreign keys are not enforced,
+    /// so references to nodes outside the dataset are kept.
+    pub(super) fn write(path: &Path, statements: &[String], tables: &[SqlTable]) -> Result<()> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .foreign_keys(false);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async {
+            let mut connection = SqliteConnection::connect_with(&options)
+                .await
+                .map_err(sql_error)?;
+            let mut transaction = connection.begin().await.map_err(sql_error)?;
+            for statement in statements {
+                transaction
+                    .execute(statement.as_str())
+                    .await
+                    .map_err(sql_error)?;
+            }
+            for table in tables {
+                let placeholders = vec!["?"; table.columns.len()].join(", ");
+                let insert = format!(
+                    "INSERT INTO {} VALUES ({placeholders})",
+                    quote_identifier(&table.name)
+                );
+                for row in 0..table.sheet.rows.len() {
+                    let mut query = sqlx::query(&insert);
+                    for column in &table.columns {
+                        query = match &column.cells[row] {
+                            TypedCell::Null => query.bind(None::<&str>),
+                            TypedCell::Integer(number) => query.bind(*number),
+                            TypedCell::Real(number) => query.bind(*number),
+                            TypedCell::Boolean(flag) => query.bind(*flag),
+                            TypedCell::Date(date) => {
+                                query.bind(date.format("%Y-%m-%d").to_string())
+                            }
+                            TypedCell::DateTime(datetime) => {
+                                query.bind(datetime.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+                            }
+                            TypedCell::Text(text) => query.bind(text.as_str()),
+                        };
+                    }
+                    query.execute(&mut *transaction).await.map_err(sql_error)?;
+                }
+            }
+            transaction.commit().await.map_err(sql_error)?;
+            connection.close().await.map_err(sql_error)?;
+            Ok(())
+        })
+    }
+
+    fn sql_error(err: sqlx::Error) -> ToolError {
+        ToolError::Sql(err.to_string())
+    }
+}
+
+#[cfg(not(feature = "sql"))]
+mod sqlite {
+    use std::path::Path;
+
+    use super::SqlTable;
+    use crate::aideon::tools::error::{Result, ToolError};
+
+    pub(super) fn write(_path: &Path, _statements: &[String], _tables: &[SqlTable]) -> Result<()> {
+        Err(ToolError::Sql(
+            "SQLite output requires the `sql` feature".into(),
+        ))
+    }
+}
+
+#[cfg(feature = "sql")]
+mod query {
+    use std::fmt::Write;
+
+    use sqlx::any::{AnyRow, AnyTypeInfoKind};
+    use sqlx::{AnyConnection, Column, Connection, Row as _, ValueRef};
+
+    use crate::aideon::tools::error::{Result, ToolError};
+    use crate::aideon::tools::mapping::Row;
+    use crate::aideon::tools::model::ScalarValue;
+
+    const XSD_HEX_BINARY: &str = "http://www.w3.org/2001/XMLSchema#hexBinary";
+
+    /// Runs `statements` against the database at `url` and returns the rows
+    /// of each.
+    pub(super) fn fetch(url: &str, statements: &[&str]) -> Result<Vec<Vec<Row>>> {
+        sqlx::any::install_default_drivers();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async {
+            let mut connection = AnyConnection::connect(url).await.map_err(sql_error)?;
+            let mut results = Vec::with_capacity(statements.len());
+            for statement in statements {
+                let rows = sqlx::query(statement)
+                    .fetch_all(&mut connection)
+                    .await
+                    .map_err(sql_error)?;
+                results.push(rows.iter().map(row).collect::<Result<_>>()?);
+            }
+            connection.close().await.map_err(sql_error)?;
+            Ok(results)
+        })
+    }
+
+    fn row(row: &AnyRow) -> Result<Row> {
+        row.columns()
+            .iter()
+            .map(|column| {
+                let index = column.ordinal();
+                let raw = row.try_get_raw(index).map_err(sql_error)?;
+                let kind = raw.type_info().kind();
+                let value = if raw.is_null() {
+                    ScalarValue::Null
+                } else {
+                    match kind {
+                        AnyTypeInfoKind::Null => ScalarValue::Null,
+                        AnyTypeInfoKind::Bool => ScalarValue::Boolean(get(row, index)?),
+                        AnyTypeInfoKind::SmallInt
+                        | AnyTypeInfoKind::Integer
+                        | AnyTypeInfoKind::BigInt => ScalarValue::Integer(get(row, index)?),
+                        AnyTypeInfoKind::Real | AnyTypeInfoKind::Double => {
+                            ScalarValue::Double(get(row, index)?)
+                        }
+                        AnyTypeInfoKind::Text => ScalarValue::String(get(row, index)?),
+                        AnyTypeInfoKind::Blob => {
+                            let bytes: Vec<u8> = get(row, index)?;
+                            let hex = bytes.iter().fold(String::new(), |mut hex, byte| {
+                                let _ = write!(hex, "{byte:02X}");
+                                hex
+                            });
+                            ScalarValue::typed(hex, XSD_HEX_BINARY)
+                        }
+                    }
+                };
+                Ok((column.name().to_string(), value))
+            })
+            .collect()
+    }
+
+    fn get<'r, T>(row: &'r AnyRow, index: usize) -> Result<T>
+    where
+        T: sqlx::Decode<'r, sqlx::Any> + sqlx::Type<sqlx::Any>,
+    {
+        row.try_get(index).map_err(sql_error)
+    }
+
+    fn sql_error(err: sqlx::Error) -> ToolError {
+        ToolError::Sql(err.to_string())
+    }
+}
+
+#[cfg(not(feature = "sql"))]
+mod query {
+    use crate::aideon::tools::error::{Result, ToolError};
+    use crate::aideon::tools::mapping::Row;
+
+    pub(super) fn fetch(_url: &str, _statements: &[&str]) -> Result<Vec<Vec<Row>>> {
+        Err(ToolError::Sql(
+            "SQL input requires the `sql` feature".into(),
+        ))
+    }
+}
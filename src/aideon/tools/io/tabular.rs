@@ -0,0 +1,1658 @@
+//! Format-independent ingestion of flattened tables.
+//!
+//! Excel workbooks and CSV directories share the same sheet conventions
+//! produced by [`build_workbook`](crate::aideon::tools::flatten::build_workbook).
+//! Readers expose their sheets as rows of cell text through [`SheetSource`]
+//! and this module rebuilds the node set from them.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, btree_map::Entry};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde_json::{Map, Value};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::aideon::tools::diff::{RowLocation, RowLocations};
+use crate::aideon::tools::error::{CellError, Result, ToolError};
+use crate::aideon::tools::flatten::{
+    CONTEXT_SHEET, ColumnKind, DATASET_KEYS, DATASET_SHEET, DATASET_TYPE, ENTITIES_SHEET,
+    METADATA_SHEET, SUBTYPE_COLUMN, TEXT_PREFIX, UNTYPED_MARKER, WHOLE_CONTEXT_TERM, WorkbookData,
+};
+use crate::aideon::tools::interrupt;
+use crate::aideon::tools::io::coercion::{Coercion, ScalarCoercions};
+use crate::aideon::tools::model::{
+    ArrayItem, ArrayValue, Interner, Iri, Node, NodeId, PropertyValue, ScalarValue, XSD_BOOLEAN,
+    XSD_DATE, XSD_DATE_TIME, XSD_DECIMAL, XSD_DOUBLE, XSD_INTEGER, XSD_NAMESPACE,
+};
+use crate::aideon::tools::provenance::CellProvenance;
+use crate::aideon::tools::validate::{IssueKind, Severity, ValidationIssue, ValidationReport};
+
+/// Rows of a sheet, including the header row, as cell text.
+pub(crate) type SheetRows = Vec<Vec<String>>;
+
+type NodeKey = (Option<Iri>, NodeId);
+
+type TypeSheetMap = HashMap<String, Iri>;
+type ChildSheetMap = HashMap<String, (Iri, Iri)>;
+/// Declared columns of each type sheet, keyed by sheet and then header.
+type ColumnMap = HashMap<String, HashMap<String, DeclaredColumn>>;
+/// Graphs each id is listed under in the `Entities` sheet.
+type EntityGraphs = HashMap<NodeId, BTreeSet<Option<Iri>>>;
+/// Types of each node, as listed in the `Entities` sheet or given by the
+/// type sheets it has rows on; untyped nodes have [`UNTYPED_MARKER`].
+type NodeTypes = BTreeMap<NodeKey, BTreeSet<Iri>>;
+
+/// Column description read from a `column` row of the `Metadata` sheet.
+struct DeclaredColumn {
+    predicate: Iri,
+    kind: ColumnKind,
+    datatype: Option<String>,
+    /// Cell text read for rows that leave the predicate empty, from the
+    /// optional `default` column users add to `Metadata`.
+    default: Option<String>,
+}
+
+/// Where a cell sits: its sheet and the row number users see, counting the
+/// header as row 1.
+#[derive(Debug, Clone)]
+struct CellLocation {
+    sheet: String,
+    row: usize,
+}
+
+/// A type sheet cell giving a property of a node another value than an
+/// earlier row describing the same node.
+struct CellConflict {
+    node: NodeKey,
+    predicate: Iri,
+    column: String,
+    at: CellLocation,
+    earlier: CellLocation,
+}
+
+/// What reading the sheets found out about their nodes.
+#[derive(Default)]
+struct SheetFindings {
+    /// Types of each node given by the type sheets it has rows on.
+    typed: NodeTypes,
+    /// Sheet being read and rows above its header.
+    sheet: String,
+    band: usize,
+    /// Cell each property value read from a type sheet so far came from.
+    cells: HashMap<(NodeKey, Iri), CellLocation>,
+    conflicts: Vec<CellConflict>,
+    /// Cells whose text could not be read, with the node of their row.
+    invalid: Vec<(NodeKey, CellError)>,
+    /// Relationships read from the child and matrix sheets of earlier
+    /// types.
+    filled: HashSet<(NodeKey, Iri)>,
+    /// Cells each value came from, when provenance is asked for.
+    provenance: Option<Vec<CellProvenance>>,
+}
+
+/// What [`read_tables`] read: the nodes, the disagreements between the
+/// `Entities` sheet and the type sheets, the cells left out of the nodes,
+/// and the cells the values came from, when asked for.
+struct TableRead {
+    nodes: Vec<Node>,
+    report: ValidationReport,
+    conflicts: Vec<CellConflict>,
+    invalid: Vec<(NodeKey, CellError)>,
+    provenance: Vec<CellProvenance>,
+}
+
+impl TableRead {
+    /// Fails with [`ToolError::InvalidCells`] when cells could not be read.
+    fn check_cells(&mut self) -> Result<()> {
+        if self.invalid.is_empty() {
+            return Ok(());
+        }
+        let cells = self.invalid.drain(..).map(|(_, cell)| cell).collect();
+        Err(ToolError::InvalidCells(cells))
+    }
+}
+
+impl SheetFindings {
+    /// Where row `row_idx` of the current sheet sits.
+    fn location(&self, row_idx: usize) -> CellLocation {
+        CellLocation {
+            sheet: self.sheet.clone(),
+            row: row_idx + 1 + self.band,
+        }
+    }
+
+    /// Records that row `row_idx` of the current sheet gives `predicate` of
+    /// `node`, which held `previous`, the value `value`.
+    fn record(
+        &mut self,
+        node: NodeKey,
+        predicate: &Iri,
+        column: &str,
+        row_idx: usize,
+        previous: Option<&PropertyValue>,
+        value: &PropertyValue,
+    ) {
+        let at = self.location(row_idx);
+        let key = (node, predicate.clone());
+        if let Some(earlier) = self.cells.get(&key)
+            && previous.is_some_and(|previous| previous != value)
+        {
+            self.conflicts.push(CellConflict {
+                node: key.0.clone(),
+                predicate: predicate.clone(),
+                column: column.to_string(),
+                at: at.clone(),
+                earlier: earlier.clone(),
+            });
+        }
+        self.cells.insert(key, at);
+    }
+
+    /// Records, when provenance is asked for, that the cell in `column` of
+    /// row `row_idx` of the current sheet gave `predicate` of `node` a
+    /// value.
+    fn trace(&mut self, (graph, id): &NodeKey, predicate: &Iri, column: &str, row_idx: usize) {
+        let row = row_idx + 1 + self.band;
+        if let Some(provenance) = &mut self.provenance {
+            provenance.push(CellProvenance {
+                node: id.clone(),
+                graph: graph.clone(),
+                predicate: predicate.clone(),
+                sheet: self.sheet.clone(),
+                row,
+                column: column.to_string(),
+            });
+        }
+    }
+}
+
+/// Sheet mappings read from the `Metadata` sheet.
+#[derive(Default)]
+struct SheetIndex {
+    type_sheets: TypeSheetMap,
+    child_sheets: ChildSheetMap,
+    matrix_sheets: ChildSheetMap,
+    columns: ColumnMap,
+    /// Prefixes expanding undeclared compact headers.
+    prefixes: BTreeMap<String, String>,
+    /// Context terms expanding undeclared headers.
+    terms: BTreeMap<String, Iri>,
+}
+
+/// Provides access to the sheets of a flattened dataset.
+pub(crate) trait SheetSource {
+    /// Returns the rows of the sheet called `name`, failing with
+    /// [`ToolError::InvalidWorkbook`] when it does not exist.
+    fn sheet(&mut self, name: &str) -> Result<SheetRows>;
+
+    /// Returns whether the sheet called `name` exists.
+    fn has_sheet(&mut self, name: &str) -> bool;
+}
+
+/// A [`SheetSource`] exposing a sample of another: the first `rows` data
+/// rows of each type sheet, and the rows of `Entities` and of the child and
+/// matrix sheets describing the nodes they hold. The `Metadata`, `Context`,
+/// and `Dataset` sheets are read whole.
+pub(crate) struct PreviewSource<S> {
+    source: S,
+    rows: usize,
+    sample: Option<Sample>,
+}
+
+/// The type sheets of a [`PreviewSource`] and the ids of the rows kept on
+/// each, by type.
+struct Sample {
+    type_sheets: TypeSheetMap,
+    kept: HashMap<Iri, HashSet<String>>,
+}
+
+impl Sample {
+    /// Whether the `Entities` row listing `id` with `type_name` is kept:
+    /// when the sheet of that type keeps the node, or any sheet does for
+    /// types without one.
+    fn keeps(&self, type_name: &str, id: &str) -> bool {
+        match self.kept.get(type_name) {
+            Some(ids) => ids.contains(id),
+            None => self.keeps_any(id),
+        }
+    }
+
+    fn keeps_any(&self, id: &str) -> bool {
+        self.kept.values().any(|ids| ids.contains(id))
+    }
+}
+
+impl<S: SheetSource> PreviewSource<S> {
+    pub(crate) fn new(source: S, rows: usize) -> Self {
+        Self {
+            source,
+            rows,
+            sample: None,
+        }
+    }
+
+    /// Reads the type sheets named in `Metadata` to find the nodes kept.
+    fn sample(&mut self) -> Result<&Sample> {
+        if self.sample.is_none() {
+            let index = parse_metadata(&self.source.sheet(METADATA_SHEET)?)?;
+            let mut kept: HashMap<Iri, HashSet<String>> = HashMap::new();
+            for (sheet_name, type_name) in &index.type_sheets {
+                let rows = self.source.sheet(sheet_name)?;
+                let (head, id) = data_start(&rows);
+                kept.entry(type_name.clone()).or_default().extend(
+                    rows.iter()
+                        .skip(head)
+                        .take(self.rows)
+                        .map(|row| string_at(row, id))
+                        .filter(|id| !id.is_empty()),
+                );
+            }
+            self.sample = Some(Sample {
+                type_sheets: index.type_sheets,
+                kept,
+            });
+        }
+        Ok(self.sample.as_ref().expect("sample read above"))
+    }
+}
+
+/// Returns the number of band and header rows of a sheet and the column of
+/// the node each data row describes: `id` when there is one, the first
+/// column otherwise.
+fn data_start(rows: &[Vec<String>]) -> (usize, usize) {
+    let band = band_rows(rows);
+    let id = rows
+        .get(band)
+        .and_then(|header| header.iter().position(|column| column == "id"))
+        .unwrap_or(0);
+    (band + 1, id)
+}
+
+impl<S: SheetSource> SheetSource for PreviewSource<S> {
+    fn sheet(&mut self, name: &str) -> Result<SheetRows> {
+        if [METADATA_SHEET, CONTEXT_SHEET, DATASET_SHEET].contains(&name) {
+            return self.source.sheet(name);
+        }
+        let rows = self.rows;
+        self.sample()?;
+        let mut sheet = self.source.sheet(name)?;
+        let sample = self.sample.as_ref().expect("sample read above");
+        if name == ENTITIES_SHEET {
+            let data = sheet.split_off(1.min(sheet.len()));
+            sheet.extend(
+                data.into_iter()
+                    .filter(|row| sample.keeps(string_at(row, 1).trim(), &string_at(row, 0))),
+            );
+            return Ok(sheet);
+        }
+        let (head, id) = data_start(&sheet);
+        let data = sheet.split_off(head.min(sheet.len()));
+        if sample.type_sheets.contains_key(name) {
+            sheet.extend(data.into_iter().take(rows));
+        } else {
+            sheet.extend(
+                data.into_iter()
+                    .filter(|row| sample.keeps_any(&string_at(row, id))),
+            );
+        }
+        Ok(sheet)
+    }
+
+    fn has_sheet(&mut self, name: &str) -> bool {
+        self.source.has_sheet(name)
+    }
+}
+
+/// Reads the JSON-LD context embedded in the `Context` sheet, written by
+/// [`WorkbookLayout::context_table`](crate::aideon::tools::flatten::WorkbookLayout::context_table),
+/// or `None` when there is no such sheet.
+pub(crate) fn read_context<S: SheetSource>(source: &mut S) -> Result<Option<Value>> {
+    if !source.has_sheet(CONTEXT_SHEET) {
+        return Ok(None);
+    }
+    let mut terms = Map::new();
+    for row in source.sheet(CONTEXT_SHEET)?.iter().skip(1) {
+        let term = string_at(row, 0).trim().to_string();
+        if term.is_empty() {
+            continue;
+        }
+        let definition = string_at(row, 1);
+        if term == WHOLE_CONTEXT_TERM {
+            let context = serde_json::from_str(&definition).map_err(|err| {
+                ToolError::InvalidWorkbook(format!(
+                    "invalid context on sheet '{CONTEXT_SHEET}': {err}"
+                ))
+            })?;
+            return Ok(Some(context));
+        }
+        let definition = match serde_json::from_str(&definition) {
+            Ok(Value::String(_)) | Err(_) => Value::String(definition),
+            Ok(value) => value,
+        };
+        terms.insert(term, definition);
+    }
+    Ok(Some(Value::Object(terms)))
+}
+
+/// Lists the rows of the type, child, and matrix sheets of `source` that
+/// describe each node, for reporting changes against the workbook.
+pub(crate) fn locate_rows<S: SheetSource>(source: &mut S) -> Result<RowLocations> {
+    let index = parse_metadata(&source.sheet(METADATA_SHEET)?)?;
+    let mut sheets: Vec<&String> = index
+        .type_sheets
+        .keys()
+        .chain(index.child_sheets.keys())
+        .chain(index.matrix_sheets.keys())
+        .collect();
+    sheets.sort();
+    sheets.dedup();
+
+    let mut locations = RowLocations::new();
+    for sheet in sheets {
+        if !source.has_sheet(sheet) {
+            continue;
+        }
+        let mut rows = source.sheet(sheet)?;
+        let bands = band_rows(&rows);
+        rows.drain(..bands);
+        let headers = read_headers(&rows);
+        // Child sheets written before graphs were recorded go straight from
+        // the parent to the target column.
+        let has_graph_column = !index.child_sheets.contains_key(sheet)
+            || (headers.len() >= 3 && !string_at(&headers, 1).ends_with("Id"));
+        for (row_idx, row) in rows.iter().enumerate().skip(1) {
+            let id = string_at(row, 0);
+            if id.is_empty() {
+                continue;
+            }
+            let graph = if has_graph_column {
+                string_at(row, 1)
+            } else {
+                String::new()
+            };
+            locations
+                .entry((graph, NodeId::from(id)))
+                .or_default()
+                .push(RowLocation {
+                    sheet: sheet.clone(),
+                    row: bands + row_idx + 1,
+                });
+        }
+    }
+    Ok(locations)
+}
+
+/// Rebuilds nodes from the sheets exposed by `source`, reading literal cells
+/// through `coercions` and leaving out the sheets named in `skipped`, see
+/// [`read_nodes_reporting`]. Disagreements between the `Entities` sheet and
+/// the type sheets are logged as warnings.
+pub(crate) fn read_nodes<S: SheetSource>(
+    source: &mut S,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>> {
+    let (nodes, report) = read_nodes_reporting(source, coercions, skipped)?;
+    for issue in &report.issues {
+        warn!(node = %issue.node, "{}", issue.message);
+    }
+    Ok(nodes)
+}
+
+/// Rebuilds nodes like [`read_nodes`], returning the disagreements between
+/// the `Entities` sheet and the type sheets as
+/// [`IssueKind::EntityIndexMismatch`] warnings: ids listed on one but not
+/// the other, and types one assigns that the other does not. Nodes get the
+/// types of both.
+///
+/// Skipped sheets are not read. When every sheet of a type is skipped, its
+/// `Entities` rows and relationship sheets are left out as well, so nodes
+/// known only under that type are not read.
+pub(crate) fn read_nodes_reporting<S: SheetSource>(
+    source: &mut S,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    let mut read = read_tables(source, coercions, skipped, false)?;
+    read.check_cells()?;
+    Ok(report_conflicts(read))
+}
+
+/// Rebuilds nodes like [`read_nodes`], also returning the cell each value
+/// was read from.
+pub(crate) fn read_nodes_with_provenance<S: SheetSource>(
+    source: &mut S,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, Vec<CellProvenance>)> {
+    let mut read = read_tables(source, coercions, skipped, true)?;
+    read.check_cells()?;
+    let provenance = std::mem::take(&mut read.provenance);
+    let (nodes, report) = report_conflicts(read);
+    for issue in &report.issues {
+        warn!(node = %issue.node, "{}", issue.message);
+    }
+    Ok((nodes, provenance))
+}
+
+/// Rebuilds nodes like [`read_nodes_reporting`], leaving out the cells
+/// whose text cannot be read instead of failing on them, and reporting each
+/// as an [`IssueKind::InvalidCell`] error after the warnings.
+pub(crate) fn read_nodes_keep_going<S: SheetSource>(
+    source: &mut S,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    let mut read = read_tables(source, coercions, skipped, false)?;
+    let invalid = std::mem::take(&mut read.invalid);
+    let (nodes, mut report) = report_conflicts(read);
+    for ((graph, id), cell) in invalid {
+        report.issues.push(ValidationIssue {
+            severity: Severity::Error,
+            kind: IssueKind::InvalidCell,
+            node: id,
+            graph,
+            predicate: None,
+            message: cell.to_string(),
+        });
+    }
+    Ok((nodes, report))
+}
+
+/// Adds the conflicting cells of `read` to its report as
+/// [`IssueKind::ConflictingCell`] warnings.
+fn report_conflicts(read: TableRead) -> (Vec<Node>, ValidationReport) {
+    let TableRead {
+        nodes,
+        mut report,
+        conflicts,
+        ..
+    } = read;
+    for conflict in conflicts {
+        let CellConflict {
+            node: (graph, id),
+            predicate,
+            column,
+            at,
+            earlier,
+        } = conflict;
+        report.issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            kind: IssueKind::ConflictingCell,
+            node: id,
+            graph,
+            message: format!(
+                "column {column} of row {} on sheet '{}' replaces the value given by row {} on sheet '{}'",
+                at.row, at.sheet, earlier.row, earlier.sheet
+            ),
+            predicate: Some(predicate.to_string()),
+        });
+    }
+    (nodes, report)
+}
+
+/// Rebuilds nodes like [`read_nodes`], failing with
+/// [`ToolError::ConflictingCell`] at the first type sheet cell that gives a
+/// property of a node another value than an earlier row describing it,
+/// instead of keeping the later value.
+pub(crate) fn read_nodes_strict<S: SheetSource>(
+    source: &mut S,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>> {
+    let mut read = read_tables(source, coercions, skipped, false)?;
+    read.check_cells()?;
+    let TableRead {
+        nodes,
+        report,
+        conflicts,
+        ..
+    } = read;
+    if let Some(conflict) = conflicts.into_iter().next() {
+        return Err(ToolError::ConflictingCell {
+            sheet: conflict.at.sheet,
+            row: conflict.at.row,
+            column: conflict.column,
+        });
+    }
+    for issue in &report.issues {
+        warn!(node = %issue.node, "{}", issue.message);
+    }
+    Ok(nodes)
+}
+
+/// Rebuilds nodes like [`read_nodes_reporting`], returning the conflicting
+/// type sheet cells and the cells that could not be read apart, and tracing
+/// the cell of each value when `provenance` is set.
+fn read_tables<S: SheetSource>(
+    source: &mut S,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+    provenance: bool,
+) -> Result<TableRead> {
+    let metadata_rows = source.sheet(METADATA_SHEET)?;
+    let entities_rows = source.sheet(ENTITIES_SHEET)?;
+
+    let mut index = parse_metadata(&metadata_rows)?;
+    let skipped_types = skip_sheets(&mut index, skipped);
+    let SheetIndex {
+        type_sheets,
+        child_sheets,
+        matrix_sheets,
+        columns,
+        prefixes,
+        terms,
+    } = index;
+    let (mut nodes, graphs, listed) = initialize_nodes(&entities_rows, &skipped_types)?;
+
+    let no_columns = HashMap::new();
+    let mut findings = SheetFindings {
+        provenance: provenance.then(Vec::new),
+        ..SheetFindings::default()
+    };
+    // Sheets are read in name order so conflicts are found in the same
+    // order every time.
+    let mut sheets: Vec<_> = type_sheets.iter().collect();
+    sheets.sort();
+    for (sheet_name, type_name) in sheets {
+        interrupt::check(nodes.len())?;
+        let mut rows = source.sheet(sheet_name)?;
+        let band = band_rows(&rows);
+        rows.drain(..band);
+        let declared = columns.get(sheet_name).unwrap_or(&no_columns);
+        expand_headers(&mut rows, declared, &prefixes, &terms);
+        findings.sheet.clone_from(sheet_name);
+        findings.band = band;
+        ingest_type_sheet(
+            &rows,
+            type_name,
+            declared,
+            coercions,
+            &graphs,
+            &mut nodes,
+            &mut findings,
+        )
+        .map_err(|err| sheet_error(err, sheet_name))?;
+    }
+    let report = check_entities(&listed, &findings.typed, &type_sheets);
+
+    // Relationships are repeated on the child sheet of every type a node
+    // has; sheets after the first only contribute targets not seen yet.
+    let mut child_sheets: Vec<_> = child_sheets.into_iter().collect();
+    child_sheets.sort();
+    for (sheet_name, (_type_name, predicate)) in &child_sheets {
+        let rows = source.sheet(sheet_name)?;
+        let declared = columns.get(sheet_name).unwrap_or(&no_columns);
+        findings.sheet.clone_from(sheet_name);
+        findings.band = 0;
+        ingest_child_sheet(
+            &rows,
+            predicate,
+            declared,
+            coercions,
+            &graphs,
+            &mut nodes,
+            &mut findings,
+        )
+        .map_err(|err| sheet_error(err, sheet_name))?;
+    }
+    let mut matrix_sheets: Vec<_> = matrix_sheets.into_iter().collect();
+    matrix_sheets.sort();
+    for (sheet_name, (_type_name, predicate)) in &matrix_sheets {
+        let rows = source.sheet(sheet_name)?;
+        findings.sheet.clone_from(sheet_name);
+        findings.band = 0;
+        ingest_matrix_sheet(
+            &rows,
+            predicate,
+            coercions,
+            &graphs,
+            &mut nodes,
+            &mut findings,
+        )
+        .map_err(|err| sheet_error(err, sheet_name))?;
+    }
+    if !skipped.contains(DATASET_SHEET) && source.has_sheet(DATASET_SHEET) {
+        let rows = source.sheet(DATASET_SHEET)?;
+        ingest_dataset_sheet(&rows, coercions, &mut nodes)
+            .map_err(|err| sheet_error(err, DATASET_SHEET))?;
+    }
+
+    // Ids and reference targets are read from separate cells; pooling them
+    // stores each distinct id once.
+    let mut interner = Interner::new();
+    let mut nodes: Vec<Node> = nodes
+        .into_values()
+        .map(|node| interner.intern_node(node))
+        .collect();
+    nodes.sort_by(|lhs, rhs| lhs.graph.cmp(&rhs.graph).then_with(|| lhs.id.cmp(&rhs.id)));
+    Ok(TableRead {
+        nodes,
+        report,
+        conflicts: findings.conflicts,
+        invalid: findings.invalid,
+        provenance: findings.provenance.unwrap_or_default(),
+    })
+}
+
+/// Removes the sheets named in `skipped` from `index`, together with the
+/// relationship sheets of types left without a sheet, and returns those
+/// types.
+fn skip_sheets(index: &mut SheetIndex, skipped: &BTreeSet<String>) -> HashSet<Iri> {
+    if skipped.is_empty() {
+        return HashSet::new();
+    }
+    let mut types: HashSet<Iri> = index
+        .type_sheets
+        .iter()
+        .filter(|(sheet_name, _)| skipped.contains(*sheet_name))
+        .map(|(_, type_name)| type_name.clone())
+        .collect();
+    index
+        .type_sheets
+        .retain(|sheet_name, _| !skipped.contains(sheet_name));
+    types.retain(|type_name| !index.type_sheets.values().any(|kept| kept == type_name));
+    for sheets in [&mut index.child_sheets, &mut index.matrix_sheets] {
+        sheets.retain(|sheet_name, (type_name, _)| {
+            !skipped.contains(sheet_name) && !types.contains(type_name)
+        });
+    }
+    index
+        .columns
+        .retain(|sheet_name, _| !skipped.contains(sheet_name));
+    types
+}
+
+/// Compares the types the `Entities` sheet lists for each node, `listed`,
+/// with those of the type sheets it has rows on, `typed`. Rows of
+/// `Entities` with a blank type list the id without claiming a type.
+fn check_entities(
+    listed: &NodeTypes,
+    typed: &NodeTypes,
+    type_sheets: &TypeSheetMap,
+) -> ValidationReport {
+    let mut sheets: BTreeMap<&Iri, &str> = BTreeMap::new();
+    for (sheet_name, type_name) in type_sheets {
+        let sheet = sheets.entry(type_name).or_insert(sheet_name);
+        *sheet = (*sheet).min(sheet_name.as_str());
+    }
+    let sheet_of = |type_name: &Iri| match sheets.get(type_name) {
+        Some(sheet) => format!("sheet '{sheet}'"),
+        None => format!("the sheet of {type_name}"),
+    };
+    let names =
+        |types: &BTreeSet<Iri>| types.iter().map(Iri::as_str).collect::<Vec<_>>().join(", ");
+
+    let mut report = ValidationReport::default();
+    let mut push = |(graph, id): &NodeKey, message: String| {
+        report.issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            kind: IssueKind::EntityIndexMismatch,
+            node: id.clone(),
+            graph: graph.clone(),
+            predicate: None,
+            message,
+        });
+    };
+    let keys: BTreeSet<&NodeKey> = listed.keys().chain(typed.keys()).collect();
+    for key in keys {
+        let on_sheets = typed.get(key);
+        let Some(in_index) = listed.get(key) else {
+            for type_name in on_sheets.into_iter().flatten() {
+                push(
+                    key,
+                    format!(
+                        "has a row on {} but is missing from the Entities sheet",
+                        sheet_of(type_name)
+                    ),
+                );
+            }
+            continue;
+        };
+        let in_index: BTreeSet<Iri> = in_index
+            .iter()
+            .filter(|type_name| !type_name.is_empty())
+            .cloned()
+            .collect();
+        for type_name in in_index.iter() {
+            if on_sheets.is_some_and(|types| types.contains(type_name)) {
+                continue;
+            }
+            let message = if sheets.contains_key(type_name) {
+                format!(
+                    "is listed as {type_name} in the Entities sheet but has no row on {}",
+                    sheet_of(type_name)
+                )
+            } else {
+                format!(
+                    "is listed as {type_name} in the Entities sheet but no sheet holds that type"
+                )
+            };
+            push(key, message);
+        }
+        if in_index.is_empty() {
+            continue;
+        }
+        for type_name in on_sheets.into_iter().flatten() {
+            if !in_index.contains(type_name) {
+                push(
+                    key,
+                    format!(
+                        "has a row on {} as {type_name} but the Entities sheet lists it as {}",
+                        sheet_of(type_name),
+                        names(&in_index)
+                    ),
+                );
+            }
+        }
+    }
+    report
+}
+
+/// Rebuilds nodes from tables already held in memory.
+pub(crate) fn read_workbook(workbook: &WorkbookData) -> Result<Vec<Node>> {
+    read_workbook_with(workbook, &ScalarCoercions::default(), &BTreeSet::new())
+}
+
+/// Rebuilds nodes from tables held in memory like [`read_nodes`].
+pub(crate) fn read_workbook_with(
+    workbook: &WorkbookData,
+    coercions: &ScalarCoercions,
+    skipped: &BTreeSet<String>,
+) -> Result<Vec<Node>> {
+    read_nodes(&mut MemorySource { workbook }, coercions, skipped)
+}
+
+/// Reads the context embedded in tables held in memory like
+/// [`read_context`].
+pub(crate) fn workbook_context(workbook: &WorkbookData) -> Result<Option<Value>> {
+    read_context(&mut MemorySource { workbook })
+}
+
+struct MemorySource<'a> {
+    workbook: &'a WorkbookData,
+}
+
+impl SheetSource for MemorySource<'_> {
+    fn sheet(&mut self, name: &str) -> Result<SheetRows> {
+        let table = self
+            .workbook
+            .tables
+            .iter()
+            .find(|table| table.sheet_name == name)
+            .ok_or_else(|| missing_sheet(name))?;
+        let mut rows = Vec::with_capacity(table.rows.len() + 1);
+        rows.push(table.columns.clone());
+        rows.extend(table.rows.iter().cloned());
+        Ok(rows)
+    }
+
+    fn has_sheet(&mut self, name: &str) -> bool {
+        self.workbook
+            .tables
+            .iter()
+            .any(|table| table.sheet_name == name)
+    }
+}
+
+/// Error returned by sources when a required sheet is absent.
+pub(crate) fn missing_sheet(name: &str) -> ToolError {
+    ToolError::InvalidWorkbook(format!("missing sheet '{name}'"))
+}
+
+/// Reads the sheet mappings. Workbooks written before column kinds were
+/// recorded have no `column` rows; their cells are interpreted from the
+/// header and cell text alone.
+fn parse_metadata(rows: &[Vec<String>]) -> Result<SheetIndex> {
+    let mut index = SheetIndex::default();
+    let default_index = rows
+        .first()
+        .and_then(|header| header.iter().position(|column| column.trim() == "default"));
+
+    for row in rows.iter().skip(1) {
+        let kind = string_at(row, 0);
+        if kind.is_empty() {
+            continue;
+        }
+        let sheet = string_at(row, 1);
+        let type_name = Iri::from(string_at(row, 2));
+        let predicate = Iri::from(string_at(row, 3));
+
+        match kind.as_str() {
+            "type" => {
+                index.type_sheets.insert(sheet, type_name);
+            }
+            "child" => {
+                index.child_sheets.insert(sheet, (type_name, predicate));
+            }
+            "matrix" => {
+                index.matrix_sheets.insert(sheet, (type_name, predicate));
+            }
+            "prefix" => {
+                index
+                    .prefixes
+                    .insert(string_at(row, 4), predicate.to_string());
+            }
+            "term" => {
+                index.terms.insert(string_at(row, 4), predicate);
+            }
+            // Bands only shape how workbooks show their columns.
+            "group" => {}
+            "column" => {
+                let value_kind = string_at(row, 5);
+                let kind = ColumnKind::from_name(&value_kind).ok_or_else(|| {
+                    ToolError::InvalidWorkbook(format!("unknown column kind '{value_kind}'"))
+                })?;
+                index.columns.entry(sheet).or_default().insert(
+                    string_at(row, 4),
+                    DeclaredColumn {
+                        predicate,
+                        kind,
+                        datatype: normalize_optional(string_at(row, 6)).map(expand_datatype),
+                        default: default_index
+                            .and_then(|column| normalize_optional(string_at(row, column))),
+                    },
+                );
+            }
+            other => {
+                return Err(ToolError::InvalidWorkbook(format!(
+                    "unknown metadata kind '{other}'"
+                )));
+            }
+        }
+    }
+
+    Ok(index)
+}
+
+/// Creates the nodes listed in the `Entities` sheet and records the graphs
+/// each id belongs to and the types it is listed with. Workbooks without a
+/// `graph` column yield no graph index.
+fn initialize_nodes(
+    rows: &[Vec<String>],
+    skipped_types: &HashSet<Iri>,
+) -> Result<(BTreeMap<NodeKey, Node>, EntityGraphs, NodeTypes)> {
+    let mut nodes = BTreeMap::new();
+    let mut graphs = EntityGraphs::new();
+    let mut listed = NodeTypes::new();
+    let has_graph_column = rows
+        .first()
+        .is_some_and(|header| string_at(header, 2) == "graph");
+
+    for row in rows.iter().skip(1) {
+        let id = string_at(row, 0);
+        if id.is_empty() {
+            continue;
+        }
+        let type_name = Iri::from(string_at(row, 1).trim());
+        if skipped_types.contains(&type_name) {
+            continue;
+        }
+        let graph = normalize_optional(string_at(row, 2)).map(Iri::from);
+        let node = ensure_node(&mut nodes, &id, graph);
+        if has_graph_column {
+            graphs
+                .entry(node.id.clone())
+                .or_default()
+                .insert(node.graph.clone());
+        }
+        listed
+            .entry((node.graph.clone(), node.id.clone()))
+            .or_default()
+            .insert(type_name.clone());
+        if !type_name.is_empty() && type_name != UNTYPED_MARKER {
+            node.types.insert(type_name);
+        }
+    }
+
+    Ok((nodes, graphs, listed))
+}
+
+/// Returns the node a type or child sheet row refers to. The `Entities`
+/// sheet is authoritative for graph membership: when the row's graph does
+/// not match a known node and the id is listed under a single graph there,
+/// the row is attached to that graph.
+fn row_node<'a>(
+    nodes: &'a mut BTreeMap<NodeKey, Node>,
+    graphs: &EntityGraphs,
+    id: &str,
+    raw_graph: String,
+) -> &'a mut Node {
+    let graph = normalize_optional(raw_graph).map(Iri::from);
+    let known = nodes.contains_key(&(graph.clone(), NodeId::from(id)));
+    let graph = match graphs.get(id) {
+        Some(listed) if !known && listed.len() == 1 => listed.iter().next().cloned().flatten(),
+        _ => graph,
+    };
+    ensure_node(nodes, id, graph)
+}
+
+fn ingest_type_sheet(
+    rows: &[Vec<String>],
+    type_name: &Iri,
+    declared: &HashMap<String, DeclaredColumn>,
+    coercions: &ScalarCoercions,
+    graphs: &EntityGraphs,
+    nodes: &mut BTreeMap<NodeKey, Node>,
+    findings: &mut SheetFindings,
+) -> Result<()> {
+    let headers = read_headers(rows);
+    if headers.is_empty() {
+        return Ok(());
+    }
+
+    let mut defaults = Vec::new();
+    for column in declared.values() {
+        if let Some(default) = &column.default
+            && let Some(property) = parse_declared_cell(column, default, coercions)
+                .map_err(|err| cell_error(err, "default of", &column.predicate))?
+        {
+            defaults.push((column.predicate.clone(), property));
+        }
+    }
+    defaults.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+    // Sheets holding consolidated subtypes list the types of their rows.
+    let subtype_column = headers
+        .iter()
+        .position(|header| header == SUBTYPE_COLUMN && !declared.contains_key(header));
+
+    for (row_idx, row) in rows.iter().enumerate().skip(1) {
+        let id = string_at(row, 0);
+        if id.is_empty() {
+            continue;
+        }
+
+        let node = row_node(nodes, graphs, &id, string_at(row, 1));
+        let subtypes = subtype_column.map(|column| string_at(row, column));
+        let mut row_types: Vec<Iri> = subtypes
+            .iter()
+            .flat_map(|cell| cell.split_whitespace())
+            .map(Iri::from)
+            .collect();
+        if row_types.is_empty() {
+            row_types.push(type_name.clone());
+        }
+        let key = (node.graph.clone(), node.id.clone());
+        for type_name in row_types {
+            findings
+                .typed
+                .entry((node.graph.clone(), node.id.clone()))
+                .or_default()
+                .insert(type_name.clone());
+            if !type_name.is_empty() && type_name != UNTYPED_MARKER {
+                node.types.insert(type_name);
+            }
+        }
+
+        for (col_idx, raw_value) in row.iter().enumerate().skip(2) {
+            let Some(header) = headers.get(col_idx) else {
+                continue;
+            };
+            if header.is_empty() || subtype_column == Some(col_idx) {
+                continue;
+            }
+
+            if raw_value.trim().is_empty() {
+                continue;
+            }
+
+            let entry = match declared.get(header) {
+                Some(column) => parse_declared_cell(column, raw_value, coercions)
+                    .map(|cell| cell.map(|property| (column.predicate.clone(), property))),
+                None => parse_property_entry(header, raw_value, coercions),
+            };
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    let at = findings.location(row_idx);
+                    findings
+                        .invalid
+                        .push((key.clone(), invalid_cell(err, at, header, raw_value)));
+                    continue;
+                }
+            };
+            if let Some((predicate, property)) = entry {
+                findings.trace(&key, &predicate, header, row_idx);
+                let previous = node.properties.get(&predicate);
+                findings.record(
+                    key.clone(),
+                    &predicate,
+                    header,
+                    row_idx,
+                    previous,
+                    &property,
+                );
+                node.insert_property(predicate, property);
+            }
+        }
+
+        for (predicate, property) in &defaults {
+            if !node.properties.contains_key(predicate) {
+                node.insert_property(predicate.clone(), property.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the rows of a child sheet. Columns after the target column
+/// describe the relationship: a row filling any of them links the parent to
+/// an intermediate node holding those values and the target under the same
+/// predicate, instead of to the target itself.
+fn ingest_child_sheet(
+    rows: &[Vec<String>],
+    predicate: &Iri,
+    declared: &HashMap<String, DeclaredColumn>,
+    coercions: &ScalarCoercions,
+    graphs: &EntityGraphs,
+    nodes: &mut BTreeMap<NodeKey, Node>,
+    findings: &mut SheetFindings,
+) -> Result<()> {
+    let mut touched: HashSet<(NodeKey, Iri)> = HashSet::new();
+    let headers = read_headers(rows);
+    // Workbooks written before graphs were recorded only have the parent
+    // and target columns, followed by any relationship columns.
+    let has_graph_column = headers.len() >= 3 && !string_at(&headers, 1).ends_with("Id");
+    let target_index = if has_graph_column { 2 } else { 1 };
+
+    for (row_idx, row) in rows.iter().enumerate().skip(1) {
+        let parent = string_at(row, 0);
+        let mut target = NodeId::from(string_at(row, target_index));
+        if parent.is_empty() || target.is_empty() || skips_reference(coercions, &target) {
+            continue;
+        }
+
+        let raw_graph = if has_graph_column {
+            string_at(row, 1)
+        } else {
+            String::new()
+        };
+        let node = row_node(nodes, graphs, &parent, raw_graph);
+        let parent_key = (node.graph.clone(), node.id.clone());
+
+        let mut attributes = Vec::new();
+        for (col_idx, raw_value) in row.iter().enumerate().skip(target_index + 1) {
+            let header = string_at(&headers, col_idx);
+            if header.is_empty() || raw_value.trim().is_empty() {
+                continue;
+            }
+            let entry = match declared.get(&header) {
+                Some(column) => parse_declared_cell(column, raw_value, coercions)
+                    .map(|cell| cell.map(|property| (column.predicate.clone(), property))),
+                None => parse_property_entry(&header, raw_value, coercions),
+            };
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    let at = findings.location(row_idx);
+                    findings.invalid.push((
+                        parent_key.clone(),
+                        invalid_cell(err, at, &header, raw_value),
+                    ));
+                    continue;
+                }
+            };
+            if let Some(entry) = entry {
+                attributes.push((header, raw_value.trim(), entry));
+            }
+        }
+        let target_header = string_at(&headers, target_index);
+        if !attributes.is_empty() {
+            let relationship_id = relationship_id(&parent_key, predicate, &target, &attributes);
+            let relationship_key = (parent_key.0.clone(), NodeId::from(relationship_id.as_str()));
+            findings.trace(&relationship_key, predicate, &target_header, row_idx);
+            for (header, _, (attribute, _)) in &attributes {
+                findings.trace(&relationship_key, attribute, header, row_idx);
+            }
+            let relationship = ensure_node(nodes, &relationship_id, parent_key.0.clone());
+            relationship.insert_property(predicate.clone(), PropertyValue::ObjectRef(target));
+            for (_, _, (attribute, property)) in attributes {
+                relationship.insert_property(attribute, property);
+            }
+            target = relationship_id.into();
+        }
+
+        findings.trace(&parent_key, predicate, &target_header, row_idx);
+        link_target(
+            nodes,
+            parent_key,
+            predicate,
+            target,
+            &findings.filled,
+            &mut touched,
+        )?;
+    }
+
+    findings.filled.extend(touched);
+    Ok(())
+}
+
+/// Reads the rows of a matrix sheet: after the `id` and `graph` columns,
+/// every column is headed by a target and a non-blank cell links the row's
+/// node to it. Cells that `coercions` skip or read as null leave the link
+/// out, as on child sheets.
+fn ingest_matrix_sheet(
+    rows: &[Vec<String>],
+    predicate: &Iri,
+    coercions: &ScalarCoercions,
+    graphs: &EntityGraphs,
+    nodes: &mut BTreeMap<NodeKey, Node>,
+    findings: &mut SheetFindings,
+) -> Result<()> {
+    let mut touched: HashSet<(NodeKey, Iri)> = HashSet::new();
+    let headers = read_headers(rows);
+
+    for (row_idx, row) in rows.iter().enumerate().skip(1) {
+        let id = string_at(row, 0);
+        if id.is_empty() {
+            continue;
+        }
+        let node = row_node(nodes, graphs, &id, string_at(row, 1));
+        let parent_key = (node.graph.clone(), node.id.clone());
+
+        for (col_idx, raw_value) in row.iter().enumerate().skip(2) {
+            let target = string_at(&headers, col_idx);
+            if target.trim().is_empty()
+                || raw_value.trim().is_empty()
+                || skips_reference(coercions, raw_value)
+            {
+                continue;
+            }
+            findings.trace(&parent_key, predicate, &target, row_idx);
+            link_target(
+                nodes,
+                parent_key.clone(),
+                predicate,
+                target.trim().into(),
+                &findings.filled,
+                &mut touched,
+            )?;
+        }
+    }
+
+    findings.filled.extend(touched);
+    Ok(())
+}
+
+/// Adds `target` to the references of `predicate` on the node at
+/// `parent_key`. Relationships already filled from the sheet of another
+/// type only gain targets not seen yet.
+/// Reads the dataset node described by the `key` and `value` rows of the
+/// `Dataset` sheet, written by
+/// [`build_dataset_table`](crate::aideon::tools::flatten::build_dataset_table),
+/// and adds its types and properties to `nodes`. Keys ending in `Id` hold
+/// references; keys given on several rows make multi-valued properties.
+fn ingest_dataset_sheet(
+    rows: &[Vec<String>],
+    coercions: &ScalarCoercions,
+    nodes: &mut BTreeMap<NodeKey, Node>,
+) -> Result<()> {
+    let mut id = None;
+    let mut graph = None;
+    let mut types = BTreeSet::from([Iri::from(DATASET_TYPE)]);
+    let mut values: BTreeMap<Iri, Vec<ArrayItem>> = BTreeMap::new();
+    for row in rows.iter().skip(1) {
+        let key = string_at(row, 0).trim().to_string();
+        let raw_value = string_at(row, 1);
+        if key.is_empty() || raw_value.is_empty() {
+            continue;
+        }
+        match key.as_str() {
+            "id" => id = normalize_optional(raw_value),
+            "graph" => graph = normalize_optional(raw_value).map(Iri::from),
+            "type" => {
+                types.insert(Iri::from(raw_value.trim()));
+            }
+            _ => {
+                let predicate = |key: &str| {
+                    DATASET_KEYS
+                        .iter()
+                        .find(|(name, _)| *name == key)
+                        .map_or(Iri::from(key), |(_, iri)| Iri::from(*iri))
+                };
+                if let Some(key) = key.strip_suffix("Id") {
+                    if !skips_reference(coercions, &raw_value) {
+                        values
+                            .entry(predicate(key))
+                            .or_default()
+                            .push(ArrayItem::ObjectRef(raw_value.into()));
+                    }
+                } else if let Some(value) = parse_literal_cell(&raw_value, coercions, None)
+                    .map_err(|err| cell_error(err, &format!("key {key}"), "value"))?
+                {
+                    values
+                        .entry(predicate(&key))
+                        .or_default()
+                        .extend(value.items());
+                }
+            }
+        }
+    }
+    let Some(id) = id else {
+        return Err(ToolError::InvalidWorkbook(
+            "the dataset has no `id` row".to_string(),
+        ));
+    };
+
+    let node = ensure_node(nodes, &id, graph);
+    node.types.extend(types);
+    for (predicate, mut items) in values {
+        let value = match (items.len(), items.pop()) {
+            (1, Some(ArrayItem::Scalar(scalar))) => PropertyValue::Scalar(scalar),
+            (1, Some(ArrayItem::ObjectRef(target))) => PropertyValue::ObjectRef(target),
+            (_, last) => {
+                items.extend(last);
+                PropertyValue::Array(ArrayValue::from_items(items))
+            }
+        };
+        node.insert_property(predicate, value);
+    }
+    Ok(())
+}
+
+fn link_target(
+    nodes: &mut BTreeMap<NodeKey, Node>,
+    parent_key: NodeKey,
+    predicate: &Iri,
+    target: NodeId,
+    filled: &HashSet<(NodeKey, Iri)>,
+    touched: &mut HashSet<(NodeKey, Iri)>,
+) -> Result<()> {
+    let node = nodes.get_mut(&parent_key).expect("parent node created");
+    let predicate_key = predicate.clone();
+    let fill_key = (parent_key, predicate_key.clone());
+    let repeated = filled.contains(&fill_key);
+    touched.insert(fill_key);
+
+    match node.properties.entry(predicate_key) {
+        Entry::Occupied(mut entry) => match entry.get_mut() {
+            PropertyValue::Array(ArrayValue::ObjectRefs(ids)) => {
+                if !repeated || !ids.contains(&target) {
+                    ids.push(target);
+                }
+            }
+            _ => {
+                return Err(ToolError::InvalidWorkbook(format!(
+                    "predicate '{predicate}' is not an object reference array"
+                )));
+            }
+        },
+        Entry::Vacant(entry) => {
+            entry.insert(PropertyValue::Array(ArrayValue::ObjectRefs(vec![target])));
+        }
+    }
+    Ok(())
+}
+
+/// Derives a stable `urn:uuid:` id for the intermediate node of a qualified
+/// relationship from its parent, predicate, target, and attribute cells, so
+/// copies of the row on the child sheets of other types yield the same node.
+fn relationship_id(
+    parent: &NodeKey,
+    predicate: &Iri,
+    target: &NodeId,
+    attributes: &[(String, &str, (Iri, PropertyValue))],
+) -> String {
+    let mut canonical = format!(
+        "{}\u{1f}{}\u{1f}{predicate}\u{1f}{target}",
+        parent.1,
+        parent.0.as_deref().unwrap_or_default()
+    );
+    let mut cells: Vec<_> = attributes
+        .iter()
+        .map(|(header, text, _)| (header.as_str(), *text))
+        .collect();
+    cells.sort_unstable();
+    for (header, text) in cells {
+        canonical.push_str(&format!("\u{1f}{header}={text}"));
+    }
+    let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, canonical.as_bytes());
+    format!("urn:uuid:{uuid}")
+}
+
+/// Adds the cell a workbook error was raised for to its message.
+fn cell_error(err: ToolError, location: &str, column: &str) -> ToolError {
+    match err {
+        ToolError::InvalidWorkbook(message) => {
+            ToolError::InvalidWorkbook(format!("{location} column '{column}': {message}"))
+        }
+        other => other,
+    }
+}
+
+/// Describes the cell at `at` in column `column` holding `text`, which
+/// could not be read because of `err`.
+fn invalid_cell(err: ToolError, at: CellLocation, column: &str, text: &str) -> CellError {
+    let reason = match err {
+        ToolError::InvalidWorkbook(message) => message,
+        other => other.to_string(),
+    };
+    CellError {
+        sheet: at.sheet,
+        row: at.row,
+        column: column.to_string(),
+        text: text.to_string(),
+        reason,
+    }
+}
+
+/// Adds the sheet a workbook error was raised for to its message.
+fn sheet_error(err: ToolError, sheet_name: &str) -> ToolError {
+    match err {
+        ToolError::InvalidWorkbook(message) => {
+            ToolError::InvalidWorkbook(format!("sheet '{sheet_name}', {message}"))
+        }
+        other => other,
+    }
+}
+
+/// Extracts the header row, returning an empty collection when absent.
+/// Expands the compact headers of columns not declared in `Metadata`, such
+/// as ones users add, with the context terms and prefixes the workbook
+/// declares.
+fn expand_headers(
+    rows: &mut [Vec<String>],
+    declared: &HashMap<String, DeclaredColumn>,
+    prefixes: &BTreeMap<String, String>,
+    terms: &BTreeMap<String, Iri>,
+) {
+    if prefixes.is_empty() && terms.is_empty() {
+        return;
+    }
+    let Some(headers) = rows.first_mut() else {
+        return;
+    };
+    for header in headers.iter_mut().skip(2) {
+        if declared.contains_key(header.as_str()) {
+            continue;
+        }
+        if let Some(iri) = terms.get(header.as_str()) {
+            *header = iri.to_string();
+        } else if let Some(iri) = header.strip_suffix("Id").and_then(|term| terms.get(term)) {
+            *header = format!("{iri}Id");
+        } else {
+            *header = Iri::expand(header, prefixes).into();
+        }
+    }
+}
+
+/// Number of rows above the header labelling bands of columns. Headers
+/// start with the `id` or `ParentId` column, which bands leave out.
+fn band_rows(rows: &[Vec<String>]) -> usize {
+    match rows {
+        [bands, header, ..]
+            if string_at(bands, 0).is_empty() && !string_at(header, 0).is_empty() =>
+        {
+            1
+        }
+        _ => 0,
+    }
+}
+
+fn read_headers(rows: &[Vec<String>]) -> Vec<String> {
+    rows.first().cloned().unwrap_or_default()
+}
+
+/// Returns the cell at `index`, or an empty string when missing.
+fn string_at(row: &[String], index: usize) -> String {
+    row.get(index).cloned().unwrap_or_default()
+}
+
+/// Returns the node matching `id` and `graph`, creating it when needed.
+fn ensure_node<'a>(
+    nodes: &'a mut BTreeMap<NodeKey, Node>,
+    id: &str,
+    graph: Option<Iri>,
+) -> &'a mut Node {
+    let id_key = NodeId::from(id);
+    let key = (graph.clone(), id_key.clone());
+    let node = nodes
+        .entry(key)
+        .or_insert_with(|| Node::with_graph(id_key.clone(), graph.clone()));
+    node.set_graph(graph);
+    node
+}
+
+/// Converts a cell of a column declared in `Metadata` into a property value.
+/// Literals in a column with a declared datatype become literals of that
+/// datatype, and single literals in an array column become one-item arrays.
+/// Returns `None` for cells that `coercions` skip.
+fn parse_declared_cell(
+    column: &DeclaredColumn,
+    raw_value: &str,
+    coercions: &ScalarCoercions,
+) -> Result<Option<PropertyValue>> {
+    if column.kind == ColumnKind::Ref {
+        return Ok((!skips_reference(coercions, raw_value))
+            .then(|| PropertyValue::ObjectRef(raw_value.into())));
+    }
+    Ok(
+        parse_literal_cell(raw_value, coercions, column.datatype.as_deref())?.map(|value| {
+            match value {
+                PropertyValue::Scalar(scalar) if column.kind == ColumnKind::ScalarArray => {
+                    PropertyValue::Array(ArrayValue::Scalars(vec![scalar]))
+                }
+                other => other,
+            }
+        }),
+    )
+}
+
+/// Converts a header/value pair coming from a type sheet row into a property
+/// entry for columns without a `Metadata` declaration: headers ending in `Id`
+/// hold references and every other cell holds literals.
+fn parse_property_entry(
+    header: &str,
+    raw_value: &str,
+    coercions: &ScalarCoercions,
+) -> Result<Option<(Iri, PropertyValue)>> {
+    if let Some(predicate) = header.strip_suffix("Id") {
+        return Ok((!skips_reference(coercions, raw_value))
+            .then(|| (predicate.into(), PropertyValue::ObjectRef(raw_value.into()))));
+    }
+
+    Ok(parse_literal_cell(raw_value, coercions, None)?.map(|value| (header.into(), value)))
+}
+
+/// Parses a literal cell into a scalar or an array of scalars, or `None`
+/// when `coercions` skip it. Plain strings, numbers, and booleans become
+/// literals of `datatype`, when given; value objects and coerced text keep
+/// their own type.
+pub(crate) fn parse_literal_cell(
+    raw_value: &str,
+    coercions: &ScalarCoercions,
+    datatype: Option<&str>,
+) -> Result<Option<PropertyValue>> {
+    // Cells hold JSON or, in the bare encoding, plain text; a leading
+    // apostrophe marks text that would otherwise be read as JSON.
+    if let Some(text) = raw_value.strip_prefix(TEXT_PREFIX) {
+        let scalar = with_datatype(ScalarValue::String(text.to_string()), datatype)?;
+        return Ok(Some(PropertyValue::Scalar(scalar)));
+    }
+    if let Some(coercion) = coercions.get(raw_value.trim()) {
+        return Ok(coerced(coercion).map(PropertyValue::Scalar));
+    }
+    let Ok(parsed) = serde_json::from_str::<Value>(raw_value) else {
+        let scalar = with_datatype(ScalarValue::String(raw_value.to_string()), datatype)?;
+        return Ok(Some(PropertyValue::Scalar(scalar)));
+    };
+    Ok(match parsed {
+        Value::Array(items) => {
+            let mut coerced_items = Vec::with_capacity(items.len());
+            for item in items {
+                let value_object = item.is_object();
+                match value_to_item(item)? {
+                    ArrayItem::Scalar(scalar) if value_object => {
+                        coerced_items.push(ArrayItem::Scalar(scalar));
+                    }
+                    ArrayItem::Scalar(scalar) => coerced_items
+                        .extend(coerce(scalar, coercions, datatype)?.map(ArrayItem::Scalar)),
+                    reference => coerced_items.push(reference),
+                }
+            }
+            Some(PropertyValue::Array(ArrayValue::from_items(coerced_items)))
+        }
+        Value::Object(map) => Some(PropertyValue::Scalar(value_to_scalar(Value::Object(map))?)),
+        other => coerce(value_to_scalar(other)?, coercions, datatype)?.map(PropertyValue::Scalar),
+    })
+}
+
+/// Applies the rule matching a string scalar's text, if any, or else gives
+/// the scalar `datatype`.
+fn coerce(
+    scalar: ScalarValue,
+    coercions: &ScalarCoercions,
+    datatype: Option<&str>,
+) -> Result<Option<ScalarValue>> {
+    if let ScalarValue::String(text) = &scalar
+        && let Some(coercion) = coercions.get(text)
+    {
+        return Ok(coerced(coercion));
+    }
+    with_datatype(scalar, datatype).map(Some)
+}
+
+/// Reads a plain string, number, or boolean as a literal of `datatype`,
+/// failing when it is not a valid lexical form of a built-in XML Schema
+/// datatype.
+fn with_datatype(scalar: ScalarValue, datatype: Option<&str>) -> Result<ScalarValue> {
+    let Some(datatype) = datatype else {
+        return Ok(scalar);
+    };
+    let lexical = match scalar {
+        ScalarValue::String(text) => text,
+        ScalarValue::Integer(number) => number.to_string(),
+        ScalarValue::Double(number) => number.to_string(),
+        ScalarValue::Boolean(flag) => flag.to_string(),
+        other => return Ok(other),
+    };
+    // Date cells at midnight read as a bare date, and boolean cells saved
+    // as text by Excel as `TRUE` or `FALSE`.
+    let lexical = if datatype == XSD_DATE_TIME && is_valid_lexical(&lexical, XSD_DATE) {
+        format!("{lexical}T00:00:00")
+    } else if datatype == XSD_BOOLEAN
+        && (lexical.eq_ignore_ascii_case("true") || lexical.eq_ignore_ascii_case("false"))
+    {
+        lexical.to_ascii_lowercase()
+    } else {
+        lexical
+    };
+    if !is_valid_lexical(&lexical, datatype) {
+        return Err(ToolError::InvalidWorkbook(format!(
+            "'{lexical}' is not a valid <{datatype}> literal"
+        )));
+    }
+    Ok(ScalarValue::from_lexical(lexical, datatype))
+}
+
+/// Returns whether `lexical` is a valid lexical form of `datatype`. Only
+/// the built-in XML Schema datatypes the workbook writes are checked; other
+/// datatypes accept any text.
+fn is_valid_lexical(lexical: &str, datatype: &str) -> bool {
+    let digits = |text: &str| !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit());
+    let unsigned = |text: &str| text.strip_prefix(['+', '-']).unwrap_or(text).to_string();
+    match datatype {
+        XSD_BOOLEAN => matches!(lexical, "true" | "false" | "1" | "0"),
+        XSD_INTEGER => digits(&unsigned(lexical)),
+        XSD_DECIMAL => {
+            let text = unsigned(lexical);
+            let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+            (digits(whole) || digits(fraction))
+                && (whole.is_empty() || digits(whole))
+                && (fraction.is_empty() || digits(fraction))
+        }
+        XSD_DOUBLE => {
+            matches!(lexical, "INF" | "+INF" | "-INF" | "NaN")
+                || (!lexical.contains(['i', 'I', 'n', 'N']) && lexical.parse::<f64>().is_ok())
+        }
+        XSD_DATE => {
+            let date = lexical.strip_suffix('Z').unwrap_or(lexical);
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok()
+                || NaiveDate::parse_from_str(date, "%Y-%m-%d%:z").is_ok()
+        }
+        XSD_DATE_TIME => {
+            DateTime::parse_from_rfc3339(lexical).is_ok()
+                || NaiveDateTime::parse_from_str(lexical, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+        }
+        _ => true,
+    }
+}
+
+fn coerced(coercion: &Coercion) -> Option<ScalarValue> {
+    match coercion {
+        Coercion::Scalar(scalar) => Some(scalar.clone()),
+        Coercion::Skip => None,
+    }
+}
+
+/// Returns whether a reference cell is coerced away: references cannot
+/// become literals, so only rules skipping the text or reading it as null
+/// apply to them.
+fn skips_reference(coercions: &ScalarCoercions, raw_value: &str) -> bool {
+    matches!(
+        coercions.get(raw_value.trim()),
+        Some(Coercion::Skip | Coercion::Scalar(ScalarValue::Null))
+    )
+}
+
+/// Converts an array entry, reading `{"@id": ...}` objects as references as
+/// written for mixed arrays.
+fn value_to_item(value: Value) -> Result<ArrayItem> {
+    match value {
+        Value::Object(map) if map.len() == 1 && map.contains_key("@id") => match &map["@id"] {
+            Value::String(target) => Ok(ArrayItem::ObjectRef(target.into())),
+            other => Err(ToolError::InvalidWorkbook(format!(
+                "invalid reference {other} in array cell"
+            ))),
+        },
+        other => Ok(ArrayItem::Scalar(value_to_scalar(other)?)),
+    }
+}
+
+pub(crate) fn value_to_scalar(value: Value) -> Result<ScalarValue> {
+    Ok(match value {
+        Value::Null => ScalarValue::Null,
+        Value::Bool(value) => ScalarValue::Boolean(value),
+        Value::Number(number) => ScalarValue::from_json_number(&number)
+            .ok_or_else(|| ToolError::InvalidWorkbook("invalid number literal".into()))?,
+        Value::String(value) => ScalarValue::String(value),
+        Value::Object(map) if map.contains_key("@value") => value_object_to_scalar(&map)?,
+        other => ScalarValue::String(serde_json::to_string(&other)?),
+    })
+}
+
+/// Rebuilds a typed or language-tagged literal from the JSON-LD value object
+/// notation used for such cells.
+fn value_object_to_scalar(map: &serde_json::Map<String, Value>) -> Result<ScalarValue> {
+    let literal = map.get("@value").cloned().unwrap_or(Value::Null);
+    let lexical = match &literal {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+
+    if let Some(language) = map.get("@language").and_then(Value::as_str) {
+        return Ok(ScalarValue::lang_tagged(lexical, language));
+    }
+
+    match map.get("@type").and_then(Value::as_str) {
+        Some(datatype) => Ok(ScalarValue::from_lexical(lexical, datatype)),
+        None => value_to_scalar(literal),
+    }
+}
+
+/// Expands the `xsd:` prefix users may write in the `datatype` column.
+fn expand_datatype(datatype: String) -> String {
+    match datatype.strip_prefix("xsd:") {
+        Some(name) => format!("{XSD_NAMESPACE}{name}"),
+        None => datatype,
+    }
+}
+
+fn normalize_optional(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
@@ -0,0 +1,196 @@
+//! Character encodings of JSON-LD and RDF inputs.
+//!
+//! Parsers read UTF-8 only. Exports from older systems often arrive as
+//! UTF-16 or Latin-1 instead, so inputs are transcoded to UTF-8 on the way
+//! in: [`TextEncoding::Auto`] recognises byte order marks and unmarked
+//! UTF-16, and Latin-1, which cannot be told apart from UTF-8 by its bytes,
+//! is chosen explicitly.
+
+use std::io::{self, Read};
+
+use encoding_rs::{Decoder, DecoderResult, Encoding};
+use serde::Deserialize;
+
+use crate::aideon::tools::error::{Result, ToolError};
+
+/// Bytes read from the underlying reader at a time.
+const CHUNK: usize = 8 * 1024;
+
+/// Character encoding of a text input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextEncoding {
+    /// UTF-8 or UTF-16, told apart by the byte order mark or, for unmarked
+    /// UTF-16, by the zero bytes around the first ASCII character.
+    #[default]
+    Auto,
+    /// UTF-8.
+    #[serde(rename = "utf-8")]
+    Utf8,
+    /// UTF-16, little-endian.
+    #[serde(rename = "utf-16le")]
+    Utf16Le,
+    /// UTF-16, big-endian.
+    #[serde(rename = "utf-16be")]
+    Utf16Be,
+    /// ISO-8859-1, read as its Windows-1252 superset like web browsers do.
+    #[serde(rename = "latin-1")]
+    Latin1,
+}
+
+impl TextEncoding {
+    /// Decodes the bytes of a whole input, failing with
+    /// [`ToolError::TextEncoding`] on bytes that are not valid in the
+    /// encoding.
+    pub fn decode(self, bytes: &[u8]) -> Result<String> {
+        let mut text = String::with_capacity(bytes.len());
+        self.reader(bytes)
+            .read_to_string(&mut text)
+            .map_err(|err| ToolError::TextEncoding(err.to_string()))?;
+        Ok(text)
+    }
+
+    /// Wraps `reader` so that it yields UTF-8. Bytes that are not valid in
+    /// the encoding fail the read with [`io::ErrorKind::InvalidData`].
+    pub fn reader<R: Read>(self, reader: R) -> DecodingReader<R> {
+        DecodingReader {
+            inner: reader,
+            encoding: self,
+            decoder: None,
+            input: Vec::with_capacity(CHUNK),
+            start: 0,
+            output: vec![0; 3 * CHUNK],
+            output_start: 0,
+            output_end: 0,
+            consumed: 0,
+            last: false,
+        }
+    }
+
+    /// Returns the encoding of an input starting with `prefix` and the
+    /// length of its byte order mark.
+    fn detect(self, prefix: &[u8]) -> (&'static Encoding, usize) {
+        let bom = Encoding::for_bom(prefix);
+        match (self, bom) {
+            (Self::Auto, Some(found)) => found,
+            (Self::Auto, None) => match prefix {
+                [0, second, ..] if *second != 0 => (encoding_rs::UTF_16BE, 0),
+                [first, 0, ..] if *first != 0 => (encoding_rs::UTF_16LE, 0),
+                _ => (encoding_rs::UTF_8, 0),
+            },
+            (Self::Utf8, found) => (encoding_rs::UTF_8, bom_length(found, encoding_rs::UTF_8)),
+            (Self::Utf16Le, found) => (
+                encoding_rs::UTF_16LE,
+                bom_length(found, encoding_rs::UTF_16LE),
+            ),
+            (Self::Utf16Be, found) => (
+                encoding_rs::UTF_16BE,
+                bom_length(found, encoding_rs::UTF_16BE),
+            ),
+            (Self::Latin1, _) => (encoding_rs::WINDOWS_1252, 0),
+        }
+    }
+}
+
+fn bom_length(found: Option<(&'static Encoding, usize)>, expected: &'static Encoding) -> usize {
+    match found {
+        Some((encoding, length)) if encoding == expected => length,
+        _ => 0,
+    }
+}
+
+/// Reader transcoding a text input to UTF-8, created by
+/// [`TextEncoding::reader`].
+pub struct DecodingReader<R> {
+    inner: R,
+    encoding: TextEncoding,
+    /// Chosen once the first bytes have been seen.
+    decoder: Option<Decoder>,
+    input: Vec<u8>,
+    start: usize,
+    output: Vec<u8>,
+    output_start: usize,
+    output_end: usize,
+    /// Input bytes decoded so far, for locating malformed ones.
+    consumed: usize,
+    last: bool,
+}
+
+impl<R: Read> DecodingReader<R> {
+    /// Reads more input, returning whether any arrived.
+    fn fill(&mut self) -> io::Result<bool> {
+        self.input.drain(..self.start);
+        self.start = 0;
+        let filled = self.input.len();
+        self.input.resize(filled + CHUNK, 0);
+        let read = loop {
+            match self.inner.read(&mut self.input[filled..]) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+        let read = read.inspect_err(|_| self.input.truncate(filled))?;
+        self.input.truncate(filled + read);
+        Ok(read > 0)
+    }
+
+    /// Chooses the decoder from the first bytes of the input.
+    fn choose_decoder(&mut self) -> io::Result<()> {
+        if self.decoder.is_none() {
+            // A byte order mark may take three bytes.
+            while self.input.len() < 3 && !self.last {
+                self.last = !self.fill()?;
+            }
+            let (encoding, bom) = self.encoding.detect(&self.input);
+            self.start = bom;
+            self.consumed = bom;
+            self.decoder = Some(encoding.new_decoder_without_bom_handling());
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.output_start == self.output_end {
+            if self.start == self.input.len() {
+                if self.last && self.decoder.is_some() {
+                    return Ok(0);
+                }
+                if !self.last {
+                    self.last = !self.fill()?;
+                }
+            }
+            let auto = self.encoding == TextEncoding::Auto;
+            self.choose_decoder()?;
+            let last = self.last;
+            let decoder = self.decoder.as_mut().expect("decoder chosen");
+            let encoding = decoder.encoding();
+            let (result, read, written) = decoder.decode_to_utf8_without_replacement(
+                &self.input[self.start..],
+                &mut self.output,
+                last,
+            );
+            self.start += read;
+            self.consumed += read;
+            self.output_start = 0;
+            self.output_end = written;
+            if let DecoderResult::Malformed(bad, after) = result {
+                let offset = self.consumed - usize::from(bad) - usize::from(after);
+                let mut message =
+                    format!("input is not valid {} at byte {offset}", encoding.name());
+                if auto {
+                    message.push_str("; name its encoding, such as latin-1, to transcode it");
+                }
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+            if last && self.start == self.input.len() && written == 0 {
+                return Ok(0);
+            }
+        }
+        let length = buf.len().min(self.output_end - self.output_start);
+        buf[..length].copy_from_slice(&self.output[self.output_start..self.output_start + length]);
+        self.output_start += length;
+        Ok(length)
+    }
+}
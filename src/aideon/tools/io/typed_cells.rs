@@ -0,0 +1,201 @@
+//! Typed values of the cells of flattened tables, for outputs whose columns
+//! have a type of their own, such as SQLite databases and Parquet files.
+//!
+//! The `scalar` columns declared in `Metadata` are decoded the way a reader
+//! of the workbook decodes them, so their cells hold the literal itself
+//! rather than its cell encoding: strings lose their quotes and value
+//! objects become their lexical form. A column whose datatype is an XML
+//! Schema integer, floating point, decimal, boolean, date, or date-time
+//! type takes that type, as long as every one of its cells holds a literal
+//! of the datatype; otherwise it stays text. Other columns, such as those
+//! holding ids, arrays, or references, keep the text of their cells.
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::aideon::tools::flatten::SheetTable;
+use crate::aideon::tools::io::coercion::ScalarCoercions;
+use crate::aideon::tools::io::excel_write::{LiteralColumns, literal_datatypes};
+use crate::aideon::tools::io::tabular::parse_literal_cell;
+use crate::aideon::tools::model::{
+    PropertyValue, ScalarValue, XSD_BOOLEAN, XSD_DATE, XSD_DATE_TIME, XSD_DECIMAL, XSD_DOUBLE,
+    XSD_INTEGER, XSD_NAMESPACE,
+};
+
+/// Integer datatypes of XML Schema besides `xsd:integer`, by local name.
+const INTEGER_TYPES: [&str; 12] = [
+    "long",
+    "int",
+    "short",
+    "byte",
+    "nonNegativeInteger",
+    "positiveInteger",
+    "nonPositiveInteger",
+    "negativeInteger",
+    "unsignedLong",
+    "unsignedInt",
+    "unsignedShort",
+    "unsignedByte",
+];
+
+/// Type of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ColumnType {
+    Integer,
+    Real,
+    /// Decimal numbers, kept as their lexical form so no digit is lost.
+    Numeric,
+    Boolean,
+    Date,
+    DateTime,
+    Text,
+}
+
+impl ColumnType {
+    /// Returns the type of the columns holding literals of `datatype`.
+    fn of(datatype: &str) -> Self {
+        let local = datatype.strip_prefix(XSD_NAMESPACE);
+        match datatype {
+            XSD_INTEGER => ColumnType::Integer,
+            XSD_DOUBLE => ColumnType::Real,
+            XSD_DECIMAL => ColumnType::Numeric,
+            XSD_BOOLEAN => ColumnType::Boolean,
+            XSD_DATE => ColumnType::Date,
+            XSD_DATE_TIME => ColumnType::DateTime,
+            _ if local.is_some_and(|local| INTEGER_TYPES.contains(&local)) => ColumnType::Integer,
+            _ if local == Some("float") => ColumnType::Real,
+            _ => ColumnType::Text,
+        }
+    }
+}
+
+/// Value of a cell; blank cells are nulls.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TypedCell {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Boolean(bool),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    Text(String),
+}
+
+/// A column of a table with the values of its cells, one per row.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TypedColumn {
+    pub(crate) column_type: ColumnType,
+    pub(crate) cells: Vec<TypedCell>,
+}
+
+/// Returns the columns of `table` with their types and values, given the
+/// `scalar` columns declared in `Metadata`.
+pub(crate) fn typed_columns(table: &SheetTable, literals: &LiteralColumns) -> Vec<TypedColumn> {
+    literal_datatypes(table, literals)
+        .into_iter()
+        .enumerate()
+        .map(|(index, datatype)| {
+            let raw: Vec<&str> = table
+                .rows
+                .iter()
+                .map(|row| row.get(index).map(String::as_str).unwrap_or_default())
+                .collect();
+            match datatype.and_then(|datatype| literal_column(&raw, datatype)) {
+                Some(column) => column,
+                None => text_column(raw.into_iter().map(str::to_string)),
+            }
+        })
+        .collect()
+}
+
+/// Decodes the cells of a `scalar` column of `datatype`, empty when its
+/// literals have several, or `None` when a cell holds anything but a single
+/// literal.
+fn literal_column(raw: &[&str], datatype: &str) -> Option<TypedColumn> {
+    let declared = Some(datatype).filter(|datatype| !datatype.is_empty());
+    let scalars: Vec<Option<ScalarValue>> = raw
+        .iter()
+        .map(|raw| {
+            if raw.is_empty() {
+                return Some(None);
+            }
+            match parse_literal_cell(raw, &ScalarCoercions::default(), declared) {
+                Ok(Some(PropertyValue::Scalar(ScalarValue::Null)) | None) => Some(None),
+                Ok(Some(PropertyValue::Scalar(scalar))) => Some(Some(scalar)),
+                _ => None,
+            }
+        })
+        .collect::<Option<_>>()?;
+    let column_type = ColumnType::of(datatype);
+    if column_type != ColumnType::Text {
+        let cells: Option<Vec<TypedCell>> = scalars
+            .iter()
+            .map(|scalar| match scalar {
+                None => Some(TypedCell::Null),
+                Some(scalar) if scalar.datatype() == Some(datatype) => {
+                    typed_cell(&lexical(scalar), column_type)
+                }
+                Some(_) => None,
+            })
+            .collect();
+        if let Some(cells) = cells {
+            return Some(TypedColumn { column_type, cells });
+        }
+    }
+    Some(text_column(scalars.iter().map(|scalar| {
+        scalar.as_ref().map(lexical).unwrap_or_default()
+    })))
+}
+
+/// Reads the lexical form of a literal as a value of `column_type`.
+fn typed_cell(lexical: &str, column_type: ColumnType) -> Option<TypedCell> {
+    Some(match column_type {
+        ColumnType::Integer => TypedCell::Integer(lexical.parse().ok()?),
+        ColumnType::Real => TypedCell::Real(lexical.parse().ok()?),
+        ColumnType::Numeric => {
+            lexical.parse::<f64>().ok()?;
+            TypedCell::Text(lexical.to_string())
+        }
+        ColumnType::Boolean => match lexical {
+            "true" | "1" => TypedCell::Boolean(true),
+            "false" | "0" => TypedCell::Boolean(false),
+            _ => return None,
+        },
+        ColumnType::Date => TypedCell::Date(NaiveDate::parse_from_str(lexical, "%Y-%m-%d").ok()?),
+        ColumnType::DateTime => TypedCell::DateTime(
+            NaiveDateTime::parse_from_str(lexical, "%Y-%m-%dT%H:%M:%S%.f").ok()?,
+        ),
+        ColumnType::Text => TypedCell::Text(lexical.to_string()),
+    })
+}
+
+/// Returns the lexical form of `scalar`, without its datatype or language.
+fn lexical(scalar: &ScalarValue) -> String {
+    match scalar {
+        ScalarValue::String(text) => text.clone(),
+        ScalarValue::Integer(number) => number.to_string(),
+        ScalarValue::Double(number) => number.to_string(),
+        ScalarValue::Boolean(flag) => flag.to_string(),
+        ScalarValue::Null => String::new(),
+        ScalarValue::LangString { value, .. } => value.clone(),
+        other => other
+            .typed_lexical()
+            .map(|(lexical, _)| lexical.into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+/// Returns a text column of `cells`, blank cells being nulls.
+fn text_column(cells: impl Iterator<Item = String>) -> TypedColumn {
+    TypedColumn {
+        column_type: ColumnType::Text,
+        cells: cells
+            .map(|cell| {
+                if cell.is_empty() {
+                    TypedCell::Null
+                } else {
+                    TypedCell::Text(cell)
+                }
+            })
+            .collect(),
+    }
+}
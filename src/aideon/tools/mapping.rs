@@ -0,0 +1,222 @@
+//! Mapping rows of foreign tables onto nodes.
+//!
+//! Sources that are not laid out like exported workbooks, such as the
+//! results of SQL queries, come as rows of named cells. A [`RowMapping`]
+//! declares how each row becomes a node: the type it gets, templates
+//! building its id and graph from the row's cells, and the predicate each
+//! column fills. Templates name cells in braces:
+//!
+//! ```json
+//! {
+//!   "type": "schema:Person",
+//!   "id": "https://example.com/people/{person_id}",
+//!   "columns": {
+//!     "name": "schema:name",
+//!     "born": { "predicate": "schema:birthDate", "datatype": "xsd:date" },
+//!     "manager_id": {
+//!       "predicate": "schema:knows",
+//!       "reference": "https://example.com/people/{manager_id}"
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Types, predicates, and datatypes may be compact IRIs. Empty cells, such
+//! as SQL `NULL`, give no value, and columns without a mapping are ignored.
+//! Rows mapping to the same node, for instance one per entry of a joined
+//! table, add their values to it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::model::{ArrayValue, Iri, Node, NodeId, PropertyValue, ScalarValue};
+
+/// Cells of a row by column name; [`ScalarValue::Null`] marks empty cells.
+pub type Row = BTreeMap<String, ScalarValue>;
+
+/// How the rows of one table become nodes.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RowMapping {
+    /// Type given to every node, if any.
+    #[serde(rename = "type", default)]
+    pub type_name: Option<String>,
+    /// Template of the node ids.
+    pub id: String,
+    /// Template of the graph holding the nodes, the default graph if unset.
+    #[serde(default)]
+    pub graph: Option<String>,
+    /// Property filled by each mapped column.
+    #[serde(default)]
+    pub columns: BTreeMap<String, ColumnMapping>,
+}
+
+/// The property a column fills: a predicate alone keeps the cell's value as
+/// read, the long form can retype it or turn it into a reference.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ColumnMapping {
+    /// Predicate given the cell's value.
+    Predicate(String),
+    /// Predicate given the cell's value, retyped or referenced.
+    Property {
+        /// Predicate given the value.
+        predicate: String,
+        /// Datatype the cell's text is read as, such as `xsd:date`.
+        #[serde(default)]
+        datatype: Option<String>,
+        /// Template of the id of the node the value refers to.
+        #[serde(default)]
+        reference: Option<String>,
+    },
+}
+
+/// Builds nodes from rows, merging the rows that map to the same node.
+#[derive(Debug, Default)]
+pub struct RowMapper {
+    prefixes: BTreeMap<String, String>,
+    nodes: Vec<Node>,
+    index: HashMap<(Option<Iri>, NodeId), usize>,
+}
+
+impl RowMapper {
+    /// Creates a mapper expanding compact IRIs with `prefixes`.
+    pub fn new(prefixes: BTreeMap<String, String>) -> Self {
+        RowMapper {
+            prefixes,
+            ..RowMapper::default()
+        }
+    }
+
+    /// Adds the node `mapping` makes of `row`.
+    ///
+    /// Fails with [`ToolError::InvalidConfig`] when a template names a
+    /// column the row lacks, or the id template an empty cell.
+    pub fn add(&mut self, mapping: &RowMapping, row: &Row) -> Result<()> {
+        let id = fill(&mapping.id, row)?
+            .ok_or_else(|| invalid(format!("empty cell in id template '{}'", mapping.id)))?;
+        let graph = match &mapping.graph {
+            Some(template) => fill(template, row)?.map(Iri::from),
+            None => None,
+        };
+        let key = (graph.clone(), NodeId::from(id.as_str()));
+        let position = match self.index.get(&key) {
+            Some(&position) => position,
+            None => {
+                self.nodes.push(Node::with_graph(id, graph));
+                self.index.insert(key, self.nodes.len() - 1);
+                self.nodes.len() - 1
+            }
+        };
+        if let Some(type_name) = &mapping.type_name {
+            let type_name = Iri::expand(type_name, &self.prefixes);
+            self.nodes[position].types.insert(type_name);
+        }
+        for (column, target) in &mapping.columns {
+            let cell = row
+                .get(column)
+                .ok_or_else(|| invalid(format!("no column '{column}' in the row")))?;
+            if matches!(cell, ScalarValue::Null) {
+                continue;
+            }
+            let (predicate, value) = match target {
+                ColumnMapping::Predicate(predicate) => {
+                    (predicate, PropertyValue::Scalar(cell.clone()))
+                }
+                ColumnMapping::Property {
+                    predicate,
+                    reference: Some(template),
+                    ..
+                } => match fill(template, row)? {
+                    Some(target) => (predicate, PropertyValue::ObjectRef(NodeId::from(target))),
+                    None => continue,
+                },
+                ColumnMapping::Property {
+                    predicate,
+                    datatype,
+                    reference: None,
+                } => {
+                    let value = match datatype {
+                        Some(datatype) => {
+                            let datatype = Iri::expand(datatype, &self.prefixes);
+                            ScalarValue::from_lexical(lexical(cell), &datatype)
+                        }
+                        None => cell.clone(),
+                    };
+                    (predicate, PropertyValue::Scalar(value))
+                }
+            };
+            let predicate = Iri::expand(predicate, &self.prefixes);
+            add_value(&mut self.nodes[position], predicate, value);
+        }
+        Ok(())
+    }
+
+    /// Returns the nodes built so far, in the order their first row came.
+    pub fn into_nodes(self) -> Vec<Node> {
+        self.nodes
+    }
+}
+
+/// Adds `value` to the values `node` holds for `predicate`, unless it is
+/// already among them.
+fn add_value(node: &mut Node, predicate: Iri, value: PropertyValue) {
+    match node.properties.get_mut(&predicate) {
+        Some(existing) => {
+            let mut items = existing.items();
+            let item = value.items().remove(0);
+            if !items.contains(&item) {
+                items.push(item);
+                *existing = PropertyValue::Array(ArrayValue::from_items(items));
+            }
+        }
+        None => node.insert_property(predicate, value),
+    }
+}
+
+/// Replaces the `{column}` placeholders of `template` with the text of the
+/// row's cells, or returns `None` when one of them is empty.
+fn fill(template: &str, row: &Row) -> Result<Option<String>> {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| invalid(format!("unclosed placeholder in template '{template}'")))?;
+        let column = &rest[start + 1..start + end];
+        let cell = row
+            .get(column)
+            .ok_or_else(|| invalid(format!("no column '{column}' in the row")))?;
+        if matches!(cell, ScalarValue::Null) {
+            return Ok(None);
+        }
+        filled.push_str(&rest[..start]);
+        filled.push_str(&lexical(cell));
+        rest = &rest[start + end + 1..];
+    }
+    filled.push_str(rest);
+    Ok(Some(filled))
+}
+
+/// Text of a cell, without datatype or language.
+fn lexical(cell: &ScalarValue) -> String {
+    match cell {
+        ScalarValue::String(value)
+        | ScalarValue::TypedLiteral { value, .. }
+        | ScalarValue::LangString { value, .. } => value.clone(),
+        ScalarValue::Integer(number) => number.to_string(),
+        ScalarValue::Double(number) => number.to_string(),
+        ScalarValue::Boolean(flag) => flag.to_string(),
+        ScalarValue::Null => String::new(),
+        ScalarValue::Date(_) | ScalarValue::DateTime(_) => cell
+            .typed_lexical()
+            .map(|(value, _)| value.into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+fn invalid(message: String) -> ToolError {
+    ToolError::InvalidConfig(message)
+}
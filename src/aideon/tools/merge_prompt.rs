@@ -0,0 +1,100 @@
+//! Interactive resolution of merge conflicts.
+//!
+//! [`ConflictPrompt`] shows each conflict of
+//! [`sync::merge_with`](crate::aideon::tools::sync::merge_with) with both
+//! values in their expanded JSON-LD form and asks which to keep:
+//!
+//! ```text
+//! conflict on https://example.com/people/1 [https://schema.org/name]
+//!   ours:   "Alice"
+//!   theirs: "Alice Smith"
+//! keep [o]urs, [t]heirs, or [e]dit?
+//! ```
+//!
+//! Ours is the left input and theirs the right one. Editing reads a new
+//! value as JSON-LD, such as `"Alice Jones"`, `{"@id": "https://…"}`, or
+//! `["a", "b"]`.
+
+use std::io::{BufRead, Write};
+
+use serde_json::{Map, Value};
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::io::jsonld;
+use crate::aideon::tools::model::PropertyValue;
+use crate::aideon::tools::sync::MergeConflict;
+
+/// Asks for the resolution of merge conflicts on `output`, reading the
+/// answers line by line from `input`.
+pub struct ConflictPrompt<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> ConflictPrompt<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// Asks for the value of `conflict` until a valid answer is given,
+    /// failing with the conflict when the input ends first.
+    pub fn resolve(&mut self, conflict: &MergeConflict) -> Result<PropertyValue> {
+        write!(
+            self.output,
+            "conflict on {} [{}]",
+            conflict.id, conflict.predicate
+        )?;
+        if let Some(graph) = &conflict.graph {
+            write!(self.output, " in {graph}")?;
+        }
+        writeln!(self.output)?;
+        writeln!(self.output, "  ours:   {}", conflict.left.to_json())?;
+        writeln!(self.output, "  theirs: {}", conflict.right.to_json())?;
+
+        loop {
+            let Some(answer) = self.ask("keep [o]urs, [t]heirs, or [e]dit? ")? else {
+                return Err(conflict.error("no resolution was given"));
+            };
+            match answer.to_ascii_lowercase().as_str() {
+                "o" | "ours" => return Ok(conflict.left.clone()),
+                "t" | "theirs" => return Ok(conflict.right.clone()),
+                "e" | "edit" => {
+                    let Some(text) = self.ask("value as JSON-LD: ")? else {
+                        return Err(conflict.error("no resolution was given"));
+                    };
+                    match parse_value(conflict, &text) {
+                        Ok(value) => return Ok(value),
+                        Err(reason) => writeln!(self.output, "{reason}")?,
+                    }
+                }
+                _ => writeln!(self.output, "answer o, t, or e")?,
+            }
+        }
+    }
+
+    /// Prints `question` and reads the trimmed answer, `None` at the end of
+    /// the input.
+    fn ask(&mut self, question: &str) -> Result<Option<String>> {
+        write!(self.output, "{question}")?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim().to_string()))
+    }
+}
+
+/// Reads `text` as the JSON-LD value of the conflicting predicate.
+fn parse_value(conflict: &MergeConflict, text: &str) -> std::result::Result<PropertyValue, String> {
+    let value: Value = serde_json::from_str(text).map_err(|err| format!("invalid JSON: {err}"))?;
+    let mut object = Map::new();
+    object.insert("@id".into(), Value::String(conflict.id.to_string()));
+    object.insert(conflict.predicate.to_string(), value);
+    let nodes = jsonld::parse_jsonld_document(&Value::Object(object))
+        .map_err(|err| format!("invalid JSON-LD value: {err}"))?;
+    nodes
+        .into_iter()
+        .find_map(|mut node| node.properties.remove(&conflict.predicate))
+        .ok_or_else(|| "the value is empty".to_string())
+}
@@ -1,7 +1,36 @@
+pub mod batch;
+pub mod blank_nodes;
+pub mod change_feed;
+pub mod config;
+pub mod convert;
+pub mod diff;
+pub mod equivalence;
 pub mod error;
+pub mod filter;
 pub mod flatten;
+pub mod history;
+pub mod inspect;
+pub mod interrupt;
 pub mod io;
+pub mod mapping;
+pub mod merge_prompt;
 pub mod model;
+pub mod provenance;
+pub mod query;
+pub mod rdfc;
+pub mod report;
+pub mod roundtrip;
+pub mod same_as;
+pub mod schema;
+pub mod script;
+pub mod shacl;
+pub mod signing;
+pub mod site;
+pub mod snippet;
 pub mod sync;
+pub mod telemetry;
+pub mod validate;
+pub mod view;
+pub mod visualize;
 
 pub use error::{Result, ToolError};
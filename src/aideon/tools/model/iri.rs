@@ -0,0 +1,293 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{ArrayItem, ArrayValue, Node, PropertyValue};
+
+/// Shared, immutable string used for node ids, graph names, type IRIs, and
+/// predicates.
+///
+/// Cloning an `Iri` only bumps a reference count, and values obtained from
+/// the same [`Interner`] share one allocation, so equal values compare by
+/// pointer before falling back to their text. It dereferences to `str` and
+/// borrows as `str`, so maps keyed by `Iri` can be looked up with `&str`.
+#[derive(Clone)]
+pub struct Iri(Arc<str>);
+
+impl Iri {
+    /// Creates a value that does not share its allocation with any other.
+    pub fn new(value: &str) -> Self {
+        Self(Arc::from(value))
+    }
+
+    /// Returns the text of the value.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns whether `self` and `other` share one allocation, as values
+    /// handed out by the same [`Interner`] for the same text do.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Expands a compact IRI such as `schema:name` whose prefix is a key of
+    /// `prefixes`; other values, including full IRIs, are kept as written.
+    pub fn expand(value: &str, prefixes: &BTreeMap<String, String>) -> Self {
+        match value.split_once(':') {
+            Some((prefix, local)) if !local.starts_with("//") => match prefixes.get(prefix) {
+                Some(namespace) => Self::from(format!("{namespace}{local}")),
+                None => Self::from(value),
+            },
+            _ => Self::from(value),
+        }
+    }
+
+    /// Abbreviates `value` with the longest namespace of `prefixes` it starts
+    /// with, the reverse of [`Self::expand`], or returns `None` when no
+    /// namespace leaves a local name behind.
+    pub fn compact(value: &str, prefixes: &BTreeMap<String, String>) -> Option<String> {
+        prefixes
+            .iter()
+            .filter(|(prefix, namespace)| {
+                !prefix.is_empty() && !prefix.contains(':') && !namespace.is_empty()
+            })
+            .filter_map(|(prefix, namespace)| {
+                let local = value.strip_prefix(namespace.as_str())?;
+                (!local.is_empty() && !local.starts_with("//"))
+                    .then_some((prefix, namespace, local))
+            })
+            .max_by_key(|(_, namespace, _)| namespace.len())
+            .map(|(prefix, _, local)| format!("{prefix}:{local}"))
+    }
+}
+
+impl Deref for Iri {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Iri {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Iri {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for Iri {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+impl Eq for Iri {}
+
+impl Ord for Iri {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if Arc::ptr_eq(&self.0, &other.0) {
+            Ordering::Equal
+        } else {
+            self.0.cmp(&other.0)
+        }
+    }
+}
+
+impl PartialOrd for Iri {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for Iri {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialEq<str> for Iri {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Iri {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Iri {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl PartialEq<Iri> for str {
+    fn eq(&self, other: &Iri) -> bool {
+        self == &*other.0
+    }
+}
+
+impl PartialEq<Iri> for &str {
+    fn eq(&self, other: &Iri) -> bool {
+        *self == &*other.0
+    }
+}
+
+impl PartialEq<Iri> for String {
+    fn eq(&self, other: &Iri) -> bool {
+        **self == *other.0
+    }
+}
+
+impl fmt::Debug for Iri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Iri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Iri {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&String> for Iri {
+    fn from(value: &String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for Iri {
+    fn from(value: String) -> Self {
+        Self(Arc::from(value))
+    }
+}
+
+impl From<&Iri> for Iri {
+    fn from(value: &Iri) -> Self {
+        value.clone()
+    }
+}
+
+impl From<Iri> for String {
+    fn from(value: Iri) -> Self {
+        value.0.to_string()
+    }
+}
+
+impl Serialize for Iri {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Iri {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Iri::from)
+    }
+}
+
+/// Pool handing out one shared [`Iri`] per distinct text.
+///
+/// Readers keep an interner for the duration of a parse so that the
+/// predicates, types, and ids repeated across a dataset are stored once.
+/// Streaming readers keep one per batch, so the pool does not outgrow it.
+#[derive(Debug, Default)]
+pub struct Interner {
+    pool: HashSet<Iri>,
+}
+
+impl Interner {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pooled value for `value`, adding it on first use.
+    pub fn intern(&mut self, value: &str) -> Iri {
+        if let Some(existing) = self.pool.get(value) {
+            return existing.clone();
+        }
+        let iri = Iri::new(value);
+        self.pool.insert(iri.clone());
+        iri
+    }
+
+    /// Returns `node` with its id, graph, types, predicates, and reference
+    /// targets replaced by their pooled values.
+    pub fn intern_node(&mut self, node: Node) -> Node {
+        Node {
+            id: self.intern(&node.id),
+            graph: node.graph.map(|graph| self.intern(&graph)),
+            types: node
+                .types
+                .into_iter()
+                .map(|type_name| self.intern(&type_name))
+                .collect(),
+            properties: node
+                .properties
+                .into_iter()
+                .map(|(predicate, value)| (self.intern(&predicate), self.intern_value(value)))
+                .collect(),
+            lists: node
+                .lists
+                .into_iter()
+                .map(|predicate| self.intern(&predicate))
+                .collect(),
+        }
+    }
+
+    fn intern_value(&mut self, value: PropertyValue) -> PropertyValue {
+        match value {
+            PropertyValue::ObjectRef(target) => PropertyValue::ObjectRef(self.intern(&target)),
+            PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => PropertyValue::Array(
+                ArrayValue::ObjectRefs(targets.iter().map(|target| self.intern(target)).collect()),
+            ),
+            PropertyValue::Array(ArrayValue::Mixed(items)) => {
+                PropertyValue::Array(ArrayValue::Mixed(
+                    items
+                        .into_iter()
+                        .map(|item| match item {
+                            ArrayItem::ObjectRef(target) => {
+                                ArrayItem::ObjectRef(self.intern(&target))
+                            }
+                            scalar => scalar,
+                        })
+                        .collect(),
+                ))
+            }
+            scalar => scalar,
+        }
+    }
+
+    /// Returns the number of distinct values in the pool.
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Returns whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
+}
@@ -1,11 +1,41 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
 
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
+mod iri;
+
+pub use iri::{Interner, Iri};
+
 /// Identifier assigned to a node. It mirrors the JSON-LD `@id` semantics and
-/// intentionally keeps the plain string representation for ease of
-/// interoperability with Excel.
-pub type NodeId = String;
+/// serialises as a plain string for ease of interoperability with Excel.
+pub type NodeId = Iri;
+
+/// Namespace of the XML Schema datatypes.
+pub const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema#";
+/// XML Schema string datatype IRI.
+pub const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+/// XML Schema boolean datatype IRI.
+pub const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+/// XML Schema integer datatype IRI.
+pub const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+/// XML Schema decimal datatype IRI.
+pub const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
+/// XML Schema double datatype IRI.
+pub const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+/// XML Schema date datatype IRI.
+pub const XSD_DATE: &str = "http://www.w3.org/2001/XMLSchema#date";
+/// XML Schema dateTime datatype IRI.
+pub const XSD_DATE_TIME: &str = "http://www.w3.org/2001/XMLSchema#dateTime";
+/// Datatype IRI of language-tagged strings.
+pub const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+
+/// Lexical form of [`ScalarValue::Date`] literals.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+/// Lexical form of [`ScalarValue::DateTime`] literals; fractional seconds
+/// are only written when present.
+const DATE_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
 
 /// Represents a scalar literal value in the graph.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -13,29 +43,178 @@ pub type NodeId = String;
 pub enum ScalarValue {
     /// Plain string literal.
     String(String),
-    /// Floating point number literal.
-    Number(f64),
+    /// Integer literal (`xsd:integer`), such as a JSON number without a
+    /// fraction or exponent.
+    Integer(i64),
+    /// Floating point literal (`xsd:double`).
+    Double(f64),
     /// Boolean literal.
     Boolean(bool),
     /// Explicit JSON `null` literal.
     Null,
+    /// `xsd:date` literal without a timezone.
+    Date(NaiveDate),
+    /// `xsd:dateTime` literal without a timezone.
+    DateTime(NaiveDateTime),
+    /// Literal carrying an explicit datatype IRI.
+    TypedLiteral {
+        /// Lexical form of the literal.
+        value: String,
+        /// Datatype IRI of the literal.
+        datatype: String,
+    },
+    /// Language-tagged string literal (`rdf:langString`).
+    LangString {
+        /// Lexical form of the literal.
+        value: String,
+        /// BCP 47 language tag.
+        language: String,
+    },
 }
 
 impl ScalarValue {
+    /// Creates a literal with an explicit datatype IRI.
+    pub fn typed(value: impl Into<String>, datatype: impl Into<String>) -> Self {
+        ScalarValue::TypedLiteral {
+            value: value.into(),
+            datatype: datatype.into(),
+        }
+    }
+
+    /// Builds a scalar from a lexical form and datatype IRI. Datatypes with a
+    /// native representation (strings, booleans, integers, doubles, and
+    /// dates) map onto the corresponding variants; everything else, including
+    /// `xsd:decimal` and numeric and boolean literals that fail to parse, is
+    /// kept as a [`ScalarValue::TypedLiteral`]. Integers, dates, and date-times become
+    /// native values only when written in the form those variants print,
+    /// without a sign, leading zeros, or a timezone, so their lexical form is
+    /// kept exactly.
+    pub fn from_lexical(value: impl Into<String>, datatype: &str) -> Self {
+        let value = value.into();
+        match datatype {
+            XSD_STRING => ScalarValue::String(value),
+            XSD_BOOLEAN => match value.as_str() {
+                "true" | "1" => ScalarValue::Boolean(true),
+                "false" | "0" => ScalarValue::Boolean(false),
+                _ => ScalarValue::typed(value, datatype),
+            },
+            XSD_INTEGER => match value.parse::<i64>() {
+                Ok(number) if number.to_string() == value => ScalarValue::Integer(number),
+                _ => ScalarValue::typed(value, datatype),
+            },
+            XSD_DOUBLE => match value.parse::<f64>() {
+                Ok(number) => ScalarValue::Double(number),
+                Err(_) => ScalarValue::typed(value, datatype),
+            },
+            XSD_DATE => match NaiveDate::parse_from_str(&value, DATE_FORMAT) {
+                Ok(date) if date.format(DATE_FORMAT).to_string() == value => {
+                    ScalarValue::Date(date)
+                }
+                _ => ScalarValue::typed(value, datatype),
+            },
+            XSD_DATE_TIME => match NaiveDateTime::parse_from_str(&value, DATE_TIME_FORMAT) {
+                Ok(datetime) if datetime.format(DATE_TIME_FORMAT).to_string() == value => {
+                    ScalarValue::DateTime(datetime)
+                }
+                _ => ScalarValue::typed(value, datatype),
+            },
+            _ => ScalarValue::typed(value, datatype),
+        }
+    }
+
+    /// Builds a scalar from a JSON number: [`ScalarValue::Integer`] for
+    /// numbers written without a fraction or exponent that fit in an `i64`,
+    /// [`ScalarValue::Double`] otherwise.
+    pub fn from_json_number(number: &serde_json::Number) -> Option<Self> {
+        match number.as_i64() {
+            Some(integer) => Some(ScalarValue::Integer(integer)),
+            None => number.as_f64().map(ScalarValue::Double),
+        }
+    }
+
+    /// Returns the lexical form and datatype IRI of typed literals, dates,
+    /// and date-times.
+    pub fn typed_lexical(&self) -> Option<(Cow<'_, str>, &str)> {
+        match self {
+            ScalarValue::TypedLiteral { value, datatype } => {
+                Some((Cow::Borrowed(value), datatype.as_str()))
+            }
+            ScalarValue::Date(date) => {
+                Some((Cow::Owned(date.format(DATE_FORMAT).to_string()), XSD_DATE))
+            }
+            ScalarValue::DateTime(datetime) => Some((
+                Cow::Owned(datetime.format(DATE_TIME_FORMAT).to_string()),
+                XSD_DATE_TIME,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the datatype IRI of the literal, with plain strings, numbers,
+    /// and booleans counting as the XML Schema datatype they stand for.
+    /// Null has none.
+    pub fn datatype(&self) -> Option<&str> {
+        match self {
+            ScalarValue::String(_) => Some(XSD_STRING),
+            ScalarValue::Integer(_) => Some(XSD_INTEGER),
+            ScalarValue::Double(_) => Some(XSD_DOUBLE),
+            ScalarValue::Boolean(_) => Some(XSD_BOOLEAN),
+            ScalarValue::Null => None,
+            ScalarValue::Date(_) => Some(XSD_DATE),
+            ScalarValue::DateTime(_) => Some(XSD_DATE_TIME),
+            ScalarValue::TypedLiteral { datatype, .. } => Some(datatype),
+            ScalarValue::LangString { .. } => Some(RDF_LANG_STRING),
+        }
+    }
+
+    /// Creates a language-tagged string literal (`rdf:langString`).
+    pub fn lang_tagged(value: impl Into<String>, language: impl Into<String>) -> Self {
+        ScalarValue::LangString {
+            value: value.into(),
+            language: language.into(),
+        }
+    }
+
     /// Converts the scalar into the JSON representation used in JSON-LD
-    /// payloads.
+    /// payloads. Typed and language-tagged literals are emitted as JSON-LD
+    /// value objects.
     pub fn to_json(&self) -> serde_json::Value {
         match self {
             ScalarValue::String(value) => serde_json::Value::String(value.clone()),
-            ScalarValue::Number(value) => serde_json::Number::from_f64(*value)
+            ScalarValue::Integer(value) => serde_json::Value::Number((*value).into()),
+            // serde_json writes integral doubles with a fraction (`30.0`), so
+            // they read back as doubles.
+            ScalarValue::Double(value) => serde_json::Number::from_f64(*value)
                 .map(serde_json::Value::Number)
                 .unwrap_or(serde_json::Value::Null),
             ScalarValue::Boolean(value) => serde_json::Value::Bool(*value),
             ScalarValue::Null => serde_json::Value::Null,
+            ScalarValue::TypedLiteral { .. } | ScalarValue::Date(_) | ScalarValue::DateTime(_) => {
+                match self.typed_lexical() {
+                    Some((value, datatype)) => value_object(&value, "@type", datatype),
+                    None => serde_json::Value::Null,
+                }
+            }
+            ScalarValue::LangString { value, language } => {
+                value_object(value, "@language", language)
+            }
         }
     }
 }
 
+fn value_object(value: &str, qualifier: &str, qualifier_value: &str) -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+    map.insert(
+        "@value".to_string(),
+        serde_json::Value::String(value.to_string()),
+    );
+    map.insert(
+        qualifier.to_string(),
+        serde_json::Value::String(qualifier_value.to_string()),
+    );
+    serde_json::Value::Object(map)
+}
+
 /// Represents multi-valued predicates.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "kind", content = "items")]
@@ -44,6 +223,65 @@ pub enum ArrayValue {
     Scalars(Vec<ScalarValue>),
     /// Array of object references (node identifiers).
     ObjectRefs(Vec<NodeId>),
+    /// Array mixing literals and object references, such as an `author`
+    /// given as a name for some entries and as a person for others.
+    Mixed(Vec<ArrayItem>),
+}
+
+/// Single entry of a multi-valued predicate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "variant", content = "value")]
+pub enum ArrayItem {
+    /// Scalar literal entry.
+    Scalar(ScalarValue),
+    /// Object reference entry.
+    ObjectRef(NodeId),
+}
+
+impl ArrayValue {
+    /// Builds an array from `items`, using [`ArrayValue::Scalars`] or
+    /// [`ArrayValue::ObjectRefs`] when the entries are all of one kind.
+    pub fn from_items(items: Vec<ArrayItem>) -> Self {
+        if items
+            .iter()
+            .all(|item| matches!(item, ArrayItem::Scalar(_)))
+        {
+            return ArrayValue::Scalars(
+                items
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        ArrayItem::Scalar(scalar) => Some(scalar),
+                        ArrayItem::ObjectRef(_) => None,
+                    })
+                    .collect(),
+            );
+        }
+        if items
+            .iter()
+            .all(|item| matches!(item, ArrayItem::ObjectRef(_)))
+        {
+            return ArrayValue::ObjectRefs(
+                items
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        ArrayItem::ObjectRef(target) => Some(target),
+                        ArrayItem::Scalar(_) => None,
+                    })
+                    .collect(),
+            );
+        }
+        ArrayValue::Mixed(items)
+    }
+}
+
+impl ArrayItem {
+    /// Converts the entry into its expanded JSON-LD form.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            ArrayItem::Scalar(scalar) => scalar.to_json(),
+            ArrayItem::ObjectRef(target) => serde_json::json!({ "@id": target }),
+        }
+    }
 }
 
 /// Represents property values associated with a node.
@@ -54,21 +292,62 @@ pub enum PropertyValue {
     Scalar(ScalarValue),
     /// Object reference pointing to another node.
     ObjectRef(NodeId),
-    /// Array value consisting either of literals or object references.
+    /// Array value consisting of literals, object references, or both.
     Array(ArrayValue),
 }
 
+impl PropertyValue {
+    /// Converts the value into its expanded JSON-LD form, with references
+    /// written as `{"@id": ...}` objects.
+    pub fn to_json(&self) -> serde_json::Value {
+        let reference = |target: &NodeId| serde_json::json!({ "@id": target });
+        match self {
+            PropertyValue::Scalar(scalar) => scalar.to_json(),
+            PropertyValue::ObjectRef(target) => reference(target),
+            PropertyValue::Array(ArrayValue::Scalars(items)) => {
+                serde_json::Value::Array(items.iter().map(ScalarValue::to_json).collect())
+            }
+            PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => {
+                serde_json::Value::Array(targets.iter().map(reference).collect())
+            }
+            PropertyValue::Array(ArrayValue::Mixed(items)) => {
+                serde_json::Value::Array(items.iter().map(ArrayItem::to_json).collect())
+            }
+        }
+    }
+
+    /// Returns the entries of the value in order; single values yield one
+    /// entry.
+    pub fn items(&self) -> Vec<ArrayItem> {
+        match self {
+            PropertyValue::Scalar(scalar) => vec![ArrayItem::Scalar(scalar.clone())],
+            PropertyValue::ObjectRef(target) => vec![ArrayItem::ObjectRef(target.clone())],
+            PropertyValue::Array(ArrayValue::Scalars(items)) => {
+                items.iter().cloned().map(ArrayItem::Scalar).collect()
+            }
+            PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => {
+                targets.iter().cloned().map(ArrayItem::ObjectRef).collect()
+            }
+            PropertyValue::Array(ArrayValue::Mixed(items)) => items.clone(),
+        }
+    }
+}
+
 /// Represents an entity in the graph.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Node {
     /// Node identifier.
     pub id: NodeId,
     /// Optional name of the graph the node belongs to.
-    pub graph: Option<String>,
+    pub graph: Option<Iri>,
     /// Node types. Multi-typed nodes contain multiple entries.
-    pub types: BTreeSet<String>,
+    pub types: BTreeSet<Iri>,
     /// Predicate → value mapping.
-    pub properties: BTreeMap<String, PropertyValue>,
+    pub properties: BTreeMap<Iri, PropertyValue>,
+    /// Predicates whose values are ordered lists, read from RDF collections
+    /// or JSON-LD `@list` objects, rather than unordered sets.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub lists: BTreeSet<Iri>,
 }
 
 impl Node {
@@ -79,26 +358,28 @@ impl Node {
             graph: None,
             types: BTreeSet::new(),
             properties: BTreeMap::new(),
+            lists: BTreeSet::new(),
         }
     }
 
     /// Creates a new node with the provided identifier assigned to the given graph.
-    pub fn with_graph(id: impl Into<NodeId>, graph: Option<String>) -> Self {
+    pub fn with_graph(id: impl Into<NodeId>, graph: Option<Iri>) -> Self {
         Self {
             id: id.into(),
             graph,
             types: BTreeSet::new(),
             properties: BTreeMap::new(),
+            lists: BTreeSet::new(),
         }
     }
 
     /// Sets the graph the node belongs to.
-    pub fn set_graph(&mut self, graph: Option<String>) {
+    pub fn set_graph(&mut self, graph: Option<Iri>) {
         self.graph = graph;
     }
 
     /// Inserts or replaces a property value.
-    pub fn insert_property(&mut self, predicate: String, value: PropertyValue) {
+    pub fn insert_property(&mut self, predicate: Iri, value: PropertyValue) {
         self.properties.insert(predicate, value);
     }
 }
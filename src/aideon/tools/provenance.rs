@@ -0,0 +1,101 @@
+//! Cell-level provenance of datasets read from workbooks and CSV
+//! directories.
+//!
+//! Readers asked for provenance, such as
+//! [`excel_read::read_nodes_with_provenance`](crate::aideon::tools::io::excel_read::read_nodes_with_provenance),
+//! return a [`CellProvenance`] for every cell that gave a node a value: the
+//! literal and reference cells of type sheets, the targets and relationship
+//! columns of child sheets, and the cells of matrix sheets. Records are
+//! written as a JSON list, or as [PROV-O](https://www.w3.org/TR/prov-o/)
+//! statements by [`provenance_nodes`]: one `rdf:Statement` per record,
+//! naming the node and predicate, `prov:wasDerivedFrom` the cell, a
+//! `prov:Entity` with the `prov:hadPrimarySource` of the file read.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::io::jsonld::percent_encode;
+use crate::aideon::tools::io::rdf::{RDF_PREDICATE, RDF_STATEMENT, RDF_SUBJECT};
+use crate::aideon::tools::model::{Iri, Node, NodeId, PropertyValue};
+
+/// Namespace of the PROV ontology.
+pub const PROV_NAMESPACE: &str = "http://www.w3.org/ns/prov#";
+
+/// The cell one value of a node was read from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CellProvenance {
+    /// Node given the value, as its id was read.
+    pub node: NodeId,
+    /// Graph of the node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph: Option<Iri>,
+    /// Property the cell gave a value.
+    pub predicate: Iri,
+    /// Sheet holding the cell; CSV tables go by the sheet names of their
+    /// files.
+    pub sheet: String,
+    /// Row of the cell, counting the header as row 1.
+    pub row: usize,
+    /// Header of the cell's column.
+    pub column: String,
+}
+
+impl CellProvenance {
+    /// IRI naming the cell within the file at `source`:
+    /// `<source>#sheet=…&row=…&column=…`.
+    pub fn cell_iri(&self, source: &str) -> String {
+        format!(
+            "{source}#sheet={}&row={}&column={}",
+            percent_encode(&self.sheet),
+            self.row,
+            percent_encode(&self.column)
+        )
+    }
+}
+
+/// Describes `records` as PROV-O statements about the file at `source`,
+/// each in the graph of its node.
+pub fn provenance_nodes(records: &[CellProvenance], source: &str) -> Vec<Node> {
+    let iri = |name: &str| Iri::from(format!("{PROV_NAMESPACE}{name}"));
+    let reference = |id: &str| PropertyValue::ObjectRef(NodeId::from(id));
+    let mut cells: BTreeMap<(Option<Iri>, String), Node> = BTreeMap::new();
+    let mut statements = Vec::with_capacity(records.len());
+    for (index, record) in records.iter().enumerate() {
+        let cell = record.cell_iri(source);
+        cells
+            .entry((record.graph.clone(), cell.clone()))
+            .or_insert_with(|| {
+                let mut node = Node::with_graph(cell.as_str(), record.graph.clone());
+                node.types.insert(iri("Entity"));
+                node.insert_property(iri("hadPrimarySource"), reference(source));
+                node
+            });
+        let mut statement = Node::with_graph(format!("_:prov{index}"), record.graph.clone());
+        statement.types.insert(Iri::from(RDF_STATEMENT));
+        statement.insert_property(Iri::from(RDF_SUBJECT), reference(&record.node));
+        statement.insert_property(Iri::from(RDF_PREDICATE), reference(&record.predicate));
+        statement.insert_property(iri("wasDerivedFrom"), reference(&cell));
+        statements.push(statement);
+    }
+    statements.extend(cells.into_values());
+    statements
+}
+
+/// The `file:` IRI of the file or directory at `path`.
+pub fn source_iri(path: &Path) -> Result<String> {
+    let path = path.canonicalize()?;
+    let segments: Vec<String> = path
+        .to_string_lossy()
+        .split(std::path::MAIN_SEPARATOR)
+        .map(percent_encode)
+        .collect();
+    let path = segments.join("/");
+    Ok(if path.starts_with('/') {
+        format!("file://{path}")
+    } else {
+        format!("file:///{path}")
+    })
+}
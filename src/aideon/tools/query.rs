@@ -0,0 +1,368 @@
+//! SPARQL queries over a node set.
+//!
+//! The nodes are loaded into an in-memory store, so any supported input can
+//! be queried the same way. Nodes in named graphs are only visible through
+//! `GRAPH` patterns; the default graph holds the nodes without one.
+//!
+//! [`partition_nodes`] splits a node set with a `SELECT` query instead: the
+//! `?node` binding of each solution joins the partition named by the
+//! solution's other bindings, such as one per department, and
+//! [`apply_conditions`] keeps or drops the nodes a [`NodeCondition`] binds.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+pub use oxigraph::io::RdfFormat;
+use oxigraph::io::RdfSerializer;
+use oxigraph::model::Term;
+pub use oxigraph::sparql::results::QueryResultsFormat;
+use oxigraph::sparql::results::QueryResultsSerializer;
+use oxigraph::sparql::{QueryResults, SparqlEvaluator};
+use oxigraph::store::Store;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::io::rdf::node_quads;
+use crate::aideon::tools::model::{ArrayItem, Node};
+
+/// Variable of a partitioning query that binds the members of a partition.
+pub const PARTITION_VARIABLE: &str = "node";
+
+/// The form of an executed query, which decides the shape of its results.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QueryForm {
+    /// A `SELECT` query, written as a solutions table.
+    Select,
+    /// An `ASK` query, written as a single boolean.
+    Ask,
+    /// A `CONSTRUCT` or `DESCRIBE` query, written as an RDF graph.
+    Construct,
+}
+
+/// Output formats for query results.
+#[derive(Copy, Clone, Debug)]
+pub struct QueryOutputFormat {
+    /// Format of `SELECT` and `ASK` results.
+    pub results: QueryResultsFormat,
+    /// Format of `CONSTRUCT` and `DESCRIBE` results.
+    pub graph: RdfFormat,
+}
+
+impl Default for QueryOutputFormat {
+    fn default() -> Self {
+        Self {
+            results: QueryResultsFormat::Csv,
+            graph: RdfFormat::Turtle,
+        }
+    }
+}
+
+/// Loads `nodes` into an in-memory store.
+pub fn load_store(nodes: &[Node]) -> Result<Store> {
+    let store = Store::new().map_err(sparql_error)?;
+    for node in nodes {
+        store.extend(node_quads(node)?).map_err(sparql_error)?;
+    }
+    Ok(store)
+}
+
+/// Runs `query` against `nodes` and writes its results to `writer`, returning
+/// the form of the query. Solutions keep the variable order of the query.
+pub fn run_query<W: Write>(
+    nodes: &[Node],
+    query: &str,
+    writer: W,
+    format: QueryOutputFormat,
+) -> Result<QueryForm> {
+    let store = load_store(nodes)?;
+    let results = SparqlEvaluator::new()
+        .parse_query(query)
+        .map_err(sparql_error)?
+        .on_store(&store)
+        .execute()
+        .map_err(sparql_error)?;
+
+    match results {
+        QueryResults::Solutions(solutions) => {
+            let mut serializer = QueryResultsSerializer::from_format(format.results)
+                .serialize_solutions_to_writer(writer, solutions.variables().to_vec())?;
+            for solution in solutions {
+                let solution = solution.map_err(sparql_error)?;
+                serializer.serialize(solution.iter())?;
+            }
+            serializer.finish()?;
+            Ok(QueryForm::Select)
+        }
+        QueryResults::Boolean(value) => {
+            QueryResultsSerializer::from_format(format.results)
+                .serialize_boolean_to_writer(writer, value)?;
+            Ok(QueryForm::Ask)
+        }
+        QueryResults::Graph(triples) => {
+            let mut serializer = RdfSerializer::from_format(format.graph).for_writer(writer);
+            for triple in triples {
+                let triple = triple.map_err(sparql_error)?;
+                serializer
+                    .serialize_triple(&triple)
+                    .map_err(|err| ToolError::Rdf(err.to_string()))?;
+            }
+            serializer
+                .finish()
+                .map_err(|err| ToolError::Rdf(err.to_string()))?;
+            Ok(QueryForm::Construct)
+        }
+    }
+}
+
+/// The nodes of one partition of a node set, see [`partition_nodes`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partition {
+    /// Values of the query's variables other than `?node`, in the order of
+    /// the query: IRIs, the lexical forms of literals, `None` when unbound.
+    pub key: Vec<Option<String>>,
+    /// Name of the partition, unique among the partitions of a query and
+    /// usable as a file name: the key's values joined by `_`, IRIs by their
+    /// last segment, unbound values as `none`, and any character but ASCII
+    /// letters, digits, and `-` replaced with `_`.
+    pub name: String,
+    /// Members of the partition, with the blank nodes they reference, in
+    /// the order of the input.
+    pub nodes: Vec<Node>,
+}
+
+/// Splits `nodes` into the partitions of the `SELECT` query `query`: every
+/// solution adds the node bound to `?node` to the partition of its other
+/// bindings. A node may belong to several partitions, and solutions without
+/// a `?node` or binding it to a literal are ignored. Partitions are ordered
+/// by key; two keys with the same name fail the split.
+pub fn partition_nodes(nodes: &[Node], query: &str) -> Result<Vec<Partition>> {
+    let store = load_store(nodes)?;
+    let results = SparqlEvaluator::new()
+        .parse_query(query)
+        .map_err(sparql_error)?
+        .on_store(&store)
+        .execute()
+        .map_err(sparql_error)?;
+    let not_partitioning = || {
+        ToolError::Sparql(format!(
+            "partitioning queries must be SELECT queries binding ?{PARTITION_VARIABLE}"
+        ))
+    };
+    let QueryResults::Solutions(solutions) = results else {
+        return Err(not_partitioning());
+    };
+    let keys: Vec<_> = solutions
+        .variables()
+        .iter()
+        .filter(|variable| variable.as_str() != PARTITION_VARIABLE)
+        .cloned()
+        .collect();
+    if keys.len() == solutions.variables().len() {
+        return Err(not_partitioning());
+    }
+
+    let mut members: BTreeMap<Vec<Option<String>>, (String, BTreeSet<String>)> = BTreeMap::new();
+    for solution in solutions {
+        let solution = solution.map_err(sparql_error)?;
+        let id = match solution.get(PARTITION_VARIABLE) {
+            Some(Term::NamedNode(node)) => node.as_str().to_string(),
+            Some(Term::BlankNode(node)) => format!("_:{}", node.as_str()),
+            _ => continue,
+        };
+        let values: Vec<Option<&Term>> = keys.iter().map(|key| solution.get(key)).collect();
+        let key = values.iter().map(|value| value.map(term_value)).collect();
+        members
+            .entry(key)
+            .or_insert_with(|| (partition_name(&values), BTreeSet::new()))
+            .1
+            .insert(id);
+    }
+
+    let mut named: BTreeMap<&str, &[Option<String>]> = BTreeMap::new();
+    for (key, (name, _)) in &members {
+        if let Some(other) = named.insert(name, key) {
+            return Err(ToolError::InvalidConfig(format!(
+                "partitions {other:?} and {key:?} are both named `{name}`"
+            )));
+        }
+    }
+    Ok(members
+        .into_iter()
+        .map(|(key, (name, ids))| Partition {
+            key,
+            name,
+            nodes: with_blank_nodes(nodes, ids),
+        })
+        .collect())
+}
+
+/// A SPARQL group graph pattern selecting the nodes it binds to `?node`,
+/// such as `?node schema:age ?age FILTER(?age < 18)`, for
+/// [`apply_conditions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeCondition {
+    query: String,
+    keep: bool,
+}
+
+impl NodeCondition {
+    /// Keeps only the nodes `pattern` binds, declaring `prefixes` for it.
+    pub fn keeping(pattern: &str, prefixes: &BTreeMap<String, String>) -> Self {
+        Self::new(pattern, prefixes, true)
+    }
+
+    /// Drops the nodes `pattern` binds, declaring `prefixes` for it.
+    pub fn dropping(pattern: &str, prefixes: &BTreeMap<String, String>) -> Self {
+        Self::new(pattern, prefixes, false)
+    }
+
+    fn new(pattern: &str, prefixes: &BTreeMap<String, String>, keep: bool) -> Self {
+        let mut query = String::new();
+        for (prefix, namespace) in prefixes {
+            query.push_str(&format!("PREFIX {prefix}: <{namespace}>\n"));
+        }
+        query.push_str(&format!(
+            "SELECT DISTINCT ?{PARTITION_VARIABLE} WHERE {{\n{pattern}\n}}"
+        ));
+        NodeCondition { query, keep }
+    }
+
+    /// Whether the nodes the pattern binds are kept rather than dropped.
+    pub fn keeps(&self) -> bool {
+        self.keep
+    }
+
+    /// The `SELECT` query the pattern is evaluated with.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+}
+
+/// Returns the nodes of `nodes` every keeping condition of `conditions`
+/// binds and no dropping one does, in order, with the blank nodes they
+/// reference. All conditions are evaluated against the whole of `nodes`,
+/// and a binding selects the nodes with its id in every graph.
+pub fn apply_conditions(nodes: Vec<Node>, conditions: &[NodeCondition]) -> Result<Vec<Node>> {
+    if conditions.is_empty() {
+        return Ok(nodes);
+    }
+    let store = load_store(&nodes)?;
+    let mut kept: Option<BTreeSet<String>> = None;
+    let mut dropped = BTreeSet::new();
+    for condition in conditions {
+        let bound = bound_nodes(&store, &condition.query)?;
+        if condition.keep {
+            kept = Some(match kept {
+                Some(kept) => kept.intersection(&bound).cloned().collect(),
+                None => bound,
+            });
+        } else {
+            dropped.extend(bound);
+        }
+    }
+    let ids: BTreeSet<String> = match kept {
+        Some(kept) => with_blank_nodes(&nodes, kept)
+            .into_iter()
+            .map(|node| node.id.to_string())
+            .collect(),
+        None => nodes.iter().map(|node| node.id.to_string()).collect(),
+    };
+    Ok(nodes
+        .into_iter()
+        .filter(|node| ids.contains(node.id.as_str()) && !dropped.contains(node.id.as_str()))
+        .collect())
+}
+
+/// Returns the ids `query` binds to `?node` in `store`.
+fn bound_nodes(store: &Store, query: &str) -> Result<BTreeSet<String>> {
+    let results = SparqlEvaluator::new()
+        .parse_query(query)
+        .map_err(sparql_error)?
+        .on_store(store)
+        .execute()
+        .map_err(sparql_error)?;
+    let QueryResults::Solutions(solutions) = results else {
+        return Err(ToolError::Sparql(format!(
+            "node conditions must bind ?{PARTITION_VARIABLE}"
+        )));
+    };
+    let mut ids = BTreeSet::new();
+    for solution in solutions {
+        let solution = solution.map_err(sparql_error)?;
+        if let Some(term @ (Term::NamedNode(_) | Term::BlankNode(_))) =
+            solution.get(PARTITION_VARIABLE)
+        {
+            ids.insert(term_value(term));
+        }
+    }
+    Ok(ids)
+}
+
+/// Returns the nodes of `nodes` identified by `ids` and the blank nodes
+/// they reference, directly or through other blank nodes.
+fn with_blank_nodes(nodes: &[Node], mut ids: BTreeSet<String>) -> Vec<Node> {
+    let mut pending: Vec<String> = ids.iter().cloned().collect();
+    while let Some(id) = pending.pop() {
+        for node in nodes.iter().filter(|node| node.id.as_str() == id) {
+            for value in node.properties.values() {
+                for item in value.items() {
+                    if let ArrayItem::ObjectRef(target) = item
+                        && target.as_str().starts_with("_:")
+                        && ids.insert(target.to_string())
+                    {
+                        pending.push(target.to_string());
+                    }
+                }
+            }
+        }
+    }
+    nodes
+        .iter()
+        .filter(|node| ids.contains(node.id.as_str()))
+        .cloned()
+        .collect()
+}
+
+fn term_value(term: &Term) -> String {
+    match term {
+        Term::NamedNode(node) => node.as_str().to_string(),
+        Term::BlankNode(node) => format!("_:{}", node.as_str()),
+        Term::Literal(literal) => literal.value().to_string(),
+    }
+}
+
+/// Builds the [`Partition::name`] of the key `values`.
+fn partition_name(values: &[Option<&Term>]) -> String {
+    let parts: Vec<String> = values
+        .iter()
+        .map(|value| {
+            let text = match value {
+                None => "none".to_string(),
+                Some(Term::NamedNode(node)) => {
+                    let iri = node.as_str().trim_end_matches(['/', '#']);
+                    iri.rsplit(['/', '#', ':'])
+                        .next()
+                        .unwrap_or(iri)
+                        .to_string()
+                }
+                Some(term) => term_value(term),
+            };
+            text.chars()
+                .map(|ch| {
+                    if ch.is_ascii_alphanumeric() || ch == '-' {
+                        ch
+                    } else {
+                        '_'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    match parts.join("_") {
+        name if name.is_empty() => "none".to_string(),
+        name => name,
+    }
+}
+
+fn sparql_error(err: impl std::fmt::Display) -> ToolError {
+    ToolError::Sparql(err.to_string())
+}
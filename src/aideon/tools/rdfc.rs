@@ -0,0 +1,368 @@
+//! RDF Dataset Canonicalization ([RDFC-1.0]).
+//!
+//! [`canonical_nquads`] serialises a dataset as the W3C algorithm does: its
+//! blank nodes are labelled `_:c14n0`, `_:c14n1`, and so on after the hashes
+//! of the statements around them, using SHA-256, and the statements are
+//! written as canonical N-Quads in code point order without duplicates. Any
+//! RDFC-1.0 implementation gives the same text for the same dataset,
+//! whatever the order of its statements and the labels of its blank nodes.
+//!
+//! Blank nodes the first-degree hashes cannot tell apart are settled by
+//! trying the orders of their neighbours, which grows factorially on very
+//! symmetric data; past [`WORK_BUDGET`] tries, canonicalization fails with
+//! [`ToolError::SymmetricBlankNodes`] rather than running on.
+//!
+//! [RDFC-1.0]: https://www.w3.org/TR/rdf-canon/
+
+use std::collections::{BTreeMap, HashMap};
+
+use oxigraph::model::{BlankNode, GraphName, NamedOrBlankNode, Quad, Term};
+use sha2::{Digest, Sha256};
+
+use crate::aideon::tools::error::{Result, ToolError};
+
+/// Upper bound on the N-degree hashes and neighbour orders tried for one
+/// dataset.
+pub const WORK_BUDGET: usize = 65_536;
+
+/// Prefix of the canonical blank node labels.
+const CANONICAL_PREFIX: &str = "c14n";
+
+/// Prefix of the temporary labels issued while hashing.
+const TEMPORARY_PREFIX: &str = "b";
+
+/// Returns the canonical N-Quads of `quads`, one statement per line.
+pub fn canonical_nquads(quads: &[Quad]) -> Result<String> {
+    let mut state = State::new(quads);
+    let mut first_degree: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let blank_nodes: Vec<String> = state.quads_of.keys().cloned().collect();
+    for blank_node in blank_nodes {
+        let hash = state.hash_first_degree(&blank_node);
+        first_degree.entry(hash).or_default().push(blank_node);
+    }
+
+    // Blank nodes with a hash of their own are labelled in hash order.
+    let mut shared = Vec::new();
+    for (_, blank_nodes) in first_degree {
+        match <[String; 1]>::try_from(blank_nodes) {
+            Ok([blank_node]) => {
+                state.canonical.issue(&blank_node);
+            }
+            Err(blank_nodes) => shared.push(blank_nodes),
+        }
+    }
+
+    // The others are told apart by the paths through their neighbours.
+    for blank_nodes in shared {
+        let mut paths = Vec::new();
+        for blank_node in &blank_nodes {
+            if state.canonical.get(blank_node).is_some() {
+                continue;
+            }
+            let mut issuer = Issuer::new(TEMPORARY_PREFIX);
+            issuer.issue(blank_node);
+            paths.push(state.hash_n_degree(blank_node, issuer)?);
+        }
+        paths.sort_by(|left, right| left.0.cmp(&right.0));
+        for (_, issuer) in paths {
+            for blank_node in issuer.order {
+                state.canonical.issue(&blank_node);
+            }
+        }
+    }
+
+    let mut lines: Vec<String> = quads
+        .iter()
+        .map(|quad| {
+            let quad = relabel(quad, |blank_node| {
+                state.canonical.get(blank_node).unwrap_or(blank_node).into()
+            });
+            format!("{quad} .\n")
+        })
+        .collect();
+    lines.sort_unstable();
+    lines.dedup();
+    Ok(lines.concat())
+}
+
+/// Issues labels with a prefix and a counter, remembering the order in
+/// which the blank nodes got them.
+#[derive(Debug, Clone)]
+struct Issuer {
+    prefix: &'static str,
+    issued: HashMap<String, String>,
+    order: Vec<String>,
+}
+
+impl Issuer {
+    fn new(prefix: &'static str) -> Self {
+        Self {
+            prefix,
+            issued: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    fn get(&self, blank_node: &str) -> Option<&str> {
+        self.issued.get(blank_node).map(String::as_str)
+    }
+
+    /// Returns the label of `blank_node`, issuing the next one when it has
+    /// none yet.
+    fn issue(&mut self, blank_node: &str) -> String {
+        if let Some(label) = self.issued.get(blank_node) {
+            return label.clone();
+        }
+        let label = format!("{}{}", self.prefix, self.order.len());
+        self.issued.insert(blank_node.to_string(), label.clone());
+        self.order.push(blank_node.to_string());
+        label
+    }
+}
+
+/// Canonicalization state: the statements mentioning each blank node, the
+/// canonical labels issued so far, and the work left.
+struct State<'q> {
+    quads_of: BTreeMap<String, Vec<&'q Quad>>,
+    first_degree: HashMap<String, String>,
+    canonical: Issuer,
+    budget: usize,
+}
+
+impl<'q> State<'q> {
+    fn new(quads: &'q [Quad]) -> Self {
+        let mut quads_of: BTreeMap<String, Vec<&Quad>> = BTreeMap::new();
+        for quad in quads {
+            let mut mentioned: Vec<&str> = blank_nodes(quad).map(|(_, label)| label).collect();
+            mentioned.sort_unstable();
+            mentioned.dedup();
+            for label in mentioned {
+                quads_of.entry(label.to_string()).or_default().push(quad);
+            }
+        }
+        Self {
+            quads_of,
+            first_degree: HashMap::new(),
+            canonical: Issuer::new(CANONICAL_PREFIX),
+            budget: WORK_BUDGET,
+        }
+    }
+
+    /// Hashes the statements mentioning `blank_node`, with it labelled `a`
+    /// and every other blank node `z`.
+    fn hash_first_degree(&mut self, blank_node: &str) -> String {
+        if let Some(hash) = self.first_degree.get(blank_node) {
+            return hash.clone();
+        }
+        let mut lines: Vec<String> = self
+            .quads_of
+            .get(blank_node)
+            .into_iter()
+            .flatten()
+            .map(|quad| {
+                let quad = relabel(quad, |label| {
+                    if label == blank_node { "a" } else { "z" }.into()
+                });
+                format!("{quad} .\n")
+            })
+            .collect();
+        lines.sort_unstable();
+        let hash = sha256(&lines.concat());
+        self.first_degree
+            .insert(blank_node.to_string(), hash.clone());
+        hash
+    }
+
+    /// Hashes `related`, a blank node found at `position` of `quad`, as seen
+    /// from a blank node being hashed with `issuer`.
+    fn hash_related(
+        &mut self,
+        related: &str,
+        quad: &Quad,
+        issuer: &Issuer,
+        position: char,
+    ) -> String {
+        let mut input = String::from(position);
+        if position != 'g' {
+            input.push_str(&quad.predicate.to_string());
+        }
+        match self.canonical.get(related).or_else(|| issuer.get(related)) {
+            Some(label) => {
+                input.push_str("_:");
+                input.push_str(label);
+            }
+            None => input.push_str(&self.hash_first_degree(related)),
+        }
+        sha256(&input)
+    }
+
+    /// Hashes `blank_node` after the paths through the blank nodes around
+    /// it, returning the hash and `issuer` with the labels the chosen paths
+    /// issued.
+    fn hash_n_degree(&mut self, blank_node: &str, mut issuer: Issuer) -> Result<(String, Issuer)> {
+        self.spend()?;
+
+        let mut related_by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let quads = self.quads_of.get(blank_node).cloned().unwrap_or_default();
+        for quad in quads {
+            for (position, related) in blank_nodes(quad) {
+                if related == blank_node {
+                    continue;
+                }
+                let hash = self.hash_related(related, quad, &issuer, position);
+                related_by_hash
+                    .entry(hash)
+                    .or_default()
+                    .push(related.to_string());
+            }
+        }
+
+        let mut data = String::new();
+        for (hash, related) in related_by_hash {
+            data.push_str(&hash);
+            let mut chosen: Option<(String, Issuer)> = None;
+            let mut order: Vec<usize> = (0..related.len()).collect();
+            loop {
+                self.spend()?;
+                let permutation: Vec<&String> =
+                    order.iter().map(|index| &related[*index]).collect();
+                if let Some(path) = self.path(&permutation, &issuer, chosen.as_ref())?
+                    && chosen.as_ref().is_none_or(|(best, _)| path.0 < *best)
+                {
+                    chosen = Some(path);
+                }
+                if !next_permutation(&mut order) {
+                    break;
+                }
+            }
+            if let Some((path, chosen)) = chosen {
+                data.push_str(&path);
+                issuer = chosen;
+            }
+        }
+        Ok((sha256(&data), issuer))
+    }
+
+    /// Counts one step of work against [`WORK_BUDGET`], failing once it is
+    /// spent.
+    fn spend(&mut self) -> Result<()> {
+        if self.budget == 0 {
+            return Err(ToolError::SymmetricBlankNodes {
+                count: self.quads_of.len(),
+                budget: WORK_BUDGET,
+            });
+        }
+        self.budget -= 1;
+        Ok(())
+    }
+
+    /// Returns the path through the blank nodes of `permutation` and the
+    /// issuer that labelled them, or `None` as soon as it cannot beat the
+    /// `chosen` one.
+    fn path(
+        &mut self,
+        permutation: &[&String],
+        issuer: &Issuer,
+        chosen: Option<&(String, Issuer)>,
+    ) -> Result<Option<(String, Issuer)>> {
+        let worse = |path: &str| {
+            chosen.is_some_and(|(best, _)| path.len() >= best.len() && path > best.as_str())
+        };
+        let mut issuer = issuer.clone();
+        let mut path = String::new();
+        let mut recursion = Vec::new();
+        for related in permutation {
+            let label = match self.canonical.get(related) {
+                Some(label) => label.to_string(),
+                None => {
+                    if issuer.get(related).is_none() {
+                        recursion.push(*related);
+                    }
+                    issuer.issue(related)
+                }
+            };
+            path.push_str("_:");
+            path.push_str(&label);
+            if worse(&path) {
+                return Ok(None);
+            }
+        }
+        for related in recursion {
+            let (hash, issued) = self.hash_n_degree(related, issuer.clone())?;
+            path.push_str("_:");
+            path.push_str(&issuer.issue(related));
+            path.push('<');
+            path.push_str(&hash);
+            path.push('>');
+            issuer = issued;
+            if worse(&path) {
+                return Ok(None);
+            }
+        }
+        Ok(Some((path, issuer)))
+    }
+}
+
+/// Returns the blank nodes of `quad` with their position: `s` for the
+/// subject, `o` for the object, and `g` for the graph name.
+fn blank_nodes(quad: &Quad) -> impl Iterator<Item = (char, &str)> {
+    let subject = match &quad.subject {
+        NamedOrBlankNode::BlankNode(node) => Some(('s', node.as_str())),
+        _ => None,
+    };
+    let object = match &quad.object {
+        Term::BlankNode(node) => Some(('o', node.as_str())),
+        _ => None,
+    };
+    let graph = match &quad.graph_name {
+        GraphName::BlankNode(node) => Some(('g', node.as_str())),
+        _ => None,
+    };
+    [subject, object, graph].into_iter().flatten()
+}
+
+/// Returns `quad` with its blank nodes labelled by `label`.
+fn relabel(quad: &Quad, label: impl Fn(&str) -> String) -> Quad {
+    let blank = |node: &BlankNode| BlankNode::new_unchecked(label(node.as_str()));
+    Quad {
+        subject: match &quad.subject {
+            NamedOrBlankNode::BlankNode(node) => blank(node).into(),
+            other => other.clone(),
+        },
+        predicate: quad.predicate.clone(),
+        object: match &quad.object {
+            Term::BlankNode(node) => blank(node).into(),
+            other => other.clone(),
+        },
+        graph_name: match &quad.graph_name {
+            GraphName::BlankNode(node) => blank(node).into(),
+            other => other.clone(),
+        },
+    }
+}
+
+/// Moves `order` to the next permutation in lexicographic order, returning
+/// `false` once it was the last.
+fn next_permutation(order: &mut [usize]) -> bool {
+    let Some(pivot) = (1..order.len())
+        .rev()
+        .find(|&index| order[index - 1] < order[index])
+    else {
+        return false;
+    };
+    let successor = (pivot..order.len())
+        .rev()
+        .find(|&index| order[index] > order[pivot - 1])
+        .unwrap_or(pivot);
+    order.swap(pivot - 1, successor);
+    order[pivot..].reverse();
+    true
+}
+
+/// Returns the SHA-256 digest of `text` in lowercase hexadecimal.
+fn sha256(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
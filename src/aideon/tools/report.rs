@@ -0,0 +1,283 @@
+//! Human-readable summaries of a synchronisation run.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::aideon::tools::diff::{ChangeKind, NodeChange};
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::flatten::{SheetLayout, WorkbookOptions, build_workbook_with_options};
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::model::Node;
+use crate::aideon::tools::roundtrip::{self, RoundTripLoss};
+use crate::aideon::tools::validate::{self, ValidationReport};
+
+/// Label used for nodes without a type or graph in summaries.
+pub const NONE_LABEL: &str = "(none)";
+
+/// Aggregate counts describing a node set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DatasetSummary {
+    /// Total number of nodes.
+    pub node_count: usize,
+    /// Number of nodes per type IRI. Untyped nodes are not counted.
+    pub type_counts: BTreeMap<String, usize>,
+    /// Number of nodes per graph; `None` is the default graph.
+    pub graph_counts: BTreeMap<Option<String>, usize>,
+}
+
+impl DatasetSummary {
+    /// Computes the summary of `nodes`.
+    pub fn from_nodes(nodes: &[Node]) -> Self {
+        let mut summary = Self {
+            node_count: nodes.len(),
+            ..Self::default()
+        };
+        for node in nodes {
+            for type_name in &node.types {
+                *summary
+                    .type_counts
+                    .entry(type_name.to_string())
+                    .or_default() += 1;
+            }
+            let graph = node.graph.as_deref().map(str::to_string);
+            *summary.graph_counts.entry(graph).or_default() += 1;
+        }
+        summary
+    }
+}
+
+/// Report describing what a conversion would write, produced instead of the
+/// output by a dry run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunReport {
+    /// Summary of the node set that would be written.
+    pub summary: DatasetSummary,
+    /// Sheets of the workbook or CSV files that would be written, with their
+    /// data row counts. Empty for JSON-LD and RDF outputs.
+    pub sheets: Vec<SheetLayout>,
+    /// Nodes, types, and properties the workbook or CSV files would lose or
+    /// alter, see [`roundtrip::check_workbook`].
+    pub losses: Vec<RoundTripLoss>,
+    /// Structural validation of the node set.
+    pub validation: ValidationReport,
+}
+
+impl DryRunReport {
+    /// Describes writing `nodes` as JSON-LD or RDF, which keep the node set
+    /// whole.
+    pub fn for_graph(nodes: &[Node]) -> Self {
+        Self {
+            summary: DatasetSummary::from_nodes(nodes),
+            sheets: Vec::new(),
+            losses: Vec::new(),
+            validation: validate::validate_nodes(nodes),
+        }
+    }
+
+    /// Describes writing `nodes` as a workbook or CSV files laid out with
+    /// `options`, reading the tables back in memory to predict losses.
+    pub fn for_workbook(nodes: &[Node], options: &WorkbookOptions) -> Result<Self> {
+        let workbook = build_workbook_with_options(nodes, options)?;
+        let losses = roundtrip::check_workbook(nodes, &workbook)?.losses;
+        let sheets = workbook
+            .tables
+            .into_iter()
+            .map(|table| SheetLayout {
+                sheet_name: table.sheet_name,
+                columns: table.columns,
+                row_count: table.rows.len(),
+            })
+            .collect();
+        Ok(Self {
+            sheets,
+            losses,
+            ..Self::for_graph(nodes)
+        })
+    }
+}
+
+/// Report describing the outcome of a single sync invocation.
+#[derive(Debug, Clone)]
+pub struct SyncReport {
+    /// Source file or directory.
+    pub input: PathBuf,
+    /// Destination file or directory.
+    pub output: PathBuf,
+    /// Name of the source representation.
+    pub from: String,
+    /// Name of the target representation.
+    pub to: String,
+    /// Summary of the node set written to the output.
+    pub summary: DatasetSummary,
+    /// Node-level changes applied to the output.
+    pub changes: Vec<NodeChange>,
+    /// Structural validation of the output, when it was run.
+    pub validation: Option<ValidationReport>,
+}
+
+impl SyncReport {
+    /// Renders the report as a standalone HTML document suitable for email or
+    /// static hosting. All values are HTML-escaped.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let generated = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
+
+        html.push_str(concat!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n",
+            "<title>aideon-tools sync report</title>\n<style>\n",
+            "body{font-family:system-ui,sans-serif;margin:2rem;color:#222}\n",
+            "table{border-collapse:collapse;margin-bottom:1.5rem}\n",
+            "th,td{border:1px solid #ccc;padding:.3rem .6rem;text-align:left}\n",
+            "th{background:#f3f3f3}\n.created{color:#1a7f37}.updated{color:#9a6700}",
+            ".deleted,.error{color:#cf222e}.warning{color:#9a6700}\n</style>\n</head>\n<body>\n",
+            "<h1>Sync report</h1>\n"
+        ));
+
+        let _ = write!(
+            html,
+            "<p>Generated {generated}.</p>\n<table>\n\
+             <tr><th>Input</th><td><a href=\"{input}\">{input}</a> ({from})</td></tr>\n\
+             <tr><th>Output</th><td><a href=\"{output}\">{output}</a> ({to})</td></tr>\n\
+             <tr><th>Nodes</th><td>{nodes}</td></tr>\n</table>\n",
+            input = escape_html(&self.input.display().to_string()),
+            output = escape_html(&self.output.display().to_string()),
+            from = escape_html(&self.from),
+            to = escape_html(&self.to),
+            nodes = self.summary.node_count,
+        );
+
+        push_count_table(
+            &mut html,
+            "Nodes per type",
+            "Type",
+            self.summary
+                .type_counts
+                .iter()
+                .map(|(name, count)| (name.as_str(), *count)),
+        );
+        push_count_table(
+            &mut html,
+            "Nodes per graph",
+            "Graph",
+            self.summary
+                .graph_counts
+                .iter()
+                .map(|(graph, count)| (graph.as_deref().unwrap_or(NONE_LABEL), *count)),
+        );
+        self.push_changes(&mut html);
+        self.push_validation(&mut html);
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Writes the HTML rendering of the report to `path`.
+    pub fn write_html(&self, path: &Path) -> Result<()> {
+        atomic::write_atomically(path, |partial| Ok(fs::write(partial, self.to_html())?))
+    }
+
+    fn push_changes(&self, html: &mut String) {
+        let count = |kind: ChangeKind| {
+            self.changes
+                .iter()
+                .filter(|change| change.change == kind)
+                .count()
+        };
+        let _ = write!(
+            html,
+            "<h2>Changes</h2>\n<p>{} created, {} updated, {} deleted.</p>\n",
+            count(ChangeKind::Created),
+            count(ChangeKind::Updated),
+            count(ChangeKind::Deleted),
+        );
+
+        if self.changes.is_empty() {
+            return;
+        }
+
+        html.push_str("<table>\n<tr><th>Change</th><th>Node</th><th>Graph</th></tr>\n");
+        for change in &self.changes {
+            let kind = match change.change {
+                ChangeKind::Created => "created",
+                ChangeKind::Updated => "updated",
+                ChangeKind::Deleted => "deleted",
+            };
+            let _ = writeln!(
+                html,
+                "<tr><td class=\"{kind}\">{kind}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&change.id),
+                escape_html(change.graph.as_deref().unwrap_or(NONE_LABEL)),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn push_validation(&self, html: &mut String) {
+        let Some(validation) = &self.validation else {
+            return;
+        };
+        let _ = write!(
+            html,
+            "<h2>Validation</h2>\n<p>{} error(s), {} warning(s).</p>\n",
+            validation.error_count(),
+            validation.warning_count(),
+        );
+
+        if validation.issues.is_empty() {
+            return;
+        }
+
+        html.push_str(
+            "<table>\n<tr><th>Severity</th><th>Node</th><th>Predicate</th><th>Issue</th></tr>\n",
+        );
+        for issue in &validation.issues {
+            let severity = issue.severity.as_str();
+            let _ = writeln!(
+                html,
+                "<tr><td class=\"{severity}\">{severity}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&issue.node),
+                escape_html(issue.predicate.as_deref().unwrap_or("")),
+                escape_html(&issue.message),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+}
+
+fn push_count_table<'a>(
+    html: &mut String,
+    title: &str,
+    label: &str,
+    rows: impl Iterator<Item = (&'a str, usize)>,
+) {
+    let _ = write!(
+        html,
+        "<h2>{title}</h2>\n<table>\n<tr><th>{label}</th><th>Nodes</th></tr>\n"
+    );
+    for (name, count) in rows {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{count}</td></tr>",
+            escape_html(name)
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+/// Escapes the characters that are significant in HTML text and attributes.
+pub(crate) fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
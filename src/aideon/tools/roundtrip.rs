@@ -0,0 +1,191 @@
+//! Lossiness check for the spreadsheet representation.
+//!
+//! A node set is flattened into workbook tables and read back in memory, and
+//! every node, type, and property that does not survive unchanged is
+//! reported.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::aideon::tools::equivalence;
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::flatten::{CellEncoding, WorkbookData, build_workbook_with};
+use crate::aideon::tools::io::tabular;
+use crate::aideon::tools::model::{Iri, Node, NodeId, PropertyValue};
+
+/// Category of a round-trip difference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LossKind {
+    /// The node is missing after the round trip.
+    NodeLost,
+    /// A node appears that was not in the input.
+    NodeAdded,
+    /// A type assignment is missing after the round trip.
+    TypeLost,
+    /// A type assignment appears that was not in the input.
+    TypeAdded,
+    /// A predicate is missing after the round trip.
+    PropertyLost,
+    /// A predicate appears that was not in the input.
+    PropertyAdded,
+    /// A predicate survives with a different value.
+    PropertyAltered,
+}
+
+impl LossKind {
+    /// Kebab-case label used in reports.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LossKind::NodeLost => "node-lost",
+            LossKind::NodeAdded => "node-added",
+            LossKind::TypeLost => "type-lost",
+            LossKind::TypeAdded => "type-added",
+            LossKind::PropertyLost => "property-lost",
+            LossKind::PropertyAdded => "property-added",
+            LossKind::PropertyAltered => "property-altered",
+        }
+    }
+}
+
+/// A node, type, or property that does not survive the round trip.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoundTripLoss {
+    /// Category of the difference.
+    pub kind: LossKind,
+    /// Affected node.
+    pub node: NodeId,
+    /// Graph of the affected node.
+    pub graph: Option<Iri>,
+    /// Affected predicate, or the type IRI for type differences.
+    pub predicate: Option<Iri>,
+    /// Value in the input, if any.
+    pub before: Option<PropertyValue>,
+    /// Value after the round trip, if any.
+    pub after: Option<PropertyValue>,
+}
+
+/// Outcome of a round-trip check.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RoundTripReport {
+    /// Differences ordered by graph, node, and predicate.
+    pub losses: Vec<RoundTripLoss>,
+}
+
+impl RoundTripReport {
+    /// Returns `true` when the node set survives unchanged.
+    pub fn is_lossless(&self) -> bool {
+        self.losses.is_empty()
+    }
+}
+
+/// Converts `nodes` into workbook tables with `encoding` and back, and
+/// reports every difference between the input and the restored node set.
+/// Blank node labels and the order of array items are not significant.
+pub fn check_roundtrip(nodes: &[Node], encoding: CellEncoding) -> Result<RoundTripReport> {
+    check_workbook(nodes, &build_workbook_with(nodes, encoding)?)
+}
+
+/// Reads `workbook`, the tables built from `nodes`, back in memory and
+/// reports every difference like [`check_roundtrip`].
+pub fn check_workbook(nodes: &[Node], workbook: &WorkbookData) -> Result<RoundTripReport> {
+    let restored = tabular::read_workbook(workbook)?;
+
+    let index = |nodes: &[Node]| -> BTreeMap<(Option<Iri>, NodeId), Node> {
+        nodes
+            .iter()
+            .map(|node| ((node.graph.clone(), node.id.clone()), node.clone()))
+            .collect()
+    };
+    let before = index(nodes);
+    let mut after = index(&equivalence::align_blank_nodes(nodes, &restored));
+    let mut report = RoundTripReport::default();
+
+    for ((graph, id), node) in &before {
+        let mut losses = Losses {
+            node: id,
+            graph,
+            report: &mut report,
+        };
+        let Some(restored) = after.remove(&(graph.clone(), id.clone())) else {
+            losses.push(LossKind::NodeLost, None, None, None);
+            continue;
+        };
+
+        for type_name in node.types.difference(&restored.types) {
+            losses.push(LossKind::TypeLost, Some(type_name), None, None);
+        }
+        for type_name in restored.types.difference(&node.types) {
+            losses.push(LossKind::TypeAdded, Some(type_name), None, None);
+        }
+
+        for (predicate, value) in &node.properties {
+            match restored.properties.get(predicate) {
+                None => losses.push(
+                    LossKind::PropertyLost,
+                    Some(predicate),
+                    Some(value.clone()),
+                    None,
+                ),
+                Some(restored) if !equivalence::same_value(value, restored) => losses.push(
+                    LossKind::PropertyAltered,
+                    Some(predicate),
+                    Some(value.clone()),
+                    Some(restored.clone()),
+                ),
+                Some(_) => {}
+            }
+        }
+        for (predicate, value) in &restored.properties {
+            if !node.properties.contains_key(predicate) {
+                losses.push(
+                    LossKind::PropertyAdded,
+                    Some(predicate),
+                    None,
+                    Some(value.clone()),
+                );
+            }
+        }
+    }
+
+    for ((graph, id), _) in after {
+        report.losses.push(RoundTripLoss {
+            kind: LossKind::NodeAdded,
+            node: id,
+            graph,
+            predicate: None,
+            before: None,
+            after: None,
+        });
+    }
+    report
+        .losses
+        .sort_by(|lhs, rhs| (&lhs.graph, &lhs.node).cmp(&(&rhs.graph, &rhs.node)));
+    Ok(report)
+}
+
+struct Losses<'a> {
+    node: &'a NodeId,
+    graph: &'a Option<Iri>,
+    report: &'a mut RoundTripReport,
+}
+
+impl Losses<'_> {
+    fn push(
+        &mut self,
+        kind: LossKind,
+        predicate: Option<&Iri>,
+        before: Option<PropertyValue>,
+        after: Option<PropertyValue>,
+    ) {
+        self.report.losses.push(RoundTripLoss {
+            kind,
+            node: self.node.clone(),
+            graph: self.graph.clone(),
+            predicate: predicate.cloned(),
+            before,
+            after,
+        });
+    }
+}
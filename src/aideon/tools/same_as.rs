@@ -0,0 +1,193 @@
+//! Nodes linked by `owl:sameAs`.
+//!
+//! Data merged from several sources often names one thing by several ids and
+//! links them with `owl:sameAs`. [`same_as_clusters`] groups the nodes of each
+//! graph that are linked, directly or through other nodes, and [`SameAs`]
+//! chooses what becomes of them: [`merge_same_as`] merges each group into its
+//! canonical node, and [`row_anchors`] keeps them apart but lets workbooks
+//! list their rows together.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+use tracing::info;
+
+use crate::aideon::tools::equivalence::is_blank;
+use crate::aideon::tools::model::{ArrayItem, ArrayValue, Iri, Node, NodeId, PropertyValue};
+use crate::aideon::tools::sync::{DuplicateCluster, merge_clusters};
+
+/// The `owl:sameAs` predicate.
+pub const OWL_SAME_AS: &str = "http://www.w3.org/2002/07/owl#sameAs";
+
+/// The graph and id of a node.
+type Key<'a> = (Option<&'a Iri>, &'a NodeId);
+
+/// What becomes of nodes linked by `owl:sameAs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SameAs {
+    /// Merge each cluster into its canonical node, see [`merge_same_as`].
+    Merge,
+    /// Keep the nodes, writing the rows of each cluster next to each other
+    /// on workbook sheets, see [`row_anchors`].
+    Keep,
+    /// Keep the nodes and log each cluster.
+    Report,
+}
+
+/// Returns the clusters of nodes of a graph linked by `owl:sameAs` in graph
+/// and canonical id order, with the other ids of each in order. The canonical id is the
+/// first IRI of the cluster in id order, or its first blank node when it
+/// has no IRI. Links to ids without a node in the graph are left out.
+pub fn same_as_clusters(nodes: &[Node]) -> Vec<DuplicateCluster> {
+    let present: BTreeSet<Key> = nodes
+        .iter()
+        .map(|node| (node.graph.as_ref(), &node.id))
+        .collect();
+    let mut parents: BTreeMap<Key, Key> = BTreeMap::new();
+    for node in nodes {
+        let Some(value) = node.properties.get(OWL_SAME_AS) else {
+            continue;
+        };
+        for target in targets(value) {
+            let target = (node.graph.as_ref(), target);
+            if present.contains(&target) {
+                let (lhs, rhs) = (
+                    root(&parents, (node.graph.as_ref(), &node.id)),
+                    root(&parents, target),
+                );
+                if lhs != rhs {
+                    parents.insert(lhs.max(rhs), lhs.min(rhs));
+                }
+            }
+        }
+    }
+
+    let mut members: BTreeMap<Key, BTreeSet<&NodeId>> = BTreeMap::new();
+    for &key in parents.keys() {
+        let cluster = members.entry(root(&parents, key)).or_default();
+        cluster.insert(key.1);
+        cluster.insert(root(&parents, key).1);
+    }
+    let mut clusters: Vec<DuplicateCluster> = members
+        .into_iter()
+        .filter_map(|((graph, _), ids)| {
+            let canonical = ids
+                .iter()
+                .find(|id| !is_blank(id))
+                .or_else(|| ids.first())
+                .copied()?;
+            Some(DuplicateCluster {
+                graph: graph.cloned(),
+                id: canonical.clone(),
+                merged: ids
+                    .into_iter()
+                    .filter(|id| *id != canonical)
+                    .cloned()
+                    .collect(),
+            })
+        })
+        .collect();
+    clusters.sort_by(|lhs, rhs| (&lhs.graph, &lhs.id).cmp(&(&rhs.graph, &rhs.id)));
+    clusters
+}
+
+/// Merges each cluster of [`same_as_clusters`] into its canonical node like
+/// [`deduplicate`](crate::aideon::tools::sync::deduplicate), rewriting
+/// references to the other ids. The `owl:sameAs` links within a cluster are
+/// dropped; links to ids outside the data stay.
+pub fn merge_same_as(nodes: &[Node]) -> Vec<Node> {
+    let clusters = same_as_clusters(nodes);
+    if clusters.is_empty() {
+        return nodes.to_vec();
+    }
+    let mut merged = merge_clusters(nodes, &clusters);
+    for node in &mut merged {
+        let Some(value) = node.properties.remove(OWL_SAME_AS) else {
+            continue;
+        };
+        let items: Vec<ArrayItem> = value
+            .items()
+            .into_iter()
+            .filter(|item| !matches!(item, ArrayItem::ObjectRef(target) if *target == node.id))
+            .collect();
+        let value = match <[ArrayItem; 1]>::try_from(items) {
+            Ok([ArrayItem::ObjectRef(target)]) => Some(PropertyValue::ObjectRef(target)),
+            Ok([ArrayItem::Scalar(scalar)]) => Some(PropertyValue::Scalar(scalar)),
+            Err(items) if items.is_empty() => None,
+            Err(items) => Some(PropertyValue::Array(ArrayValue::from_items(items))),
+        };
+        if let Some(value) = value {
+            node.properties.insert(OWL_SAME_AS.into(), value);
+        }
+    }
+    info!(
+        clusters = clusters.len(),
+        node_count = merged.len(),
+        "merged owl:sameAs clusters"
+    );
+    merged
+}
+
+/// Maps the graph and id of each node of a cluster of [`same_as_clusters`]
+/// to the canonical id of the cluster, which workbook rows are sorted by
+/// ahead of their own id, see
+/// [`WorkbookOptions::colocate_same_as`](crate::aideon::tools::flatten::WorkbookOptions::colocate_same_as).
+pub fn row_anchors(nodes: &[Node]) -> BTreeMap<(Option<Iri>, NodeId), NodeId> {
+    same_as_clusters(nodes)
+        .into_iter()
+        .flat_map(|cluster| {
+            let DuplicateCluster { graph, id, merged } = cluster;
+            merged
+                .into_iter()
+                .map(move |other| ((graph.clone(), other), id.clone()))
+        })
+        .collect()
+}
+
+/// Applies `mode` to `nodes`: merges their clusters, or logs each of them
+/// under [`SameAs::Report`]. [`SameAs::Keep`] leaves them to the workbook
+/// layout.
+pub fn apply(nodes: Vec<Node>, mode: SameAs) -> Vec<Node> {
+    match mode {
+        SameAs::Merge => merge_same_as(&nodes),
+        SameAs::Keep => nodes,
+        SameAs::Report => {
+            for cluster in same_as_clusters(&nodes) {
+                let linked: Vec<&str> = cluster.merged.iter().map(|id| id.as_str()).collect();
+                info!(
+                    node = %cluster.id,
+                    graph = cluster.graph.as_deref().unwrap_or_default(),
+                    "owl:sameAs links {} to {}",
+                    linked.join(", "),
+                    cluster.id
+                );
+            }
+            nodes
+        }
+    }
+}
+
+/// Returns the references of `value`.
+fn targets(value: &PropertyValue) -> Vec<&NodeId> {
+    match value {
+        PropertyValue::ObjectRef(target) => vec![target],
+        PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => targets.iter().collect(),
+        PropertyValue::Array(ArrayValue::Mixed(items)) => items
+            .iter()
+            .filter_map(|item| match item {
+                ArrayItem::ObjectRef(target) => Some(target),
+                ArrayItem::Scalar(_) => None,
+            })
+            .collect(),
+        PropertyValue::Scalar(_) | PropertyValue::Array(ArrayValue::Scalars(_)) => Vec::new(),
+    }
+}
+
+/// Returns the representative of the set holding `key`.
+fn root<'a>(parents: &BTreeMap<Key<'a>, Key<'a>>, mut key: Key<'a>) -> Key<'a> {
+    while let Some(&parent) = parents.get(&key) {
+        key = parent;
+    }
+    key
+}
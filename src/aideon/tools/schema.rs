@@ -0,0 +1,265 @@
+//! Schemas inferred from the nodes of a dataset.
+//!
+//! Spreadsheets often arrive with no description of what they hold.
+//! [`infer_schema`] looks at the nodes read from any input and records, for
+//! each type, how many instances it has and which predicates they use: how
+//! many values each instance gives a predicate, the datatypes of its
+//! literals, and the types of the nodes it references. The result is
+//! written as an RDFS and SHACL skeleton, see [`InferredSchema::to_nodes`],
+//! or as a [`SCHEMA_SHEET`] table, see [`InferredSchema::to_table`], for the
+//! people documenting the data to edit.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::aideon::tools::flatten::SheetTable;
+use crate::aideon::tools::model::{ArrayItem, Iri, Node, NodeId, PropertyValue, ScalarValue};
+use crate::aideon::tools::shacl::SH;
+
+/// Name of the sheet [`InferredSchema::to_table`] lays the schema out on.
+pub const SCHEMA_SHEET: &str = "Schema";
+
+const RDF_PROPERTY: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#Property";
+const RDFS_CLASS: &str = "http://www.w3.org/2000/01/rdf-schema#Class";
+const RDFS_DOMAIN: &str = "http://www.w3.org/2000/01/rdf-schema#domain";
+const RDFS_RANGE: &str = "http://www.w3.org/2000/01/rdf-schema#range";
+const RDFS_COMMENT: &str = "http://www.w3.org/2000/01/rdf-schema#comment";
+
+/// What the instances of each type hold, by type.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct InferredSchema {
+    pub classes: BTreeMap<Iri, ClassSchema>,
+}
+
+/// What the instances of a type hold.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ClassSchema {
+    /// Nodes of the type.
+    pub instances: usize,
+    /// Predicates the nodes of the type use.
+    pub properties: BTreeMap<Iri, PropertySchema>,
+}
+
+/// How the instances of a type use a predicate.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PropertySchema {
+    /// Fewest values an instance gives the predicate, zero when some
+    /// instances leave it out.
+    pub min_count: usize,
+    /// Most values an instance gives the predicate.
+    pub max_count: usize,
+    /// Datatypes of its literals.
+    pub datatypes: BTreeSet<String>,
+    /// Types of the nodes it references.
+    pub classes: BTreeSet<Iri>,
+    /// References to nodes the data does not describe with a type.
+    pub untyped_references: usize,
+    /// Instances giving the predicate a value.
+    #[serde(skip)]
+    used_by: usize,
+}
+
+impl PropertySchema {
+    /// Whether the predicate holds literals only.
+    fn literal_only(&self) -> bool {
+        self.classes.is_empty() && self.untyped_references == 0
+    }
+
+    /// Whether the predicate holds references only.
+    fn reference_only(&self) -> bool {
+        self.datatypes.is_empty() && (!self.classes.is_empty() || self.untyped_references > 0)
+    }
+
+    /// The single datatype or type of its values, if there is one.
+    fn range(&self) -> Option<&str> {
+        if self.literal_only() && self.datatypes.len() == 1 {
+            self.datatypes.first().map(String::as_str)
+        } else if self.reference_only() && self.untyped_references == 0 && self.classes.len() == 1 {
+            self.classes.first().map(Iri::as_str)
+        } else {
+            None
+        }
+    }
+}
+
+/// Infers the schema `nodes` follow. Nodes without a type are only counted
+/// as the targets of references.
+pub fn infer_schema(nodes: &[Node]) -> InferredSchema {
+    let mut types: BTreeMap<&NodeId, BTreeSet<&Iri>> = BTreeMap::new();
+    for node in nodes {
+        types.entry(&node.id).or_default().extend(&node.types);
+    }
+
+    let mut schema = InferredSchema::default();
+    for node in nodes {
+        for type_name in &node.types {
+            let class = schema.classes.entry(type_name.clone()).or_default();
+            class.instances += 1;
+            for (predicate, value) in &node.properties {
+                let property = class.properties.entry(predicate.clone()).or_default();
+                let items = value.items();
+                if property.used_by == 0 {
+                    property.min_count = items.len();
+                }
+                property.used_by += 1;
+                property.min_count = property.min_count.min(items.len());
+                property.max_count = property.max_count.max(items.len());
+                for item in items {
+                    match item {
+                        ArrayItem::Scalar(scalar) => {
+                            if let Some(datatype) = scalar.datatype() {
+                                property.datatypes.insert(datatype.to_string());
+                            }
+                        }
+                        ArrayItem::ObjectRef(target) => {
+                            match types.get(&target).filter(|types| !types.is_empty()) {
+                                Some(targets) => {
+                                    property.classes.extend(targets.iter().copied().cloned());
+                                }
+                                None => property.untyped_references += 1,
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    for class in schema.classes.values_mut() {
+        for property in class.properties.values_mut() {
+            if property.used_by < class.instances {
+                property.min_count = 0;
+            }
+        }
+    }
+    schema
+}
+
+impl InferredSchema {
+    /// Describes the schema as RDFS and SHACL: each type as an `rdfs:Class`
+    /// targeted by a `sh:NodeShape` with a property shape per predicate,
+    /// giving the observed `sh:minCount`, when above zero, and
+    /// `sh:maxCount`, and the `sh:datatype` or `sh:class` of the values when
+    /// they all share one; and each predicate as an `rdf:Property` with the
+    /// `rdfs:domain` and `rdfs:range` it has when a single type uses it and
+    /// its values share one. Shapes are blank nodes.
+    pub fn to_nodes(&self) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut domains: BTreeMap<&Iri, Vec<(&Iri, &PropertySchema)>> = BTreeMap::new();
+        for (index, (type_name, class)) in self.classes.iter().enumerate() {
+            let mut class_node = Node::new(type_name.as_str());
+            class_node.types.insert(RDFS_CLASS.into());
+            nodes.push(class_node);
+
+            let mut shape = Node::new(format!("_:shape{index}"));
+            shape.types.insert(format!("{SH}NodeShape").into());
+            shape.insert_property(
+                format!("{SH}targetClass").into(),
+                PropertyValue::ObjectRef(type_name.as_str().into()),
+            );
+            shape.insert_property(
+                RDFS_COMMENT.into(),
+                PropertyValue::Scalar(ScalarValue::String(format!(
+                    "{} instance(s) observed",
+                    class.instances
+                ))),
+            );
+            for (position, (predicate, property)) in class.properties.iter().enumerate() {
+                domains
+                    .entry(predicate)
+                    .or_default()
+                    .push((type_name, property));
+                let mut property_shape = Node::new(format!("_:shape{index}-{position}"));
+                property_shape.insert_property(
+                    format!("{SH}path").into(),
+                    PropertyValue::ObjectRef(predicate.as_str().into()),
+                );
+                let count = |count: usize| {
+                    PropertyValue::Scalar(ScalarValue::Integer(
+                        i64::try_from(count).unwrap_or(i64::MAX),
+                    ))
+                };
+                if property.min_count > 0 {
+                    property_shape
+                        .insert_property(format!("{SH}minCount").into(), count(property.min_count));
+                }
+                property_shape
+                    .insert_property(format!("{SH}maxCount").into(), count(property.max_count));
+                match property.range() {
+                    Some(datatype) if property.literal_only() => property_shape.insert_property(
+                        format!("{SH}datatype").into(),
+                        PropertyValue::ObjectRef(datatype.into()),
+                    ),
+                    Some(class) => property_shape.insert_property(
+                        format!("{SH}class").into(),
+                        PropertyValue::ObjectRef(class.into()),
+                    ),
+                    None if property.reference_only() => property_shape.insert_property(
+                        format!("{SH}nodeKind").into(),
+                        PropertyValue::ObjectRef(format!("{SH}BlankNodeOrIRI").into()),
+                    ),
+                    None => {}
+                }
+                shape.insert_property(
+                    format!("{SH}property").into(),
+                    PropertyValue::ObjectRef(property_shape.id.clone()),
+                );
+                nodes.push(property_shape);
+            }
+            nodes.push(shape);
+        }
+
+        for (predicate, uses) in domains {
+            let mut property = Node::new(predicate.as_str());
+            property.types.insert(RDF_PROPERTY.into());
+            if let [(domain, schema)] = uses.as_slice() {
+                property.insert_property(
+                    RDFS_DOMAIN.into(),
+                    PropertyValue::ObjectRef(domain.as_str().into()),
+                );
+                if let Some(range) = schema.range() {
+                    property
+                        .insert_property(RDFS_RANGE.into(), PropertyValue::ObjectRef(range.into()));
+                }
+            }
+            nodes.push(property);
+        }
+        nodes
+    }
+
+    /// Lays the schema out as a [`SCHEMA_SHEET`] table with a row per type
+    /// and predicate it uses: the number of instances, the observed value
+    /// counts, and the datatypes and types of the values, space separated.
+    pub fn to_table(&self) -> SheetTable {
+        let mut rows = Vec::new();
+        for (type_name, class) in &self.classes {
+            for (predicate, property) in &class.properties {
+                let classes: Vec<&str> = property.classes.iter().map(Iri::as_str).collect();
+                rows.push(vec![
+                    type_name.to_string(),
+                    class.instances.to_string(),
+                    predicate.to_string(),
+                    property.min_count.to_string(),
+                    property.max_count.to_string(),
+                    Vec::from_iter(property.datatypes.iter().map(String::as_str)).join(" "),
+                    classes.join(" "),
+                ]);
+            }
+        }
+        SheetTable {
+            sheet_name: SCHEMA_SHEET.to_string(),
+            columns: [
+                "class",
+                "instances",
+                "property",
+                "minCount",
+                "maxCount",
+                "datatypes",
+                "classes",
+            ]
+            .map(String::from)
+            .to_vec(),
+            rows,
+        }
+    }
+}
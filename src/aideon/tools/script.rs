@@ -0,0 +1,156 @@
+//! User scripts transforming nodes as they are read.
+//!
+//! A script is written in [Rhai](https://rhai.rs) and defines a
+//! `transform(node)` function, which is called once for every node read by a
+//! conversion given the script in
+//! [`SyncOptions::script`](crate::aideon::tools::sync::SyncOptions::script).
+//! The node arrives as its JSON-LD node object, with `@id`, `@type`, and
+//! expanded predicates as keys, and the function returns what replaces it:
+//!
+//! * the node object, changed or not, to keep it;
+//! * `()` to drop the node;
+//! * an array of node objects to emit several nodes in its place.
+//!
+//! ```rhai
+//! fn transform(node) {
+//!     if node["@type"] == "https://schema.org/Draft" { return; }
+//!     node["https://schema.org/name"] = node["https://schema.org/name"].to_upper();
+//!     node
+//! }
+//! ```
+//!
+//! Returned nodes stay in the graph of the node they replace. Scripts need
+//! the `scripting` feature; without it, [`NodeScript::load`] fails.
+
+use std::path::Path;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::model::Node;
+
+/// A compiled transform script, cheap to clone.
+#[derive(Clone)]
+pub struct NodeScript {
+    #[cfg(feature = "scripting")]
+    compiled: std::sync::Arc<compiled::Compiled>,
+}
+
+impl std::fmt::Debug for NodeScript {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeScript").finish_non_exhaustive()
+    }
+}
+
+impl NodeScript {
+    /// Reads and compiles the script at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)?;
+        Self::from_source(&source)
+            .map_err(|err| ToolError::Script(format!("{}: {err}", path.display())))
+    }
+
+    /// Compiles `source`, which must define a `transform` function taking
+    /// one node.
+    #[cfg(feature = "scripting")]
+    pub fn from_source(source: &str) -> Result<Self> {
+        Ok(NodeScript {
+            compiled: std::sync::Arc::new(compiled::Compiled::new(source)?),
+        })
+    }
+
+    /// Compiles `source`, which must define a `transform` function taking
+    /// one node.
+    #[cfg(not(feature = "scripting"))]
+    pub fn from_source(_source: &str) -> Result<Self> {
+        Err(ToolError::Script(
+            "transform scripts require the `scripting` feature".into(),
+        ))
+    }
+
+    /// Runs the script over `nodes`, returning the nodes it keeps and emits.
+    #[cfg(feature = "scripting")]
+    pub fn apply(&self, nodes: Vec<Node>) -> Result<Vec<Node>> {
+        let mut transformed = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            transformed.extend(self.compiled.transform(&node)?);
+        }
+        Ok(transformed)
+    }
+
+    /// Runs the script over `nodes`, returning the nodes it keeps and emits.
+    #[cfg(not(feature = "scripting"))]
+    pub fn apply(&self, nodes: Vec<Node>) -> Result<Vec<Node>> {
+        Ok(nodes)
+    }
+}
+
+#[cfg(feature = "scripting")]
+mod compiled {
+    use rhai::{AST, CallFnOptions, Dynamic, Engine, Scope};
+    use serde_json::Value;
+
+    use crate::aideon::tools::error::{Result, ToolError};
+    use crate::aideon::tools::io::jsonld;
+    use crate::aideon::tools::model::Node;
+
+    const TRANSFORM: &str = "transform";
+
+    pub(super) struct Compiled {
+        engine: Engine,
+        ast: AST,
+    }
+
+    impl Compiled {
+        pub(super) fn new(source: &str) -> Result<Self> {
+            let engine = Engine::new();
+            let ast = engine
+                .compile(source)
+                .map_err(|err| ToolError::Script(err.to_string()))?;
+            if !ast
+                .iter_functions()
+                .any(|function| function.name == TRANSFORM && function.params.len() == 1)
+            {
+                return Err(ToolError::Script(
+                    "the script does not define `fn transform(node)`".into(),
+                ));
+            }
+            Ok(Compiled { engine, ast })
+        }
+
+        /// Calls `transform` on `node` and reads back what it returns.
+        pub(super) fn transform(&self, node: &Node) -> Result<Vec<Node>> {
+            let failed = |err: String| ToolError::Script(format!("node {}: {err}", node.id));
+            let argument = rhai::serde::to_dynamic(jsonld::node_to_json(node))
+                .map_err(|err| failed(err.to_string()))?;
+            let result: Dynamic = self
+                .engine
+                .call_fn_with_options(
+                    CallFnOptions::new().eval_ast(false),
+                    &mut Scope::new(),
+                    &self.ast,
+                    TRANSFORM,
+                    (argument,),
+                )
+                .map_err(|err| failed(err.to_string()))?;
+            if result.is_unit() {
+                return Ok(Vec::new());
+            }
+            let entries = match rhai::serde::from_dynamic::<Value>(&result)
+                .map_err(|err| failed(err.to_string()))?
+            {
+                Value::Object(entry) => vec![Value::Object(entry)],
+                Value::Array(entries) if entries.iter().all(Value::is_object) => entries,
+                other => {
+                    return Err(failed(format!(
+                        "`transform` must return a node, an array of nodes, or (), found {other}"
+                    )));
+                }
+            };
+            let mut nodes = jsonld::parse_jsonld_document(&Value::Array(entries))
+                .map_err(|err| failed(err.to_string()))?;
+            for emitted in &mut nodes {
+                emitted.graph = node.graph.clone();
+            }
+            Ok(nodes)
+        }
+    }
+}
@@ -0,0 +1,1042 @@
+//! SHACL Core validation of node sets.
+//!
+//! Shapes are parsed with oxigraph and evaluated directly against the
+//! internal node model. The supported subset covers `sh:targetClass`,
+//! `sh:targetNode`, `sh:targetSubjectsOf`, and `sh:targetObjectsOf` targets,
+//! predicate paths, and the `sh:minCount`, `sh:maxCount`, `sh:datatype`,
+//! `sh:class`, `sh:nodeKind`, `sh:minLength`, `sh:maxLength`, `sh:pattern`,
+//! `sh:in`, and `sh:hasValue` constraints. Shapes using other features are
+//! evaluated without them and a warning is logged.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::path::Path;
+
+use oxigraph::io::{RdfFormat, RdfParser, RdfSerializer};
+use oxigraph::model::vocab::{rdf as rdf_vocab, rdfs, xsd};
+use oxigraph::model::{
+    BlankNode, Graph, Literal, NamedNode, NamedNodeRef, NamedOrBlankNodeRef, Term, TermRef, Triple,
+    TripleRef,
+};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::flatten::{Cardinality, ColumnGroup};
+use crate::aideon::tools::io::{atomic, rdf};
+use crate::aideon::tools::model::{
+    ArrayItem, ArrayValue, Iri, Node, NodeId, PropertyValue, ScalarValue,
+};
+
+/// SHACL namespace IRI.
+pub const SH: &str = "http://www.w3.org/ns/shacl#";
+
+/// Constraint and shape features recognised but not evaluated.
+const UNSUPPORTED: &[&str] = &[
+    "node",
+    "and",
+    "or",
+    "not",
+    "xone",
+    "qualifiedValueShape",
+    "closed",
+    "equals",
+    "disjoint",
+    "lessThan",
+    "lessThanOrEquals",
+    "uniqueLang",
+    "languageIn",
+    "minInclusive",
+    "maxInclusive",
+    "minExclusive",
+    "maxExclusive",
+    "sparql",
+];
+
+/// Severity of a SHACL validation result (`sh:resultSeverity`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShaclSeverity {
+    /// `sh:Violation`, the default severity.
+    Violation,
+    /// `sh:Warning`.
+    Warning,
+    /// `sh:Info`.
+    Info,
+}
+
+impl ShaclSeverity {
+    /// Lowercase label used in reports.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShaclSeverity::Violation => "violation",
+            ShaclSeverity::Warning => "warning",
+            ShaclSeverity::Info => "info",
+        }
+    }
+
+    fn local_name(self) -> &'static str {
+        match self {
+            ShaclSeverity::Violation => "Violation",
+            ShaclSeverity::Warning => "Warning",
+            ShaclSeverity::Info => "Info",
+        }
+    }
+
+    fn from_iri(iri: &str) -> Option<Self> {
+        match iri.strip_prefix(SH)? {
+            "Violation" => Some(ShaclSeverity::Violation),
+            "Warning" => Some(ShaclSeverity::Warning),
+            "Info" => Some(ShaclSeverity::Info),
+            _ => None,
+        }
+    }
+}
+
+/// A single SHACL validation result (`sh:ValidationResult`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShaclResult {
+    /// Focus node that failed the constraint.
+    pub focus_node: NodeId,
+    /// Graph of the focus node.
+    pub graph: Option<Iri>,
+    /// Predicate path of the failing property shape.
+    pub path: Option<String>,
+    /// Offending value, for value-level constraints.
+    pub value: Option<PropertyValue>,
+    /// Severity declared by the shape.
+    pub severity: ShaclSeverity,
+    /// IRI of the constraint component, e.g. `sh:MinCountConstraintComponent`.
+    pub constraint: String,
+    /// Identifier of the shape that produced the result.
+    pub source_shape: String,
+    /// Human-readable description, taken from `sh:message` when present.
+    pub message: String,
+}
+
+/// Outcome of validating a node set against a shapes graph.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ShaclReport {
+    /// Validation results in shape and focus node order.
+    pub results: Vec<ShaclResult>,
+}
+
+impl ShaclReport {
+    /// Returns `true` when no results were produced (`sh:conforms`).
+    pub fn conforms(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Number of results with [`ShaclSeverity::Violation`].
+    pub fn violation_count(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.severity == ShaclSeverity::Violation)
+            .count()
+    }
+
+    /// Builds the standard `sh:ValidationReport` graph for the results.
+    pub fn to_graph(&self) -> Result<Graph> {
+        let mut graph = Graph::new();
+        let report = BlankNode::default();
+        graph.insert(&Triple::new(
+            report.clone(),
+            rdf_vocab::TYPE,
+            sh("ValidationReport"),
+        ));
+        graph.insert(&Triple::new(
+            report.clone(),
+            sh("conforms"),
+            Literal::from(self.conforms()),
+        ));
+
+        for result in &self.results {
+            let node = BlankNode::default();
+            let mut add = |predicate: NamedNode, object: Term| {
+                graph.insert(&Triple::new(node.clone(), predicate, object));
+            };
+            add(rdf_vocab::TYPE.into_owned(), sh("ValidationResult").into());
+            add(sh("focusNode"), rdf::id_to_term(&result.focus_node)?);
+            if let Some(path) = &result.path {
+                add(sh("resultPath"), NamedNode::new(path)?.into());
+            }
+            if let Some(value) = &result.value
+                && let Some(term) = value_to_term(value)?
+            {
+                add(sh("value"), term);
+            }
+            add(
+                sh("resultSeverity"),
+                sh(result.severity.local_name()).into(),
+            );
+            add(
+                sh("sourceConstraintComponent"),
+                NamedNode::new(&result.constraint)?.into(),
+            );
+            add(sh("sourceShape"), rdf::id_to_term(&result.source_shape)?);
+            add(
+                sh("resultMessage"),
+                Literal::new_simple_literal(&result.message).into(),
+            );
+            graph.insert(&Triple::new(report.clone(), sh("result"), node));
+        }
+
+        Ok(graph)
+    }
+
+    /// Writes the validation report graph to `path` in the given format.
+    pub fn write(&self, path: &Path, format: RdfFormat) -> Result<()> {
+        let graph = self.to_graph()?;
+        atomic::write_atomically(path, |partial| {
+            let mut serializer = RdfSerializer::from_format(format)
+                .with_prefix("sh", SH)
+                .map_err(|err| ToolError::Rdf(err.to_string()))?
+                .for_writer(File::create(partial)?);
+            for triple in graph.iter() {
+                serializer.serialize_triple(triple)?;
+            }
+            serializer.finish()?;
+            Ok(())
+        })
+    }
+}
+
+/// Parsed SHACL shapes ready to validate node sets.
+#[derive(Debug, Clone, Default)]
+pub struct ShapesGraph {
+    shapes: Vec<Shape>,
+}
+
+impl ShapesGraph {
+    /// Loads shapes from an RDF file; the format is inferred from the
+    /// extension when not given.
+    pub fn load(path: &Path, format: Option<RdfFormat>) -> Result<Self> {
+        let format = rdf::resolve_format(path, format)?;
+
+        let mut graph = Graph::new();
+        for quad in RdfParser::from_format(format).for_reader(File::open(path)?) {
+            let quad = quad.map_err(|err| ToolError::Rdf(err.to_string()))?;
+            graph.insert(&Triple::from(quad));
+        }
+        Self::from_graph(&graph)
+    }
+
+    /// Extracts the targeted shapes declared in `graph`.
+    pub fn from_graph(graph: &Graph) -> Result<Self> {
+        let mut roots: BTreeMap<String, NamedOrBlankNodeRef<'_>> = BTreeMap::new();
+        for target in [
+            "targetClass",
+            "targetNode",
+            "targetSubjectsOf",
+            "targetObjectsOf",
+        ] {
+            for triple in graph.triples_for_predicate(&sh(target)) {
+                roots.insert(subject_id(triple.subject)?, triple.subject);
+            }
+        }
+        for subject in graph.subjects_for_predicate_object(rdf_vocab::TYPE, &sh("NodeShape")) {
+            if graph.contains(TripleRef::new(subject, rdf_vocab::TYPE, rdfs::CLASS)) {
+                roots.insert(subject_id(subject)?, subject);
+            }
+        }
+
+        let parser = ShapeParser { graph };
+        let shapes = roots
+            .into_iter()
+            .map(|(id, subject)| parser.shape(id, subject, true))
+            .collect::<Result<_>>()?;
+        Ok(Self { shapes })
+    }
+
+    /// Number of targeted shapes.
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Returns `true` when the shapes graph declares no targeted shapes.
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// Cardinality of the predicates with an `sh:maxCount`, for laying them
+    /// out in workbooks: [`Cardinality::One`] for a count of one and
+    /// [`Cardinality::Many`] for more, which wins when shapes disagree.
+    pub fn cardinalities(&self) -> BTreeMap<Iri, Cardinality> {
+        let mut cardinalities = BTreeMap::new();
+        let mut pending: Vec<&Shape> = self.shapes.iter().collect();
+        while let Some(shape) = pending.pop() {
+            pending.extend(&shape.properties);
+            let Some(path) = shape.path.as_deref().filter(|_| !shape.deactivated) else {
+                continue;
+            };
+            for constraint in &shape.constraints {
+                if let Constraint::MaxCount(count) = constraint {
+                    let cardinality = if *count > 1 {
+                        Cardinality::Many
+                    } else {
+                        Cardinality::One
+                    };
+                    let entry = cardinalities.entry(Iri::from(path)).or_insert(cardinality);
+                    if cardinality == Cardinality::Many {
+                        *entry = Cardinality::Many;
+                    }
+                }
+            }
+        }
+        cardinalities
+    }
+
+    /// Bands of columns for the sheets of the classes that shapes target,
+    /// from the `sh:group` of their property shapes: groups sorted by their
+    /// `sh:order` and then label, their predicates by `sh:order` and then
+    /// IRI, with unordered groups and predicates last.
+    pub fn column_groups(&self) -> BTreeMap<Iri, Vec<ColumnGroup>> {
+        let rank = |order: Option<f64>| order.unwrap_or(f64::INFINITY);
+        let mut grouped: BTreeMap<&str, Vec<(&PropertyGroup, &Shape)>> = BTreeMap::new();
+        for shape in self.shapes.iter().filter(|shape| !shape.deactivated) {
+            for target in &shape.targets {
+                let Target::Class(class) = target else {
+                    continue;
+                };
+                let properties = grouped.entry(class).or_default();
+                for property in shape.properties.iter().filter(|shape| !shape.deactivated) {
+                    if let (Some(group), Some(_)) = (&property.group, &property.path) {
+                        properties.push((group, property));
+                    }
+                }
+            }
+        }
+        grouped
+            .into_iter()
+            .filter(|(_, properties)| !properties.is_empty())
+            .map(|(class, mut properties)| {
+                properties.sort_by(|(lhs_group, lhs), (rhs_group, rhs)| {
+                    rank(lhs_group.order)
+                        .total_cmp(&rank(rhs_group.order))
+                        .then_with(|| lhs_group.label.cmp(&rhs_group.label))
+                        .then_with(|| rank(lhs.order).total_cmp(&rank(rhs.order)))
+                        .then_with(|| lhs.path.cmp(&rhs.path))
+                });
+                let mut groups: Vec<ColumnGroup> = Vec::new();
+                for (group, property) in properties {
+                    let predicate = Iri::from(property.path.as_deref().unwrap_or_default());
+                    match groups.last_mut() {
+                        Some(last) if last.label == group.label => {
+                            if !last.predicates.contains(&predicate) {
+                                last.predicates.push(predicate);
+                            }
+                        }
+                        _ => groups.push(ColumnGroup {
+                            label: group.label.clone(),
+                            predicates: vec![predicate],
+                        }),
+                    }
+                }
+                (Iri::from(class), groups)
+            })
+            .collect()
+    }
+
+    /// Validates `nodes` against every targeted shape.
+    pub fn validate(&self, nodes: &[Node]) -> ShaclReport {
+        let data = DataIndex::new(nodes);
+        let mut report = ShaclReport::default();
+        for shape in self.shapes.iter().filter(|shape| !shape.deactivated) {
+            for focus in data.focus_nodes(&shape.targets) {
+                shape.evaluate(&focus, &data, &mut report);
+            }
+        }
+        report
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Shape {
+    id: String,
+    targets: Vec<Target>,
+    path: Option<String>,
+    constraints: Vec<Constraint>,
+    properties: Vec<Shape>,
+    severity: ShaclSeverity,
+    message: Option<String>,
+    deactivated: bool,
+    /// `sh:group` of a property shape, for laying out workbooks.
+    group: Option<PropertyGroup>,
+    /// `sh:order` of a property shape within its group.
+    order: Option<f64>,
+}
+
+/// An `sh:PropertyGroup`, labelled by its `rdfs:label` or local name.
+#[derive(Debug, Clone, PartialEq)]
+struct PropertyGroup {
+    label: String,
+    order: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+enum Target {
+    Class(String),
+    Node(String),
+    SubjectsOf(String),
+    ObjectsOf(String),
+}
+
+#[derive(Debug, Clone)]
+enum Constraint {
+    MinCount(usize),
+    MaxCount(usize),
+    Datatype(String),
+    Class(String),
+    NodeKind(NodeKind),
+    MinLength(usize),
+    MaxLength(usize),
+    Pattern(Regex),
+    In(Vec<PropertyValue>),
+    HasValue(PropertyValue),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NodeKind {
+    BlankNode,
+    Iri,
+    Literal,
+    BlankNodeOrIri,
+    BlankNodeOrLiteral,
+    IriOrLiteral,
+}
+
+struct ShapeParser<'a> {
+    graph: &'a Graph,
+}
+
+impl<'a> ShapeParser<'a> {
+    fn shape(&self, id: String, subject: NamedOrBlankNodeRef<'a>, root: bool) -> Result<Shape> {
+        let mut shape = Shape {
+            targets: Vec::new(),
+            path: None,
+            constraints: Vec::new(),
+            properties: Vec::new(),
+            severity: ShaclSeverity::Violation,
+            message: None,
+            deactivated: false,
+            group: None,
+            order: None,
+            id,
+        };
+
+        if root {
+            shape.targets = self.targets(subject)?;
+        }
+
+        if let Some(path) = self.object(subject, "path") {
+            match path {
+                TermRef::NamedNode(predicate) => shape.path = Some(predicate.as_str().into()),
+                _ => {
+                    warn!(shape = %shape.id, "only predicate paths are supported; skipping shape");
+                    shape.deactivated = true;
+                    return Ok(shape);
+                }
+            }
+        }
+
+        if let Some(TermRef::NamedNode(severity)) = self.object(subject, "severity") {
+            shape.severity =
+                ShaclSeverity::from_iri(severity.as_str()).unwrap_or(ShaclSeverity::Violation);
+        }
+        if let Some(TermRef::Literal(message)) = self.object(subject, "message") {
+            shape.message = Some(message.value().into());
+        }
+        if let Some(TermRef::Literal(flag)) = self.object(subject, "deactivated") {
+            shape.deactivated = flag.value() == "true";
+        }
+        shape.order = self.order(subject);
+        shape.group = self
+            .object(subject, "group")
+            .and_then(term_subject)
+            .map(|group| self.group(group));
+
+        shape.constraints = self.constraints(&shape.id, subject)?;
+
+        if shape.path.is_none() {
+            for property in self
+                .graph
+                .objects_for_subject_predicate(subject, &sh("property"))
+            {
+                let child = term_subject(property).ok_or_else(|| {
+                    ToolError::Shacl(format!(
+                        "sh:property of shape {} must be an IRI or blank node",
+                        shape.id
+                    ))
+                })?;
+                let child = self.shape(subject_id(child)?, child, false)?;
+                if child.path.is_none() && !child.deactivated {
+                    return Err(ToolError::Shacl(format!(
+                        "property shape {} has no sh:path",
+                        child.id
+                    )));
+                }
+                shape.properties.push(child);
+            }
+        }
+
+        for feature in UNSUPPORTED {
+            if self.object(subject, feature).is_some() {
+                warn!(shape = %shape.id, feature = %format!("sh:{feature}"), "SHACL feature is not supported; ignoring it");
+            }
+        }
+
+        Ok(shape)
+    }
+
+    fn targets(&self, subject: NamedOrBlankNodeRef<'a>) -> Result<Vec<Target>> {
+        let mut targets = Vec::new();
+        for (name, build) in [
+            ("targetClass", Target::Class as fn(String) -> Target),
+            ("targetNode", Target::Node),
+            ("targetSubjectsOf", Target::SubjectsOf),
+            ("targetObjectsOf", Target::ObjectsOf),
+        ] {
+            for object in self.graph.objects_for_subject_predicate(subject, &sh(name)) {
+                let value = match object {
+                    TermRef::NamedNode(node) => node.as_str().to_string(),
+                    TermRef::BlankNode(node) => format!("_:{}", node.as_str()),
+                    TermRef::Literal(_) => {
+                        return Err(ToolError::Shacl(format!(
+                            "sh:{name} of shape {} must be an IRI",
+                            subject_id(subject)?
+                        )));
+                    }
+                };
+                targets.push(build(value));
+            }
+        }
+
+        let is_node_shape =
+            self.graph
+                .contains(TripleRef::new(subject, rdf_vocab::TYPE, &sh("NodeShape")));
+        let is_class = self
+            .graph
+            .contains(TripleRef::new(subject, rdf_vocab::TYPE, rdfs::CLASS));
+        if is_node_shape
+            && is_class
+            && let NamedOrBlankNodeRef::NamedNode(class) = subject
+        {
+            targets.push(Target::Class(class.as_str().into()));
+        }
+        Ok(targets)
+    }
+
+    fn constraints(&self, id: &str, subject: NamedOrBlankNodeRef<'a>) -> Result<Vec<Constraint>> {
+        let mut constraints = Vec::new();
+
+        for (name, build) in [
+            ("minCount", Constraint::MinCount as fn(usize) -> Constraint),
+            ("maxCount", Constraint::MaxCount),
+            ("minLength", Constraint::MinLength),
+            ("maxLength", Constraint::MaxLength),
+        ] {
+            if let Some(object) = self.object(subject, name) {
+                let count = match object {
+                    TermRef::Literal(literal) => literal.value().parse::<usize>().ok(),
+                    _ => None,
+                };
+                let count = count.ok_or_else(|| {
+                    ToolError::Shacl(format!(
+                        "sh:{name} of shape {id} must be a non-negative integer"
+                    ))
+                })?;
+                constraints.push(build(count));
+            }
+        }
+
+        for (name, build) in [
+            ("datatype", Constraint::Datatype as fn(String) -> Constraint),
+            ("class", Constraint::Class),
+        ] {
+            for object in self.graph.objects_for_subject_predicate(subject, &sh(name)) {
+                let TermRef::NamedNode(iri) = object else {
+                    return Err(ToolError::Shacl(format!(
+                        "sh:{name} of shape {id} must be an IRI"
+                    )));
+                };
+                constraints.push(build(iri.as_str().into()));
+            }
+        }
+
+        if let Some(object) = self.object(subject, "nodeKind") {
+            let kind = match object {
+                TermRef::NamedNode(iri) => NodeKind::from_iri(iri.as_str()),
+                _ => None,
+            };
+            let kind = kind.ok_or_else(|| {
+                ToolError::Shacl(format!(
+                    "sh:nodeKind of shape {id} is not a SHACL node kind"
+                ))
+            })?;
+            constraints.push(Constraint::NodeKind(kind));
+        }
+
+        if let Some(TermRef::Literal(pattern)) = self.object(subject, "pattern") {
+            let mut builder = RegexBuilder::new(pattern.value());
+            if let Some(TermRef::Literal(flags)) = self.object(subject, "flags") {
+                for flag in flags.value().chars() {
+                    match flag {
+                        'i' => builder.case_insensitive(true),
+                        'm' => builder.multi_line(true),
+                        's' => builder.dot_matches_new_line(true),
+                        'x' => builder.ignore_whitespace(true),
+                        _ => &mut builder,
+                    };
+                }
+            }
+            let regex = builder.build().map_err(|err| {
+                ToolError::Shacl(format!("invalid sh:pattern on shape {id}: {err}"))
+            })?;
+            constraints.push(Constraint::Pattern(regex));
+        }
+
+        if let Some(list) = self.object(subject, "in") {
+            let members = self
+                .list(list)
+                .ok_or_else(|| {
+                    ToolError::Shacl(format!("sh:in of shape {id} must be an RDF list"))
+                })?
+                .into_iter()
+                .map(term_to_value)
+                .collect::<Result<_>>()?;
+            constraints.push(Constraint::In(members));
+        }
+
+        for value in self
+            .graph
+            .objects_for_subject_predicate(subject, &sh("hasValue"))
+        {
+            constraints.push(Constraint::HasValue(term_to_value(value)?));
+        }
+
+        Ok(constraints)
+    }
+
+    /// Reads the `sh:order` of `subject`, ignoring values that are not
+    /// numbers.
+    fn order(&self, subject: NamedOrBlankNodeRef<'a>) -> Option<f64> {
+        match self.object(subject, "order")? {
+            TermRef::Literal(order) => order.value().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn group(&self, group: NamedOrBlankNodeRef<'a>) -> PropertyGroup {
+        let label = match self.graph.object_for_subject_predicate(group, rdfs::LABEL) {
+            Some(TermRef::Literal(label)) => label.value().to_string(),
+            _ => match group {
+                NamedOrBlankNodeRef::NamedNode(node) => node
+                    .as_str()
+                    .rsplit(['/', '#'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                NamedOrBlankNodeRef::BlankNode(node) => node.as_str().to_string(),
+            },
+        };
+        PropertyGroup {
+            label,
+            order: self.order(group),
+        }
+    }
+
+    fn object(&self, subject: NamedOrBlankNodeRef<'a>, name: &str) -> Option<TermRef<'a>> {
+        self.graph.object_for_subject_predicate(subject, &sh(name))
+    }
+
+    fn list(&self, mut head: TermRef<'a>) -> Option<Vec<TermRef<'a>>> {
+        let mut members = Vec::new();
+        while head != TermRef::from(rdf_vocab::NIL) {
+            let node = term_subject(head)?;
+            members.push(
+                self.graph
+                    .object_for_subject_predicate(node, rdf_vocab::FIRST)?,
+            );
+            head = self
+                .graph
+                .object_for_subject_predicate(node, rdf_vocab::REST)?;
+        }
+        Some(members)
+    }
+}
+
+struct Focus<'a> {
+    id: NodeId,
+    graph: Option<Iri>,
+    node: Option<&'a Node>,
+}
+
+struct DataIndex<'a> {
+    nodes: &'a [Node],
+    by_id: HashMap<&'a str, Vec<&'a Node>>,
+}
+
+impl<'a> DataIndex<'a> {
+    fn new(nodes: &'a [Node]) -> Self {
+        let mut by_id: HashMap<&str, Vec<&Node>> = HashMap::new();
+        for node in nodes {
+            by_id.entry(node.id.as_str()).or_default().push(node);
+        }
+        Self { nodes, by_id }
+    }
+
+    fn has_type(&self, id: &str, class: &str) -> bool {
+        self.by_id
+            .get(id)
+            .is_some_and(|nodes| nodes.iter().any(|node| node.types.contains(class)))
+    }
+
+    fn focus_nodes(&self, targets: &[Target]) -> Vec<Focus<'a>> {
+        let mut matched: Vec<&'a Node> = Vec::new();
+        let mut ids = Vec::new();
+
+        for target in targets {
+            match target {
+                Target::Class(class) => matched.extend(
+                    self.nodes
+                        .iter()
+                        .filter(|node| node.types.contains(class.as_str())),
+                ),
+                Target::Node(id) => ids.push(NodeId::from(id)),
+                Target::SubjectsOf(predicate) => matched.extend(
+                    self.nodes
+                        .iter()
+                        .filter(|node| !path_values(Some(node), predicate).is_empty()),
+                ),
+                Target::ObjectsOf(predicate) => {
+                    for node in self.nodes {
+                        for value in path_values(Some(node), predicate) {
+                            if let PropertyValue::ObjectRef(id) = value {
+                                ids.push(id);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut focus: BTreeMap<(Option<Iri>, NodeId), Option<&'a Node>> = BTreeMap::new();
+        for id in ids {
+            match self.by_id.get(id.as_str()) {
+                Some(nodes) => matched.extend(nodes.iter().copied()),
+                None => {
+                    focus.insert((None, id), None);
+                }
+            }
+        }
+        for node in matched {
+            focus.insert((node.graph.clone(), node.id.clone()), Some(node));
+        }
+
+        focus
+            .into_iter()
+            .map(|((graph, id), node)| Focus { id, graph, node })
+            .collect()
+    }
+}
+
+impl Shape {
+    fn evaluate(&self, focus: &Focus<'_>, data: &DataIndex<'_>, report: &mut ShaclReport) {
+        let values = match &self.path {
+            Some(path) => path_values(focus.node, path),
+            None => vec![PropertyValue::ObjectRef(focus.id.clone())],
+        };
+
+        for constraint in &self.constraints {
+            match constraint {
+                Constraint::MinCount(min) if values.len() < *min => self.report(
+                    report,
+                    focus,
+                    constraint,
+                    None,
+                    format!("expected at least {min} value(s), found {}", values.len()),
+                ),
+                Constraint::MaxCount(max) if values.len() > *max => self.report(
+                    report,
+                    focus,
+                    constraint,
+                    None,
+                    format!("expected at most {max} value(s), found {}", values.len()),
+                ),
+                Constraint::HasValue(expected) if !values.contains(expected) => self.report(
+                    report,
+                    focus,
+                    constraint,
+                    None,
+                    "required value is missing".into(),
+                ),
+                Constraint::MinCount(_) | Constraint::MaxCount(_) | Constraint::HasValue(_) => {}
+                _ => {
+                    for value in &values {
+                        if let Some(message) = constraint.check_value(value, data) {
+                            self.report(report, focus, constraint, Some(value.clone()), message);
+                        }
+                    }
+                }
+            }
+        }
+
+        for property in self.properties.iter().filter(|shape| !shape.deactivated) {
+            property.evaluate(focus, data, report);
+        }
+    }
+
+    fn report(
+        &self,
+        report: &mut ShaclReport,
+        focus: &Focus<'_>,
+        constraint: &Constraint,
+        value: Option<PropertyValue>,
+        message: String,
+    ) {
+        report.results.push(ShaclResult {
+            focus_node: focus.id.clone(),
+            graph: focus.graph.clone(),
+            path: self.path.clone(),
+            value,
+            severity: self.severity,
+            constraint: format!("{SH}{}", constraint.component()),
+            source_shape: self.id.clone(),
+            message: self.message.clone().unwrap_or(message),
+        });
+    }
+}
+
+impl Constraint {
+    fn component(&self) -> &'static str {
+        match self {
+            Constraint::MinCount(_) => "MinCountConstraintComponent",
+            Constraint::MaxCount(_) => "MaxCountConstraintComponent",
+            Constraint::Datatype(_) => "DatatypeConstraintComponent",
+            Constraint::Class(_) => "ClassConstraintComponent",
+            Constraint::NodeKind(_) => "NodeKindConstraintComponent",
+            Constraint::MinLength(_) => "MinLengthConstraintComponent",
+            Constraint::MaxLength(_) => "MaxLengthConstraintComponent",
+            Constraint::Pattern(_) => "PatternConstraintComponent",
+            Constraint::In(_) => "InConstraintComponent",
+            Constraint::HasValue(_) => "HasValueConstraintComponent",
+        }
+    }
+
+    /// Returns a failure message when `value` violates this value-level
+    /// constraint.
+    fn check_value(&self, value: &PropertyValue, data: &DataIndex<'_>) -> Option<String> {
+        let length = || lexical_form(value).map(|form| form.chars().count());
+        match self {
+            Constraint::Datatype(datatype) => match value {
+                PropertyValue::Scalar(scalar) if datatype_matches(scalar, datatype) => None,
+                _ => Some(format!("value does not have datatype {datatype}")),
+            },
+            Constraint::Class(class) => match value {
+                PropertyValue::ObjectRef(id) if data.has_type(id, class) => None,
+                _ => Some(format!("value is not an instance of {class}")),
+            },
+            Constraint::NodeKind(kind) => {
+                (!kind.matches(value)).then(|| format!("value is not a {}", kind.label()))
+            }
+            Constraint::MinLength(min) => match length() {
+                Some(length) if length >= *min => None,
+                _ => Some(format!("value is shorter than {min} character(s)")),
+            },
+            Constraint::MaxLength(max) => match length() {
+                Some(length) if length <= *max => None,
+                _ => Some(format!("value is longer than {max} character(s)")),
+            },
+            Constraint::Pattern(regex) => match lexical_form(value) {
+                Some(form) if regex.is_match(&form) => None,
+                _ => Some(format!("value does not match pattern {}", regex.as_str())),
+            },
+            Constraint::In(members) => {
+                (!members.contains(value)).then(|| "value is not in the allowed list".into())
+            }
+            Constraint::MinCount(_) | Constraint::MaxCount(_) | Constraint::HasValue(_) => None,
+        }
+    }
+}
+
+impl NodeKind {
+    fn from_iri(iri: &str) -> Option<Self> {
+        match iri.strip_prefix(SH)? {
+            "BlankNode" => Some(NodeKind::BlankNode),
+            "IRI" => Some(NodeKind::Iri),
+            "Literal" => Some(NodeKind::Literal),
+            "BlankNodeOrIRI" => Some(NodeKind::BlankNodeOrIri),
+            "BlankNodeOrLiteral" => Some(NodeKind::BlankNodeOrLiteral),
+            "IRIOrLiteral" => Some(NodeKind::IriOrLiteral),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            NodeKind::BlankNode => "blank node",
+            NodeKind::Iri => "IRI",
+            NodeKind::Literal => "literal",
+            NodeKind::BlankNodeOrIri => "blank node or IRI",
+            NodeKind::BlankNodeOrLiteral => "blank node or literal",
+            NodeKind::IriOrLiteral => "IRI or literal",
+        }
+    }
+
+    fn matches(self, value: &PropertyValue) -> bool {
+        let (blank, iri, literal) = match value {
+            PropertyValue::ObjectRef(id) => (id.starts_with("_:"), !id.starts_with("_:"), false),
+            PropertyValue::Scalar(_) => (false, false, true),
+            PropertyValue::Array(_) => (false, false, false),
+        };
+        match self {
+            NodeKind::BlankNode => blank,
+            NodeKind::Iri => iri,
+            NodeKind::Literal => literal,
+            NodeKind::BlankNodeOrIri => blank || iri,
+            NodeKind::BlankNodeOrLiteral => blank || literal,
+            NodeKind::IriOrLiteral => iri || literal,
+        }
+    }
+}
+
+/// Collects the individual values of `path` on `node`. Array properties
+/// contribute one value per item and `null` literals are treated as absent.
+fn path_values(node: Option<&Node>, path: &str) -> Vec<PropertyValue> {
+    let Some(node) = node else {
+        return Vec::new();
+    };
+    if path == rdf::RDF_TYPE {
+        return node
+            .types
+            .iter()
+            .map(|type_name| PropertyValue::ObjectRef(type_name.clone()))
+            .collect();
+    }
+
+    let scalar = |scalar: &ScalarValue| {
+        (!matches!(scalar, ScalarValue::Null)).then(|| PropertyValue::Scalar(scalar.clone()))
+    };
+    match node.properties.get(path) {
+        None => Vec::new(),
+        Some(PropertyValue::Scalar(value)) => scalar(value).into_iter().collect(),
+        Some(PropertyValue::ObjectRef(id)) => vec![PropertyValue::ObjectRef(id.clone())],
+        Some(PropertyValue::Array(ArrayValue::Scalars(items))) => {
+            items.iter().filter_map(scalar).collect()
+        }
+        Some(PropertyValue::Array(ArrayValue::ObjectRefs(ids))) => ids
+            .iter()
+            .map(|id| PropertyValue::ObjectRef(id.clone()))
+            .collect(),
+        Some(PropertyValue::Array(ArrayValue::Mixed(items))) => items
+            .iter()
+            .filter_map(|item| match item {
+                ArrayItem::Scalar(value) => scalar(value),
+                ArrayItem::ObjectRef(id) => Some(PropertyValue::ObjectRef(id.clone())),
+            })
+            .collect(),
+    }
+}
+
+/// Returns the lexical form used by string-based constraints. Blank nodes
+/// have none.
+fn lexical_form(value: &PropertyValue) -> Option<String> {
+    match value {
+        PropertyValue::ObjectRef(id) => (!id.starts_with("_:")).then(|| id.to_string()),
+        PropertyValue::Scalar(scalar) => match scalar {
+            ScalarValue::String(value)
+            | ScalarValue::TypedLiteral { value, .. }
+            | ScalarValue::LangString { value, .. } => Some(value.clone()),
+            ScalarValue::Integer(number) => Some(number.to_string()),
+            ScalarValue::Double(number) => Some(number.to_string()),
+            ScalarValue::Boolean(flag) => Some(flag.to_string()),
+            ScalarValue::Date(_) | ScalarValue::DateTime(_) => scalar
+                .typed_lexical()
+                .map(|(lexical, _)| lexical.into_owned()),
+            ScalarValue::Null => None,
+        },
+        PropertyValue::Array(_) => None,
+    }
+}
+
+/// Checks a literal against `sh:datatype`. Integers also match the types
+/// derived from `xsd:integer` whose range contains them and `xsd:decimal`,
+/// which `xsd:integer` is derived from; doubles also match `xsd:float`.
+fn datatype_matches(scalar: &ScalarValue, datatype: &str) -> bool {
+    let is = |iri: NamedNodeRef<'_>| datatype == iri.as_str();
+    match scalar {
+        ScalarValue::String(_) => is(xsd::STRING),
+        ScalarValue::LangString { .. } => is(rdf_vocab::LANG_STRING),
+        ScalarValue::Boolean(_) => is(xsd::BOOLEAN),
+        ScalarValue::TypedLiteral {
+            datatype: actual, ..
+        } => actual == datatype,
+        ScalarValue::Date(_) => is(xsd::DATE),
+        ScalarValue::DateTime(_) => is(xsd::DATE_TIME),
+        ScalarValue::Null => false,
+        ScalarValue::Double(_) => is(xsd::DOUBLE) || is(xsd::FLOAT),
+        ScalarValue::Integer(number) => {
+            let within = |min: i64, max: i64| (min..=max).contains(number);
+            if is(xsd::INTEGER) || is(xsd::DECIMAL) || is(xsd::LONG) {
+                true
+            } else if is(xsd::INT) {
+                within(i32::MIN.into(), i32::MAX.into())
+            } else if is(xsd::SHORT) {
+                within(i16::MIN.into(), i16::MAX.into())
+            } else if is(xsd::BYTE) {
+                within(i8::MIN.into(), i8::MAX.into())
+            } else if is(xsd::NON_NEGATIVE_INTEGER) || is(xsd::UNSIGNED_LONG) {
+                *number >= 0
+            } else if is(xsd::UNSIGNED_INT) {
+                within(0, u32::MAX.into())
+            } else if is(xsd::UNSIGNED_SHORT) {
+                within(0, u16::MAX.into())
+            } else if is(xsd::UNSIGNED_BYTE) {
+                within(0, u8::MAX.into())
+            } else if is(xsd::POSITIVE_INTEGER) {
+                *number > 0
+            } else if is(xsd::NON_POSITIVE_INTEGER) {
+                *number <= 0
+            } else if is(xsd::NEGATIVE_INTEGER) {
+                *number < 0
+            } else {
+                false
+            }
+        }
+    }
+}
+
+fn sh(local: &str) -> NamedNode {
+    NamedNode::new_unchecked(format!("{SH}{local}"))
+}
+
+fn subject_id(subject: NamedOrBlankNodeRef<'_>) -> Result<String> {
+    rdf::subject_to_id(&subject.into_owned())
+}
+
+fn term_subject(term: TermRef<'_>) -> Option<NamedOrBlankNodeRef<'_>> {
+    match term {
+        TermRef::NamedNode(node) => Some(node.into()),
+        TermRef::BlankNode(node) => Some(node.into()),
+        TermRef::Literal(_) => None,
+    }
+}
+
+fn term_to_value(term: TermRef<'_>) -> Result<PropertyValue> {
+    Ok(match term {
+        TermRef::NamedNode(node) => PropertyValue::ObjectRef(node.as_str().into()),
+        TermRef::BlankNode(node) => PropertyValue::ObjectRef(format!("_:{}", node.as_str()).into()),
+        TermRef::Literal(literal) => {
+            PropertyValue::Scalar(rdf::literal_to_scalar(&literal.into_owned())?)
+        }
+    })
+}
+
+fn value_to_term(value: &PropertyValue) -> Result<Option<Term>> {
+    match value {
+        PropertyValue::Scalar(scalar) => rdf::scalar_to_term(scalar),
+        PropertyValue::ObjectRef(id) => rdf::id_to_term(id).map(Some),
+        PropertyValue::Array(_) => Ok(None),
+    }
+}
@@ -0,0 +1,195 @@
+//! Detached signatures over datasets.
+//!
+//! A dataset is signed in its canonical form, the RDF Dataset
+//! Canonicalization (RDFC-1.0) of its statements, see [`rdfc`], rather than
+//! as the bytes of a file, so a partner can check a dataset received in any
+//! format, with any statement order and blank node labels, against the
+//! signature of the one that was sent, with this tool or any RDFC-1.0
+//! implementation. RDF files are signed over the statements they hold, read
+//! with [`read_dataset`](crate::aideon::tools::io::rdf::read_dataset), so
+//! literals the node model would normalise are covered as written; other
+//! inputs over the statements of their nodes, see
+//! [`dataset_quads`](crate::aideon::tools::io::rdf::dataset_quads).
+//!
+//! Signatures are detached JSON Web Signatures ([RFC 7515], appendix F)
+//! over the unencoded canonical N-Quads ([RFC 7797]), made with Ed25519
+//! keys: `BASE64URL(header)..BASE64URL(signature)`, with the header
+//! `{"alg":"EdDSA","b64":false,"crit":["b64"]}` and an optional `kid`.
+//! Private keys are PKCS#8 documents, such as `openssl genpkey -algorithm
+//! ed25519` writes, and public keys SubjectPublicKeyInfo documents, such as
+//! `openssl pkey -pubout` writes, in PEM or DER; the raw 32 bytes of either
+//! are accepted too. Signing and verifying need the `signing` feature;
+//! without it, [`sign`] and [`verify`] fail.
+//!
+//! [RFC 7515]: https://www.rfc-editor.org/rfc/rfc7515
+//! [RFC 7797]: https://www.rfc-editor.org/rfc/rfc7797
+
+use oxigraph::model::Quad;
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::io::rdf;
+use crate::aideon::tools::model::Node;
+use crate::aideon::tools::rdfc;
+
+/// Signs the canonical form of the statements of `nodes`, see
+/// [`sign_quads`].
+pub fn sign(nodes: &[Node], private_key: &[u8], key_id: Option<&str>) -> Result<String> {
+    sign_quads(&rdf::dataset_quads(nodes)?, private_key, key_id)
+}
+
+/// Checks `signature` over the canonical form of the statements of `nodes`,
+/// see [`verify_quads`].
+pub fn verify(nodes: &[Node], signature: &str, public_key: &[u8]) -> Result<()> {
+    verify_quads(&rdf::dataset_quads(nodes)?, signature, public_key)
+}
+
+/// Signs the canonical form of `quads` with the Ed25519 `private_key`,
+/// naming the key `key_id` in the signature's header when given, and
+/// returns the detached JWS. Datasets whose blank nodes are too symmetric
+/// to canonicalize fail with
+/// [`ToolError::SymmetricBlankNodes`](crate::aideon::tools::ToolError::SymmetricBlankNodes).
+pub fn sign_quads(quads: &[Quad], private_key: &[u8], key_id: Option<&str>) -> Result<String> {
+    let payload = rdfc::canonical_nquads(quads)?;
+    jws::sign(payload.as_bytes(), private_key, key_id)
+}
+
+/// Checks the detached JWS `signature` over the canonical form of `quads`
+/// with the Ed25519 `public_key`, failing with
+/// [`ToolError::InvalidSignature`](crate::aideon::tools::ToolError::InvalidSignature)
+/// when the dataset is not the one that was signed.
+pub fn verify_quads(quads: &[Quad], signature: &str, public_key: &[u8]) -> Result<()> {
+    let payload = rdfc::canonical_nquads(quads)?;
+    jws::verify(payload.as_bytes(), signature.trim(), public_key)
+}
+
+#[cfg(feature = "signing")]
+mod jws {
+    use base64::Engine;
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+    use ring::signature::{ED25519, Ed25519KeyPair, UnparsedPublicKey};
+    use serde_json::{Value, json};
+
+    use crate::aideon::tools::error::{Result, ToolError};
+
+    /// DER prefix of the SubjectPublicKeyInfo of an Ed25519 key, followed by
+    /// its 32 bytes.
+    const SPKI_PREFIX: [u8; 12] = [
+        0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+    ];
+
+    pub(super) fn sign(payload: &[u8], private_key: &[u8], key_id: Option<&str>) -> Result<String> {
+        let key = decode_pem(private_key, "PRIVATE KEY")?;
+        let pair = if key.len() == 32 {
+            Ed25519KeyPair::from_seed_unchecked(&key)
+        } else {
+            Ed25519KeyPair::from_pkcs8_maybe_unchecked(&key)
+        }
+        .map_err(|err| signature_error(format!("invalid Ed25519 private key: {err}")))?;
+
+        let mut header = json!({ "alg": "EdDSA", "b64": false, "crit": ["b64"] });
+        if let Some(key_id) = key_id {
+            header["kid"] = key_id.into();
+        }
+        let header = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?);
+        let signature = pair.sign(&signing_input(&header, payload));
+        Ok(format!(
+            "{header}..{}",
+            URL_SAFE_NO_PAD.encode(signature.as_ref())
+        ))
+    }
+
+    pub(super) fn verify(payload: &[u8], signature: &str, public_key: &[u8]) -> Result<()> {
+        let Some((header, "", encoded)) = signature
+            .split_once('.')
+            .and_then(|(header, rest)| rest.split_once('.').map(|(body, sig)| (header, body, sig)))
+        else {
+            return Err(signature_error(
+                "expected a detached JWS `header..signature`".into(),
+            ));
+        };
+        let fields: Value = URL_SAFE_NO_PAD
+            .decode(header)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| signature_error("the JWS header is not base64url JSON".into()))?;
+        if fields["alg"] != "EdDSA" || fields["b64"] != false {
+            return Err(signature_error(
+                "only EdDSA signatures over unencoded payloads are supported".into(),
+            ));
+        }
+        // RFC 7797 requires `b64` to be marked critical, and RFC 7515 rejects
+        // critical extensions the verifier does not understand.
+        if fields["crit"] != json!(["b64"]) {
+            return Err(signature_error(
+                "the JWS header must list `b64`, and only `b64`, as critical".into(),
+            ));
+        }
+        let signature = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|err| signature_error(format!("the JWS signature is not base64url: {err}")))?;
+
+        let key = decode_pem(public_key, "PUBLIC KEY")?;
+        let key = match key.strip_prefix(&SPKI_PREFIX[..]) {
+            Some(raw) => raw,
+            None => &key,
+        };
+        if key.len() != 32 {
+            return Err(signature_error("invalid Ed25519 public key".into()));
+        }
+        UnparsedPublicKey::new(&ED25519, key)
+            .verify(&signing_input(header, payload), &signature)
+            .map_err(|_| ToolError::InvalidSignature)
+    }
+
+    fn signing_input(header: &str, payload: &[u8]) -> Vec<u8> {
+        let mut input = Vec::with_capacity(header.len() + 1 + payload.len());
+        input.extend_from_slice(header.as_bytes());
+        input.push(b'.');
+        input.extend_from_slice(payload);
+        input
+    }
+
+    /// Returns the DER document of a PEM `label` block, or `key` itself when
+    /// it is not PEM.
+    fn decode_pem(key: &[u8], label: &str) -> Result<Vec<u8>> {
+        let Ok(text) = std::str::from_utf8(key) else {
+            return Ok(key.to_vec());
+        };
+        let begin = format!("-----BEGIN {label}-----");
+        let Some((_, rest)) = text.split_once(&begin) else {
+            return Ok(key.to_vec());
+        };
+        let body = rest
+            .split_once(&format!("-----END {label}-----"))
+            .map_or(rest, |(body, _)| body);
+        let body: String = body.split_whitespace().collect();
+        STANDARD
+            .decode(body)
+            .map_err(|err| signature_error(format!("invalid PEM {label}: {err}")))
+    }
+
+    fn signature_error(message: String) -> ToolError {
+        ToolError::Signature(message)
+    }
+}
+
+#[cfg(not(feature = "signing"))]
+mod jws {
+    use crate::aideon::tools::error::{Result, ToolError};
+
+    pub(super) fn sign(
+        _payload: &[u8],
+        _private_key: &[u8],
+        _key_id: Option<&str>,
+    ) -> Result<String> {
+        Err(unsupported())
+    }
+
+    pub(super) fn verify(_payload: &[u8], _signature: &str, _public_key: &[u8]) -> Result<()> {
+        Err(unsupported())
+    }
+
+    fn unsupported() -> ToolError {
+        ToolError::Signature("signatures require the `signing` feature".into())
+    }
+}
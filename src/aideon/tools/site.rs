@@ -0,0 +1,378 @@
+//! Static HTML rendering of a node set for browsing on any web server.
+//!
+//! The site contains an `index.html` listing every type, one page per type
+//! under `types/`, and one page per node under `nodes/`. Object references
+//! between nodes become relative links labelled with their predicate, and
+//! each node page lists the nodes that link to it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use uuid::Uuid;
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::model::{
+    ArrayItem, ArrayValue, Node, NodeId, PropertyValue, ScalarValue,
+};
+use crate::aideon::tools::report::escape_html;
+
+/// Predicates consulted, in order, for a node's display label.
+const LABEL_PREDICATES: &[&str] = &[
+    "http://www.w3.org/2000/01/rdf-schema#label",
+    "http://www.w3.org/2004/02/skos/core#prefLabel",
+    "https://schema.org/name",
+    "http://schema.org/name",
+    "http://xmlns.com/foaf/0.1/name",
+    "http://purl.org/dc/terms/title",
+];
+
+const STYLE: &str = concat!(
+    "body{font-family:system-ui,sans-serif;margin:2rem auto;max-width:60rem;color:#222}\n",
+    "table{border-collapse:collapse;width:100%}\n",
+    "th,td{border:1px solid #ccc;padding:.3rem .6rem;text-align:left;vertical-align:top}\n",
+    "th{background:#f3f3f3;width:25%}\nul{padding-left:1.2rem}\n",
+    ".iri{color:#666;font-size:.9em;word-break:break-all}\n"
+);
+
+/// Writes a browsable static site for `nodes` into `dir`, creating it when
+/// needed.
+pub fn write_site(dir: &Path, nodes: &[Node]) -> Result<()> {
+    let site = Site::new(nodes);
+    fs::create_dir_all(dir.join("nodes"))?;
+    fs::create_dir_all(dir.join("types"))?;
+
+    write_page(&dir.join("index.html"), site.index_page())?;
+    for (type_name, members) in &site.types {
+        write_page(
+            &dir.join("types").join(page_name(type_name)),
+            site.type_page(type_name, members),
+        )?;
+    }
+    for (index, node) in nodes.iter().enumerate() {
+        write_page(
+            &dir.join("nodes").join(&site.pages[index]),
+            site.node_page(index, node),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_page(path: &Path, html: String) -> Result<()> {
+    atomic::write_atomically(path, |partial| Ok(fs::write(partial, html)?))
+}
+
+struct Site<'a> {
+    nodes: &'a [Node],
+    /// Page file name per node, aligned with `nodes`.
+    pages: Vec<String>,
+    /// Node indices per id, used to resolve references.
+    by_id: HashMap<&'a str, Vec<usize>>,
+    /// Node indices per type IRI.
+    types: BTreeMap<&'a str, Vec<usize>>,
+    /// Incoming `(source index, predicate)` links per target index.
+    incoming: HashMap<usize, Vec<(usize, &'a str)>>,
+}
+
+impl<'a> Site<'a> {
+    fn new(nodes: &'a [Node]) -> Self {
+        let pages = nodes
+            .iter()
+            .map(|node| {
+                let key = format!("{}\n{}", node.graph.as_deref().unwrap_or(""), node.id);
+                page_name(&key)
+            })
+            .collect();
+        let mut site = Self {
+            nodes,
+            pages,
+            by_id: HashMap::new(),
+            types: BTreeMap::new(),
+            incoming: HashMap::new(),
+        };
+
+        for (index, node) in nodes.iter().enumerate() {
+            site.by_id.entry(node.id.as_str()).or_default().push(index);
+            for type_name in &node.types {
+                site.types
+                    .entry(type_name.as_str())
+                    .or_default()
+                    .push(index);
+            }
+        }
+        for (index, node) in nodes.iter().enumerate() {
+            for (predicate, value) in &node.properties {
+                for target in references(value) {
+                    if let Some(target) = site.resolve(index, target) {
+                        site.incoming
+                            .entry(target)
+                            .or_default()
+                            .push((index, predicate.as_str()));
+                    }
+                }
+            }
+        }
+        site
+    }
+
+    /// Resolves a reference from `source`, preferring a node in the same
+    /// graph.
+    fn resolve(&self, source: usize, target: &str) -> Option<usize> {
+        let candidates = self.by_id.get(target)?;
+        let graph = &self.nodes[source].graph;
+        candidates
+            .iter()
+            .copied()
+            .find(|&candidate| &self.nodes[candidate].graph == graph)
+            .or_else(|| candidates.first().copied())
+    }
+
+    fn index_page(&self) -> String {
+        let mut html = page_start("Dataset");
+        let _ = writeln!(html, "<h1>Dataset</h1>\n<p>{} nodes.</p>", self.nodes.len());
+
+        html.push_str("<h2>Types</h2>\n<ul>\n");
+        for (type_name, members) in &self.types {
+            let _ = writeln!(
+                html,
+                "<li><a href=\"types/{}\">{}</a> ({})</li>",
+                page_name(type_name),
+                escape_html(local_name(type_name)),
+                members.len()
+            );
+        }
+        html.push_str("</ul>\n");
+
+        let untyped: Vec<usize> = (0..self.nodes.len())
+            .filter(|&index| self.nodes[index].types.is_empty())
+            .collect();
+        if !untyped.is_empty() {
+            html.push_str("<h2>Untyped nodes</h2>\n");
+            self.push_node_list(&mut html, &untyped, "nodes/");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn type_page(&self, type_name: &str, members: &[usize]) -> String {
+        let title = local_name(type_name);
+        let mut html = page_start(title);
+        let _ = writeln!(
+            html,
+            "<p><a href=\"../index.html\">Index</a></p>\n<h1>{}</h1>\n<p class=\"iri\">{}</p>",
+            escape_html(title),
+            escape_html(type_name)
+        );
+        self.push_node_list(&mut html, members, "../nodes/");
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn node_page(&self, index: usize, node: &Node) -> String {
+        let label = self.label(index);
+        let mut html = page_start(&label);
+        let _ = writeln!(
+            html,
+            "<p><a href=\"../index.html\">Index</a></p>\n<h1>{}</h1>\n<p class=\"iri\">{}</p>",
+            escape_html(&label),
+            escape_html(&node.id)
+        );
+
+        html.push_str("<table>\n");
+        if !node.types.is_empty() {
+            let types: Vec<String> = node
+                .types
+                .iter()
+                .map(|type_name| {
+                    format!(
+                        "<a href=\"../types/{}\">{}</a>",
+                        page_name(type_name),
+                        escape_html(local_name(type_name))
+                    )
+                })
+                .collect();
+            let _ = writeln!(html, "<tr><th>Type</th><td>{}</td></tr>", types.join(", "));
+        }
+        if let Some(graph) = &node.graph {
+            let _ = writeln!(
+                html,
+                "<tr><th>Graph</th><td class=\"iri\">{}</td></tr>",
+                escape_html(graph)
+            );
+        }
+        for (predicate, value) in &node.properties {
+            let _ = writeln!(
+                html,
+                "<tr><th title=\"{}\">{}</th><td>{}</td></tr>",
+                escape_html(predicate),
+                escape_html(local_name(predicate)),
+                self.render_value(index, value)
+            );
+        }
+        html.push_str("</table>\n");
+
+        if let Some(links) = self.incoming.get(&index) {
+            html.push_str("<h2>Referenced by</h2>\n<ul>\n");
+            for (source, predicate) in links {
+                let _ = writeln!(
+                    html,
+                    "<li><a href=\"{}\">{}</a> <span class=\"iri\">via {}</span></li>",
+                    self.pages[*source],
+                    escape_html(&self.label(*source)),
+                    escape_html(local_name(predicate))
+                );
+            }
+            html.push_str("</ul>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn push_node_list(&self, html: &mut String, members: &[usize], prefix: &str) {
+        let mut entries: Vec<(String, usize)> = members
+            .iter()
+            .map(|&index| (self.label(index), index))
+            .collect();
+        entries.sort();
+
+        html.push_str("<ul>\n");
+        for (label, index) in entries {
+            let _ = writeln!(
+                html,
+                "<li><a href=\"{prefix}{}\">{}</a></li>",
+                self.pages[index],
+                escape_html(&label)
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+
+    fn render_value(&self, source: usize, value: &PropertyValue) -> String {
+        match value {
+            PropertyValue::Scalar(scalar) => render_scalar(scalar),
+            PropertyValue::ObjectRef(target) => self.render_reference(source, target),
+            PropertyValue::Array(ArrayValue::Scalars(items)) => {
+                render_list(items.iter().map(render_scalar))
+            }
+            PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => render_list(
+                targets
+                    .iter()
+                    .map(|target| self.render_reference(source, target)),
+            ),
+            PropertyValue::Array(ArrayValue::Mixed(items)) => {
+                render_list(items.iter().map(|item| match item {
+                    ArrayItem::Scalar(scalar) => render_scalar(scalar),
+                    ArrayItem::ObjectRef(target) => self.render_reference(source, target),
+                }))
+            }
+        }
+    }
+
+    fn render_reference(&self, source: usize, target: &str) -> String {
+        match self.resolve(source, target) {
+            Some(index) => format!(
+                "<a href=\"{}\">{}</a>",
+                self.pages[index],
+                escape_html(&self.label(index))
+            ),
+            None if target.starts_with("http://") || target.starts_with("https://") => {
+                format!("<a href=\"{0}\" class=\"iri\">{0}</a>", escape_html(target))
+            }
+            None => format!("<span class=\"iri\">{}</span>", escape_html(target)),
+        }
+    }
+
+    fn label(&self, index: usize) -> String {
+        let node = &self.nodes[index];
+        LABEL_PREDICATES
+            .iter()
+            .find_map(|predicate| match node.properties.get(*predicate)? {
+                PropertyValue::Scalar(scalar) => text(scalar),
+                PropertyValue::Array(ArrayValue::Scalars(items)) => items.iter().find_map(text),
+                _ => None,
+            })
+            .unwrap_or_else(|| node.id.to_string())
+    }
+}
+
+fn page_start(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n",
+        escape_html(title)
+    )
+}
+
+fn render_scalar(scalar: &ScalarValue) -> String {
+    match scalar {
+        ScalarValue::String(value) => escape_html(value),
+        ScalarValue::Integer(number) => number.to_string(),
+        ScalarValue::Double(number) => number.to_string(),
+        ScalarValue::Boolean(flag) => flag.to_string(),
+        ScalarValue::Null => "<em>null</em>".into(),
+        ScalarValue::Date(_) | ScalarValue::DateTime(_) => scalar
+            .typed_lexical()
+            .map(|(lexical, _)| escape_html(&lexical))
+            .unwrap_or_default(),
+        ScalarValue::TypedLiteral { value, datatype } => format!(
+            "{} <span class=\"iri\">({})</span>",
+            escape_html(value),
+            escape_html(local_name(datatype))
+        ),
+        ScalarValue::LangString { value, language } => format!(
+            "<span lang=\"{0}\">{1}</span> <span class=\"iri\">@{0}</span>",
+            escape_html(language),
+            escape_html(value)
+        ),
+    }
+}
+
+fn render_list(items: impl Iterator<Item = String>) -> String {
+    let mut html = String::from("<ul>");
+    for item in items {
+        let _ = write!(html, "<li>{item}</li>");
+    }
+    html.push_str("</ul>");
+    html
+}
+
+fn references(value: &PropertyValue) -> Vec<&NodeId> {
+    match value {
+        PropertyValue::ObjectRef(target) => vec![target],
+        PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => targets.iter().collect(),
+        PropertyValue::Array(ArrayValue::Mixed(items)) => items
+            .iter()
+            .filter_map(|item| match item {
+                ArrayItem::ObjectRef(target) => Some(target),
+                ArrayItem::Scalar(_) => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn text(scalar: &ScalarValue) -> Option<String> {
+    match scalar {
+        ScalarValue::String(value) | ScalarValue::LangString { value, .. } => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the fragment or last path segment of an IRI for display.
+fn local_name(iri: &str) -> &str {
+    iri.rsplit(['#', '/', ':'])
+        .find(|segment| !segment.is_empty())
+        .unwrap_or(iri)
+}
+
+/// Derives a stable, filesystem-safe page name from an identifier.
+fn page_name(key: &str) -> String {
+    format!(
+        "{}.html",
+        Uuid::new_v5(&Uuid::NAMESPACE_URL, key.as_bytes()).simple()
+    )
+}
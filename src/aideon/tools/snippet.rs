@@ -0,0 +1,80 @@
+//! Embeddable `<script type="application/ld+json">` snippets, one per node,
+//! for structured-data markup on web pages.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::io::jsonld::{self, JsonLdVersion};
+use crate::aideon::tools::model::{Node, NodeId};
+use crate::aideon::tools::sync::SyncOptions;
+
+/// Compacts each node on its own and wraps it in a script element.
+///
+/// References to other nodes stay as `{"@id": ...}` objects and graph names
+/// are dropped. When `context` is given the snippet keeps it verbatim as its
+/// `@context`, so a remote reference such as `"https://schema.org"` is
+/// emitted as written while being resolved through `options` for
+/// compaction.
+pub fn node_snippets(
+    nodes: &[Node],
+    context: Option<&Value>,
+    options: &SyncOptions,
+) -> Result<Vec<(NodeId, String)>> {
+    let compaction_context = match (context, &options.remote_contexts) {
+        (Some(context), Some(loader)) => Some(loader.resolve_context(context)?),
+        (context, _) => context.cloned(),
+    };
+
+    nodes
+        .iter()
+        .map(|node| {
+            let mut standalone = node.clone();
+            standalone.set_graph(None);
+            let mut json = jsonld::nodes_to_jsonld_versioned(
+                std::slice::from_ref(&standalone),
+                compaction_context.clone(),
+                options.jsonld_version,
+            )?;
+            if let (Some(context), Value::Object(map)) = (context, &mut json) {
+                let mut context = context.clone();
+                if options.jsonld_version == JsonLdVersion::V1_0 {
+                    jsonld::strip_version(&mut context);
+                }
+                map.insert("@context".into(), context);
+            }
+            Ok((node.id.clone(), script_element(&json)?))
+        })
+        .collect()
+}
+
+/// Writes every node snippet to a single HTML fragment at `path`, each
+/// preceded by a comment naming its node.
+pub fn write_snippets(
+    path: &Path,
+    nodes: &[Node],
+    context: Option<&Value>,
+    options: &SyncOptions,
+) -> Result<()> {
+    let mut html = String::new();
+    for (id, snippet) in node_snippets(nodes, context, options)? {
+        // With every `-` escaped the id cannot end the comment early or
+        // leave it malformed, whatever it ends with.
+        let _ = writeln!(html, "<!-- {} -->\n{snippet}", id.replace('-', "&#45;"));
+    }
+    atomic::write_atomically(path, |partial| Ok(fs::write(partial, html)?))
+}
+
+fn script_element(json: &Value) -> Result<String> {
+    let body = serde_json::to_string_pretty(json)?;
+    // `<` only occurs inside JSON strings, where the escape is equivalent and
+    // keeps `</script>` or `<!--` in a value from ending the element.
+    let body = body.replace('<', "\\u003c");
+    Ok(format!(
+        "<script type=\"application/ld+json\">\n{body}\n</script>"
+    ))
+}
@@ -1,104 +1,1706 @@
-use std::fs;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
 use std::path::Path;
 
-use serde_json::Value;
+use serde::Serialize;
+use serde_json::{Map, Value};
 
-use crate::aideon::tools::error::Result;
-use crate::aideon::tools::flatten::build_workbook;
+use crate::aideon::tools::blank_nodes::{BlankNodeLabels, relabel_blank_nodes};
+use crate::aideon::tools::equivalence::{is_blank, relabel};
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::filter::{IriPattern, NodeFilter};
+use crate::aideon::tools::flatten::{
+    Cardinality, CellEncoding, ColumnGroup, LayoutBuilder, SheetNaming, WorkbookData,
+    WorkbookOptions, build_workbook_with_options, check_labels, without_ignored,
+};
+use crate::aideon::tools::io::atomic;
+use crate::aideon::tools::io::coercion::ScalarCoercions;
+use crate::aideon::tools::io::csv_dialect::CsvDialect;
+use crate::aideon::tools::io::csv_read;
+use crate::aideon::tools::io::csv_write;
 use crate::aideon::tools::io::excel_read;
-use crate::aideon::tools::io::excel_write;
-use crate::aideon::tools::io::jsonld;
+use crate::aideon::tools::io::excel_update::{self, WorkbookUpdate};
+use crate::aideon::tools::io::excel_write::{self, StreamingWorkbookWriter};
+use crate::aideon::tools::io::jsonld::{
+    self, ContextCoverage, IdStrategy, JsonLdVersion, UnmappedIri,
+};
+use crate::aideon::tools::io::nodes_json;
 use crate::aideon::tools::io::rdf::{self, RdfFormat};
-use crate::aideon::tools::model::Node;
-use tracing::{debug, info, instrument};
+use crate::aideon::tools::io::remote_context::RemoteContextLoader;
+use crate::aideon::tools::io::sql::{self, SqlSource};
+use crate::aideon::tools::io::text_encoding::TextEncoding;
+use crate::aideon::tools::model::{
+    ArrayItem, ArrayValue, Iri, Node, NodeId, PropertyValue, ScalarValue,
+};
+use crate::aideon::tools::query::{self, NodeCondition};
+use crate::aideon::tools::report::{DatasetSummary, NONE_LABEL};
+use crate::aideon::tools::same_as::{self, SameAs};
+use crate::aideon::tools::script::NodeScript;
+use crate::aideon::tools::shacl::{ShaclReport, ShapesGraph};
+use crate::aideon::tools::telemetry::{self, Counter};
+use crate::aideon::tools::validate::ValidationReport;
+use tracing::field::Empty;
+use tracing::{Level, Span, debug, info, instrument, warn};
+use uuid::Uuid;
+
+/// Options shared by the synchronisation routines.
+#[derive(Debug, Clone, Default)]
+pub struct SyncOptions {
+    /// Loader used to resolve remote `@context` references in JSON-LD inputs
+    /// and output contexts. Remote references are rejected when unset.
+    pub remote_contexts: Option<RemoteContextLoader>,
+    /// Number of nodes processed at a time by `rdf_to_excel` and
+    /// `rdf_to_jsonld`. When set, the RDF input is streamed instead of being
+    /// loaded whole, which keeps memory bounded for input sorted by subject.
+    pub chunk_size: Option<usize>,
+    /// JSON-LD version that serialised documents are restricted to.
+    pub jsonld_version: JsonLdVersion,
+    /// JSON-LD contexts compacting the nodes of each named graph in written
+    /// JSON-LD documents instead of the document context, see
+    /// [`jsonld::nodes_to_jsonld_with_graph_contexts`]. Framed documents use
+    /// the document context only, and RDF is not streamed into JSON-LD when
+    /// any are set.
+    pub graph_contexts: BTreeMap<Iri, Value>,
+    /// Encoding of literal cells in written workbooks and CSV files. Readers
+    /// accept either encoding.
+    pub cell_encoding: CellEncoding,
+    /// JSON-LD frame that written JSON-LD documents are shaped by, see
+    /// [`jsonld::frame_nodes`]. Framing needs the whole graph, so it takes
+    /// precedence over `chunk_size` in `rdf_to_jsonld`.
+    pub frame: Option<Value>,
+    /// Readings of literal cell text applied when workbooks and CSV files
+    /// are read.
+    pub coercions: ScalarCoercions,
+    /// Predicates written as matrix sheets in workbooks and CSV files, see
+    /// [`WorkbookOptions::matrix_predicates`].
+    pub matrix_predicates: BTreeSet<Iri>,
+    /// Predicates whose columns lead the sheet of each type in workbooks and
+    /// CSV files, see [`WorkbookOptions::column_order`].
+    pub column_order: BTreeMap<Iri, Vec<Iri>>,
+    /// Labelled bands of columns leading the sheet of each type in workbooks
+    /// and CSV files, see [`WorkbookOptions::column_groups`].
+    pub column_groups: BTreeMap<Iri, Vec<ColumnGroup>>,
+    /// Workbook sheets and CSV tables left out when they are read, see
+    /// [`excel_read::read_nodes_skipping`].
+    pub skip_sheets: BTreeSet<String>,
+    /// Predicate holding the id of the nodes of each type, applied to every
+    /// input read, see [`assign_ids`]. Renaming needs the whole graph, so
+    /// `chunk_size` is ignored when any are set.
+    pub id_properties: BTreeMap<Iri, Iri>,
+    /// Predicates whose values identify the nodes of each type that have a
+    /// surrogate id, such as the rows of a workbook, applied to every input
+    /// read after `id_properties`, see [`natural_key_ids`]. Renaming needs
+    /// the whole graph, so `chunk_size` is ignored when any are set.
+    pub natural_keys: BTreeMap<Iri, Vec<Iri>>,
+    /// Prefixes expanding compact ids found by `id_properties`, declared in
+    /// the context of written JSON-LD documents, and abbreviating IRIs in
+    /// written RDF, see [`Self::prefix_registry`].
+    pub prefixes: BTreeMap<String, String>,
+    /// Abbreviates the headers of written workbooks and CSV files with
+    /// [`Self::prefix_registry`], see [`WorkbookOptions::prefixes`].
+    pub compact_headers: bool,
+    /// Context terms heading the columns of written workbooks and CSV files,
+    /// see [`WorkbookOptions::header_terms`].
+    pub header_terms: BTreeMap<Iri, String>,
+    /// JSON-LD context embedded in written workbooks and CSV files as their
+    /// `Context` sheet, together with `prefixes`. Reading a workbook or CSV
+    /// directory into JSON-LD without a context uses the embedded one.
+    pub embedded_context: Option<Value>,
+    /// Most nodes an input may hold. Reading more stops the conversion with
+    /// [`ToolError::LimitExceeded`] before any output is written; streamed
+    /// inputs stop at the first chunk past the limit.
+    pub max_nodes: Option<usize>,
+    /// Most cells written workbooks and CSV files may hold, see
+    /// [`WorkbookOptions::max_cells`].
+    pub max_cells: Option<usize>,
+    /// Delimiter, quoting, and encoding of CSV files read and written.
+    pub csv_dialect: CsvDialect,
+    /// Character encoding of JSON-LD and RDF inputs, transcoded to UTF-8
+    /// while they are read.
+    pub input_encoding: TextEncoding,
+    /// Serialisation of RDF inputs. Inputs are detected from their
+    /// extension when unset.
+    pub rdf_input_format: Option<RdfFormat>,
+    /// Connection URL of SQL sources, in place of the one their file names,
+    /// see [`SqlSource::with_url`].
+    pub database_url: Option<String>,
+    /// Labels naming the sheets and columns of written workbooks and CSV
+    /// files, see [`WorkbookOptions::labels`].
+    pub labels: BTreeMap<Iri, String>,
+    /// Cardinality of predicates in written workbooks and CSV files, see
+    /// [`WorkbookOptions::cardinalities`].
+    pub cardinalities: BTreeMap<Iri, Cardinality>,
+    /// Naming of the sheets in written workbooks and CSV files that
+    /// `sheet_names` leaves unnamed, see [`WorkbookOptions::sheet_naming`].
+    pub sheet_naming: SheetNaming,
+    /// Names of the sheets in written workbooks and CSV files, see
+    /// [`WorkbookOptions::sheet_names`].
+    pub sheet_names: BTreeMap<Iri, String>,
+    /// Types written on the sheet of another type in workbooks and CSV
+    /// files, see [`WorkbookOptions::consolidated_types`].
+    pub consolidated_types: BTreeMap<Iri, Iri>,
+    /// Predicates whose values are written to RDF as ordered collections,
+    /// see [`rdf::build_lists`]. Collections are only collapsed when the
+    /// whole graph is read, so `chunk_size` is ignored when any are set.
+    pub rdf_lists: BTreeSet<Iri>,
+    /// Reads the reified statements of RDF inputs as relationship nodes,
+    /// see [`rdf::fold_reifications`]. Folding needs the whole graph, so
+    /// `chunk_size` is ignored when set.
+    pub fold_reifications: bool,
+    /// Graphs, types, and predicates kept from every input read, before the
+    /// node limit is checked and the nodes are flattened. Streamed inputs
+    /// are filtered chunk by chunk.
+    pub node_filter: NodeFilter,
+    /// SPARQL patterns keeping or dropping the nodes of every input read,
+    /// after `node_filter`, see [`query::apply_conditions`]. Patterns may
+    /// join nodes across the whole input, so `chunk_size` is ignored when
+    /// any are given.
+    pub node_conditions: Vec<NodeCondition>,
+    /// Predicates left out of written workbooks and CSV files, such as
+    /// volatile audit metadata, and carried over from the existing output
+    /// when they are read back into JSON-LD or RDF, see [`keep_ignored`].
+    pub ignored_predicates: Vec<IriPattern>,
+    /// Fails JSON-LD output with [`ToolError::UnmappedIris`] when its
+    /// context leaves predicates or types unmapped. Each is logged as a
+    /// warning either way.
+    pub strict_context: bool,
+    /// Fails reading workbooks and CSV directories with
+    /// [`ToolError::ConflictingCell`] when two rows describing the same node
+    /// give one of its properties different values, see
+    /// [`excel_read::read_nodes_strict`]. The later value is kept, with a
+    /// warning, otherwise.
+    pub strict_cells: bool,
+    /// Reads only a sample of workbooks and CSV directories, the first rows
+    /// of each sheet, see [`excel_read::read_nodes_preview`]. `strict_cells`
+    /// is ignored for samples.
+    pub preview_rows: Option<usize>,
+    /// Script run over every node read, after `node_conditions`, see
+    /// [`NodeScript`]. Streamed inputs are transformed chunk by chunk.
+    pub script: Option<NodeScript>,
+    /// Labels given to the blank nodes of every input read, after `script`
+    /// and `id_properties`, see [`relabel_blank_nodes`]. Canonical and
+    /// skolemized labels derive from the whole graph, so `chunk_size` is
+    /// ignored for them.
+    pub blank_nodes: BlankNodeLabels,
+    /// How JSON-LD node objects without an `@id` are given one, see
+    /// [`IdStrategy`].
+    pub id_strategy: IdStrategy,
+    /// What becomes of the nodes of every input read that `owl:sameAs`
+    /// links, after `id_properties`, see [`SameAs`]. Nodes are left as they
+    /// are when unset. Clusters need the whole graph, so `chunk_size` is
+    /// ignored when set.
+    pub same_as: Option<SameAs>,
+}
+
+impl SyncOptions {
+    /// Layout choices of the workbooks and CSV files written with these
+    /// options.
+    pub fn workbook_options(&self) -> WorkbookOptions {
+        WorkbookOptions {
+            encoding: self.cell_encoding,
+            matrix_predicates: self.matrix_predicates.clone(),
+            column_order: self.column_order.clone(),
+            column_groups: self.column_groups.clone(),
+            context: self
+                .embedded_context
+                .clone()
+                .map(|context| declare_prefixes(context, &self.prefixes)),
+            max_cells: self.max_cells,
+            prefixes: if self.compact_headers {
+                self.prefix_registry(self.embedded_context.as_ref())
+            } else {
+                BTreeMap::new()
+            },
+            header_terms: self.header_terms.clone(),
+            labels: self.labels.clone(),
+            cardinalities: self.cardinalities.clone(),
+            sheet_naming: self.sheet_naming,
+            sheet_names: self.sheet_names.clone(),
+            consolidated_types: self.consolidated_types.clone(),
+            ignored_predicates: self.ignored_predicates.clone(),
+            colocate_same_as: self.same_as == Some(SameAs::Keep),
+        }
+    }
+
+    /// Prefixes abbreviating IRIs in written Turtle and TriG files and
+    /// compact headers: the [`rdf::WELL_KNOWN_PREFIXES`], then those defined
+    /// by `context`, then [`Self::prefixes`], each overriding the former.
+    pub fn prefix_registry(&self, context: Option<&Value>) -> BTreeMap<String, String> {
+        let mut registry: BTreeMap<String, String> = rdf::WELL_KNOWN_PREFIXES
+            .iter()
+            .map(|(prefix, namespace)| (prefix.to_string(), namespace.to_string()))
+            .collect();
+        registry.extend(context.map(jsonld::context_prefixes).unwrap_or_default());
+        registry.extend(self.prefixes.clone());
+        registry
+    }
+
+    /// Fails with [`ToolError::LimitExceeded`] when `found` nodes are more
+    /// than `max_nodes` allows.
+    fn check_nodes(&self, found: usize) -> Result<()> {
+        match self.max_nodes {
+            Some(limit) if found > limit => Err(ToolError::LimitExceeded {
+                what: "nodes",
+                found,
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the chunk size to stream with, if streaming is possible.
+    fn streaming_chunk_size(&self) -> Option<usize> {
+        self.chunk_size.filter(|_| {
+            self.id_properties.is_empty()
+                && self.node_conditions.is_empty()
+                && self.natural_keys.is_empty()
+                && self.same_as.is_none()
+                && self.rdf_lists.is_empty()
+                && !self.fold_reifications
+                && matches!(
+                    self.blank_nodes,
+                    BlankNodeLabels::Parsed | BlankNodeLabels::Deskolemized
+                )
+        })
+    }
+}
 
 /// Synchronises a JSON-LD document into an Excel workbook.
+pub fn jsonld_to_excel(input: &Path, output: &Path) -> Result<()> {
+    jsonld_to_excel_with_options(input, output, &SyncOptions::default())
+}
+
+/// Runs [`jsonld_to_excel`] with `options`.
 #[instrument(
+    name = "jsonld_to_excel",
     level = "info",
     skip_all,
-    fields(input = %input.display(), output = %output.display())
+    fields(
+        input = %input.display(),
+        output = %output.display(),
+        node_count = Empty,
+        cell_count = Empty
+    )
 )]
-pub fn jsonld_to_excel(input: &Path, output: &Path) -> Result<()> {
-    let source = fs::read_to_string(input)?;
-    let json: Value = serde_json::from_str(&source)?;
-    let nodes = jsonld::parse_jsonld_document(&json)?;
+pub fn jsonld_to_excel_with_options(
+    input: &Path,
+    output: &Path,
+    options: &SyncOptions,
+) -> Result<()> {
+    let nodes = read_jsonld(input, options)?;
     info!(node_count = nodes.len(), "parsed nodes from JSON-LD source");
-    let workbook = build_workbook(&nodes)?;
+    trace_nodes(&nodes);
+    let workbook = build_workbook_with_options(&nodes, &options.workbook_options())?;
     debug!(sheet_count = workbook.tables.len(), "workbook constructed");
+    trace_cells(&workbook);
     excel_write::write_workbook(output, &workbook)
 }
 
 /// Synchronises an Excel workbook back into JSON-LD.
+pub fn excel_to_jsonld(input: &Path, output: &Path, context: Option<Value>) -> Result<()> {
+    excel_to_jsonld_with_options(input, output, context, &SyncOptions::default())
+}
+
+/// Runs [`excel_to_jsonld`] with `options`.
 #[instrument(
+    name = "excel_to_jsonld",
     level = "info",
     skip_all,
-    fields(input = %input.display(), output = %output.display())
+    fields(
+        input = %input.display(),
+        output = %output.display(),
+        node_count = Empty
+    )
 )]
-pub fn excel_to_jsonld(input: &Path, output: &Path, context: Option<Value>) -> Result<()> {
-    let nodes = excel_read::read_nodes(input)?;
+pub fn excel_to_jsonld_with_options(
+    input: &Path,
+    output: &Path,
+    context: Option<Value>,
+    options: &SyncOptions,
+) -> Result<()> {
+    let nodes = read_excel(input, options)?;
     info!(node_count = nodes.len(), "read nodes from workbook");
-    let json = jsonld::nodes_to_jsonld(&nodes, context)?;
-    let json_string = serde_json::to_string_pretty(&json)?;
-    fs::write(output, json_string)?;
-    Ok(())
+    trace_nodes(&nodes);
+    let nodes = keep_ignored_from(nodes, output, options, read_jsonld)?;
+    let context = context_or_embedded(context, options, || excel_read::read_context(input))?;
+    write_jsonld(&nodes, output, context, options)
 }
 
 /// Loads an RDF graph and materialises it as an Excel workbook.
+pub fn rdf_to_excel(input: &Path, output: &Path) -> Result<()> {
+    rdf_to_excel_with_options(input, output, &SyncOptions::default())
+}
+
+/// Runs [`rdf_to_excel`] with `options`.
 #[instrument(
+    name = "rdf_to_excel",
     level = "info",
     skip_all,
-    fields(input = %input.display(), output = %output.display())
+    fields(
+        input = %input.display(),
+        output = %output.display(),
+        node_count = Empty,
+        cell_count = Empty
+    )
 )]
-pub fn rdf_to_excel(input: &Path, output: &Path) -> Result<()> {
-    let nodes = rdf::read_rdf(input, None)?;
+pub fn rdf_to_excel_with_options(input: &Path, output: &Path, options: &SyncOptions) -> Result<()> {
+    if let Some(chunk_size) = options.streaming_chunk_size() {
+        let (node_count, cell_count) = rdf_to_excel_chunked(input, output, chunk_size, options)?;
+        let span = Span::current();
+        span.record("node_count", node_count);
+        span.record("cell_count", cell_count);
+        telemetry::count(Counter::NodesRead, node_count);
+        telemetry::count(Counter::CellsWritten, cell_count);
+        debug!(cell_count, "cells written");
+        return Ok(());
+    }
+    let nodes = read_rdf(input, None, options)?;
     info!(node_count = nodes.len(), "parsed nodes from RDF source");
-    let workbook = build_workbook(&nodes)?;
+    trace_nodes(&nodes);
+    let workbook = build_workbook_with_options(&nodes, &options.workbook_options())?;
     debug!(sheet_count = workbook.tables.len(), "workbook constructed");
+    trace_cells(&workbook);
     excel_write::write_workbook(output, &workbook)
 }
 
-/// Persists the current node set into an RDF graph.
+/// Persists the current node set into an RDF graph, abbreviating IRIs with
+/// the prefix registry and the prefixes of the workbook's `Context` sheet.
+pub fn excel_to_rdf(input: &Path, output: &Path, format: RdfFormat) -> Result<()> {
+    excel_to_rdf_with_options(input, output, format, &SyncOptions::default())
+}
+
+/// Runs [`excel_to_rdf`] with `options`.
 #[instrument(
+    name = "excel_to_rdf",
     level = "info",
     skip_all,
-    fields(input = %input.display(), output = %output.display(), ?format)
+    fields(
+        input = %input.display(),
+        output = %output.display(), ?format,
+        node_count = Empty,
+        triple_count = Empty
+    )
 )]
-pub fn excel_to_rdf(input: &Path, output: &Path, format: RdfFormat) -> Result<()> {
-    let nodes = excel_read::read_nodes(input)?;
+pub fn excel_to_rdf_with_options(
+    input: &Path,
+    output: &Path,
+    format: RdfFormat,
+    options: &SyncOptions,
+) -> Result<()> {
+    let nodes = read_excel(input, options)?;
     info!(node_count = nodes.len(), "read nodes from workbook");
-    rdf::write_rdf(output, &nodes, format)
+    trace_nodes(&nodes);
+    let nodes = keep_ignored_from(nodes, output, options, |path, plain| {
+        read_rdf(path, Some(format), plain)
+    })?;
+    let context = excel_read::read_context(input)?;
+    write_rdf(&nodes, output, format, context.as_ref(), options)
 }
 
 /// Converts a JSON-LD document directly into RDF.
+pub fn jsonld_to_rdf(input: &Path, output: &Path, format: RdfFormat) -> Result<()> {
+    jsonld_to_rdf_with_options(input, output, format, &SyncOptions::default())
+}
+
+/// Runs [`jsonld_to_rdf`] with `options`.
 #[instrument(
+    name = "jsonld_to_rdf",
     level = "info",
     skip_all,
-    fields(input = %input.display(), output = %output.display(), ?format)
+    fields(
+        input = %input.display(),
+        output = %output.display(), ?format,
+        node_count = Empty,
+        triple_count = Empty
+    )
 )]
-pub fn jsonld_to_rdf(input: &Path, output: &Path, format: RdfFormat) -> Result<()> {
-    let source = fs::read_to_string(input)?;
-    let json: Value = serde_json::from_str(&source)?;
-    let nodes = jsonld::parse_jsonld_document(&json)?;
+pub fn jsonld_to_rdf_with_options(
+    input: &Path,
+    output: &Path,
+    format: RdfFormat,
+    options: &SyncOptions,
+) -> Result<()> {
+    let nodes = read_jsonld(input, options)?;
     info!(node_count = nodes.len(), "parsed nodes from JSON-LD source");
-    rdf::write_rdf(output, &nodes, format)
+    trace_nodes(&nodes);
+    write_rdf(&nodes, output, format, None, options)
 }
 
 /// Converts an RDF graph into JSON-LD.
+pub fn rdf_to_jsonld(input: &Path, output: &Path, context: Option<Value>) -> Result<()> {
+    rdf_to_jsonld_with_options(input, output, context, &SyncOptions::default())
+}
+
+/// Runs [`rdf_to_jsonld`] with `options`.
 #[instrument(
+    name = "rdf_to_jsonld",
     level = "info",
     skip_all,
-    fields(input = %input.display(), output = %output.display())
+    fields(
+        input = %input.display(),
+        output = %output.display(),
+        node_count = Empty
+    )
 )]
-pub fn rdf_to_jsonld(input: &Path, output: &Path, context: Option<Value>) -> Result<()> {
-    let nodes = rdf::read_rdf(input, None)?;
+pub fn rdf_to_jsonld_with_options(
+    input: &Path,
+    output: &Path,
+    context: Option<Value>,
+    options: &SyncOptions,
+) -> Result<()> {
+    if let (Some(chunk_size), None, true) = (
+        options.streaming_chunk_size(),
+        &options.frame,
+        options.graph_contexts.is_empty(),
+    ) {
+        let context = output_context(context, options)?;
+        let coverage = context.as_ref().map(ContextCoverage::new).transpose()?;
+        let mut reported = BTreeSet::new();
+        let mut seen = 0;
+        let chunks = stream_rdf(input, chunk_size, options)?.map(|chunk| {
+            let chunk = chunk?;
+            seen += chunk.len();
+            options.check_nodes(seen)?;
+            if let Some(coverage) = &coverage {
+                let mut unmapped = coverage.unmapped(&chunk);
+                unmapped.retain(|iri| reported.insert(iri.iri.clone()));
+                report_unmapped(&unmapped, options)?;
+            }
+            Ok(chunk)
+        });
+        let node_count = atomic::write_atomically(output, |partial| {
+            let writer = BufWriter::new(File::create(partial)?);
+            jsonld::write_jsonld_stream(writer, chunks, context, options.jsonld_version)
+        })?;
+        info!(node_count, "streamed nodes from RDF source");
+        Span::current().record("node_count", node_count);
+        telemetry::count(Counter::NodesRead, node_count);
+        return Ok(());
+    }
+    let nodes = read_rdf(input, None, options)?;
     info!(node_count = nodes.len(), "parsed nodes from RDF source");
-    excel_to_jsonld_internal(&nodes, output, context)
+    trace_nodes(&nodes);
+    write_jsonld(&nodes, output, context, options)
+}
+
+/// Flattens a JSON-LD document into a directory of CSV files.
+#[instrument(
+    level = "info",
+    skip_all,
+    fields(
+        input = %input.display(),
+        output = %output.display(),
+        node_count = Empty,
+        cell_count = Empty
+    )
+)]
+pub fn jsonld_to_csv(input: &Path, output: &Path, options: &SyncOptions) -> Result<()> {
+    let nodes = read_jsonld(input, options)?;
+    info!(node_count = nodes.len(), "parsed nodes from JSON-LD source");
+    trace_nodes(&nodes);
+    let workbook = build_workbook_with_options(&nodes, &options.workbook_options())?;
+    debug!(table_count = workbook.tables.len(), "tables constructed");
+    trace_cells(&workbook);
+    csv_write::write_workbook_in_dialect(output, &workbook, &options.csv_dialect)
+}
+
+/// Rebuilds JSON-LD from a directory of CSV files.
+#[instrument(
+    level = "info",
+    skip_all,
+    fields(
+        input = %input.display(),
+        output = %output.display(),
+        node_count = Empty
+    )
+)]
+pub fn csv_to_jsonld(
+    input: &Path,
+    output: &Path,
+    context: Option<Value>,
+    options: &SyncOptions,
+) -> Result<()> {
+    let nodes = read_csv(input, options)?;
+    info!(node_count = nodes.len(), "read nodes from CSV directory");
+    trace_nodes(&nodes);
+    let nodes = keep_ignored_from(nodes, output, options, read_jsonld)?;
+    let context = context_or_embedded(context, options, || {
+        csv_read::read_context_in_dialect(input, &options.csv_dialect)
+    })?;
+    write_jsonld(&nodes, output, context, options)
+}
+
+/// Flattens an RDF graph into a directory of CSV files.
+#[instrument(
+    level = "info",
+    skip_all,
+    fields(
+        input = %input.display(),
+        output = %output.display(),
+        node_count = Empty,
+        cell_count = Empty
+    )
+)]
+pub fn rdf_to_csv(input: &Path, output: &Path, options: &SyncOptions) -> Result<()> {
+    let nodes = read_rdf(input, None, options)?;
+    info!(node_count = nodes.len(), "parsed nodes from RDF source");
+    trace_nodes(&nodes);
+    let workbook = build_workbook_with_options(&nodes, &options.workbook_options())?;
+    debug!(table_count = workbook.tables.len(), "tables constructed");
+    trace_cells(&workbook);
+    csv_write::write_workbook_in_dialect(output, &workbook, &options.csv_dialect)
+}
+
+/// Converts a directory of CSV files into an RDF graph, abbreviating IRIs
+/// like [`excel_to_rdf`].
+#[instrument(
+    level = "info",
+    skip_all,
+    fields(
+        input = %input.display(),
+        output = %output.display(), ?format,
+        node_count = Empty,
+        triple_count = Empty
+    )
+)]
+pub fn csv_to_rdf(
+    input: &Path,
+    output: &Path,
+    format: RdfFormat,
+    options: &SyncOptions,
+) -> Result<()> {
+    let nodes = read_csv(input, options)?;
+    info!(node_count = nodes.len(), "read nodes from CSV directory");
+    trace_nodes(&nodes);
+    let nodes = keep_ignored_from(nodes, output, options, |path, plain| {
+        read_rdf(path, Some(format), plain)
+    })?;
+    let context = csv_read::read_context_in_dialect(input, &options.csv_dialect)?;
+    write_rdf(&nodes, output, format, context.as_ref(), options)
+}
+
+/// Exports the tables of an Excel workbook as a directory of CSV files.
+#[instrument(
+    level = "info",
+    skip_all,
+    fields(
+        input = %input.display(),
+        output = %output.display(),
+        node_count = Empty,
+        cell_count = Empty
+    )
+)]
+pub fn excel_to_csv(input: &Path, output: &Path, options: &SyncOptions) -> Result<()> {
+    let nodes = read_excel(input, options)?;
+    info!(node_count = nodes.len(), "read nodes from workbook");
+    trace_nodes(&nodes);
+    let workbook = build_workbook_with_options(&nodes, &options.workbook_options())?;
+    trace_cells(&workbook);
+    csv_write::write_workbook_in_dialect(output, &workbook, &options.csv_dialect)
+}
+
+/// Assembles a directory of CSV files into an Excel workbook.
+#[instrument(
+    level = "info",
+    skip_all,
+    fields(
+        input = %input.display(),
+        output = %output.display(),
+        node_count = Empty,
+        cell_count = Empty
+    )
+)]
+pub fn csv_to_excel(input: &Path, output: &Path, options: &SyncOptions) -> Result<()> {
+    let nodes = read_csv(input, options)?;
+    info!(node_count = nodes.len(), "read nodes from CSV directory");
+    trace_nodes(&nodes);
+    let workbook = build_workbook_with_options(&nodes, &options.workbook_options())?;
+    trace_cells(&workbook);
+    excel_write::write_workbook(output, &workbook)
+}
+
+/// Writes `nodes` into the workbook at `output`, updating it in place when it
+/// exists so that formatting, comments, extra columns, and user sheets
+/// survive; see [`excel_update::update_workbook`]. A missing workbook is
+/// written from scratch.
+#[instrument(
+    level = "info",
+    skip_all,
+    fields(output = %output.display(), node_count = nodes.len(), cell_count = Empty)
+)]
+pub fn update_excel(
+    nodes: &[Node],
+    output: &Path,
+    options: &SyncOptions,
+) -> Result<WorkbookUpdate> {
+    let workbook = build_workbook_with_options(nodes, &options.workbook_options())?;
+    trace_cells(&workbook);
+    if !output.exists() {
+        excel_write::write_workbook(output, &workbook)?;
+        return Ok(WorkbookUpdate {
+            inserted_rows: workbook.tables.iter().map(|table| table.rows.len()).sum(),
+            added_sheets: workbook
+                .tables
+                .iter()
+                .map(|table| table.sheet_name.clone())
+                .collect(),
+            ..WorkbookUpdate::default()
+        });
+    }
+    let update = excel_update::update_workbook(output, &workbook)?;
+    info!(
+        inserted = update.inserted_rows,
+        updated = update.updated_rows,
+        deleted = update.deleted_rows,
+        added_sheets = update.added_sheets.len(),
+        "updated workbook rows"
+    );
+    Ok(update)
+}
+
+/// How [`merge`] resolves a predicate that has different values on both
+/// sides.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep the value from the left dataset.
+    #[default]
+    PreferLeft,
+    /// Keep the value from the right dataset.
+    PreferRight,
+    /// Combine both values into one array without duplicates, left values
+    /// first. Literals and references combine into a mixed array.
+    UnionArrays,
+    /// Fail with [`ToolError::MergeConflict`].
+    ErrorOnConflict,
+}
+
+impl MergeStrategy {
+    /// Returns the value that `conflict` is resolved to, failing under
+    /// [`MergeStrategy::ErrorOnConflict`].
+    pub fn resolve(self, conflict: &MergeConflict) -> Result<PropertyValue> {
+        match self {
+            MergeStrategy::PreferLeft => Ok(conflict.left.clone()),
+            MergeStrategy::PreferRight => Ok(conflict.right.clone()),
+            MergeStrategy::UnionArrays => Ok(union_values(&conflict.left, &conflict.right)),
+            MergeStrategy::ErrorOnConflict => Err(conflict.error("values differ")),
+        }
+    }
+}
+
+/// A predicate of a node whose values differ between the two sides of a
+/// [`merge`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// Identifier of the node.
+    pub id: NodeId,
+    /// Graph the node belongs to.
+    pub graph: Option<Iri>,
+    /// Predicate whose values differ.
+    pub predicate: Iri,
+    /// Value of the left dataset.
+    pub left: PropertyValue,
+    /// Value of the right dataset.
+    pub right: PropertyValue,
+}
+
+impl MergeConflict {
+    /// The [`ToolError::MergeConflict`] for the conflict, giving `reason`.
+    pub fn error(&self, reason: &str) -> ToolError {
+        ToolError::MergeConflict {
+            id: self.id.to_string(),
+            predicate: self.predicate.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Combines two node sets keyed by `(graph, id)`.
+///
+/// Nodes present on one side are copied as they are. For nodes present on
+/// both sides the types are united, predicates present on one side are kept,
+/// and predicates with different values are resolved with `strategy`. The
+/// result is ordered by graph and id.
+#[instrument(level = "info", skip_all, fields(left = left.len(), right = right.len(), ?strategy))]
+pub fn merge(left: &[Node], right: &[Node], strategy: MergeStrategy) -> Result<Vec<Node>> {
+    merge_with(left, right, |conflict| strategy.resolve(conflict))
+}
+
+/// Combines two node sets like [`merge`], taking the value of every
+/// predicate whose values differ from `resolve`, in the order of the result.
+pub fn merge_with(
+    left: &[Node],
+    right: &[Node],
+    mut resolve: impl FnMut(&MergeConflict) -> Result<PropertyValue>,
+) -> Result<Vec<Node>> {
+    let mut merged: BTreeMap<(Option<Iri>, NodeId), Node> = left
+        .iter()
+        .map(|node| ((node.graph.clone(), node.id.clone()), node.clone()))
+        .collect();
+    let mut conflicts = Vec::new();
+
+    for node in right {
+        let key = (node.graph.clone(), node.id.clone());
+        let Some(target) = merged.get_mut(&key) else {
+            merged.insert(key, node.clone());
+            continue;
+        };
+        target.types.extend(node.types.iter().cloned());
+        for (predicate, value) in &node.properties {
+            let Some(existing) = target.properties.get(predicate) else {
+                target.properties.insert(predicate.clone(), value.clone());
+                continue;
+            };
+            if existing == value {
+                continue;
+            }
+            conflicts.push(MergeConflict {
+                id: node.id.clone(),
+                graph: node.graph.clone(),
+                predicate: predicate.clone(),
+                left: existing.clone(),
+                right: value.clone(),
+            });
+        }
+    }
+
+    // Conflicts are resolved once the nodes are merged, so a prompt meets
+    // them in the order of the output rather than of the right input.
+    conflicts.sort_by(|a, b| (&a.graph, &a.id, &a.predicate).cmp(&(&b.graph, &b.id, &b.predicate)));
+    for conflict in &conflicts {
+        let value = resolve(conflict)?;
+        let key = (conflict.graph.clone(), conflict.id.clone());
+        if let Some(node) = merged.get_mut(&key) {
+            node.properties.insert(conflict.predicate.clone(), value);
+        }
+    }
+
+    info!(
+        node_count = merged.len(),
+        conflicts = conflicts.len(),
+        "merged node sets"
+    );
+    Ok(merged.into_values().collect())
+}
+
+/// What [`deduplicate`] matches nodes on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DedupeKey {
+    /// The types of the node, all of them.
+    Type,
+    /// The values of a predicate.
+    Predicate(Iri),
+}
+
+impl From<&str> for DedupeKey {
+    /// Reads `type` or `@type` as [`DedupeKey::Type`] and anything else as
+    /// a predicate IRI.
+    fn from(key: &str) -> Self {
+        match key {
+            "type" | "@type" => DedupeKey::Type,
+            predicate => DedupeKey::Predicate(predicate.into()),
+        }
+    }
+}
+
+/// Nodes of one graph that [`deduplicate`] merged into one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateCluster {
+    /// Graph the nodes belong to.
+    pub graph: Option<Iri>,
+    /// Id the merged node keeps, the first of the cluster in id order.
+    pub id: NodeId,
+    /// Ids of the other nodes of the cluster, merged into it.
+    pub merged: Vec<NodeId>,
+}
+
+/// Normalised values of each key, see [`deduplicate`].
+type MatchKey = Vec<BTreeSet<String>>;
+
+/// Nodes left by [`deduplicate`] and the clusters it merged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deduplication {
+    pub nodes: Vec<Node>,
+    pub clusters: Vec<DuplicateCluster>,
+}
+
+/// Merges the nodes of a graph that agree on every one of `keys`, such as
+/// the rows typed twice into a spreadsheet with differing ids.
+///
+/// String literals are compared ignoring case and runs of whitespace, and
+/// multi-valued predicates by their set of values. Nodes lacking a key
+/// never match. Each cluster keeps the id first in order; it gets the
+/// types of all its nodes and their values, with differing values united
+/// in input order like [`MergeStrategy::UnionArrays`], and references to
+/// the other ids in the graph are rewritten to it. Nodes keep the order of
+/// the input, a cluster taking the place of its first node.
+#[instrument(level = "info", skip_all, fields(node_count = nodes.len(), keys = keys.len()))]
+pub fn deduplicate(nodes: &[Node], keys: &[DedupeKey]) -> Deduplication {
+    let mut groups: BTreeMap<(Option<&Iri>, MatchKey), BTreeSet<&NodeId>> = BTreeMap::new();
+    if !keys.is_empty() {
+        for node in nodes {
+            if let Some(key) = match_key(node, keys) {
+                groups
+                    .entry((node.graph.as_ref(), key))
+                    .or_default()
+                    .insert(&node.id);
+            }
+        }
+    }
+    let mut clusters = Vec::new();
+    for ((graph, _), ids) in groups {
+        let mut ids = ids.into_iter();
+        let Some(kept) = ids.next() else {
+            continue;
+        };
+        let merged: Vec<NodeId> = ids.cloned().collect();
+        if !merged.is_empty() {
+            clusters.push(DuplicateCluster {
+                graph: graph.cloned(),
+                id: kept.clone(),
+                merged,
+            });
+        }
+    }
+
+    let deduplicated = merge_clusters(nodes, &clusters);
+    info!(
+        node_count = deduplicated.len(),
+        clusters = clusters.len(),
+        "deduplicated nodes"
+    );
+    Deduplication {
+        nodes: deduplicated,
+        clusters,
+    }
+}
+
+/// Merges the nodes of each of `clusters` into the node of the id it keeps,
+/// like [`deduplicate`]: types and values are united and references to the
+/// merged ids within the graph rewritten. Nodes keep the order of `nodes`.
+pub(crate) fn merge_clusters(nodes: &[Node], clusters: &[DuplicateCluster]) -> Vec<Node> {
+    let mut renames: BTreeMap<Option<&Iri>, BTreeMap<&NodeId, &NodeId>> = BTreeMap::new();
+    for cluster in clusters {
+        renames
+            .entry(cluster.graph.as_ref())
+            .or_default()
+            .extend(cluster.merged.iter().map(|id| (id, &cluster.id)));
+    }
+
+    let no_renames = BTreeMap::new();
+    let mut positions: BTreeMap<(Option<Iri>, NodeId), usize> = BTreeMap::new();
+    let mut merged: Vec<Node> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let graph_renames = renames.get(&node.graph.as_ref()).unwrap_or(&no_renames);
+        let id = graph_renames.get(&node.id).map_or(&node.id, |kept| *kept);
+        let mut node = Node {
+            id: id.clone(),
+            graph: node.graph.clone(),
+            types: node.types.clone(),
+            properties: node
+                .properties
+                .iter()
+                .map(|(predicate, value)| (predicate.clone(), renamed(value, graph_renames)))
+                .collect(),
+            lists: node.lists.clone(),
+        };
+        let key = (node.graph.clone(), node.id.clone());
+        let Some(&position) = positions.get(&key) else {
+            positions.insert(key, merged.len());
+            merged.push(node);
+            continue;
+        };
+        let target = &mut merged[position];
+        target.types.append(&mut node.types);
+        target.lists.append(&mut node.lists);
+        for (predicate, value) in node.properties {
+            match target.properties.get(&predicate) {
+                None => {
+                    target.properties.insert(predicate, value);
+                }
+                Some(existing) if *existing != value => {
+                    let united = union_values(existing, &value);
+                    target.properties.insert(predicate, united);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    merged
+}
+
+/// Returns what `node` is matched on for each of `keys`, or `None` when it
+/// lacks one.
+fn match_key(node: &Node, keys: &[DedupeKey]) -> Option<MatchKey> {
+    keys.iter()
+        .map(|key| match key {
+            DedupeKey::Type => (!node.types.is_empty()).then(|| {
+                node.types
+                    .iter()
+                    .map(|type_name| type_name.to_string())
+                    .collect()
+            }),
+            DedupeKey::Predicate(predicate) => {
+                let items = node.properties.get(predicate)?.items();
+                (!items.is_empty()).then(|| items.iter().map(normalized_item).collect())
+            }
+        })
+        .collect()
+}
+
+/// Returns the JSON text `item` is matched by, with the case and spacing of
+/// strings normalised.
+fn normalized_item(item: &ArrayItem) -> String {
+    match item {
+        ArrayItem::Scalar(ScalarValue::String(text))
+        | ArrayItem::Scalar(ScalarValue::LangString { value: text, .. }) => {
+            let words: Vec<&str> = text.split_whitespace().collect();
+            Value::String(words.join(" ").to_lowercase()).to_string()
+        }
+        other => other.to_json().to_string(),
+    }
+}
+
+/// Returns `value` with its references to ids in `renames` rewritten,
+/// leaving out references an array then repeats.
+fn renamed(value: &PropertyValue, renames: &BTreeMap<&NodeId, &NodeId>) -> PropertyValue {
+    let rename = |target: &NodeId| renames.get(target).map_or(target, |kept| *kept).clone();
+    match value {
+        PropertyValue::Scalar(_) => value.clone(),
+        PropertyValue::ObjectRef(target) => PropertyValue::ObjectRef(rename(target)),
+        PropertyValue::Array(_) if renames.is_empty() => value.clone(),
+        PropertyValue::Array(_) => {
+            let mut items: Vec<ArrayItem> = Vec::new();
+            for item in value.items() {
+                match item {
+                    ArrayItem::ObjectRef(target) => {
+                        let item = ArrayItem::ObjectRef(rename(&target));
+                        if !items.contains(&item) {
+                            items.push(item);
+                        }
+                    }
+                    scalar => items.push(scalar),
+                }
+            }
+            PropertyValue::Array(ArrayValue::from_items(items))
+        }
+    }
+}
+
+/// Gives `nodes`, read back from a workbook or CSV directory, the values of
+/// the `ignored` predicates that the node of the same graph and id holds in
+/// `previous`, the output they replace, since the workbook left them out.
+/// Values read for those predicates win, and nodes `previous` lacks are
+/// kept as they are, as are those removed from the workbook.
+pub fn keep_ignored(nodes: Vec<Node>, previous: &[Node], ignored: &[IriPattern]) -> Vec<Node> {
+    let ignores = |predicate: &Iri| ignored.iter().any(|pattern| pattern.matches(predicate));
+    let previous: BTreeMap<(Option<&Iri>, &NodeId), &Node> = previous
+        .iter()
+        .map(|node| ((node.graph.as_ref(), &node.id), node))
+        .collect();
+    nodes
+        .into_iter()
+        .map(|mut node| {
+            if let Some(before) = previous.get(&(node.graph.as_ref(), &node.id)) {
+                for (predicate, value) in &before.properties {
+                    if ignores(predicate) && !node.properties.contains_key(predicate) {
+                        node.properties.insert(predicate.clone(), value.clone());
+                    }
+                }
+            }
+            node
+        })
+        .collect()
+}
+
+/// Applies [`keep_ignored`] with the ignored predicates of `options` and the
+/// nodes of the existing `output`, read by `read` without the filters,
+/// scripts, and ids of `options`. The nodes are returned as they are when
+/// no predicates are ignored or `output` does not exist yet.
+pub fn keep_ignored_from(
+    nodes: Vec<Node>,
+    output: &Path,
+    options: &SyncOptions,
+    read: impl FnOnce(&Path, &SyncOptions) -> Result<Vec<Node>>,
+) -> Result<Vec<Node>> {
+    if options.ignored_predicates.is_empty() || !output.exists() {
+        return Ok(nodes);
+    }
+    let plain = SyncOptions {
+        remote_contexts: options.remote_contexts.clone(),
+        ..SyncOptions::default()
+    };
+    let previous = read(output, &plain)?;
+    debug!(
+        node_count = previous.len(),
+        "read ignored predicates from existing output"
+    );
+    Ok(keep_ignored(nodes, &previous, &options.ignored_predicates))
+}
+
+/// Unites two values into an array holding the entries of both, without
+/// repeating entries. Literals and references together form a mixed array.
+fn union_values(left: &PropertyValue, right: &PropertyValue) -> PropertyValue {
+    let mut items = left.items();
+    for item in right.items() {
+        if !items.contains(&item) {
+            items.push(item);
+        }
+    }
+    PropertyValue::Array(ArrayValue::from_items(items))
+}
+
+/// Validates `nodes` against the SHACL shapes stored at `shapes_path`. The
+/// shapes serialisation is inferred from the file extension.
+#[instrument(level = "info", skip(nodes), fields(node_count = nodes.len(), shapes = %shapes_path.display()))]
+pub fn validate_shacl(nodes: &[Node], shapes_path: &Path) -> Result<ShaclReport> {
+    let shapes = ShapesGraph::load(shapes_path, None)?;
+    debug!(shape_count = shapes.len(), "loaded SHACL shapes");
+    let report = shapes.validate(nodes);
+    info!(
+        result_count = report.results.len(),
+        conforms = report.conforms(),
+        "SHACL validation finished"
+    );
+    Ok(report)
+}
+
+/// Reads a JSON-LD document in the input encoding of `options`, resolving
+/// remote contexts when enabled.
+#[instrument(level = "debug", skip(options), fields(input = %input.display()))]
+pub fn read_jsonld(input: &Path, options: &SyncOptions) -> Result<Vec<Node>> {
+    parse_jsonld(&options.input_encoding.decode(&fs::read(input)?)?, options)
+}
+
+/// Parses the JSON-LD document `source` like [`read_jsonld`].
+pub(crate) fn parse_jsonld(source: &str, options: &SyncOptions) -> Result<Vec<Node>> {
+    let nodes = match &options.remote_contexts {
+        Some(loader) => {
+            let json: Value = serde_json::from_str(source)?;
+            let document = loader.resolve_document(&json)?;
+            jsonld::parse_jsonld_document_with_ids(&document, &options.id_strategy)?
+        }
+        None => jsonld::parse_jsonld_str_with_ids(source, &options.id_strategy)?,
+    };
+    accept(nodes, options)
+}
+
+/// Reads an Excel workbook, or the sample of it `options` previews, with
+/// the coercions and skipped sheets of `options`.
+#[instrument(level = "debug", skip(options), fields(input = %input.display()))]
+pub fn read_excel(input: &Path, options: &SyncOptions) -> Result<Vec<Node>> {
+    let nodes = if let Some(rows) = options.preview_rows {
+        excel_read::read_nodes_preview(input, &options.coercions, &options.skip_sheets, rows)?
+    } else if options.strict_cells {
+        excel_read::read_nodes_strict(input, &options.coercions, &options.skip_sheets)?
+    } else {
+        excel_read::read_nodes_skipping(input, &options.coercions, &options.skip_sheets)?
+    };
+    accept(nodes, options)
+}
+
+/// Reads a directory of CSV files, or the sample of it `options` previews,
+/// with the coercions and skipped tables of `options`.
+#[instrument(level = "debug", skip(options), fields(input = %input.display()))]
+pub fn read_csv(input: &Path, options: &SyncOptions) -> Result<Vec<Node>> {
+    if let Some(rows) = options.preview_rows {
+        let nodes = csv_read::read_nodes_preview_in_dialect(
+            input,
+            &options.csv_dialect,
+            &options.coercions,
+            &options.skip_sheets,
+            rows,
+        )?;
+        return accept(nodes, options);
+    }
+    let read = if options.strict_cells {
+        csv_read::read_nodes_strict_in_dialect
+    } else {
+        csv_read::read_nodes_in_dialect
+    };
+    let nodes = read(
+        input,
+        &options.csv_dialect,
+        &options.coercions,
+        &options.skip_sheets,
+    )?;
+    accept(nodes, options)
+}
+
+/// Reads an Excel workbook like [`read_excel`], leaving out the cells that
+/// cannot be read instead of failing at the first, see
+/// [`excel_read::read_nodes_keep_going`]. Returns them, as errors, with the
+/// warnings found while reading.
+#[instrument(level = "debug", skip(options), fields(input = %input.display()))]
+pub fn read_excel_keep_going(
+    input: &Path,
+    options: &SyncOptions,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    let (nodes, report) =
+        excel_read::read_nodes_keep_going(input, &options.coercions, &options.skip_sheets)?;
+    Ok((accept(nodes, options)?, report))
+}
+
+/// Reads a directory of CSV files like [`read_csv`], reporting the cells
+/// that cannot be read like [`read_excel_keep_going`].
+#[instrument(level = "debug", skip(options), fields(input = %input.display()))]
+pub fn read_csv_keep_going(
+    input: &Path,
+    options: &SyncOptions,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    let (nodes, report) = csv_read::read_nodes_keep_going_in_dialect(
+        input,
+        &options.csv_dialect,
+        &options.coercions,
+        &options.skip_sheets,
+    )?;
+    Ok((accept(nodes, options)?, report))
+}
+
+/// Reads an RDF file like [`read_rdf`], leaving out the statements that
+/// cannot be parsed instead of failing at the first, see
+/// [`rdf::read_rdf_keep_going_from`].
+#[instrument(level = "debug", skip(options), fields(input = %input.display()))]
+pub fn read_rdf_keep_going(
+    input: &Path,
+    format: Option<RdfFormat>,
+    options: &SyncOptions,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    let format = rdf::resolve_format(input, format.or(options.rdf_input_format))?;
+    let reader = options.input_encoding.reader(File::open(input)?);
+    let (nodes, report) = rdf::read_rdf_keep_going_from(reader, format)
+        .map_err(|err| rdf_input_error(input, format, err))?;
+    Ok((accept(fold_reifications(nodes, options), options)?, report))
+}
+
+/// Runs the queries of the SQL source file `input`, see [`sql`], against the
+/// database URL of `options` when set, expanding the compact IRIs of its
+/// mappings with the prefixes of `options` too.
+#[instrument(level = "debug", skip(options), fields(input = %input.display()))]
+pub fn read_sql(input: &Path, options: &SyncOptions) -> Result<Vec<Node>> {
+    let mut source = SqlSource::load(input)?;
+    if let Some(url) = &options.database_url {
+        source = source.with_url(url.clone());
+    }
+    let nodes = sql::read_nodes(&source, &options.prefixes)?;
+    accept(nodes, options)
+}
+
+/// Reads a node document, see [`nodes_json`], in the input encoding of
+/// `options`.
+#[instrument(level = "debug", skip(options), fields(input = %input.display()))]
+pub fn read_nodes_json(input: &Path, options: &SyncOptions) -> Result<Vec<Node>> {
+    read_nodes_json_from(File::open(input)?, options)
+}
+
+/// Reads the node document held by `reader` like [`read_nodes_json`].
+pub fn read_nodes_json_from<R: Read>(reader: R, options: &SyncOptions) -> Result<Vec<Node>> {
+    let nodes = nodes_json::read_nodes_json_from(options.input_encoding.reader(reader))?;
+    accept(nodes, options)
+}
+
+/// Reads an RDF file in the input encoding of `options`. The serialisation
+/// is `format`, else the input format of `options`, else detected from the
+/// extension.
+#[instrument(level = "debug", skip(options), fields(input = %input.display()))]
+pub fn read_rdf(
+    input: &Path,
+    format: Option<RdfFormat>,
+    options: &SyncOptions,
+) -> Result<Vec<Node>> {
+    let format = rdf::resolve_format(input, format.or(options.rdf_input_format))?;
+    let reader = options.input_encoding.reader(File::open(input)?);
+    let nodes =
+        rdf::read_rdf_from(reader, format).map_err(|err| rdf_input_error(input, format, err))?;
+    accept(fold_reifications(nodes, options), options)
 }
 
-#[instrument(level = "debug", skip(nodes, context), fields(output = %output.display()))]
-fn excel_to_jsonld_internal(nodes: &[Node], output: &Path, context: Option<Value>) -> Result<()> {
-    let json = jsonld::nodes_to_jsonld(nodes, context)?;
+/// Folds the reified statements of the RDF `nodes` into relationship nodes
+/// when `options` asks for it, see [`SyncOptions::fold_reifications`].
+pub(crate) fn fold_reifications(nodes: Vec<Node>, options: &SyncOptions) -> Vec<Node> {
+    if options.fold_reifications {
+        rdf::fold_reifications(nodes)
+    } else {
+        nodes
+    }
+}
+
+/// Streams the nodes of an RDF file like [`read_rdf`], see
+/// [`rdf::stream_nodes`], keeping those the node filter of `options`
+/// selects, running its script over them, and deskolemizing them if asked.
+fn stream_rdf(
+    input: &Path,
+    chunk_size: usize,
+    options: &SyncOptions,
+) -> Result<impl Iterator<Item = Result<Vec<Node>>> + use<>> {
+    let format = rdf::resolve_format(input, options.rdf_input_format)?;
+    let reader = options.input_encoding.reader(File::open(input)?);
+    let input = input.to_path_buf();
+    let filter = options.node_filter.clone();
+    let script = options.script.clone();
+    let labels = options.blank_nodes.clone();
+    Ok(
+        rdf::stream_nodes_from(reader, format, chunk_size).map(move |chunk| {
+            let chunk = match chunk {
+                Ok(chunk) => match &script {
+                    Some(script) => script.apply(filter.apply(chunk))?,
+                    None => filter.apply(chunk),
+                },
+                Err(err) => return Err(rdf_input_error(&input, format, err)),
+            };
+            relabel_blank_nodes(chunk, &labels)
+        }),
+    )
+}
+
+/// Names the input and the serialisation it was parsed as in RDF errors,
+/// which otherwise only give a position in a file read in the wrong format.
+fn rdf_input_error(input: &Path, format: RdfFormat, err: ToolError) -> ToolError {
+    match err {
+        ToolError::Rdf(message) => ToolError::Rdf(format!(
+            "failed to read {} as {}: {message}",
+            input.display(),
+            format.name()
+        )),
+        other => other,
+    }
+}
+
+/// Keeps the nodes read from an input that the node filter and conditions
+/// of `options` select, runs its script over them, checks them against its node limit,
+/// assigns their configured and natural key ids, resolves their `owl:sameAs` links, and
+/// gives them their blank node labels.
+pub(crate) fn accept(nodes: Vec<Node>, options: &SyncOptions) -> Result<Vec<Node>> {
+    let mut nodes =
+        query::apply_conditions(options.node_filter.apply(nodes), &options.node_conditions)?;
+    if let Some(script) = &options.script {
+        nodes = script.apply(nodes)?;
+    }
+    options.check_nodes(nodes.len())?;
+    let mut nodes = natural_key_ids(assign_ids(nodes, options)?, options)?;
+    if let Some(mode) = options.same_as {
+        nodes = same_as::apply(nodes, mode);
+    }
+    relabel_blank_nodes(nodes, &options.blank_nodes)
+}
+
+/// Gives the nodes of each type in [`SyncOptions::id_properties`] the id
+/// held by that type's property, expanded with [`SyncOptions::prefixes`]
+/// when it is a compact IRI, and points references at the new ids. Nodes
+/// without a single string or reference for the property keep their id.
+///
+/// Fails with [`ToolError::InvalidConfig`] when two nodes would end up with
+/// the same id.
+pub fn assign_ids(nodes: Vec<Node>, options: &SyncOptions) -> Result<Vec<Node>> {
+    if options.id_properties.is_empty() {
+        return Ok(nodes);
+    }
+    let mut labels: BTreeMap<NodeId, NodeId> = BTreeMap::new();
+    for node in &nodes {
+        let value = node
+            .types
+            .iter()
+            .filter_map(|type_name| options.id_properties.get(type_name))
+            .find_map(|predicate| match node.properties.get(predicate) {
+                Some(PropertyValue::Scalar(ScalarValue::String(value))) => Some(value.trim()),
+                Some(PropertyValue::ObjectRef(target)) => Some(target.as_str()),
+                _ => None,
+            });
+        let Some(value) = value.filter(|value| !value.is_empty()) else {
+            continue;
+        };
+        let id = Iri::expand(value, &options.prefixes);
+        if let Some(previous) = labels.insert(node.id.clone(), id.clone())
+            && previous != id
+        {
+            return Err(ToolError::InvalidConfig(format!(
+                "node {} is given both the id {previous} and {id}",
+                node.id
+            )));
+        }
+    }
+
+    let mut owners: BTreeMap<&NodeId, &NodeId> = nodes
+        .iter()
+        .filter(|node| !labels.contains_key(&node.id))
+        .map(|node| (&node.id, &node.id))
+        .collect();
+    for (old, new) in &labels {
+        if let Some(owner) = owners.insert(new, old)
+            && owner != old
+        {
+            return Err(ToolError::InvalidConfig(format!(
+                "nodes {owner} and {old} would both get the id {new}"
+            )));
+        }
+    }
+    debug!(renamed = labels.len(), "assigned ids from id properties");
+    Ok(nodes.iter().map(|node| relabel(node, &labels)).collect())
+}
+
+/// Gives the nodes of each type in [`SyncOptions::natural_keys`] that have a
+/// surrogate id, a blank node or `urn:uuid:`, a `urn:uuid:` name-based UUID
+/// hashed from the type and the values of its key predicates, and points
+/// references at the new ids. Editing other values, or reading the node
+/// again with another surrogate id, keeps the id. Nodes lacking a value for
+/// any key keep their id.
+///
+/// Fails with [`ToolError::InvalidConfig`] when two nodes share a natural
+/// key.
+pub fn natural_key_ids(nodes: Vec<Node>, options: &SyncOptions) -> Result<Vec<Node>> {
+    if options.natural_keys.is_empty() {
+        return Ok(nodes);
+    }
+    let mut labels: BTreeMap<(Option<Iri>, NodeId), NodeId> = BTreeMap::new();
+    for node in nodes.iter().filter(|node| is_surrogate(&node.id)) {
+        let Some(id) = node.types.iter().find_map(|type_name| {
+            natural_key_id(node, type_name, options.natural_keys.get(type_name)?)
+        }) else {
+            continue;
+        };
+        labels.insert((node.graph.clone(), node.id.clone()), id);
+    }
+
+    let mut owners: BTreeMap<(&Option<Iri>, &NodeId), &NodeId> = nodes
+        .iter()
+        .filter(|node| !labels.contains_key(&(node.graph.clone(), node.id.clone())))
+        .map(|node| ((&node.graph, &node.id), &node.id))
+        .collect();
+    for ((graph, old), new) in &labels {
+        if let Some(owner) = owners.insert((graph, new), old)
+            && owner != old
+        {
+            return Err(ToolError::InvalidConfig(format!(
+                "nodes {owner} and {old} share the natural key of {new}"
+            )));
+        }
+    }
+    debug!(renamed = labels.len(), "assigned ids from natural keys");
+    let mut graphs: BTreeMap<&Option<Iri>, BTreeMap<NodeId, NodeId>> = BTreeMap::new();
+    for ((graph, old), new) in &labels {
+        graphs
+            .entry(graph)
+            .or_default()
+            .insert(old.clone(), new.clone());
+    }
+    let none = BTreeMap::new();
+    Ok(nodes
+        .iter()
+        .map(|node| relabel(node, graphs.get(&node.graph).unwrap_or(&none)))
+        .collect())
+}
+
+/// Whether `id` was minted for a node rather than chosen for it.
+fn is_surrogate(id: &str) -> bool {
+    is_blank(id) || id.starts_with("urn:uuid:")
+}
+
+/// Returns the id [`natural_key_ids`] gives `node` as a `type_name` with the
+/// key predicates `keys`, unless it lacks a value for one of them.
+fn natural_key_id(node: &Node, type_name: &Iri, keys: &[Iri]) -> Option<NodeId> {
+    let mut values: Vec<(&str, Vec<Value>)> = Vec::with_capacity(keys.len());
+    for key in keys {
+        let items: Vec<Value> = node
+            .properties
+            .get(key)?
+            .items()
+            .into_iter()
+            .map(|item| match item {
+                ArrayItem::Scalar(ScalarValue::String(text)) => Value::from(text.trim()),
+                ArrayItem::Scalar(scalar) => scalar.to_json(),
+                ArrayItem::ObjectRef(target) => serde_json::json!({ "@id": target.as_str() }),
+            })
+            .collect();
+        if items.is_empty() {
+            return None;
+        }
+        values.push((key.as_str(), items));
+    }
+    let canonical = serde_json::to_string(&(type_name.as_str(), values)).ok()?;
+    let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, canonical.as_bytes());
+    Some(format!("urn:uuid:{uuid}").into())
+}
+
+/// Streams an RDF file twice: once to collect the workbook layout and once to
+/// write the rows, so only `chunk_size` nodes are held at a time. Limits are
+/// checked during the first pass, before the workbook is created. Returns the
+/// number of nodes read and of cells written.
+#[instrument(
+    level = "debug",
+    skip(options),
+    fields(input = %input.display(), output = %output.display())
+)]
+fn rdf_to_excel_chunked(
+    input: &Path,
+    output: &Path,
+    chunk_size: usize,
+    options: &SyncOptions,
+) -> Result<(usize, usize)> {
+    let workbook = options.workbook_options();
+    check_labels(&workbook.labels)?;
+    let mut layout = LayoutBuilder::default()
+        .with_matrix_predicates(workbook.matrix_predicates.iter().cloned())
+        .with_column_order(workbook.column_order.clone())
+        .with_column_groups(workbook.column_groups.clone())
+        .with_prefixes(workbook.prefixes.clone())
+        .with_header_terms(workbook.header_terms.clone())
+        .with_labels(workbook.labels.clone())
+        .with_sheet_names(workbook.sheet_naming, workbook.sheet_names.clone())
+        .with_cardinalities(workbook.cardinalities.clone())
+        .with_consolidated_types(workbook.consolidated_types.clone());
+    let mut node_count = 0;
+    for chunk in stream_rdf(input, chunk_size, options)? {
+        let chunk = chunk?;
+        node_count += chunk.len();
+        options.check_nodes(node_count)?;
+        layout.observe(&without_ignored(&chunk, &workbook.ignored_predicates));
+    }
+    let layout = layout
+        .build()
+        .with_encoding(workbook.encoding)
+        .with_context(workbook.context);
+    info!(node_count, "collected workbook layout from RDF source");
+    layout.check_cells(workbook.max_cells)?;
+
+    let mut writer = StreamingWorkbookWriter::new(&layout)?;
+    for chunk in stream_rdf(input, chunk_size, options)? {
+        let chunk = chunk?;
+        writer.append(&layout.tables(&without_ignored(&chunk, &workbook.ignored_predicates))?)?;
+    }
+    debug!(sheet_count = layout.sheets().len(), "workbook streamed");
+    writer.finish(output)?;
+    Ok((node_count, layout.cell_count()))
+}
+
+/// Records the number of `nodes` read on the current span and emits the
+/// number in each graph at debug level.
+fn trace_nodes(nodes: &[Node]) {
+    Span::current().record("node_count", nodes.len());
+    telemetry::count(Counter::NodesRead, nodes.len());
+    if !tracing::enabled!(Level::DEBUG) {
+        return;
+    }
+    for (graph, node_count) in DatasetSummary::from_nodes(nodes).graph_counts {
+        let graph = graph.as_deref().unwrap_or(NONE_LABEL);
+        debug!(graph, node_count, "nodes in graph");
+    }
+}
+
+/// Records the number of cells written from `workbook` on the current span
+/// and emits it at debug level.
+fn trace_cells(workbook: &WorkbookData) {
+    let cell_count = workbook.cell_count();
+    Span::current().record("cell_count", cell_count);
+    telemetry::count(Counter::CellsWritten, cell_count);
+    debug!(cell_count, "cells written");
+}
+
+/// Writes `nodes` as RDF in `format`, abbreviated with the prefix registry
+/// of `options` and `context`, see [`SyncOptions::prefix_registry`], and with
+/// the values of [`SyncOptions::rdf_lists`] as collections.
+pub fn write_rdf(
+    nodes: &[Node],
+    output: &Path,
+    format: RdfFormat,
+    context: Option<&Value>,
+    options: &SyncOptions,
+) -> Result<()> {
+    atomic::write_atomically(output, |partial| {
+        write_rdf_to(nodes, File::create(partial)?, format, context, options)
+    })
+}
+
+/// Writes `nodes` like [`write_rdf`], to `writer` instead of a file.
+pub fn write_rdf_to<W: Write>(
+    nodes: &[Node],
+    writer: W,
+    format: RdfFormat,
+    context: Option<&Value>,
+    options: &SyncOptions,
+) -> Result<()> {
+    let nodes = rdf::build_lists(nodes, &options.rdf_lists);
+    let prefixes = options.prefix_registry(context);
+    rdf::write_rdf_to(writer, &nodes, format, &prefixes)
+}
+
+/// Writes `nodes` as a JSON-LD document, compacted with `context` when given
+/// and shaped by the frame of `options` when set.
+#[instrument(level = "debug", skip(nodes, context, options), fields(output = %output.display()))]
+pub fn write_jsonld(
+    nodes: &[Node],
+    output: &Path,
+    context: Option<Value>,
+    options: &SyncOptions,
+) -> Result<()> {
+    let json = jsonld_document(nodes, context, options)?;
     let json_string = serde_json::to_string_pretty(&json)?;
-    fs::write(output, json_string)?;
+    atomic::write_atomically(output, |partial| Ok(fs::write(partial, json_string)?))
+}
+
+/// Reads the JSON-LD document `input` and returns it compacted with
+/// `context` instead of the contexts it declares, see [`jsonld::recompact`].
+/// Remote contexts are resolved when enabled, and the prefixes of `options`
+/// are declared in `context`.
+#[instrument(level = "debug", skip(context, options), fields(input = %input.display()))]
+pub fn recompact_jsonld(input: &Path, context: Value, options: &SyncOptions) -> Result<Value> {
+    let mut document: Value =
+        serde_json::from_str(&options.input_encoding.decode(&fs::read(input)?)?)?;
+    if let Some(loader) = &options.remote_contexts {
+        document = loader.resolve_document(&document)?;
+    }
+    let context = output_context(Some(context), options)?.expect("a context was given");
+    jsonld::recompact(document, context, options.jsonld_version)
+}
+
+/// Writes `nodes` like [`write_jsonld`], to `writer` instead of a file.
+pub fn write_jsonld_to<W: Write>(
+    nodes: &[Node],
+    writer: W,
+    context: Option<Value>,
+    options: &SyncOptions,
+) -> Result<()> {
+    serde_json::to_writer_pretty(writer, &jsonld_document(nodes, context, options)?)?;
+    Ok(())
+}
+
+/// Builds the JSON-LD document [`write_jsonld`] writes.
+pub(crate) fn jsonld_document(
+    nodes: &[Node],
+    context: Option<Value>,
+    options: &SyncOptions,
+) -> Result<Value> {
+    let context = output_context(context, options)?;
+    let framed = options
+        .frame
+        .as_ref()
+        .and_then(|frame| frame.get("@context"));
+    if options.frame.is_none() && !options.graph_contexts.is_empty() {
+        return graph_compacted_document(nodes, context, options);
+    }
+    if let Some(context) = context.as_ref().or(framed) {
+        report_unmapped(&ContextCoverage::new(context)?.unmapped(nodes), options)?;
+    }
+    match &options.frame {
+        Some(frame) => jsonld::frame_nodes(nodes, frame, context, options.jsonld_version),
+        None => jsonld::nodes_to_jsonld_versioned(nodes, context, options.jsonld_version),
+    }
+}
+
+/// Builds the JSON-LD document [`jsonld_document`] writes when named graphs
+/// have contexts of their own, checking each node against the context that
+/// compacts it.
+fn graph_compacted_document(
+    nodes: &[Node],
+    context: Option<Value>,
+    options: &SyncOptions,
+) -> Result<Value> {
+    let mut graph_contexts = BTreeMap::new();
+    for (graph, graph_context) in &options.graph_contexts {
+        let graph_context = match &options.remote_contexts {
+            Some(loader) => loader.resolve_context(graph_context)?,
+            None => graph_context.clone(),
+        };
+        graph_contexts.insert(graph.clone(), graph_context);
+    }
+    let (scoped, rest): (Vec<Node>, Vec<Node>) = nodes.iter().cloned().partition(|node| {
+        node.graph
+            .as_ref()
+            .is_some_and(|graph| graph_contexts.contains_key(graph))
+    });
+    if let Some(context) = &context {
+        report_unmapped(&ContextCoverage::new(context)?.unmapped(&rest), options)?;
+    }
+    for (graph, graph_context) in &graph_contexts {
+        let members: Vec<Node> = scoped
+            .iter()
+            .filter(|node| node.graph.as_ref() == Some(graph))
+            .cloned()
+            .collect();
+        report_unmapped(
+            &ContextCoverage::new(graph_context)?.unmapped(&members),
+            options,
+        )?;
+    }
+    jsonld::nodes_to_jsonld_with_graph_contexts(
+        nodes,
+        context,
+        &graph_contexts,
+        options.jsonld_version,
+    )
+}
+
+/// Warns about each IRI an output context leaves unmapped, then fails when
+/// `options` asks for a strict context.
+fn report_unmapped(unmapped: &[UnmappedIri], options: &SyncOptions) -> Result<()> {
+    for iri in unmapped {
+        warn!(
+            node = %iri.node,
+            "the JSON-LD context does not map the {} {}; it is written in full",
+            iri.role,
+            iri.iri
+        );
+    }
+    if options.strict_context && !unmapped.is_empty() {
+        return Err(ToolError::UnmappedIris {
+            count: unmapped.len(),
+        });
+    }
     Ok(())
 }
+
+/// Returns `context`, or else the context embedded in the input as read by
+/// `embedded`, unless the frame of `options` brings its own.
+pub(crate) fn context_or_embedded(
+    context: Option<Value>,
+    options: &SyncOptions,
+    embedded: impl FnOnce() -> Result<Option<Value>>,
+) -> Result<Option<Value>> {
+    let framed = options
+        .frame
+        .as_ref()
+        .is_some_and(|frame| frame.get("@context").is_some());
+    match context {
+        None if !framed => embedded(),
+        context => Ok(context),
+    }
+}
+
+/// Resolves a remote output context when remote contexts are enabled and
+/// declares the prefixes of `options` in it, keeping terms the context
+/// already defines.
+fn output_context(context: Option<Value>, options: &SyncOptions) -> Result<Option<Value>> {
+    let context = match (context, &options.remote_contexts) {
+        (Some(context), Some(loader)) => Some(loader.resolve_context(&context)?),
+        (context, _) => context,
+    };
+    if options.prefixes.is_empty() {
+        return Ok(context);
+    }
+    let context = context.unwrap_or_else(|| Value::Object(Map::new()));
+    Ok(Some(declare_prefixes(context, &options.prefixes)))
+}
+
+/// Adds the `prefixes` the context does not define yet to `context`.
+fn declare_prefixes(context: Value, prefixes: &BTreeMap<String, String>) -> Value {
+    let mut prefixes: Map<String, Value> = prefixes
+        .iter()
+        .map(|(prefix, namespace)| (prefix.clone(), Value::String(namespace.clone())))
+        .collect();
+    match context {
+        Value::Object(mut terms) => {
+            for (prefix, namespace) in prefixes {
+                terms.entry(prefix).or_insert(namespace);
+            }
+            Value::Object(terms)
+        }
+        _ if prefixes.is_empty() => context,
+        Value::Array(mut contexts) => {
+            if let Some(Value::Object(terms)) = contexts.last() {
+                prefixes.retain(|prefix, _| !terms.contains_key(prefix));
+            }
+            contexts.push(Value::Object(prefixes));
+            Value::Array(contexts)
+        }
+        other => Value::Array(vec![other, Value::Object(prefixes)]),
+    }
+}
@@ -0,0 +1,145 @@
+//! Export of traces and metrics to an OpenTelemetry collector.
+//!
+//! Built with the `otel` feature, [`layer`] sends the tracing spans of
+//! conversions, with the statistics recorded on them, to an OTLP/HTTP
+//! endpoint, and the [`Counter`]s incremented by conversions as metrics.
+//! Without the feature counting does nothing and [`layer`] fails.
+
+#[cfg(feature = "otel")]
+use opentelemetry::trace::TracerProvider as _;
+#[cfg(feature = "otel")]
+use opentelemetry_otlp::WithExportConfig;
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::Resource;
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::Subscriber;
+use tracing_subscriber::Layer;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::aideon::tools::error::{Result, ToolError};
+
+/// Name under which traces and metrics are reported.
+pub const SERVICE_NAME: &str = "aideon-tools";
+
+/// Monotonic counters of the work done by conversions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counter {
+    /// Nodes read from inputs.
+    NodesRead,
+    /// Statements written to RDF outputs.
+    TriplesWritten,
+    /// Cells written to workbooks and CSV files, headers included.
+    CellsWritten,
+}
+
+impl Counter {
+    /// Returns the name of the metric.
+    pub fn name(self) -> &'static str {
+        match self {
+            Counter::NodesRead => "aideon.nodes.read",
+            Counter::TriplesWritten => "aideon.triples.written",
+            Counter::CellsWritten => "aideon.cells.written",
+        }
+    }
+}
+
+/// Adds `value` to `counter` in the global meter provider.
+#[cfg(feature = "otel")]
+pub(crate) fn count(counter: Counter, value: usize) {
+    // Counters are cheap to look up and are only bumped once per
+    // conversion; caching them would pin the no-op meter in use before a
+    // provider is installed.
+    opentelemetry::global::meter(SERVICE_NAME)
+        .u64_counter(counter.name())
+        .build()
+        .add(value as u64, &[]);
+}
+
+#[cfg(not(feature = "otel"))]
+pub(crate) fn count(_counter: Counter, _value: usize) {}
+
+/// Exporters started by [`layer`]. Dropping them loses the spans and
+/// metrics not yet exported, so call [`Self::shutdown`] before exiting.
+#[derive(Debug)]
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    tracer_provider: SdkTracerProvider,
+    #[cfg(feature = "otel")]
+    meter_provider: SdkMeterProvider,
+}
+
+impl Telemetry {
+    /// Exports the pending spans and metrics and stops the exporters.
+    pub fn shutdown(self) -> Result<()> {
+        #[cfg(feature = "otel")]
+        {
+            let error =
+                |err: opentelemetry_sdk::error::OTelSdkError| ToolError::Telemetry(err.to_string());
+            self.tracer_provider.shutdown().map_err(error)?;
+            self.meter_provider.shutdown().map_err(error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns a layer exporting spans to the OTLP/HTTP collector at
+/// `endpoint`, such as `http://localhost:4318`, and installs the global
+/// meter provider exporting [`Counter`]s there.
+///
+/// Fails with [`ToolError::Telemetry`] when the exporters cannot be built
+/// or the crate was built without the `otel` feature.
+#[cfg(feature = "otel")]
+pub fn layer<S>(endpoint: &str) -> Result<(impl Layer<S>, Telemetry)>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let url = |signal: &str| format!("{}/v1/{signal}", endpoint.trim_end_matches('/'));
+    let error = |err: opentelemetry_otlp::ExporterBuildError| {
+        ToolError::Telemetry(format!("cannot export to '{endpoint}': {err}"))
+    };
+    let resource = Resource::builder().with_service_name(SERVICE_NAME).build();
+
+    let spans = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(url("traces"))
+        .build()
+        .map_err(error)?;
+    let tracer_provider = SdkTracerProvider::builder()
+        .with_batch_exporter(spans)
+        .with_resource(resource.clone())
+        .build();
+
+    let metrics = opentelemetry_otlp::MetricExporter::builder()
+        .with_http()
+        .with_endpoint(url("metrics"))
+        .build()
+        .map_err(error)?;
+    let meter_provider = SdkMeterProvider::builder()
+        .with_periodic_exporter(metrics)
+        .with_resource(resource)
+        .build();
+    opentelemetry::global::set_meter_provider(meter_provider.clone());
+
+    let layer = tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer(SERVICE_NAME));
+    Ok((
+        layer,
+        Telemetry {
+            tracer_provider,
+            meter_provider,
+        },
+    ))
+}
+
+/// Fails: exporting telemetry needs the `otel` feature.
+#[cfg(not(feature = "otel"))]
+pub fn layer<S>(endpoint: &str) -> Result<(impl Layer<S>, Telemetry)>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    Err::<(tracing_subscriber::layer::Identity, Telemetry), _>(ToolError::Telemetry(format!(
+        "exporting to '{endpoint}' requires the `otel` feature"
+    )))
+}
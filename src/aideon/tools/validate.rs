@@ -0,0 +1,271 @@
+//! Structural checks over a parsed node set.
+
+use std::collections::{BTreeSet, HashSet};
+use std::mem::discriminant;
+
+use iref::Iri as IriRef;
+use serde::Serialize;
+
+use crate::aideon::tools::model::{
+    ArrayItem, ArrayValue, Iri, Node, NodeId, PropertyValue, ScalarValue,
+};
+
+/// Severity attached to a validation issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The dataset cannot be converted faithfully.
+    Error,
+    /// The dataset converts but is likely not what the author intended.
+    Warning,
+}
+
+impl Severity {
+    /// Lowercase label used in reports.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// Category of a validation issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueKind {
+    /// An object reference points at an id that no node in the dataset has.
+    DanglingReference,
+    /// Several nodes share the same id within one graph.
+    DuplicateId,
+    /// A predicate is blank or has no values.
+    EmptyPredicate,
+    /// A node id, graph, type, predicate, or reference is not a valid IRI or
+    /// blank node label.
+    InvalidIri,
+    /// An array mixes literals of different kinds.
+    MixedArray,
+    /// The `Entities` sheet of a workbook or CSV directory and its type
+    /// sheets disagree about a node: one lists an id the other does not, or
+    /// assigns it a type the other does not.
+    EntityIndexMismatch,
+    /// Two rows of a workbook or CSV directory describing the same node give
+    /// one of its properties different values; the later one is read.
+    ConflictingCell,
+    /// A cell of a workbook or CSV directory could not be read and was left
+    /// out.
+    InvalidCell,
+    /// A statement of an RDF input could not be parsed and was left out.
+    InvalidStatement,
+}
+
+/// A single problem found while validating a dataset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    /// Severity of the issue.
+    pub severity: Severity,
+    /// Category of the issue.
+    pub kind: IssueKind,
+    /// Node the issue was found on.
+    pub node: NodeId,
+    /// Graph of the affected node.
+    pub graph: Option<Iri>,
+    /// Predicate involved, if any.
+    pub predicate: Option<String>,
+    /// Human-readable description.
+    pub message: String,
+}
+
+/// Outcome of validating a dataset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    /// Issues in node order.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Number of issues with [`Severity::Error`].
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    /// Number of issues with [`Severity::Warning`].
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    /// Returns `true` when at least one error was found.
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .count()
+    }
+}
+
+/// Checks `nodes` for dangling references, duplicate ids, empty predicates,
+/// invalid IRIs, and mixed-type arrays.
+pub fn validate_nodes(nodes: &[Node]) -> ValidationReport {
+    let known_ids: HashSet<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+    let mut seen: BTreeSet<(Option<&str>, &str)> = BTreeSet::new();
+    let mut report = ValidationReport::default();
+
+    for node in nodes {
+        let mut issues = NodeIssues {
+            node,
+            report: &mut report,
+        };
+
+        if !seen.insert((node.graph.as_deref(), node.id.as_str())) {
+            issues.push(
+                Severity::Error,
+                IssueKind::DuplicateId,
+                None,
+                format!("id '{}' appears more than once in the same graph", node.id),
+            );
+        }
+
+        issues.check_identifier("node id", &node.id, None);
+        if let Some(graph) = &node.graph {
+            issues.check_identifier("graph name", graph, None);
+        }
+        for type_name in &node.types {
+            issues.check_iri("type", type_name, None);
+        }
+
+        for (predicate, value) in &node.properties {
+            if predicate.trim().is_empty() {
+                issues.push(
+                    Severity::Error,
+                    IssueKind::EmptyPredicate,
+                    Some(predicate),
+                    "predicate name is empty".into(),
+                );
+            } else {
+                issues.check_iri("predicate", predicate, Some(predicate));
+            }
+
+            match value {
+                PropertyValue::Scalar(_) => {}
+                PropertyValue::ObjectRef(target) => {
+                    issues.check_reference(target, predicate, &known_ids);
+                }
+                PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => {
+                    if targets.is_empty() {
+                        issues.push_empty(predicate);
+                    }
+                    for target in targets {
+                        issues.check_reference(target, predicate, &known_ids);
+                    }
+                }
+                PropertyValue::Array(ArrayValue::Mixed(items)) => {
+                    for item in items {
+                        if let ArrayItem::ObjectRef(target) = item {
+                            issues.check_reference(target, predicate, &known_ids);
+                        }
+                    }
+                }
+                PropertyValue::Array(ArrayValue::Scalars(items)) => {
+                    if items.is_empty() {
+                        issues.push_empty(predicate);
+                    } else if is_mixed(items) {
+                        issues.push(
+                            Severity::Warning,
+                            IssueKind::MixedArray,
+                            Some(predicate),
+                            "array mixes literals of different kinds".into(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    report
+}
+
+struct NodeIssues<'a> {
+    node: &'a Node,
+    report: &'a mut ValidationReport,
+}
+
+impl NodeIssues<'_> {
+    fn push(
+        &mut self,
+        severity: Severity,
+        kind: IssueKind,
+        predicate: Option<&str>,
+        message: String,
+    ) {
+        self.report.issues.push(ValidationIssue {
+            severity,
+            kind,
+            node: self.node.id.clone(),
+            graph: self.node.graph.clone(),
+            predicate: predicate.map(str::to_string),
+            message,
+        });
+    }
+
+    fn push_empty(&mut self, predicate: &str) {
+        self.push(
+            Severity::Warning,
+            IssueKind::EmptyPredicate,
+            Some(predicate),
+            "predicate has no values".into(),
+        );
+    }
+
+    fn check_iri(&mut self, what: &str, value: &str, predicate: Option<&str>) {
+        if IriRef::new(value).is_err() {
+            self.push(
+                Severity::Error,
+                IssueKind::InvalidIri,
+                predicate,
+                format!("{what} '{value}' is not a valid IRI"),
+            );
+        }
+    }
+
+    fn check_identifier(&mut self, what: &str, value: &str, predicate: Option<&str>) {
+        if !is_blank_node(value) {
+            self.check_iri(what, value, predicate);
+        }
+    }
+
+    fn check_reference(&mut self, target: &str, predicate: &str, known_ids: &HashSet<&str>) {
+        self.check_identifier("reference", target, Some(predicate));
+        if !known_ids.contains(target) {
+            self.push(
+                Severity::Error,
+                IssueKind::DanglingReference,
+                Some(predicate),
+                format!("reference to '{target}' does not match any node"),
+            );
+        }
+    }
+}
+
+fn is_blank_node(value: &str) -> bool {
+    value
+        .strip_prefix("_:")
+        .is_some_and(|label| !label.is_empty())
+}
+
+/// Returns whether `items` hold literals of different kinds. Integers and
+/// doubles are both numbers.
+fn is_mixed(items: &[ScalarValue]) -> bool {
+    let kinds: Vec<_> = items
+        .iter()
+        .filter(|item| !matches!(item, ScalarValue::Null))
+        .map(|item| match item {
+            ScalarValue::Integer(_) => discriminant(&ScalarValue::Double(0.0)),
+            other => discriminant(other),
+        })
+        .collect();
+    kinds.windows(2).any(|pair| pair[0] != pair[1])
+}
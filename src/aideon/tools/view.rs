@@ -0,0 +1,140 @@
+//! Typed views of the nodes of a type.
+//!
+//! A [`View`] is a Rust struct deserialised from the nodes of one type, so
+//! applications embedding the library read records instead of property maps:
+//!
+//! ```
+//! use aideon_tools::aideon::tools::view::{self, View, Views};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Person {
+//!     #[serde(rename = "@id")]
+//!     id: String,
+//!     name: String,
+//!     #[serde(default, deserialize_with = "view::one_or_many")]
+//!     knows: Vec<String>,
+//! }
+//!
+//! impl View for Person {
+//!     const TYPE: &'static str = "https://schema.org/Person";
+//!     const VOCABULARY: Option<&'static str> = Some("https://schema.org/");
+//! }
+//!
+//! # fn people(nodes: &[aideon_tools::model::Node]) -> aideon_tools::Result<()> {
+//! for person in nodes.view::<Person>()? {
+//!     println!("{} ({}) knows {:?}", person.name, person.id, person.knows);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each node reads as a JSON object keyed by its predicates, with the
+//! [`View::VOCABULARY`] namespace left off, plus `@id` and, in named graphs,
+//! `@graph`. Literals read as their JSON value, or as their lexical form when
+//! JSON has none, such as dates and typed literals; language tags are
+//! dropped. References read as the id of their target and multi-valued
+//! predicates as arrays, which [`one_or_many`] also accepts single values
+//! for.
+
+use serde::Deserialize;
+use serde::de::{DeserializeOwned, Deserializer};
+use serde_json::{Map, Value};
+
+use crate::aideon::tools::error::{Result, ToolError};
+use crate::aideon::tools::model::{ArrayItem, Node, PropertyValue, ScalarValue};
+
+/// A struct read from the nodes of a type, see the [module](self) docs.
+pub trait View: DeserializeOwned {
+    /// IRI of the type whose nodes are read.
+    const TYPE: &'static str;
+    /// Namespace left off the predicates the fields are named by, such as
+    /// `https://schema.org/` for a `name` field; `None` names the fields by
+    /// the full predicate IRIs.
+    const VOCABULARY: Option<&'static str> = None;
+}
+
+/// Typed access to a set of nodes.
+pub trait Views {
+    /// Reads the nodes of type [`View::TYPE`] as `T`, in order, failing on
+    /// the first node that does not fit it.
+    fn view<T: View>(&self) -> Result<Vec<T>>;
+}
+
+impl Views for [Node] {
+    fn view<T: View>(&self) -> Result<Vec<T>> {
+        self.iter()
+            .filter(|node| node.types.contains(T::TYPE))
+            .map(view_node)
+            .collect()
+    }
+}
+
+/// Reads `node` as `T`, whatever its types.
+pub fn view_node<T: View>(node: &Node) -> Result<T> {
+    serde_json::from_value(node_object(node, T::VOCABULARY)).map_err(|err| ToolError::View {
+        id: node.id.to_string(),
+        reason: err.to_string(),
+    })
+}
+
+/// Deserialises a field from an array or a single value, for predicates
+/// that may have one value or several.
+pub fn one_or_many<'de, D, T>(deserializer: D) -> std::result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::Many(values) => values,
+        OneOrMany::One(value) => vec![value],
+    })
+}
+
+/// Builds the JSON object `node` is deserialised from.
+fn node_object(node: &Node, vocabulary: Option<&str>) -> Value {
+    let mut object = Map::new();
+    object.insert("@id".into(), Value::String(node.id.to_string()));
+    if let Some(graph) = &node.graph {
+        object.insert("@graph".into(), Value::String(graph.to_string()));
+    }
+    for (predicate, value) in &node.properties {
+        let key = vocabulary
+            .and_then(|vocabulary| predicate.strip_prefix(vocabulary))
+            .filter(|term| !term.is_empty())
+            .unwrap_or(predicate);
+        let value = match value {
+            PropertyValue::Array(_) => Value::Array(value.items().iter().map(item).collect()),
+            PropertyValue::Scalar(scalar) => literal(scalar),
+            PropertyValue::ObjectRef(target) => Value::String(target.to_string()),
+        };
+        object.insert(key.to_string(), value);
+    }
+    Value::Object(object)
+}
+
+fn item(item: &ArrayItem) -> Value {
+    match item {
+        ArrayItem::Scalar(scalar) => literal(scalar),
+        ArrayItem::ObjectRef(target) => Value::String(target.to_string()),
+    }
+}
+
+fn literal(scalar: &ScalarValue) -> Value {
+    match scalar {
+        ScalarValue::LangString { value, .. } => Value::String(value.clone()),
+        ScalarValue::TypedLiteral { .. } | ScalarValue::Date(_) | ScalarValue::DateTime(_) => {
+            scalar
+                .typed_lexical()
+                .map(|(value, _)| Value::String(value.into_owned()))
+                .unwrap_or(Value::Null)
+        }
+        _ => scalar.to_json(),
+    }
+}
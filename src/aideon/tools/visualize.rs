@@ -0,0 +1,276 @@
+//! Diagrams of the node graph for review.
+//!
+//! [`write_graph`] draws the nodes as the vertices of a Graphviz DOT or
+//! Mermaid flowchart and their references as edges labelled with the local
+//! name of the predicate, so the people editing a workbook can see what it
+//! describes:
+//!
+//! ```text
+//! digraph dataset {
+//!   "https://example.com/people/1" [label="Ada"];
+//!   "https://example.com/people/2" [label="Grace"];
+//!   "https://example.com/people/1" -> "https://example.com/people/2" [label="knows"];
+//! }
+//! ```
+//!
+//! A [`GraphSelection`] narrows the drawing down to nodes of some types, or
+//! to those within a number of references of root nodes. Nodes are drawn by
+//! id, so a node described in several graphs is drawn once, and references
+//! to nodes that are not drawn are left out.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::Write;
+
+use crate::aideon::tools::error::Result;
+use crate::aideon::tools::filter::IriPattern;
+use crate::aideon::tools::model::{
+    ArrayItem, ArrayValue, Iri, Node, NodeId, PropertyValue, ScalarValue,
+};
+
+/// Predicate labelling the nodes unless another is chosen.
+pub const DEFAULT_LABEL_PREDICATE: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+
+/// Diagram languages [`write_graph`] writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT, rendered with `dot -Tsvg`.
+    Dot,
+    /// Mermaid flowchart, rendered by Markdown viewers such as GitHub's.
+    Mermaid,
+}
+
+impl GraphFormat {
+    /// Detects the format from a file extension: `mmd` and `mermaid` are
+    /// Mermaid, `dot` and `gv` DOT.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "dot" | "gv" => Some(GraphFormat::Dot),
+            "mmd" | "mermaid" => Some(GraphFormat::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+/// Which nodes are drawn and how they are labelled.
+#[derive(Debug, Clone)]
+pub struct GraphSelection {
+    /// Types of the nodes drawn; every node when empty.
+    pub types: Vec<IriPattern>,
+    /// Nodes the drawing starts from; every node is drawn when empty.
+    pub roots: Vec<NodeId>,
+    /// Most references followed from the roots; unlimited when `None`.
+    pub depth: Option<usize>,
+    /// Predicate whose first literal labels each node; nodes without one
+    /// are labelled with their id.
+    pub label_predicate: Iri,
+}
+
+impl Default for GraphSelection {
+    fn default() -> Self {
+        Self {
+            types: Vec::new(),
+            roots: Vec::new(),
+            depth: None,
+            label_predicate: Iri::from(DEFAULT_LABEL_PREDICATE),
+        }
+    }
+}
+
+impl GraphSelection {
+    /// Returns the nodes drawn, in order. Only nodes of the chosen types are
+    /// drawn or walked through from the roots.
+    pub fn select<'a>(&self, nodes: &'a [Node]) -> Vec<&'a Node> {
+        let typed: Vec<&Node> = nodes
+            .iter()
+            .filter(|node| {
+                self.types.is_empty()
+                    || node.types.iter().any(|type_name| {
+                        self.types.iter().any(|pattern| pattern.matches(type_name))
+                    })
+            })
+            .collect();
+        if self.roots.is_empty() {
+            return typed;
+        }
+
+        let mut by_id: BTreeMap<&NodeId, Vec<&Node>> = BTreeMap::new();
+        for node in &typed {
+            by_id.entry(&node.id).or_default().push(node);
+        }
+        let mut reached: BTreeSet<&NodeId> = BTreeSet::new();
+        let mut queue: VecDeque<(&NodeId, usize)> = VecDeque::new();
+        for root in &self.roots {
+            if let Some((id, _)) = by_id.get_key_value(root)
+                && reached.insert(id)
+            {
+                queue.push_back((id, 0));
+            }
+        }
+        while let Some((id, distance)) = queue.pop_front() {
+            if self.depth.is_some_and(|depth| distance >= depth) {
+                continue;
+            }
+            for node in &by_id[id] {
+                for (_, target) in references(node) {
+                    if let Some((target, _)) = by_id.get_key_value(target)
+                        && reached.insert(target)
+                    {
+                        queue.push_back((target, distance + 1));
+                    }
+                }
+            }
+        }
+        typed
+            .into_iter()
+            .filter(|node| reached.contains(&node.id))
+            .collect()
+    }
+
+    /// Returns the label of `node`: the first literal of the label
+    /// predicate, else its id.
+    fn label(&self, node: &Node) -> String {
+        node.properties
+            .get(&self.label_predicate)
+            .and_then(|value| {
+                value.items().into_iter().find_map(|item| match item {
+                    ArrayItem::Scalar(scalar) => literal_text(&scalar),
+                    ArrayItem::ObjectRef(_) => None,
+                })
+            })
+            .unwrap_or_else(|| node.id.to_string())
+    }
+}
+
+/// Writes the nodes `selection` picks from `nodes` to `writer` as a diagram
+/// in `format`.
+pub fn write_graph<W: Write>(
+    mut writer: W,
+    nodes: &[Node],
+    selection: &GraphSelection,
+    format: GraphFormat,
+) -> Result<()> {
+    let selected = selection.select(nodes);
+    // Vertices by id, labelled after the first node describing them.
+    let mut vertices: BTreeMap<&NodeId, String> = BTreeMap::new();
+    for node in &selected {
+        vertices
+            .entry(&node.id)
+            .or_insert_with(|| selection.label(node));
+    }
+    let mut edges: BTreeSet<(&NodeId, &NodeId, &str)> = BTreeSet::new();
+    for node in &selected {
+        for (predicate, target) in references(node) {
+            if vertices.contains_key(target) {
+                edges.insert((&node.id, target, local_name(predicate)));
+            }
+        }
+    }
+
+    match format {
+        GraphFormat::Dot => {
+            writeln!(writer, "digraph dataset {{")?;
+            for (id, label) in &vertices {
+                writeln!(
+                    writer,
+                    "  {} [label={}];",
+                    dot_string(id),
+                    dot_string(label)
+                )?;
+            }
+            for (source, target, predicate) in &edges {
+                writeln!(
+                    writer,
+                    "  {} -> {} [label={}];",
+                    dot_string(source),
+                    dot_string(target),
+                    dot_string(predicate)
+                )?;
+            }
+            writeln!(writer, "}}")?;
+        }
+        GraphFormat::Mermaid => {
+            // Mermaid ids cannot hold IRIs, so vertices are numbered in id
+            // order.
+            let numbers: BTreeMap<&NodeId, usize> = vertices
+                .keys()
+                .enumerate()
+                .map(|(number, id)| (*id, number))
+                .collect();
+            writeln!(writer, "graph LR")?;
+            for (id, label) in &vertices {
+                writeln!(writer, "  n{}[{}]", numbers[id], mermaid_string(label))?;
+            }
+            for (source, target, predicate) in &edges {
+                writeln!(
+                    writer,
+                    "  n{} -->|{}| n{}",
+                    numbers[source],
+                    mermaid_string(predicate),
+                    numbers[target]
+                )?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Returns the predicates and targets of the references of `node`.
+fn references(node: &Node) -> Vec<(&Iri, &NodeId)> {
+    let mut references = Vec::new();
+    for (predicate, value) in &node.properties {
+        match value {
+            PropertyValue::ObjectRef(target) => references.push((predicate, target)),
+            PropertyValue::Array(ArrayValue::ObjectRefs(targets)) => {
+                references.extend(targets.iter().map(|target| (predicate, target)));
+            }
+            PropertyValue::Array(ArrayValue::Mixed(items)) => {
+                references.extend(items.iter().filter_map(|item| match item {
+                    ArrayItem::ObjectRef(target) => Some((predicate, target)),
+                    ArrayItem::Scalar(_) => None,
+                }));
+            }
+            PropertyValue::Scalar(_) | PropertyValue::Array(ArrayValue::Scalars(_)) => {}
+        }
+    }
+    references
+}
+
+/// Returns the part of `iri` after its last `#` or `/`, or all of it when
+/// that is empty.
+fn local_name(iri: &str) -> &str {
+    match iri.rsplit(['#', '/']).next() {
+        Some(name) if !name.is_empty() => name,
+        _ => iri,
+    }
+}
+
+fn literal_text(scalar: &ScalarValue) -> Option<String> {
+    match scalar {
+        ScalarValue::String(value)
+        | ScalarValue::LangString { value, .. }
+        | ScalarValue::TypedLiteral { value, .. } => Some(value.clone()),
+        ScalarValue::Null => None,
+        ScalarValue::Date(_) | ScalarValue::DateTime(_) => {
+            scalar.typed_lexical().map(|(value, _)| value.into_owned())
+        }
+        ScalarValue::Integer(_) | ScalarValue::Double(_) | ScalarValue::Boolean(_) => {
+            Some(scalar.to_json().to_string())
+        }
+    }
+}
+
+/// Quotes `text` as a DOT string, keeping its line breaks.
+fn dot_string(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+/// Quotes `text` as a Mermaid label on one line, writing quotes as entity
+/// codes.
+fn mermaid_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "#quot;").replace('\n', " "))
+}
@@ -0,0 +1,496 @@
+//! Top-level command line and the flags shared by several subcommands.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::flatten::{SheetNaming, consolidate_subclasses};
+use aideon_tools::aideon::tools::io::coercion::ScalarCoercions;
+use aideon_tools::aideon::tools::io::csv_dialect::CsvDialect;
+use aideon_tools::aideon::tools::io::jsonld::{self, IdStrategy};
+use aideon_tools::aideon::tools::io::rdf;
+use aideon_tools::aideon::tools::io::remote_context::RemoteContextLoader;
+use aideon_tools::aideon::tools::io::text_encoding::TextEncoding;
+use aideon_tools::aideon::tools::model::Iri;
+use aideon_tools::aideon::tools::same_as::SameAs;
+use aideon_tools::aideon::tools::shacl::ShapesGraph;
+use aideon_tools::aideon::tools::sync::{MergeStrategy, SyncOptions};
+use aideon_tools::{Result, ToolError};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::cli::dedupe::DedupeArgs;
+use crate::cli::diff::DiffArgs;
+use crate::cli::export::ExportArgs;
+use crate::cli::formats::{CsvEncodingKind, CsvQuotingKind, TextEncodingKind};
+use crate::cli::history::HistoryCommand;
+use crate::cli::inspect::InspectArgs;
+use crate::cli::io::load_json;
+use crate::cli::merge::MergeArgs;
+use crate::cli::query::QueryArgs;
+use crate::cli::recompact::RecompactArgs;
+use crate::cli::roundtrip::CheckRoundtripArgs;
+use crate::cli::schema::InferSchemaArgs;
+use crate::cli::signing::{SignArgs, VerifySignatureArgs};
+use crate::cli::split::SplitArgs;
+use crate::cli::sync::SyncArgs;
+use crate::cli::validate::ValidateArgs;
+use crate::cli::visualize::VisualizeArgs;
+
+/// Command-line interface definition for the aideon tools.
+#[derive(Parser, Debug)]
+#[command(
+    author,
+    version,
+    about = "Round-trip JSON-LD, RDF, and Excel data sets."
+)]
+pub(crate) struct Cli {
+    /// Desired minimum log level for the current invocation.
+    #[arg(long, value_enum, default_value_t = LogLevel::Info, global = true)]
+    pub(crate) log_level: LogLevel,
+
+    /// Configuration file declaring conversion defaults such as the
+    /// context, RDF format, prefixes, column order, skipped sheets, and id
+    /// properties. `aideon.toml` in the working directory is used when
+    /// omitted; flags override it.
+    #[arg(long, value_name = "PATH", global = true)]
+    pub(crate) config: Option<PathBuf>,
+
+    /// OTLP/HTTP collector, such as `http://localhost:4318`, receiving the
+    /// traces of the run and its node, triple, and cell counters. Requires
+    /// the `otel` feature.
+    #[arg(long, value_name = "URL", global = true)]
+    pub(crate) otel_endpoint: Option<String>,
+
+    /// Read and convert the inputs of `sync`, `merge`, and `export` without
+    /// writing anything, printing what would be written instead: node,
+    /// type, sheet, and column counts, the values a workbook or CSV output
+    /// would lose, and validation warnings. `recompact` lists the documents
+    /// it would rewrite.
+    #[arg(long, global = true)]
+    pub(crate) dry_run: bool,
+
+    #[command(subcommand)]
+    pub(crate) command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Synchronise two representations of the dataset.
+    Sync(Box<SyncArgs>),
+    /// Check a dataset for structural problems.
+    Validate(ValidateArgs),
+    /// Export the dataset in a publishable, one-way format.
+    Export(ExportArgs),
+    /// Combine two datasets into a single output.
+    Merge(Box<MergeArgs>),
+    /// Merge the nodes that share the values of key predicates.
+    Dedupe(Box<DedupeArgs>),
+    /// Report what a round trip through the workbook would lose or alter.
+    CheckRoundtrip(CheckRoundtripArgs),
+    /// Run a SPARQL query over the dataset.
+    Query(QueryArgs),
+    /// Write one output per partition of the dataset chosen by a SPARQL
+    /// SELECT query.
+    Split(Box<SplitArgs>),
+    /// Print statistics describing the dataset.
+    Inspect(InspectArgs),
+    /// Infer an RDFS and SHACL skeleton describing the types of the dataset.
+    InferSchema(InferSchemaArgs),
+    /// Draw the node graph, or part of it, as a DOT or Mermaid diagram.
+    Visualize(VisualizeArgs),
+    /// Compare two workbooks by their content, ignoring their presentation.
+    Diff(DiffArgs),
+    /// Rewrite existing JSON-LD documents against a new context.
+    Recompact(RecompactArgs),
+    /// List, retrieve, and compare the versions of a history directory.
+    #[command(subcommand)]
+    History(HistoryCommand),
+    /// Write a detached signature of the canonical dataset.
+    Sign(SignArgs),
+    /// Check a dataset against a detached signature.
+    VerifySignature(VerifySignatureArgs),
+}
+
+/// Flags controlling remote JSON-LD context resolution.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct RemoteContextArgs {
+    /// Resolve remote `@context` references such as `https://schema.org`.
+    #[arg(long)]
+    allow_remote_contexts: bool,
+
+    /// IRI prefix a remote context must start with to be loaded (repeatable).
+    /// Any `https://` context is accepted when omitted.
+    #[arg(
+        long = "context-allowlist",
+        value_name = "PREFIX",
+        requires = "allow_remote_contexts"
+    )]
+    context_allowlist: Vec<String>,
+
+    /// Directory used to cache fetched remote contexts.
+    #[arg(long, value_name = "DIR", requires = "allow_remote_contexts")]
+    context_cache_dir: Option<PathBuf>,
+}
+
+impl RemoteContextArgs {
+    pub(crate) fn sync_options(&self) -> SyncOptions {
+        let remote_contexts = self.allow_remote_contexts.then(|| {
+            let loader = RemoteContextLoader::new().with_allowlist(self.context_allowlist.clone());
+            match &self.context_cache_dir {
+                Some(dir) => loader.with_cache_dir(Some(dir.clone())),
+                None => loader,
+            }
+        });
+
+        SyncOptions {
+            remote_contexts,
+            ..SyncOptions::default()
+        }
+    }
+}
+
+/// Safety limits that stop a conversion before its output is written.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct LimitArgs {
+    /// Stop when an input holds more than this many nodes.
+    #[arg(long, value_name = "NODES")]
+    pub(crate) max_nodes: Option<usize>,
+
+    /// Stop when a written workbook or CSV directory would hold more than
+    /// this many cells, headers included.
+    #[arg(long, value_name = "CELLS")]
+    pub(crate) max_cells: Option<usize>,
+}
+
+/// Prefixes abbreviating IRIs in written Turtle, TriG, and headers.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct PrefixArgs {
+    /// JSON object mapping prefixes to the namespaces they abbreviate, such
+    /// as `{"ex": "https://example.com/"}`. Extends the well-known `rdf`,
+    /// `rdfs`, `xsd`, and `schema` prefixes and those of the context.
+    #[arg(long = "prefixes", value_name = "PATH")]
+    path: Option<PathBuf>,
+
+    /// Head workbook and CSV columns with compact IRIs such as `schema:name`;
+    /// `Metadata` records the prefixes used.
+    #[arg(long)]
+    compact_headers: bool,
+}
+
+impl PrefixArgs {
+    /// Adds the prefixes defined by `context`, then those of the prefixes
+    /// file, to the configured ones, which only the file overrides.
+    pub(crate) fn apply(&self, options: &mut SyncOptions, context: Option<&Value>) -> Result<()> {
+        for (prefix, namespace) in context.map(jsonld::context_prefixes).unwrap_or_default() {
+            options.prefixes.entry(prefix).or_insert(namespace);
+        }
+        if let Some(path) = &self.path {
+            let prefixes: BTreeMap<String, String> = serde_json::from_value(load_json(path)?)?;
+            options.prefixes.extend(prefixes);
+        }
+        options.compact_headers |= self.compact_headers;
+        Ok(())
+    }
+}
+
+/// Naming and layout of the sheets and columns of written workbooks.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct LayoutArgs {
+    /// Name the sheets and columns of written workbooks and CSV files with
+    /// the configuration file's labels for this language, such as `fr`,
+    /// instead of its `language`. `Metadata` keeps the IRIs.
+    #[arg(long, value_name = "TAG")]
+    language: Option<String>,
+
+    /// SHACL shapes whose `sh:maxCount` lays out predicates in written
+    /// workbooks and CSV files: a column for one value, an array cell or
+    /// child sheet for more, whatever the data holds.
+    #[arg(long, value_name = "PATH")]
+    cardinality_shapes: Option<PathBuf>,
+
+    /// SHACL shapes whose `sh:group` and `sh:order` band the columns of the
+    /// classes they target in written workbooks and CSV files, labelled
+    /// with the `rdfs:label` of each group.
+    #[arg(long, value_name = "PATH")]
+    column_group_shapes: Option<PathBuf>,
+
+    /// Name the sheets of written workbooks and CSV files after the whole
+    /// IRIs of their types, their local names (such as `Person`), or the
+    /// terms the JSON-LD context gives them, falling back to local names.
+    #[arg(long, value_enum, default_value = "iri")]
+    sheet_names: SheetNamesKind,
+
+    /// JSON file mapping type and predicate IRIs to the names of their
+    /// sheets, such as `{"https://schema.org/Person": "People"}`, ahead of
+    /// `--sheet-names`.
+    #[arg(long, value_name = "PATH")]
+    sheet_name_map: Option<PathBuf>,
+
+    /// RDF file whose `rdfs:subClassOf` statements put the rows of every
+    /// subclass on the sheet of its topmost superclass in written workbooks
+    /// and CSV files, with a `subtype` column giving their types.
+    #[arg(long, value_name = "PATH")]
+    consolidate_subclasses: Option<PathBuf>,
+}
+
+/// Sources of sheet names exposed as CLI values.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum SheetNamesKind {
+    Iri,
+    LocalName,
+    Context,
+}
+
+impl LayoutArgs {
+    /// Replaces the labels of the profile's language with those of
+    /// `--language`, adds the cardinalities of `--cardinality-shapes` and the
+    /// column groups of `--column-group-shapes` to those the profile
+    /// declares, names sheets as `--sheet-names` and
+    /// `--sheet-name-map` ask, heads columns with the terms of `context`, and
+    /// consolidates the subclasses of `--consolidate-subclasses`.
+    pub(crate) fn apply(
+        &self,
+        options: &mut SyncOptions,
+        profile: &Profile,
+        context: Option<&Value>,
+    ) -> Result<()> {
+        if let Some(language) = &self.language {
+            options.labels = profile.labels(language)?;
+        }
+        if let Some(context) = context {
+            let terms = match &options.remote_contexts {
+                Some(loader) => loader
+                    .resolve_context(context)
+                    .and_then(|resolved| jsonld::context_terms(&resolved)),
+                None => jsonld::context_terms(context),
+            };
+            match terms {
+                Ok(terms) => options.header_terms = terms,
+                Err(err) => warn!(error = %err, "cannot head columns with context terms"),
+            }
+        }
+        match self.sheet_names {
+            SheetNamesKind::Iri => {}
+            SheetNamesKind::LocalName => options.sheet_naming = SheetNaming::LocalName,
+            SheetNamesKind::Context => {
+                options.sheet_naming = SheetNaming::LocalName;
+                if let Some(context) = context {
+                    options.sheet_names = jsonld::context_terms(context)?;
+                }
+            }
+        }
+        if let Some(path) = &self.sheet_name_map {
+            let names: BTreeMap<Iri, String> =
+                serde_json::from_value(load_json(path)?).map_err(|err| {
+                    ToolError::InvalidConfig(format!(
+                        "{} must map IRIs to sheet names: {err}",
+                        path.display()
+                    ))
+                })?;
+            options.sheet_names.extend(names);
+        }
+        if let Some(path) = &self.consolidate_subclasses {
+            options.consolidated_types = consolidate_subclasses(&rdf::read_rdf(path, None)?);
+        }
+        if let Some(path) = &self.cardinality_shapes {
+            for (predicate, cardinality) in ShapesGraph::load(path, None)?.cardinalities() {
+                options
+                    .cardinalities
+                    .entry(predicate)
+                    .or_insert(cardinality);
+            }
+        }
+        if let Some(path) = &self.column_group_shapes {
+            for (type_name, groups) in ShapesGraph::load(path, None)?.column_groups() {
+                options.column_groups.entry(type_name).or_insert(groups);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Flags controlling how workbook and CSV cells are read.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct CoercionArgs {
+    /// JSON file mapping literal cell text to the value it reads as, for
+    /// example `{"yes": true, "N/A": null, "∞": "@skip"}`.
+    #[arg(long, value_name = "PATH")]
+    coercions: Option<PathBuf>,
+}
+
+impl CoercionArgs {
+    pub(crate) fn load(&self) -> Result<ScalarCoercions> {
+        match &self.coercions {
+            Some(path) => ScalarCoercions::from_json(&load_json(path)?),
+            None => Ok(ScalarCoercions::default()),
+        }
+    }
+}
+
+/// Delimiter, quoting, and encoding of CSV files read and written.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct CsvArgs {
+    /// Character separating CSV fields, such as `;`, or `\t` for tabs.
+    /// Defaults to `,`.
+    #[arg(long, value_name = "CHAR", value_parser = parse_csv_char)]
+    csv_delimiter: Option<char>,
+
+    /// Character enclosing quoted CSV fields. Defaults to `"`.
+    #[arg(long, value_name = "CHAR", value_parser = parse_csv_char)]
+    csv_quote: Option<char>,
+
+    /// Fields quoted in written CSV files: those that need it (the
+    /// default), all, all but numbers, or none.
+    #[arg(long, value_enum)]
+    csv_quoting: Option<CsvQuotingKind>,
+
+    /// Text encoding of CSV files. Defaults to `utf-8`.
+    #[arg(long, value_enum)]
+    csv_encoding: Option<CsvEncodingKind>,
+
+    /// Start written CSV files with a byte order mark, as Excel expects of
+    /// UTF-8 files. A mark is detected on read either way.
+    #[arg(long)]
+    csv_bom: bool,
+}
+
+impl CsvArgs {
+    /// Overrides the configured dialect with the flags given.
+    pub(crate) fn apply(&self, dialect: &mut CsvDialect) {
+        if let Some(delimiter) = self.csv_delimiter {
+            dialect.delimiter = delimiter;
+        }
+        if let Some(quote) = self.csv_quote {
+            dialect.quote = quote;
+        }
+        if let Some(quoting) = self.csv_quoting {
+            dialect.quoting = quoting.into();
+        }
+        if let Some(encoding) = self.csv_encoding {
+            dialect.encoding = encoding.into();
+        }
+        dialect.bom |= self.csv_bom;
+    }
+}
+
+/// Character encoding of JSON-LD and RDF inputs.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct InputEncodingArgs {
+    /// Character encoding of JSON-LD and RDF inputs. `auto`, the default,
+    /// reads UTF-8 and recognises UTF-16 by its byte order mark or zero
+    /// bytes; Latin-1 has to be named.
+    #[arg(long, value_enum)]
+    input_encoding: Option<TextEncodingKind>,
+}
+
+impl InputEncodingArgs {
+    /// Overrides the configured input encoding with the flag when given.
+    pub(crate) fn apply(&self, encoding: &mut TextEncoding) {
+        if let Some(kind) = self.input_encoding {
+            *encoding = kind.into();
+        }
+    }
+}
+
+/// Reads a CSV delimiter or quote, accepting `\t` for a tab.
+fn parse_csv_char(value: &str) -> std::result::Result<char, String> {
+    let mut chars = value.chars();
+    match (value, chars.next(), chars.next()) {
+        ("\\t" | "tab", _, _) => Ok('\t'),
+        (_, Some(character), None) if character.is_ascii() => Ok(character),
+        _ => Err(format!(
+            "expected a single ASCII character, found '{value}'"
+        )),
+    }
+}
+
+/// Reads an `--id-strategy` value: a strategy name, `keys:` followed by
+/// comma-separated properties, or a template holding `{placeholders}`.
+pub(crate) fn parse_id_strategy(value: &str) -> std::result::Result<IdStrategy, String> {
+    match value {
+        "uuid-v5" => Ok(IdStrategy::UuidV5),
+        "uuid-v4" => Ok(IdStrategy::UuidV4),
+        _ => match value.strip_prefix("keys:") {
+            Some(keys) => Ok(IdStrategy::KeyHash(
+                keys.split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(String::from)
+                    .collect(),
+            )),
+            None if value.contains('{') => Ok(IdStrategy::Template(value.to_string())),
+            None => Err(format!(
+                "expected uuid-v5, uuid-v4, keys:PROPERTY,…, or a template, found '{value}'"
+            )),
+        },
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum SameAsKind {
+    Merge,
+    Keep,
+    Report,
+}
+
+impl From<SameAsKind> for SameAs {
+    fn from(kind: SameAsKind) -> Self {
+        match kind {
+            SameAsKind::Merge => SameAs::Merge,
+            SameAsKind::Keep => SameAs::Keep,
+            SameAsKind::Report => SameAs::Report,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum MergeStrategyKind {
+    PreferLeft,
+    PreferRight,
+    UnionArrays,
+    ErrorOnConflict,
+}
+
+impl From<MergeStrategyKind> for MergeStrategy {
+    fn from(kind: MergeStrategyKind) -> Self {
+        match kind {
+            MergeStrategyKind::PreferLeft => MergeStrategy::PreferLeft,
+            MergeStrategyKind::PreferRight => MergeStrategy::PreferRight,
+            MergeStrategyKind::UnionArrays => MergeStrategy::UnionArrays,
+            MergeStrategyKind::ErrorOnConflict => MergeStrategy::ErrorOnConflict,
+        }
+    }
+}
+
+/// Collects the IRIs of a repeated flag such as `--matrix`.
+pub(crate) fn iri_set(iris: &[String]) -> BTreeSet<Iri> {
+    iris.iter().map(|iri| Iri::from(iri.as_str())).collect()
+}
+
+/// Supported logging levels exposed as CLI values.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub(crate) enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub(crate) fn as_directive(self) -> tracing_subscriber::filter::Directive {
+        use tracing::Level;
+
+        let level = match self {
+            LogLevel::Error => Level::ERROR,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Trace => Level::TRACE,
+        };
+
+        level.into()
+    }
+}
@@ -0,0 +1,134 @@
+//! The `dedupe` subcommand, merging nodes that describe the same thing.
+
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::sync::{self, DedupeKey, SyncOptions};
+use aideon_tools::{Result, ToolError};
+
+use crate::cli::args::{
+    CoercionArgs, CsvArgs, InputEncodingArgs, LayoutArgs, PrefixArgs, RemoteContextArgs,
+};
+use crate::cli::formats::{
+    CellEncodingKind, DataFormat, RdfFormatKind, input_format, output_format,
+};
+use crate::cli::io::{load_context, read_nodes, resolve_rdf_format, write_nodes};
+use crate::cli::report::write_json_report;
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct DedupeArgs {
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Representation of the input; detected from the path when omitted.
+    #[arg(long, value_parser = input_format())]
+    format: Option<DataFormat>,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_input_format: Option<RdfFormatKind>,
+
+    /// Predicate whose values duplicates share, or `type` for their types.
+    /// May be repeated; nodes are merged when they agree on every key.
+    #[arg(long = "key", value_name = "PREDICATE", required = true)]
+    keys: Vec<String>,
+
+    /// Target representation.
+    #[arg(long, value_parser = output_format())]
+    to: DataFormat,
+
+    /// Output file path (a directory for CSV).
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Write the merged clusters as a JSON report.
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+
+    /// Optional JSON-LD context to use when serialising.
+    #[arg(long)]
+    context: Option<PathBuf>,
+
+    /// Explicit RDF serialisation format to use when writing RDF files.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// Encoding of literal cells in written workbooks and CSV files.
+    #[arg(long, value_enum, default_value = "json")]
+    cell_encoding: CellEncodingKind,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+
+    #[command(flatten)]
+    prefixes: PrefixArgs,
+
+    #[command(flatten)]
+    layout: LayoutArgs,
+}
+
+/// Executes the dedupe subcommand, printing one line per cluster of nodes
+/// merged.
+pub(crate) fn execute_dedupe(args: DedupeArgs, profile: &Profile) -> Result<()> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input));
+    }
+
+    let format = args
+        .format
+        .unwrap_or_else(|| DataFormat::detect(&args.input));
+    let rdf_input_format = args.rdf_input_format.map(RdfFormat::from);
+    let mut options = SyncOptions {
+        cell_encoding: args.cell_encoding.into(),
+        coercions: args.coercions.load()?,
+        rdf_input_format,
+        ..args.remote.sync_options()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let nodes = read_nodes(format, &args.input, rdf_input_format, &options)?;
+    let keys: Vec<DedupeKey> = args.keys.iter().map(|key| key.as_str().into()).collect();
+    let deduplication = sync::deduplicate(&nodes, &keys);
+    for cluster in &deduplication.clusters {
+        let graph = cluster
+            .graph
+            .as_deref()
+            .map(|graph| format!(" in {graph}"))
+            .unwrap_or_default();
+        let merged: Vec<&str> = cluster.merged.iter().map(|id| id.as_str()).collect();
+        eprintln!("merged {} into {}{graph}", merged.join(", "), cluster.id);
+    }
+    eprintln!(
+        "merged {} duplicate(s) in {} cluster(s)",
+        nodes.len() - deduplication.nodes.len(),
+        deduplication.clusters.len()
+    );
+    if let Some(report_path) = &args.report {
+        write_json_report(report_path, &deduplication.clusters)?;
+    }
+
+    let context = load_context(args.context.as_ref(), profile)?;
+    args.prefixes.apply(&mut options, context.as_ref())?;
+    args.layout.apply(&mut options, profile, context.as_ref())?;
+    let rdf_format = resolve_rdf_format(args.rdf_format, profile, &args.output)?;
+    write_nodes(
+        args.to,
+        &args.output,
+        &deduplication.nodes,
+        rdf_format,
+        context,
+        &options,
+    )
+}
@@ -0,0 +1,63 @@
+//! The `diff` subcommand, listing the changes between two workbooks.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::diff;
+use aideon_tools::aideon::tools::io::{atomic, excel_read};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use aideon_tools::{Result, ToolError};
+use tracing::info;
+
+use crate::cli::args::CoercionArgs;
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct DiffArgs {
+    /// Older version of the workbook.
+    #[arg(long, value_name = "PATH")]
+    before: PathBuf,
+
+    /// Newer version of the workbook, whose rows the changes point at.
+    #[arg(long, value_name = "PATH")]
+    after: PathBuf,
+
+    /// Where to write the NDJSON change events (`-` for stdout).
+    #[arg(long, value_name = "PATH", default_value = "-")]
+    output: PathBuf,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+}
+
+/// Executes the diff subcommand, writing one NDJSON event per node that
+/// differs between the two workbooks, located on the rows of the newer one.
+pub(crate) fn execute_diff(args: DiffArgs, profile: &Profile) -> Result<()> {
+    for input in [&args.before, &args.after] {
+        if !input.exists() {
+            return Err(ToolError::MissingInput(input.clone()));
+        }
+    }
+    let mut options = SyncOptions {
+        coercions: args.coercions.load()?,
+        ..SyncOptions::default()
+    };
+    profile.apply(&mut options);
+    let before = sync::read_excel(&args.before, &options)?;
+    let after = sync::read_excel(&args.after, &options)?;
+
+    let changes = diff::locate_changes(
+        diff::diff_nodes(&before, &after),
+        &excel_read::locate_rows(&args.after)?,
+    );
+    if args.output.as_os_str() == "-" {
+        diff::write_ndjson(std::io::stdout().lock(), &changes)?;
+    } else {
+        atomic::write_atomically(&args.output, |partial| {
+            diff::write_ndjson(BufWriter::new(File::create(partial)?), &changes)
+        })?;
+    }
+    info!(change_count = changes.len(), "workbook comparison finished");
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! The `export` subcommand, rendering a data set as a site or snippets.
+
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::report::DryRunReport;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::aideon::tools::{site, snippet};
+use aideon_tools::{Result, ToolError};
+use clap::ValueEnum;
+use tracing::info;
+
+use crate::cli::args::{CoercionArgs, CsvArgs, InputEncodingArgs, RemoteContextArgs};
+use crate::cli::formats::{
+    DataFormat, ExportFormat, JsonLdVersionKind, RdfFormatKind, input_format,
+};
+use crate::cli::io::{load_context, read_nodes};
+use crate::cli::report::print_dry_run;
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct ExportArgs {
+    /// Source representation.
+    #[arg(long, value_parser = input_format())]
+    from: DataFormat,
+
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Export format.
+    #[arg(long, value_enum)]
+    to: ExportFormat,
+
+    /// Output path (a directory for HTML).
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// JSON-LD context value used to compact snippets: a term map or an IRI
+    /// string such as `"https://schema.org"`.
+    #[arg(long)]
+    context: Option<PathBuf>,
+
+    /// JSON-LD version of the snippets; `1.0` suits legacy parsers.
+    #[arg(long, value_enum, default_value = "1.1")]
+    json_ld_version: JsonLdVersionKind,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+}
+
+/// Executes the export subcommand, rendering the input in a publishable form.
+pub(crate) fn execute_export(args: ExportArgs, profile: &Profile, dry_run: bool) -> Result<()> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input));
+    }
+
+    let mut options = SyncOptions {
+        jsonld_version: args.json_ld_version.into(),
+        coercions: args.coercions.load()?,
+        ..args.remote.sync_options()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let rdf_format = args.rdf_format.map(RdfFormat::from);
+    let nodes = read_nodes(args.from, &args.input, rdf_format, &options)?;
+    if dry_run {
+        let report = DryRunReport::for_graph(&nodes);
+        let format = args
+            .to
+            .to_possible_value()
+            .expect("export formats are named");
+        return print_dry_run(&report, &args.output, format.get_name());
+    }
+    info!(node_count = nodes.len(), to = ?args.to, "exporting nodes");
+    match args.to {
+        ExportFormat::Html => site::write_site(&args.output, &nodes),
+        ExportFormat::JsonLdSnippets => {
+            let context = load_context(args.context.as_ref(), profile)?;
+            snippet::write_snippets(&args.output, &nodes, context.as_ref(), &options)
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Data formats, serialisations, and encodings chosen on the command line,
+//! mapped onto their library counterparts.
+
+use std::path::Path;
+
+use aideon_tools::aideon::tools::flatten::CellEncoding;
+use aideon_tools::aideon::tools::io::csv_dialect::{CsvEncoding, CsvQuoting};
+use aideon_tools::aideon::tools::io::jsonld::JsonLdVersion;
+use aideon_tools::aideon::tools::io::rdf::{JsonLdProfileSet, RdfFormat};
+use aideon_tools::aideon::tools::io::text_encoding::TextEncoding;
+use aideon_tools::aideon::tools::query::QueryResultsFormat;
+use aideon_tools::aideon::tools::visualize::GraphFormat;
+use clap::ValueEnum;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum DataFormat {
+    JsonLd,
+    Excel,
+    Rdf,
+    Csv,
+    Sql,
+    NodesJson,
+    Parquet,
+    Sqlite,
+}
+
+impl DataFormat {
+    /// Infers the representation stored at `path`: directories hold CSV,
+    /// `.xlsx` files are workbooks, `.nodes.json` files node documents,
+    /// other `.jsonld` and `.json` files JSON-LD, and anything else is read
+    /// as RDF.
+    pub(crate) fn detect(path: &Path) -> Self {
+        if path.is_dir() {
+            return DataFormat::Csv;
+        }
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_ascii_lowercase());
+        if name.is_some_and(|name| name.ends_with(".nodes.json")) {
+            return DataFormat::NodesJson;
+        }
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("xlsx") => DataFormat::Excel,
+            Some("jsonld" | "json") => DataFormat::JsonLd,
+            _ => DataFormat::Rdf,
+        }
+    }
+}
+
+impl std::fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataFormat::JsonLd => write!(f, "jsonld"),
+            DataFormat::Excel => write!(f, "xlsx"),
+            DataFormat::Rdf => write!(f, "rdf"),
+            DataFormat::Csv => write!(f, "csv"),
+            DataFormat::Sql => write!(f, "sql"),
+            DataFormat::NodesJson => write!(f, "nodes-json"),
+            DataFormat::Parquet => write!(f, "parquet"),
+            DataFormat::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+/// Parses the representation of an input, leaving out those that can only
+/// be written, so clap rejects them like any value it does not know.
+pub(crate) fn input_format() -> impl TypedValueParser<Value = DataFormat> {
+    formats(|format| !matches!(format, DataFormat::Parquet | DataFormat::Sqlite))
+}
+
+/// Parses the representation of an output, leaving out SQL sources, which
+/// can only be read.
+pub(crate) fn output_format() -> impl TypedValueParser<Value = DataFormat> {
+    formats(|format| !matches!(format, DataFormat::Sql))
+}
+
+fn formats(keep: fn(DataFormat) -> bool) -> impl TypedValueParser<Value = DataFormat> {
+    let formats = DataFormat::value_variants()
+        .iter()
+        .copied()
+        .filter(move |format| keep(*format));
+    PossibleValuesParser::new(formats.filter_map(|format| format.to_possible_value()))
+        .map(|name| <DataFormat as ValueEnum>::from_str(&name, false).expect("a listed format"))
+}
+
+/// One-way export targets.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum ExportFormat {
+    /// Static HTML site with one page per node and an index per type.
+    Html,
+    /// HTML fragment with one `<script type="application/ld+json">` per node.
+    JsonLdSnippets,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum RdfFormatKind {
+    Turtle,
+    NTriples,
+    NQuads,
+    TriG,
+    JsonLd,
+    N3,
+}
+
+impl From<RdfFormatKind> for RdfFormat {
+    fn from(kind: RdfFormatKind) -> Self {
+        match kind {
+            RdfFormatKind::Turtle => RdfFormat::Turtle,
+            RdfFormatKind::NTriples => RdfFormat::NTriples,
+            RdfFormatKind::NQuads => RdfFormat::NQuads,
+            RdfFormatKind::TriG => RdfFormat::TriG,
+            RdfFormatKind::JsonLd => RdfFormat::JsonLd {
+                profile: JsonLdProfileSet::empty(),
+            },
+            RdfFormatKind::N3 => RdfFormat::N3,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum JsonLdVersionKind {
+    #[value(name = "1.0")]
+    V1_0,
+    #[value(name = "1.1")]
+    V1_1,
+}
+
+impl From<JsonLdVersionKind> for JsonLdVersion {
+    fn from(kind: JsonLdVersionKind) -> Self {
+        match kind {
+            JsonLdVersionKind::V1_0 => JsonLdVersion::V1_0,
+            JsonLdVersionKind::V1_1 => JsonLdVersion::V1_1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum CellEncodingKind {
+    Json,
+    Bare,
+}
+
+impl From<CellEncodingKind> for CellEncoding {
+    fn from(kind: CellEncodingKind) -> Self {
+        match kind {
+            CellEncodingKind::Json => CellEncoding::Json,
+            CellEncodingKind::Bare => CellEncoding::Bare,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum CsvQuotingKind {
+    Necessary,
+    Always,
+    NonNumeric,
+    Never,
+}
+
+impl From<CsvQuotingKind> for CsvQuoting {
+    fn from(kind: CsvQuotingKind) -> Self {
+        match kind {
+            CsvQuotingKind::Necessary => CsvQuoting::Necessary,
+            CsvQuotingKind::Always => CsvQuoting::Always,
+            CsvQuotingKind::NonNumeric => CsvQuoting::NonNumeric,
+            CsvQuotingKind::Never => CsvQuoting::Never,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum CsvEncodingKind {
+    #[value(name = "utf-8")]
+    Utf8,
+    #[value(name = "utf-16le")]
+    Utf16Le,
+    #[value(name = "utf-16be")]
+    Utf16Be,
+    #[value(name = "windows-1252")]
+    Windows1252,
+}
+
+impl From<CsvEncodingKind> for CsvEncoding {
+    fn from(kind: CsvEncodingKind) -> Self {
+        match kind {
+            CsvEncodingKind::Utf8 => CsvEncoding::Utf8,
+            CsvEncodingKind::Utf16Le => CsvEncoding::Utf16Le,
+            CsvEncodingKind::Utf16Be => CsvEncoding::Utf16Be,
+            CsvEncodingKind::Windows1252 => CsvEncoding::Windows1252,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum TextEncodingKind {
+    Auto,
+    #[value(name = "utf-8")]
+    Utf8,
+    #[value(name = "utf-16le")]
+    Utf16Le,
+    #[value(name = "utf-16be")]
+    Utf16Be,
+    #[value(name = "latin-1")]
+    Latin1,
+}
+
+impl From<TextEncodingKind> for TextEncoding {
+    fn from(kind: TextEncodingKind) -> Self {
+        match kind {
+            TextEncodingKind::Auto => TextEncoding::Auto,
+            TextEncodingKind::Utf8 => TextEncoding::Utf8,
+            TextEncodingKind::Utf16Le => TextEncoding::Utf16Le,
+            TextEncodingKind::Utf16Be => TextEncoding::Utf16Be,
+            TextEncodingKind::Latin1 => TextEncoding::Latin1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum ResultsFormatKind {
+    Csv,
+    Tsv,
+    Json,
+}
+
+impl From<ResultsFormatKind> for QueryResultsFormat {
+    fn from(kind: ResultsFormatKind) -> Self {
+        match kind {
+            ResultsFormatKind::Csv => QueryResultsFormat::Csv,
+            ResultsFormatKind::Tsv => QueryResultsFormat::Tsv,
+            ResultsFormatKind::Json => QueryResultsFormat::Json,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum GraphFormatKind {
+    Dot,
+    Mermaid,
+}
+
+impl From<GraphFormatKind> for GraphFormat {
+    fn from(kind: GraphFormatKind) -> Self {
+        match kind {
+            GraphFormatKind::Dot => GraphFormat::Dot,
+            GraphFormatKind::Mermaid => GraphFormat::Mermaid,
+        }
+    }
+}
+
+/// Vocabulary of change feed events.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum ChangeFeedFormat {
+    /// ActivityStreams `Create`, `Update`, and `Delete` activities.
+    ActivityStreams,
+    /// Versioned members of a Linked Data Event Stream.
+    Ldes,
+}
@@ -0,0 +1,133 @@
+//! The `history` subcommands, browsing the versions committed by
+//! `sync --history`.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::diff;
+use aideon_tools::aideon::tools::history::{self, History};
+use aideon_tools::aideon::tools::io::atomic;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::{Result, ToolError};
+use clap::Subcommand;
+use tracing::info;
+
+use crate::cli::formats::{DataFormat, RdfFormatKind, output_format};
+use crate::cli::io::{load_context, resolve_rdf_format, write_nodes};
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum HistoryCommand {
+    /// List the versions, oldest first, with their number, hash, commit
+    /// time, and node count.
+    Log(HistoryLogArgs),
+    /// Write the dataset as it was at a version.
+    Show(HistoryShowArgs),
+    /// Compare two versions, writing one NDJSON event per changed node.
+    Diff(HistoryDiffArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct HistoryLogArgs {
+    /// History directory written by `sync --history`.
+    #[arg(long, value_name = "DIR")]
+    dir: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct HistoryShowArgs {
+    /// History directory written by `sync --history`.
+    #[arg(long, value_name = "DIR")]
+    dir: PathBuf,
+
+    /// Version to write: its number, a prefix of its hash, or an RFC 3339
+    /// time or date picking the version current then.
+    #[arg(long, value_name = "VERSION")]
+    as_of: String,
+
+    /// Representation to write the version in.
+    #[arg(long, value_parser = output_format())]
+    to: DataFormat,
+
+    /// Output file path (a directory for CSV).
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Explicit RDF serialisation format of the output.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// JSON-LD context compacting a JSON-LD output.
+    #[arg(long)]
+    context: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct HistoryDiffArgs {
+    /// History directory written by `sync --history`.
+    #[arg(long, value_name = "DIR")]
+    dir: PathBuf,
+
+    /// Older version, named like `history show --as-of`.
+    #[arg(long, value_name = "VERSION")]
+    before: String,
+
+    /// Newer version; the latest when omitted.
+    #[arg(long, value_name = "VERSION")]
+    after: Option<String>,
+
+    /// Where to write the NDJSON change events (`-` for stdout).
+    #[arg(long, value_name = "PATH", default_value = "-")]
+    output: PathBuf,
+}
+
+/// Executes the history subcommands over the versions committed by
+/// `sync --history`.
+pub(crate) fn execute_history(command: HistoryCommand, profile: &Profile) -> Result<()> {
+    match command {
+        HistoryCommand::Log(args) => {
+            for version in History::open(&args.dir)?.versions() {
+                println!("{}", history::describe(version));
+            }
+        }
+        HistoryCommand::Show(args) => {
+            let history = History::open(&args.dir)?;
+            let version = history.resolve(&args.as_of)?;
+            let nodes = history.load(version)?;
+            let context = load_context(args.context.as_ref(), profile)?;
+            let mut options = SyncOptions::default();
+            profile.apply(&mut options);
+            let rdf_format = resolve_rdf_format(args.rdf_format, profile, &args.output)?;
+            write_nodes(args.to, &args.output, &nodes, rdf_format, context, &options)?;
+            info!(
+                version = version.number,
+                node_count = nodes.len(),
+                "wrote dataset version"
+            );
+        }
+        HistoryCommand::Diff(args) => {
+            let history = History::open(&args.dir)?;
+            let before = history.load(history.resolve(&args.before)?)?;
+            let after = match &args.after {
+                Some(spec) => history.resolve(spec)?,
+                None => history.latest().ok_or_else(|| {
+                    ToolError::InvalidConfig(format!(
+                        "no version in the history {}",
+                        args.dir.display()
+                    ))
+                })?,
+            };
+            let changes = diff::diff_nodes(&before, &history.load(after)?);
+            if args.output.as_os_str() == "-" {
+                diff::write_ndjson(std::io::stdout().lock(), &changes)?;
+            } else {
+                atomic::write_atomically(&args.output, |partial| {
+                    diff::write_ndjson(BufWriter::new(File::create(partial)?), &changes)
+                })?;
+            }
+            info!(change_count = changes.len(), "version comparison finished");
+        }
+    }
+    Ok(())
+}
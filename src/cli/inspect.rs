@@ -0,0 +1,115 @@
+//! The `inspect` subcommand, printing the statistics of a data set.
+
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::inspect::DatasetStatistics;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::report::NONE_LABEL;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::{Result, ToolError};
+use tracing::info;
+
+use crate::cli::args::{CoercionArgs, CsvArgs, InputEncodingArgs, RemoteContextArgs};
+use crate::cli::formats::{DataFormat, RdfFormatKind, input_format};
+use crate::cli::io::read_nodes;
+use crate::cli::report::write_json_report;
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct InspectArgs {
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Representation of the input; detected from the path when omitted.
+    #[arg(long, value_parser = input_format())]
+    format: Option<DataFormat>,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// Number of sheets, the largest first, to print.
+    #[arg(long, value_name = "SHEETS", default_value_t = 10)]
+    top: usize,
+
+    /// Write the statistics, with every sheet, as a JSON report.
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+}
+
+/// Executes the inspect subcommand, printing the statistics of the input.
+pub(crate) fn execute_inspect(args: InspectArgs, profile: &Profile) -> Result<()> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input));
+    }
+
+    let format = args
+        .format
+        .unwrap_or_else(|| DataFormat::detect(&args.input));
+    let rdf_format = args.rdf_format.map(RdfFormat::from);
+    let mut options = SyncOptions {
+        coercions: args.coercions.load()?,
+        ..args.remote.sync_options()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let nodes = read_nodes(format, &args.input, rdf_format, &options)?;
+    let statistics = DatasetStatistics::with_sheets(&nodes, &options.workbook_options())?;
+
+    println!("nodes: {}", statistics.node_count);
+    for (type_name, count) in &statistics.type_counts {
+        println!("type: {type_name}: {count} node(s)");
+    }
+    if statistics.untyped_count > 0 {
+        println!("type: {NONE_LABEL}: {} node(s)", statistics.untyped_count);
+    }
+    for usage in &statistics.graphs {
+        let graph = usage.graph.as_deref().unwrap_or(NONE_LABEL);
+        println!("graph: {graph}: {} node(s)", usage.node_count);
+    }
+    for (predicate, usage) in statistics.predicates_by_use() {
+        println!(
+            "predicate: {predicate}: {} value(s) on {} node(s)",
+            usage.value_count, usage.node_count
+        );
+    }
+    for (datatype, count) in &statistics.datatype_counts {
+        println!("datatype: {datatype}: {count} literal(s)");
+    }
+    for orphan in &statistics.orphan_references {
+        println!(
+            "orphan: {} [{}]: {}",
+            orphan.node, orphan.predicate, orphan.target
+        );
+    }
+    for sheet in statistics.sheets.iter().take(args.top) {
+        println!(
+            "sheet: {}: {} row(s), {} column(s)",
+            sheet.sheet_name, sheet.row_count, sheet.column_count
+        );
+    }
+    info!(
+        node_count = statistics.node_count,
+        orphans = statistics.orphan_references.len(),
+        "inspection finished"
+    );
+
+    if let Some(report_path) = &args.report {
+        write_json_report(report_path, &statistics)?;
+    }
+    Ok(())
+}
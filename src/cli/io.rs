@@ -0,0 +1,156 @@
+//! Reading and writing the nodes of the data formats the subcommands take,
+//! and the contexts and JSON files their flags point at.
+
+use std::path::{Path, PathBuf};
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::flatten::build_workbook_with_options;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::io::{csv_write, excel_write, nodes_json, parquet_write, sql};
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use aideon_tools::aideon::tools::validate::ValidationReport;
+use aideon_tools::{Result, ToolError};
+use serde_json::Value;
+
+use crate::cli::formats::{DataFormat, RdfFormatKind};
+
+/// Returns whether `path` is `-`, standing for stdin or stdout.
+pub(crate) fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// Reads the node set stored at `path` in the given representation. The RDF
+/// serialisation is detected from the extension when `rdf_format` is `None`.
+pub(crate) fn read_nodes(
+    format: DataFormat,
+    path: &Path,
+    rdf_format: Option<RdfFormat>,
+    options: &SyncOptions,
+) -> Result<Vec<Node>> {
+    match format {
+        DataFormat::JsonLd => sync::read_jsonld(path, options),
+        DataFormat::Excel => sync::read_excel(path, options),
+        DataFormat::Rdf => sync::read_rdf(path, rdf_format, options),
+        DataFormat::Csv => sync::read_csv(path, options),
+        DataFormat::Sql => sync::read_sql(path, options),
+        DataFormat::NodesJson => sync::read_nodes_json(path, options),
+        DataFormat::Parquet | DataFormat::Sqlite => Err(output_only(format)),
+    }
+}
+
+/// Reads the node set stored at `path` like [`read_nodes`], leaving out the
+/// cells of workbooks and CSV directories and the RDF statements that cannot
+/// be read and returning them as errors. JSON-LD and SQL inputs are read
+/// whole or not at all.
+pub(crate) fn read_nodes_keep_going(
+    format: DataFormat,
+    path: &Path,
+    rdf_format: Option<RdfFormat>,
+    options: &SyncOptions,
+) -> Result<(Vec<Node>, ValidationReport)> {
+    match format {
+        DataFormat::Excel => sync::read_excel_keep_going(path, options),
+        DataFormat::Csv => sync::read_csv_keep_going(path, options),
+        DataFormat::Rdf => sync::read_rdf_keep_going(path, rdf_format, options),
+        format => Ok((
+            read_nodes(format, path, rdf_format, options)?,
+            ValidationReport::default(),
+        )),
+    }
+}
+
+/// Writes `nodes` to `path` in the given representation.
+pub(crate) fn write_nodes(
+    format: DataFormat,
+    path: &Path,
+    nodes: &[Node],
+    rdf_format: RdfFormat,
+    context: Option<Value>,
+    options: &SyncOptions,
+) -> Result<()> {
+    match format {
+        DataFormat::JsonLd => sync::write_jsonld(nodes, path, context, options),
+        DataFormat::Excel => excel_write::write_workbook(
+            path,
+            &build_workbook_with_options(nodes, &options.workbook_options())?,
+        ),
+        DataFormat::Rdf => sync::write_rdf(nodes, path, rdf_format, context.as_ref(), options),
+        DataFormat::Csv => csv_write::write_workbook_in_dialect(
+            path,
+            &build_workbook_with_options(nodes, &options.workbook_options())?,
+            &options.csv_dialect,
+        ),
+        DataFormat::Sql => Err(sql_output()),
+        DataFormat::NodesJson => nodes_json::write_nodes_json(path, nodes),
+        DataFormat::Parquet => parquet_write::write_workbook(
+            path,
+            &build_workbook_with_options(nodes, &options.workbook_options())?,
+        ),
+        DataFormat::Sqlite => sql::write_sqlite(
+            path,
+            &build_workbook_with_options(nodes, &options.workbook_options())?,
+        ),
+    }
+}
+
+/// Returns the extension of files written as `format`, RDF as
+/// `rdf_format`; CSV and Parquet outputs are directories without one.
+pub(crate) fn output_extension(format: DataFormat, rdf_format: RdfFormat) -> Result<&'static str> {
+    Ok(match format {
+        DataFormat::JsonLd => "jsonld",
+        DataFormat::Excel => "xlsx",
+        DataFormat::Csv | DataFormat::Parquet => "",
+        DataFormat::Sql => return Err(sql_output()),
+        DataFormat::NodesJson => "nodes.json",
+        DataFormat::Sqlite => "db",
+        DataFormat::Rdf => rdf_format.file_extension(),
+    })
+}
+
+/// The error for conversions asked to write to a SQL database, which can
+/// only be read.
+pub(crate) fn sql_output() -> ToolError {
+    ToolError::InvalidConfig("SQL sources can be read but not written".into())
+}
+
+/// The error for conversions asked to read Parquet files or SQLite
+/// databases, which can only be written.
+pub(crate) fn output_only(format: DataFormat) -> ToolError {
+    ToolError::InvalidConfig(format!("{format} outputs can be written but not read"))
+}
+
+/// Attempts to infer the RDF serialisation from a target path when none was
+/// provided explicitly.
+pub(crate) fn guess_rdf_format(path: &Path) -> RdfFormat {
+    aideon_tools::io::rdf::detect_format(path).unwrap_or(RdfFormat::Turtle)
+}
+
+/// Returns the RDF serialisation to write `output` in: the explicit
+/// `--rdf-format`, the profile's, or the one inferred from the extension.
+pub(crate) fn resolve_rdf_format(
+    explicit: Option<RdfFormatKind>,
+    profile: &Profile,
+    output: &Path,
+) -> Result<RdfFormat> {
+    Ok(match explicit.map(RdfFormat::from) {
+        Some(format) => format,
+        None => profile
+            .rdf_format()?
+            .unwrap_or_else(|| guess_rdf_format(output)),
+    })
+}
+
+/// Loads the JSON-LD context given with `--context`, or else the profile's.
+pub(crate) fn load_context(explicit: Option<&PathBuf>, profile: &Profile) -> Result<Option<Value>> {
+    match explicit.or(profile.context.as_ref()) {
+        Some(path) => Ok(Some(load_json(path)?)),
+        None => Ok(None),
+    }
+}
+
+/// Loads a JSON value from the supplied path.
+pub(crate) fn load_json(path: &PathBuf) -> Result<Value> {
+    let data = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
@@ -0,0 +1,150 @@
+//! The `merge` subcommand, combining two data sets into one.
+
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::merge_prompt::ConflictPrompt;
+use aideon_tools::aideon::tools::sync::{self, MergeStrategy, SyncOptions};
+use aideon_tools::{Result, ToolError};
+
+use crate::cli::args::{
+    CoercionArgs, CsvArgs, InputEncodingArgs, LayoutArgs, LimitArgs, MergeStrategyKind, PrefixArgs,
+    RemoteContextArgs, iri_set,
+};
+use crate::cli::formats::{
+    CellEncodingKind, DataFormat, RdfFormatKind, input_format, output_format,
+};
+use crate::cli::io::{load_context, read_nodes, resolve_rdf_format, write_nodes};
+use crate::cli::report::{preview, print_dry_run};
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct MergeArgs {
+    /// First input path (a directory for CSV).
+    #[arg(long)]
+    left: PathBuf,
+
+    /// Representation of the first input.
+    #[arg(long, value_parser = input_format())]
+    left_format: DataFormat,
+
+    /// Second input path (a directory for CSV).
+    #[arg(long)]
+    right: PathBuf,
+
+    /// Representation of the second input.
+    #[arg(long, value_parser = input_format())]
+    right_format: DataFormat,
+
+    /// Target representation.
+    #[arg(long, value_parser = output_format())]
+    to: DataFormat,
+
+    /// Output file path (a directory for CSV).
+    #[arg(long)]
+    output: PathBuf,
+
+    /// How to resolve predicates whose values differ between the inputs
+    /// when they are not resolved at a prompt.
+    #[arg(long, value_enum, default_value = "prefer-left")]
+    strategy: MergeStrategyKind,
+
+    /// Resolve conflicts with `--strategy` instead of prompting for each
+    /// one when run in a terminal.
+    #[arg(long)]
+    non_interactive: bool,
+
+    /// Optional JSON-LD context to use when serialising.
+    #[arg(long)]
+    context: Option<PathBuf>,
+
+    /// Explicit RDF serialisation format to use when writing RDF files.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// Encoding of literal cells in written workbooks and CSV files.
+    #[arg(long, value_enum, default_value = "json")]
+    cell_encoding: CellEncodingKind,
+
+    /// Predicate written as a matrix sheet in workbooks and CSV files. May
+    /// be repeated.
+    #[arg(long = "matrix", value_name = "PREDICATE")]
+    matrix_predicates: Vec<String>,
+
+    /// Predicate whose values are written to RDF as an ordered `rdf:List`.
+    /// May be repeated.
+    #[arg(long = "rdf-list", value_name = "PREDICATE")]
+    rdf_lists: Vec<String>,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+
+    #[command(flatten)]
+    limits: LimitArgs,
+
+    #[command(flatten)]
+    prefixes: PrefixArgs,
+
+    #[command(flatten)]
+    layout: LayoutArgs,
+}
+
+impl MergeArgs {
+    pub(crate) fn sync_options(&self) -> SyncOptions {
+        SyncOptions {
+            cell_encoding: self.cell_encoding.into(),
+            matrix_predicates: iri_set(&self.matrix_predicates),
+            rdf_lists: iri_set(&self.rdf_lists),
+            max_nodes: self.limits.max_nodes,
+            max_cells: self.limits.max_cells,
+            ..self.remote.sync_options()
+        }
+    }
+}
+
+/// Executes the merge subcommand, combining both inputs into the output.
+pub(crate) fn execute_merge(args: MergeArgs, profile: &Profile, dry_run: bool) -> Result<()> {
+    for input in [&args.left, &args.right] {
+        if !input.exists() {
+            return Err(ToolError::MissingInput(input.clone()));
+        }
+    }
+
+    let mut options = args.sync_options();
+    options.coercions = args.coercions.load()?;
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let left = read_nodes(args.left_format, &args.left, None, &options)?;
+    let right = read_nodes(args.right_format, &args.right, None, &options)?;
+    let strategy = MergeStrategy::from(args.strategy);
+    let interactive = !args.non_interactive
+        && !dry_run
+        && std::io::stdin().is_terminal()
+        && std::io::stderr().is_terminal();
+    let nodes = if interactive {
+        let mut prompt = ConflictPrompt::new(std::io::stdin().lock(), std::io::stderr());
+        sync::merge_with(&left, &right, |conflict| prompt.resolve(conflict))?
+    } else {
+        sync::merge(&left, &right, strategy)?
+    };
+
+    let context = load_context(args.context.as_ref(), profile)?;
+    args.prefixes.apply(&mut options, context.as_ref())?;
+    args.layout.apply(&mut options, profile, context.as_ref())?;
+    if dry_run {
+        let report = preview(args.to, &nodes, &options)?;
+        return print_dry_run(&report, &args.output, &args.to.to_string());
+    }
+    let rdf_format = resolve_rdf_format(args.rdf_format, profile, &args.output)?;
+    write_nodes(args.to, &args.output, &nodes, rdf_format, context, &options)
+}
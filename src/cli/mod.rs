@@ -0,0 +1,83 @@
+//! Subcommands of the aideon-tools binary: their flags, parsed with clap, and
+//! the routines running them against the library.
+
+pub(crate) mod args;
+mod dedupe;
+mod diff;
+mod export;
+mod formats;
+mod history;
+mod inspect;
+mod io;
+mod merge;
+mod query;
+mod recompact;
+mod report;
+mod roundtrip;
+mod schema;
+mod signing;
+mod split;
+mod sync;
+mod validate;
+mod visualize;
+
+use std::path::Path;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::{Result, ToolError};
+use tracing::debug;
+
+use crate::cli::args::{Cli, Command};
+use crate::cli::dedupe::execute_dedupe;
+use crate::cli::diff::execute_diff;
+use crate::cli::export::execute_export;
+use crate::cli::history::execute_history;
+use crate::cli::inspect::execute_inspect;
+use crate::cli::merge::execute_merge;
+use crate::cli::query::execute_query;
+use crate::cli::recompact::execute_recompact;
+use crate::cli::roundtrip::execute_check_roundtrip;
+use crate::cli::schema::execute_infer_schema;
+use crate::cli::signing::{execute_sign, execute_verify_signature};
+use crate::cli::split::execute_split;
+use crate::cli::sync::execute_sync;
+use crate::cli::validate::execute_validate;
+use crate::cli::visualize::execute_visualize;
+
+/// Executes the CLI command selected by the user.
+pub(crate) fn run(cli: Cli) -> Result<()> {
+    let profile = match &cli.config {
+        Some(path) => Profile::load(path)?,
+        None => Profile::discover(Path::new("."))?.unwrap_or_default(),
+    };
+    debug!(command = ?cli.command, ?profile, "dispatching command");
+    let dry_run = cli.dry_run;
+    if dry_run
+        && !matches!(
+            cli.command,
+            Command::Sync(_) | Command::Merge(_) | Command::Export(_) | Command::Recompact(_)
+        )
+    {
+        return Err(ToolError::InvalidConfig(
+            "--dry-run previews sync, merge, export, and recompact only".into(),
+        ));
+    }
+    match cli.command {
+        Command::Sync(args) => execute_sync(*args, &profile, dry_run),
+        Command::Validate(args) => execute_validate(args, &profile),
+        Command::Export(args) => execute_export(args, &profile, dry_run),
+        Command::Merge(args) => execute_merge(*args, &profile, dry_run),
+        Command::Dedupe(args) => execute_dedupe(*args, &profile),
+        Command::CheckRoundtrip(args) => execute_check_roundtrip(args, &profile),
+        Command::Query(args) => execute_query(args, &profile),
+        Command::Split(args) => execute_split(*args, &profile),
+        Command::Inspect(args) => execute_inspect(args, &profile),
+        Command::InferSchema(args) => execute_infer_schema(args, &profile),
+        Command::Visualize(args) => execute_visualize(args, &profile),
+        Command::Diff(args) => execute_diff(args, &profile),
+        Command::Recompact(args) => execute_recompact(args, &profile, dry_run),
+        Command::History(args) => execute_history(args, &profile),
+        Command::Sign(args) => execute_sign(args, &profile),
+        Command::VerifySignature(args) => execute_verify_signature(args, &profile),
+    }
+}
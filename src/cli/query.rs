@@ -0,0 +1,112 @@
+//! The `query` subcommand, running SPARQL over a data set.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::io::atomic;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::query::{self, QueryOutputFormat, QueryResultsFormat};
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::{Result, ToolError};
+use tracing::info;
+
+use crate::cli::args::{CoercionArgs, CsvArgs, InputEncodingArgs, RemoteContextArgs};
+use crate::cli::formats::{DataFormat, RdfFormatKind, ResultsFormatKind, input_format};
+use crate::cli::io::{guess_rdf_format, read_nodes};
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct QueryArgs {
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Representation of the input; detected from the path when omitted.
+    #[arg(long, value_parser = input_format())]
+    format: Option<DataFormat>,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// File holding the SPARQL query.
+    #[arg(long, value_name = "PATH")]
+    sparql: PathBuf,
+
+    /// Output file path, or `-` for stdout.
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Format of SELECT and ASK results; detected from the output extension
+    /// when omitted, defaulting to CSV.
+    #[arg(long, value_enum)]
+    results_format: Option<ResultsFormatKind>,
+
+    /// RDF serialisation of CONSTRUCT and DESCRIBE results; detected from the
+    /// output extension when omitted, defaulting to Turtle.
+    #[arg(long, value_enum)]
+    graph_format: Option<RdfFormatKind>,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+}
+
+/// Executes the query subcommand, writing the results of the SPARQL query.
+pub(crate) fn execute_query(args: QueryArgs, profile: &Profile) -> Result<()> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input));
+    }
+
+    let format = args
+        .format
+        .unwrap_or_else(|| DataFormat::detect(&args.input));
+    let rdf_format = args.rdf_format.map(RdfFormat::from);
+    let mut options = SyncOptions {
+        coercions: args.coercions.load()?,
+        ..args.remote.sync_options()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let nodes = read_nodes(format, &args.input, rdf_format, &options)?;
+    let sparql = std::fs::read_to_string(&args.sparql)?;
+
+    let extension = args
+        .output
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let output_format = QueryOutputFormat {
+        results: args
+            .results_format
+            .map(QueryResultsFormat::from)
+            .or_else(|| QueryResultsFormat::from_extension(&extension))
+            .unwrap_or(QueryResultsFormat::Csv),
+        graph: args
+            .graph_format
+            .map(RdfFormat::from)
+            .unwrap_or_else(|| guess_rdf_format(&args.output)),
+    };
+
+    let form = if args.output == Path::new("-") {
+        query::run_query(&nodes, &sparql, std::io::stdout().lock(), output_format)?
+    } else {
+        atomic::write_atomically(&args.output, |partial| {
+            let writer = BufWriter::new(File::create(partial)?);
+            query::run_query(&nodes, &sparql, writer, output_format)
+        })?
+    };
+    info!(node_count = nodes.len(), ?form, "query finished");
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! The `recompact` subcommand, rewriting JSON-LD documents against a new
+//! context.
+
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::batch::{self, Glob};
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::interrupt;
+use aideon_tools::aideon::tools::io::atomic;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use aideon_tools::{Result, ToolError};
+use serde_json::Value;
+use tracing::{error, info};
+
+use crate::cli::args::{InputEncodingArgs, RemoteContextArgs};
+use crate::cli::formats::JsonLdVersionKind;
+use crate::cli::io::load_context;
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct RecompactArgs {
+    /// Directory holding the JSON-LD documents to rewrite.
+    #[arg(long, value_name = "DIR")]
+    input_dir: PathBuf,
+
+    /// Pattern selecting the documents rewritten from `--input-dir`.
+    #[arg(long, value_name = "PATTERN", default_value = "*.jsonld")]
+    glob: String,
+
+    /// Directory receiving the rewritten documents, at the relative paths of
+    /// their inputs. Documents are rewritten in place when omitted.
+    #[arg(long, value_name = "DIR")]
+    output_dir: Option<PathBuf>,
+
+    /// JSON-LD context the documents are compacted with; the configured
+    /// context when omitted.
+    #[arg(long)]
+    context: Option<PathBuf>,
+
+    /// JSON-LD version of the rewritten documents.
+    #[arg(long, value_enum, default_value = "1.1")]
+    json_ld_version: JsonLdVersionKind,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+}
+
+/// Executes the recompact subcommand, rewriting every JSON-LD document below
+/// `--input-dir` matching `--glob` against the new context. Documents that
+/// already match it are left untouched, and failures do not stop the run.
+pub(crate) fn execute_recompact(
+    args: RecompactArgs,
+    profile: &Profile,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(context) = load_context(args.context.as_ref(), profile)? else {
+        return Err(ToolError::InvalidConfig(
+            "recompact needs a --context or a configured context".into(),
+        ));
+    };
+    let mut options = SyncOptions {
+        jsonld_version: args.json_ld_version.into(),
+        ..args.remote.sync_options()
+    };
+    profile.apply(&mut options);
+    args.encoding.apply(&mut options.input_encoding);
+    let output_dir = args.output_dir.as_deref().unwrap_or(&args.input_dir);
+
+    let glob = Glob::new(&args.glob)?;
+    let inputs = batch::matching_files(&args.input_dir, &glob)?;
+    let mut rewritten = 0;
+    let mut failures = Vec::new();
+    for (done, relative) in inputs.iter().enumerate() {
+        interrupt::check(done)?;
+        let output = output_dir.join(relative);
+        let result =
+            sync::recompact_jsonld(&args.input_dir.join(relative), context.clone(), &options)
+                .and_then(|document| {
+                    let unchanged = std::fs::read_to_string(&output)
+                        .ok()
+                        .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+                        .is_some_and(|existing| existing == document);
+                    if unchanged {
+                        return Ok(false);
+                    }
+                    if dry_run {
+                        println!("would rewrite {}", output.display());
+                        return Ok(true);
+                    }
+                    if let Some(parent) = output.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    let text = serde_json::to_string_pretty(&document)?;
+                    atomic::write_atomically(&output, |partial| {
+                        Ok(std::fs::write(partial, text)?)
+                    })?;
+                    Ok(true)
+                });
+        match result {
+            Ok(changed) => {
+                rewritten += usize::from(changed);
+                info!(input = %relative.display(), changed, "recompacted");
+            }
+            Err(error) => {
+                error!(input = %relative.display(), %error, "recompaction failed");
+                failures.push((relative, error));
+            }
+        }
+    }
+
+    let verb = if dry_run { "would rewrite" } else { "rewrote" };
+    eprintln!("{verb} {rewritten} of {} file(s)", inputs.len());
+    for (relative, error) in &failures {
+        eprintln!("  {}: {error}", relative.display());
+    }
+    if !failures.is_empty() {
+        return Err(ToolError::BatchFailed {
+            failed: failures.len(),
+            total: inputs.len(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! Dry-run previews, diagnostics, and JSON reports printed or written by the
+//! subcommands.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::io::atomic;
+use aideon_tools::aideon::tools::model::{Node, PropertyValue};
+use aideon_tools::aideon::tools::report::DryRunReport;
+use aideon_tools::aideon::tools::roundtrip::RoundTripLoss;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::aideon::tools::validate::{ValidationIssue, ValidationReport};
+use aideon_tools::{Result, ToolError};
+use tracing::info;
+
+use crate::cli::formats::DataFormat;
+use crate::cli::io::{is_stdio, sql_output};
+
+/// Describes what writing `nodes` in the given representation would
+/// produce, without writing it.
+pub(crate) fn preview(
+    format: DataFormat,
+    nodes: &[Node],
+    options: &SyncOptions,
+) -> Result<DryRunReport> {
+    match format {
+        DataFormat::Excel | DataFormat::Csv | DataFormat::Parquet | DataFormat::Sqlite => {
+            DryRunReport::for_workbook(nodes, &options.workbook_options())
+        }
+        DataFormat::JsonLd | DataFormat::Rdf | DataFormat::NodesJson => {
+            Ok(DryRunReport::for_graph(nodes))
+        }
+        DataFormat::Sql => Err(sql_output()),
+    }
+}
+
+/// Prints `report`, the dry run of writing `output` as `format`: the node
+/// count, nodes per type, the sheets and columns of workbook and CSV
+/// outputs, the values they would lose in the format of `check-roundtrip`,
+/// and validation issues.
+pub(crate) fn print_dry_run(report: &DryRunReport, output: &Path, format: &str) -> Result<()> {
+    let summary = &report.summary;
+    println!(
+        "dry run: {} node(s) would be written to {} as {format}",
+        summary.node_count,
+        output.display()
+    );
+    if !is_stdio(output) && output.exists() {
+        println!("warning: {} exists and would be replaced", output.display());
+    }
+    for (type_name, count) in &summary.type_counts {
+        println!("type: {type_name}: {count} node(s)");
+    }
+    for sheet in &report.sheets {
+        println!(
+            "sheet: {}: {} row(s), {} column(s)",
+            sheet.sheet_name,
+            sheet.row_count,
+            sheet.columns.len()
+        );
+    }
+    report.losses.iter().for_each(print_loss);
+    report.validation.issues.iter().for_each(print_issue);
+    info!(
+        node_count = summary.node_count,
+        sheets = report.sheets.len(),
+        losses = report.losses.len(),
+        issues = report.validation.issues.len(),
+        "dry run finished; nothing written"
+    );
+    Ok(())
+}
+
+/// Prints the problems read past under `--keep-going` to stderr, failing
+/// when any of them is an error.
+pub(crate) fn report_diagnostics(report: &ValidationReport) -> Result<()> {
+    for issue in &report.issues {
+        eprintln!("{}", issue_line(issue));
+    }
+    match report.error_count() {
+        0 => Ok(()),
+        errors => Err(ToolError::ValidationFailed { errors }),
+    }
+}
+
+/// Prints a round-trip loss as `kind: node [predicate]: before -> after`.
+pub(crate) fn print_loss(loss: &RoundTripLoss) {
+    let predicate = loss
+        .predicate
+        .as_deref()
+        .map(|predicate| format!(" [{predicate}]"))
+        .unwrap_or_default();
+    let render = |value: &Option<PropertyValue>| match value {
+        Some(value) => value.to_json().to_string(),
+        None => "-".to_string(),
+    };
+    println!(
+        "{}: {}{predicate}: {} -> {}",
+        loss.kind.as_str(),
+        loss.node,
+        render(&loss.before),
+        render(&loss.after)
+    );
+}
+
+/// Prints a validation issue as `severity: node [predicate]: message`.
+pub(crate) fn print_issue(issue: &ValidationIssue) {
+    println!("{}", issue_line(issue));
+}
+
+/// Formats a validation issue as `severity: node [predicate]: message`,
+/// leaving out the node of issues found before any node was read, such as
+/// RDF statements that cannot be parsed.
+fn issue_line(issue: &ValidationIssue) -> String {
+    let predicate = issue
+        .predicate
+        .as_deref()
+        .map(|predicate| format!(" [{predicate}]"))
+        .unwrap_or_default();
+    let severity = issue.severity.as_str();
+    if issue.node.is_empty() {
+        return format!("{severity}: {}", issue.message);
+    }
+    format!("{severity}: {}{predicate}: {}", issue.node, issue.message)
+}
+
+/// Writes `report` to `path` as pretty-printed JSON.
+pub(crate) fn write_json_report(path: &Path, report: &impl serde::Serialize) -> Result<()> {
+    atomic::write_atomically(path, |partial| {
+        serde_json::to_writer_pretty(BufWriter::new(File::create(partial)?), report)?;
+        Ok(())
+    })
+}
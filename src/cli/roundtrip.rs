@@ -0,0 +1,87 @@
+//! The `check-roundtrip` subcommand, reporting what the workbook
+//! representation of a data set loses.
+
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::roundtrip;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::{Result, ToolError};
+use tracing::info;
+
+use crate::cli::args::{CoercionArgs, CsvArgs, InputEncodingArgs, RemoteContextArgs};
+use crate::cli::formats::{CellEncodingKind, DataFormat, RdfFormatKind, input_format};
+use crate::cli::io::read_nodes;
+use crate::cli::report::{print_loss, write_json_report};
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct CheckRoundtripArgs {
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Representation of the input.
+    #[arg(long, value_parser = input_format(), default_value = "json-ld")]
+    format: DataFormat,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// Cell encoding to check the workbook round trip with.
+    #[arg(long, value_enum, default_value = "json")]
+    cell_encoding: CellEncodingKind,
+
+    /// Write the differences as a JSON report.
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+}
+
+/// Executes the check-roundtrip subcommand, printing one line per node,
+/// type, or property that the workbook representation loses or alters.
+pub(crate) fn execute_check_roundtrip(args: CheckRoundtripArgs, profile: &Profile) -> Result<()> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input));
+    }
+
+    let rdf_format = args.rdf_format.map(RdfFormat::from);
+    let mut options = SyncOptions {
+        coercions: args.coercions.load()?,
+        ..args.remote.sync_options()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let nodes = read_nodes(args.format, &args.input, rdf_format, &options)?;
+    let report = roundtrip::check_roundtrip(&nodes, args.cell_encoding.into())?;
+    report.losses.iter().for_each(print_loss);
+    info!(
+        node_count = nodes.len(),
+        losses = report.losses.len(),
+        "round-trip check finished"
+    );
+
+    if let Some(report_path) = &args.report {
+        write_json_report(report_path, &report)?;
+    }
+
+    if !report.is_lossless() {
+        return Err(ToolError::LossyRoundTrip {
+            losses: report.losses.len(),
+        });
+    }
+    Ok(())
+}
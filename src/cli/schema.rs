@@ -0,0 +1,104 @@
+//! The `infer-schema` subcommand, describing the classes and properties a
+//! data set uses.
+
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::flatten::WorkbookData;
+use aideon_tools::aideon::tools::io::excel_write;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::schema;
+use aideon_tools::aideon::tools::shacl::SH;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::{Result, ToolError};
+
+use crate::cli::args::{CoercionArgs, CsvArgs, InputEncodingArgs, RemoteContextArgs};
+use crate::cli::formats::{DataFormat, RdfFormatKind, input_format};
+use crate::cli::io::{read_nodes, resolve_rdf_format};
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct InferSchemaArgs {
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Representation of the input; detected from the path when omitted.
+    #[arg(long, value_parser = input_format())]
+    format: Option<DataFormat>,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_input_format: Option<RdfFormatKind>,
+
+    /// Output file path: a workbook with a `Schema` sheet for `.xlsx`, RDF
+    /// otherwise.
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Explicit RDF serialisation format of the schema; detected from the
+    /// output extension when omitted.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+}
+
+/// Executes the infer-schema subcommand, writing the classes and properties
+/// the input uses as RDFS and SHACL or as a `Schema` sheet.
+pub(crate) fn execute_infer_schema(args: InferSchemaArgs, profile: &Profile) -> Result<()> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input));
+    }
+
+    let format = args
+        .format
+        .unwrap_or_else(|| DataFormat::detect(&args.input));
+    let rdf_input_format = args.rdf_input_format.map(RdfFormat::from);
+    let mut options = SyncOptions {
+        coercions: args.coercions.load()?,
+        rdf_input_format,
+        ..args.remote.sync_options()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let nodes = read_nodes(format, &args.input, rdf_input_format, &options)?;
+    let schema = schema::infer_schema(&nodes);
+
+    let is_workbook = args
+        .output
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("xlsx"));
+    if is_workbook {
+        let workbook = WorkbookData {
+            tables: vec![schema.to_table()],
+        };
+        excel_write::write_workbook(&args.output, &workbook)?;
+    } else {
+        let rdf_format = resolve_rdf_format(args.rdf_format, profile, &args.output)?;
+        let mut prefixes = options.prefix_registry(None);
+        prefixes.insert("sh".to_string(), SH.to_string());
+        rdf::write_rdf_with_prefixes(&args.output, &schema.to_nodes(), rdf_format, &prefixes)?;
+    }
+    let property_count: usize = schema
+        .classes
+        .values()
+        .map(|class| class.properties.len())
+        .sum();
+    eprintln!(
+        "inferred {} class(es) using {property_count} propert(ies) from {} node(s)",
+        schema.classes.len(),
+        nodes.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! The `sign` and `verify-signature` subcommands, signing the canonical form
+//! of a data set with a detached JWS.
+
+use std::path::{Path, PathBuf};
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::io::atomic;
+use aideon_tools::aideon::tools::io::rdf::{self, Quad, RdfFormat};
+use aideon_tools::aideon::tools::signing;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::{Result, ToolError};
+use tracing::info;
+
+use crate::cli::args::{CoercionArgs, CsvArgs, InputEncodingArgs};
+use crate::cli::formats::{DataFormat, RdfFormatKind, input_format};
+use crate::cli::io::read_nodes;
+
+#[derive(clap::Args, Debug)]
+struct SignedDatasetArgs {
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Representation of the input; detected from the path when omitted.
+    #[arg(long, value_parser = input_format())]
+    format: Option<DataFormat>,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct SignArgs {
+    #[command(flatten)]
+    dataset: SignedDatasetArgs,
+
+    /// Ed25519 private key, as PKCS#8 PEM or DER.
+    #[arg(long, value_name = "PATH")]
+    key: PathBuf,
+
+    /// Key identifier recorded in the signature's `kid` header.
+    #[arg(long, value_name = "ID")]
+    key_id: Option<String>,
+
+    /// Where to write the signature; `<input>.jws` when omitted.
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct VerifySignatureArgs {
+    #[command(flatten)]
+    dataset: SignedDatasetArgs,
+
+    /// Ed25519 public key, as SubjectPublicKeyInfo PEM or DER.
+    #[arg(long, value_name = "PATH")]
+    key: PathBuf,
+
+    /// Signature written by `sign`; `<input>.jws` when omitted.
+    #[arg(long, value_name = "PATH")]
+    signature: Option<PathBuf>,
+}
+
+/// Executes the sign subcommand, writing a detached JWS of the canonical
+/// form of the input.
+pub(crate) fn execute_sign(args: SignArgs, profile: &Profile) -> Result<()> {
+    let quads = read_signed(&args.dataset, profile)?;
+    let key = std::fs::read(&args.key)?;
+    let signature = signing::sign_quads(&quads, &key, args.key_id.as_deref())?;
+    let output = args
+        .output
+        .unwrap_or_else(|| signature_path(&args.dataset.input));
+    atomic::write_atomically(&output, |partial| {
+        Ok(std::fs::write(partial, format!("{signature}\n"))?)
+    })?;
+    info!(statement_count = quads.len(), output = %output.display(), "signed dataset");
+    Ok(())
+}
+
+/// Executes the verify-signature subcommand, failing unless the signature
+/// matches the canonical form of the input.
+pub(crate) fn execute_verify_signature(args: VerifySignatureArgs, profile: &Profile) -> Result<()> {
+    let quads = read_signed(&args.dataset, profile)?;
+    let signature_file = args
+        .signature
+        .unwrap_or_else(|| signature_path(&args.dataset.input));
+    if !signature_file.exists() {
+        return Err(ToolError::MissingInput(signature_file));
+    }
+    let signature = std::fs::read_to_string(&signature_file)?;
+    signing::verify_quads(&quads, &signature, &std::fs::read(&args.key)?)?;
+    println!("signature verified for {} statement(s)", quads.len());
+    Ok(())
+}
+
+/// Reads the statements a signature is made or checked over: those of RDF
+/// files as parsed, and those of the nodes of other inputs.
+fn read_signed(args: &SignedDatasetArgs, profile: &Profile) -> Result<Vec<Quad>> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input.clone()));
+    }
+    let format = args
+        .format
+        .unwrap_or_else(|| DataFormat::detect(&args.input));
+    let rdf_format = args.rdf_format.map(RdfFormat::from);
+    if matches!(format, DataFormat::Rdf) {
+        return rdf::read_dataset(&args.input, rdf_format);
+    }
+    let mut options = SyncOptions {
+        coercions: args.coercions.load()?,
+        ..SyncOptions::default()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    rdf::dataset_quads(&read_nodes(format, &args.input, rdf_format, &options)?)
+}
+
+/// Where the signature of `input` is written by default: `<input>.jws`.
+fn signature_path(input: &Path) -> PathBuf {
+    let mut path = input.as_os_str().to_owned();
+    path.push(".jws");
+    PathBuf::from(path)
+}
@@ -0,0 +1,132 @@
+//! The `split` subcommand, partitioning a data set with a SPARQL query.
+
+use std::path::{Path, PathBuf};
+
+use aideon_tools::aideon::tools::batch;
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::query;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::{Result, ToolError};
+use tracing::info;
+
+use crate::cli::args::{
+    CoercionArgs, CsvArgs, InputEncodingArgs, LayoutArgs, PrefixArgs, RemoteContextArgs,
+};
+use crate::cli::formats::{
+    CellEncodingKind, DataFormat, RdfFormatKind, input_format, output_format,
+};
+use crate::cli::io::{load_context, output_extension, read_nodes, resolve_rdf_format, write_nodes};
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct SplitArgs {
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Representation of the input; detected from the path when omitted.
+    #[arg(long, value_parser = input_format())]
+    format: Option<DataFormat>,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_input_format: Option<RdfFormatKind>,
+
+    /// File holding the SELECT query choosing the partitions: `?node` binds
+    /// their members and the other variables name them.
+    #[arg(long, value_name = "PATH")]
+    sparql: PathBuf,
+
+    /// Target representation.
+    #[arg(long, value_parser = output_format())]
+    to: DataFormat,
+
+    /// Directory receiving one output per partition, named after it.
+    #[arg(long, value_name = "DIR")]
+    output_dir: PathBuf,
+
+    /// Optional JSON-LD context to use when serialising.
+    #[arg(long)]
+    context: Option<PathBuf>,
+
+    /// Explicit RDF serialisation format to use when writing RDF files.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// Encoding of literal cells in written workbooks and CSV files.
+    #[arg(long, value_enum, default_value = "json")]
+    cell_encoding: CellEncodingKind,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+
+    #[command(flatten)]
+    prefixes: PrefixArgs,
+
+    #[command(flatten)]
+    layout: LayoutArgs,
+}
+
+/// Executes the split subcommand, writing the nodes of every partition
+/// selected by the `SELECT` query to its own output below `--output-dir`.
+pub(crate) fn execute_split(args: SplitArgs, profile: &Profile) -> Result<()> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input));
+    }
+
+    let format = args
+        .format
+        .unwrap_or_else(|| DataFormat::detect(&args.input));
+    let rdf_input_format = args.rdf_input_format.map(RdfFormat::from);
+    let mut options = SyncOptions {
+        cell_encoding: args.cell_encoding.into(),
+        coercions: args.coercions.load()?,
+        rdf_input_format,
+        ..args.remote.sync_options()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let nodes = read_nodes(format, &args.input, rdf_input_format, &options)?;
+    let sparql = std::fs::read_to_string(&args.sparql)?;
+    let partitions = query::partition_nodes(&nodes, &sparql)?;
+
+    let context = load_context(args.context.as_ref(), profile)?;
+    args.prefixes.apply(&mut options, context.as_ref())?;
+    args.layout.apply(&mut options, profile, context.as_ref())?;
+    let rdf_format = resolve_rdf_format(args.rdf_format, profile, Path::new(""))?;
+    let extension = output_extension(args.to, rdf_format)?;
+    std::fs::create_dir_all(&args.output_dir)?;
+    for partition in &partitions {
+        let output = batch::output_path(&args.output_dir, Path::new(&partition.name), extension);
+        write_nodes(
+            args.to,
+            &output,
+            &partition.nodes,
+            rdf_format,
+            context.clone(),
+            &options,
+        )?;
+        info!(
+            partition = partition.name,
+            node_count = partition.nodes.len(),
+            output = %output.display(),
+            "wrote partition"
+        );
+    }
+    eprintln!(
+        "wrote {} partition(s) of {} node(s)",
+        partitions.len(),
+        nodes.len()
+    );
+    Ok(())
+}
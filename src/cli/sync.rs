@@ -0,0 +1,877 @@
+//! The `sync` subcommand, converting a file or a directory of files between
+//! two data formats.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use aideon_tools::aideon::tools::batch::{self, Glob};
+use aideon_tools::aideon::tools::blank_nodes::BlankNodeLabels;
+use aideon_tools::aideon::tools::change_feed::{self, FeedFormat};
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::convert::Converter;
+use aideon_tools::aideon::tools::diff;
+use aideon_tools::aideon::tools::filter::{IriPattern, NodeFilter};
+use aideon_tools::aideon::tools::history::History;
+use aideon_tools::aideon::tools::io::jsonld::IdStrategy;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::io::{atomic, csv_read, excel_read, nodes_json};
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::provenance::{self, CellProvenance};
+use aideon_tools::aideon::tools::query::NodeCondition;
+use aideon_tools::aideon::tools::report::{DatasetSummary, SyncReport};
+use aideon_tools::aideon::tools::same_as::SameAs;
+use aideon_tools::aideon::tools::script::NodeScript;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use aideon_tools::aideon::tools::validate;
+use aideon_tools::aideon::tools::validate::ValidationReport;
+use aideon_tools::{Result, ToolError};
+use serde_json::{Value, json};
+use tracing::{debug, error, info};
+
+use crate::cli::args::{
+    CoercionArgs, CsvArgs, InputEncodingArgs, LayoutArgs, LimitArgs, PrefixArgs, RemoteContextArgs,
+    SameAsKind, iri_set, parse_id_strategy,
+};
+use crate::cli::formats::{
+    CellEncodingKind, ChangeFeedFormat, DataFormat, JsonLdVersionKind, RdfFormatKind, input_format,
+    output_format,
+};
+use crate::cli::io::{
+    guess_rdf_format, is_stdio, load_context, load_json, output_extension, output_only, read_nodes,
+    read_nodes_keep_going, resolve_rdf_format, write_nodes,
+};
+use crate::cli::report::{preview, print_dry_run, report_diagnostics, write_json_report};
+
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct SyncArgs {
+    /// Source representation.
+    #[arg(long, value_parser = input_format())]
+    from: DataFormat,
+
+    /// Input file path (a directory for CSV), or `-` to read JSON-LD or RDF
+    /// from stdin.
+    #[arg(long, required_unless_present = "input_dir")]
+    input: Option<PathBuf>,
+
+    /// Target representation.
+    #[arg(long, value_parser = output_format())]
+    to: DataFormat,
+
+    /// Output file path (a directory for CSV), or `-` to write JSON-LD or
+    /// RDF to stdout.
+    #[arg(long, required_unless_present_any = ["input_dir", "preview"])]
+    output: Option<PathBuf>,
+
+    /// Convert every file below this directory that matches `--glob`
+    /// instead of a single input.
+    #[arg(
+        long,
+        value_name = "DIR",
+        requires = "output_dir",
+        conflicts_with_all = ["input", "output", "emit_changes", "change_feed", "html_report", "history", "provenance"]
+    )]
+    input_dir: Option<PathBuf>,
+
+    /// Pattern selecting the files converted from `--input-dir`, such as
+    /// `*.jsonld` (at any depth) or `sources/**/*.ttl`.
+    #[arg(long, value_name = "PATTERN", default_value = "*")]
+    glob: String,
+
+    /// Directory receiving the outputs of `--input-dir`, at the relative
+    /// paths of their inputs with the extension of the target format.
+    #[arg(long, value_name = "DIR", requires = "input_dir")]
+    output_dir: Option<PathBuf>,
+
+    /// Optional JSON-LD context to use when serialising. Workbook and CSV
+    /// inputs with an embedded context use it when omitted.
+    #[arg(long)]
+    context: Option<PathBuf>,
+
+    /// Embed the context, with the configured prefixes, in a `Context` sheet
+    /// of written workbooks and CSV files.
+    #[arg(long)]
+    embed_context: bool,
+
+    /// Fail JSON-LD output whose context leaves predicates or types of the
+    /// dataset unmapped, which would otherwise be written as absolute keys.
+    #[arg(long)]
+    strict_context: bool,
+
+    /// Fail reading a workbook or CSV directory where two rows describing
+    /// the same node give one of its properties different values, naming
+    /// the sheet, row, and column, instead of keeping the later value.
+    #[arg(long)]
+    strict: bool,
+
+    /// Read past the problems of a workbook, CSV directory, or RDF input,
+    /// leaving out the cells and statements that cannot be read, and report
+    /// them all with their severity once the output is written. Still fails
+    /// when any of them is an error.
+    #[arg(long, conflicts_with = "strict")]
+    keep_going: bool,
+
+    /// JSON-LD frame that re-nests referenced nodes in JSON-LD output. Its
+    /// `@context` compacts the output unless `--context` is given.
+    #[arg(long, value_name = "PATH")]
+    frame: Option<PathBuf>,
+
+    /// Explicit RDF serialisation format to use when writing RDF files.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// RDF serialisation of the input, whatever its extension. Input files
+    /// are detected from their extension and stdin is Turtle by default.
+    #[arg(long, value_enum, alias = "stdin-rdf-format")]
+    rdf_input_format: Option<RdfFormatKind>,
+
+    /// Connection URL of a `--from sql` source, such as
+    /// `postgres://user@host/database`, in place of the `url` of its file.
+    #[arg(long, value_name = "URL")]
+    database_url: Option<String>,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+
+    #[command(flatten)]
+    limits: LimitArgs,
+
+    #[command(flatten)]
+    prefixes: PrefixArgs,
+
+    #[command(flatten)]
+    layout: LayoutArgs,
+
+    /// JSON-LD version of the output; `1.0` suits legacy parsers.
+    #[arg(long, value_enum, default_value = "1.1")]
+    json_ld_version: JsonLdVersionKind,
+
+    /// Encoding of literal cells in written workbooks and CSV files: `json`
+    /// quotes strings, `bare` writes them as plain text.
+    #[arg(long, value_enum, default_value = "json")]
+    cell_encoding: CellEncodingKind,
+
+    /// Predicate whose multi-valued references are written as a matrix
+    /// sheet (one row per subject, one column per target) instead of a
+    /// child sheet. May be repeated.
+    #[arg(long = "matrix", value_name = "PREDICATE")]
+    matrix_predicates: Vec<String>,
+
+    /// Predicate whose values are written to RDF as an ordered `rdf:List`.
+    /// May be repeated.
+    #[arg(long = "rdf-list", value_name = "PREDICATE")]
+    rdf_lists: Vec<String>,
+
+    /// Read classic RDF reifications of statements the input asserts as
+    /// relationship nodes holding the annotations.
+    #[arg(long)]
+    fold_reifications: bool,
+
+    /// Convert only the nodes of this named graph. May be repeated.
+    #[arg(long = "graph", value_name = "IRI")]
+    graphs: Vec<String>,
+
+    /// Convert only the nodes of the default graph.
+    #[arg(long, conflicts_with = "graphs")]
+    default_graph_only: bool,
+
+    /// Convert only the nodes with a type matching this IRI or pattern,
+    /// such as `schema:Person` or `https://schema.org/*`. May be repeated.
+    #[arg(long = "include-type", value_name = "PATTERN")]
+    include_types: Vec<String>,
+
+    /// Leave out the nodes with a type matching this pattern. May be
+    /// repeated.
+    #[arg(long = "exclude-type", value_name = "PATTERN")]
+    exclude_types: Vec<String>,
+
+    /// Convert only the properties whose predicate matches this pattern.
+    /// May be repeated.
+    #[arg(long = "include-predicate", value_name = "PATTERN")]
+    include_predicates: Vec<String>,
+
+    /// Leave out the properties whose predicate matches this pattern. May
+    /// be repeated.
+    #[arg(long = "exclude-predicate", value_name = "PATTERN")]
+    exclude_predicates: Vec<String>,
+
+    /// Convert only the nodes this SPARQL graph pattern binds to `?node`,
+    /// such as `?node schema:worksFor ?org`. May be repeated.
+    #[arg(long = "include-where", value_name = "PATTERN")]
+    include_where: Vec<String>,
+
+    /// Leave out the nodes this SPARQL graph pattern binds to `?node`. May
+    /// be repeated.
+    #[arg(long = "exclude-where", value_name = "PATTERN")]
+    exclude_where: Vec<String>,
+
+    /// Leave the properties whose predicate matches this pattern out of
+    /// written workbooks and CSV files, and keep their values from the
+    /// existing output when those are read back. May be repeated.
+    #[arg(long = "ignore-predicate", value_name = "PATTERN")]
+    ignored_predicates: Vec<String>,
+
+    /// Rhai script whose `transform(node)` function changes, drops, or
+    /// multiplies every node read. Needs the `scripting` feature.
+    #[arg(long, value_name = "PATH")]
+    script: Option<PathBuf>,
+
+    /// Label blank nodes after their content instead of as the parser
+    /// numbered them, so they keep their ids across conversions.
+    #[arg(long)]
+    canonical_blank_nodes: bool,
+
+    /// Replace blank nodes with well-known IRIs under this base, such as
+    /// `https://example.com/.well-known/genid/…`, derived like
+    /// `--canonical-blank-nodes` labels.
+    #[arg(long, value_name = "BASE", conflicts_with = "canonical_blank_nodes")]
+    skolemize: Option<String>,
+
+    /// Turn well-known IRIs such as `https://example.com/.well-known/genid/…`
+    /// back into blank nodes, undoing `--skolemize`.
+    #[arg(long, conflicts_with_all = ["canonical_blank_nodes", "skolemize"])]
+    deskolemize: bool,
+
+    /// How JSON-LD node objects without an `@id` get one: `uuid-v5` (a
+    /// `urn:uuid:` hashed from the object, the default), `uuid-v4` (random),
+    /// `keys:PROPERTY,…` (hashed from these properties), or a template such
+    /// as `https://example.org/{type}/{name}`.
+    #[arg(long, value_name = "STRATEGY", value_parser = parse_id_strategy)]
+    id_strategy: Option<IdStrategy>,
+
+    /// What becomes of nodes linked by `owl:sameAs`: merge each cluster
+    /// into one node, keep them with their workbook rows next to each
+    /// other, or only report the clusters. Turns off `--chunk-size`.
+    #[arg(long, value_enum, value_name = "MODE")]
+    same_as: Option<SameAsKind>,
+
+    /// Stream RDF input in chunks of this many nodes to bound memory use.
+    /// Input should be sorted by subject (for example sorted N-Quads).
+    #[arg(long, value_name = "NODES")]
+    chunk_size: Option<usize>,
+
+    /// Convert only the first ROWS rows of each sheet of a workbook or CSV
+    /// input, with the rows describing their nodes on the other sheets, and
+    /// print the JSON-LD or RDF sample to stdout, or write it to `--output`,
+    /// to check a mapping before a full conversion.
+    #[arg(
+        long,
+        value_name = "ROWS",
+        conflicts_with_all = ["input_dir", "update", "emit_changes", "change_feed", "html_report", "history", "provenance"]
+    )]
+    preview: Option<usize>,
+
+    /// Update an existing output workbook in place, changing only the rows
+    /// that differ and keeping formatting, comments, extra columns, and
+    /// other sheets. Applies to `--to excel`.
+    #[arg(long, conflicts_with = "chunk_size")]
+    update: bool,
+
+    /// Write one NDJSON event per node created, updated, or deleted in the
+    /// output by this sync (`-` for stdout).
+    #[arg(long, value_name = "PATH")]
+    emit_changes: Option<PathBuf>,
+
+    /// Write a standalone HTML summary of the sync (counts and changes).
+    #[arg(long, value_name = "PATH")]
+    html_report: Option<PathBuf>,
+
+    /// Append one JSON-LD event per node created, updated, or deleted in
+    /// the output by this sync to this change feed (`-` for stdout).
+    #[arg(long, value_name = "PATH")]
+    change_feed: Option<PathBuf>,
+
+    /// Vocabulary of the `--change-feed` events.
+    #[arg(long, value_enum, default_value_t = ChangeFeedFormat::ActivityStreams)]
+    change_feed_format: ChangeFeedFormat,
+
+    /// IRI of the event stream that `--change-feed-format ldes` events add
+    /// members to.
+    #[arg(long, value_name = "IRI", required_if_eq("change_feed_format", "ldes"))]
+    feed_stream: Option<String>,
+
+    /// Commit the output of the sync as a new version of the history kept
+    /// in this directory, unless it did not change.
+    #[arg(long, value_name = "DIR")]
+    history: Option<PathBuf>,
+
+    /// Write the sheet, row, and column of the workbook or CSV cell each
+    /// value was read from: a JSON list for `.json` paths, PROV-O statements
+    /// in the RDF format of the extension otherwise.
+    #[arg(long, value_name = "PATH")]
+    provenance: Option<PathBuf>,
+}
+
+impl SyncArgs {
+    /// Returns the input of a single-file sync, which clap requires unless
+    /// `--input-dir` is given.
+    fn input(&self) -> &Path {
+        self.input.as_deref().unwrap_or(Path::new("-"))
+    }
+
+    /// Returns the output of a single-file sync, see [`Self::input`].
+    fn output(&self) -> &Path {
+        self.output.as_deref().unwrap_or(Path::new("-"))
+    }
+
+    /// Returns whether the sync compares the output before and after the
+    /// conversion, which reads it back.
+    fn reads_output_back(&self) -> bool {
+        self.emit_changes.is_some()
+            || self.html_report.is_some()
+            || self.change_feed.is_some()
+            || self.history.is_some()
+    }
+
+    pub(crate) fn sync_options(&self) -> SyncOptions {
+        SyncOptions {
+            chunk_size: self.chunk_size,
+            jsonld_version: self.json_ld_version.into(),
+            cell_encoding: self.cell_encoding.into(),
+            matrix_predicates: iri_set(&self.matrix_predicates),
+            rdf_lists: iri_set(&self.rdf_lists),
+            fold_reifications: self.fold_reifications,
+            max_nodes: self.limits.max_nodes,
+            max_cells: self.limits.max_cells,
+            rdf_input_format: self.rdf_input_format.map(RdfFormat::from),
+            database_url: self.database_url.clone(),
+            node_filter: NodeFilter {
+                graphs: iri_set(&self.graphs),
+                default_graph: self.default_graph_only,
+                ..NodeFilter::default()
+            },
+            strict_context: self.strict_context,
+            strict_cells: self.strict,
+            blank_nodes: match &self.skolemize {
+                Some(base) => BlankNodeLabels::Skolemized(base.clone()),
+                None if self.canonical_blank_nodes => BlankNodeLabels::Canonical,
+                None if self.deskolemize => BlankNodeLabels::Deskolemized,
+                None => BlankNodeLabels::Parsed,
+            },
+            id_strategy: self.id_strategy.clone().unwrap_or_default(),
+            same_as: self.same_as.map(SameAs::from),
+            ..self.remote.sync_options()
+        }
+    }
+
+    /// Adds the type and predicate patterns to `filter`, expanding their
+    /// prefixes with `prefixes`.
+    fn apply_filters(&self, filter: &mut NodeFilter, prefixes: &BTreeMap<String, String>) {
+        let patterns = |patterns: &[String]| -> Vec<IriPattern> {
+            patterns
+                .iter()
+                .map(|pattern| IriPattern::new(pattern, prefixes))
+                .collect()
+        };
+        filter.include_types = patterns(&self.include_types);
+        filter.exclude_types = patterns(&self.exclude_types);
+        filter.include_predicates = patterns(&self.include_predicates);
+        filter.exclude_predicates = patterns(&self.exclude_predicates);
+    }
+
+    /// Adds the SPARQL node conditions to `options`, declaring `prefixes`
+    /// for them.
+    fn apply_conditions(&self, options: &mut SyncOptions, prefixes: &BTreeMap<String, String>) {
+        let keeping = self
+            .include_where
+            .iter()
+            .map(|pattern| NodeCondition::keeping(pattern, prefixes));
+        let dropping = self
+            .exclude_where
+            .iter()
+            .map(|pattern| NodeCondition::dropping(pattern, prefixes));
+        options.node_conditions.extend(keeping.chain(dropping));
+    }
+
+    /// Adds the ignored predicate patterns to `options`, expanding their
+    /// prefixes with `prefixes`.
+    fn apply_ignored(&self, options: &mut SyncOptions, prefixes: &BTreeMap<String, String>) {
+        options.ignored_predicates.extend(
+            self.ignored_predicates
+                .iter()
+                .map(|pattern| IriPattern::new(pattern, prefixes)),
+        );
+    }
+}
+
+/// Executes the sync subcommand by delegating to the appropriate conversion
+/// routine, or only reports what it would write on a dry run.
+pub(crate) fn execute_sync(args: SyncArgs, profile: &Profile, dry_run: bool) -> Result<()> {
+    match &args.input_dir {
+        Some(input_dir) => execute_batch(&args, input_dir, profile, dry_run),
+        None => sync_file(&args, profile, dry_run),
+    }
+}
+
+/// Converts the single input of `args`.
+fn sync_file(args: &SyncArgs, profile: &Profile, dry_run: bool) -> Result<()> {
+    let (input, output) = (args.input(), args.output());
+    if !is_stdio(input) && !input.exists() {
+        return Err(ToolError::MissingInput(input.to_path_buf()));
+    }
+    if args.provenance.is_some() && !matches!(args.from, DataFormat::Excel | DataFormat::Csv) {
+        return Err(provenance_input_error(args.from));
+    }
+    if args.reads_output_back() && matches!(args.to, DataFormat::Parquet | DataFormat::Sqlite) {
+        return Err(ToolError::InvalidConfig(format!(
+            "--emit-changes, --change-feed, --history, and --html-report read the output back, and {}",
+            output_only(args.to)
+        )));
+    }
+
+    debug!(
+        from = %args.from,
+        to = %args.to,
+        input = %input.display(),
+        output = %output.display(),
+        has_context = args.context.is_some(),
+        "resolved sync arguments"
+    );
+
+    let context = load_context(args.context.as_ref(), profile)?;
+    let mut options = args.sync_options();
+    options.coercions = args.coercions.load()?;
+    if let Some(path) = &args.frame {
+        options.frame = Some(load_json(path)?);
+    }
+    if let Some(path) = &args.script {
+        options.script = Some(NodeScript::load(path)?);
+    }
+    profile.apply(&mut options);
+    args.layout.apply(&mut options, profile, context.as_ref())?;
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    args.prefixes.apply(&mut options, context.as_ref())?;
+    let prefixes = options.prefix_registry(context.as_ref());
+    args.apply_filters(&mut options.node_filter, &prefixes);
+    args.apply_conditions(&mut options, &prefixes);
+    args.apply_ignored(&mut options, &prefixes);
+    if args.embed_context {
+        options.embedded_context = Some(context.clone().unwrap_or_else(|| json!({})));
+    }
+    let output_format = resolve_rdf_format(args.rdf_format, profile, output)?;
+
+    if let Some(rows) = args.preview {
+        if !matches!(args.from, DataFormat::Excel | DataFormat::Csv) {
+            return Err(ToolError::InvalidConfig(
+                "--preview reads workbooks and CSV directories".into(),
+            ));
+        }
+        options.preview_rows = Some(rows);
+        return convert_stdio(args, context, output_format, &options);
+    }
+    if dry_run {
+        let nodes = read_input(args, &options)?;
+        let report = preview(args.to, &nodes, &options)?;
+        return print_dry_run(&report, output, &args.to.to_string());
+    }
+    if is_stdio(input) || is_stdio(output) {
+        return convert_stdio(args, context, output_format, &options);
+    }
+    if !args.reads_output_back() {
+        let diagnostics = convert_reporting(args, context, output_format, &options)?;
+        write_provenance(args, &options)?;
+        return report_diagnostics(&diagnostics);
+    }
+
+    let before = if output.exists() {
+        read_nodes(args.to, output, Some(output_format), &options)?
+    } else {
+        Vec::new()
+    };
+    let diagnostics = convert_reporting(args, context, output_format, &options)?;
+    write_provenance(args, &options)?;
+    let after = read_nodes(args.to, output, Some(output_format), &options)?;
+
+    let changes = diff::diff_nodes(&before, &after);
+    info!(change_count = changes.len(), "computed node changes");
+
+    if let Some(history_dir) = &args.history {
+        match History::open(history_dir)?.commit(&after, chrono::Utc::now())? {
+            Some(version) => info!(
+                version = version.number,
+                hash = %version.hash,
+                "committed dataset version"
+            ),
+            None => info!("dataset unchanged since the latest version"),
+        }
+    }
+
+    if let Some(changes_path) = &args.emit_changes {
+        if changes_path.as_os_str() == "-" {
+            diff::write_ndjson(std::io::stdout().lock(), &changes)?;
+        } else {
+            atomic::write_atomically(changes_path, |partial| {
+                diff::write_ndjson(BufWriter::new(File::create(partial)?), &changes)
+            })?;
+        }
+    }
+
+    if let Some(feed_path) = &args.change_feed {
+        let format = match args.change_feed_format {
+            ChangeFeedFormat::ActivityStreams => FeedFormat::ActivityStreams,
+            ChangeFeedFormat::Ldes => FeedFormat::Ldes {
+                stream: args.feed_stream.clone().unwrap_or_default(),
+            },
+        };
+        let events = change_feed::feed_events(&changes, &format, chrono::Utc::now());
+        change_feed::append_feed(feed_path, &events)?;
+        info!(event_count = events.len(), feed = %feed_path.display(), "appended change feed");
+    }
+
+    if let Some(report_path) = &args.html_report {
+        let report = SyncReport {
+            input: input.to_path_buf(),
+            output: output.to_path_buf(),
+            from: args.from.to_string(),
+            to: args.to.to_string(),
+            summary: DatasetSummary::from_nodes(&after),
+            validation: Some(validate::validate_nodes(&after)),
+            changes,
+        };
+        report.write_html(report_path)?;
+        info!(report = %report_path.display(), "wrote HTML report");
+    }
+
+    report_diagnostics(&diagnostics)
+}
+
+/// Converts every file below `input_dir` matching `--glob` like a single
+/// sync, writing the outputs below `--output-dir`. Failures do not stop the
+/// batch; a summary at the end lists them.
+fn execute_batch(
+    args: &SyncArgs,
+    input_dir: &Path,
+    profile: &Profile,
+    dry_run: bool,
+) -> Result<()> {
+    if matches!(args.from, DataFormat::Csv) {
+        return Err(ToolError::InvalidConfig(
+            "--input-dir converts files and cannot read CSV directories".into(),
+        ));
+    }
+    let output_dir = args.output_dir.as_deref().unwrap_or(Path::new("."));
+    let extension = output_extension(
+        args.to,
+        resolve_rdf_format(args.rdf_format, profile, Path::new(""))?,
+    )?;
+
+    let glob = Glob::new(&args.glob)?;
+    let inputs = batch::matching_files(input_dir, &glob)?;
+    info!(
+        input_dir = %input_dir.display(),
+        glob = glob.as_str(),
+        file_count = inputs.len(),
+        "converting batch"
+    );
+    let mut converted = 0;
+    let mut failures = Vec::new();
+    let mut interrupted = None;
+    for relative in &inputs {
+        let output = batch::output_path(output_dir, relative, extension);
+        let file_args = SyncArgs {
+            input: Some(input_dir.join(relative)),
+            output: Some(output.clone()),
+            input_dir: None,
+            ..args.clone()
+        };
+        let result = match output.parent() {
+            Some(parent) if !dry_run => std::fs::create_dir_all(parent).map_err(ToolError::from),
+            _ => Ok(()),
+        }
+        .and_then(|()| sync_file(&file_args, profile, dry_run));
+        match result {
+            Ok(()) => {
+                converted += 1;
+                info!(input = %relative.display(), output = %output.display(), "converted");
+            }
+            Err(error @ ToolError::Interrupted { .. }) => {
+                interrupted = Some(error);
+                break;
+            }
+            Err(error) => {
+                error!(input = %relative.display(), %error, "conversion failed");
+                failures.push((relative, error));
+            }
+        }
+    }
+
+    let verb = if dry_run { "previewed" } else { "converted" };
+    eprintln!("{verb} {converted} of {} file(s)", inputs.len());
+    for (relative, error) in &failures {
+        eprintln!("  {}: {error}", relative.display());
+    }
+    if let Some(error) = interrupted {
+        return Err(error);
+    }
+    if !failures.is_empty() {
+        return Err(ToolError::BatchFailed {
+            failed: failures.len(),
+            total: inputs.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Writes the cell each value of the workbook or CSV input of `args` was
+/// read from to `--provenance`, when given.
+fn write_provenance(args: &SyncArgs, options: &SyncOptions) -> Result<()> {
+    let Some(path) = &args.provenance else {
+        return Ok(());
+    };
+    let input = args.input();
+    let (nodes, records): (_, Vec<CellProvenance>) = match args.from {
+        DataFormat::Excel => {
+            excel_read::read_nodes_with_provenance(input, &options.coercions, &options.skip_sheets)?
+        }
+        DataFormat::Csv => csv_read::read_nodes_with_provenance_in_dialect(
+            input,
+            &options.csv_dialect,
+            &options.coercions,
+            &options.skip_sheets,
+        )?,
+        format => return Err(provenance_input_error(format)),
+    };
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    if is_json {
+        write_json_report(path, &records)?;
+    } else {
+        let statements = provenance::provenance_nodes(&records, &provenance::source_iri(input)?);
+        let prefixes = rdf::WELL_KNOWN_PREFIXES
+            .into_iter()
+            .chain([("prov", provenance::PROV_NAMESPACE)])
+            .map(|(prefix, namespace)| (prefix.to_string(), namespace.to_string()))
+            .collect();
+        rdf::write_rdf_with_prefixes(path, &statements, guess_rdf_format(path), &prefixes)?;
+    }
+    info!(
+        node_count = nodes.len(),
+        cell_count = records.len(),
+        provenance = %path.display(),
+        "wrote cell provenance"
+    );
+    Ok(())
+}
+
+fn provenance_input_error(format: DataFormat) -> ToolError {
+    ToolError::InvalidConfig(format!(
+        "--provenance needs a workbook or CSV input, not {format}"
+    ))
+}
+
+/// Runs the conversion like [`convert`], returning the problems of the input
+/// read past under `--keep-going`, which are none otherwise.
+fn convert_reporting(
+    args: &SyncArgs,
+    context: Option<Value>,
+    rdf_format: RdfFormat,
+    options: &SyncOptions,
+) -> Result<ValidationReport> {
+    if !args.keep_going {
+        convert(args, context, rdf_format, options)?;
+        return Ok(ValidationReport::default());
+    }
+    let (nodes, report) = read_nodes_keep_going(args.from, args.input(), None, options)?;
+    info!(
+        node_count = nodes.len(),
+        issues = report.issues.len(),
+        "read nodes"
+    );
+    if args.update && matches!(args.to, DataFormat::Excel) {
+        sync::update_excel(&nodes, args.output(), options)?;
+        return Ok(report);
+    }
+    // Like the conversions, workbook and CSV inputs fall back on their
+    // embedded context unless the frame has one.
+    let framed = options
+        .frame
+        .as_ref()
+        .is_some_and(|frame| frame.get("@context").is_some());
+    let context = match args.from {
+        DataFormat::Excel if context.is_none() && !framed => {
+            excel_read::read_context(args.input())?
+        }
+        DataFormat::Csv if context.is_none() && !framed => {
+            csv_read::read_context_in_dialect(args.input(), &options.csv_dialect)?
+        }
+        _ => context,
+    };
+    let nodes = keep_ignored(args, nodes, rdf_format, options)?;
+    write_nodes(args.to, args.output(), &nodes, rdf_format, context, options)?;
+    Ok(report)
+}
+
+/// Carries the values of the ignored predicates of `options` over from the
+/// existing output onto `nodes` read from a workbook or CSV directory, like
+/// the conversions of [`sync`] do, see [`sync::keep_ignored_from`].
+fn keep_ignored(
+    args: &SyncArgs,
+    nodes: Vec<Node>,
+    rdf_format: RdfFormat,
+    options: &SyncOptions,
+) -> Result<Vec<Node>> {
+    let from_tables = matches!(args.from, DataFormat::Excel | DataFormat::Csv);
+    let to_graph = matches!(
+        args.to,
+        DataFormat::JsonLd | DataFormat::Rdf | DataFormat::NodesJson
+    );
+    if !from_tables || !to_graph {
+        return Ok(nodes);
+    }
+    sync::keep_ignored_from(nodes, args.output(), options, |path, plain| {
+        read_nodes(args.to, path, Some(rdf_format), plain)
+    })
+}
+
+/// Runs the conversion selected by `args.from` and `args.to`, writing RDF as
+/// `rdf_format`.
+fn convert(
+    args: &SyncArgs,
+    context: Option<Value>,
+    rdf_format: RdfFormat,
+    options: &SyncOptions,
+) -> Result<()> {
+    if args.update && matches!(args.to, DataFormat::Excel) {
+        let nodes = read_nodes(args.from, args.input(), None, options)?;
+        sync::update_excel(&nodes, args.output(), options)?;
+        return Ok(());
+    }
+    match (args.from, args.to) {
+        (DataFormat::JsonLd, DataFormat::Excel) => {
+            sync::jsonld_to_excel_with_options(args.input(), args.output(), options)
+        }
+        (DataFormat::Excel, DataFormat::JsonLd) => {
+            sync::excel_to_jsonld_with_options(args.input(), args.output(), context, options)
+        }
+        (DataFormat::JsonLd, DataFormat::Rdf) => {
+            sync::jsonld_to_rdf_with_options(args.input(), args.output(), rdf_format, options)
+        }
+        (DataFormat::Excel, DataFormat::Rdf) => {
+            sync::excel_to_rdf_with_options(args.input(), args.output(), rdf_format, options)
+        }
+        (DataFormat::Rdf, DataFormat::Excel) => {
+            sync::rdf_to_excel_with_options(args.input(), args.output(), options)
+        }
+        (DataFormat::Rdf, DataFormat::JsonLd) => {
+            sync::rdf_to_jsonld_with_options(args.input(), args.output(), context, options)
+        }
+        (DataFormat::JsonLd, DataFormat::Csv) => {
+            sync::jsonld_to_csv(args.input(), args.output(), options)
+        }
+        (DataFormat::Csv, DataFormat::JsonLd) => {
+            sync::csv_to_jsonld(args.input(), args.output(), context, options)
+        }
+        (DataFormat::Rdf, DataFormat::Csv) => {
+            sync::rdf_to_csv(args.input(), args.output(), options)
+        }
+        (DataFormat::Csv, DataFormat::Rdf) => {
+            sync::csv_to_rdf(args.input(), args.output(), rdf_format, options)
+        }
+        (DataFormat::Excel, DataFormat::Csv) => {
+            sync::excel_to_csv(args.input(), args.output(), options)
+        }
+        (DataFormat::Csv, DataFormat::Excel) => {
+            sync::csv_to_excel(args.input(), args.output(), options)
+        }
+        (DataFormat::Sql, to) => {
+            let nodes = sync::read_sql(args.input(), options)?;
+            write_nodes(to, args.output(), &nodes, rdf_format, context, options)
+        }
+        (from, to @ DataFormat::NodesJson)
+        | (from @ DataFormat::NodesJson, to)
+        | (from, to @ (DataFormat::Parquet | DataFormat::Sqlite)) => {
+            let nodes = read_nodes(from, args.input(), None, options)?;
+            let nodes = keep_ignored(args, nodes, rdf_format, options)?;
+            write_nodes(to, args.output(), &nodes, rdf_format, context, options)
+        }
+        _ => Err(ToolError::UnsupportedConversion {
+            from: args.from.to_string(),
+            to: args.to.to_string(),
+        }),
+    }
+}
+
+/// Runs the conversion like [`convert`] when the input or output is `-`,
+/// reading the input from stdin or writing the output to stdout. Only
+/// JSON-LD and RDF travel through pipes; the other side may be a file of any
+/// representation.
+fn convert_stdio(
+    args: &SyncArgs,
+    context: Option<Value>,
+    rdf_format: RdfFormat,
+    options: &SyncOptions,
+) -> Result<()> {
+    for (path, format) in [(args.input(), args.from), (args.output(), args.to)] {
+        if is_stdio(path)
+            && !matches!(
+                format,
+                DataFormat::JsonLd | DataFormat::Rdf | DataFormat::NodesJson
+            )
+        {
+            return Err(ToolError::UnsupportedStream {
+                format: format.to_string(),
+            });
+        }
+    }
+    if args.update || args.reads_output_back() || args.keep_going || args.provenance.is_some() {
+        return Err(ToolError::InvalidConfig(
+            "--update, --emit-changes, --change-feed, --history, --keep-going, --provenance, and --html-report need files, not `-`"
+                .into(),
+        ));
+    }
+
+    let nodes = read_input(args, options)?;
+    info!(node_count = nodes.len(), "read nodes");
+
+    if !is_stdio(args.output()) {
+        return write_nodes(args.to, args.output(), &nodes, rdf_format, context, options);
+    }
+    let mut stdout = BufWriter::new(std::io::stdout().lock());
+    match args.to {
+        DataFormat::Rdf => {
+            sync::write_rdf_to(&nodes, &mut stdout, rdf_format, context.as_ref(), options)?
+        }
+        DataFormat::NodesJson => nodes_json::write_nodes_json_to(&mut stdout, &nodes)?,
+        _ => {
+            sync::write_jsonld_to(&nodes, &mut stdout, context, options)?;
+            stdout.write_all(b"\n")?;
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Reads the input of `args`, from stdin when it is `-`.
+fn read_input(args: &SyncArgs, options: &SyncOptions) -> Result<Vec<Node>> {
+    if !is_stdio(args.input()) {
+        return read_nodes(args.from, args.input(), None, options);
+    }
+    let converter = Converter::new().with_options(options.clone());
+    let stdin = std::io::stdin().lock();
+    let conversion = match args.from {
+        DataFormat::Rdf => {
+            let format = options.rdf_input_format.unwrap_or(RdfFormat::Turtle);
+            converter.read_rdf(stdin, format)
+        }
+        DataFormat::JsonLd => converter.read_jsonld(stdin),
+        DataFormat::NodesJson => return sync::read_nodes_json_from(stdin, options),
+        format => {
+            return Err(ToolError::UnsupportedStream {
+                format: format.to_string(),
+            });
+        }
+    };
+    Ok(conversion?.into_nodes())
+}
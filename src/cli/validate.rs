@@ -0,0 +1,139 @@
+//! The `validate` subcommand, checking a data set for structural issues and
+//! against SHACL shapes.
+
+use std::path::PathBuf;
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::io::{csv_read, excel_read};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use aideon_tools::aideon::tools::validate;
+use aideon_tools::aideon::tools::validate::ValidationReport;
+use aideon_tools::{Result, ToolError};
+use tracing::info;
+
+use crate::cli::args::{CoercionArgs, CsvArgs, InputEncodingArgs};
+use crate::cli::formats::{DataFormat, RdfFormatKind, input_format};
+use crate::cli::io::{guess_rdf_format, read_nodes, read_nodes_keep_going};
+use crate::cli::report::{print_issue, write_json_report};
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct ValidateArgs {
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Representation of the input.
+    #[arg(long, value_parser = input_format())]
+    format: DataFormat,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// Write the issues as a JSON report.
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+
+    /// SHACL shapes graph to validate the input against.
+    #[arg(long, value_name = "PATH")]
+    shapes: Option<PathBuf>,
+
+    /// Write the SHACL validation report graph (format inferred from the
+    /// extension, Turtle by default).
+    #[arg(long, value_name = "PATH", requires = "shapes")]
+    shacl_report: Option<PathBuf>,
+
+    /// Report the cells of a workbook or CSV directory and the statements
+    /// of an RDF input that cannot be read as errors, together with the
+    /// other issues, instead of failing at the first.
+    #[arg(long)]
+    keep_going: bool,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+}
+
+/// Executes the validate subcommand, reporting structural issues and SHACL
+/// results and failing when any error or violation is found.
+pub(crate) fn execute_validate(args: ValidateArgs, profile: &Profile) -> Result<()> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input));
+    }
+
+    let rdf_format = args.rdf_format.map(RdfFormat::from);
+    let mut options = SyncOptions {
+        coercions: args.coercions.load()?,
+        ..SyncOptions::default()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let (nodes, sheet_report) = match args.format {
+        format if args.keep_going => {
+            read_nodes_keep_going(format, &args.input, rdf_format, &options)?
+        }
+        DataFormat::Excel => excel_read::read_nodes_with_report(
+            &args.input,
+            &options.coercions,
+            &options.skip_sheets,
+        )?,
+        DataFormat::Csv => csv_read::read_nodes_with_report_in_dialect(
+            &args.input,
+            &options.csv_dialect,
+            &options.coercions,
+            &options.skip_sheets,
+        )?,
+        format => (
+            read_nodes(format, &args.input, rdf_format, &options)?,
+            ValidationReport::default(),
+        ),
+    };
+    let nodes = sync::natural_key_ids(sync::assign_ids(nodes, &options)?, &options)?;
+    let mut report = validate::validate_nodes(&nodes);
+    report.issues.extend(sheet_report.issues);
+    let mut errors = report.error_count();
+    report.issues.iter().for_each(print_issue);
+    info!(
+        node_count = nodes.len(),
+        errors = report.error_count(),
+        warnings = report.warning_count(),
+        "validation finished"
+    );
+
+    if let Some(report_path) = &args.report {
+        write_json_report(report_path, &report)?;
+    }
+
+    if let Some(shapes) = &args.shapes {
+        let shacl = sync::validate_shacl(&nodes, shapes)?;
+        for result in &shacl.results {
+            let path = result
+                .path
+                .as_deref()
+                .map(|path| format!(" [{path}]"))
+                .unwrap_or_default();
+            println!(
+                "{}: {}{path}: {}",
+                result.severity.as_str(),
+                result.focus_node,
+                result.message
+            );
+        }
+        if let Some(report_path) = &args.shacl_report {
+            shacl.write(report_path, guess_rdf_format(report_path))?;
+        }
+        errors += shacl.violation_count();
+    }
+
+    if errors > 0 {
+        return Err(ToolError::ValidationFailed { errors });
+    }
+    Ok(())
+}
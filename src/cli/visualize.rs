@@ -0,0 +1,136 @@
+//! The `visualize` subcommand, drawing a data set as a DOT or Mermaid
+//! diagram.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::filter::IriPattern;
+use aideon_tools::aideon::tools::io::atomic;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::model::Iri;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use aideon_tools::aideon::tools::visualize::{self, GraphFormat, GraphSelection};
+use aideon_tools::{Result, ToolError};
+use tracing::info;
+
+use crate::cli::args::{CoercionArgs, CsvArgs, InputEncodingArgs, RemoteContextArgs};
+use crate::cli::formats::{DataFormat, GraphFormatKind, RdfFormatKind, input_format};
+use crate::cli::io::read_nodes;
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct VisualizeArgs {
+    /// Input file path (a directory for CSV).
+    #[arg(long)]
+    input: PathBuf,
+
+    /// Representation of the input; detected from the path when omitted.
+    #[arg(long, value_parser = input_format())]
+    format: Option<DataFormat>,
+
+    /// Explicit RDF serialisation format of the input.
+    #[arg(long, value_enum)]
+    rdf_format: Option<RdfFormatKind>,
+
+    /// Output file path, or `-` for stdout.
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Diagram language; detected from the output extension (`.dot`, `.gv`,
+    /// `.mmd`) when omitted, defaulting to DOT.
+    #[arg(long, value_enum)]
+    graph_format: Option<GraphFormatKind>,
+
+    /// Draw only the nodes with a type matching this IRI or pattern, such as
+    /// `schema:Person`. May be repeated.
+    #[arg(long = "include-type", value_name = "PATTERN")]
+    include_types: Vec<String>,
+
+    /// Draw the nodes reachable from this node through references. May be
+    /// repeated.
+    #[arg(long = "root", value_name = "IRI")]
+    roots: Vec<String>,
+
+    /// Most references followed from the roots.
+    #[arg(long, value_name = "HOPS", requires = "roots")]
+    depth: Option<usize>,
+
+    /// Predicate labelling the nodes, `rdfs:label` by default; nodes without
+    /// one are labelled with their id.
+    #[arg(long, value_name = "IRI")]
+    label_predicate: Option<String>,
+
+    #[command(flatten)]
+    remote: RemoteContextArgs,
+
+    #[command(flatten)]
+    coercions: CoercionArgs,
+
+    #[command(flatten)]
+    csv: CsvArgs,
+
+    #[command(flatten)]
+    encoding: InputEncodingArgs,
+}
+
+/// Executes the visualize subcommand, drawing the nodes chosen by the type,
+/// root, and depth flags as a DOT or Mermaid diagram.
+pub(crate) fn execute_visualize(args: VisualizeArgs, profile: &Profile) -> Result<()> {
+    if !args.input.exists() {
+        return Err(ToolError::MissingInput(args.input));
+    }
+
+    let format = args
+        .format
+        .unwrap_or_else(|| DataFormat::detect(&args.input));
+    let rdf_format = args.rdf_format.map(RdfFormat::from);
+    let mut options = SyncOptions {
+        coercions: args.coercions.load()?,
+        ..args.remote.sync_options()
+    };
+    profile.apply(&mut options);
+    args.csv.apply(&mut options.csv_dialect);
+    args.encoding.apply(&mut options.input_encoding);
+    let nodes = read_nodes(format, &args.input, rdf_format, &options)?;
+
+    let prefixes = options.prefix_registry(None);
+    let mut selection = GraphSelection {
+        types: args
+            .include_types
+            .iter()
+            .map(|pattern| IriPattern::new(pattern, &prefixes))
+            .collect(),
+        roots: args
+            .roots
+            .iter()
+            .map(|root| Iri::expand(root, &prefixes))
+            .collect(),
+        depth: args.depth,
+        ..GraphSelection::default()
+    };
+    if let Some(predicate) = &args.label_predicate {
+        selection.label_predicate = Iri::expand(predicate, &prefixes);
+    }
+    let graph_format = args
+        .graph_format
+        .map(GraphFormat::from)
+        .or_else(|| {
+            args.output
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(GraphFormat::from_extension)
+        })
+        .unwrap_or(GraphFormat::Dot);
+
+    if args.output == Path::new("-") {
+        visualize::write_graph(std::io::stdout().lock(), &nodes, &selection, graph_format)?;
+    } else {
+        atomic::write_atomically(&args.output, |partial| {
+            let writer = BufWriter::new(File::create(partial)?);
+            visualize::write_graph(writer, &nodes, &selection, graph_format)
+        })?;
+    }
+    info!(node_count = nodes.len(), ?graph_format, "graph drawn");
+    Ok(())
+}
@@ -9,4 +9,7 @@
 
 pub mod aideon;
 
-pub use aideon::tools::{Result, ToolError, error, flatten, io, model, sync};
+pub use aideon::tools::{
+    Result, ToolError, batch, config, convert, diff, error, flatten, interrupt, io, model, report,
+    roundtrip, shacl, site, snippet, sync, telemetry, validate,
+};
@@ -0,0 +1,53 @@
+use aideon_tools::aideon::tools::flatten::CellEncoding;
+use aideon_tools::aideon::tools::roundtrip::{self, LossKind};
+
+use crate::common::parse_jsonld;
+
+#[test]
+fn faithful_dataset_is_lossless() {
+    let document = serde_json::json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Alice",
+                "https://schema.org/knows": [{ "@id": "https://example.com/people/2" }]
+            },
+            { "@id": "https://example.com/people/2", "https://schema.org/name": "30" }
+        ]
+    });
+    let nodes = parse_jsonld(&document);
+
+    for encoding in [CellEncoding::Json, CellEncoding::Bare] {
+        let report = roundtrip::check_roundtrip(&nodes, encoding).expect("check run");
+        assert!(report.is_lossless(), "{:?}", report.losses);
+    }
+}
+
+#[test]
+fn lossy_properties_are_reported() {
+    // A reference and a literal named `<predicate>Id` share one column.
+    let document = serde_json::json!({
+        "@id": "https://example.com/people/1",
+        "@type": "https://schema.org/Person",
+        "https://example.com/vocab/employee": { "@id": "https://example.com/staff/42" },
+        "https://example.com/vocab/employeeId": "E-42"
+    });
+    let nodes = parse_jsonld(&document);
+
+    let report = roundtrip::check_roundtrip(&nodes, CellEncoding::Json).expect("check run");
+    let kinds: Vec<(LossKind, &str)> = report
+        .losses
+        .iter()
+        .map(|loss| (loss.kind, loss.predicate.as_deref().unwrap_or_default()))
+        .collect();
+
+    assert!(kinds.contains(&(
+        LossKind::PropertyLost,
+        "https://example.com/vocab/employeeId"
+    )));
+    assert!(kinds.contains(&(
+        LossKind::PropertyAltered,
+        "https://example.com/vocab/employee"
+    )));
+}
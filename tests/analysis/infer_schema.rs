@@ -0,0 +1,158 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::model::{Node, PropertyValue};
+use aideon_tools::aideon::tools::schema::{self, SCHEMA_SHEET};
+use aideon_tools::aideon::tools::shacl::ShapesGraph;
+use calamine::{Reader, Xlsx, open_workbook};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{ORGANIZATION, PERSON, aideon, assert_success, parse_jsonld};
+
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+
+fn staff_directory() -> Value {
+    json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": PERSON,
+                "https://schema.org/name": "Ada",
+                "https://schema.org/email": ["ada@example.com", "countess@example.com"],
+                "https://schema.org/worksFor": { "@id": "https://example.com/acme" },
+                "https://schema.org/knows": { "@id": "https://example.com/people/9" }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": PERSON,
+                "https://schema.org/name": "Grace",
+                "https://schema.org/birthDate": {
+                    "@value": "1906-12-09",
+                    "@type": "http://www.w3.org/2001/XMLSchema#date"
+                }
+            },
+            {
+                "@id": "https://example.com/acme",
+                "@type": ORGANIZATION,
+                "https://schema.org/name": "ACME"
+            }
+        ]
+    })
+}
+
+fn nodes() -> Vec<Node> {
+    parse_jsonld(&staff_directory())
+}
+
+#[test]
+fn observed_counts_datatypes_and_classes_make_conforming_shapes() {
+    let nodes = nodes();
+    let schema = schema::infer_schema(&nodes);
+
+    let person = &schema.classes[PERSON];
+    assert_eq!(person.instances, 2);
+    let name = &person.properties["https://schema.org/name"];
+    assert_eq!((name.min_count, name.max_count), (1, 1));
+    assert_eq!(Vec::from_iter(&name.datatypes), [XSD_STRING]);
+    let email = &person.properties["https://schema.org/email"];
+    assert_eq!((email.min_count, email.max_count), (0, 2));
+    let works_for = &person.properties["https://schema.org/worksFor"];
+    assert!(works_for.datatypes.is_empty());
+    assert_eq!(Vec::from_iter(&works_for.classes), [ORGANIZATION]);
+    let knows = &person.properties["https://schema.org/knows"];
+    assert!(knows.classes.is_empty());
+    assert_eq!(knows.untyped_references, 1);
+    assert_eq!(schema.classes[ORGANIZATION].properties.len(), 1);
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("schema.ttl");
+    rdf::write_rdf(&path, &schema.to_nodes(), RdfFormat::Turtle).expect("schema written");
+    let shapes = ShapesGraph::load(&path, None).expect("shapes read");
+    assert_eq!(shapes.len(), 2);
+    let report = shapes.validate(&nodes);
+    assert!(report.conforms(), "{:?}", report.results);
+
+    let mut grown = staff_directory();
+    grown["@graph"][1]["https://schema.org/worksFor"] = json!("ACME");
+    grown["@graph"][2]["https://schema.org/name"] = json!(["ACME", "Acme Corp"]);
+    let report = shapes.validate(&parse_jsonld(&grown));
+    assert_eq!(report.violation_count(), 2, "{:?}", report.results);
+
+    let schema_nodes = schema.to_nodes();
+    let birth_date = schema_nodes
+        .iter()
+        .find(|node| node.id.as_str() == "https://schema.org/birthDate")
+        .expect("property described");
+    assert_eq!(
+        birth_date.properties["http://www.w3.org/2000/01/rdf-schema#range"],
+        PropertyValue::ObjectRef("http://www.w3.org/2001/XMLSchema#date".into())
+    );
+    let name = schema_nodes
+        .iter()
+        .find(|node| node.id.as_str() == "https://schema.org/name")
+        .unwrap();
+    assert!(
+        !name
+            .properties
+            .contains_key("http://www.w3.org/2000/01/rdf-schema#domain"),
+        "two types use it"
+    );
+}
+
+#[test]
+fn infer_schema_writes_turtle_or_a_schema_sheet() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, staff_directory().to_string()).unwrap();
+
+    let turtle = temp_dir.path().join("schema.ttl");
+    let workbook = temp_dir.path().join("schema.xlsx");
+    for output in [&turtle, &workbook] {
+        let run = aideon()
+            .arg("infer-schema")
+            .arg("--input")
+            .arg(&input)
+            .arg("--output")
+            .arg(output)
+            .output()
+            .expect("CLI ran");
+        assert_success(&run);
+        assert!(
+            String::from_utf8_lossy(&run.stderr)
+                .contains("inferred 2 class(es) using 6 propert(ies) from 3 node(s)")
+        );
+    }
+
+    let turtle = fs::read_to_string(&turtle).unwrap();
+    assert!(turtle.contains("@prefix sh:"), "{turtle}");
+    assert!(turtle.contains("sh:targetClass schema:Person"), "{turtle}");
+
+    let mut excel: Xlsx<_> = open_workbook(&workbook).expect("workbook opened");
+    let range = excel.worksheet_range(SCHEMA_SHEET).expect("schema sheet");
+    let rows: Vec<Vec<String>> = range
+        .rows()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .collect();
+    assert_eq!(
+        rows[0],
+        [
+            "class",
+            "instances",
+            "property",
+            "minCount",
+            "maxCount",
+            "datatypes",
+            "classes"
+        ]
+    );
+    assert!(rows.contains(&vec![
+        PERSON.to_string(),
+        "2".to_string(),
+        "https://schema.org/worksFor".to_string(),
+        "0".to_string(),
+        "1".to_string(),
+        String::new(),
+        ORGANIZATION.to_string(),
+    ]));
+}
@@ -0,0 +1,111 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::{WorkbookOptions, build_workbook};
+use aideon_tools::aideon::tools::inspect::{DatasetStatistics, NULL_LABEL};
+use aideon_tools::aideon::tools::io::excel_write;
+use aideon_tools::aideon::tools::model::{Node, XSD_INTEGER, XSD_STRING};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{PERSON, aideon, assert_success, parse_jsonld};
+
+const KNOWS: &str = "https://schema.org/knows";
+
+fn untidy_graph() -> Vec<Node> {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": PERSON,
+                "https://schema.org/name": ["Ada", "Augusta"],
+                "https://schema.org/knows": [
+                    { "@id": "https://example.com/people/2" },
+                    { "@id": "https://example.com/people/9" }
+                ]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": PERSON,
+                "https://schema.org/name": "Bob",
+                "https://schema.org/age": 41,
+                "https://schema.org/nickname": null
+            },
+            {
+                "@id": "https://example.com/notes/1",
+                "https://schema.org/text": "unsorted"
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+#[test]
+fn statistics_count_types_predicates_datatypes_and_orphans() {
+    let statistics = DatasetStatistics::with_sheets(&untidy_graph(), &WorkbookOptions::default())
+        .expect("statistics computed");
+    assert_eq!(statistics.node_count, 3);
+    assert_eq!(statistics.type_counts[PERSON], 2);
+    assert_eq!(statistics.untyped_count, 1);
+    assert_eq!(statistics.graphs.len(), 1);
+    assert_eq!(statistics.graphs[0].graph, None);
+
+    let name = &statistics.predicates["https://schema.org/name"];
+    assert_eq!((name.node_count, name.value_count), (2, 3));
+    assert_eq!(
+        statistics.predicates_by_use()[0].0,
+        "https://schema.org/name"
+    );
+    assert_eq!(statistics.datatype_counts[XSD_STRING], 4);
+    assert_eq!(statistics.datatype_counts[XSD_INTEGER], 1);
+    assert_eq!(statistics.datatype_counts[NULL_LABEL], 1);
+
+    assert_eq!(statistics.orphan_references.len(), 1);
+    let orphan = &statistics.orphan_references[0];
+    assert_eq!(orphan.predicate, KNOWS);
+    assert_eq!(orphan.target, "https://example.com/people/9");
+
+    // Bookkeeping sheets are left out; the type sheet with the most rows
+    // comes first.
+    assert!(
+        statistics
+            .sheets
+            .iter()
+            .all(|sheet| sheet.sheet_name != "Entities")
+    );
+    assert_eq!(statistics.sheets[0].sheet_name, "https___schema.org_Person");
+    assert_eq!(statistics.sheets[0].row_count, 2);
+}
+
+#[test]
+fn inspect_prints_and_reports_workbook_statistics() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&input, &build_workbook(&untidy_graph()).unwrap()).unwrap();
+    let report = temp_dir.path().join("statistics.json");
+
+    let run = aideon()
+        .args(["inspect", "--top", "1", "--input"])
+        .arg(&input)
+        .arg("--report")
+        .arg(&report)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    let stdout = String::from_utf8_lossy(&run.stdout);
+    assert!(stdout.starts_with("nodes: 3\n"), "{stdout}");
+    assert!(stdout.contains("type: https://schema.org/Person: 2 node(s)\n"));
+    assert!(stdout.contains("type: (none): 1 node(s)\n"));
+    assert!(stdout.contains("predicate: https://schema.org/name: 3 value(s) on 2 node(s)\n"));
+    assert!(stdout.contains(
+        "orphan: https://example.com/people/1 [https://schema.org/knows]: https://example.com/people/9\n"
+    ));
+    assert_eq!(stdout.matches("sheet: ").count(), 1);
+
+    let report: Value = serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(report["node_count"], 3);
+    assert_eq!(
+        report["orphan_references"][0]["target"],
+        "https://example.com/people/9"
+    );
+    assert!(report["sheets"].as_array().unwrap().len() > 1);
+}
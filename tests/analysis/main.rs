@@ -0,0 +1,14 @@
+//! Integration tests of the commands that inspect, query, split, and
+//! validate graphs.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod check_roundtrip;
+mod infer_schema;
+mod inspect;
+mod query;
+mod shacl;
+mod split;
+mod validate;
+mod view;
@@ -0,0 +1,118 @@
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::io::rdf;
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::query::{
+    self, QueryForm, QueryOutputFormat, QueryResultsFormat, RdfFormat,
+};
+use serde_json::{Value, json};
+
+use crate::common::parse_jsonld;
+
+fn people_in_graphs() -> Vec<Node> {
+    let document = json!({
+        "@context": { "@vocab": "https://schema.org/" },
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "Person",
+                "name": "Alice",
+                "knows": [{ "@id": "https://example.com/people/2" }]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "Person",
+                "name": "Bob"
+            },
+            {
+                "@id": "https://example.com/archive",
+                "@graph": [
+                    {
+                        "@id": "https://example.com/people/3",
+                        "@type": "Person",
+                        "name": "Carol"
+                    }
+                ]
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+fn run(query: &str, format: QueryOutputFormat) -> (QueryForm, String) {
+    let mut output = Vec::new();
+    let form =
+        query::run_query(&people_in_graphs(), query, &mut output, format).expect("query ran");
+    (form, String::from_utf8(output).expect("UTF-8 output"))
+}
+
+#[test]
+fn select_writes_solutions_in_query_variable_order() {
+    let (form, csv) = run(
+        "PREFIX s: <https://schema.org/>
+         SELECT ?name ?person WHERE { ?person a s:Person ; s:name ?name } ORDER BY ?name",
+        QueryOutputFormat::default(),
+    );
+    assert_eq!(form, QueryForm::Select);
+    assert_eq!(
+        csv,
+        "name,person\r\nAlice,https://example.com/people/1\r\nBob,https://example.com/people/2\r\n"
+    );
+
+    let (_, json) = run(
+        "PREFIX s: <https://schema.org/>
+         SELECT ?name WHERE { GRAPH <https://example.com/archive> { ?person s:name ?name } }",
+        QueryOutputFormat {
+            results: QueryResultsFormat::Json,
+            ..QueryOutputFormat::default()
+        },
+    );
+    let json: Value = serde_json::from_str(&json).expect("JSON results");
+    assert_eq!(json["head"]["vars"], json!(["name"]));
+    assert_eq!(json["results"]["bindings"][0]["name"]["value"], "Carol");
+}
+
+#[test]
+fn ask_and_construct_use_their_own_result_shapes() {
+    let (form, tsv) = run(
+        "ASK { <https://example.com/people/1> <https://schema.org/knows> ?friend }",
+        QueryOutputFormat {
+            results: QueryResultsFormat::Tsv,
+            ..QueryOutputFormat::default()
+        },
+    );
+    assert_eq!(form, QueryForm::Ask);
+    assert_eq!(tsv.trim(), "true");
+
+    let (form, ntriples) = run(
+        "PREFIX s: <https://schema.org/>
+         CONSTRUCT { ?friend s:knows ?person } WHERE { ?person s:knows ?friend }",
+        QueryOutputFormat {
+            graph: RdfFormat::NTriples,
+            ..QueryOutputFormat::default()
+        },
+    );
+    assert_eq!(form, QueryForm::Construct);
+    assert_eq!(
+        ntriples,
+        "<https://example.com/people/2> <https://schema.org/knows> <https://example.com/people/1> .\n"
+    );
+
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let path = dir.path().join("inverse.nt");
+    std::fs::write(&path, ntriples).expect("graph written");
+    let nodes = rdf::read_rdf(&path, None).expect("graph read back");
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].id, "https://example.com/people/2");
+}
+
+#[test]
+fn invalid_queries_are_reported() {
+    let error = query::run_query(
+        &people_in_graphs(),
+        "SELECT WHERE {",
+        Vec::new(),
+        QueryOutputFormat::default(),
+    )
+    .expect_err("syntax error");
+    assert!(matches!(error, ToolError::Sparql(_)));
+}
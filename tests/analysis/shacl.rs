@@ -0,0 +1,209 @@
+use aideon_tools::aideon::tools::io::rdf;
+use aideon_tools::aideon::tools::model::{PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::shacl::{SH, ShaclSeverity};
+use aideon_tools::aideon::tools::sync;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const SHAPES: &str = r#"
+@prefix sh: <http://www.w3.org/ns/shacl#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+@prefix schema: <https://schema.org/> .
+@prefix ex: <https://example.com/shapes/> .
+
+ex:PersonShape a sh:NodeShape ;
+    sh:targetClass schema:Person ;
+    sh:nodeKind sh:IRI ;
+    sh:property [
+        sh:path schema:name ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:datatype xsd:string ;
+        sh:minLength 2
+    ] ;
+    sh:property [
+        sh:path schema:email ;
+        sh:pattern "^[^@]+@example\\.com$" ;
+        sh:flags "i" ;
+        sh:severity sh:Warning ;
+        sh:message "email must be an example.com address"
+    ] ;
+    sh:property [
+        sh:path schema:knows ;
+        sh:class schema:Person
+    ] ;
+    sh:property [
+        sh:path schema:status ;
+        sh:in ( "active" "retired" )
+    ] .
+"#;
+
+fn nonconforming_people() -> serde_json::Value {
+    serde_json::json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Alice",
+                "https://schema.org/email": "ALICE@EXAMPLE.COM",
+                "https://schema.org/status": "active",
+                "https://schema.org/knows": { "@id": "https://example.com/people/2" }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": ["B", "Bobby"],
+                "https://schema.org/email": "bob@elsewhere.org",
+                "https://schema.org/status": "unknown",
+                "https://schema.org/knows": { "@id": "https://example.com/org/1" }
+            },
+            {
+                "@id": "https://example.com/org/1",
+                "@type": "https://schema.org/Organization"
+            },
+            {
+                "@id": "_:ghost",
+                "@type": "https://schema.org/Person"
+            }
+        ]
+    })
+}
+
+#[test]
+fn shacl_reports_violations_per_node_and_predicate() {
+    let dir = tempdir().expect("temp dir");
+    let shapes_path = dir.path().join("shapes.ttl");
+    std::fs::write(&shapes_path, SHAPES).expect("shapes written");
+    let nodes = parse_jsonld(&nonconforming_people());
+
+    let report = sync::validate_shacl(&nodes, &shapes_path).expect("shapes evaluated");
+    let mut results: Vec<(&str, Option<&str>, &str, ShaclSeverity)> = report
+        .results
+        .iter()
+        .map(|result| {
+            (
+                result.focus_node.as_str(),
+                result.path.as_deref(),
+                result.constraint.strip_prefix(SH).unwrap_or(""),
+                result.severity,
+            )
+        })
+        .collect();
+    results.sort();
+
+    assert_eq!(
+        results,
+        vec![
+            (
+                "_:ghost",
+                None,
+                "NodeKindConstraintComponent",
+                ShaclSeverity::Violation
+            ),
+            (
+                "_:ghost",
+                Some("https://schema.org/name"),
+                "MinCountConstraintComponent",
+                ShaclSeverity::Violation
+            ),
+            (
+                "https://example.com/people/2",
+                Some("https://schema.org/email"),
+                "PatternConstraintComponent",
+                ShaclSeverity::Warning
+            ),
+            (
+                "https://example.com/people/2",
+                Some("https://schema.org/knows"),
+                "ClassConstraintComponent",
+                ShaclSeverity::Violation
+            ),
+            (
+                "https://example.com/people/2",
+                Some("https://schema.org/name"),
+                "MaxCountConstraintComponent",
+                ShaclSeverity::Violation
+            ),
+            (
+                "https://example.com/people/2",
+                Some("https://schema.org/name"),
+                "MinLengthConstraintComponent",
+                ShaclSeverity::Violation
+            ),
+            (
+                "https://example.com/people/2",
+                Some("https://schema.org/status"),
+                "InConstraintComponent",
+                ShaclSeverity::Violation
+            ),
+        ]
+    );
+    assert!(!report.conforms());
+    assert_eq!(report.violation_count(), 6);
+
+    let too_short = report
+        .results
+        .iter()
+        .find(|result| result.constraint.ends_with("MinLengthConstraintComponent"))
+        .expect("min length result");
+    assert_eq!(
+        too_short.value,
+        Some(PropertyValue::Scalar(ScalarValue::String("B".into())))
+    );
+    let pattern = report
+        .results
+        .iter()
+        .find(|result| result.severity == ShaclSeverity::Warning)
+        .expect("pattern result");
+    assert_eq!(pattern.message, "email must be an example.com address");
+}
+
+#[test]
+fn shacl_report_is_written_as_a_validation_report_graph() {
+    let dir = tempdir().expect("temp dir");
+    let shapes_path = dir.path().join("shapes.ttl");
+    let report_path = dir.path().join("report.ttl");
+    std::fs::write(&shapes_path, SHAPES).expect("shapes written");
+    let nodes = parse_jsonld(&nonconforming_people());
+
+    let report = sync::validate_shacl(&nodes, &shapes_path).expect("shapes evaluated");
+    report
+        .write(&report_path, rdf::RdfFormat::Turtle)
+        .expect("report written");
+
+    let graph = rdf::read_rdf(&report_path, None).expect("report parsed");
+    let validation = graph
+        .iter()
+        .find(|node| {
+            node.types
+                .contains(format!("{SH}ValidationReport").as_str())
+        })
+        .expect("validation report node");
+    assert_eq!(
+        validation.properties.get(format!("{SH}conforms").as_str()),
+        Some(&PropertyValue::Scalar(ScalarValue::Boolean(false)))
+    );
+    let result_count = graph
+        .iter()
+        .filter(|node| {
+            node.types
+                .contains(format!("{SH}ValidationResult").as_str())
+        })
+        .count();
+    assert_eq!(result_count, report.results.len());
+
+    let alice: Vec<_> = nodes
+        .into_iter()
+        .filter(|node| node.id == "https://example.com/people/1")
+        .collect();
+    let alice_only = sync::validate_shacl(&alice, &shapes_path).expect("shapes evaluated");
+    assert_eq!(
+        alice_only
+            .results
+            .iter()
+            .map(|result| result.constraint.as_str())
+            .collect::<Vec<_>>(),
+        vec![format!("{SH}ClassConstraintComponent")]
+    );
+}
@@ -0,0 +1,104 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::excel_read;
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::query;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+const DEPARTMENTS: &str = "
+    PREFIX schema: <https://schema.org/>
+    SELECT ?department ?node WHERE {
+        ?node a schema:Person ; schema:worksFor ?department .
+    }
+";
+
+fn staff() -> serde_json::Value {
+    json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/worksFor": { "@id": "https://example.com/departments/sales" },
+                "https://schema.org/address": {
+                    "@id": "_:address",
+                    "https://schema.org/addressLocality": "London"
+                }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/worksFor": [
+                    { "@id": "https://example.com/departments/sales" },
+                    { "@id": "https://example.com/departments/research" }
+                ]
+            },
+            {
+                "@id": "https://example.com/people/3",
+                "@type": "https://schema.org/Person"
+            }
+        ]
+    })
+}
+
+fn ids(nodes: &[Node]) -> Vec<&str> {
+    nodes.iter().map(|node| node.id.as_str()).collect()
+}
+
+#[test]
+fn solutions_group_their_nodes_by_the_other_bindings() {
+    let nodes = parse_jsonld(&staff());
+    let partitions = query::partition_nodes(&nodes, DEPARTMENTS).expect("partitioned");
+
+    let names: Vec<&str> = partitions
+        .iter()
+        .map(|partition| partition.name.as_str())
+        .collect();
+    assert_eq!(names, ["research", "sales"]);
+    assert_eq!(
+        partitions[0].key,
+        [Some("https://example.com/departments/research".to_string())]
+    );
+    assert_eq!(ids(&partitions[0].nodes), ["https://example.com/people/2"]);
+    let sales = ids(&partitions[1].nodes);
+    assert_eq!(sales.len(), 3, "{sales:?}");
+    assert!(sales.contains(&"https://example.com/people/1"));
+    assert!(sales.contains(&"https://example.com/people/2"));
+    assert!(sales.iter().any(|id| id.starts_with("_:")), "the address");
+
+    let ask = query::partition_nodes(&nodes, "ASK { ?s ?p ?o }").unwrap_err();
+    assert!(ask.to_string().contains("SELECT queries binding ?node"));
+}
+
+#[test]
+fn split_writes_one_workbook_per_partition() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("staff.jsonld");
+    fs::write(&input, staff().to_string()).unwrap();
+    let sparql = temp_dir.path().join("departments.rq");
+    fs::write(&sparql, DEPARTMENTS).unwrap();
+    let output = temp_dir.path().join("departments");
+
+    let run = aideon()
+        .arg("split")
+        .arg("--input")
+        .arg(&input)
+        .arg("--sparql")
+        .arg(&sparql)
+        .args(["--to", "excel", "--output-dir"])
+        .arg(&output)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+
+    let mut files: Vec<String> = fs::read_dir(&output)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["research.xlsx", "sales.xlsx"]);
+    let research = excel_read::read_nodes(&output.join("research.xlsx")).unwrap();
+    assert_eq!(ids(&research), ["https://example.com/people/2"]);
+}
@@ -0,0 +1,81 @@
+use aideon_tools::aideon::tools::model::{ArrayValue, Node, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::validate::{self, IssueKind, Severity};
+
+fn person(id: &str) -> Node {
+    let mut node = Node::new(id);
+    node.types.insert("https://schema.org/Person".into());
+    node
+}
+
+#[test]
+fn valid_dataset_has_no_issues() {
+    let mut alice = person("https://example.com/people/1");
+    alice.insert_property(
+        "https://schema.org/knows".into(),
+        PropertyValue::ObjectRef("_:bob".into()),
+    );
+    alice.insert_property(
+        "https://schema.org/alternateName".into(),
+        PropertyValue::Array(ArrayValue::Scalars(vec![
+            ScalarValue::String("Ali".into()),
+            ScalarValue::String("Al".into()),
+        ])),
+    );
+    let bob = person("_:bob");
+
+    let report = validate::validate_nodes(&[alice, bob]);
+
+    assert!(report.issues.is_empty(), "{:?}", report.issues);
+    assert!(!report.has_errors());
+}
+
+#[test]
+fn structural_problems_are_reported() {
+    let mut alice = person("https://example.com/people/1");
+    alice.insert_property(
+        "https://schema.org/knows".into(),
+        PropertyValue::ObjectRef("https://example.com/people/404".into()),
+    );
+    alice.insert_property(
+        "".into(),
+        PropertyValue::Scalar(ScalarValue::String("orphan".into())),
+    );
+    alice.insert_property(
+        "https://schema.org/award".into(),
+        PropertyValue::Array(ArrayValue::Scalars(Vec::new())),
+    );
+    alice.insert_property(
+        "https://schema.org/identifier".into(),
+        PropertyValue::Array(ArrayValue::Scalars(vec![
+            ScalarValue::String("A-1".into()),
+            ScalarValue::Integer(1),
+        ])),
+    );
+    let duplicate = person("https://example.com/people/1");
+    let invalid = person("not an iri");
+
+    let report = validate::validate_nodes(&[alice, duplicate, invalid]);
+    let kinds: Vec<(Severity, IssueKind)> = report
+        .issues
+        .iter()
+        .map(|issue| (issue.severity, issue.kind))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (Severity::Error, IssueKind::EmptyPredicate),
+            (Severity::Warning, IssueKind::EmptyPredicate),
+            (Severity::Warning, IssueKind::MixedArray),
+            (Severity::Error, IssueKind::DanglingReference),
+            (Severity::Error, IssueKind::DuplicateId),
+            (Severity::Error, IssueKind::InvalidIri),
+        ]
+    );
+    assert_eq!(report.error_count(), 4);
+    assert_eq!(report.warning_count(), 2);
+
+    let json = serde_json::to_value(&report).expect("report serialises");
+    assert_eq!(json["issues"][3]["kind"], "dangling-reference");
+    assert_eq!(json["issues"][3]["predicate"], "https://schema.org/knows");
+}
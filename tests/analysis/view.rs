@@ -0,0 +1,102 @@
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::view::{self, View, Views};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::common::parse_jsonld;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Person {
+    #[serde(rename = "@id")]
+    id: String,
+    name: String,
+    #[serde(rename = "birthDate")]
+    birth_date: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "view::one_or_many")]
+    knows: Vec<String>,
+}
+
+impl View for Person {
+    const TYPE: &'static str = "https://schema.org/Person";
+    const VOCABULARY: Option<&'static str> = Some("https://schema.org/");
+}
+
+fn people_and_an_organization() -> Vec<Node> {
+    parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": { "@value": "Ada", "@language": "en" },
+                "https://schema.org/birthDate": {
+                    "@value": "1815-12-10",
+                    "@type": "http://www.w3.org/2001/XMLSchema#date"
+                },
+                "https://schema.org/knows": { "@id": "https://example.com/people/2" }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Grace",
+                "https://schema.org/knows": [
+                    { "@id": "https://example.com/people/1" },
+                    { "@id": "https://example.com/people/3" }
+                ]
+            },
+            {
+                "@id": "https://example.com/org/1",
+                "@type": "https://schema.org/Organization",
+                "https://schema.org/name": "Analytical Engines"
+            }
+        ]
+    }))
+}
+
+#[test]
+fn nodes_of_the_type_read_as_structs() {
+    let mut people: Vec<Person> = people_and_an_organization().view().expect("viewed");
+    people.sort_by(|left, right| left.id.cmp(&right.id));
+
+    assert_eq!(
+        people,
+        [
+            Person {
+                id: "https://example.com/people/1".into(),
+                name: "Ada".into(),
+                birth_date: NaiveDate::from_ymd_opt(1815, 12, 10),
+                knows: vec!["https://example.com/people/2".into()],
+            },
+            Person {
+                id: "https://example.com/people/2".into(),
+                name: "Grace".into(),
+                birth_date: None,
+                knows: vec![
+                    "https://example.com/people/1".into(),
+                    "https://example.com/people/3".into()
+                ],
+            },
+        ]
+    );
+}
+
+#[derive(Debug, Deserialize)]
+struct Named {
+    #[serde(rename = "https://schema.org/name")]
+    _name: u32,
+}
+
+impl View for Named {
+    const TYPE: &'static str = "https://schema.org/Organization";
+}
+
+#[test]
+fn nodes_that_do_not_fit_fail_with_their_id() {
+    let error = people_and_an_organization().view::<Named>().unwrap_err();
+    assert!(
+        matches!(&error, ToolError::View { id, .. } if id == "https://example.com/org/1"),
+        "{error}"
+    );
+    assert!(error.to_string().contains("expected u32"), "{error}");
+}
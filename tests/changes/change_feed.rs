@@ -0,0 +1,91 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::change_feed::{FeedFormat, feed_events};
+use aideon_tools::aideon::tools::diff::diff_nodes;
+use aideon_tools::aideon::tools::io::jsonld;
+use aideon_tools::aideon::tools::model::{Iri, PropertyValue};
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, named_people, parse_jsonld};
+
+#[test]
+fn changes_become_activities_or_stream_members() {
+    let before = named_people(&[("1", "Ada"), ("2", "Grace")]);
+    let after = named_people(&[("1", "Augusta Ada"), ("3", "Edsger")]);
+    let changes = diff_nodes(&before, &after);
+    let published = Utc.with_ymd_and_hms(2026, 10, 14, 9, 30, 0).unwrap();
+
+    let activities = feed_events(&changes, &FeedFormat::ActivityStreams, published);
+    let kinds: Vec<&str> = activities
+        .iter()
+        .map(|activity| activity["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds, ["Update", "Delete", "Create"]);
+    assert_eq!(activities[0]["published"], "2026-10-14T09:30:00Z");
+    assert_eq!(
+        activities[0]["object"]["https://schema.org/name"],
+        "Augusta Ada"
+    );
+    assert_eq!(activities[1]["object"]["@type"], "Tombstone");
+    assert_eq!(
+        activities,
+        feed_events(&changes, &FeedFormat::ActivityStreams, published),
+        "ids derive from the change and its time"
+    );
+
+    let stream = FeedFormat::Ldes {
+        stream: "https://example.com/feed".into(),
+    };
+    let members = feed_events(&changes, &stream, published);
+    let nodes = parse_jsonld(&members[0]);
+    let version = nodes
+        .iter()
+        .find(|node| node.id.as_str().starts_with("urn:uuid:"))
+        .expect("version node");
+    assert_eq!(
+        version.properties[&Iri::from("http://purl.org/dc/terms/isVersionOf")],
+        PropertyValue::ObjectRef("https://example.com/people/1".into())
+    );
+    let stream_node = nodes
+        .iter()
+        .find(|node| node.id.as_str() == "https://example.com/feed")
+        .expect("event stream");
+    assert_eq!(
+        stream_node.properties[&Iri::from("https://w3id.org/tree#member")],
+        PropertyValue::ObjectRef(version.id.clone())
+    );
+}
+
+#[test]
+fn sync_appends_to_the_change_feed() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    let output = temp_dir.path().join("people.nq");
+    let feed = temp_dir.path().join("feed.jsonl");
+    for names in [&[("1", "Ada"), ("2", "Grace")][..], &[("1", "Augusta Ada")]] {
+        let document = jsonld::nodes_to_jsonld(&named_people(names), None).unwrap();
+        fs::write(&input, document.to_string()).unwrap();
+        let run = aideon()
+            .args(["sync", "--from", "json-ld", "--input"])
+            .arg(&input)
+            .args(["--to", "rdf", "--output"])
+            .arg(&output)
+            .arg("--change-feed")
+            .arg(&feed)
+            .output()
+            .expect("CLI ran");
+        assert_success(&run);
+    }
+
+    let kinds: Vec<String> = fs::read_to_string(&feed)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let activity: Value = serde_json::from_str(line).expect("JSON line");
+            activity["type"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(kinds, ["Create", "Create", "Update", "Delete"]);
+}
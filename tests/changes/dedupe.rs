@@ -0,0 +1,134 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::jsonld;
+use aideon_tools::aideon::tools::model::{ArrayValue, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::sync::{self, DedupeKey, DuplicateCluster};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, node, parse_jsonld};
+
+const NAME: &str = "https://schema.org/name";
+const EMAIL: &str = "https://schema.org/email";
+const KNOWS: &str = "https://schema.org/knows";
+
+fn near_duplicates() -> serde_json::Value {
+    json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada  Lovelace",
+                "https://schema.org/email": "ada@example.com"
+            },
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "ada lovelace",
+                "https://schema.org/email": "countess@example.com"
+            },
+            {
+                "@id": "https://example.com/org/1",
+                "@type": "https://schema.org/Organization",
+                "https://schema.org/name": "Ada Lovelace"
+            },
+            {
+                "@id": "https://example.com/people/3",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Grace Hopper",
+                "https://schema.org/knows": [
+                    { "@id": "https://example.com/people/1" },
+                    { "@id": "https://example.com/people/2" }
+                ]
+            }
+        ]
+    })
+}
+
+#[test]
+fn nodes_sharing_the_keys_merge_into_the_first_id() {
+    let nodes = parse_jsonld(&near_duplicates());
+    let keys = [DedupeKey::from(NAME), DedupeKey::from("type")];
+    let deduplication = sync::deduplicate(&nodes, &keys);
+
+    assert_eq!(
+        deduplication.clusters,
+        [DuplicateCluster {
+            graph: None,
+            id: "https://example.com/people/1".into(),
+            merged: vec!["https://example.com/people/2".into()],
+        }]
+    );
+    let nodes = &deduplication.nodes;
+    assert_eq!(nodes.len(), 3);
+    assert!(node(nodes, "https://example.com/people/2").is_none());
+    assert!(
+        node(nodes, "https://example.com/org/1").is_some(),
+        "other type"
+    );
+    // Values come in input order, which the reader sorts by id.
+    let ada = node(nodes, "https://example.com/people/1").expect("kept");
+    assert_eq!(
+        ada.properties[EMAIL],
+        PropertyValue::Array(ArrayValue::Scalars(vec![
+            ScalarValue::String("countess@example.com".into()),
+            ScalarValue::String("ada@example.com".into()),
+        ]))
+    );
+    let grace = node(nodes, "https://example.com/people/3").unwrap();
+    assert_eq!(
+        grace.properties[KNOWS],
+        PropertyValue::Array(ArrayValue::ObjectRefs(vec![
+            "https://example.com/people/1".into()
+        ]))
+    );
+
+    let untouched = sync::deduplicate(&nodes[..], &[DedupeKey::from(EMAIL)]);
+    assert!(untouched.clusters.is_empty());
+    assert_eq!(untouched.nodes, *nodes);
+}
+
+#[test]
+fn dedupe_writes_the_merged_nodes_and_a_report() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, near_duplicates().to_string()).unwrap();
+    let output = temp_dir.path().join("deduplicated.jsonld");
+    let report = temp_dir.path().join("clusters.json");
+
+    let run = aideon()
+        .arg("dedupe")
+        .arg("--input")
+        .arg(&input)
+        .args([
+            "--key", NAME, "--key", "type", "--to", "json-ld", "--output",
+        ])
+        .arg(&output)
+        .arg("--report")
+        .arg(&report)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(
+        stderr.contains("merged https://example.com/people/2 into https://example.com/people/1"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("merged 1 duplicate(s) in 1 cluster(s)"));
+
+    let nodes = jsonld::parse_jsonld_document(
+        &serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(nodes.len(), 3);
+    let clusters: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(
+        clusters,
+        json!([{
+            "graph": null,
+            "id": "https://example.com/people/1",
+            "merged": ["https://example.com/people/2"]
+        }])
+    );
+}
@@ -0,0 +1,141 @@
+use aideon_tools::aideon::tools::diff::{self, ChangeKind};
+use aideon_tools::aideon::tools::equivalence;
+use aideon_tools::aideon::tools::io::rdf;
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue};
+use std::fs;
+use tempfile::tempdir;
+
+fn person(id: &str, name: &str) -> Node {
+    let mut node = Node::new(id);
+    node.types.insert("https://schema.org/Person".into());
+    node.insert_property(
+        "https://schema.org/name".into(),
+        PropertyValue::Scalar(ScalarValue::String(name.into())),
+    );
+    node
+}
+
+#[test]
+fn diff_reports_created_updated_and_deleted_nodes() {
+    let before = vec![
+        person("https://example.com/people/1", "Alice"),
+        person("https://example.com/people/2", "Bob"),
+        person("https://example.com/people/3", "Carol"),
+    ];
+    let after = vec![
+        person("https://example.com/people/1", "Alice"),
+        person("https://example.com/people/2", "Robert"),
+        person("https://example.com/people/4", "Dave"),
+    ];
+
+    let changes = diff::diff_nodes(&before, &after);
+    let summary: Vec<(ChangeKind, &str)> = changes
+        .iter()
+        .map(|change| (change.change, change.id.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (ChangeKind::Updated, "https://example.com/people/2"),
+            (ChangeKind::Deleted, "https://example.com/people/3"),
+            (ChangeKind::Created, "https://example.com/people/4"),
+        ]
+    );
+    assert_eq!(changes[0].before.as_ref(), Some(&before[1]));
+    assert_eq!(changes[0].after.as_ref(), Some(&after[1]));
+}
+
+#[test]
+fn changes_are_written_as_ndjson_events() {
+    let after = vec![person("https://example.com/people/1", "Alice")];
+    let changes = diff::diff_nodes(&[], &after);
+
+    let mut buffer = Vec::new();
+    diff::write_ndjson(&mut buffer, &changes).expect("NDJSON written");
+    let text = String::from_utf8(buffer).expect("UTF-8 output");
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 1);
+
+    let event: serde_json::Value = serde_json::from_str(lines[0]).expect("event parsed");
+    assert_eq!(event["change"], "created");
+    assert_eq!(event["id"], "https://example.com/people/1");
+    assert!(event["before"].is_null());
+    assert_eq!(event["after"]["id"], "https://example.com/people/1");
+}
+
+fn read_turtle(source: &str) -> Vec<Node> {
+    let dir = tempdir().expect("temporary directory");
+    let path = dir.path().join("data.ttl");
+    fs::write(&path, source).expect("Turtle written");
+    rdf::read_rdf(&path, None).expect("Turtle parsed")
+}
+
+#[test]
+fn relabelled_blank_nodes_and_reordered_values_are_equal() {
+    let before = read_turtle(
+        r#"
+        @prefix s: <https://schema.org/> .
+        <https://example.com/people/1> s:name "Alice" ;
+            s:address _:home ;
+            s:knows _:friend1, _:friend2, <https://example.com/people/2> .
+        _:home s:streetAddress "1 Main St" ; s:geo _:geo .
+        _:geo s:latitude "52.1" .
+        _:friend1 s:name "Anon" .
+        _:friend2 s:name "Anon" .
+        "#,
+    );
+    let after = read_turtle(
+        r#"
+        @prefix s: <https://schema.org/> .
+        _:z s:name "Anon" .
+        _:y s:latitude "52.1" .
+        <https://example.com/people/1> s:knows <https://example.com/people/2>, _:w, _:z ;
+            s:address _:x ;
+            s:name "Alice" .
+        _:w s:name "Anon" .
+        _:x s:geo _:y ; s:streetAddress "1 Main St" .
+        "#,
+    );
+
+    assert!(equivalence::equivalent(&before, &after));
+    assert!(diff::diff_nodes(&before, &after).is_empty());
+}
+
+#[test]
+fn changed_blank_nodes_are_still_reported() {
+    let before = read_turtle(
+        r#"
+        @prefix s: <https://schema.org/> .
+        <https://example.com/people/1> s:address _:home .
+        _:home s:streetAddress "1 Main St" .
+        <https://example.com/people/2> s:address _:office .
+        _:office s:streetAddress "2 Side St" .
+        "#,
+    );
+    let after = read_turtle(
+        r#"
+        @prefix s: <https://schema.org/> .
+        <https://example.com/people/1> s:address _:a .
+        _:a s:streetAddress "1 Main St" .
+        <https://example.com/people/2> s:address _:b .
+        _:b s:streetAddress "3 Other St" .
+        "#,
+    );
+
+    assert!(!equivalence::equivalent(&before, &after));
+    let mapping = equivalence::blank_node_mapping(&before, &after);
+    assert_eq!(mapping.len(), 1);
+
+    // The unchanged address is matched; the edited one shows up as a change.
+    let changes = diff::diff_nodes(&before, &after);
+    assert!(
+        changes
+            .iter()
+            .all(|change| change.id != "https://example.com/people/1")
+    );
+    assert!(
+        changes
+            .iter()
+            .any(|change| change.id == "https://example.com/people/2")
+    );
+}
@@ -0,0 +1,107 @@
+use std::fs;
+use std::process::Output;
+
+use aideon_tools::aideon::tools::history::History;
+use aideon_tools::aideon::tools::io::jsonld;
+use aideon_tools::aideon::tools::model::{Iri, PropertyValue, ScalarValue};
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, named_people};
+
+#[test]
+fn versions_are_stored_once_and_found_by_number_hash_or_time() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let dir = temp_dir.path().join("history");
+    let at = |day| Utc.with_ymd_and_hms(2026, 10, day, 12, 0, 0).unwrap();
+
+    let mut history = History::open(&dir).expect("empty history");
+    let first = history
+        .commit(&named_people(&[("1", "Ada")]), at(1))
+        .unwrap()
+        .expect("first version");
+    assert!(
+        history
+            .commit(&named_people(&[("1", "Ada")]), at(2))
+            .unwrap()
+            .is_none(),
+        "unchanged datasets are not committed"
+    );
+    let second = history
+        .commit(
+            &named_people(&[("1", "Augusta Ada"), ("2", "Grace")]),
+            at(3),
+        )
+        .unwrap()
+        .expect("second version");
+    assert_eq!((first.number, second.number), (1, 2));
+
+    let history = History::open(&dir).expect("history reopened");
+    assert_eq!(history.versions(), [first.clone(), second.clone()]);
+    assert_eq!(history.resolve("1").unwrap(), &first);
+    assert_eq!(history.resolve(&second.hash[..8]).unwrap(), &second);
+    assert_eq!(history.resolve("2026-10-02").unwrap(), &first);
+    assert_eq!(history.resolve("2026-10-03T12:00:00Z").unwrap(), &second);
+    assert!(history.resolve("2026-09-30").is_err());
+    assert!(history.resolve("7").is_err());
+
+    let nodes = history.load(&first).expect("snapshot read");
+    assert_eq!(
+        nodes[0].properties[&Iri::from("https://schema.org/name")],
+        PropertyValue::Scalar(ScalarValue::String("Ada".into()))
+    );
+}
+
+fn run(args: &[&str]) -> Output {
+    aideon().args(args).output().expect("CLI ran")
+}
+
+#[test]
+fn sync_commits_versions_that_history_lists_shows_and_compares() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = |name: &str| temp_dir.path().join(name).display().to_string();
+    let (input, output, dir) = (path("people.jsonld"), path("people.nt"), path("history"));
+    for names in [&[("1", "Ada"), ("2", "Grace")][..], &[("1", "Augusta Ada")]] {
+        let document = jsonld::nodes_to_jsonld(&named_people(names), None).unwrap();
+        fs::write(&input, document.to_string()).unwrap();
+        let sync = run(&[
+            "sync",
+            "--from",
+            "json-ld",
+            "--input",
+            &input,
+            "--to",
+            "rdf",
+            "--output",
+            &output,
+            "--history",
+            &dir,
+        ]);
+        assert_success(&sync);
+    }
+
+    let log = run(&["history", "log", "--dir", &dir]);
+    let log = String::from_utf8_lossy(&log.stdout);
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 2, "{log}");
+    assert!(lines[0].starts_with("1 ") && lines[0].ends_with(" 2 node(s)"));
+
+    let shown = path("first.jsonld");
+    let show = run(&[
+        "history", "show", "--dir", &dir, "--as-of", "1", "--to", "json-ld", "--output", &shown,
+    ]);
+    assert!(show.status.success());
+    let nodes = jsonld::parse_jsonld_str(&fs::read_to_string(&shown).unwrap()).unwrap();
+    assert_eq!(nodes.len(), 2);
+
+    let diff = run(&["history", "diff", "--dir", &dir, "--before", "1"]);
+    let changes: Vec<String> = String::from_utf8_lossy(&diff.stdout)
+        .lines()
+        .map(|line| {
+            let event: Value = serde_json::from_str(line).expect("JSON line");
+            event["change"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(changes, ["updated", "deleted"]);
+}
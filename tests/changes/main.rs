@@ -0,0 +1,12 @@
+//! Integration tests of changes between graphs: diffs, merges, history,
+//! feeds, and duplicates.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod change_feed;
+mod dedupe;
+mod diff;
+mod history;
+mod merge;
+mod merge_prompt;
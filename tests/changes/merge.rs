@@ -0,0 +1,106 @@
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::model::{ArrayItem, ArrayValue, Node, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::sync::{self, MergeStrategy};
+
+use crate::common::parse_jsonld;
+
+const ALICE: &str = "https://example.com/people/1";
+const NAME: &str = "https://schema.org/name";
+
+fn parse(document: serde_json::Value) -> Vec<Node> {
+    parse_jsonld(&document)
+}
+
+fn canonical() -> Vec<Node> {
+    parse(serde_json::json!({
+        "@graph": [
+            {
+                "@id": ALICE,
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Alice",
+                "https://schema.org/knows": { "@id": "https://example.com/people/2" }
+            },
+            { "@id": "https://example.com/people/2", "https://schema.org/name": "Bob" }
+        ]
+    }))
+}
+
+fn edited() -> Vec<Node> {
+    parse(serde_json::json!({
+        "@graph": [
+            {
+                "@id": ALICE,
+                "@type": "https://schema.org/Agent",
+                "https://schema.org/name": "Alice Smith",
+                "https://schema.org/email": "alice@example.com"
+            },
+            { "@id": "https://example.com/people/3", "https://schema.org/name": "Carol" }
+        ]
+    }))
+}
+
+fn alice(nodes: &[Node]) -> &Node {
+    nodes.iter().find(|node| node.id == ALICE).expect("Alice")
+}
+
+fn string(value: &str) -> ScalarValue {
+    ScalarValue::String(value.into())
+}
+
+#[test]
+fn merge_combines_nodes_and_resolves_conflicts() {
+    let merged = sync::merge(&canonical(), &edited(), MergeStrategy::PreferLeft).expect("merged");
+    assert_eq!(merged.len(), 3);
+    let node = alice(&merged);
+    assert_eq!(node.types.len(), 2);
+    assert_eq!(
+        node.properties[NAME],
+        PropertyValue::Scalar(string("Alice"))
+    );
+    assert!(node.properties.contains_key("https://schema.org/email"));
+    assert!(node.properties.contains_key("https://schema.org/knows"));
+
+    let merged = sync::merge(&canonical(), &edited(), MergeStrategy::PreferRight).expect("merged");
+    assert_eq!(
+        alice(&merged).properties[NAME],
+        PropertyValue::Scalar(string("Alice Smith"))
+    );
+
+    let merged = sync::merge(&canonical(), &edited(), MergeStrategy::UnionArrays).expect("merged");
+    assert_eq!(
+        alice(&merged).properties[NAME],
+        PropertyValue::Array(ArrayValue::Scalars(vec![
+            string("Alice"),
+            string("Alice Smith")
+        ]))
+    );
+}
+
+#[test]
+fn conflicting_values_can_fail_the_merge() {
+    let result = sync::merge(&canonical(), &edited(), MergeStrategy::ErrorOnConflict);
+    assert!(matches!(
+        result,
+        Err(ToolError::MergeConflict { id, predicate, .. }) if id == ALICE && predicate == NAME
+    ));
+
+    let same = sync::merge(&canonical(), &canonical(), MergeStrategy::ErrorOnConflict)
+        .expect("identical inputs merge");
+    assert_eq!(same, canonical());
+}
+
+#[test]
+fn union_of_literal_and_reference_is_a_mixed_array() {
+    let reference = parse(serde_json::json!({
+        "@id": ALICE,
+        "https://schema.org/name": { "@id": "https://example.com/names/alice" }
+    }));
+    let merged = sync::merge(&canonical(), &reference, MergeStrategy::UnionArrays).expect("merged");
+    assert_eq!(
+        alice(&merged).properties[NAME],
+        PropertyValue::Array(ArrayValue::Mixed(vec![
+            ArrayItem::Scalar(string("Alice")),
+            ArrayItem::ObjectRef("https://example.com/names/alice".into())
+        ]))
+    );
+}
@@ -0,0 +1,65 @@
+use std::io::Cursor;
+
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::merge_prompt::ConflictPrompt;
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::sync;
+
+use crate::common::{parse_jsonld, string};
+
+const NAME: &str = "https://schema.org/name";
+
+fn person(name: &str, email: &str) -> Vec<Node> {
+    parse_jsonld(&serde_json::json!({
+        "@id": "https://example.com/people/1",
+        "https://schema.org/name": name,
+        "https://schema.org/email": email
+    }))
+}
+
+#[test]
+fn each_conflict_is_resolved_at_the_prompt() {
+    let left = person("Alice", "alice@example.com");
+    let right = person("Alice Smith", "alice@example.org");
+    // The email conflict comes first, in predicate order: a bad answer, then
+    // theirs; the name is edited after an invalid value.
+    let answers = "x\nt\ne\n{not json\ne\n\"Alice Jones\"\n";
+    let mut shown = Vec::new();
+    let mut prompt = ConflictPrompt::new(Cursor::new(answers), &mut shown);
+    let merged =
+        sync::merge_with(&left, &right, |conflict| prompt.resolve(conflict)).expect("merged");
+
+    let node = &merged[0];
+    assert_eq!(
+        node.properties["https://schema.org/email"],
+        string("alice@example.org")
+    );
+    assert_eq!(node.properties[NAME], string("Alice Jones"));
+    let shown = String::from_utf8(shown).unwrap();
+    assert!(
+        shown.contains("conflict on https://example.com/people/1 [https://schema.org/name]"),
+        "{shown}"
+    );
+    assert!(shown.contains("  ours:   \"Alice\"\n  theirs: \"Alice Smith\""));
+    assert!(shown.contains("answer o, t, or e"));
+    assert!(shown.contains("invalid JSON"));
+}
+
+#[test]
+fn the_merge_fails_when_the_prompt_gets_no_answer() {
+    let left = person("Alice", "alice@example.com");
+    let right = person("Alice Smith", "alice@example.org");
+    // Merging without conflicts asks nothing, so the answer is left for the
+    // email conflict and the name finds the input ended.
+    let mut prompt = ConflictPrompt::new(Cursor::new("ours\n"), Vec::new());
+    let merged =
+        sync::merge_with(&left, &left, |conflict| prompt.resolve(conflict)).expect("merged");
+    assert_eq!(merged, left);
+
+    let result = sync::merge_with(&left, &right, |conflict| prompt.resolve(conflict));
+    assert!(matches!(
+        result,
+        Err(ToolError::MergeConflict { predicate, reason, .. })
+            if predicate == NAME && reason == "no resolution was given"
+    ));
+}
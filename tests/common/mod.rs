@@ -0,0 +1,90 @@
+//! Fixtures and helpers shared by the integration tests.
+
+// Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use std::process::{Command, Output};
+
+use aideon_tools::aideon::tools::io::jsonld;
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue};
+use serde_json::{Value, json};
+
+pub const PERSON: &str = "https://schema.org/Person";
+pub const ORGANIZATION: &str = "https://schema.org/Organization";
+
+/// Returns two people, Ada, who knows Grace, and Grace. Tests add the
+/// properties they need to its `@graph`.
+pub fn people() -> Value {
+    json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": PERSON,
+                "https://schema.org/name": "Ada",
+                "https://schema.org/knows": { "@id": "https://example.com/people/2" }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": PERSON,
+                "https://schema.org/name": "Grace"
+            }
+        ]
+    })
+}
+
+/// Returns people with only an id, numbered as in [`people`], and a name.
+pub fn named_people(names: &[(&str, &str)]) -> Vec<Node> {
+    let graph: Vec<Value> = names
+        .iter()
+        .map(|(id, name)| {
+            json!({
+                "@id": format!("https://example.com/people/{id}"),
+                "@type": PERSON,
+                "https://schema.org/name": name
+            })
+        })
+        .collect();
+    parse_jsonld(&json!({ "@graph": graph }))
+}
+
+/// Returns a JSON-LD array of references to the people numbered `numbers`.
+pub fn references(numbers: &[u32]) -> Value {
+    numbers
+        .iter()
+        .map(|number| json!({ "@id": format!("https://example.com/people/{number}") }))
+        .collect()
+}
+
+/// Parses a JSON-LD document into nodes.
+pub fn parse_jsonld(document: &Value) -> Vec<Node> {
+    jsonld::parse_jsonld_document(document).expect("JSON-LD parsed")
+}
+
+/// Returns the node of `nodes` identified by `id`.
+pub fn node<'a>(nodes: &'a [Node], id: &str) -> Option<&'a Node> {
+    nodes.iter().find(|node| node.id.as_str() == id)
+}
+
+/// Returns the value of the schema.org property `name` of `node`.
+pub fn property<'a>(node: &'a Node, name: &str) -> &'a PropertyValue {
+    &node.properties[format!("https://schema.org/{name}").as_str()]
+}
+
+/// Returns a plain string value.
+pub fn string(value: &str) -> PropertyValue {
+    PropertyValue::Scalar(ScalarValue::String(value.into()))
+}
+
+/// Returns a command running the aideon-tools binary.
+pub fn aideon() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_aideon-tools"))
+}
+
+/// Fails with the standard error of `run` unless it succeeded.
+pub fn assert_success(run: &Output) {
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+}
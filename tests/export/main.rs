@@ -0,0 +1,10 @@
+//! Integration tests of the published outputs: sites, snippets, diagrams,
+//! and reports.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod report;
+mod site;
+mod snippet;
+mod visualize;
@@ -0,0 +1,55 @@
+use aideon_tools::aideon::tools::diff;
+use aideon_tools::aideon::tools::report::{DatasetSummary, SyncReport};
+use aideon_tools::aideon::tools::validate;
+use std::path::PathBuf;
+
+use crate::common::parse_jsonld;
+
+#[test]
+fn html_report_lists_counts_and_changes() {
+    let document = serde_json::json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Alice"
+            },
+            {
+                "@id": "https://example.com/graphs/<named>",
+                "@graph": [
+                    {
+                        "@id": "https://example.com/people/2",
+                        "@type": "https://schema.org/Person"
+                    }
+                ]
+            }
+        ]
+    });
+    let nodes = parse_jsonld(&document);
+
+    let summary = DatasetSummary::from_nodes(&nodes);
+    assert_eq!(summary.node_count, 2);
+    assert_eq!(
+        summary.type_counts.get("https://schema.org/Person"),
+        Some(&2)
+    );
+    assert_eq!(summary.graph_counts.get(&None), Some(&1));
+
+    let report = SyncReport {
+        input: PathBuf::from("input.jsonld"),
+        output: PathBuf::from("output.xlsx"),
+        from: "jsonld".into(),
+        to: "xlsx".into(),
+        summary,
+        changes: diff::diff_nodes(&[], &nodes),
+        validation: Some(validate::validate_nodes(&nodes)),
+    };
+    let html = report.to_html();
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<a href=\"output.xlsx\">output.xlsx</a>"));
+    assert!(html.contains("2 created, 0 updated, 0 deleted."));
+    assert!(html.contains("<h2>Validation</h2>"));
+    assert!(html.contains("https://example.com/graphs/&lt;named&gt;"));
+    assert!(!html.contains("<named>"));
+}
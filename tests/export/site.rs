@@ -0,0 +1,66 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::site;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+#[test]
+fn site_links_nodes_and_types() {
+    let document = serde_json::json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Alice <admin>",
+                "https://schema.org/knows": { "@id": "https://example.com/people/2" }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Bob",
+                "https://schema.org/sameAs": { "@id": "https://bob.example.org/" }
+            },
+            {
+                "@id": "https://example.com/notes/1",
+                "http://www.w3.org/2000/01/rdf-schema#label": "Loose note"
+            }
+        ]
+    });
+    let nodes = parse_jsonld(&document);
+    let dir = tempdir().expect("temp dir");
+
+    site::write_site(dir.path(), &nodes).expect("site written");
+
+    let index = fs::read_to_string(dir.path().join("index.html")).expect("index page");
+    assert!(index.contains(">Person</a> (2)"));
+    assert!(index.contains("<h2>Untyped nodes</h2>"));
+    assert!(index.contains(">Loose note</a>"));
+
+    let type_pages: Vec<_> = fs::read_dir(dir.path().join("types"))
+        .expect("types directory")
+        .collect();
+    assert_eq!(type_pages.len(), 1);
+
+    let pages: Vec<String> = fs::read_dir(dir.path().join("nodes"))
+        .expect("nodes directory")
+        .map(|entry| fs::read_to_string(entry.expect("entry").path()).expect("node page"))
+        .collect();
+    assert_eq!(pages.len(), 3);
+
+    let alice = pages
+        .iter()
+        .find(|page| page.contains("<h1>Alice &lt;admin&gt;</h1>"))
+        .expect("Alice page");
+    assert!(alice.contains(">knows</th>"));
+    assert!(alice.contains(">Bob</a>"));
+    assert!(alice.contains("href=\"../types/"));
+
+    let bob = pages
+        .iter()
+        .find(|page| page.contains("<h1>Bob</h1>"))
+        .expect("Bob page");
+    assert!(bob.contains("<h2>Referenced by</h2>"));
+    assert!(bob.contains(">Alice &lt;admin&gt;</a> <span class=\"iri\">via knows</span>"));
+    assert!(bob.contains("<a href=\"https://bob.example.org/\" class=\"iri\">"));
+}
@@ -0,0 +1,103 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::remote_context::RemoteContextLoader;
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::snippet;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{parse_jsonld, people};
+
+const CONTEXT_IRI: &str = "https://contexts.example.com/schema.jsonld";
+
+fn people_with_markup() -> Vec<Node> {
+    let mut document = people();
+    document["@graph"][0]["https://schema.org/name"] = json!("Ada </script>");
+    parse_jsonld(&document)
+}
+
+fn script_json(snippet: &str) -> Value {
+    let body = snippet
+        .strip_prefix("<script type=\"application/ld+json\">\n")
+        .and_then(|rest| rest.strip_suffix("\n</script>"))
+        .expect("script element");
+    serde_json::from_str(body).expect("snippet JSON")
+}
+
+#[test]
+fn snippets_are_compacted_per_node() {
+    let context = serde_json::json!({ "@vocab": "https://schema.org/" });
+    let snippets = snippet::node_snippets(
+        &people_with_markup(),
+        Some(&context),
+        &SyncOptions::default(),
+    )
+    .expect("snippets built");
+
+    assert_eq!(snippets.len(), 2);
+    let (id, alice) = &snippets[0];
+    assert_eq!(id, "https://example.com/people/1");
+    assert!(!alice.contains("</script>\""));
+    assert!(alice.contains("\\u003c/script>"));
+
+    let json = script_json(alice);
+    assert_eq!(json["@context"], context);
+    assert_eq!(json["@type"], "Person");
+    assert_eq!(json["name"], "Ada </script>");
+    assert_eq!(json["knows"]["@id"], "https://example.com/people/2");
+}
+
+#[test]
+fn remote_context_is_kept_by_reference() {
+    let dir = tempdir().expect("temp dir");
+    let loader = RemoteContextLoader::new().with_cache_dir(Some(dir.path().join("cache")));
+    let cache_path = loader.cache_path(CONTEXT_IRI).expect("cache enabled");
+    fs::create_dir_all(cache_path.parent().unwrap()).expect("cache directory");
+    let cached = serde_json::json!({ "@context": { "@vocab": "https://schema.org/" } });
+    fs::write(&cache_path, cached.to_string()).expect("cache seeded");
+    let options = SyncOptions {
+        remote_contexts: Some(loader),
+        ..SyncOptions::default()
+    };
+
+    let output = dir.path().join("snippets.html");
+    let context = Value::String(CONTEXT_IRI.into());
+    snippet::write_snippets(&output, &people_with_markup(), Some(&context), &options)
+        .expect("snippets written");
+
+    let html = fs::read_to_string(&output).expect("snippets read");
+    assert!(html.starts_with("<!-- https://example.com/people/1 -->\n<script"));
+    assert_eq!(
+        html.matches("<script type=\"application/ld+json\">")
+            .count(),
+        2
+    );
+    assert!(html.contains(&format!("\"@context\": \"{CONTEXT_IRI}\"")));
+    assert!(html.contains("\"name\": \"Grace\""));
+}
+
+#[test]
+fn node_ids_cannot_end_their_comment() {
+    let dir = tempdir().expect("temp dir");
+    let document = serde_json::json!([
+        { "@id": "urn:x:a--->b", "https://schema.org/name": "A" },
+        { "@id": "urn:x:c-", "https://schema.org/name": "C" }
+    ]);
+    let nodes = parse_jsonld(&document);
+
+    let output = dir.path().join("snippets.html");
+    snippet::write_snippets(&output, &nodes, None, &SyncOptions::default())
+        .expect("snippets written");
+
+    let html = fs::read_to_string(&output).expect("snippets read");
+    assert!(
+        html.contains("<!-- urn:x:a&#45;&#45;&#45;>b -->\n"),
+        "{html}"
+    );
+    assert!(html.contains("<!-- urn:x:c&#45; -->\n"), "{html}");
+    for comment in html.split("<!--").skip(1) {
+        let (text, _) = comment.split_once("-->").expect("closed comment");
+        assert!(!text.contains('-'), "{text}");
+    }
+}
@@ -0,0 +1,100 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::model::{Iri, Node};
+use aideon_tools::aideon::tools::visualize::{self, GraphFormat, GraphSelection};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+fn chain() -> serde_json::Value {
+    json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada \"the Countess\"",
+                "https://schema.org/knows": { "@id": "https://example.com/people/2" }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Grace",
+                "https://schema.org/knows": { "@id": "https://example.com/people/3" },
+                "https://schema.org/worksFor": { "@id": "https://example.com/org/1" }
+            },
+            {
+                "@id": "https://example.com/people/3",
+                "@type": "https://schema.org/Person"
+            },
+            {
+                "@id": "https://example.com/org/1",
+                "@type": "https://schema.org/Organization",
+                "https://schema.org/name": "Navy"
+            }
+        ]
+    })
+}
+
+fn draw(nodes: &[Node], selection: &GraphSelection, format: GraphFormat) -> String {
+    let mut diagram = Vec::new();
+    visualize::write_graph(&mut diagram, nodes, selection, format).unwrap();
+    String::from_utf8(diagram).unwrap()
+}
+
+#[test]
+fn roots_and_depth_bound_the_diagram() {
+    let nodes = parse_jsonld(&chain());
+    let selection = GraphSelection {
+        roots: vec![Iri::from("https://example.com/people/1")],
+        depth: Some(1),
+        label_predicate: Iri::from("https://schema.org/name"),
+        ..GraphSelection::default()
+    };
+
+    let dot = draw(&nodes, &selection, GraphFormat::Dot);
+    assert!(dot.starts_with("digraph dataset {\n"), "{dot}");
+    assert!(
+        dot.contains(r#""https://example.com/people/1" [label="Ada \"the Countess\""];"#),
+        "{dot}"
+    );
+    assert!(dot.contains(
+        r#""https://example.com/people/1" -> "https://example.com/people/2" [label="knows"];"#
+    ));
+    assert!(!dot.contains("people/3"), "beyond the depth: {dot}");
+    assert!(!dot.contains("org/1"));
+
+    let mermaid = draw(&nodes, &selection, GraphFormat::Mermaid);
+    assert_eq!(
+        mermaid,
+        "graph LR\n  n0[\"Ada #quot;the Countess#quot;\"]\n  n1[\"Grace\"]\n  n0 -->|\"knows\"| n1\n"
+    );
+}
+
+#[test]
+fn visualize_draws_the_nodes_of_the_included_types() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, chain().to_string()).unwrap();
+    let output = temp_dir.path().join("people.mmd");
+
+    let run = aideon()
+        .arg("visualize")
+        .arg("--input")
+        .arg(&input)
+        .arg("--output")
+        .arg(&output)
+        .args(["--include-type", "schema:Person"])
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+
+    let mermaid = fs::read_to_string(&output).unwrap();
+    assert!(mermaid.starts_with("graph LR\n"), "{mermaid}");
+    assert!(
+        mermaid.contains("[\"https://example.com/people/3\"]"),
+        "{mermaid}"
+    );
+    assert!(!mermaid.contains("org/1"), "{mermaid}");
+    assert_eq!(mermaid.matches("-->").count(), 2, "{mermaid}");
+}
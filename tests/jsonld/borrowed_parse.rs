@@ -0,0 +1,100 @@
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::io::jsonld;
+use serde_json::{Value, json};
+
+use crate::common::parse_jsonld;
+
+fn assert_same_nodes(document: Value) {
+    let expected = parse_jsonld(&document);
+    let source = serde_json::to_string_pretty(&document).expect("document serialised");
+    let nodes = jsonld::parse_jsonld_str(&source).expect("text parsed");
+    assert_eq!(nodes, expected, "{source}");
+}
+
+#[test]
+fn text_parser_matches_value_parser() {
+    assert_same_nodes(json!({
+        "@context": {
+            "@vocab": "https://schema.org/",
+            "label": { "@id": "https://schema.org/name", "@container": "@language" },
+            "knows": { "@type": "@id" }
+        },
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "Person",
+                "label": { "en": "Alice", "de": "Alicia" },
+                "description": "Caf\u{e9} \"quoted\"\nline",
+                "knows": ["https://example.com/people/2"],
+                "address": { "@type": "PostalAddress", "addressLocality": "Wien" },
+                "birthDate": { "@value": "1990-01-01", "@type": "http://www.w3.org/2001/XMLSchema#date" }
+            },
+            {
+                "@id": "https://example.com/archive",
+                "@graph": [
+                    { "@id": "https://example.com/people/2", "@type": "Person", "age": 30 }
+                ]
+            }
+        ]
+    }));
+
+    assert_same_nodes(json!([
+        {
+            "@context": { "@vocab": "https://schema.org/" },
+            "@id": "https://example.com/people/1",
+            "name": "Alice"
+        },
+        { "@id": "https://example.com/people/1", "https://schema.org/age": 30.5 }
+    ]));
+
+    // Documents whose root carries more than a context and a graph are
+    // parsed as a whole.
+    assert_same_nodes(json!({
+        "@context": { "@vocab": "https://schema.org/" },
+        "@id": "https://example.com/archive",
+        "@graph": { "@id": "https://example.com/people/3", "name": "Carol" }
+    }));
+    assert_same_nodes(json!({
+        "@context": { "@vocab": "https://schema.org/" },
+        "@graph": { "@id": "https://example.com/people/3", "name": "Carol" }
+    }));
+}
+
+#[test]
+fn text_parser_reports_invalid_documents() {
+    assert!(matches!(
+        jsonld::parse_jsonld_str("{\"@graph\": [").expect_err("truncated"),
+        ToolError::Json(_)
+    ));
+    assert!(matches!(
+        jsonld::parse_jsonld_str("42").expect_err("not a document"),
+        ToolError::JsonLd(_)
+    ));
+    let error = jsonld::parse_jsonld_str(r#"{"@graph": [{"@id": "urn:a", "@type": 1}]}"#)
+        .expect_err("invalid type");
+    assert!(error.to_string().contains("invalid @type entry"), "{error}");
+}
+
+#[test]
+fn text_parser_reads_a_graph_before_its_context() {
+    let source = r#"{
+        "@graph": [{ "@id": "https://example.com/people/1", "name": "Alice" }],
+        "@context": { "@vocab": "https://schema.org/" }
+    }"#;
+    let expected = parse_jsonld(&serde_json::from_str(source).unwrap());
+    assert_eq!(
+        jsonld::parse_jsonld_str(source).expect("text parsed"),
+        expected
+    );
+    assert!(
+        expected[0]
+            .properties
+            .contains_key("https://schema.org/name")
+    );
+
+    let error = jsonld::parse_jsonld_str(r#"{"@graph": 42}"#).expect_err("scalar graph");
+    assert!(
+        error.to_string().contains("invalid @graph entry"),
+        "{error}"
+    );
+}
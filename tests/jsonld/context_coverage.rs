@@ -0,0 +1,99 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::jsonld::{self, ContextCoverage, IriRole};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::aideon;
+
+const PEOPLE: &str = r#"{
+  "@graph": [
+    {
+      "@id": "https://example.com/people/1",
+      "@type": ["https://schema.org/Person", "https://example.org/hr#Employee"],
+      "https://schema.org/name": "Ada",
+      "https://example.org/hr#grade": "B",
+      "http://xmlns.com/foaf/0.1/nick": "ada"
+    }
+  ]
+}"#;
+
+#[test]
+fn coverage_reports_the_iris_a_context_leaves_absolute() {
+    let nodes = jsonld::parse_jsonld_str(PEOPLE).expect("JSON-LD parsed");
+    let context = json!({
+        "@vocab": "https://schema.org/",
+        "hr": "https://example.org/hr#",
+        "nick": "http://xmlns.com/foaf/0.1/nick"
+    });
+    let coverage = ContextCoverage::new(&context).expect("context read");
+    assert!(coverage.unmapped(&nodes).is_empty());
+
+    let coverage = ContextCoverage::new(&json!({ "name": "https://schema.org/name" })).unwrap();
+    assert!(coverage.maps("https://schema.org/name"));
+    let unmapped: Vec<(IriRole, String)> = coverage
+        .unmapped(&nodes)
+        .into_iter()
+        .map(|iri| (iri.role, iri.iri.to_string()))
+        .collect();
+    assert_eq!(
+        unmapped,
+        [
+            (IriRole::Predicate, "http://xmlns.com/foaf/0.1/nick".into()),
+            (IriRole::Predicate, "https://example.org/hr#grade".into()),
+            (IriRole::Type, "https://example.org/hr#Employee".into()),
+            (IriRole::Type, "https://schema.org/Person".into()),
+        ]
+    );
+}
+
+#[test]
+fn sync_warns_about_unmapped_iris_and_fails_with_strict_context() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.ttl");
+    fs::write(
+        &input,
+        r#"<https://example.com/people/1> a <https://schema.org/Person>, <https://example.org/hr#Employee> ;
+    <https://schema.org/name> "Ada" ;
+    <https://example.org/hr#grade> "B" ;
+    <http://xmlns.com/foaf/0.1/nick> "ada" .
+"#,
+    )
+    .unwrap();
+    let context = temp_dir.path().join("context.json");
+    fs::write(&context, r#"{ "@vocab": "https://schema.org/" }"#).unwrap();
+    let output = temp_dir.path().join("out.jsonld");
+
+    let sync = |flags: &[&str]| {
+        aideon()
+            .args(["sync", "--from", "rdf", "--input"])
+            .arg(&input)
+            .args(["--to", "json-ld", "--output"])
+            .arg(&output)
+            .arg("--context")
+            .arg(&context)
+            .args(flags)
+            .output()
+            .expect("CLI ran")
+    };
+    let run = sync(&[]);
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(run.status.success(), "{stderr}");
+    let stdout = String::from_utf8_lossy(&run.stdout);
+    let logged = format!("{stdout}{stderr}");
+    assert!(
+        logged.contains("does not map the predicate https://example.org/hr#grade"),
+        "{logged}"
+    );
+    assert!(logged.contains("does not map the type https://example.org/hr#Employee"));
+    assert!(!logged.contains("https://schema.org/name;"));
+
+    fs::remove_file(&output).unwrap();
+    let run = sync(&["--strict-context"]);
+    assert!(!run.status.success());
+    assert!(
+        String::from_utf8_lossy(&run.stderr)
+            .contains("the JSON-LD context does not map 3 predicate or type IRI(s)")
+    );
+    assert!(!output.exists());
+}
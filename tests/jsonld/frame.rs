@@ -0,0 +1,123 @@
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::excel_write;
+use aideon_tools::aideon::tools::io::jsonld::{self, JsonLdVersion};
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+fn housemates() -> Vec<Node> {
+    let document = json!({
+        "@context": { "@vocab": "https://schema.org/" },
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "Person",
+                "name": "Alice",
+                "address": { "@id": "https://example.com/addresses/1" },
+                "knows": [{ "@id": "https://example.com/people/2" }]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "Person",
+                "name": "Bob",
+                "address": { "@id": "https://example.com/addresses/1" },
+                "knows": [{ "@id": "https://example.com/people/1" }]
+            },
+            {
+                "@id": "https://example.com/addresses/1",
+                "@type": "PostalAddress",
+                "streetAddress": "1 Main St"
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+fn graph_entries(document: &Value) -> Vec<Value> {
+    match document.get("@graph") {
+        Some(Value::Array(entries)) => entries.clone(),
+        _ => vec![document.clone()],
+    }
+}
+
+#[test]
+fn frame_nests_referenced_nodes_under_matches() {
+    let frame = json!({
+        "@context": { "@vocab": "https://schema.org/" },
+        "@type": "Person",
+        "knows": { "@embed": "@never" }
+    });
+    let output = jsonld::frame_nodes(&housemates(), &frame, None, JsonLdVersion::V1_1)
+        .expect("frame applied");
+    assert_eq!(output["@context"], frame["@context"]);
+
+    let entries = graph_entries(&output);
+    let ids: Vec<&str> = entries
+        .iter()
+        .map(|entry| entry["@id"].as_str().expect("id"))
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            "https://example.com/people/1",
+            "https://example.com/people/2"
+        ]
+    );
+
+    // The address is embedded once; the second reference stays a reference.
+    assert_eq!(entries[0]["address"]["streetAddress"], "1 Main St");
+    assert_eq!(
+        entries[1]["address"],
+        json!({ "@id": "https://example.com/addresses/1" })
+    );
+    assert_eq!(
+        entries[0]["knows"],
+        json!({ "@id": "https://example.com/people/2" })
+    );
+}
+
+#[test]
+fn excel_to_jsonld_applies_frame_option() {
+    let dir = tempdir().expect("temporary directory");
+    let workbook_path = dir.path().join("people.xlsx");
+    let workbook = build_workbook(&housemates()).expect("workbook built");
+    excel_write::write_workbook(&workbook_path, &workbook).expect("workbook written");
+
+    let options = SyncOptions {
+        frame: Some(json!({
+            "@context": { "@vocab": "https://schema.org/" },
+            "@id": "https://example.com/people/1",
+            "@explicit": true,
+            "name": {},
+            "knows": { "@explicit": false, "@embed": "@always" }
+        })),
+        ..SyncOptions::default()
+    };
+    let output = dir.path().join("people.jsonld");
+    sync::excel_to_jsonld_with_options(&workbook_path, &output, None, &options).expect("converted");
+
+    let document: Value =
+        serde_json::from_str(&fs::read_to_string(&output).expect("output read")).expect("JSON");
+    let entries = graph_entries(&document);
+    assert_eq!(entries.len(), 1);
+    let alice = &entries[0];
+    assert_eq!(alice["name"], "Alice");
+    assert!(alice.get("address").is_none());
+
+    // Bob is embedded with all his properties, but Alice is already on the
+    // embedding path and stays a reference.
+    let bob = &alice["knows"];
+    assert_eq!(bob["name"], "Bob");
+    assert_eq!(bob["address"]["streetAddress"], "1 Main St");
+    assert_eq!(
+        bob["knows"],
+        json!({ "@id": "https://example.com/people/1" })
+    );
+
+    let invalid = json!({ "@type": "Person", "@requireAll": true });
+    assert!(jsonld::frame_nodes(&housemates(), &invalid, None, JsonLdVersion::V1_1).is_err());
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::io::jsonld::{self, JsonLdVersion};
+use aideon_tools::aideon::tools::model::{Iri, Node};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+const TENANT_A: &str = "https://example.com/graphs/a";
+const TENANT_B: &str = "https://example.com/graphs/b";
+
+const QUADS: &str = r#"<https://example.com/people/1> <https://schema.org/name> "Ada" .
+<https://example.com/people/2> <https://schema.org/name> "Bob" <https://example.com/graphs/a> .
+<https://example.com/people/3> <http://xmlns.com/foaf/0.1/name> "Cy" <https://example.com/graphs/b> .
+"#;
+
+fn nodes() -> Vec<Node> {
+    let mut nodes = jsonld::parse_jsonld_str(
+        &serde_json::to_string(&json!({
+            "@graph": [
+                { "@id": "https://example.com/people/1", "https://schema.org/name": "Ada" },
+                {
+                    "@id": TENANT_A,
+                    "@graph": [
+                        { "@id": "https://example.com/people/2", "https://schema.org/name": "Bob" }
+                    ]
+                },
+                {
+                    "@id": TENANT_B,
+                    "@graph": [
+                        { "@id": "https://example.com/people/3", "http://xmlns.com/foaf/0.1/name": "Cy" }
+                    ]
+                }
+            ]
+        }))
+        .unwrap(),
+    )
+    .expect("JSON-LD parsed");
+    nodes.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+    nodes
+}
+
+/// Returns the graph object of `graph` in `document`.
+fn graph_object<'a>(document: &'a Value, graph: &str) -> &'a Value {
+    document["@graph"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["@id"] == graph)
+        .expect("graph object")
+}
+
+#[test]
+fn named_graphs_are_compacted_with_their_own_context() {
+    let nodes = nodes();
+    let graph_contexts: BTreeMap<Iri, Value> = [
+        (
+            TENANT_B.into(),
+            json!({ "nom": "http://xmlns.com/foaf/0.1/name" }),
+        ),
+        (
+            TENANT_A.into(),
+            json!({ "label": "https://schema.org/name" }),
+        ),
+    ]
+    .into();
+    let document = jsonld::nodes_to_jsonld_with_graph_contexts(
+        &nodes,
+        Some(json!({ "name": "https://schema.org/name" })),
+        &graph_contexts,
+        JsonLdVersion::V1_1,
+    )
+    .expect("document built");
+
+    assert_eq!(
+        document["@context"],
+        json!({ "name": "https://schema.org/name" })
+    );
+    let ada = &document["@graph"][0];
+    assert_eq!(ada["name"], "Ada");
+    let tenant_b = graph_object(&document, TENANT_B);
+    assert_eq!(
+        tenant_b["@context"],
+        json!([null, { "nom": "http://xmlns.com/foaf/0.1/name" }])
+    );
+    assert_eq!(tenant_b["@graph"][0]["nom"], "Cy");
+    let tenant_a = graph_object(&document, TENANT_A);
+    assert_eq!(tenant_a["@graph"][0]["label"], "Bob");
+
+    let mut read = parse_jsonld(&document);
+    read.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+    assert_eq!(read, nodes);
+
+    // Without a document context the graph contexts stand alone.
+    let document = jsonld::nodes_to_jsonld_with_graph_contexts(
+        &nodes,
+        None,
+        &graph_contexts,
+        JsonLdVersion::V1_1,
+    )
+    .unwrap();
+    assert!(document.get("@context").is_none());
+    assert_eq!(
+        graph_object(&document, TENANT_A)["@context"],
+        json!({ "label": "https://schema.org/name" })
+    );
+}
+
+#[test]
+fn configured_graph_contexts_compact_sync_output() {
+    let temp_dir = tempdir().expect("temporary directory");
+    fs::write(temp_dir.path().join("people.nq"), QUADS).unwrap();
+    fs::create_dir(temp_dir.path().join("contexts")).unwrap();
+    fs::write(
+        temp_dir.path().join("contexts/b.json"),
+        json!({ "foaf": "http://xmlns.com/foaf/0.1/" }).to_string(),
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("aideon.toml"),
+        r#"
+[prefixes]
+graphs = "https://example.com/graphs/"
+
+[graph-contexts]
+"graphs:a" = { "@vocab" = "https://schema.org/" }
+"graphs:b" = "contexts/b.json"
+"#,
+    )
+    .unwrap();
+
+    let run = aideon()
+        .current_dir(temp_dir.path())
+        .args([
+            "sync",
+            "--from",
+            "rdf",
+            "--input",
+            "people.nq",
+            "--to",
+            "json-ld",
+            "--output",
+            "people.jsonld",
+        ])
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+
+    let document: Value =
+        serde_json::from_str(&fs::read_to_string(temp_dir.path().join("people.jsonld")).unwrap())
+            .unwrap();
+    let tenant_a = graph_object(&document, TENANT_A);
+    assert_eq!(tenant_a["@graph"][0]["name"], "Bob");
+    let tenant_b = graph_object(&document, TENANT_B);
+    assert_eq!(tenant_b["@graph"][0]["foaf:name"], "Cy");
+    let mut read = parse_jsonld(&document);
+    read.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+    assert_eq!(read, nodes());
+}
@@ -0,0 +1,93 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::jsonld::{
+    IdStrategy, parse_jsonld_str, parse_jsonld_str_with_ids,
+};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success};
+
+const PEOPLE: &str = r#"{
+    "@context": { "@vocab": "https://schema.org/" },
+    "@graph": [
+        { "@type": "Person", "name": "Ada Lovelace", "email": "ada@example.com", "jobTitle": "Analyst" },
+        { "@type": "Person", "name": "Ada Lovelace", "email": "ada@example.com" }
+    ]
+}"#;
+
+fn ids(strategy: &IdStrategy) -> Vec<String> {
+    let mut ids: Vec<String> = parse_jsonld_str_with_ids(PEOPLE, strategy)
+        .expect("JSON-LD parsed")
+        .into_iter()
+        .map(|node| node.id.to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn strategies_mint_the_ids_of_nodes_without_one() {
+    let default: Vec<String> = parse_jsonld_str(PEOPLE)
+        .unwrap()
+        .into_iter()
+        .map(|node| node.id.to_string())
+        .collect();
+    assert_eq!(ids(&IdStrategy::UuidV5).len(), 2);
+    assert_eq!(ids(&IdStrategy::UuidV5).len(), default.len());
+    assert!(default.iter().all(|id| id.starts_with("urn:uuid:")));
+    assert_ne!(ids(&IdStrategy::UuidV4), ids(&IdStrategy::UuidV4));
+
+    // Both objects agree on the keys, so they become one node.
+    let keyed = ids(&IdStrategy::KeyHash(vec![
+        "name".into(),
+        "https://schema.org/email".into(),
+    ]));
+    assert_eq!(keyed.len(), 1, "{keyed:?}");
+    assert!(keyed[0].starts_with("urn:uuid:"));
+
+    assert_eq!(
+        ids(&IdStrategy::Template(
+            "https://example.org/{type}/{name}".into()
+        )),
+        ["https://example.org/Person/Ada%20Lovelace"]
+    );
+    let error = parse_jsonld_str_with_ids(
+        PEOPLE,
+        &IdStrategy::Template("https://example.org/{nickname}".into()),
+    )
+    .expect_err("no nickname");
+    assert!(error.to_string().contains("'nickname'"), "{error}");
+}
+
+#[test]
+fn sync_applies_the_id_strategy() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, PEOPLE).unwrap();
+    let output = temp_dir.path().join("people.nt");
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "rdf", "--output"])
+        .arg(&output)
+        .args(["--id-strategy", "https://example.org/people/{email}"])
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    let triples = fs::read_to_string(&output).unwrap();
+    assert!(
+        triples.contains("<https://example.org/people/ada%40example.com>"),
+        "{triples}"
+    );
+    assert!(!triples.contains("urn:uuid:"), "{triples}");
+
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "rdf", "--output"])
+        .arg(&output)
+        .args(["--id-strategy", "sequential"])
+        .output()
+        .expect("CLI ran");
+    assert!(!run.status.success());
+}
@@ -0,0 +1,101 @@
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::model::{Interner, Iri, Node, PropertyValue};
+use serde_json::json;
+
+use crate::common::parse_jsonld;
+
+const NAME: &str = "https://schema.org/name";
+
+/// Returns the key `predicate` has in the properties of `node`.
+fn predicate<'a>(node: &'a Node, predicate: &str) -> &'a Iri {
+    node.properties
+        .keys()
+        .find(|key| key.as_str() == predicate)
+        .unwrap_or_else(|| panic!("{predicate} read: {node:?}"))
+}
+
+#[test]
+fn interner_pools_repeated_values() {
+    let mut interner = Interner::new();
+    let first = interner.intern("https://schema.org/name");
+    let second = interner.intern("https://schema.org/name");
+    interner.intern("https://schema.org/knows");
+    assert!(first.ptr_eq(&second));
+    assert!(!first.ptr_eq(&Iri::from("https://schema.org/name")));
+    assert_eq!(interner.len(), 2);
+
+    let mut node = Node::new("https://example.com/people/1");
+    node.types.insert("https://schema.org/Person".into());
+    node.insert_property(
+        "https://schema.org/knows".into(),
+        PropertyValue::ObjectRef("https://example.com/people/1".into()),
+    );
+    let node = interner.intern_node(node);
+    assert_eq!(interner.len(), 4);
+    let Some(PropertyValue::ObjectRef(target)) = node.properties.get("https://schema.org/knows")
+    else {
+        panic!("reference interned: {node:?}");
+    };
+    assert!(target.ptr_eq(&node.id));
+    assert_eq!(node.id, "https://example.com/people/1");
+    assert!(node.types.contains("https://schema.org/Person"));
+    assert!(node.properties.contains_key("https://schema.org/knows"));
+}
+
+#[test]
+fn iri_serialises_as_plain_string() {
+    let iri = Iri::from("https://example.com/people/1");
+    assert_eq!(
+        serde_json::to_value(&iri).expect("serialised"),
+        json!(iri.as_str())
+    );
+
+    let node = Node::new(iri.clone());
+    let value = serde_json::to_value(&node).expect("node serialised");
+    assert_eq!(value["id"], "https://example.com/people/1");
+    let restored: Node = serde_json::from_value(value).expect("node deserialised");
+    assert_eq!(restored, node);
+}
+
+#[test]
+fn readers_return_interned_iris() {
+    let turtle = r#"@prefix schema: <https://schema.org/> .
+<https://example.com/people/1> schema:name "Ada" ; schema:knows <https://example.com/people/2> .
+<https://example.com/people/2> schema:name "Grace" .
+"#;
+    let nodes = rdf::read_rdf_from(turtle.as_bytes(), RdfFormat::Turtle).expect("RDF read");
+    assert!(predicate(&nodes[0], NAME).ptr_eq(predicate(&nodes[1], NAME)));
+    let Some(PropertyValue::ObjectRef(target)) =
+        nodes[0].properties.get("https://schema.org/knows")
+    else {
+        panic!("reference read: {nodes:?}");
+    };
+    assert!(target.ptr_eq(&nodes[1].id));
+
+    let document = json!({
+        "@context": { "@vocab": "https://schema.org/" },
+        "@graph": [
+            { "@id": "https://example.com/people/1", "name": "Ada" },
+            { "@id": "https://example.com/people/2", "name": "Grace" }
+        ]
+    });
+    let nodes = parse_jsonld(&document);
+    assert!(predicate(&nodes[0], NAME).ptr_eq(predicate(&nodes[1], NAME)));
+}
+
+#[test]
+fn streamed_batches_intern_within_each_batch() {
+    let triples = "<https://example.com/people/1> <https://schema.org/name> \"Ada\" .
+<https://example.com/people/2> <https://schema.org/name> \"Grace\" .
+<https://example.com/people/3> <https://schema.org/name> \"Alan\" .
+<https://example.com/people/4> <https://schema.org/name> \"Edsger\" .
+";
+    let batches: Vec<Vec<Node>> =
+        rdf::stream_nodes_from(triples.as_bytes(), RdfFormat::NTriples, 2)
+            .collect::<Result<_, _>>()
+            .expect("RDF streamed");
+    assert_eq!(batches.len(), 2);
+    assert!(predicate(&batches[0][0], NAME).ptr_eq(predicate(&batches[0][1], NAME)));
+    // Each batch has a pool of its own, dropped with the batch.
+    assert!(!predicate(&batches[0][0], NAME).ptr_eq(predicate(&batches[1][1], NAME)));
+}
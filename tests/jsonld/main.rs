@@ -0,0 +1,14 @@
+//! Integration tests of reading and writing JSON-LD.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod borrowed_parse;
+mod context_coverage;
+mod frame;
+mod graph_contexts;
+mod id_strategy;
+mod interning;
+mod nodes_json;
+mod recompact;
+mod remote_context;
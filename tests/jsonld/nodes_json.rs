@@ -0,0 +1,94 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::{jsonld, nodes_json};
+use aideon_tools::aideon::tools::model::Node;
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+fn varied_people() -> Vec<Node> {
+    let mut nodes = parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": { "@value": "Ada", "@language": "en" },
+                "https://schema.org/birthDate": {
+                    "@value": "1815-12-10",
+                    "@type": "http://www.w3.org/2001/XMLSchema#date"
+                },
+                "https://schema.org/knows": [
+                    { "@id": "https://example.com/people/2" },
+                    "Charles"
+                ]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "https://schema.org/age": 85
+            }
+        ]
+    }));
+    nodes[1].set_graph(Some("https://example.com/graphs/hr".into()));
+    nodes
+}
+
+#[test]
+fn node_documents_keep_the_node_model_exactly() {
+    let nodes = varied_people();
+    let mut document = Vec::new();
+    nodes_json::write_nodes_json_to(&mut document, &nodes).unwrap();
+    let json: Value = serde_json::from_slice(&document).unwrap();
+    assert_eq!(
+        (&json["format"], &json["version"]),
+        (&json!(nodes_json::FORMAT), &json!(nodes_json::VERSION))
+    );
+    assert_eq!(
+        nodes_json::read_nodes_json_from(document.as_slice()).unwrap(),
+        nodes
+    );
+
+    let mut newer = json.clone();
+    newer["version"] = json!(nodes_json::VERSION + 1);
+    let err = nodes_json::read_nodes_json_from(newer.to_string().as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("version"), "{err}");
+}
+
+#[test]
+fn sync_reads_and_writes_node_documents() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    let document = jsonld::nodes_to_jsonld(&varied_people(), None).unwrap();
+    fs::write(&input, document.to_string()).unwrap();
+
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "nodes-json", "--output", "-"])
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    let nodes = nodes_json::read_nodes_json_from(run.stdout.as_slice()).unwrap();
+    assert_eq!(
+        nodes,
+        jsonld::parse_jsonld_str(&document.to_string()).unwrap()
+    );
+
+    let dump = temp_dir.path().join("people.nodes.json");
+    fs::write(&dump, &run.stdout).unwrap();
+    let output = temp_dir.path().join("people.xlsx");
+    let run = aideon()
+        .args(["sync", "--from", "nodes-json", "--input"])
+        .arg(&dump)
+        .args(["--to", "excel", "--output"])
+        .arg(&output)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    let inspect = aideon()
+        .args(["inspect", "--input"])
+        .arg(&dump)
+        .output()
+        .expect("CLI ran");
+    assert_success(&inspect);
+}
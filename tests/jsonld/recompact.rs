@@ -0,0 +1,87 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::jsonld::{self, JsonLdVersion};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::aideon;
+
+fn old_document() -> Value {
+    json!({
+        "@context": { "name": "https://schema.org/name", "Person": "https://schema.org/Person" },
+        "@id": "https://example.com/people/1",
+        "@type": "Person",
+        "name": "Ada"
+    })
+}
+
+fn new_context() -> Value {
+    json!({ "schema": "https://schema.org/" })
+}
+
+#[test]
+fn recompact_rewrites_a_document_against_a_new_context() {
+    let document =
+        jsonld::recompact(old_document(), new_context(), JsonLdVersion::V1_1).expect("recompacted");
+    assert_eq!(
+        document,
+        json!({
+            "@context": { "schema": "https://schema.org/" },
+            "@id": "https://example.com/people/1",
+            "@type": "schema:Person",
+            "schema:name": "Ada"
+        })
+    );
+}
+
+#[test]
+fn recompact_command_rewrites_the_documents_matching_the_glob() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let published = temp_dir.path().join("published");
+    fs::create_dir_all(published.join("people")).unwrap();
+    let old = serde_json::to_string_pretty(&old_document()).unwrap();
+    fs::write(published.join("people/ada.jsonld"), &old).unwrap();
+    fs::write(published.join("notes.txt"), "not JSON-LD").unwrap();
+    let current = jsonld::recompact(old_document(), new_context(), JsonLdVersion::V1_1).unwrap();
+    let current = serde_json::to_string(&current).unwrap();
+    fs::write(published.join("current.jsonld"), &current).unwrap();
+    let context = temp_dir.path().join("context.json");
+    fs::write(&context, new_context().to_string()).unwrap();
+
+    let recompact = |dry_run: bool| {
+        let mut command = aideon();
+        if dry_run {
+            command.arg("--dry-run");
+        }
+        command
+            .args(["recompact", "--input-dir"])
+            .arg(&published)
+            .arg("--context")
+            .arg(&context)
+            .output()
+            .expect("CLI ran")
+    };
+
+    let run = recompact(true);
+    assert!(run.status.success());
+    assert!(String::from_utf8_lossy(&run.stdout).contains("ada.jsonld"));
+    assert!(String::from_utf8_lossy(&run.stderr).contains("would rewrite 1 of 2 file(s)"));
+    assert_eq!(
+        fs::read_to_string(published.join("people/ada.jsonld")).unwrap(),
+        old
+    );
+
+    let run = recompact(false);
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(run.status.success(), "{stderr}");
+    assert!(stderr.contains("rewrote 1 of 2 file(s)"), "{stderr}");
+    let rewritten: Value =
+        serde_json::from_str(&fs::read_to_string(published.join("people/ada.jsonld")).unwrap())
+            .unwrap();
+    assert_eq!(rewritten["schema:name"], "Ada");
+    // Documents already compacted with the context keep their bytes.
+    assert_eq!(
+        fs::read_to_string(published.join("current.jsonld")).unwrap(),
+        current
+    );
+}
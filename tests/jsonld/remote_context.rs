@@ -0,0 +1,247 @@
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::io::remote_context::RemoteContextLoader;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use std::fs;
+use tempfile::tempdir;
+
+const CONTEXT_IRI: &str = "https://contexts.example.com/people.jsonld";
+
+fn seeded_loader(cache_dir: &std::path::Path) -> RemoteContextLoader {
+    let loader = RemoteContextLoader::new().with_cache_dir(Some(cache_dir.to_path_buf()));
+    let cache_path = loader.cache_path(CONTEXT_IRI).expect("cache enabled");
+    fs::create_dir_all(cache_path.parent().unwrap()).expect("cache directory");
+    let context_document = serde_json::json!({
+        "@context": {
+            "@vocab": "https://schema.org/",
+            "knows": {"@type": "@id"}
+        }
+    });
+    fs::write(&cache_path, context_document.to_string()).expect("cache seeded");
+    loader
+}
+
+#[test]
+fn cached_remote_context_is_applied_to_jsonld_input() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let loader = seeded_loader(&temp_dir.path().join("cache"));
+
+    let input = temp_dir.path().join("people.jsonld");
+    let document = serde_json::json!({
+        "@context": CONTEXT_IRI,
+        "@id": "https://example.com/people/1",
+        "name": "Alice",
+        "knows": "https://example.com/people/2"
+    });
+    fs::write(&input, document.to_string()).expect("input written");
+
+    let output = temp_dir.path().join("people.nt");
+    let options = SyncOptions {
+        remote_contexts: Some(loader),
+        ..SyncOptions::default()
+    };
+    sync::jsonld_to_rdf_with_options(&input, &output, RdfFormat::NTriples, &options)
+        .expect("conversion with remote context");
+
+    let nodes = rdf::read_rdf(&output, None).expect("RDF read");
+    let person = &nodes[0];
+    assert!(person.properties.contains_key("https://schema.org/name"));
+    assert!(person.properties.contains_key("https://schema.org/knows"));
+}
+
+#[test]
+fn remote_contexts_are_rejected_without_loader() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    let document = serde_json::json!({
+        "@context": CONTEXT_IRI,
+        "@id": "https://example.com/people/1",
+        "name": "Alice"
+    });
+    fs::write(&input, document.to_string()).expect("input written");
+
+    let output = temp_dir.path().join("people.nt");
+    let result = sync::jsonld_to_rdf_with_options(
+        &input,
+        &output,
+        RdfFormat::NTriples,
+        &SyncOptions::default(),
+    );
+    assert!(matches!(result, Err(ToolError::JsonLd(_))));
+}
+
+#[test]
+fn allowlist_blocks_unlisted_contexts() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let loader =
+        seeded_loader(&temp_dir.path().join("cache")).with_allowlist(["https://schema.org/"]);
+
+    let result = loader.resolve_context(&serde_json::json!(CONTEXT_IRI));
+    assert!(matches!(result, Err(ToolError::RemoteContext(_))));
+
+    let insecure = RemoteContextLoader::new().with_cache_dir(None);
+    let result = insecure.resolve_context(&serde_json::json!("http://example.com/context"));
+    assert!(matches!(result, Err(ToolError::RemoteContext(_))));
+}
+
+#[test]
+fn allowlist_entries_match_whole_hosts_and_path_segments() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let loader = seeded_loader(&temp_dir.path().join("cache"));
+    let context = serde_json::json!(CONTEXT_IRI);
+
+    for allowed in [
+        "https://contexts.example.com",
+        "https://CONTEXTS.example.com:443/",
+        "https://contexts.example.com/people.jsonld",
+    ] {
+        let loader = loader.clone().with_allowlist([allowed]);
+        assert!(loader.resolve_context(&context).is_ok(), "{allowed}");
+    }
+    for refused in [
+        "https://contexts.example",
+        "https://contexts.example.com:8443/",
+        "http://contexts.example.com/",
+        "https://contexts.example.com/people",
+    ] {
+        let loader = loader.clone().with_allowlist([refused]);
+        assert!(
+            matches!(
+                loader.resolve_context(&context),
+                Err(ToolError::RemoteContext(_))
+            ),
+            "{refused}"
+        );
+    }
+
+    let loader = loader.with_allowlist(["https://contexts.example.com"]);
+    for iri in [
+        "https://contexts.example.com.attacker.example/people.jsonld",
+        "https://contexts.example.com@attacker.example/people.jsonld",
+    ] {
+        let err = loader.resolve_context(&serde_json::json!(iri)).unwrap_err();
+        assert!(err.to_string().contains("is not permitted"), "{iri}: {err}");
+    }
+}
+
+/// Answers one HTTP request on a local port with `response` and returns the
+/// origin it listens on.
+#[cfg(feature = "remote-contexts")]
+fn serve_once(response: &'static str) -> String {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("local port");
+    let origin = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("request");
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+            line.clear();
+        }
+        reader.get_mut().write_all(response.as_bytes()).unwrap();
+    });
+    origin
+}
+
+#[cfg(feature = "remote-contexts")]
+#[test]
+fn alternate_links_and_redirects_must_be_allowlisted() {
+    for response in [
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 0\r\nConnection: close\r\n\
+         Link: <http://contexts.example.com/people.jsonld>; rel=\"alternate\"; type=\"application/ld+json\"\r\n\r\n",
+        "HTTP/1.1 302 Found\r\nLocation: http://contexts.example.com/people.jsonld\r\n\
+         Content-Length: 0\r\nConnection: close\r\n\r\n",
+    ] {
+        let origin = serve_once(response);
+        let loader = RemoteContextLoader::new()
+            .with_cache_dir(None)
+            .with_allowlist([format!("{origin}/")]);
+
+        let err = loader
+            .resolve_context(&serde_json::json!(format!("{origin}/context")))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "remote context error: context 'http://contexts.example.com/people.jsonld' is not permitted by the remote context allowlist"
+        );
+    }
+}
+
+#[cfg(feature = "remote-contexts")]
+#[test]
+fn relative_redirects_resolve_against_the_request_path() {
+    let origin = serve_once(
+        "HTTP/1.1 302 Found\r\nLocation: ../other/people.jsonld\r\n\
+         Content-Length: 0\r\nConnection: close\r\n\r\n",
+    );
+    let loader = RemoteContextLoader::new()
+        .with_cache_dir(None)
+        .with_allowlist([format!("{origin}/contexts/current")]);
+
+    let err = loader
+        .resolve_context(&serde_json::json!(format!(
+            "{origin}/contexts/current/people.jsonld"
+        )))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "remote context error: context '{origin}/contexts/other/people.jsonld' is not permitted by the remote context allowlist"
+        )
+    );
+}
+
+#[cfg(all(unix, not(feature = "remote-contexts")))]
+#[test]
+fn cached_contexts_others_can_write_are_ignored() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let cache_dir = temp_dir.path().join("cache");
+    let loader = seeded_loader(&cache_dir);
+    fs::set_permissions(&cache_dir, fs::Permissions::from_mode(0o777)).unwrap();
+
+    let err = loader
+        .resolve_context(&serde_json::json!(CONTEXT_IRI))
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("requires the `remote-contexts` feature"),
+        "{err}"
+    );
+}
+
+#[cfg(all(unix, feature = "remote-contexts"))]
+#[test]
+fn fetched_contexts_are_cached_in_a_private_directory() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let origin = serve_once(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/ld+json\r\nContent-Length: 47\r\n\
+         Connection: close\r\n\r\n{\"@context\": {\"@vocab\": \"https://schema.org/\"}}",
+    );
+    let temp_dir = tempdir().expect("temporary directory");
+    let cache_dir = temp_dir.path().join("cache").join("contexts");
+    let loader = RemoteContextLoader::new()
+        .with_cache_dir(Some(cache_dir.clone()))
+        .with_allowlist([format!("{origin}/")]);
+    let iri = format!("{origin}/context");
+
+    let context = loader.resolve_context(&serde_json::json!(iri)).unwrap();
+    assert_eq!(
+        context,
+        serde_json::json!({"@vocab": "https://schema.org/"})
+    );
+    let mode = fs::metadata(&cache_dir).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+    assert!(loader.cache_path(&iri).unwrap().exists());
+}
+
+#[test]
+fn contexts_are_not_cached_in_the_shared_temporary_directory() {
+    let loader = RemoteContextLoader::new();
+    if let Some(path) = loader.cache_path(CONTEXT_IRI) {
+        assert!(!path.starts_with(std::env::temp_dir().join("aideon-tools")));
+    }
+}
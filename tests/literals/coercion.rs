@@ -0,0 +1,153 @@
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::flatten::{CellEncoding, WorkbookData, build_workbook_with};
+use aideon_tools::aideon::tools::io::coercion::{Coercion, ScalarCoercions};
+use aideon_tools::aideon::tools::io::{csv_read, csv_write, excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{ArrayValue, Node, PropertyValue, ScalarValue};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const NOTE: &str = "https://schema.org/note";
+
+fn person_workbook() -> WorkbookData {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Alice",
+                "https://schema.org/active": "yes",
+                "https://schema.org/retired": "no",
+                "https://schema.org/phone": "N/A",
+                "https://schema.org/rating": "∞",
+                "https://schema.org/note": "yes",
+                "https://schema.org/tags": ["yes", "∞", "maybe"],
+                "https://schema.org/employer": { "@id": "N/A" }
+            }
+        ]
+    });
+    let nodes = parse_jsonld(&document);
+    let mut workbook = build_workbook_with(&nodes, CellEncoding::Bare).expect("tables built");
+
+    // The text marker keeps a cell from being coerced.
+    let table = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.columns.iter().any(|column| column == NOTE))
+        .expect("person sheet");
+    let column = table
+        .columns
+        .iter()
+        .position(|column| column == NOTE)
+        .unwrap();
+    table.rows[0][column] = "'yes".to_string();
+    workbook
+}
+
+fn coercions() -> ScalarCoercions {
+    ScalarCoercions::from_json(&json!({
+        "yes": true,
+        "no": false,
+        "N/A": null,
+        "∞": "@skip"
+    }))
+    .expect("coercions parsed")
+}
+
+fn property<'a>(node: &'a Node, name: &str) -> Option<&'a PropertyValue> {
+    node.properties
+        .get(format!("https://schema.org/{name}").as_str())
+}
+
+#[test]
+fn workbook_cells_are_read_through_the_coercion_table() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &person_workbook()).expect("workbook written");
+
+    let nodes = excel_read::read_nodes_with(&path, &coercions()).expect("workbook read");
+    let person = &nodes[0];
+    let scalar = |name| property(person, name).cloned();
+    assert_eq!(
+        scalar("active"),
+        Some(PropertyValue::Scalar(ScalarValue::Boolean(true)))
+    );
+    assert_eq!(
+        scalar("retired"),
+        Some(PropertyValue::Scalar(ScalarValue::Boolean(false)))
+    );
+    assert_eq!(
+        scalar("phone"),
+        Some(PropertyValue::Scalar(ScalarValue::Null))
+    );
+    assert_eq!(scalar("rating"), None);
+    assert_eq!(
+        scalar("note"),
+        Some(PropertyValue::Scalar(ScalarValue::String("yes".into())))
+    );
+    assert_eq!(
+        scalar("tags"),
+        Some(PropertyValue::Array(ArrayValue::Scalars(vec![
+            ScalarValue::Boolean(true),
+            ScalarValue::String("maybe".into()),
+        ])))
+    );
+    assert_eq!(scalar("employer"), None);
+    assert_eq!(
+        scalar("name"),
+        Some(PropertyValue::Scalar(ScalarValue::String("Alice".into())))
+    );
+
+    // Without a table every cell keeps its text.
+    let csv_dir = temp_dir.path().join("people");
+    csv_write::write_workbook(&csv_dir, &person_workbook()).expect("CSV written");
+    let nodes = csv_read::read_nodes(&csv_dir).expect("CSV read");
+    assert_eq!(
+        property(&nodes[0], "active"),
+        Some(&PropertyValue::Scalar(ScalarValue::String("yes".into())))
+    );
+    assert_eq!(
+        property(&nodes[0], "employer"),
+        Some(&PropertyValue::ObjectRef("N/A".into()))
+    );
+    let nodes = csv_read::read_nodes_with(&csv_dir, &coercions()).expect("CSV read");
+    assert_eq!(property(&nodes[0], "rating"), None);
+}
+
+#[test]
+fn coercion_tables_are_read_from_json() {
+    let coercions = ScalarCoercions::from_json(&json!({
+        "TBD": "to be decided",
+        "today": { "@value": "2024-05-01", "@type": "http://www.w3.org/2001/XMLSchema#date" },
+        "-": "@skip"
+    }))
+    .expect("coercions parsed");
+    assert_eq!(coercions.len(), 3);
+    assert_eq!(
+        coercions.get("TBD"),
+        Some(&Coercion::Scalar(ScalarValue::String(
+            "to be decided".into()
+        )))
+    );
+    assert_eq!(
+        coercions.get("today"),
+        Some(&Coercion::Scalar(ScalarValue::from_lexical(
+            "2024-05-01",
+            "http://www.w3.org/2001/XMLSchema#date"
+        )))
+    );
+    assert_eq!(coercions.get("-"), Some(&Coercion::Skip));
+    assert_eq!(coercions.get("tbd"), None);
+
+    for invalid in [
+        json!(["yes"]),
+        json!({ "yes": [true] }),
+        json!({ "yes": {} }),
+    ] {
+        assert!(matches!(
+            ScalarCoercions::from_json(&invalid).expect_err("invalid table"),
+            ToolError::InvalidCoercions(_)
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write, jsonld};
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue};
+use calamine::{Data, Reader, Xlsx, open_workbook};
+use chrono::NaiveDate;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{parse_jsonld, property};
+
+const XSD_DATE: &str = "http://www.w3.org/2001/XMLSchema#date";
+const XSD_DATE_TIME: &str = "http://www.w3.org/2001/XMLSchema#dateTime";
+
+fn events() -> Vec<Node> {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/events/1",
+                "@type": "https://schema.org/Event",
+                "https://schema.org/startDate": { "@value": "2024-05-01", "@type": XSD_DATE },
+                "https://schema.org/doorTime": { "@value": "2024-05-01T18:30:00", "@type": XSD_DATE_TIME },
+                "https://schema.org/endDate": { "@value": "2024-05-02T01:00:00Z", "@type": XSD_DATE_TIME }
+            },
+            {
+                "@id": "https://example.com/events/2",
+                "@type": "https://schema.org/Event",
+                "https://schema.org/startDate": { "@value": "1850-01-01", "@type": XSD_DATE },
+                "https://schema.org/doorTime": { "@value": "2024-06-01T09:15:30.25", "@type": XSD_DATE_TIME },
+                "https://schema.org/endDate": { "@value": "2024-06-01", "@type": XSD_DATE_TIME }
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+#[test]
+fn dates_without_timezone_are_native_scalars() {
+    let nodes = events();
+    let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+    assert_eq!(
+        property(&nodes[0], "startDate"),
+        &PropertyValue::Scalar(ScalarValue::Date(date))
+    );
+    assert_eq!(
+        property(&nodes[0], "doorTime"),
+        &PropertyValue::Scalar(ScalarValue::DateTime(date.and_hms_opt(18, 30, 0).unwrap()))
+    );
+    // Lexical forms the variants would not print back stay typed literals.
+    assert_eq!(
+        property(&nodes[0], "endDate"),
+        &PropertyValue::Scalar(ScalarValue::typed("2024-05-02T01:00:00Z", XSD_DATE_TIME))
+    );
+    assert_eq!(
+        property(&nodes[1], "endDate"),
+        &PropertyValue::Scalar(ScalarValue::typed("2024-06-01", XSD_DATE_TIME))
+    );
+
+    let document = jsonld::nodes_to_jsonld(&nodes, None).expect("JSON-LD written");
+    assert_eq!(parse_jsonld(&document), nodes);
+}
+
+#[test]
+fn dates_are_written_as_excel_date_cells() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("events.xlsx");
+    let nodes = events();
+    excel_write::write_workbook(&path, &build_workbook(&nodes).unwrap()).expect("written");
+
+    let mut workbook: Xlsx<_> = open_workbook(&path).expect("workbook opened");
+    let range = workbook
+        .worksheet_range("https___schema.org_Event")
+        .expect("event sheet");
+    let rows: Vec<_> = range.rows().collect();
+    let column = |name: &str| {
+        rows[0]
+            .iter()
+            .position(|cell| cell.to_string() == format!("https://schema.org/{name}"))
+            .unwrap()
+    };
+    let first = rows[1];
+    let datetime = |cell: &Data| match cell {
+        Data::DateTime(value) => value.as_datetime(),
+        other => panic!("expected a date cell, found {other:?}"),
+    };
+    assert_eq!(
+        datetime(&first[column("startDate")]),
+        NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+    );
+    assert_eq!(
+        datetime(&first[column("doorTime")]),
+        NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(18, 30, 0)
+    );
+    // Dates Excel cannot show, fractional seconds, and columns without a
+    // single datatype keep their text.
+    let second = rows[2];
+    assert!(matches!(second[column("startDate")], Data::String(_)));
+    assert!(matches!(second[column("doorTime")], Data::String(_)));
+    assert!(matches!(first[column("endDate")], Data::String(_)));
+
+    assert_eq!(excel_read::read_nodes(&path).expect("read back"), nodes);
+}
+
+#[test]
+fn dates_are_written_as_typed_rdf_literals() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("events.nt");
+    let nodes = events();
+    rdf::write_rdf(&path, &nodes, RdfFormat::NTriples).expect("RDF written");
+
+    let triples = fs::read_to_string(&path).unwrap();
+    assert!(
+        triples.contains(&format!("\"2024-05-01\"^^<{XSD_DATE}>")),
+        "{triples}"
+    );
+    assert!(
+        triples.contains(&format!("\"2024-05-01T18:30:00\"^^<{XSD_DATE_TIME}>")),
+        "{triples}"
+    );
+    assert_eq!(
+        rdf::read_rdf(&path, Some(RdfFormat::NTriples)).expect("RDF read"),
+        nodes
+    );
+}
@@ -0,0 +1,355 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::excel_read;
+use aideon_tools::aideon::tools::io::excel_write;
+use aideon_tools::aideon::tools::io::jsonld::{self, JsonLdVersion};
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::model::{ArrayItem, ArrayValue, PropertyValue, ScalarValue};
+use chrono::NaiveDate;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+#[test]
+fn typed_literals_survive_excel_and_rdf_roundtrips() {
+    let json_source = serde_json::json!({
+        "@context": {
+            "xsd": "http://www.w3.org/2001/XMLSchema#"
+        },
+        "@graph": [
+            {
+                "@id": "https://example.com/events/1",
+                "@type": "https://schema.org/Event",
+                "https://schema.org/startDate": {"@value": "2024-05-01", "@type": "xsd:date"},
+                "https://schema.org/url": {
+                    "@value": "https://example.com/events/1.html",
+                    "@type": "http://www.w3.org/2001/XMLSchema#anyURI"
+                },
+                "https://schema.org/name": {"@value": "Konferenz", "@language": "de"}
+            }
+        ]
+    });
+
+    let nodes = parse_jsonld(&json_source);
+    let event = &nodes[0];
+    assert_eq!(
+        event.properties.get("https://schema.org/startDate"),
+        Some(&PropertyValue::Scalar(ScalarValue::Date(
+            NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+        )))
+    );
+    assert_eq!(
+        event.properties.get("https://schema.org/name"),
+        Some(&PropertyValue::Scalar(ScalarValue::lang_tagged(
+            "Konferenz",
+            "de"
+        )))
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let xlsx_path = temp_dir.path().join("typed.xlsx");
+    let workbook = build_workbook(&nodes).expect("workbook built");
+    excel_write::write_workbook(&xlsx_path, &workbook).expect("Excel written");
+    let from_excel = excel_read::read_nodes(&xlsx_path).expect("Excel read");
+    assert_eq!(nodes, from_excel);
+
+    let rdf_path = temp_dir.path().join("typed.ttl");
+    rdf::write_rdf(&rdf_path, &nodes, RdfFormat::Turtle).expect("RDF written");
+    let turtle = fs::read_to_string(&rdf_path).expect("Turtle read");
+    assert!(turtle.contains("<http://www.w3.org/2001/XMLSchema#date>"));
+    assert!(turtle.contains("\"Konferenz\"@de"));
+    let from_rdf = rdf::read_rdf(&rdf_path, Some(RdfFormat::Turtle)).expect("RDF read");
+    assert_eq!(nodes, from_rdf);
+
+    let jsonld_output = jsonld::nodes_to_jsonld(&from_rdf, None).expect("JSON-LD written");
+    let reparsed = parse_jsonld(&jsonld_output);
+    assert_eq!(nodes, reparsed);
+}
+
+#[test]
+fn language_tagged_literals_roundtrip_across_formats() {
+    let json_source = serde_json::json!({
+        "@context": {
+            "@vocab": "https://schema.org/",
+            "@language": "en",
+            "label": {
+                "@id": "http://www.w3.org/2000/01/rdf-schema#label",
+                "@container": "@language"
+            }
+        },
+        "@id": "https://example.com/places/1",
+        "label": {"en": "Vienna", "de": "Wien"},
+        "description": "Capital of Austria"
+    });
+
+    let nodes = parse_jsonld(&json_source);
+    let place = &nodes[0];
+    assert_eq!(
+        place.properties.get("https://schema.org/description"),
+        Some(&PropertyValue::Scalar(ScalarValue::lang_tagged(
+            "Capital of Austria",
+            "en"
+        )))
+    );
+    assert_eq!(
+        place
+            .properties
+            .get("http://www.w3.org/2000/01/rdf-schema#label"),
+        Some(&PropertyValue::Array(ArrayValue::Scalars(vec![
+            ScalarValue::lang_tagged("Wien", "de"),
+            ScalarValue::lang_tagged("Vienna", "en"),
+        ])))
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let xlsx_path = temp_dir.path().join("lang.xlsx");
+    let workbook = build_workbook(&nodes).expect("workbook built");
+    excel_write::write_workbook(&xlsx_path, &workbook).expect("Excel written");
+    assert_eq!(
+        nodes,
+        excel_read::read_nodes(&xlsx_path).expect("Excel read")
+    );
+
+    let rdf_path = temp_dir.path().join("lang.nt");
+    rdf::write_rdf(&rdf_path, &nodes, RdfFormat::NTriples).expect("RDF written");
+    let ntriples = fs::read_to_string(&rdf_path).expect("N-Triples read");
+    assert!(ntriples.contains("\"Wien\"@de"));
+    assert_eq!(
+        nodes,
+        rdf::read_rdf(&rdf_path, Some(RdfFormat::NTriples)).expect("RDF read")
+    );
+
+    let compact_context = serde_json::json!({
+        "label": {
+            "@id": "http://www.w3.org/2000/01/rdf-schema#label",
+            "@container": "@language"
+        }
+    });
+    let compacted = jsonld::nodes_to_jsonld(&nodes, Some(compact_context)).expect("compacted");
+    assert_eq!(
+        compacted.get("label"),
+        Some(&serde_json::json!({"de": "Wien", "en": "Vienna"}))
+    );
+}
+
+#[test]
+fn jsonld_1_0_output_avoids_1_1_constructs() {
+    let json_source = serde_json::json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/birthDate": {
+                    "@value": "1990-01-01",
+                    "@type": "http://www.w3.org/2001/XMLSchema#date"
+                }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person"
+            }
+        ]
+    });
+    let nodes = parse_jsonld(&json_source);
+    let context = serde_json::json!({
+        "@version": 1.1,
+        "@vocab": "https://schema.org/",
+        "kind": "@type"
+    });
+
+    let output = jsonld::nodes_to_jsonld_versioned(&nodes, Some(context), JsonLdVersion::V1_0)
+        .expect("JSON-LD 1.0 output");
+    assert!(output["@context"].get("@version").is_none());
+    let alice = &output["@graph"][0];
+    assert_eq!(alice["kind"], serde_json::json!(["Person"]));
+    assert!(alice["birthDate"]["kind"].is_string());
+
+    let expanded = jsonld::nodes_to_jsonld_versioned(&nodes, None, JsonLdVersion::V1_0)
+        .expect("expanded JSON-LD 1.0 output");
+    assert_eq!(
+        expanded["@graph"][1]["@type"],
+        serde_json::json!(["https://schema.org/Person"])
+    );
+    assert_eq!(parse_jsonld(&expanded), nodes);
+
+    let propagate = serde_json::json!({ "@propagate": false, "@vocab": "https://schema.org/" });
+    assert!(
+        jsonld::nodes_to_jsonld_versioned(&nodes, Some(propagate), JsonLdVersion::V1_0).is_err()
+    );
+}
+
+#[test]
+fn child_tables_are_written_for_every_type() {
+    let json_source = serde_json::json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": ["https://schema.org/Person", "https://schema.org/Agent"],
+                "https://schema.org/knows": [
+                    {"@id": "https://example.com/people/2"},
+                    {"@id": "https://example.com/people/3"}
+                ]
+            }
+        ]
+    });
+    let nodes = parse_jsonld(&json_source);
+    let mut workbook = build_workbook(&nodes).expect("workbook built");
+
+    let child_tables: Vec<_> = workbook
+        .tables
+        .iter()
+        .filter(|table| table.columns.first().map(String::as_str) == Some("ParentId"))
+        .collect();
+    assert_eq!(child_tables.len(), 2);
+    assert!(child_tables.iter().all(|table| table.rows.len() == 2));
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let xlsx_path = temp_dir.path().join("graph.xlsx");
+    excel_write::write_workbook(&xlsx_path, &workbook).expect("Excel written");
+    assert_eq!(
+        excel_read::read_nodes(&xlsx_path).expect("Excel read"),
+        nodes
+    );
+
+    // The relationships survive on the remaining type's sheet alone.
+    let first_child = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.columns.first().map(String::as_str) == Some("ParentId"))
+        .expect("child table");
+    first_child.rows.clear();
+    excel_write::write_workbook(&xlsx_path, &workbook).expect("Excel written");
+    assert_eq!(
+        excel_read::read_nodes(&xlsx_path).expect("Excel read"),
+        nodes
+    );
+}
+
+#[test]
+fn embedded_node_objects_become_nodes() {
+    let json_source = serde_json::json!({
+        "@context": { "@vocab": "https://schema.org/" },
+        "@id": "https://example.com/people/1",
+        "@type": "Person",
+        "address": {
+            "@type": "PostalAddress",
+            "streetAddress": "1 Main St",
+            "geo": { "latitude": 52.1, "longitude": 4.3 }
+        },
+        "knows": [
+            { "@id": "https://example.com/people/2", "name": "Bob" },
+            { "@id": "https://example.com/people/3" }
+        ]
+    });
+    let nodes = parse_jsonld(&json_source);
+    assert_eq!(nodes.len(), 4);
+
+    let node = |id: &str| nodes.iter().find(|node| node.id == id).expect("node");
+    let person = node("https://example.com/people/1");
+    let PropertyValue::ObjectRef(address_id) = &person.properties["https://schema.org/address"]
+    else {
+        panic!("address should be a reference");
+    };
+    assert!(address_id.starts_with("urn:uuid:"));
+    let address = node(address_id);
+    assert!(address.types.contains("https://schema.org/PostalAddress"));
+    let PropertyValue::ObjectRef(geo_id) = &address.properties["https://schema.org/geo"] else {
+        panic!("geo should be a reference");
+    };
+    assert_eq!(
+        node(geo_id).properties["https://schema.org/latitude"],
+        PropertyValue::Scalar(ScalarValue::Double(52.1))
+    );
+    assert_eq!(
+        node("https://example.com/people/2").properties["https://schema.org/name"],
+        PropertyValue::Scalar(ScalarValue::String("Bob".into()))
+    );
+
+    let workbook = build_workbook(&nodes).expect("workbook built");
+    let metadata = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == "Metadata")
+        .expect("Metadata sheet");
+    assert!(
+        metadata
+            .rows
+            .iter()
+            .any(|row| row[0] == "type" && row[2] == "https://schema.org/PostalAddress")
+    );
+    let temp_dir = tempdir().expect("temporary directory");
+    let xlsx_path = temp_dir.path().join("graph.xlsx");
+    excel_write::write_workbook(&xlsx_path, &workbook).expect("Excel written");
+    let mut restored = excel_read::read_nodes(&xlsx_path).expect("Excel read");
+    let mut expected = nodes.clone();
+    restored.sort_by(|a, b| a.id.cmp(&b.id));
+    expected.sort_by(|a, b| a.id.cmp(&b.id));
+    assert_eq!(restored, expected);
+}
+
+#[test]
+fn mixed_literal_and_reference_arrays_roundtrip() {
+    let json_source = serde_json::json!({
+        "@id": "https://example.com/books/1",
+        "@type": "https://schema.org/Book",
+        "https://schema.org/author": [
+            "Anonymous",
+            { "@id": "https://example.com/people/1" }
+        ]
+    });
+    let nodes = parse_jsonld(&json_source);
+    let expected = PropertyValue::Array(ArrayValue::Mixed(vec![
+        ArrayItem::Scalar(ScalarValue::String("Anonymous".into())),
+        ArrayItem::ObjectRef("https://example.com/people/1".into()),
+    ]));
+    assert_eq!(nodes[0].properties["https://schema.org/author"], expected);
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let xlsx_path = temp_dir.path().join("books.xlsx");
+    let workbook = build_workbook(&nodes).expect("workbook built");
+    excel_write::write_workbook(&xlsx_path, &workbook).expect("Excel written");
+    let from_excel = excel_read::read_nodes(&xlsx_path).expect("Excel read");
+    assert_eq!(from_excel, nodes);
+
+    let ttl_path = temp_dir.path().join("books.ttl");
+    rdf::write_rdf(&ttl_path, &from_excel, RdfFormat::Turtle).expect("RDF written");
+    let from_rdf = rdf::read_rdf(&ttl_path, None).expect("RDF read");
+    let PropertyValue::Array(ArrayValue::Mixed(items)) =
+        &from_rdf[0].properties["https://schema.org/author"]
+    else {
+        panic!("author should stay a mixed array");
+    };
+    assert_eq!(items.len(), 2);
+    assert!(items.contains(&ArrayItem::ObjectRef("https://example.com/people/1".into())));
+
+    let output = jsonld::nodes_to_jsonld(&nodes, None).expect("JSON-LD written");
+    assert!(
+        output
+            .to_string()
+            .contains(r#"{"@id":"https://example.com/people/1"}"#)
+    );
+}
+
+#[test]
+fn invalid_boolean_literals_keep_their_lexical_form() {
+    const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+    assert_eq!(
+        ScalarValue::from_lexical("0", XSD_BOOLEAN),
+        ScalarValue::Boolean(false)
+    );
+    assert_eq!(
+        ScalarValue::from_lexical("true", XSD_BOOLEAN),
+        ScalarValue::Boolean(true)
+    );
+
+    let turtle = r#"<https://example.com/flags/1> <https://schema.org/value> "yes"^^<http://www.w3.org/2001/XMLSchema#boolean> ."#;
+    let nodes = rdf::read_rdf_from(turtle.as_bytes(), RdfFormat::Turtle).expect("RDF read");
+    assert_eq!(
+        nodes[0].properties.get("https://schema.org/value"),
+        Some(&PropertyValue::Scalar(ScalarValue::typed(
+            "yes",
+            XSD_BOOLEAN
+        )))
+    );
+}
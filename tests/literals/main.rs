@@ -0,0 +1,10 @@
+//! Integration tests of literals: their datatypes, dates, numbers, and
+//! coercions.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod coercion;
+mod dates;
+mod literals;
+mod numbers;
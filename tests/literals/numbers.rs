@@ -0,0 +1,90 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write, jsonld};
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue, XSD_DECIMAL};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{parse_jsonld, property};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+fn measurements() -> Vec<Node> {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/items/1",
+                "@type": "https://schema.org/Product",
+                "https://schema.org/position": 30,
+                "https://schema.org/weight": 2.5,
+                "https://schema.org/width": 30.0,
+                "https://schema.org/price": { "@value": "19.90", "@type": XSD_DECIMAL }
+            },
+            {
+                "@id": "https://example.com/items/2",
+                "@type": "https://schema.org/Product",
+                "https://schema.org/position": 31,
+                "https://schema.org/width": 12
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+#[test]
+fn integers_and_doubles_stay_distinct() {
+    let nodes = measurements();
+    let scalar = |scalar: ScalarValue| PropertyValue::Scalar(scalar);
+    assert_eq!(
+        property(&nodes[0], "position"),
+        &scalar(ScalarValue::Integer(30))
+    );
+    assert_eq!(
+        property(&nodes[0], "weight"),
+        &scalar(ScalarValue::Double(2.5))
+    );
+    assert_eq!(
+        property(&nodes[0], "width"),
+        &scalar(ScalarValue::Double(30.0))
+    );
+    assert_eq!(
+        property(&nodes[1], "width"),
+        &scalar(ScalarValue::Integer(12))
+    );
+    assert_eq!(
+        property(&nodes[0], "price"),
+        &scalar(ScalarValue::typed("19.90", XSD_DECIMAL))
+    );
+
+    let document = jsonld::nodes_to_jsonld(&nodes, None).expect("JSON-LD written");
+    assert_eq!(parse_jsonld(&document), nodes);
+
+    // Workbooks keep the distinction, also in a column mixing both.
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("items.xlsx");
+    excel_write::write_workbook(&path, &build_workbook(&nodes).unwrap()).expect("written");
+    assert_eq!(excel_read::read_nodes(&path).expect("read back"), nodes);
+}
+
+#[test]
+fn rdf_output_keeps_the_numeric_datatype() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("items.nt");
+    let nodes = measurements();
+    rdf::write_rdf(&path, &nodes, RdfFormat::NTriples).expect("RDF written");
+
+    let triples = fs::read_to_string(&path).unwrap();
+    for literal in [
+        format!("\"30\"^^<{XSD}integer>"),
+        format!("\"2.5\"^^<{XSD}double>"),
+        format!("\"19.90\"^^<{XSD}decimal>"),
+    ] {
+        assert!(triples.contains(&literal), "{literal} in {triples}");
+    }
+    assert_eq!(
+        rdf::read_rdf(&path, Some(RdfFormat::NTriples)).expect("RDF read"),
+        nodes
+    );
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::blank_nodes::{BlankNodeLabels, relabel_blank_nodes};
+use aideon_tools::aideon::tools::diff::diff_nodes;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::model::Node;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success};
+
+const PEOPLE: &str = r#"
+@prefix schema: <https://schema.org/> .
+<https://example.com/people/1> schema:address [ schema:streetAddress "1 Main St" ;
+                                                schema:geo [ schema:latitude 1.5 ] ] ;
+    schema:knows [ schema:name "Anon" ], [ schema:name "Anon" ] .
+"#;
+
+/// The same data with its statements, and so its blank nodes, in another order.
+const PEOPLE_REORDERED: &str = r#"
+@prefix schema: <https://schema.org/> .
+_:geo schema:latitude 1.5 .
+_:friend2 schema:name "Anon" .
+<https://example.com/people/1> schema:knows _:friend2, _:friend1 .
+_:friend1 schema:name "Anon" .
+_:address schema:geo _:geo ; schema:streetAddress "1 Main St" .
+<https://example.com/people/1> schema:address _:address .
+"#;
+
+fn read(turtle: &str, labels: &BlankNodeLabels) -> Vec<Node> {
+    let nodes = rdf::read_rdf_from(turtle.as_bytes(), RdfFormat::Turtle).expect("RDF read");
+    relabel_blank_nodes(nodes, labels).expect("blank nodes labelled")
+}
+
+#[test]
+fn canonical_labels_ignore_parser_order() {
+    let first = read(PEOPLE, &BlankNodeLabels::Canonical);
+    let second = read(PEOPLE_REORDERED, &BlankNodeLabels::Canonical);
+    assert_eq!(first, second);
+    let blank: Vec<&str> = first
+        .iter()
+        .map(|node| node.id.as_str())
+        .filter(|id| id.starts_with("_:"))
+        .collect();
+    assert_eq!(blank.len(), 4, "{blank:?}");
+    assert!(blank.iter().all(|id| id.starts_with("_:c14n")), "{blank:?}");
+
+    let skolemized = read(
+        PEOPLE_REORDERED,
+        &BlankNodeLabels::Skolemized("https://example.com/".into()),
+    );
+    let ids: Vec<&str> = skolemized.iter().map(|node| node.id.as_str()).collect();
+    assert!(ids.iter().all(|id| !id.starts_with("_:")), "{ids:?}");
+    assert_eq!(
+        ids.iter()
+            .filter(|id| id.starts_with("https://example.com/.well-known/genid/"))
+            .count(),
+        4
+    );
+}
+
+#[test]
+fn sync_writes_the_same_ids_for_reordered_input() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let mut outputs = Vec::new();
+    for (name, turtle) in [("first", PEOPLE), ("second", PEOPLE_REORDERED)] {
+        let input = temp_dir.path().join(format!("{name}.ttl"));
+        fs::write(&input, turtle).unwrap();
+        let output = temp_dir.path().join(format!("{name}.jsonld"));
+        let run = aideon()
+            .args(["sync", "--from", "rdf", "--input"])
+            .arg(&input)
+            .args(["--to", "json-ld", "--output"])
+            .arg(&output)
+            .arg("--canonical-blank-nodes")
+            .output()
+            .expect("CLI ran");
+        assert_success(&run);
+        outputs.push(fs::read_to_string(&output).unwrap());
+    }
+    assert_eq!(outputs[0], outputs[1]);
+    assert!(outputs[0].contains("_:c14n"), "{}", outputs[0]);
+}
+
+fn sync(from: &str, input: &Path, to: &str, output: &Path, flags: &[&str]) {
+    let run = aideon()
+        .args(["sync", "--from", from, "--input"])
+        .arg(input)
+        .args(["--to", to, "--output"])
+        .arg(output)
+        .args(flags)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+}
+
+#[test]
+fn skolemized_workbooks_deskolemize_back_to_blank_nodes() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.ttl");
+    fs::write(&input, PEOPLE).unwrap();
+    let workbook = temp_dir.path().join("people.xlsx");
+    let output = temp_dir.path().join("people-back.ttl");
+    sync(
+        "rdf",
+        &input,
+        "excel",
+        &workbook,
+        &["--skolemize", "https://example.com/"],
+    );
+    sync("excel", &workbook, "rdf", &output, &["--deskolemize"]);
+
+    let turtle = fs::read_to_string(&output).unwrap();
+    assert!(!turtle.contains(".well-known/genid"), "{turtle}");
+    let back = rdf::read_rdf(&output, None).expect("RDF read back");
+    let original = read(PEOPLE, &BlankNodeLabels::Parsed);
+    assert_eq!(diff_nodes(&original, &back), Vec::new());
+}
+
+/// A cycle of four blank nodes, written in two orders that number its
+/// nodes differently, and ten blank nodes alike but for their labels.
+fn symmetric(order: [&str; 4]) -> String {
+    let mut turtle = String::from("@prefix ex: <https://example.com/> .\n");
+    for (index, label) in order.iter().enumerate() {
+        let next = order[(index + 1) % order.len()];
+        turtle.push_str(&format!("_:{label} ex:next _:{next} .\n"));
+    }
+    for index in 0..10 {
+        let label = if order[1] == "b" { index } else { 9 - index };
+        turtle.push_str(&format!("ex:list ex:item _:item{label} .\n"));
+        turtle.push_str(&format!("_:item{label} ex:name \"Same\" .\n"));
+    }
+    turtle
+}
+
+#[test]
+fn symmetric_blank_nodes_are_labelled_alike_under_any_parse() {
+    let first = read(
+        &symmetric(["a", "b", "c", "d"]),
+        &BlankNodeLabels::Canonical,
+    );
+    let second = read(
+        &symmetric(["a", "c", "b", "d"]),
+        &BlankNodeLabels::Canonical,
+    );
+    assert_eq!(first, second);
+    let labels: std::collections::BTreeSet<&str> = first
+        .iter()
+        .map(|node| node.id.as_str())
+        .filter(|id| id.starts_with("_:c14n"))
+        .collect();
+    assert_eq!(labels.len(), 14, "{labels:?}");
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let mut outputs = Vec::new();
+    for (name, order) in [
+        ("first", ["a", "b", "c", "d"]),
+        ("second", ["a", "c", "b", "d"]),
+    ] {
+        let input = temp_dir.path().join(format!("{name}.ttl"));
+        fs::write(&input, symmetric(order)).unwrap();
+        let document = temp_dir.path().join(format!("{name}.jsonld"));
+        let output = temp_dir.path().join(format!("{name}.nq"));
+        sync(
+            "rdf",
+            &input,
+            "json-ld",
+            &document,
+            &["--canonical-blank-nodes"],
+        );
+        sync(
+            "json-ld",
+            &document,
+            "rdf",
+            &output,
+            &["--canonical-blank-nodes"],
+        );
+        let mut lines: Vec<String> = fs::read_to_string(&output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        lines.sort();
+        outputs.push(lines);
+    }
+    assert_eq!(outputs[0], outputs[1]);
+    assert_eq!(outputs[0].len(), 24, "{:?}", outputs[0]);
+}
@@ -0,0 +1,108 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::filter::NodeFilter;
+use aideon_tools::aideon::tools::io::excel_read;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success};
+
+const TRIG: &str = r#"@prefix schema: <https://schema.org/> .
+<https://example.com/people/0> schema:name "Default" .
+<https://example.com/graphs/hr> {
+    <https://example.com/people/1> a schema:Person ; schema:name "Ada" .
+}
+<https://example.com/graphs/sales> {
+    <https://example.com/people/2> a schema:Person ; schema:name "Bob" .
+    <https://example.com/people/3> a schema:Person ; schema:name "Cy" .
+}
+"#;
+
+fn ids(options: &SyncOptions, path: &std::path::Path) -> Vec<String> {
+    sync::read_rdf(path, None, options)
+        .expect("RDF read")
+        .into_iter()
+        .map(|node| node.id.to_string())
+        .collect()
+}
+
+#[test]
+fn node_filters_keep_the_selected_graphs() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.trig");
+    fs::write(&input, TRIG).unwrap();
+
+    assert_eq!(ids(&SyncOptions::default(), &input).len(), 4);
+    let options = SyncOptions {
+        node_filter: NodeFilter::new().with_graph("https://example.com/graphs/hr"),
+        max_nodes: Some(1),
+        ..SyncOptions::default()
+    };
+    // The limit counts the nodes kept.
+    assert_eq!(ids(&options, &input), ["https://example.com/people/1"]);
+    let options = SyncOptions {
+        node_filter: NodeFilter::new()
+            .with_default_graph()
+            .with_graph("https://example.com/graphs/hr"),
+        ..SyncOptions::default()
+    };
+    assert_eq!(ids(&options, &input).len(), 2);
+
+    // Streamed inputs are filtered chunk by chunk.
+    let output = temp_dir.path().join("sales.xlsx");
+    let options = SyncOptions {
+        node_filter: NodeFilter::new().with_graph("https://example.com/graphs/sales"),
+        chunk_size: Some(1),
+        ..SyncOptions::default()
+    };
+    sync::rdf_to_excel_with_options(&input, &output, &options).expect("workbook streamed");
+    let nodes = excel_read::read_nodes(&output).expect("workbook read");
+    assert_eq!(nodes.len(), 2);
+    assert!(
+        nodes
+            .iter()
+            .all(|node| node.graph.as_deref() == Some("https://example.com/graphs/sales"))
+    );
+}
+
+#[test]
+fn sync_converts_only_the_graphs_named() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.trig");
+    fs::write(&input, TRIG).unwrap();
+    let output = temp_dir.path().join("people.jsonld");
+
+    let sync = |flags: &[&str]| {
+        aideon()
+            .args(["sync", "--from", "rdf", "--input"])
+            .arg(&input)
+            .args(["--to", "json-ld", "--output"])
+            .arg(&output)
+            .args(flags)
+            .output()
+            .expect("CLI ran")
+    };
+    let run = sync(&["--default-graph-only"]);
+    assert_success(&run);
+    let written = fs::read_to_string(&output).unwrap();
+    assert!(written.contains("people/0"), "{written}");
+    assert!(!written.contains("people/1"), "{written}");
+
+    let run = sync(&[
+        "--graph",
+        "https://example.com/graphs/hr",
+        "--graph",
+        "https://example.com/graphs/sales",
+    ]);
+    assert!(run.status.success());
+    let written = fs::read_to_string(&output).unwrap();
+    assert!(!written.contains("people/0"), "{written}");
+    assert!(written.contains("people/1") && written.contains("people/3"));
+
+    let run = sync(&[
+        "--default-graph-only",
+        "--graph",
+        "https://example.com/graphs/hr",
+    ]);
+    assert!(!run.status.success());
+}
@@ -0,0 +1,15 @@
+//! Integration tests of reading, writing, and signing RDF.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod blank_nodes;
+mod graph_filter;
+mod n3;
+mod prefixes;
+mod rdf_lists;
+mod reification;
+mod same_as;
+mod signing;
+mod streaming;
+mod text_encoding;
@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success};
+
+const N3: &str = r#"@prefix schema: <https://schema.org/> .
+<https://example.com/people/1> schema:name "Ada" ;
+    schema:knows <https://example.com/people/2> .
+{ <https://example.com/people/2> schema:name "Grace" } schema:source <https://example.com/notes> .
+"#;
+
+#[test]
+fn n3_files_are_detected_and_read_as_rdf() {
+    assert_eq!(
+        rdf::detect_format(Path::new("rules.N3")),
+        Some(RdfFormat::N3)
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.n3");
+    fs::write(&input, N3).unwrap();
+    let nodes = sync::read_rdf(&input, None, &SyncOptions::default()).expect("N3 read");
+    assert_eq!(nodes.len(), 3);
+    // The formula is a graph named by a blank node.
+    assert!(nodes.iter().any(|node| {
+        node.id.as_str() == "https://example.com/people/2"
+            && node
+                .graph
+                .as_ref()
+                .is_some_and(|graph| graph.as_str().starts_with("_:"))
+    }));
+
+    fs::write(&input, "?person <https://schema.org/name> \"Ada\" .\n").unwrap();
+    let err = sync::read_rdf(&input, None, &SyncOptions::default()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains(&format!("failed to read {} as N3: ", input.display()))
+    );
+}
+
+#[test]
+fn rdf_input_format_overrides_the_extension() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.txt");
+    fs::write(&input, N3).unwrap();
+
+    let err = sync::read_rdf(&input, None, &SyncOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("people.txt"));
+
+    let options = SyncOptions {
+        rdf_input_format: Some(RdfFormat::N3),
+        ..SyncOptions::default()
+    };
+    assert_eq!(sync::read_rdf(&input, None, &options).unwrap().len(), 3);
+
+    // Read as Turtle, the formula is a syntax error naming the format.
+    let output = temp_dir.path().join("people.jsonld");
+    let run = |format: &str| {
+        aideon()
+            .args(["sync", "--from", "rdf", "--input"])
+            .arg(&input)
+            .args(["--to", "json-ld", "--output"])
+            .arg(&output)
+            .args(["--rdf-input-format", format])
+            .output()
+            .expect("CLI ran")
+    };
+    let turtle = run("turtle");
+    assert!(!turtle.status.success());
+    assert!(String::from_utf8_lossy(&turtle.stderr).contains("people.txt as Turtle"));
+    let n3 = run("n3");
+    assert_success(&n3);
+    assert!(fs::read_to_string(&output).unwrap().contains("Grace"));
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::{
+    METADATA_SHEET, WorkbookOptions, build_workbook_with_options,
+};
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::io::{csv_read, csv_write, excel_read, excel_write, jsonld};
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{parse_jsonld, people, references};
+
+fn following_people() -> Vec<Node> {
+    let mut document = people();
+    document["@graph"][0]["https://schema.org/follows"] = references(&[2, 3]);
+    parse_jsonld(&document)
+}
+
+#[test]
+fn turtle_output_declares_the_prefixes_it_uses() {
+    let context = json!({
+        "@vocab": "https://schema.org/",
+        "people": "https://example.com/people/",
+        "name": "https://schema.org/name",
+        "ex": { "@id": "https://example.com/terms", "@prefix": true }
+    });
+    assert_eq!(
+        jsonld::context_prefixes(&context),
+        BTreeMap::from([
+            ("ex".to_string(), "https://example.com/terms".to_string()),
+            (
+                "people".to_string(),
+                "https://example.com/people/".to_string()
+            ),
+        ])
+    );
+
+    let options = SyncOptions {
+        prefixes: BTreeMap::from([("schema".into(), "http://schema.org/".into())]),
+        ..SyncOptions::default()
+    };
+    let registry = options.prefix_registry(Some(&context));
+    assert_eq!(registry["schema"], "http://schema.org/");
+    assert_eq!(registry["rdfs"], "http://www.w3.org/2000/01/rdf-schema#");
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.ttl");
+    let nodes = following_people();
+    let registry = SyncOptions::default().prefix_registry(Some(&context));
+    rdf::write_rdf_with_prefixes(&path, &nodes, RdfFormat::Turtle, &registry).expect("RDF written");
+    let turtle = fs::read_to_string(&path).unwrap();
+    assert!(turtle.contains("@prefix schema: <https://schema.org/> ."));
+    assert!(turtle.contains("@prefix people: <https://example.com/people/> ."));
+    assert!(turtle.contains("schema:name"));
+    assert!(!turtle.contains("@prefix rdfs:"));
+    assert!(!turtle.contains("@prefix xsd:"));
+
+    let plain = temp_dir.path().join("people.nt");
+    rdf::write_rdf(&plain, &nodes, RdfFormat::NTriples).expect("RDF written");
+    assert_eq!(
+        rdf::read_rdf(&path, None).expect("RDF read"),
+        rdf::read_rdf(&plain, None).expect("RDF read")
+    );
+}
+
+#[test]
+fn compact_headers_are_expanded_on_read() {
+    let nodes = following_people();
+    let options = SyncOptions {
+        compact_headers: true,
+        ..SyncOptions::default()
+    };
+    let mut workbook =
+        build_workbook_with_options(&nodes, &options.workbook_options()).expect("tables built");
+    let person = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == "https___schema.org_Person")
+        .expect("person sheet");
+    assert_eq!(
+        person.columns,
+        ["id", "graph", "schema:knowsId", "schema:name"]
+    );
+    // Users add a column of their own, abbreviated like the others.
+    person.columns.push("schema:email".into());
+    for row in &mut person.rows {
+        row.push(String::new());
+    }
+    person.rows[0][4] = "\"ada@example.com\"".into();
+
+    let child = workbook
+        .tables
+        .iter()
+        .find(|table| table.columns[0] == "ParentId")
+        .expect("child sheet");
+    assert_eq!(child.columns[2], "schema:followsId");
+    let metadata = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == METADATA_SHEET)
+        .expect("metadata sheet");
+    let prefix_rows: Vec<&Vec<String>> = metadata
+        .rows
+        .iter()
+        .filter(|row| row[0] == "prefix")
+        .collect();
+    assert_eq!(
+        prefix_rows,
+        [&["prefix", "", "", "https://schema.org/", "schema", "", ""].map(String::from)]
+    );
+
+    let mut expected = nodes.clone();
+    expected[0].properties.insert(
+        "https://schema.org/email".into(),
+        PropertyValue::Scalar(ScalarValue::String("ada@example.com".into())),
+    );
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("workbook written");
+    assert_eq!(
+        excel_read::read_nodes(&path).expect("workbook read"),
+        expected
+    );
+    let dir = temp_dir.path().join("people");
+    csv_write::write_workbook(&dir, &workbook).expect("CSV written");
+    assert_eq!(csv_read::read_nodes(&dir).expect("CSV read"), expected);
+
+    let plain =
+        build_workbook_with_options(&nodes, &WorkbookOptions::default()).expect("tables built");
+    assert!(
+        plain
+            .tables
+            .iter()
+            .flat_map(|table| &table.columns)
+            .all(|column| !column.starts_with("schema:"))
+    );
+}
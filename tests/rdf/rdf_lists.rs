@@ -0,0 +1,159 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use aideon_tools::aideon::tools::io::jsonld;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::model::{
+    ArrayItem, ArrayValue, Iri, Node, NodeId, PropertyValue, ScalarValue,
+};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const STEPS: &str = "https://schema.org/step";
+const RECIPE: &str = "https://example.com/recipes/1";
+
+fn steps(nodes: &[Node]) -> &PropertyValue {
+    let recipe = nodes
+        .iter()
+        .find(|node| node.id.as_str() == RECIPE)
+        .expect("recipe read");
+    &recipe.properties[STEPS]
+}
+
+#[test]
+fn collections_read_as_ordered_arrays() {
+    let turtle = r#"@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix schema: <https://schema.org/> .
+<https://example.com/recipes/1> schema:step ( "Whisk" <https://example.com/steps/bake> "Serve" ) ;
+    schema:tool rdf:nil ;
+    schema:keywords _:shared .
+<https://example.com/recipes/2> schema:keywords _:shared .
+_:shared rdf:first "quick" ; rdf:rest rdf:nil .
+"#;
+    let nodes = rdf::read_rdf_from(turtle.as_bytes(), RdfFormat::Turtle).expect("collections read");
+    assert_eq!(
+        steps(&nodes),
+        &PropertyValue::Array(ArrayValue::Mixed(vec![
+            ArrayItem::Scalar(ScalarValue::String("Whisk".into())),
+            ArrayItem::ObjectRef("https://example.com/steps/bake".into()),
+            ArrayItem::Scalar(ScalarValue::String("Serve".into())),
+        ]))
+    );
+    // The list cells are gone and the shared list stays.
+    let ids: Vec<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(ids.len(), 3, "{ids:?}");
+    let recipe = nodes
+        .iter()
+        .find(|node| node.id.as_str() == RECIPE)
+        .unwrap();
+    assert_eq!(
+        recipe.properties["https://schema.org/tool"],
+        PropertyValue::Array(ArrayValue::Scalars(Vec::new()))
+    );
+    assert_eq!(
+        recipe.lists,
+        BTreeSet::from([Iri::from(STEPS), Iri::from("https://schema.org/tool")])
+    );
+    assert!(matches!(
+        recipe.properties["https://schema.org/keywords"],
+        PropertyValue::ObjectRef(_)
+    ));
+}
+
+#[test]
+fn list_predicates_are_written_as_collections() {
+    let mut recipe = Node::new(RECIPE);
+    recipe.properties.insert(
+        STEPS.into(),
+        PropertyValue::Array(ArrayValue::Scalars(vec![
+            ScalarValue::String("Whisk".into()),
+            ScalarValue::Null,
+            ScalarValue::String("Bake".into()),
+        ])),
+    );
+    recipe.properties.insert(
+        "https://schema.org/name".into(),
+        PropertyValue::Scalar(ScalarValue::String("Cake".into())),
+    );
+    // A blank node already named like a generated cell is not reused.
+    recipe.properties.insert(
+        "https://schema.org/author".into(),
+        PropertyValue::ObjectRef(NodeId::from("_:list1")),
+    );
+    let nodes = vec![recipe];
+
+    let lists = BTreeSet::from([Iri::from(STEPS)]);
+    let written = rdf::build_lists(&nodes, &lists);
+    assert_eq!(written.len(), 3);
+    assert!(written.iter().all(|node| node.id.as_str() != "_:list1"));
+
+    let options = SyncOptions {
+        rdf_lists: lists,
+        ..SyncOptions::default()
+    };
+    let temp_dir = tempdir().expect("temporary directory");
+    let output = temp_dir.path().join("recipes.ttl");
+    sync::write_rdf(&nodes, &output, RdfFormat::Turtle, None, &options).expect("RDF written");
+    assert!(fs::read_to_string(&output).unwrap().contains("rdf:first"));
+
+    let restored = sync::read_rdf(&output, None, &SyncOptions::default()).expect("RDF read");
+    assert_eq!(
+        steps(&restored),
+        &PropertyValue::Array(ArrayValue::Scalars(vec![
+            ScalarValue::String("Whisk".into()),
+            ScalarValue::String("Bake".into()),
+        ]))
+    );
+    assert_eq!(restored.len(), 1);
+}
+
+#[test]
+fn collections_stay_ordered_through_jsonld() {
+    let turtle = r#"@prefix schema: <https://schema.org/> .
+<https://example.com/recipes/1> schema:step ( "Whisk" "Bake" "Serve" ) ;
+    schema:tool () .
+"#;
+    let nodes = rdf::read_rdf_from(turtle.as_bytes(), RdfFormat::Turtle).expect("collections read");
+
+    let document = jsonld::nodes_to_jsonld(&nodes, None).expect("JSON-LD written");
+    let recipe = &document["@graph"][0];
+    assert_eq!(
+        recipe[STEPS],
+        json!({ "@list": ["Whisk", "Bake", "Serve"] }),
+        "{document}"
+    );
+    assert_eq!(
+        recipe["https://schema.org/tool"],
+        json!({ "@list": [] }),
+        "{document}"
+    );
+
+    // Read back from JSON-LD, the lists are written as collections without
+    // naming their predicates.
+    let restored = parse_jsonld(&document);
+    assert_eq!(restored, nodes);
+    let mut triples = Vec::new();
+    rdf::write_rdf_to(
+        &mut triples,
+        &restored,
+        RdfFormat::NTriples,
+        &Default::default(),
+    )
+    .expect("RDF written");
+    let triples = String::from_utf8(triples).unwrap();
+    assert_eq!(
+        triples.matches("rdf-syntax-ns#first").count(),
+        3,
+        "{triples}"
+    );
+    assert!(
+        triples
+            .contains("<https://schema.org/tool> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil>"),
+        "{triples}"
+    );
+    let again = rdf::read_rdf_from(triples.as_bytes(), RdfFormat::NTriples).expect("RDF read");
+    assert_eq!(again, nodes);
+}
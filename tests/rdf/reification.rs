@@ -0,0 +1,134 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::jsonld;
+use aideon_tools::aideon::tools::io::rdf::{self, RDF_STATEMENT, RdfFormat};
+use aideon_tools::aideon::tools::model::{ArrayValue, Node, PropertyValue, ScalarValue};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success};
+
+const MEMBER_OF: &str = "https://schema.org/memberOf";
+const ROLE: &str = "https://schema.org/roleName";
+const KNOWS: &str = "https://schema.org/knows";
+const PERSON: &str = "https://example.com/people/1";
+
+fn node<'a>(nodes: &'a [Node], id: &str) -> &'a Node {
+    nodes
+        .iter()
+        .find(|node| node.id.as_str() == id)
+        .unwrap_or_else(|| panic!("{id} read: {nodes:?}"))
+}
+
+#[test]
+fn asserted_statements_fold_into_relationship_nodes() {
+    let turtle = r#"@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix schema: <https://schema.org/> .
+<https://example.com/people/1> schema:memberOf <https://example.com/orgs/acme>,
+        <https://example.com/orgs/globex> ;
+    schema:name "Ada" .
+_:role a rdf:Statement ;
+    rdf:subject <https://example.com/people/1> ;
+    rdf:predicate schema:memberOf ;
+    rdf:object <https://example.com/orgs/acme> ;
+    schema:roleName "treasurer" .
+_:alias rdf:subject <https://example.com/people/1> ;
+    rdf:predicate schema:name ;
+    rdf:object "Ada" ;
+    schema:source <https://example.com/registry> .
+_:rumour a rdf:Statement ;
+    rdf:subject <https://example.com/people/1> ;
+    rdf:predicate schema:memberOf ;
+    rdf:object <https://example.com/orgs/initech> .
+"#;
+    let nodes = rdf::read_rdf_from(turtle.as_bytes(), RdfFormat::Turtle).expect("RDF read");
+    let nodes = rdf::fold_reifications(nodes);
+    assert_eq!(nodes.len(), 4, "{nodes:?}");
+
+    let person = node(&nodes, PERSON);
+    let Some(PropertyValue::Array(ArrayValue::ObjectRefs(targets))) =
+        person.properties.get(MEMBER_OF)
+    else {
+        panic!("memberOf references: {person:?}");
+    };
+    assert_eq!(targets.len(), 2);
+    assert!(
+        targets
+            .iter()
+            .any(|id| id == "https://example.com/orgs/globex")
+    );
+    let role = node(
+        &nodes,
+        targets
+            .iter()
+            .find(|id| id.starts_with("_:"))
+            .expect("relationship node referenced"),
+    );
+    assert!(role.types.is_empty());
+    assert_eq!(role.properties.len(), 2, "{role:?}");
+    assert_eq!(
+        role.properties[MEMBER_OF],
+        PropertyValue::ObjectRef("https://example.com/orgs/acme".into())
+    );
+    assert_eq!(
+        role.properties[ROLE],
+        PropertyValue::Scalar(ScalarValue::String("treasurer".into()))
+    );
+
+    // Literal objects fold too, and the relationship node holds the literal.
+    let PropertyValue::ObjectRef(alias) = &person.properties["https://schema.org/name"] else {
+        panic!("name folded: {person:?}");
+    };
+    assert_eq!(
+        node(&nodes, alias).properties["https://schema.org/name"],
+        PropertyValue::Scalar(ScalarValue::String("Ada".into()))
+    );
+
+    // A statement the graph does not assert is kept as it is.
+    let rumour = nodes
+        .iter()
+        .find(|node| {
+            node.types
+                .iter()
+                .any(|type_name| type_name == RDF_STATEMENT)
+        })
+        .expect("unasserted statement kept");
+    assert_eq!(rumour.properties.len(), 3);
+}
+
+#[test]
+fn reifications_are_folded_only_when_asked() {
+    let turtle = r#"@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix schema: <https://schema.org/> .
+<https://example.com/a> schema:knows <https://example.com/b> .
+_:r a rdf:Statement ;
+    rdf:subject <https://example.com/a> ;
+    rdf:predicate schema:knows ;
+    rdf:object <https://example.com/b> ;
+    schema:startDate "2020" .
+"#;
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.ttl");
+    fs::write(&input, turtle).unwrap();
+
+    for (flag, kept) in [(None, true), (Some("--fold-reifications"), false)] {
+        let output = temp_dir.path().join("people.jsonld");
+        let run = aideon()
+            .args(["sync", "--from", "rdf", "--input"])
+            .arg(&input)
+            .args(["--to", "json-ld", "--output"])
+            .arg(&output)
+            .args(flag)
+            .output()
+            .expect("CLI ran");
+        assert_success(&run);
+        let document = fs::read_to_string(&output).unwrap();
+        let nodes = jsonld::parse_jsonld_str(&document).expect("JSON-LD read");
+        let knows = &node(&nodes, "https://example.com/a").properties[KNOWS];
+        assert_eq!(
+            knows == &PropertyValue::ObjectRef("https://example.com/b".into()),
+            kept,
+            "{flag:?}: {document}"
+        );
+        assert!(document.contains("startDate"), "{flag:?}: {document}");
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+
+use aideon_tools::aideon::tools::flatten::{WorkbookOptions, build_workbook_with_options};
+use aideon_tools::aideon::tools::io::excel_read;
+use aideon_tools::aideon::tools::model::{ArrayValue, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::same_as::{self, OWL_SAME_AS};
+use aideon_tools::aideon::tools::sync::DuplicateCluster;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, node, parse_jsonld};
+
+const NAME: &str = "https://schema.org/name";
+const KNOWS: &str = "https://schema.org/knows";
+
+fn aliased_people() -> serde_json::Value {
+    json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada Lovelace",
+                "http://www.w3.org/2002/07/owl#sameAs": [
+                    { "@id": "https://example.com/people/3" },
+                    { "@id": "https://www.wikidata.org/entity/Q7259" }
+                ]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Grace Hopper",
+                "https://schema.org/knows": { "@id": "https://example.com/people/3" }
+            },
+            {
+                "@id": "https://example.com/people/3",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Countess of Lovelace"
+            },
+            {
+                "@id": "_:grace",
+                "@type": "https://schema.org/Person",
+                "http://www.w3.org/2002/07/owl#sameAs": { "@id": "https://example.com/people/2" }
+            }
+        ]
+    })
+}
+
+fn sync(input: &Path, output: &Path, to: &str, mode: &str) -> Output {
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(input)
+        .args(["--to", to, "--output"])
+        .arg(output)
+        .args(["--same-as", mode])
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    run
+}
+
+#[test]
+fn linked_nodes_merge_into_their_iri_or_sort_together() {
+    let nodes = parse_jsonld(&aliased_people());
+    assert_eq!(
+        same_as::same_as_clusters(&nodes),
+        [
+            DuplicateCluster {
+                graph: None,
+                id: "https://example.com/people/1".into(),
+                merged: vec!["https://example.com/people/3".into()],
+            },
+            DuplicateCluster {
+                graph: None,
+                id: "https://example.com/people/2".into(),
+                merged: vec!["_:grace".into()],
+            },
+        ]
+    );
+
+    let merged = same_as::merge_same_as(&nodes);
+    assert_eq!(merged.len(), 2);
+    let ada = node(&merged, "https://example.com/people/1").expect("kept");
+    assert_eq!(
+        ada.properties[NAME],
+        PropertyValue::Array(ArrayValue::Scalars(vec![
+            ScalarValue::String("Ada Lovelace".into()),
+            ScalarValue::String("Countess of Lovelace".into()),
+        ]))
+    );
+    assert_eq!(
+        ada.properties[OWL_SAME_AS],
+        PropertyValue::ObjectRef("https://www.wikidata.org/entity/Q7259".into())
+    );
+    let grace = node(&merged, "https://example.com/people/2").expect("kept");
+    assert_eq!(
+        grace.properties[KNOWS],
+        PropertyValue::ObjectRef("https://example.com/people/1".into())
+    );
+    assert!(!grace.properties.contains_key(OWL_SAME_AS));
+
+    let options = WorkbookOptions {
+        colocate_same_as: true,
+        ..WorkbookOptions::default()
+    };
+    let workbook = build_workbook_with_options(&nodes, &options).expect("workbook built");
+    let people = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == "https___schema.org_Person")
+        .expect("type sheet");
+    let ids: Vec<&str> = people.rows.iter().map(|row| row[0].as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "https://example.com/people/1",
+            "https://example.com/people/3",
+            "_:grace",
+            "https://example.com/people/2",
+        ]
+    );
+}
+
+#[test]
+fn sync_merges_or_reports_same_as_clusters() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, aliased_people().to_string()).unwrap();
+
+    let output = temp_dir.path().join("merged.xlsx");
+    sync(&input, &output, "excel", "merge");
+    let mut nodes = excel_read::read_nodes(&output).expect("workbook read");
+    nodes.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+    let ids: Vec<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "https://example.com/people/1",
+            "https://example.com/people/2"
+        ]
+    );
+
+    let output = temp_dir.path().join("people.xlsx");
+    let run = sync(&input, &output, "excel", "report");
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(
+        stderr.contains(
+            "owl:sameAs links https://example.com/people/3 to https://example.com/people/1"
+        ),
+        "{stderr}"
+    );
+    assert!(stderr.contains("owl:sameAs links _:grace to https://example.com/people/2"));
+}
@@ -0,0 +1,117 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::io::excel_read;
+use aideon_tools::aideon::tools::io::jsonld;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const SORTED_NQUADS: &str = r#"<https://example.com/people/1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://schema.org/Person> .
+<https://example.com/people/1> <https://schema.org/knows> <https://example.com/people/2> .
+<https://example.com/people/1> <https://schema.org/knows> <https://example.com/people/3> .
+<https://example.com/people/1> <https://schema.org/name> "Alice" .
+<https://example.com/people/2> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://schema.org/Person> .
+<https://example.com/people/2> <https://schema.org/name> "Bob" .
+<https://example.com/people/3> <https://schema.org/name> "Carol" <https://example.com/graphs/hr> .
+"#;
+
+fn sorted(mut nodes: Vec<Node>) -> Vec<Node> {
+    nodes.sort_by(|a, b| (&a.graph, &a.id).cmp(&(&b.graph, &b.id)));
+    nodes
+}
+
+#[test]
+fn sorted_nquads_stream_in_batches() {
+    let dir = tempdir().expect("temp dir");
+    let input = dir.path().join("people.nq");
+    fs::write(&input, SORTED_NQUADS).expect("input written");
+
+    let batches: Vec<Vec<Node>> = rdf::stream_nodes(&input, None, 2)
+        .expect("stream opened")
+        .collect::<Result<_, _>>()
+        .expect("batches read");
+
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 1]);
+    let streamed: Vec<Node> = batches.into_iter().flatten().collect();
+    let loaded = rdf::read_rdf(&input, Some(RdfFormat::NQuads)).expect("RDF read");
+    assert_eq!(sorted(streamed), sorted(loaded));
+}
+
+#[test]
+fn chunked_conversions_match_whole_file_conversions() {
+    let dir = tempdir().expect("temp dir");
+    let input = dir.path().join("people.nq");
+    fs::write(&input, SORTED_NQUADS).expect("input written");
+    let chunked = SyncOptions {
+        chunk_size: Some(1),
+        ..SyncOptions::default()
+    };
+
+    let whole_xlsx = dir.path().join("whole.xlsx");
+    let chunked_xlsx = dir.path().join("chunked.xlsx");
+    sync::rdf_to_excel_with_options(&input, &whole_xlsx, &SyncOptions::default())
+        .expect("whole workbook");
+    sync::rdf_to_excel_with_options(&input, &chunked_xlsx, &chunked).expect("chunked workbook");
+    let expected = sorted(excel_read::read_nodes(&whole_xlsx).expect("whole read"));
+    assert_eq!(expected.len(), 3);
+    assert_eq!(
+        sorted(excel_read::read_nodes(&chunked_xlsx).expect("chunked read")),
+        expected
+    );
+
+    let context = serde_json::json!({ "@vocab": "https://schema.org/" });
+    let output = dir.path().join("people.jsonld");
+    sync::rdf_to_jsonld_with_options(&input, &output, Some(context.clone()), &chunked)
+        .expect("chunked JSON-LD");
+    let document: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output).expect("JSON-LD read"))
+            .expect("valid JSON");
+    assert_eq!(document["@context"], context);
+    let restored = parse_jsonld(&document);
+    assert_eq!(sorted(restored), expected);
+}
+
+#[test]
+fn whole_graph_options_turn_chunking_off() {
+    let dir = tempdir().expect("temp dir");
+    let input = dir.path().join("people.ttl");
+    fs::write(
+        &input,
+        r#"@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix schema: <https://schema.org/> .
+<https://example.com/people/1> schema:knows <https://example.com/people/2> ;
+    schema:award ( "Turing" "Lovelace" ) .
+_:role a rdf:Statement ;
+    rdf:subject <https://example.com/people/1> ;
+    rdf:predicate schema:knows ;
+    rdf:object <https://example.com/people/2> ;
+    schema:startDate "2020" .
+"#,
+    )
+    .expect("input written");
+    let convert = |options: &SyncOptions| {
+        let output = dir.path().join("people.jsonld");
+        sync::rdf_to_jsonld_with_options(&input, &output, None, options).expect("JSON-LD written");
+        sorted(jsonld::parse_jsonld_str(&fs::read_to_string(&output).unwrap()).unwrap())
+    };
+
+    for whole in [
+        SyncOptions {
+            fold_reifications: true,
+            ..SyncOptions::default()
+        },
+        SyncOptions {
+            rdf_lists: ["https://schema.org/award".into()].into(),
+            ..SyncOptions::default()
+        },
+    ] {
+        let chunked = SyncOptions {
+            chunk_size: Some(1),
+            ..whole.clone()
+        };
+        assert_eq!(convert(&chunked), convert(&whole));
+    }
+}
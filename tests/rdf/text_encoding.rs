@@ -0,0 +1,99 @@
+use std::fs;
+use std::io::Read;
+
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::io::text_encoding::TextEncoding;
+use aideon_tools::aideon::tools::model::{PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use tempfile::tempdir;
+
+const TURTLE: &str =
+    "<https://example.com/people/1> <https://schema.org/name> \"Zoë Ångström\" .\n";
+
+fn utf16le(text: &str, bom: bool) -> Vec<u8> {
+    let bom = bom.then_some(0xFEFF);
+    bom.into_iter()
+        .chain(text.encode_utf16())
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+fn name(nodes: &[aideon_tools::aideon::tools::model::Node]) -> &str {
+    match nodes[0].properties.values().next() {
+        Some(PropertyValue::Scalar(ScalarValue::String(name))) => name,
+        other => panic!("unexpected name {other:?}"),
+    }
+}
+
+/// Reader returning seven bytes at a time.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = buf.len().min(7).min(self.0.len());
+        buf[..length].copy_from_slice(&self.0[..length]);
+        self.0 = &self.0[length..];
+        Ok(length)
+    }
+}
+
+#[test]
+fn utf16_inputs_are_detected_and_transcoded() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let options = SyncOptions::default();
+
+    let jsonld = temp_dir.path().join("people.jsonld");
+    let document =
+        r#"{"@id": "https://example.com/people/1", "https://schema.org/name": "Zoë Ångström"}"#;
+    fs::write(&jsonld, utf16le(document, true)).unwrap();
+    let nodes = sync::read_jsonld(&jsonld, &options).expect("UTF-16 JSON-LD read");
+    assert_eq!(name(&nodes), "Zoë Ångström");
+
+    let big_endian: Vec<u8> = TURTLE.encode_utf16().flat_map(u16::to_be_bytes).collect();
+    let turtle = temp_dir.path().join("people.ttl");
+    fs::write(&turtle, big_endian).unwrap();
+    let nodes = sync::read_rdf(&turtle, None, &options).expect("unmarked UTF-16 read");
+    assert_eq!(name(&nodes), "Zoë Ångström");
+
+    // Decoding stays correct when characters straddle the reads of the
+    // underlying reader.
+    let mut text = String::new();
+    let bytes = utf16le(&TURTLE.repeat(2_000), false);
+    TextEncoding::Utf16Le
+        .reader(Trickle(&bytes))
+        .read_to_string(&mut text)
+        .expect("decoded");
+    assert_eq!(text, TURTLE.repeat(2_000));
+    assert_eq!(TextEncoding::Auto.decode(b"\xEF\xBB\xBF{}").unwrap(), "{}");
+    assert_eq!(TextEncoding::Auto.decode(b"").unwrap(), "");
+}
+
+#[test]
+fn latin1_inputs_need_the_encoding_named() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let turtle = temp_dir.path().join("people.nt");
+    let latin1: Vec<u8> = TURTLE.chars().map(|character| character as u8).collect();
+    fs::write(&turtle, &latin1).unwrap();
+
+    let err = sync::read_rdf(&turtle, None, &SyncOptions::default()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("input is not valid UTF-8 at byte 60; name its encoding, such as latin-1")
+    );
+    let jsonld = temp_dir.path().join("people.jsonld");
+    fs::write(&jsonld, b"{\"https://schema.org/name\": \"Zo\xEB\"}").unwrap();
+    let err = sync::read_jsonld(&jsonld, &SyncOptions::default()).unwrap_err();
+    assert!(matches!(err, ToolError::TextEncoding(_)));
+    assert_eq!(
+        err.to_string(),
+        "text encoding error: input is not valid UTF-8 at byte 31; \
+         name its encoding, such as latin-1, to transcode it"
+    );
+
+    let options = SyncOptions {
+        input_encoding: TextEncoding::Latin1,
+        ..SyncOptions::default()
+    };
+    let nodes = sync::read_rdf(&turtle, None, &options).expect("Latin-1 read");
+    assert_eq!(name(&nodes), "Zoë Ångström");
+}
@@ -0,0 +1,81 @@
+use std::fs;
+
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::io::atomic;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use tempfile::tempdir;
+
+fn file_names(dir: &std::path::Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn failed_writes_keep_the_previous_output() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let output = temp_dir.path().join("people.ttl");
+    fs::write(&output, "previous").unwrap();
+
+    let partial = atomic::partial_path(&output);
+    assert_eq!(partial.parent(), output.parent());
+    assert!(
+        partial
+            .to_string_lossy()
+            .ends_with(&format!(".{}.partial", std::process::id()))
+    );
+
+    let err = atomic::write_atomically(&output, |partial| {
+        fs::write(partial, "trunc")?;
+        Err::<(), _>(ToolError::Rdf("interrupted".into()))
+    })
+    .unwrap_err();
+    assert!(matches!(err, ToolError::Rdf(_)));
+    assert_eq!(fs::read_to_string(&output).unwrap(), "previous");
+    assert_eq!(file_names(temp_dir.path()), ["people.ttl"]);
+
+    atomic::write_atomically(&output, |partial| {
+        assert_eq!(fs::read_to_string(&output).unwrap(), "previous");
+        Ok(fs::write(partial, "next")?)
+    })
+    .expect("output written");
+    assert_eq!(fs::read_to_string(&output).unwrap(), "next");
+    assert_eq!(file_names(temp_dir.path()), ["people.ttl"]);
+}
+
+#[test]
+fn interrupted_streams_leave_existing_output_alone() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.nt");
+    let triples: String = (1..=5)
+        .map(|index| {
+            format!(
+                "<https://example.com/people/{index}> <https://schema.org/name> \"Person {index}\" .\n"
+            )
+        })
+        .collect();
+    fs::write(&input, triples).unwrap();
+    let output = temp_dir.path().join("people.jsonld");
+    fs::write(&output, "{}").unwrap();
+
+    let options = SyncOptions {
+        chunk_size: Some(2),
+        max_nodes: Some(3),
+        ..SyncOptions::default()
+    };
+    let err = sync::rdf_to_jsonld_with_options(&input, &output, None, &options).unwrap_err();
+    assert!(matches!(err, ToolError::LimitExceeded { .. }));
+    assert_eq!(fs::read_to_string(&output).unwrap(), "{}");
+    assert_eq!(file_names(temp_dir.path()), ["people.jsonld", "people.nt"]);
+
+    let excel = temp_dir.path().join("people.xlsx");
+    sync::rdf_to_excel_with_options(&input, &excel, &SyncOptions::default())
+        .expect("workbook written");
+    assert_eq!(
+        file_names(temp_dir.path()),
+        ["people.jsonld", "people.nt", "people.xlsx"]
+    );
+}
@@ -0,0 +1,104 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aideon_tools::aideon::tools::batch::{self, Glob};
+use tempfile::tempdir;
+
+use crate::common::aideon;
+
+#[test]
+fn globs_select_files_by_name_or_relative_path() {
+    let by_name = Glob::new("*.jsonld").expect("glob compiled");
+    assert!(by_name.matches(Path::new("people.jsonld")));
+    assert!(by_name.matches(Path::new("teams/2024/people.jsonld")));
+    assert!(!by_name.matches(Path::new("people.jsonld.bak")));
+
+    let nested = Glob::new("teams/**/p?ople.[jt][st]*").expect("glob compiled");
+    assert!(nested.matches(Path::new("teams/people.ttl")));
+    assert!(nested.matches(Path::new("teams/2024/q1/people.jsonld")));
+    assert!(!nested.matches(Path::new("people.ttl")));
+    assert!(!nested.matches(Path::new("teams/people.nt")));
+
+    let excluded = Glob::new("[!a-c]*.nt").expect("glob compiled");
+    assert!(excluded.matches(Path::new("dump.nt")));
+    assert!(!excluded.matches(Path::new("base.nt")));
+    assert!(Glob::new("[abc").is_err());
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("teams/2024")).unwrap();
+    fs::create_dir_all(root.join(".cache")).unwrap();
+    for file in [
+        "people.jsonld",
+        "teams/2024/people.jsonld",
+        "teams/notes.txt",
+        ".cache/old.jsonld",
+        ".hidden.jsonld",
+    ] {
+        fs::write(root.join(file), "{}").unwrap();
+    }
+    assert_eq!(
+        batch::matching_files(root, &by_name).expect("files listed"),
+        [
+            PathBuf::from("people.jsonld"),
+            PathBuf::from("teams/2024/people.jsonld")
+        ]
+    );
+    assert_eq!(
+        batch::output_path(Path::new("out"), Path::new("teams/people.jsonld"), "ttl"),
+        Path::new("out/teams/people.ttl")
+    );
+    assert_eq!(
+        batch::output_path(Path::new("out"), Path::new("people.jsonld"), ""),
+        Path::new("out/people")
+    );
+}
+
+#[test]
+fn batches_convert_every_file_and_report_failures() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input_dir = temp_dir.path().join("data");
+    fs::create_dir_all(input_dir.join("teams")).unwrap();
+    fs::write(
+        input_dir.join("ada.jsonld"),
+        r#"{"@id": "https://example.com/ada", "https://schema.org/name": "Ada"}"#,
+    )
+    .unwrap();
+    fs::write(
+        input_dir.join("teams/bob.jsonld"),
+        r#"{"@id": "https://example.com/bob", "https://schema.org/name": "Bob"}"#,
+    )
+    .unwrap();
+    fs::write(input_dir.join("teams/broken.jsonld"), "not JSON").unwrap();
+    let output_dir = temp_dir.path().join("out");
+
+    let run = aideon()
+        .args([
+            "--log-level",
+            "error",
+            "sync",
+            "--from",
+            "json-ld",
+            "--to",
+            "rdf",
+        ])
+        .args(["--rdf-format", "n-triples", "--glob", "*.jsonld"])
+        .arg("--input-dir")
+        .arg(&input_dir)
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .output()
+        .expect("CLI ran");
+    assert!(!run.status.success());
+    let stderr = String::from_utf8(run.stderr).unwrap();
+    assert!(stderr.contains("converted 2 of 3 file(s)"));
+    assert!(stderr.contains("teams/broken.jsonld: JSON error"));
+    assert!(stderr.contains("error: 1 of 3 file(s) failed to convert"));
+
+    assert_eq!(
+        fs::read_to_string(output_dir.join("teams/bob.nt")).unwrap(),
+        "<https://example.com/bob> <https://schema.org/name> \"Bob\" .\n"
+    );
+    assert!(output_dir.join("ada.nt").is_file());
+    assert!(!output_dir.join("teams/broken.nt").exists());
+}
@@ -0,0 +1,173 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::config::{CONFIG_FILE, Profile};
+use aideon_tools::aideon::tools::flatten::build_workbook_with_options;
+use aideon_tools::aideon::tools::io::excel_write;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use aideon_tools::{Result, ToolError};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{ORGANIZATION, PERSON, parse_jsonld, people};
+
+fn profile() -> Value {
+    json!({
+        "context": "contexts/schema.json",
+        "rdf-format": "n-quads",
+        "prefixes": {
+            "schema": "https://schema.org/",
+            "staff": "https://example.com/staff/"
+        },
+        "column-order": { "schema:Person": ["schema:worksFor", "schema:name"] },
+        "skip-sheets": ["https___schema.org_Organization"],
+        "id-properties": { "schema:Person": "schema:identifier" }
+    })
+}
+
+fn load(config: &Value) -> Result<Profile> {
+    let temp_dir = tempdir().expect("temporary directory");
+    fs::write(
+        temp_dir.path().join(CONFIG_FILE),
+        toml::to_string(config).unwrap(),
+    )
+    .unwrap();
+    Profile::discover(temp_dir.path()).map(|profile| profile.expect("profile found"))
+}
+
+fn staff() -> Vec<Node> {
+    let mut document = people();
+    let graph = &mut document["@graph"];
+    graph[0]["https://schema.org/identifier"] = json!({ "@value": "staff:ada" });
+    graph[0]["https://schema.org/email"] = json!("ada@example.com");
+    graph[0]["https://schema.org/worksFor"] = json!({ "@id": "https://example.com/acme" });
+    graph[1]["https://schema.org/identifier"] = json!({ "@id": "https://example.com/staff/grace" });
+    graph.as_array_mut().unwrap().push(json!({
+        "@id": "https://example.com/acme",
+        "@type": ORGANIZATION,
+        "https://schema.org/name": "ACME"
+    }));
+    parse_jsonld(&document)
+}
+
+#[test]
+fn profiles_expand_compact_iris_and_resolve_paths() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("team.toml");
+    fs::write(&path, toml::to_string(&profile()).unwrap()).unwrap();
+    let profile = Profile::load(&path).expect("profile loaded");
+    assert_eq!(
+        profile.context,
+        Some(temp_dir.path().join("contexts/schema.json"))
+    );
+    assert_eq!(profile.rdf_format().unwrap(), Some(RdfFormat::NQuads));
+
+    let mut options = SyncOptions::default();
+    profile.apply(&mut options);
+    assert_eq!(
+        options.column_order[PERSON],
+        ["https://schema.org/worksFor", "https://schema.org/name"]
+    );
+    assert_eq!(
+        options.id_properties[PERSON].as_str(),
+        "https://schema.org/identifier"
+    );
+    assert!(
+        options
+            .skip_sheets
+            .contains("https___schema.org_Organization")
+    );
+
+    assert!(
+        Profile::discover(temp_dir.path())
+            .expect("no profile")
+            .is_none()
+    );
+    for config in [
+        json!({ "rdf-format": "rdf-xml" }),
+        json!({ "skip-sheet": ["Lookups"] }),
+    ] {
+        assert!(matches!(load(&config), Err(ToolError::InvalidConfig(_))));
+    }
+    // Profiles are TOML, not JSON.
+    fs::write(&path, r#"{ "rdf-format": "n-quads" }"#).unwrap();
+    assert!(matches!(
+        Profile::load(&path),
+        Err(ToolError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn profiles_shape_workbooks_and_the_nodes_read_from_them() {
+    let mut options = SyncOptions::default();
+    load(&profile())
+        .expect("profile loaded")
+        .apply(&mut options);
+
+    let workbook =
+        build_workbook_with_options(&staff(), &options.workbook_options()).expect("tables built");
+    let people_sheet = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == "https___schema.org_Person")
+        .expect("person sheet");
+    assert_eq!(
+        people_sheet.columns,
+        [
+            "id",
+            "graph",
+            "https://schema.org/worksForId",
+            "https://schema.org/name",
+            "https://schema.org/email",
+            "https://schema.org/identifier",
+            "https://schema.org/identifierId",
+            "https://schema.org/knowsId",
+        ]
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("workbook written");
+    let nodes = sync::read_excel(&path, &options).expect("workbook read");
+
+    // The organisation sheet is skipped, and people take their staff ids.
+    let ids: Vec<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "https://example.com/staff/ada",
+            "https://example.com/staff/grace"
+        ]
+    );
+    assert_eq!(
+        nodes[0].properties["https://schema.org/knows"],
+        PropertyValue::ObjectRef("https://example.com/staff/grace".into())
+    );
+
+    let output = temp_dir.path().join("people.jsonld");
+    sync::write_jsonld(&nodes, &output, None, &options).expect("JSON-LD written");
+    let document: Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(document["@context"]["staff"], "https://example.com/staff/");
+}
+
+#[test]
+fn clashing_ids_are_rejected() {
+    let mut nodes = staff();
+    let grace = nodes
+        .iter_mut()
+        .find(|node| node.id.as_str() == "https://example.com/people/2")
+        .unwrap();
+    grace.properties.insert(
+        "https://schema.org/identifier".into(),
+        PropertyValue::Scalar(ScalarValue::String("https://example.com/acme".into())),
+    );
+    let mut options = SyncOptions::default();
+    load(&profile())
+        .expect("profile loaded")
+        .apply(&mut options);
+    assert!(matches!(
+        sync::assign_ids(nodes, &options),
+        Err(ToolError::InvalidConfig(_))
+    ));
+}
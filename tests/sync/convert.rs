@@ -0,0 +1,86 @@
+use std::io::Cursor;
+
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::convert::Converter;
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use serde_json::{Value, json};
+
+const DOCUMENT: &str = r#"{
+    "@graph": [
+        {
+            "@id": "https://example.com/people/1",
+            "@type": "https://schema.org/Person",
+            "https://schema.org/name": "Ada",
+            "https://schema.org/knows": { "@id": "https://example.com/people/2" }
+        },
+        {
+            "@id": "https://example.com/people/2",
+            "@type": "https://schema.org/Person",
+            "https://schema.org/name": "Bob"
+        }
+    ]
+}"#;
+
+fn context() -> Value {
+    json!({
+        "@vocab": "https://schema.org/",
+        "people": "https://example.com/people/",
+        "knows": { "@type": "@id" }
+    })
+}
+
+#[test]
+fn workbooks_convert_through_buffers() {
+    let read = Converter::new()
+        .read_jsonld(DOCUMENT.as_bytes())
+        .expect("JSON-LD read");
+    let mut workbook = Cursor::new(Vec::new());
+    read.write_excel(&mut workbook).expect("workbook written");
+    assert!(!workbook.get_ref().is_empty());
+
+    workbook.set_position(0);
+    let from_excel = Converter::new()
+        .with_context(context())
+        .read_excel(workbook)
+        .expect("workbook read");
+    assert_eq!(from_excel.nodes(), read.nodes());
+
+    let mut output = Vec::new();
+    from_excel
+        .write_jsonld(&mut output)
+        .expect("JSON-LD written");
+    let document: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(document["@context"], context());
+    assert_eq!(document["@graph"][0]["knows"], "people:2");
+
+    let tables = read.to_workbook().expect("tables built");
+    let from_tables = Converter::new()
+        .read_workbook(&tables)
+        .expect("tables read");
+    assert_eq!(from_tables.into_nodes(), read.into_nodes());
+}
+
+#[test]
+fn rdf_converts_through_buffers() {
+    let triples = "<https://example.com/people/1> <https://schema.org/name> \"Ada\" .\n";
+    let mut turtle = Vec::new();
+    Converter::new()
+        .with_context(context())
+        .with_rdf_format(RdfFormat::Turtle)
+        .read_rdf(triples.as_bytes(), RdfFormat::NTriples)
+        .expect("RDF read")
+        .write_rdf(&mut turtle)
+        .expect("RDF written");
+    let turtle = String::from_utf8(turtle).unwrap();
+    assert!(turtle.contains("@prefix people: <https://example.com/people/> ."));
+    assert!(turtle.contains("people:1 schema:name \"Ada\""));
+
+    let limited = Converter::new()
+        .with_options(SyncOptions {
+            max_nodes: Some(1),
+            ..SyncOptions::default()
+        })
+        .read_jsonld(DOCUMENT.as_bytes());
+    assert!(matches!(limited, Err(ToolError::LimitExceeded { .. })));
+}
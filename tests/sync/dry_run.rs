@@ -0,0 +1,104 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::WorkbookOptions;
+use aideon_tools::aideon::tools::report::DryRunReport;
+use aideon_tools::aideon::tools::roundtrip::LossKind;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+fn document() -> serde_json::Value {
+    // A reference and a literal named `<predicate>Id` share one column.
+    json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada",
+                "https://example.com/vocab/employee": { "@id": "https://example.com/staff/42" },
+                "https://example.com/vocab/employeeId": "E-42"
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Bob"
+            }
+        ]
+    })
+}
+
+#[test]
+fn workbook_previews_list_sheets_and_losses() {
+    let nodes = parse_jsonld(&document());
+
+    let report =
+        DryRunReport::for_workbook(&nodes, &WorkbookOptions::default()).expect("preview built");
+    assert_eq!(report.summary.node_count, 2);
+    assert_eq!(report.summary.type_counts["https://schema.org/Person"], 2);
+    let person = report
+        .sheets
+        .iter()
+        .find(|sheet| sheet.sheet_name == "https___schema.org_Person")
+        .expect("type sheet previewed");
+    assert_eq!(person.row_count, 2);
+    assert!(
+        report
+            .losses
+            .iter()
+            .any(|loss| loss.kind == LossKind::PropertyLost)
+    );
+
+    let graph = DryRunReport::for_graph(&nodes);
+    assert!(graph.sheets.is_empty() && graph.losses.is_empty());
+    assert_eq!(graph.summary, report.summary);
+}
+
+#[test]
+fn dry_runs_write_nothing() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, document().to_string()).unwrap();
+    let output = temp_dir.path().join("people.xlsx");
+
+    let run = aideon()
+        .args(["--dry-run", "sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "excel", "--output"])
+        .arg(&output)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    assert!(!output.exists());
+    let stdout = String::from_utf8_lossy(&run.stdout);
+    assert!(stdout.starts_with("dry run: 2 node(s) would be written to "));
+    assert!(stdout.contains("type: https://schema.org/Person: 2 node(s)\n"));
+    assert!(stdout.contains("sheet: https___schema.org_Person: 2 row(s), "));
+    assert!(stdout.contains(
+        "property-lost: https://example.com/people/1 [https://example.com/vocab/employeeId]"
+    ));
+
+    let output = temp_dir.path().join("people.ttl");
+    fs::write(&output, "existing").unwrap();
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "rdf", "--output"])
+        .arg(&output)
+        .arg("--dry-run")
+        .output()
+        .expect("CLI ran");
+    assert!(run.status.success());
+    assert_eq!(fs::read_to_string(&output).unwrap(), "existing");
+    let stdout = String::from_utf8_lossy(&run.stdout);
+    assert!(stdout.contains("exists and would be replaced"));
+    assert!(!stdout.contains("sheet: "));
+
+    let run = aideon()
+        .args(["--dry-run", "validate", "--format", "json-ld", "--input"])
+        .arg(&input)
+        .output()
+        .expect("CLI ran");
+    assert!(!run.status.success());
+    assert!(String::from_utf8_lossy(&run.stderr).contains("--dry-run previews sync"));
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::filter::{IriPattern, NodeFilter};
+use aideon_tools::aideon::tools::io::jsonld;
+use calamine::{Reader, Xlsx, open_workbook};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success};
+
+const ONTOLOGY: &str = r#"{
+  "@graph": [
+    {
+      "@id": "https://example.com/people/1",
+      "@type": "https://schema.org/Person",
+      "https://schema.org/name": "Ada",
+      "https://schema.org/birthDate": "1815-12-10",
+      "https://schema.org/deathDate": "1852-11-27"
+    },
+    {
+      "@id": "https://example.com/orgs/1",
+      "@type": "https://schema.org/Organization",
+      "https://schema.org/name": "Analytical Engines"
+    },
+    {
+      "@id": "https://example.com/events/1",
+      "@type": "https://schema.org/BusinessEvent",
+      "https://schema.org/name": "Launch"
+    }
+  ]
+}"#;
+
+#[test]
+fn type_and_predicate_patterns_select_nodes_and_properties() {
+    let prefixes = BTreeMap::from([("schema".to_string(), "https://schema.org/".to_string())]);
+    let pattern = IriPattern::new("schema:*Date", &prefixes);
+    assert_eq!(pattern.as_str(), "https://schema.org/*Date");
+    assert!(pattern.matches("https://schema.org/birthDate"));
+    assert!(!pattern.matches("https://schema.org/name"));
+    assert!(
+        IriPattern::new("https://schema.org/name", &prefixes).matches("https://schema.org/name")
+    );
+
+    let nodes = jsonld::parse_jsonld_str(ONTOLOGY).expect("JSON-LD parsed");
+    let filter = NodeFilter::new()
+        .including_type(IriPattern::new("schema:*", &prefixes))
+        .excluding_type(IriPattern::new("*Event", &prefixes))
+        .excluding_predicate(pattern);
+    let kept = filter.apply(nodes);
+    let ids: Vec<&str> = kept.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        ["https://example.com/orgs/1", "https://example.com/people/1"]
+    );
+    let person = &kept[1];
+    let predicates: Vec<&str> = person.properties.keys().map(|iri| iri.as_str()).collect();
+    assert_eq!(predicates, ["https://schema.org/name"]);
+}
+
+#[test]
+fn sync_exports_only_the_included_types_and_columns() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("ontology.jsonld");
+    fs::write(&input, ONTOLOGY).unwrap();
+    let output = temp_dir.path().join("people.xlsx");
+
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "excel", "--output"])
+        .arg(&output)
+        .args(["--include-type", "schema:Person"])
+        .args(["--exclude-predicate", "schema:deathDate"])
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+
+    let mut workbook: Xlsx<_> = open_workbook(&output).expect("workbook opened");
+    let sheets = workbook.sheet_names();
+    assert!(sheets.contains(&"https___schema.org_Person".to_string()));
+    assert!(!sheets.iter().any(|sheet| sheet.contains("Organization")));
+    let range = workbook
+        .worksheet_range("https___schema.org_Person")
+        .expect("sheet read");
+    let header: Vec<String> = range
+        .rows()
+        .next()
+        .unwrap()
+        .iter()
+        .map(|cell| cell.to_string())
+        .collect();
+    assert!(header.contains(&"https://schema.org/birthDate".to_string()));
+    assert!(!header.contains(&"https://schema.org/deathDate".to_string()));
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::filter::IriPattern;
+use aideon_tools::aideon::tools::model::Iri;
+use aideon_tools::aideon::tools::sync;
+use calamine::{Reader, Xlsx, open_workbook};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld, string};
+
+const MODIFIED: &str = "https://schema.org/dateModified";
+
+fn person(name: &str, modified: Option<&str>) -> serde_json::Value {
+    let mut node = json!({
+        "@id": "https://example.com/people/1",
+        "@type": "https://schema.org/Person",
+        "https://schema.org/name": name
+    });
+    if let Some(modified) = modified {
+        node[MODIFIED] = json!(modified);
+    }
+    node
+}
+
+#[test]
+fn ignored_values_are_kept_from_the_previous_nodes() {
+    let prefixes = BTreeMap::from([("schema".to_string(), "https://schema.org/".to_string())]);
+    let ignored = [IriPattern::new("schema:date*", &prefixes)];
+    let previous = parse_jsonld(&json!([
+        person("Ada", Some("2024-01-01")),
+        { "@id": "https://example.com/people/2", "https://schema.org/dateModified": "2024-02-02" }
+    ]));
+    let edited = parse_jsonld(&json!([
+        person("Ada Lovelace", None),
+        { "@id": "https://example.com/people/3", "https://schema.org/name": "Grace" }
+    ]));
+
+    let nodes = sync::keep_ignored(edited, &previous, &ignored);
+    assert_eq!(nodes.len(), 2, "removed nodes stay removed");
+    let ada = &nodes[0];
+    assert_eq!(
+        ada.properties[&Iri::from("https://schema.org/name")],
+        string("Ada Lovelace")
+    );
+    assert_eq!(ada.properties[&Iri::from(MODIFIED)], string("2024-01-01"));
+    assert!(!nodes[1].properties.contains_key(&Iri::from(MODIFIED)));
+
+    let read = parse_jsonld(&person("Ada", Some("2025-05-05")));
+    let kept = sync::keep_ignored(read, &previous, &ignored);
+    assert_eq!(
+        kept[0].properties[&Iri::from(MODIFIED)],
+        string("2025-05-05")
+    );
+}
+
+fn sync(from: &str, input: &Path, to: &str, output: &Path) {
+    let run = aideon()
+        .args(["sync", "--from", from, "--input"])
+        .arg(input)
+        .args(["--to", to, "--output"])
+        .arg(output)
+        .args(["--ignore-predicate", "schema:dateModified"])
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+}
+
+#[test]
+fn ignored_predicates_skip_the_workbook_and_survive_the_round_trip() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let model = temp_dir.path().join("model.jsonld");
+    fs::write(&model, person("Ada", Some("2024-01-01")).to_string()).unwrap();
+    let workbook = temp_dir.path().join("model.xlsx");
+    sync("json-ld", &model, "excel", &workbook);
+
+    let mut excel: Xlsx<_> = open_workbook(&workbook).unwrap();
+    for sheet in excel.sheet_names() {
+        let range = excel.worksheet_range(&sheet).unwrap();
+        assert!(
+            !range.cells().any(|(_, _, cell)| *cell == MODIFIED),
+            "{sheet} names the ignored predicate"
+        );
+    }
+
+    sync("excel", &workbook, "json-ld", &model);
+    let nodes = sync::read_jsonld(&model, &Default::default()).unwrap();
+    assert_eq!(
+        nodes[0].properties[&Iri::from(MODIFIED)],
+        string("2024-01-01")
+    );
+}
@@ -0,0 +1,48 @@
+use std::fs;
+
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::interrupt;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use tempfile::tempdir;
+
+// Cancellation is process-wide, so this binary holds a single test.
+#[test]
+fn requested_cancellation_stops_conversions_and_keeps_outputs() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.nt");
+    let triples: String = (1..=5)
+        .map(|index| {
+            format!(
+                "<https://example.com/people/{index}> <https://schema.org/name> \"Person {index}\" .\n"
+            )
+        })
+        .collect();
+    fs::write(&input, triples).unwrap();
+    let output = temp_dir.path().join("people.jsonld");
+    fs::write(&output, "{}").unwrap();
+    let streamed = SyncOptions {
+        chunk_size: Some(2),
+        ..SyncOptions::default()
+    };
+
+    interrupt::request();
+    assert!(interrupt::is_requested());
+    assert_eq!(interrupt::signal(), None);
+    for options in [&streamed, &SyncOptions::default()] {
+        let err = sync::rdf_to_jsonld_with_options(&input, &output, None, options).unwrap_err();
+        assert!(matches!(err, ToolError::Interrupted { nodes: 0 }));
+        assert_eq!(err.to_string(), "interrupted after processing 0 node(s)");
+    }
+    let excel = temp_dir.path().join("people.xlsx");
+    let err = sync::rdf_to_excel_with_options(&input, &excel, &streamed).unwrap_err();
+    assert!(matches!(err, ToolError::Interrupted { .. }));
+    assert!(!excel.exists());
+    assert_eq!(fs::read_to_string(&output).unwrap(), "{}");
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+
+    interrupt::reset();
+    sync::rdf_to_jsonld_with_options(&input, &output, None, &streamed).expect("JSON-LD written");
+    sync::jsonld_to_excel_with_options(&output, &excel, &SyncOptions::default())
+        .expect("workbook written");
+    assert!(excel.exists());
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::flatten::{CellEncoding, build_workbook_with};
+use aideon_tools::aideon::tools::io::coercion::ScalarCoercions;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write, jsonld};
+use aideon_tools::aideon::tools::model::{Iri, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::validate::{IssueKind, Severity};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, parse_jsonld};
+
+const AGE: &str = "https://schema.org/age";
+const NAME: &str = "https://schema.org/name";
+
+/// Writes a workbook whose two people have unreadable ages.
+fn write_unreadable_ages(path: &Path) {
+    let nodes = parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada",
+                "https://schema.org/age": 36
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Grace",
+                "https://schema.org/age": 85
+            }
+        ]
+    }));
+    let mut workbook = build_workbook_with(&nodes, CellEncoding::Bare).expect("tables built");
+    let people = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == "https___schema.org_Person")
+        .expect("Person sheet");
+    let age = people
+        .columns
+        .iter()
+        .position(|column| column == AGE)
+        .expect("age column");
+    people.rows[0][age] = "thirty-six".into();
+    people.rows[1][age] = "old".into();
+    excel_write::write_workbook(path, &workbook).unwrap();
+}
+
+#[test]
+fn readers_report_every_problem_and_keep_the_rest() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    write_unreadable_ages(&path);
+
+    let (nodes, report) =
+        excel_read::read_nodes_keep_going(&path, &ScalarCoercions::default(), &BTreeSet::new())
+            .expect("workbook read past its problems");
+    assert_eq!(nodes.len(), 2);
+    assert!(nodes.iter().all(|node| {
+        node.properties.contains_key(&Iri::from(NAME))
+            && !node.properties.contains_key(&Iri::from(AGE))
+    }));
+    let invalid: Vec<_> = report
+        .issues
+        .iter()
+        .filter(|issue| issue.kind == IssueKind::InvalidCell)
+        .collect();
+    assert_eq!(invalid.len(), 2, "{:?}", report.issues);
+    assert_eq!(invalid[1].node.as_str(), "https://example.com/people/2");
+    assert!(
+        invalid[1].message.contains("'old'"),
+        "{}",
+        invalid[1].message
+    );
+    assert_eq!(report.error_count(), 2);
+
+    let triples = "<https://example.com/people/1> <https://schema.org/name> \"Ada\" .\n\
+                   <https://example.com/people/1> <https://schema.org/age> thirty-six .\n\
+                   <https://example.com/people/2> <https://schema.org/name> \"Grace\" .\n";
+    let (nodes, report) =
+        rdf::read_rdf_keep_going_from(triples.as_bytes(), RdfFormat::NTriples).unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(
+        nodes[1].properties[&Iri::from(NAME)],
+        PropertyValue::Scalar(ScalarValue::String("Grace".into()))
+    );
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(report.issues[0].kind, IssueKind::InvalidStatement);
+    assert_eq!(report.issues[0].severity, Severity::Error);
+    assert!(rdf::read_rdf_from(triples.as_bytes(), RdfFormat::NTriples).is_err());
+}
+
+#[test]
+fn keep_going_writes_the_output_and_lists_the_problems() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.xlsx");
+    write_unreadable_ages(&input);
+    let output = temp_dir.path().join("people.jsonld");
+
+    let run = aideon()
+        .args(["sync", "--from", "excel", "--input"])
+        .arg(&input)
+        .args(["--to", "json-ld", "--output"])
+        .arg(&output)
+        .arg("--keep-going")
+        .output()
+        .expect("CLI ran");
+    assert!(!run.status.success());
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    for text in ["'thirty-six'", "'old'", "validation failed with 2 error(s)"] {
+        assert!(stderr.contains(text), "{stderr}");
+    }
+    let nodes = jsonld::parse_jsonld_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(nodes.len(), 2);
+
+    let run = aideon()
+        .args(["validate", "--format", "excel", "--input"])
+        .arg(&input)
+        .arg("--keep-going")
+        .output()
+        .expect("CLI ran");
+    assert!(!run.status.success());
+    let stdout = String::from_utf8_lossy(&run.stdout);
+    assert_eq!(stdout.matches("error: ").count(), 2, "{stdout}");
+}
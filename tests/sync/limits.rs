@@ -0,0 +1,118 @@
+use std::fs;
+
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::flatten::{WorkbookOptions, build_workbook_with_options};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+fn document() -> Value {
+    let people: Vec<Value> = (1..=3)
+        .map(|index| {
+            json!({
+                "@id": format!("https://example.com/people/{index}"),
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": format!("Person {index}")
+            })
+        })
+        .collect();
+    json!({ "@graph": people })
+}
+
+#[test]
+fn node_limits_stop_conversions_before_writing() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, document().to_string()).unwrap();
+    let output = temp_dir.path().join("people.xlsx");
+    let options = SyncOptions {
+        max_nodes: Some(2),
+        ..SyncOptions::default()
+    };
+    let err = sync::jsonld_to_excel_with_options(&input, &output, &options).unwrap_err();
+    assert!(matches!(
+        err,
+        ToolError::LimitExceeded {
+            what: "nodes",
+            found: 3,
+            limit: 2
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "conversion stopped: 3 nodes exceed the limit of 2"
+    );
+    assert!(!output.exists());
+
+    let options = SyncOptions {
+        max_nodes: Some(3),
+        ..SyncOptions::default()
+    };
+    sync::jsonld_to_excel_with_options(&input, &output, &options).expect("workbook written");
+}
+
+#[test]
+fn streamed_conversions_remove_partial_output() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.nt");
+    let triples: String = (1..=5)
+        .map(|index| {
+            format!(
+                "<https://example.com/people/{index}> <https://schema.org/name> \"Person {index}\" .\n"
+            )
+        })
+        .collect();
+    fs::write(&input, triples).unwrap();
+    let options = SyncOptions {
+        chunk_size: Some(2),
+        max_nodes: Some(3),
+        ..SyncOptions::default()
+    };
+
+    let jsonld = temp_dir.path().join("people.jsonld");
+    let err = sync::rdf_to_jsonld_with_options(&input, &jsonld, None, &options).unwrap_err();
+    assert!(matches!(
+        err,
+        ToolError::LimitExceeded { what: "nodes", .. }
+    ));
+    assert!(!jsonld.exists());
+
+    let excel = temp_dir.path().join("people.xlsx");
+    let err = sync::rdf_to_excel_with_options(&input, &excel, &options).unwrap_err();
+    assert!(matches!(
+        err,
+        ToolError::LimitExceeded { what: "nodes", .. }
+    ));
+    assert!(!excel.exists());
+}
+
+#[test]
+fn cell_limits_count_every_sheet() {
+    let nodes = parse_jsonld(&document());
+    let unlimited =
+        build_workbook_with_options(&nodes, &WorkbookOptions::default()).expect("tables built");
+    let cells: usize = unlimited
+        .tables
+        .iter()
+        .map(|table| (table.rows.len() + 1) * table.columns.len())
+        .sum();
+
+    let at_limit = WorkbookOptions {
+        max_cells: Some(cells),
+        ..WorkbookOptions::default()
+    };
+    assert_eq!(
+        build_workbook_with_options(&nodes, &at_limit).expect("tables built"),
+        unlimited
+    );
+    let below = WorkbookOptions {
+        max_cells: Some(cells - 1),
+        ..WorkbookOptions::default()
+    };
+    assert!(matches!(
+        build_workbook_with_options(&nodes, &below),
+        Err(ToolError::LimitExceeded { what: "cells", found, .. }) if found == cells
+    ));
+}
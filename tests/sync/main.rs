@@ -0,0 +1,23 @@
+//! Integration tests of the sync pipeline and the command line around it.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod atomic;
+mod batch;
+mod config;
+mod convert;
+mod dry_run;
+mod export_filters;
+mod ignored_predicates;
+mod interrupt;
+mod keep_going;
+mod limits;
+mod natural_keys;
+mod node_conditions;
+mod provenance;
+mod script;
+mod stdio;
+mod streams;
+mod telemetry;
+mod tracing_stats;
@@ -0,0 +1,119 @@
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::{excel_write, jsonld};
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const ORDER: &str = "https://schema.org/Order";
+const NUMBER: &str = "https://schema.org/orderNumber";
+const SELLER: &str = "https://schema.org/seller";
+const TOTAL: &str = "https://schema.org/totalPrice";
+
+fn orders(first: &str, total: f64) -> Value {
+    json!({
+        "@graph": [
+            {
+                "@id": first,
+                "@type": ORDER,
+                "https://schema.org/orderNumber": "A-1 ",
+                "https://schema.org/seller": { "@id": "https://example.com/shop" },
+                "https://schema.org/totalPrice": total
+            },
+            {
+                "@id": "urn:uuid:00000000-0000-0000-0000-000000000002",
+                "@type": ORDER,
+                "https://schema.org/orderNumber": "A-2"
+            },
+            {
+                "@id": "https://example.com/orders/3",
+                "@type": ORDER,
+                "https://schema.org/orderNumber": "A-3",
+                "https://schema.org/seller": { "@id": "https://example.com/shop" }
+            },
+            {
+                "@id": "https://example.com/invoices/1",
+                "https://schema.org/referencesOrder": { "@id": first }
+            }
+        ]
+    })
+}
+
+fn options() -> SyncOptions {
+    SyncOptions {
+        natural_keys: [(ORDER.into(), vec![NUMBER.into(), SELLER.into()])].into(),
+        ..SyncOptions::default()
+    }
+}
+
+fn keyed(document: &Value) -> Vec<Node> {
+    let nodes = jsonld::parse_jsonld_document(document).expect("JSON-LD parsed");
+    sync::natural_key_ids(nodes, &options()).expect("natural keys applied")
+}
+
+fn order_id(nodes: &[Node]) -> String {
+    let order = nodes
+        .iter()
+        .find(|node| {
+            node.properties.get(NUMBER)
+                == Some(&PropertyValue::Scalar(ScalarValue::String("A-1 ".into())))
+        })
+        .expect("order A-1");
+    order.id.to_string()
+}
+
+#[test]
+fn surrogate_ids_follow_the_natural_key_of_their_type() {
+    let nodes = keyed(&orders(
+        "urn:uuid:00000000-0000-0000-0000-000000000001",
+        30.0,
+    ));
+    let id = order_id(&nodes);
+    assert!(id.starts_with("urn:uuid:"), "{id}");
+    assert_ne!(id, "urn:uuid:00000000-0000-0000-0000-000000000001");
+    let invoice = nodes
+        .iter()
+        .find(|node| node.id.as_str() == "https://example.com/invoices/1")
+        .unwrap();
+    assert_eq!(
+        invoice.properties["https://schema.org/referencesOrder"],
+        PropertyValue::ObjectRef(id.as_str().into())
+    );
+    let ids: Vec<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+    assert!(ids.contains(&"urn:uuid:00000000-0000-0000-0000-000000000002"));
+    assert!(ids.contains(&"https://example.com/orders/3"));
+
+    // Another surrogate and another total keep the id.
+    assert_eq!(order_id(&keyed(&orders("_:order", 45.5))), id);
+
+    let mut clash = orders("_:order", 30.0);
+    clash["@graph"][2]["@id"] = json!("_:copy");
+    clash["@graph"][2]["https://schema.org/orderNumber"] = json!("A-1");
+    let nodes = parse_jsonld(&clash);
+    let err = sync::natural_key_ids(nodes, &options()).unwrap_err();
+    assert!(err.to_string().contains("share the natural key"), "{err}");
+}
+
+#[test]
+fn re_read_workbooks_keep_the_ids_of_edited_rows() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let mut ids = Vec::new();
+    for (first, total) in [("_:b0", 30.0), ("_:b7", 99.0)] {
+        let nodes = parse_jsonld(&orders(first, total));
+        let path = temp_dir.path().join("orders.xlsx");
+        excel_write::write_workbook(&path, &build_workbook(&nodes).unwrap()).unwrap();
+        let nodes = sync::read_excel(&path, &options()).expect("workbook read");
+        let order = nodes
+            .iter()
+            .find(|node| node.id.as_str() == order_id(&nodes))
+            .unwrap();
+        assert_eq!(
+            order.properties[TOTAL],
+            PropertyValue::Scalar(ScalarValue::Double(total))
+        );
+        ids.push(order.id.clone());
+    }
+    assert_eq!(ids[0], ids[1]);
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::io::excel_read;
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::query::{self, NodeCondition};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+fn people_and_employers() -> Value {
+    json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/age": 36,
+                "https://schema.org/worksFor": { "@id": "https://example.com/acme" },
+                "https://schema.org/address": {
+                    "@id": "_:address",
+                    "https://schema.org/addressLocality": "London"
+                }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/age": 12
+            },
+            {
+                "@id": "https://example.com/people/3",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/age": 41,
+                "https://schema.org/worksFor": { "@id": "https://example.com/initech" }
+            },
+            {
+                "@id": "https://example.com/acme",
+                "@type": "https://schema.org/Organization",
+                "https://schema.org/name": "ACME"
+            },
+            {
+                "@id": "https://example.com/initech",
+                "@type": "https://schema.org/Organization"
+            }
+        ]
+    })
+}
+
+fn ids(nodes: &[Node]) -> Vec<&str> {
+    let mut ids: Vec<&str> = nodes
+        .iter()
+        .map(|node| node.id.as_str())
+        .filter(|id| !id.starts_with("_:"))
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn conditions_keep_and_drop_the_nodes_they_bind() {
+    let nodes = parse_jsonld(&people_and_employers());
+    let prefixes: BTreeMap<String, String> =
+        [("schema".to_string(), "https://schema.org/".to_string())].into();
+
+    let employed = NodeCondition::keeping("?node schema:worksFor/schema:name ?name", &prefixes);
+    assert!(employed.keeps());
+    let kept = query::apply_conditions(nodes.clone(), &[employed]).unwrap();
+    assert_eq!(ids(&kept), ["https://example.com/people/1"]);
+    assert_eq!(kept.len(), 2, "the address blank node comes along");
+
+    let minors = NodeCondition::dropping("?node schema:age ?age FILTER(?age < 18)", &prefixes);
+    let kept = query::apply_conditions(nodes.clone(), std::slice::from_ref(&minors)).unwrap();
+    assert_eq!(
+        ids(&kept),
+        [
+            "https://example.com/acme",
+            "https://example.com/initech",
+            "https://example.com/people/1",
+            "https://example.com/people/3",
+        ]
+    );
+
+    let unnamed = NodeCondition::keeping(
+        "?node a schema:Person FILTER NOT EXISTS { ?node schema:worksFor/schema:name ?name }",
+        &prefixes,
+    );
+    let kept = query::apply_conditions(nodes.clone(), &[unnamed, minors]).unwrap();
+    assert_eq!(ids(&kept), ["https://example.com/people/3"]);
+
+    let err =
+        query::apply_conditions(nodes, &[NodeCondition::keeping("?node", &prefixes)]).unwrap_err();
+    assert!(err.to_string().contains("SPARQL"), "{err}");
+}
+
+#[test]
+fn sync_applies_include_and_exclude_where() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, people_and_employers().to_string()).unwrap();
+    let output = temp_dir.path().join("people.xlsx");
+
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "excel", "--output"])
+        .arg(&output)
+        .args([
+            "--include-type",
+            "schema:Person",
+            "--exclude-where",
+            "?node schema:age ?age FILTER(?age < 18)",
+            "--chunk-size",
+            "1",
+        ])
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+
+    let nodes = excel_read::read_nodes(&output).expect("workbook read");
+    assert_eq!(
+        ids(&nodes),
+        [
+            "https://example.com/people/1",
+            "https://example.com/people/3"
+        ]
+    );
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::coercion::ScalarCoercions;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{Iri, PropertyValue};
+use aideon_tools::aideon::tools::provenance::{CellProvenance, PROV_NAMESPACE};
+use serde_json::Value;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld, people, references};
+
+const PERSON_SHEET: &str = "https___schema.org_Person";
+const NAME: &str = "https://schema.org/name";
+const KNOWS: &str = "https://schema.org/knows";
+
+fn write_people(path: &Path) {
+    let mut document = people();
+    document["@graph"][0]["https://schema.org/knows"] = references(&[2, 3]);
+    let workbook = build_workbook(&parse_jsonld(&document)).expect("tables built");
+    excel_write::write_workbook(path, &workbook).unwrap();
+}
+
+#[test]
+fn every_value_is_traced_to_its_cell() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    write_people(&path);
+
+    let (nodes, records) = excel_read::read_nodes_with_provenance(
+        &path,
+        &ScalarCoercions::default(),
+        &BTreeSet::new(),
+    )
+    .expect("workbook read");
+    assert_eq!(nodes.len(), 2);
+    let of = |id: &str, predicate: &str| -> Vec<&CellProvenance> {
+        records
+            .iter()
+            .filter(|record| record.node.as_str() == id && record.predicate.as_str() == predicate)
+            .collect()
+    };
+    let name = of("https://example.com/people/2", NAME);
+    assert_eq!(name.len(), 1, "{records:?}");
+    assert_eq!(
+        (name[0].sheet.as_str(), name[0].row, name[0].column.as_str()),
+        (PERSON_SHEET, 3, NAME)
+    );
+    let knows = of("https://example.com/people/1", KNOWS);
+    let rows: Vec<usize> = knows.iter().map(|record| record.row).collect();
+    assert_eq!(rows, [2, 3], "{records:?}");
+    assert!(knows.iter().all(|record| record.sheet != PERSON_SHEET));
+}
+
+#[test]
+fn sync_writes_provenance_as_json_or_prov_statements() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.xlsx");
+    write_people(&input);
+    for name in ["cells.json", "cells.ttl"] {
+        let run = aideon()
+            .args(["sync", "--from", "excel", "--input"])
+            .arg(&input)
+            .args(["--to", "json-ld", "--output"])
+            .arg(temp_dir.path().join("people.jsonld"))
+            .arg("--provenance")
+            .arg(temp_dir.path().join(name))
+            .output()
+            .expect("CLI ran");
+        assert_success(&run);
+    }
+
+    let records: Vec<Value> =
+        serde_json::from_str(&fs::read_to_string(temp_dir.path().join("cells.json")).unwrap())
+            .unwrap();
+    assert!(
+        records
+            .iter()
+            .any(|record| record["node"] == "https://example.com/people/1"
+                && record["predicate"] == NAME
+                && record["sheet"] == PERSON_SHEET
+                && record["row"] == 2)
+    );
+
+    let statements = rdf::read_rdf(&temp_dir.path().join("cells.ttl"), Some(RdfFormat::Turtle))
+        .expect("PROV statements read");
+    let derived = Iri::from(format!("{PROV_NAMESPACE}wasDerivedFrom"));
+    let cells: Vec<&str> = statements
+        .iter()
+        .filter_map(|node| match node.properties.get(&derived) {
+            Some(PropertyValue::ObjectRef(cell)) => Some(cell.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(cells.len(), records.len());
+    assert!(
+        cells
+            .iter()
+            .all(|cell| cell.starts_with("file://") && cell.contains("people.xlsx#sheet=")),
+        "{cells:?}"
+    );
+}
@@ -0,0 +1,80 @@
+use aideon_tools::aideon::tools::script::NodeScript;
+
+#[cfg(not(feature = "scripting"))]
+#[test]
+fn scripts_need_the_scripting_feature() {
+    let Err(err) = NodeScript::from_source("fn transform(node) { node }") else {
+        panic!("script compiled without the scripting feature");
+    };
+    assert_eq!(
+        err.to_string(),
+        "script error: transform scripts require the `scripting` feature"
+    );
+}
+
+#[cfg(feature = "scripting")]
+#[test]
+fn scripts_change_drop_and_emit_nodes() {
+    use std::fs;
+
+    use aideon_tools::aideon::tools::model::{PropertyValue, ScalarValue};
+    use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+    use tempfile::tempdir;
+
+    const SCRIPT: &str = r#"
+        fn transform(node) {
+            let name = "https://schema.org/name";
+            if node[name] == "Draft" { return; }
+            node[name] = node[name].to_upper();
+            if node["@id"] != "https://example.com/people/2" { return node; }
+            let badge = #{ "@id": "https://example.com/badges/2", "@type": "https://schema.org/Badge" };
+            node["https://schema.org/award"] = #{ "@id": badge["@id"] };
+            [node, badge]
+        }
+    "#;
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.trig");
+    fs::write(
+        &input,
+        r#"<https://example.com/graphs/hr> {
+    <https://example.com/people/1> <https://schema.org/name> "Ada" .
+    <https://example.com/people/2> <https://schema.org/name> "Bob" .
+    <https://example.com/people/3> <https://schema.org/name> "Draft" .
+}
+"#,
+    )
+    .unwrap();
+    let options = SyncOptions {
+        script: Some(NodeScript::from_source(SCRIPT).expect("script compiled")),
+        ..SyncOptions::default()
+    };
+
+    let nodes = sync::read_rdf(&input, None, &options).expect("RDF read");
+    let ids: Vec<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "https://example.com/people/1",
+            "https://example.com/badges/2",
+            "https://example.com/people/2"
+        ]
+    );
+    assert_eq!(
+        nodes[0].properties.get("https://schema.org/name"),
+        Some(&PropertyValue::Scalar(ScalarValue::String("ADA".into())))
+    );
+    assert!(nodes[2].properties.contains_key("https://schema.org/award"));
+    assert!(
+        nodes
+            .iter()
+            .all(|node| node.graph.as_deref() == Some("https://example.com/graphs/hr"))
+    );
+
+    let Err(err) = NodeScript::from_source("fn other(node) { node }") else {
+        panic!("script without transform compiled");
+    };
+    assert_eq!(
+        err.to_string(),
+        "script error: the script does not define `fn transform(node)`"
+    );
+}
@@ -0,0 +1,74 @@
+use std::io::Write;
+use std::process::{Output, Stdio};
+
+use serde_json::Value;
+
+use crate::common::aideon;
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = aideon()
+        .args(args)
+        .args(["--log-level", "error"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("CLI started");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().expect("CLI finished")
+}
+
+#[test]
+fn pipelines_convert_through_stdin_and_stdout() {
+    let document = r#"{"@id": "https://example.com/ada", "https://schema.org/name": "Ada"}"#;
+    let rdf = run(
+        &[
+            "sync",
+            "--from",
+            "json-ld",
+            "--to",
+            "rdf",
+            "--input",
+            "-",
+            "--output",
+            "-",
+            "--rdf-format",
+            "n-triples",
+        ],
+        document,
+    );
+    assert!(rdf.status.success());
+    let triples = String::from_utf8(rdf.stdout).unwrap();
+    assert_eq!(
+        triples,
+        "<https://example.com/ada> <https://schema.org/name> \"Ada\" .\n"
+    );
+
+    let jsonld = run(
+        &[
+            "sync", "--from", "rdf", "--to", "json-ld", "--input", "-", "--output", "-",
+        ],
+        &triples,
+    );
+    assert!(jsonld.status.success());
+    let written: Value = serde_json::from_slice(&jsonld.stdout).unwrap();
+    assert_eq!(written["@graph"][0]["https://schema.org/name"], "Ada");
+
+    let excel = run(
+        &[
+            "sync", "--from", "json-ld", "--to", "excel", "--input", "-", "--output", "-",
+        ],
+        document,
+    );
+    assert!(!excel.status.success());
+    assert!(
+        String::from_utf8(excel.stderr)
+            .unwrap()
+            .contains("xlsx cannot be read from stdin or written to stdout")
+    );
+}
@@ -0,0 +1,90 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Cursor;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::coercion::ScalarCoercions;
+use aideon_tools::aideon::tools::io::csv_dialect::CsvDialect;
+use aideon_tools::aideon::tools::io::rdf::{self, RdfFormat};
+use aideon_tools::aideon::tools::io::{csv_read, csv_write, excel_read, excel_write, jsonld};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use serde_json::{Value, json};
+
+const DOCUMENT: &str = r#"[
+    {
+        "@id": "https://example.com/people/1",
+        "@type": "https://schema.org/Person",
+        "https://schema.org/name": "Ada"
+    },
+    {
+        "@id": "https://example.com/people/2",
+        "@type": "https://schema.org/Person",
+        "https://schema.org/name": "Bob"
+    }
+]"#;
+
+#[test]
+fn csv_tables_convert_through_named_buffers() {
+    let nodes = jsonld::read_jsonld_from(DOCUMENT.as_bytes()).expect("JSON-LD read");
+    let workbook = build_workbook(&nodes).expect("tables built");
+
+    // Stands in for the entries of a zip archive.
+    let mut archive = BTreeMap::new();
+    for table in &workbook.tables {
+        let mut entry = Vec::new();
+        csv_write::write_table_to(&mut entry, table, &CsvDialect::default())
+            .expect("table written");
+        archive.insert(csv_write::table_file_name(&table.sheet_name), entry);
+    }
+    assert!(archive.contains_key("entities.csv"));
+
+    let open = |name: &str| Ok(archive.get(name).map(|entry| entry.as_slice()));
+    let dialect = CsvDialect::default();
+    let read = csv_read::read_nodes_from(
+        open,
+        &dialect,
+        &ScalarCoercions::default(),
+        &BTreeSet::new(),
+    )
+    .expect("CSV read");
+    assert_eq!(read, nodes);
+    assert_eq!(
+        csv_read::read_context_from(open, &dialect).expect("context read"),
+        None
+    );
+
+    let mut excel = Cursor::new(Vec::new());
+    excel_write::write_workbook_to(&mut excel, &workbook).expect("workbook written");
+    excel.set_position(0);
+    let (read, report) = excel_read::read_nodes_with_report_from(
+        excel,
+        &ScalarCoercions::default(),
+        &BTreeSet::new(),
+    )
+    .expect("workbook read");
+    assert_eq!(read, nodes);
+    assert_eq!(report.error_count() + report.warning_count(), 0);
+}
+
+#[test]
+fn rdf_and_jsonld_stream_through_buffers() {
+    let nodes = jsonld::read_jsonld_from(DOCUMENT.as_bytes()).expect("JSON-LD read");
+    let mut triples = Vec::new();
+    rdf::write_rdf_to(&mut triples, &nodes, RdfFormat::NTriples, &BTreeMap::new())
+        .expect("RDF written");
+
+    let batches: Vec<usize> = rdf::stream_nodes_from(triples.as_slice(), RdfFormat::NTriples, 1)
+        .map(|batch| batch.expect("batch read").len())
+        .collect();
+    assert_eq!(batches, [1, 1]);
+
+    let mut output = Vec::new();
+    let context = json!({ "@vocab": "https://schema.org/" });
+    sync::write_jsonld_to(&nodes, &mut output, Some(context), &SyncOptions::default())
+        .expect("JSON-LD written");
+    let document: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(document["@graph"][1]["name"], "Bob");
+    assert_eq!(
+        jsonld::read_jsonld_from(output.as_slice()).expect("JSON-LD read"),
+        nodes
+    );
+}
@@ -0,0 +1,111 @@
+use aideon_tools::aideon::tools::telemetry::{self, Counter};
+use tracing_subscriber::Registry;
+
+#[test]
+fn counters_are_named_for_dashboards() {
+    assert_eq!(Counter::NodesRead.name(), "aideon.nodes.read");
+    assert_eq!(Counter::TriplesWritten.name(), "aideon.triples.written");
+    assert_eq!(Counter::CellsWritten.name(), "aideon.cells.written");
+}
+
+#[cfg(not(feature = "otel"))]
+#[test]
+fn exporting_needs_the_otel_feature() {
+    let Err(err) = telemetry::layer::<Registry>("http://localhost:4318") else {
+        panic!("telemetry exported without the otel feature");
+    };
+    assert_eq!(
+        err.to_string(),
+        "telemetry error: exporting to 'http://localhost:4318' requires the `otel` feature"
+    );
+}
+
+#[cfg(feature = "otel")]
+#[test]
+fn spans_and_counters_reach_the_collector() {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+    use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+    use tempfile::tempdir;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    // Answers every OTLP request with an empty success and records what it
+    // was sent.
+    let listener = TcpListener::bind("127.0.0.1:0").expect("collector bound");
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    type Requests = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+    let received = Requests::default();
+    let requests = Arc::clone(&received);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = BufReader::new(stream.unwrap());
+            loop {
+                let mut request_line = String::new();
+                if stream.read_line(&mut request_line).unwrap_or(0) == 0 {
+                    break;
+                }
+                let path = request_line.split(' ').nth(1).unwrap_or("").to_string();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    stream.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                stream.read_exact(&mut body).unwrap();
+                requests.lock().unwrap().push((path, body));
+                stream
+                    .get_mut()
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .unwrap();
+            }
+        }
+    });
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(
+        &input,
+        r#"{"@id": "https://example.com/people/1", "https://schema.org/name": "Ada"}"#,
+    )
+    .unwrap();
+    let output = temp_dir.path().join("people.nt");
+
+    let (layer, exporters) = telemetry::layer::<Registry>(&endpoint).expect("exporters built");
+    let subscriber = Registry::default().with(layer);
+    tracing::subscriber::with_default(subscriber, || {
+        sync::jsonld_to_rdf_with_options(
+            &input,
+            &output,
+            RdfFormat::NTriples,
+            &SyncOptions::default(),
+        )
+        .expect("RDF written")
+    });
+    exporters.shutdown().expect("telemetry flushed");
+
+    let received = received.lock().unwrap();
+    let contains = |path: &str, text: &str| {
+        received.iter().any(|(sent, body)| {
+            sent == path
+                && body
+                    .windows(text.len())
+                    .any(|window| window == text.as_bytes())
+        })
+    };
+    assert!(contains("/v1/traces", "jsonld_to_rdf"));
+    assert!(contains("/v1/traces", "triple_count"));
+    assert!(contains("/v1/metrics", "aideon.nodes.read"));
+    assert!(contains("/v1/metrics", "aideon.triples.written"));
+}
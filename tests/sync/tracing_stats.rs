@@ -0,0 +1,82 @@
+use std::fs;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use aideon_tools::aideon::tools::io::rdf::RdfFormat;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use tempfile::tempdir;
+use tracing::Level;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// Log destination shared with the test.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn logged(run: impl FnOnce()) -> String {
+    let captured = Captured::default();
+    let writer = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_max_level(Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(move || writer.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, run);
+    let bytes = captured.0.lock().unwrap().clone();
+    String::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn sync_spans_carry_graph_type_and_cell_statistics() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(
+        &input,
+        r#"[
+            {
+                "@id": "https://example.com/staff",
+                "@graph": [
+                    {
+                        "@id": "https://example.com/people/1",
+                        "@type": "https://schema.org/Person",
+                        "https://schema.org/name": "Ada"
+                    }
+                ]
+            },
+            { "@id": "https://example.com/teams/1", "https://schema.org/name": "Core" }
+        ]"#,
+    )
+    .unwrap();
+    let options = SyncOptions::default();
+
+    let rdf = temp_dir.path().join("people.nq");
+    let logs = logged(|| {
+        sync::jsonld_to_rdf_with_options(&input, &rdf, RdfFormat::NQuads, &options)
+            .expect("RDF written")
+    });
+    assert!(logs.contains("nodes in graph graph=\"https://example.com/staff\" node_count=1"));
+    assert!(logs.contains("nodes in graph graph=\"(none)\" node_count=1"));
+    assert!(logs.contains(
+        "triples written for type type_name=\"https://schema.org/Person\" triple_count=2"
+    ));
+    assert!(logs.contains("triples written for type type_name=\"(none)\" triple_count=1"));
+    assert!(logs.contains("format=NQuads node_count=2 triple_count=3}"));
+
+    let excel = temp_dir.path().join("people.xlsx");
+    let logs = logged(|| {
+        sync::jsonld_to_excel_with_options(&input, &excel, &options).expect("workbook written")
+    });
+    assert!(logs.contains("cells written cell_count="));
+    assert!(logs.contains("node_count=2 cell_count="));
+}
@@ -0,0 +1,251 @@
+use aideon_tools::aideon::tools::flatten::{CellEncoding, build_workbook, build_workbook_with};
+use aideon_tools::aideon::tools::io::{csv_read, csv_write, excel_read};
+use aideon_tools::aideon::tools::model::{ArrayValue, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use std::fs;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+fn sample_document() -> serde_json::Value {
+    serde_json::json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Alice, \"Al\"\nSmith",
+                "https://schema.org/age": 30,
+                "https://schema.org/knows": [
+                    {"@id": "https://example.com/people/2"},
+                    {"@id": "https://example.com/people/3"}
+                ]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Bob"
+            },
+            {
+                "@id": "https://example.com/graphs/named",
+                "@graph": [
+                    {
+                        "@id": "https://example.com/people/3",
+                        "https://schema.org/name": "Carol"
+                    }
+                ]
+            }
+        ]
+    })
+}
+
+#[test]
+fn csv_directory_roundtrip_preserves_nodes() {
+    let nodes = parse_jsonld(&sample_document());
+    let workbook = build_workbook(&nodes).expect("tables built");
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let csv_dir = temp_dir.path().join("tables");
+    csv_write::write_workbook(&csv_dir, &workbook).expect("CSV written");
+
+    assert!(csv_dir.join("entities.csv").is_file());
+    assert!(csv_dir.join("metadata.csv").is_file());
+    for table in &workbook.tables {
+        assert!(csv_write::table_path(&csv_dir, &table.sheet_name).is_file());
+    }
+
+    let restored = csv_read::read_nodes(&csv_dir).expect("CSV read");
+    assert_eq!(nodes, restored);
+}
+
+#[test]
+fn csv_and_excel_convert_into_each_other() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("input.jsonld");
+    fs::write(&input, sample_document().to_string()).expect("input written");
+
+    let options = SyncOptions::default();
+    let csv_dir = temp_dir.path().join("tables");
+    sync::jsonld_to_csv(&input, &csv_dir, &options).expect("JSON-LD to CSV");
+
+    let xlsx_path = temp_dir.path().join("tables.xlsx");
+    sync::csv_to_excel(&csv_dir, &xlsx_path, &options).expect("CSV to Excel");
+
+    let from_csv = csv_read::read_nodes(&csv_dir).expect("CSV read");
+    let from_excel = excel_read::read_nodes(&xlsx_path).expect("Excel read");
+    assert_eq!(from_csv, from_excel);
+}
+
+#[test]
+fn missing_csv_table_is_reported() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let result = csv_read::read_nodes(temp_dir.path());
+    assert!(matches!(
+        result,
+        Err(aideon_tools::ToolError::InvalidWorkbook(message)) if message.contains("Metadata")
+    ));
+}
+
+#[test]
+fn bare_cell_encoding_roundtrips_and_escapes_ambiguous_text() {
+    let document = serde_json::json!({
+        "@id": "https://example.com/people/1",
+        "@type": "https://schema.org/Person",
+        "https://schema.org/name": "Alice",
+        "https://schema.org/postalCode": "3000",
+        "https://schema.org/nickname": "'Al'",
+        "https://schema.org/description": "true",
+        "https://schema.org/age": 30
+    });
+    let nodes = parse_jsonld(&document);
+    let workbook = build_workbook_with(&nodes, CellEncoding::Bare).expect("tables built");
+
+    let person = workbook
+        .tables
+        .iter()
+        .find(|table| {
+            table
+                .columns
+                .contains(&"https://schema.org/name".to_string())
+        })
+        .expect("Person sheet");
+    let cell = |column: &str| {
+        let index = person
+            .columns
+            .iter()
+            .position(|name| name == column)
+            .expect("column");
+        person.rows[0][index].as_str()
+    };
+    assert_eq!(cell("https://schema.org/name"), "Alice");
+    assert_eq!(cell("https://schema.org/postalCode"), "'3000");
+    assert_eq!(cell("https://schema.org/nickname"), "''Al'");
+    assert_eq!(cell("https://schema.org/description"), "'true");
+    assert_eq!(cell("https://schema.org/age"), "30");
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let csv_dir = temp_dir.path().join("tables");
+    csv_write::write_workbook(&csv_dir, &workbook).expect("CSV written");
+    assert_eq!(csv_read::read_nodes(&csv_dir).expect("CSV read"), nodes);
+}
+
+#[test]
+fn entities_graph_column_moves_nodes_between_graphs() {
+    let nodes = parse_jsonld(&sample_document());
+    let mut workbook = build_workbook(&nodes).expect("tables built");
+    let entities = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == "Entities")
+        .expect("Entities sheet");
+    assert_eq!(entities.columns, vec!["id", "type", "graph"]);
+    for row in &mut entities.rows {
+        if row[0] == "https://example.com/people/2" {
+            row[2] = "https://example.com/graphs/named".into();
+        }
+    }
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let csv_dir = temp_dir.path().join("tables");
+    csv_write::write_workbook(&csv_dir, &workbook).expect("CSV written");
+    let restored = csv_read::read_nodes(&csv_dir).expect("CSV read");
+
+    assert_eq!(restored.len(), nodes.len());
+    let bob: Vec<_> = restored
+        .iter()
+        .filter(|node| node.id == "https://example.com/people/2")
+        .collect();
+    assert_eq!(bob.len(), 1);
+    assert_eq!(
+        bob[0].graph.as_deref(),
+        Some("https://example.com/graphs/named")
+    );
+    assert!(bob[0].properties.contains_key("https://schema.org/name"));
+}
+
+#[test]
+fn metadata_column_kinds_guide_cell_parsing() {
+    let document = serde_json::json!({
+        "@id": "https://example.com/people/1",
+        "@type": "https://schema.org/Person",
+        "https://example.com/vocab/employeeId": "E-42",
+        "https://schema.org/birthDate": {
+            "@value": "1990-01-01",
+            "@type": "http://www.w3.org/2001/XMLSchema#date"
+        },
+        "https://schema.org/skills": ["rust", "excel"]
+    });
+    let nodes = parse_jsonld(&document);
+    let mut workbook = build_workbook_with(&nodes, CellEncoding::Bare).expect("tables built");
+
+    let metadata = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == "Metadata")
+        .expect("Metadata sheet");
+    let column = |predicate: &str| {
+        metadata
+            .rows
+            .iter()
+            .find(|row| row[0] == "column" && row[3] == predicate)
+            .map(|row| (row[5].as_str(), row[6].as_str()))
+            .expect("column row")
+    };
+    assert_eq!(
+        column("https://example.com/vocab/employeeId"),
+        ("scalar", "http://www.w3.org/2001/XMLSchema#string")
+    );
+    assert_eq!(
+        column("https://schema.org/birthDate"),
+        ("scalar", "http://www.w3.org/2001/XMLSchema#date")
+    );
+    assert_eq!(
+        column("https://schema.org/skills"),
+        ("scalar-array", "http://www.w3.org/2001/XMLSchema#string")
+    );
+
+    // Plain text typed into declared columns takes the declared kind.
+    let person = workbook
+        .tables
+        .iter_mut()
+        .find(|table| {
+            table
+                .columns
+                .contains(&"https://schema.org/skills".to_string())
+        })
+        .expect("Person sheet");
+    for (column_name, value) in [
+        ("https://schema.org/birthDate", "2000-12-31"),
+        ("https://schema.org/skills", "cobol"),
+    ] {
+        let index_of = person
+            .columns
+            .iter()
+            .position(|name| name == column_name)
+            .expect("column");
+        person.rows[0][index_of] = value.to_string();
+    }
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let csv_dir = temp_dir.path().join("tables");
+    csv_write::write_workbook(&csv_dir, &workbook).expect("CSV written");
+    let restored = csv_read::read_nodes(&csv_dir).expect("CSV read");
+    let properties = &restored[0].properties;
+
+    assert_eq!(
+        properties["https://example.com/vocab/employeeId"],
+        PropertyValue::Scalar(ScalarValue::String("E-42".into()))
+    );
+    assert_eq!(
+        properties["https://schema.org/birthDate"],
+        PropertyValue::Scalar(ScalarValue::from_lexical(
+            "2000-12-31",
+            "http://www.w3.org/2001/XMLSchema#date"
+        ))
+    );
+    assert_eq!(
+        properties["https://schema.org/skills"],
+        PropertyValue::Array(ArrayValue::Scalars(vec![ScalarValue::String(
+            "cobol".into()
+        )]))
+    );
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::config::Profile;
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::coercion::ScalarCoercions;
+use aideon_tools::aideon::tools::io::csv_dialect::{CsvDialect, CsvEncoding, CsvQuoting};
+use aideon_tools::aideon::tools::io::{csv_read, csv_write};
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::sync::SyncOptions;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+fn person_named(name: &str) -> Vec<Node> {
+    let document = json!([
+        {
+            "@id": "https://example.com/people/1",
+            "@type": "https://schema.org/Person",
+            "https://schema.org/name": name,
+            "https://schema.org/age": 36
+        }
+    ]);
+    parse_jsonld(&document)
+}
+
+fn read(dir: &std::path::Path, dialect: &CsvDialect) -> Vec<Node> {
+    csv_read::read_nodes_in_dialect(dir, dialect, &ScalarCoercions::default(), &BTreeSet::new())
+        .expect("CSV read")
+}
+
+#[test]
+fn dialects_round_trip() {
+    let nodes = person_named("Zoë; \"Ada\"");
+    let workbook = build_workbook(&nodes).expect("tables built");
+    let temp_dir = tempdir().expect("temporary directory");
+
+    let semicolons = CsvDialect {
+        delimiter: ';',
+        quoting: CsvQuoting::Always,
+        encoding: CsvEncoding::Windows1252,
+        ..CsvDialect::default()
+    };
+    let dir = temp_dir.path().join("windows");
+    csv_write::write_workbook_in_dialect(&dir, &workbook, &semicolons).expect("CSV written");
+    let bytes = fs::read(csv_write::table_path(&dir, "https___schema.org_Person")).unwrap();
+    assert!(bytes.starts_with(b"\"id\";\"graph\";"));
+    assert!(bytes.windows(3).any(|window| window == b"Zo\xEB"));
+    assert_eq!(read(&dir, &semicolons), nodes);
+
+    let utf16 = CsvDialect {
+        delimiter: '\t',
+        encoding: CsvEncoding::Utf16Le,
+        bom: true,
+        ..CsvDialect::default()
+    };
+    let dir = temp_dir.path().join("utf16");
+    csv_write::write_workbook_in_dialect(&dir, &workbook, &utf16).expect("CSV written");
+    let bytes = fs::read(csv_write::table_path(&dir, "entities")).unwrap();
+    assert!(bytes.starts_with(&[0xFF, 0xFE, b'i', 0]));
+    // The byte order mark decides the encoding, whatever the dialect says.
+    let tabs = CsvDialect {
+        delimiter: '\t',
+        ..CsvDialect::default()
+    };
+    assert_eq!(read(&dir, &tabs), nodes);
+
+    let bom = CsvDialect {
+        bom: true,
+        ..CsvDialect::default()
+    };
+    let dir = temp_dir.path().join("bom");
+    csv_write::write_workbook_in_dialect(&dir, &workbook, &bom).expect("CSV written");
+    let bytes = fs::read(csv_write::table_path(&dir, "entities")).unwrap();
+    assert!(bytes.starts_with(b"\xEF\xBB\xBFid,"));
+    assert_eq!(csv_read::read_nodes(&dir).expect("CSV read"), nodes);
+}
+
+#[test]
+fn unrepresentable_text_and_dialect_settings_fail() {
+    let workbook = build_workbook(&person_named("日本")).expect("tables built");
+    let temp_dir = tempdir().expect("temporary directory");
+    let windows = CsvDialect {
+        encoding: CsvEncoding::Windows1252,
+        ..CsvDialect::default()
+    };
+    let err =
+        csv_write::write_workbook_in_dialect(temp_dir.path(), &workbook, &windows).unwrap_err();
+    assert!(matches!(err, ToolError::CsvEncoding(_)));
+    assert_eq!(
+        err.to_string(),
+        "CSV encoding error: '日' cannot be written in windows-1252"
+    );
+
+    let wide = CsvDialect {
+        delimiter: '→',
+        ..CsvDialect::default()
+    };
+    assert!(matches!(
+        csv_write::write_workbook_in_dialect(temp_dir.path(), &workbook, &wide),
+        Err(ToolError::InvalidConfig(_))
+    ));
+
+    let config = temp_dir.path().join("aideon.toml");
+    fs::write(
+        &config,
+        r#"csv = { delimiter = ";", encoding = "utf-16be", bom = true }"#,
+    )
+    .unwrap();
+    let mut options = SyncOptions::default();
+    Profile::load(&config)
+        .expect("profile read")
+        .apply(&mut options);
+    assert_eq!(
+        options.csv_dialect,
+        CsvDialect {
+            delimiter: ';',
+            encoding: CsvEncoding::Utf16Be,
+            bom: true,
+            ..CsvDialect::default()
+        }
+    );
+}
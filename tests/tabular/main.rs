@@ -0,0 +1,10 @@
+//! Integration tests of the other tabular formats: CSV, SQL, and Parquet.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod csv;
+mod csv_dialect;
+mod parquet;
+mod sql_input;
+mod sql_output;
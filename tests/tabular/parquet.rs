@@ -0,0 +1,335 @@
+use tempfile::tempdir;
+
+use crate::common::aideon;
+#[cfg(feature = "parquet")]
+use crate::common::assert_success;
+
+/// Reads the headers and rows of the Parquet file at `path`, with the cells
+/// of every column type as text.
+#[cfg(feature = "parquet")]
+fn read_table(path: &std::path::Path) -> (Vec<String>, Vec<Vec<Option<String>>>) {
+    let (fields, rows) = read_typed_table(path);
+    (fields.into_iter().map(|(name, _)| name).collect(), rows)
+}
+
+/// Names and Arrow types of the fields of a Parquet file.
+#[cfg(feature = "parquet")]
+type Fields = Vec<(String, arrow_schema::DataType)>;
+
+/// Reads the fields and rows of the Parquet file at `path`.
+#[cfg(feature = "parquet")]
+fn read_typed_table(path: &std::path::Path) -> (Fields, Vec<Vec<Option<String>>>) {
+    use arrow_array::types::{Date32Type, TimestampMicrosecondType};
+    use arrow_array::{
+        Array, BooleanArray, Float64Array, Int64Array, PrimitiveArray, RecordBatchReader,
+        StringArray,
+    };
+    use arrow_schema::{DataType, TimeUnit};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(path).unwrap())
+        .expect("Parquet footer")
+        .build()
+        .unwrap();
+    let fields: Fields = reader
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| (field.name().clone(), field.data_type().clone()))
+        .collect();
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch.expect("row group decoded");
+        for row in 0..batch.num_rows() {
+            rows.push(
+                batch
+                    .columns()
+                    .iter()
+                    .map(|column| {
+                        if column.is_null(row) {
+                            return None;
+                        }
+                        let any = column.as_any();
+                        Some(match column.data_type() {
+                            DataType::Utf8 => any
+                                .downcast_ref::<StringArray>()
+                                .unwrap()
+                                .value(row)
+                                .to_string(),
+                            DataType::Int64 => any
+                                .downcast_ref::<Int64Array>()
+                                .unwrap()
+                                .value(row)
+                                .to_string(),
+                            DataType::Float64 => any
+                                .downcast_ref::<Float64Array>()
+                                .unwrap()
+                                .value(row)
+                                .to_string(),
+                            DataType::Boolean => any
+                                .downcast_ref::<BooleanArray>()
+                                .unwrap()
+                                .value(row)
+                                .to_string(),
+                            DataType::Date32 => any
+                                .downcast_ref::<PrimitiveArray<Date32Type>>()
+                                .unwrap()
+                                .value_as_date(row)
+                                .unwrap()
+                                .to_string(),
+                            DataType::Timestamp(TimeUnit::Microsecond, None) => any
+                                .downcast_ref::<PrimitiveArray<TimestampMicrosecondType>>()
+                                .unwrap()
+                                .value_as_datetime(row)
+                                .unwrap()
+                                .format("%Y-%m-%dT%H:%M:%S")
+                                .to_string(),
+                            other => panic!("unexpected column type {other}"),
+                        })
+                    })
+                    .collect(),
+            );
+        }
+    }
+    (fields, rows)
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn tables_read_back_from_their_parquet_files() {
+    use aideon_tools::aideon::tools::flatten::SheetTable;
+    use aideon_tools::aideon::tools::io::parquet_write;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let table = SheetTable {
+        sheet_name: "https___schema.org_Person".into(),
+        columns: vec!["id".into(), "https://schema.org/name".into()],
+        rows: vec![
+            vec!["https://example.com/people/1".into(), "Ada".into()],
+            vec!["https://example.com/people/2".into(), String::new()],
+        ],
+    };
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.parquet");
+    parquet_write::write_table_to(std::fs::File::create(&path).unwrap(), &table).unwrap();
+
+    let (columns, rows) = read_table(&path);
+    assert_eq!(columns, table.columns);
+    assert_eq!(
+        rows,
+        [
+            [
+                Some("https://example.com/people/1".into()),
+                Some("Ada".into())
+            ],
+            [Some("https://example.com/people/2".into()), None],
+        ]
+    );
+    let file = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+    let metadata = file.metadata().file_metadata();
+    assert_eq!(metadata.num_rows(), 2);
+    assert!(
+        metadata
+            .created_by()
+            .is_some_and(|created_by| created_by.starts_with("aideon-tools version")),
+        "{:?}",
+        metadata.created_by()
+    );
+
+    let empty = SheetTable {
+        rows: Vec::new(),
+        ..table
+    };
+    parquet_write::write_table_to(std::fs::File::create(&path).unwrap(), &empty).unwrap();
+    assert_eq!(read_table(&path), (empty.columns, Vec::new()));
+}
+
+#[cfg(not(feature = "parquet"))]
+#[test]
+fn parquet_output_needs_the_parquet_feature() {
+    use aideon_tools::aideon::tools::flatten::SheetTable;
+    use aideon_tools::aideon::tools::io::parquet_write;
+
+    let table = SheetTable {
+        sheet_name: "Entities".into(),
+        columns: vec!["id".into()],
+        rows: Vec::new(),
+    };
+    let err = parquet_write::write_table_to(Vec::new(), &table).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Parquet error: Parquet output requires the `parquet` feature"
+    );
+}
+
+#[test]
+fn sync_writes_one_parquet_file_per_table() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    std::fs::write(
+        &input,
+        r#"{"@id": "https://example.com/people/1", "@type": "https://schema.org/Person",
+            "https://schema.org/name": "Ada"}"#,
+    )
+    .unwrap();
+    let output = temp_dir.path().join("tables");
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "parquet", "--output"])
+        .arg(&output)
+        .output()
+        .expect("CLI ran");
+    #[cfg(feature = "parquet")]
+    {
+        assert_success(&run);
+        for name in ["entities.parquet", "metadata.parquet"] {
+            assert!(!read_table(&output.join(name)).1.is_empty(), "{name}");
+        }
+        let (columns, rows) = read_table(&output.join("https___schema.org_Person.parquet"));
+        let name = columns
+            .iter()
+            .position(|column| column == "https://schema.org/name")
+            .expect("name column");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[0][name].as_deref(),
+            Some("Ada"),
+            "strings lose their quotes"
+        );
+    }
+    #[cfg(not(feature = "parquet"))]
+    assert!(!run.status.success());
+
+    let back = aideon()
+        .args(["sync", "--from", "parquet", "--input"])
+        .arg(&output)
+        .args(["--to", "json-ld", "--output"])
+        .arg(temp_dir.path().join("back.jsonld"))
+        .output()
+        .expect("CLI ran");
+    assert_eq!(
+        back.status.code(),
+        Some(2),
+        "rejected as arguments are parsed"
+    );
+    let stderr = String::from_utf8_lossy(&back.stderr);
+    assert!(
+        stderr.contains("invalid value 'parquet' for '--from <FROM>'"),
+        "{stderr}"
+    );
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn literal_columns_get_the_arrow_type_of_their_datatype() {
+    use arrow_schema::{DataType, TimeUnit};
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    std::fs::write(
+        &input,
+        r#"{"@graph": [
+            {"@id": "https://example.com/people/1", "@type": "https://schema.org/Person",
+             "https://schema.org/name": "Ada", "https://schema.org/age": 36,
+             "https://schema.org/height": 1.65, "https://schema.org/active": true,
+             "https://schema.org/price": {"@value": "12.50",
+                 "@type": "http://www.w3.org/2001/XMLSchema#decimal"},
+             "https://schema.org/birthDate": {"@value": "1815-12-10",
+                 "@type": "http://www.w3.org/2001/XMLSchema#date"},
+             "https://schema.org/updated": {"@value": "2024-05-01T09:30:00",
+                 "@type": "http://www.w3.org/2001/XMLSchema#dateTime"},
+             "https://schema.org/nickname": ["A", "B"]},
+            {"@id": "https://example.com/people/2", "@type": "https://schema.org/Person",
+             "https://schema.org/name": "Grace", "https://schema.org/age": 85}
+        ]}"#,
+    )
+    .unwrap();
+    let output = temp_dir.path().join("tables");
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "parquet", "--output"])
+        .arg(&output)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+
+    let (fields, rows) = read_typed_table(&output.join("https___schema.org_Person.parquet"));
+    let column = |name: &str| {
+        let index = fields
+            .iter()
+            .position(|(field, _)| field == &format!("https://schema.org/{name}"))
+            .unwrap_or_else(|| panic!("{name} column in {fields:?}"));
+        let cells: Vec<Option<&str>> = rows.iter().map(|row| row[index].as_deref()).collect();
+        (fields[index].1.clone(), cells)
+    };
+    assert_eq!(
+        column("name"),
+        (DataType::Utf8, vec![Some("Ada"), Some("Grace")])
+    );
+    assert_eq!(
+        column("age"),
+        (DataType::Int64, vec![Some("36"), Some("85")])
+    );
+    assert_eq!(
+        column("height"),
+        (DataType::Float64, vec![Some("1.65"), None])
+    );
+    assert_eq!(
+        column("active"),
+        (DataType::Boolean, vec![Some("true"), None])
+    );
+    assert_eq!(column("price"), (DataType::Utf8, vec![Some("12.50"), None]));
+    assert_eq!(
+        column("birthDate"),
+        (DataType::Date32, vec![Some("1815-12-10"), None])
+    );
+    assert_eq!(
+        column("updated"),
+        (
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            vec![Some("2024-05-01T09:30:00"), None]
+        )
+    );
+    assert_eq!(
+        column("nickname"),
+        (DataType::Utf8, vec![Some(r#"["A","B"]"#), None]),
+        "arrays keep their cell text"
+    );
+}
+
+#[test]
+fn change_tracking_is_rejected_before_writing_parquet() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    std::fs::write(
+        &input,
+        r#"{"@id": "https://example.com/people/1", "@type": "https://schema.org/Person"}"#,
+    )
+    .unwrap();
+    let output = temp_dir.path().join("tables");
+    for flag in [
+        "--emit-changes",
+        "--change-feed",
+        "--history",
+        "--html-report",
+    ] {
+        let run = aideon()
+            .args(["sync", "--from", "json-ld", "--input"])
+            .arg(&input)
+            .args(["--to", "parquet", "--output"])
+            .arg(&output)
+            .arg(flag)
+            .arg(temp_dir.path().join("changes"))
+            .output()
+            .expect("CLI ran");
+        assert!(!run.status.success(), "{flag}");
+        let stderr = String::from_utf8_lossy(&run.stderr);
+        assert!(
+            stderr.contains("parquet outputs can be written but not read"),
+            "{flag}: {stderr}"
+        );
+        assert!(!output.exists(), "{flag}");
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::Output;
+
+use aideon_tools::aideon::tools::io::sql::SqlQuery;
+use aideon_tools::aideon::tools::mapping::{Row, RowMapper, RowMapping};
+use aideon_tools::aideon::tools::model::{ArrayValue, Iri, Node, PropertyValue, ScalarValue};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::aideon;
+#[cfg(feature = "sql")]
+use crate::common::assert_success;
+
+fn prefixes() -> BTreeMap<String, String> {
+    BTreeMap::from([("schema".into(), "https://schema.org/".into())])
+}
+
+#[test]
+fn rows_mapping_to_one_node_add_their_values() {
+    let mapping: RowMapping = serde_json::from_value(json!({
+        "type": "schema:Person",
+        "id": "https://example.com/people/{id}",
+        "columns": {
+            "name": "schema:name",
+            "born": { "predicate": "schema:birthDate", "datatype": "xsd:date" },
+            "friend": {
+                "predicate": "schema:knows",
+                "reference": "https://example.com/people/{friend}"
+            }
+        }
+    }))
+    .expect("mapping parsed");
+    let row = |id: i64, friend: ScalarValue| -> Row {
+        BTreeMap::from([
+            ("id".into(), ScalarValue::Integer(id)),
+            ("name".into(), ScalarValue::String("Ada".into())),
+            ("born".into(), ScalarValue::String("1815-12-10".into())),
+            ("friend".into(), friend),
+        ])
+    };
+    let mut prefixes = prefixes();
+    prefixes.insert("xsd".into(), "http://www.w3.org/2001/XMLSchema#".into());
+    let mut mapper = RowMapper::new(prefixes);
+    for friend in [
+        ScalarValue::Integer(2),
+        ScalarValue::Integer(3),
+        ScalarValue::Null,
+    ] {
+        mapper.add(&mapping, &row(1, friend)).expect("row mapped");
+    }
+
+    let nodes = mapper.into_nodes();
+    assert_eq!(nodes.len(), 1);
+    let node = &nodes[0];
+    assert_eq!(node.id.as_str(), "https://example.com/people/1");
+    assert!(node.types.contains(&Iri::from("https://schema.org/Person")));
+    assert_eq!(
+        node.properties[&Iri::from("https://schema.org/name")],
+        PropertyValue::Scalar(ScalarValue::String("Ada".into()))
+    );
+    assert!(matches!(
+        node.properties[&Iri::from("https://schema.org/birthDate")],
+        PropertyValue::Scalar(ScalarValue::Date(_))
+    ));
+    assert_eq!(
+        node.properties[&Iri::from("https://schema.org/knows")],
+        PropertyValue::Array(ArrayValue::ObjectRefs(vec![
+            "https://example.com/people/2".into(),
+            "https://example.com/people/3".into()
+        ]))
+    );
+
+    let error = mapper_error(&mapping);
+    assert!(error.contains("no column 'born'"), "{error}");
+}
+
+fn mapper_error(mapping: &RowMapping) -> String {
+    let row = BTreeMap::from([("id".into(), ScalarValue::Integer(1))]);
+    RowMapper::new(prefixes())
+        .add(mapping, &row)
+        .expect_err("incomplete row")
+        .to_string()
+}
+
+#[test]
+fn tables_are_read_whole() {
+    let query = |value: serde_json::Value| -> SqlQuery {
+        serde_json::from_value(value).expect("query parsed")
+    };
+    let table = query(json!({ "table": "person", "schema": "hr", "id": "{id}" }));
+    assert_eq!(
+        table.statement().unwrap(),
+        "SELECT * FROM \"hr\".\"person\""
+    );
+    let sql = query(json!({ "sql": "SELECT 1 AS id", "id": "{id}" }));
+    assert_eq!(sql.statement().unwrap(), "SELECT 1 AS id");
+
+    let both = query(json!({ "sql": "SELECT 1 AS id", "table": "person", "id": "{id}" }));
+    let error = both.statement().unwrap_err().to_string();
+    assert!(error.contains("either `sql` or `table`"), "{error}");
+}
+
+/// Runs `sync` from the SQL source `source` to JSON-LD, with any further
+/// `args`, returning the run and the nodes written.
+fn sync_sql(source: serde_json::Value, args: &[&str]) -> (Output, Vec<Node>) {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.json");
+    fs::write(&input, source.to_string()).unwrap();
+    let output = temp_dir.path().join("people.jsonld");
+    let run = aideon()
+        .args(["sync", "--from", "sql", "--input"])
+        .arg(&input)
+        .args(["--to", "json-ld", "--output"])
+        .arg(&output)
+        .args(args)
+        .output()
+        .expect("CLI ran");
+    let nodes = match fs::read_to_string(&output) {
+        Ok(text) => aideon_tools::aideon::tools::io::jsonld::parse_jsonld_str(&text)
+            .expect("JSON-LD written"),
+        Err(_) => Vec::new(),
+    };
+    (run, nodes)
+}
+
+fn people_source() -> serde_json::Value {
+    json!({
+        "url": "sqlite::memory:",
+        "prefixes": { "schema": "https://schema.org/" },
+        "queries": [
+            {
+                "sql": "WITH person(id, name, age) AS (VALUES (1, 'Ada', 36), (2, 'Grace', NULL)) SELECT * FROM person",
+                "type": "schema:Person",
+                "id": "https://example.com/people/{id}",
+                "columns": { "name": "schema:name", "age": "schema:age" }
+            },
+            {
+                "sql": "SELECT 1 AS person, 2 AS friend",
+                "id": "https://example.com/people/{person}",
+                "columns": {
+                    "friend": {
+                        "predicate": "schema:knows",
+                        "reference": "https://example.com/people/{friend}"
+                    }
+                }
+            }
+        ]
+    })
+}
+
+#[cfg(feature = "sql")]
+#[test]
+fn sync_reads_query_rows_as_nodes() {
+    let (run, nodes) = sync_sql(people_source(), &[]);
+    assert_success(&run);
+    assert_eq!(nodes.len(), 2, "{nodes:?}");
+    let ada = nodes
+        .iter()
+        .find(|node| node.id.as_str() == "https://example.com/people/1")
+        .expect("Ada");
+    assert_eq!(
+        ada.properties[&Iri::from("https://schema.org/age")],
+        PropertyValue::Scalar(ScalarValue::Integer(36))
+    );
+    assert_eq!(
+        ada.properties[&Iri::from("https://schema.org/knows")],
+        PropertyValue::ObjectRef("https://example.com/people/2".into())
+    );
+    let grace = nodes
+        .iter()
+        .find(|node| node.id.as_str() == "https://example.com/people/2")
+        .expect("Grace");
+    assert!(
+        !grace
+            .properties
+            .contains_key(&Iri::from("https://schema.org/age"))
+    );
+}
+
+#[cfg(feature = "sql")]
+#[test]
+fn database_url_replaces_the_one_of_the_source() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let model = temp_dir.path().join("people.jsonld");
+    fs::write(
+        &model,
+        r#"{"@id": "https://example.com/people/1", "@type": "https://schema.org/Person",
+            "https://schema.org/name": "Ada"}"#,
+    )
+    .unwrap();
+    let database = temp_dir.path().join("people.db");
+    let export = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&model)
+        .args(["--to", "sqlite", "--output"])
+        .arg(&database)
+        .output()
+        .expect("CLI ran");
+    assert_success(&export);
+    let url = format!("sqlite://{}", database.display());
+
+    let source = json!({
+        "url": "sqlite::memory:",
+        "queries": [{
+            "table": "https://schema.org/Person",
+            "type": "https://schema.org/Person",
+            "id": "{id}",
+            "columns": { "https://schema.org/name": "https://schema.org/alternateName" }
+        }]
+    });
+    let (run, nodes) = sync_sql(source.clone(), &["--database-url", &url]);
+    assert_success(&run);
+    assert_eq!(nodes.len(), 1, "{nodes:?}");
+    assert_eq!(
+        nodes[0].properties[&Iri::from("https://schema.org/alternateName")],
+        PropertyValue::Scalar(ScalarValue::String("Ada".into()))
+    );
+    // The in-memory database of the file has no such table.
+    let (run, _) = sync_sql(source, &[]);
+    assert!(!run.status.success());
+}
+
+#[cfg(not(feature = "sql"))]
+#[test]
+fn sql_input_requires_the_sql_feature() {
+    let (run, _) = sync_sql(people_source(), &[]);
+    assert!(!run.status.success());
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(stderr.contains("requires the `sql` feature"), "{stderr}");
+}
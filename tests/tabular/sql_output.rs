@@ -0,0 +1,290 @@
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::sql;
+use serde_json::json;
+use tempfile::tempdir;
+
+#[cfg(feature = "sql")]
+use crate::common::assert_success;
+use crate::common::{aideon, parse_jsonld, people, references};
+
+/// Returns [`people`] with Ada working for an organisation and knowing two
+/// people.
+fn employed_people() -> serde_json::Value {
+    let mut document = people();
+    let ada = &mut document["@graph"][0];
+    ada["https://schema.org/worksFor"] = json!({ "@id": "https://example.com/org/1" });
+    ada["https://schema.org/knows"] = references(&[2, 3]);
+    document
+}
+
+fn typed_people() -> serde_json::Value {
+    json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada",
+                "https://schema.org/age": 36,
+                "https://schema.org/height": 1.65,
+                "https://schema.org/active": true,
+                "https://schema.org/price": {
+                    "@value": "12.50",
+                    "@type": "http://www.w3.org/2001/XMLSchema#decimal"
+                },
+                "https://schema.org/birthDate": {
+                    "@value": "1815-12-10",
+                    "@type": "http://www.w3.org/2001/XMLSchema#date"
+                },
+                "https://schema.org/description": { "@value": "Countess", "@language": "en" }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Grace",
+                "https://schema.org/age": 85,
+                "https://schema.org/height": 1.6,
+                "https://schema.org/active": false
+            }
+        ]
+    })
+}
+
+/// Runs `sync` from `document` to the SQLite database `output`.
+fn export(dir: &Path, output: &Path, document: &serde_json::Value) -> Output {
+    let input = dir.join("people.jsonld");
+    fs::write(&input, document.to_string()).unwrap();
+    aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "sqlite", "--output"])
+        .arg(output)
+        .output()
+        .expect("CLI ran")
+}
+
+#[test]
+fn references_become_foreign_keys_to_the_entities() {
+    let nodes = parse_jsonld(&employed_people());
+    let statements = sql::create_statements(&build_workbook(&nodes).unwrap());
+
+    let entities = statements
+        .iter()
+        .find(|statement| statement.starts_with("CREATE TABLE \"Entities\""))
+        .expect("Entities table");
+    assert!(entities.contains("\"id\" TEXT PRIMARY KEY"), "{entities}");
+    let person = statements
+        .iter()
+        .find(|statement| statement.starts_with("CREATE TABLE \"https://schema.org/Person\" "))
+        .expect("Person table");
+    assert!(
+        person.contains(
+            "FOREIGN KEY (\"https://schema.org/worksForId\") REFERENCES \"Entities\" (\"id\")"
+        ),
+        "{person}"
+    );
+    assert!(!person.contains("FOREIGN KEY (\"https://schema.org/name\")"));
+    let knows = statements
+        .iter()
+        .find(|statement| statement.contains("\"ParentId\""))
+        .expect("child table");
+    assert!(
+        knows.starts_with("CREATE TABLE \"https://schema.org/Person__https://schema.org/knows\" "),
+        "tables are not named after the shortened sheets: {knows}"
+    );
+    assert!(knows.contains("FOREIGN KEY (\"ParentId\")"), "{knows}");
+    assert!(knows.contains("FOREIGN KEY (\"https://schema.org/knowsId\")"));
+}
+
+#[test]
+fn literal_columns_take_the_sql_type_of_their_datatype() {
+    let nodes = parse_jsonld(&typed_people());
+    let statements = sql::create_statements(&build_workbook(&nodes).unwrap());
+
+    let person = statements
+        .iter()
+        .find(|statement| statement.starts_with("CREATE TABLE \"https://schema.org/Person\" "))
+        .expect("Person table");
+    for definition in [
+        "\"https://schema.org/name\" TEXT",
+        "\"https://schema.org/age\" INTEGER",
+        "\"https://schema.org/height\" REAL",
+        "\"https://schema.org/active\" BOOLEAN",
+        "\"https://schema.org/price\" NUMERIC",
+        "\"https://schema.org/birthDate\" TEXT",
+        "\"https://schema.org/description\" TEXT",
+    ] {
+        assert!(person.contains(definition), "{definition}: {person}");
+    }
+}
+
+#[cfg(feature = "sql")]
+#[test]
+fn typed_columns_store_values_of_their_type() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let database = temp_dir.path().join("model.db");
+    let run = export(temp_dir.path(), &database, &typed_people());
+    assert_success(&run);
+
+    let source = temp_dir.path().join("source.json");
+    fs::write(
+        &source,
+        json!({
+            "url": format!("sqlite://{}", database.display()),
+            "queries": [
+                {
+                    "sql": "SELECT id,
+                                   \"https://schema.org/name\" = 'Ada' AS unquoted,
+                                   typeof(\"https://schema.org/age\") AS age,
+                                   typeof(\"https://schema.org/height\") AS height,
+                                   typeof(\"https://schema.org/active\") AS active,
+                                   typeof(\"https://schema.org/price\") AS price,
+                                   \"https://schema.org/price\" AS amount,
+                                   \"https://schema.org/birthDate\" AS birth,
+                                   \"https://schema.org/description\" AS description
+                            FROM \"https://schema.org/Person\" WHERE \"https://schema.org/age\" < 40",
+                    "id": "{id}",
+                    "columns": {
+                        "unquoted": "urn:unquoted",
+                        "age": "urn:age",
+                        "height": "urn:height",
+                        "active": "urn:active",
+                        "price": "urn:price",
+                        "amount": "urn:amount",
+                        "birth": "urn:birth",
+                        "description": "urn:description"
+                    }
+                }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let output = temp_dir.path().join("back.jsonld");
+    let back = aideon()
+        .args(["sync", "--from", "sql", "--input"])
+        .arg(&source)
+        .args(["--to", "json-ld", "--output"])
+        .arg(&output)
+        .output()
+        .expect("CLI ran");
+    assert_success(&back);
+    let back: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output).unwrap()).expect("JSON-LD written");
+    let node = back
+        .get("@graph")
+        .and_then(|graph| graph.get(0))
+        .unwrap_or(&back);
+    assert_eq!(node["urn:unquoted"], json!(1), "{back}");
+    assert_eq!(node["urn:age"], json!("integer"), "{back}");
+    assert_eq!(node["urn:height"], json!("real"), "{back}");
+    assert_eq!(node["urn:active"], json!("integer"), "{back}");
+    assert_eq!(node["urn:price"], json!("real"), "{back}");
+    assert_eq!(node["urn:amount"], json!(12.5), "{back}");
+    assert_eq!(node["urn:birth"], json!("1815-12-10"), "{back}");
+    assert_eq!(node["urn:description"], json!("Countess"), "{back}");
+}
+
+#[cfg(feature = "sql")]
+#[test]
+fn sync_writes_the_tables_to_a_sqlite_database() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let database = temp_dir.path().join("model.db");
+    let run = export(temp_dir.path(), &database, &employed_people());
+    assert_success(&run);
+
+    // Read the database back through a SQL source.
+    let source = temp_dir.path().join("source.json");
+    let url = format!("sqlite://{}", database.display());
+    fs::write(
+        &source,
+        json!({
+            "url": url,
+            "queries": [
+                {
+                    "sql": "SELECT p.id, p.\"https://schema.org/name\" AS name, k.\"https://schema.org/knowsId\" AS friend
+                            FROM \"https://schema.org/Person\" p
+                            JOIN \"https://schema.org/Person__https://schema.org/knows\" k ON k.ParentId = p.id",
+                    "id": "{id}",
+                    "columns": {
+                        "name": "https://schema.org/alternateName",
+                        "friend": { "predicate": "https://schema.org/knows", "reference": "{friend}" }
+                    }
+                },
+                {
+                    "sql": "SELECT \"table\" AS target, \"from\" AS source FROM pragma_foreign_key_list('https://schema.org/Person')",
+                    "id": "urn:fk:{source}",
+                    "columns": { "target": "https://schema.org/target" }
+                }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let output = temp_dir.path().join("back.jsonld");
+    let back = aideon()
+        .args(["sync", "--from", "sql", "--input"])
+        .arg(&source)
+        .args(["--to", "json-ld", "--output"])
+        .arg(&output)
+        .output()
+        .expect("CLI ran");
+    assert_success(&back);
+    let text = fs::read_to_string(&output).unwrap();
+    assert!(text.contains("\"Ada\""), "{text}");
+    assert!(
+        !text.contains("\\\"Ada"),
+        "strings are stored without their quotes: {text}"
+    );
+    assert!(text.contains("https://example.com/people/3"), "{text}");
+    assert!(
+        text.contains("urn:fk:https://schema.org/worksForId"),
+        "{text}"
+    );
+}
+
+#[cfg(not(feature = "sql"))]
+#[test]
+fn sqlite_output_requires_the_sql_feature() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let database = temp_dir.path().join("model.db");
+    let run = export(temp_dir.path(), &database, &employed_people());
+    assert!(!run.status.success());
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(stderr.contains("requires the `sql` feature"), "{stderr}");
+    assert!(!database.exists());
+}
+
+#[test]
+fn change_tracking_is_rejected_before_writing_sqlite() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, employed_people().to_string()).unwrap();
+    let database = temp_dir.path().join("model.db");
+    for flag in [
+        "--emit-changes",
+        "--change-feed",
+        "--history",
+        "--html-report",
+    ] {
+        let run = aideon()
+            .args(["sync", "--from", "json-ld", "--input"])
+            .arg(&input)
+            .args(["--to", "sqlite", "--output"])
+            .arg(&database)
+            .arg(flag)
+            .arg(temp_dir.path().join("changes"))
+            .output()
+            .expect("CLI ran");
+        assert!(!run.status.success(), "{flag}");
+        let stderr = String::from_utf8_lossy(&run.stderr);
+        assert!(
+            stderr.contains("sqlite outputs can be written but not read"),
+            "{flag}: {stderr}"
+        );
+        assert!(!database.exists(), "{flag}");
+    }
+}
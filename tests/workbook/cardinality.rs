@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::{
+    Cardinality, WorkbookData, WorkbookOptions, build_workbook_with_options,
+};
+use aideon_tools::aideon::tools::model::{Iri, Node};
+use aideon_tools::aideon::tools::shacl::ShapesGraph;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const KNOWS: &str = "https://schema.org/knows";
+const NICKNAME: &str = "https://schema.org/alternateName";
+const EMAIL: &str = "https://schema.org/email";
+
+fn mixed_cardinalities() -> Vec<Node> {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                KNOWS: [
+                    { "@id": "https://example.com/people/2" },
+                    { "@id": "https://example.com/people/3" }
+                ],
+                EMAIL: { "@list": ["ada@example.com"] }
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                KNOWS: { "@id": "https://example.com/people/3" },
+                NICKNAME: "Bobby"
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+fn workbook(cardinalities: BTreeMap<Iri, Cardinality>) -> WorkbookData {
+    let options = WorkbookOptions {
+        cardinalities,
+        ..WorkbookOptions::default()
+    };
+    build_workbook_with_options(&mixed_cardinalities(), &options).expect("tables built")
+}
+
+fn sheet<'a>(workbook: &'a WorkbookData, name: &str) -> &'a [Vec<String>] {
+    let table = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == name)
+        .unwrap_or_else(|| panic!("missing sheet {name}"));
+    &table.rows
+}
+
+#[test]
+fn cardinality_decides_the_layout_of_single_values() {
+    // By value shape, Bob's single acquaintance lands in a column of its own.
+    let shaped = workbook(BTreeMap::new());
+    let people_sheet = &shaped.tables[2];
+    assert!(
+        people_sheet
+            .columns
+            .contains(&"https://schema.org/knowsId".to_string())
+    );
+
+    let hinted = workbook(BTreeMap::from([
+        (Iri::from(KNOWS), Cardinality::Many),
+        (Iri::from(NICKNAME), Cardinality::Many),
+        (Iri::from(EMAIL), Cardinality::One),
+    ]));
+    let people_sheet = &hinted.tables[2];
+    assert_eq!(
+        people_sheet.columns,
+        [
+            "id",
+            "graph",
+            "https://schema.org/alternateName",
+            "https://schema.org/email"
+        ]
+    );
+    assert_eq!(
+        people_sheet.rows,
+        [
+            [
+                "https://example.com/people/1",
+                "",
+                "",
+                "\"ada@example.com\""
+            ],
+            ["https://example.com/people/2", "", "[\"Bobby\"]", ""],
+        ]
+    );
+    let knows = sheet(&hinted, "https___schema.org_Person__http");
+    assert_eq!(knows.len(), 3);
+    let metadata = sheet(&hinted, "Metadata");
+    assert!(
+        metadata
+            .iter()
+            .any(|row| row[3] == NICKNAME && row[5] == "scalar-array")
+    );
+}
+
+#[test]
+fn shapes_declare_cardinalities_by_max_count() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.ttl");
+    fs::write(
+        &path,
+        r#"@prefix sh: <http://www.w3.org/ns/shacl#> .
+@prefix schema: <https://schema.org/> .
+<https://example.com/shapes/Person> a sh:NodeShape ;
+    sh:targetClass schema:Person ;
+    sh:property [ sh:path schema:email ; sh:maxCount 1 ] ,
+        [ sh:path schema:knows ; sh:maxCount 1 ] ,
+        [ sh:path schema:name ; sh:minCount 1 ] .
+<https://example.com/shapes/Team> a sh:NodeShape ;
+    sh:targetClass schema:Organization ;
+    sh:property [ sh:path schema:knows ; sh:maxCount 10 ] .
+"#,
+    )
+    .unwrap();
+    let shapes = ShapesGraph::load(&path, None).expect("shapes loaded");
+    assert_eq!(
+        shapes.cardinalities(),
+        BTreeMap::from([
+            (Iri::from(EMAIL), Cardinality::One),
+            (Iri::from(KNOWS), Cardinality::Many),
+        ])
+    );
+}
@@ -0,0 +1,104 @@
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::flatten::{CellEncoding, WorkbookData, build_workbook_with};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::error::CellError;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, parse_jsonld};
+
+const PERSON_SHEET: &str = "https___schema.org_Person";
+const AGE: &str = "https://schema.org/age";
+const ACTIVE: &str = "https://schema.org/active";
+
+/// People whose sheet has `text` typed into the cells of `predicate` on the
+/// given rows.
+fn edited(edits: &[(usize, &str, &str)]) -> WorkbookData {
+    let nodes = parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/age": 30,
+                "https://schema.org/active": true
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/age": 41,
+                "https://schema.org/active": false
+            }
+        ]
+    }));
+    let mut workbook = build_workbook_with(&nodes, CellEncoding::Bare).expect("tables built");
+    let people = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == PERSON_SHEET)
+        .expect("Person sheet");
+    for (row, predicate, text) in edits {
+        let column = people
+            .columns
+            .iter()
+            .position(|column| column == predicate)
+            .expect("column");
+        people.rows[*row][column] = text.to_string();
+    }
+    workbook
+}
+
+#[test]
+fn every_unreadable_cell_is_reported_with_its_location() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    let workbook = edited(&[(0, AGE, "thirty"), (1, ACTIVE, "maybe")]);
+    excel_write::write_workbook(&path, &workbook).unwrap();
+
+    let error = excel_read::read_nodes(&path).expect_err("invalid cells");
+    let ToolError::InvalidCells(cells) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    let located: Vec<(&str, usize, &str, &str)> = cells
+        .iter()
+        .map(|cell: &CellError| {
+            (
+                cell.sheet.as_str(),
+                cell.row,
+                cell.column.as_str(),
+                cell.text.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        located,
+        [
+            (PERSON_SHEET, 2, AGE, "thirty"),
+            (PERSON_SHEET, 3, ACTIVE, "maybe")
+        ]
+    );
+    assert!(cells[0].reason.contains("integer"), "{}", cells[0].reason);
+}
+
+#[test]
+fn sync_prints_every_unreadable_cell() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.xlsx");
+    let workbook = edited(&[(0, AGE, "thirty"), (1, AGE, "forty-one")]);
+    excel_write::write_workbook(&input, &workbook).unwrap();
+    let run = aideon()
+        .args(["sync", "--from", "excel", "--input"])
+        .arg(&input)
+        .args(["--to", "json-ld", "--output"])
+        .arg(temp_dir.path().join("people.jsonld"))
+        .output()
+        .expect("CLI ran");
+    assert!(!run.status.success());
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(stderr.contains("2 cell(s) could not be read"), "{stderr}");
+    for (row, text) in [(2, "thirty"), (3, "forty-one")] {
+        assert!(
+            stderr.contains(&format!("row {row}, column '{AGE}': '{text}'")),
+            "{stderr}"
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use aideon_tools::aideon::tools::flatten::{METADATA_SHEET, build_workbook};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{PropertyValue, ScalarValue};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const COUNTRY: &str = "https://schema.org/addressCountry";
+const STATUS: &str = "https://schema.org/status";
+
+#[test]
+fn metadata_defaults_fill_empty_cells() {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/addressCountry": "DE"
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Bob"
+            }
+        ]
+    });
+    let nodes = parse_jsonld(&document);
+    let mut workbook = build_workbook(&nodes).expect("tables built");
+
+    // Users add a `default` column to Metadata, and may declare columns the
+    // type sheet does not have.
+    let metadata = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == METADATA_SHEET)
+        .expect("metadata sheet");
+    metadata.columns.push("default".to_string());
+    let person_sheet = metadata
+        .rows
+        .iter()
+        .find(|row| row[0] == "type")
+        .map(|row| row[1].clone())
+        .unwrap();
+    for row in &mut metadata.rows {
+        let default = if row[0] == "column" && row[3] == COUNTRY {
+            "\"AU\""
+        } else {
+            ""
+        };
+        row.push(default.to_string());
+    }
+    metadata.rows.push(
+        [
+            "column",
+            &person_sheet,
+            "https://schema.org/Person",
+            STATUS,
+            STATUS,
+            "scalar",
+            "",
+            "active",
+        ]
+        .map(str::to_string)
+        .to_vec(),
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("workbook written");
+    let restored = excel_read::read_nodes(&path).expect("workbook read");
+
+    let value = |index: usize, predicate: &str| restored[index].properties.get(predicate).cloned();
+    let text = |text: &str| Some(PropertyValue::Scalar(ScalarValue::String(text.into())));
+    assert_eq!(value(0, COUNTRY), text("DE"));
+    assert_eq!(value(1, COUNTRY), text("AU"));
+    assert_eq!(value(0, STATUS), text("active"));
+    assert_eq!(value(1, STATUS), text("active"));
+    assert_eq!(value(1, "https://schema.org/name"), text("Bob"));
+}
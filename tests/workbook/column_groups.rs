@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::{
+    ColumnGroup, WorkbookOptions, build_workbook_with_options,
+};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write, jsonld};
+use aideon_tools::aideon::tools::model::{Iri, Node};
+use calamine::{Reader, Xlsx, open_workbook};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld, people};
+
+const PERSON_SHEET: &str = "https___schema.org_Person";
+
+fn described_people() -> Vec<Node> {
+    let mut document = people();
+    let ada = &mut document["@graph"][0];
+    ada["https://schema.org/identifier"] = json!("P-1");
+    ada["https://schema.org/email"] = json!("ada@example.com");
+    ada["https://schema.org/description"] = json!("Mathematician");
+    parse_jsonld(&document)
+}
+
+/// Cell text of the first `rows` rows of the Person sheet of `path`.
+fn person_rows(path: &std::path::Path, rows: usize) -> Vec<Vec<String>> {
+    let mut workbook: Xlsx<_> = open_workbook(path).expect("workbook opened");
+    let range = workbook
+        .worksheet_range(PERSON_SHEET)
+        .expect("Person sheet");
+    range
+        .rows()
+        .take(rows)
+        .map(|row| row.iter().map(ToString::to_string).collect())
+        .collect()
+}
+
+#[test]
+fn groups_lead_the_sheet_under_a_band_row() {
+    let groups = vec![
+        ColumnGroup {
+            label: "Identification".into(),
+            predicates: vec![
+                Iri::from("https://schema.org/identifier"),
+                Iri::from("https://schema.org/name"),
+            ],
+        },
+        ColumnGroup {
+            label: "Relations".into(),
+            predicates: vec![Iri::from("https://schema.org/knows")],
+        },
+    ];
+    let options = WorkbookOptions {
+        column_groups: BTreeMap::from([(Iri::from("https://schema.org/Person"), groups)]),
+        ..WorkbookOptions::default()
+    };
+    let nodes = described_people();
+    let workbook = build_workbook_with_options(&nodes, &options).expect("workbook built");
+    let metadata = &workbook.tables[1];
+    let bands: Vec<&[String]> = metadata
+        .rows
+        .iter()
+        .filter(|row| row[0] == "group")
+        .map(|row| &row[4..7])
+        .collect();
+    assert_eq!(
+        bands,
+        [
+            [
+                "Identification",
+                "https://schema.org/identifier",
+                "https://schema.org/name"
+            ],
+            [
+                "Relations",
+                "https://schema.org/knowsId",
+                "https://schema.org/knowsId"
+            ]
+        ]
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &workbook).unwrap();
+    assert_eq!(
+        person_rows(&path, 2),
+        [
+            vec!["", "", "Identification", "", "Relations", "", ""],
+            vec![
+                "id",
+                "graph",
+                "https://schema.org/identifier",
+                "https://schema.org/name",
+                "https://schema.org/knowsId",
+                "https://schema.org/description",
+                "https://schema.org/email"
+            ]
+        ]
+    );
+    assert_eq!(excel_read::read_nodes(&path).expect("read back"), nodes);
+}
+
+#[test]
+fn sync_bands_columns_by_shacl_groups() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    let document = jsonld::nodes_to_jsonld(&described_people(), None).unwrap();
+    fs::write(&input, document.to_string()).unwrap();
+    let shapes = temp_dir.path().join("shapes.ttl");
+    fs::write(
+        &shapes,
+        r#"
+@prefix sh: <http://www.w3.org/ns/shacl#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix schema: <https://schema.org/> .
+@prefix ex: <https://example.com/shapes/> .
+
+ex:Identification a sh:PropertyGroup ; rdfs:label "Identification" ; sh:order 0 .
+ex:Description a sh:PropertyGroup ; rdfs:label "Description" ; sh:order 1 .
+
+ex:PersonShape a sh:NodeShape ;
+    sh:targetClass schema:Person ;
+    sh:property [ sh:path schema:name ; sh:group ex:Identification ; sh:order 1 ] ,
+        [ sh:path schema:identifier ; sh:group ex:Identification ; sh:order 0 ] ,
+        [ sh:path schema:description ; sh:group ex:Description ] .
+"#,
+    )
+    .unwrap();
+    let output = temp_dir.path().join("people.xlsx");
+
+    for update in [false, true] {
+        let mut command = aideon();
+        command
+            .args(["sync", "--from", "json-ld", "--input"])
+            .arg(&input)
+            .args(["--to", "excel", "--output"])
+            .arg(&output)
+            .arg("--column-group-shapes")
+            .arg(&shapes);
+        if update {
+            command.arg("--update");
+        }
+        let run = command.output().expect("CLI ran");
+        assert_success(&run);
+    }
+    let rows = person_rows(&output, 2);
+    assert_eq!(rows[0][2..5], ["Identification", "", "Description"]);
+    assert_eq!(
+        rows[1][2..5],
+        [
+            "https://schema.org/identifier",
+            "https://schema.org/name",
+            "https://schema.org/description"
+        ]
+    );
+    assert_eq!(
+        excel_read::read_nodes(&output).expect("read back"),
+        described_people()
+    );
+}
@@ -0,0 +1,157 @@
+use aideon_tools::ToolError;
+use aideon_tools::aideon::tools::flatten::{
+    CellEncoding, METADATA_SHEET, WorkbookData, build_workbook_with,
+};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{
+    Node, PropertyValue, ScalarValue, XSD_BOOLEAN, XSD_DATE, XSD_INTEGER, XSD_STRING,
+};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const NAME: &str = "https://schema.org/name";
+const AGE: &str = "https://schema.org/age";
+const SCORE: &str = "https://schema.org/score";
+const ACTIVE: &str = "https://schema.org/active";
+const JOINED: &str = "https://schema.org/joined";
+
+fn workbook() -> WorkbookData {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Alice",
+                "https://schema.org/age": 30,
+                "https://schema.org/score": 2.5,
+                "https://schema.org/active": true
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Bob",
+                "https://schema.org/score": 4
+            }
+        ]
+    });
+    let nodes = parse_jsonld(&document);
+    build_workbook_with(&nodes, CellEncoding::Bare).expect("tables built")
+}
+
+/// Replaces the cell of `predicate` on the first person row.
+fn edit(workbook: &mut WorkbookData, predicate: &str, text: &str) {
+    let table = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == "https___schema.org_Person")
+        .expect("person sheet");
+    let column = table
+        .columns
+        .iter()
+        .position(|column| column == predicate)
+        .expect("column");
+    table.rows[0][column] = text.to_string();
+}
+
+fn read(workbook: &WorkbookData) -> Result<Vec<Node>, ToolError> {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, workbook).expect("workbook written");
+    excel_read::read_nodes(&path)
+}
+
+#[test]
+fn declared_datatypes_decide_how_cells_are_read() {
+    let mut workbook = workbook();
+    let metadata = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == METADATA_SHEET)
+        .expect("metadata sheet");
+    let datatype = |predicate: &str| {
+        metadata
+            .rows
+            .iter()
+            .find(|row| row[0] == "column" && row[3] == predicate)
+            .map(|row| row[6].clone())
+            .expect("column row")
+    };
+    assert_eq!(datatype(NAME), XSD_STRING);
+    assert_eq!(datatype(AGE), XSD_INTEGER);
+    assert_eq!(datatype(SCORE), "");
+    assert_eq!(datatype(ACTIVE), XSD_BOOLEAN);
+
+    // Columns added by hand may name datatypes with the `xsd:` prefix.
+    let sheet = metadata.rows[0][1].clone();
+    metadata.rows.push(
+        [
+            "column",
+            &sheet,
+            "https://schema.org/Person",
+            JOINED,
+            JOINED,
+            "scalar",
+            "xsd:date",
+        ]
+        .map(str::to_string)
+        .to_vec(),
+    );
+    let person_sheet = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == sheet)
+        .expect("person sheet");
+    person_sheet.columns.push(JOINED.to_string());
+    for row in &mut person_sheet.rows {
+        row.push(String::new());
+    }
+    person_sheet.rows[0]
+        .last_mut()
+        .unwrap()
+        .push_str("2024-05-01");
+
+    // Hand-edited cells that would read back as JSON of another type take
+    // the declared type instead.
+    edit(&mut workbook, NAME, "30");
+    edit(&mut workbook, AGE, "\"31\"");
+    edit(&mut workbook, ACTIVE, "1");
+
+    let nodes = read(&workbook).expect("workbook read");
+    let value = |predicate: &str| nodes[0].properties.get(predicate).cloned();
+    let scalar = |scalar: ScalarValue| Some(PropertyValue::Scalar(scalar));
+    assert_eq!(value(NAME), scalar(ScalarValue::String("30".into())));
+    assert_eq!(value(AGE), scalar(ScalarValue::Integer(31)));
+    assert_eq!(value(SCORE), scalar(ScalarValue::Double(2.5)));
+    assert_eq!(value(ACTIVE), scalar(ScalarValue::Boolean(true)));
+    assert_eq!(
+        value(JOINED),
+        scalar(ScalarValue::from_lexical("2024-05-01", XSD_DATE))
+    );
+    assert_eq!(
+        nodes[1].properties.get(SCORE),
+        Some(&PropertyValue::Scalar(ScalarValue::Integer(4)))
+    );
+}
+
+#[test]
+fn cells_not_matching_the_declared_datatype_are_reported() {
+    for (predicate, text) in [(AGE, "thirty"), (AGE, "30.5"), (ACTIVE, "yes")] {
+        let mut workbook = workbook();
+        edit(&mut workbook, predicate, text);
+        let err = read(&workbook).expect_err("invalid cell");
+        let ToolError::InvalidCells(cells) = err else {
+            panic!("unexpected error {err:?}");
+        };
+        assert_eq!(cells.len(), 1, "{cells:?}");
+        assert_eq!(
+            (
+                cells[0].row,
+                cells[0].column.as_str(),
+                cells[0].text.as_str()
+            ),
+            (2, predicate, text)
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::ToolError;
+use aideon_tools::aideon::tools::flatten::{WorkbookData, build_workbook};
+use aideon_tools::aideon::tools::io::coercion::ScalarCoercions;
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{Iri, PropertyValue, ScalarValue};
+use aideon_tools::aideon::tools::validate::IssueKind;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, parse_jsonld};
+
+const PERSON_SHEET: &str = "https___schema.org_Person";
+
+/// A workbook whose Person sheet repeats Ada's row, renamed in the copy
+/// when `rename` is set.
+fn write_duplicated(path: &Path, rename: bool) {
+    let nodes = parse_jsonld(&json!({
+        "@id": "https://example.com/people/1",
+        "@type": "https://schema.org/Person",
+        "https://schema.org/name": "Ada"
+    }));
+    let mut workbook: WorkbookData = build_workbook(&nodes).expect("workbook built");
+    let people = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == PERSON_SHEET)
+        .expect("Person sheet");
+    let name = people
+        .columns
+        .iter()
+        .position(|column| column == "https://schema.org/name")
+        .expect("name column");
+    let mut copy = people.rows[0].clone();
+    if rename {
+        copy[name] = "Augusta Ada".into();
+    }
+    people.rows.push(copy);
+    excel_write::write_workbook(path, &workbook).unwrap();
+}
+
+#[test]
+fn conflicting_rows_are_reported_or_rejected() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    write_duplicated(&path, true);
+
+    let (nodes, report) =
+        excel_read::read_nodes_with_report(&path, &ScalarCoercions::default(), &BTreeSet::new())
+            .expect("lenient read");
+    assert_eq!(
+        nodes[0].properties[&Iri::from("https://schema.org/name")],
+        PropertyValue::Scalar(ScalarValue::String("Augusta Ada".into()))
+    );
+    let conflicts: Vec<_> = report
+        .issues
+        .iter()
+        .filter(|issue| issue.kind == IssueKind::ConflictingCell)
+        .collect();
+    assert_eq!(conflicts.len(), 1, "{:?}", report.issues);
+    assert_eq!(
+        conflicts[0].predicate.as_deref(),
+        Some("https://schema.org/name")
+    );
+
+    let error = excel_read::read_nodes_strict(&path, &ScalarCoercions::default(), &BTreeSet::new())
+        .expect_err("strict read");
+    match error {
+        ToolError::ConflictingCell { sheet, row, column } => {
+            assert_eq!(sheet, PERSON_SHEET);
+            assert_eq!(row, 3);
+            assert_eq!(column, "https://schema.org/name");
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+
+    write_duplicated(&path, false);
+    excel_read::read_nodes_strict(&path, &ScalarCoercions::default(), &BTreeSet::new())
+        .expect("identical rows agree");
+}
+
+#[test]
+fn strict_sync_fails_on_conflicting_rows() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.xlsx");
+    write_duplicated(&input, true);
+    let output = temp_dir.path().join("people.jsonld");
+    let sync = |strict: bool| {
+        let mut command = aideon();
+        command
+            .args(["sync", "--from", "excel", "--input"])
+            .arg(&input)
+            .args(["--to", "json-ld", "--output"])
+            .arg(&output);
+        if strict {
+            command.arg("--strict");
+        }
+        command.output().expect("CLI ran")
+    };
+
+    assert!(sync(false).status.success());
+    let run = sync(true);
+    assert!(!run.status.success());
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(stderr.contains("row 3 on sheet"), "{stderr}");
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::{
+    CONTEXT_SHEET, WHOLE_CONTEXT_TERM, WorkbookOptions, build_workbook, build_workbook_with_options,
+};
+use aideon_tools::aideon::tools::io::{csv_read, csv_write, excel_read};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{parse_jsonld, people};
+
+#[test]
+fn embedded_contexts_compact_json_ld_read_from_the_workbook() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    fs::write(&input, people().to_string()).unwrap();
+    let workbook = temp_dir.path().join("people.xlsx");
+    let options = SyncOptions {
+        embedded_context: Some(json!({
+            "@version": 1.1,
+            "@vocab": "https://schema.org/",
+            "knows": { "@id": "https://schema.org/knows", "@type": "@id" }
+        })),
+        prefixes: BTreeMap::from([("people".into(), "https://example.com/people/".into())]),
+        ..SyncOptions::default()
+    };
+    sync::jsonld_to_excel_with_options(&input, &workbook, &options).expect("workbook written");
+
+    let context = json!({
+        "@version": 1.1,
+        "@vocab": "https://schema.org/",
+        "knows": { "@id": "https://schema.org/knows", "@type": "@id" },
+        "people": "https://example.com/people/"
+    });
+    assert_eq!(
+        excel_read::read_context(&workbook).expect("context read"),
+        Some(context.clone())
+    );
+
+    let output = temp_dir.path().join("people.out.jsonld");
+    sync::excel_to_jsonld_with_options(&workbook, &output, None, &SyncOptions::default())
+        .expect("JSON-LD written");
+    let written: Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(written["@context"], context);
+    let ada = written["@graph"]
+        .as_array()
+        .expect("graph")
+        .iter()
+        .find(|node| node["@id"] == "people:1")
+        .expect("compacted id");
+    assert_eq!(ada["name"], "Ada");
+    assert_eq!(ada["knows"], "people:2");
+}
+
+#[test]
+fn contexts_other_than_term_maps_are_embedded_whole() {
+    let nodes = parse_jsonld(&people());
+    let context = json!(["https://schema.org", { "people": "https://example.com/people/" }]);
+    let options = WorkbookOptions {
+        context: Some(context.clone()),
+        ..WorkbookOptions::default()
+    };
+    let workbook = build_workbook_with_options(&nodes, &options).expect("tables built");
+    let sheet = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == CONTEXT_SHEET)
+        .expect("context sheet");
+    assert_eq!(
+        sheet.rows,
+        [[WHOLE_CONTEXT_TERM.to_string(), context.to_string()]]
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let dir = temp_dir.path().join("embedded");
+    csv_write::write_workbook(&dir, &workbook).expect("CSV written");
+    assert!(dir.join("context.csv").is_file());
+    assert_eq!(csv_read::read_context(&dir).unwrap(), Some(context));
+    assert_eq!(csv_read::read_nodes(&dir).unwrap(), nodes);
+
+    let plain = temp_dir.path().join("plain");
+    csv_write::write_workbook(&plain, &build_workbook(&nodes).unwrap()).expect("CSV written");
+    assert_eq!(csv_read::read_context(&plain).unwrap(), None);
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::excel_write;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const NQUADS: &str = r#"<https://example.com/people/1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://schema.org/Person> .
+<https://example.com/people/1> <https://schema.org/spouse> <https://example.com/people/2> .
+<https://example.com/people/1> <https://schema.org/active> "true"^^<http://www.w3.org/2001/XMLSchema#boolean> .
+<https://example.com/people/2> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://schema.org/Person> .
+<https://example.com/people/2> <https://schema.org/active> "false"^^<http://www.w3.org/2001/XMLSchema#boolean> .
+"#;
+
+/// Returns the `dataValidation` elements of each sheet of the workbook at
+/// `path`, by sheet name.
+fn validations(path: &Path) -> BTreeMap<String, Vec<String>> {
+    let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+    let mut read = |name: &str| {
+        let mut xml = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut xml)
+            .unwrap();
+        xml
+    };
+    let workbook = read("xl/workbook.xml");
+    let names: Vec<String> = workbook
+        .split("<sheet name=\"")
+        .skip(1)
+        .map(|rest| rest.split('"').next().unwrap().to_string())
+        .collect();
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let sheet = read(&format!("xl/worksheets/sheet{}.xml", index + 1));
+            let elements = sheet
+                .split("<dataValidation ")
+                .skip(1)
+                .map(|rest| rest.split("</dataValidation>").next().unwrap().to_string())
+                .collect();
+            (name, elements)
+        })
+        .collect()
+}
+
+#[test]
+fn reference_and_boolean_columns_get_dropdowns() {
+    let nodes = parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada",
+                "https://schema.org/active": true,
+                "https://schema.org/spouse": { "@id": "https://example.com/people/2" },
+                "https://schema.org/knows": [
+                    { "@id": "https://example.com/people/2" },
+                    { "@id": "https://example.com/people/3" }
+                ]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Grace",
+                "https://schema.org/active": false
+            }
+        ]
+    }));
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &build_workbook(&nodes).unwrap()).expect("written");
+
+    let validations = validations(&path);
+    let people = &validations["https___schema.org_Person"];
+    assert_eq!(people.len(), 2, "{people:?}");
+    let spouse = people
+        .iter()
+        .find(|element| element.contains("<formula1>Entities!$A$2:$A$3</formula1>"))
+        .expect("reference dropdown");
+    assert!(spouse.contains("errorStyle=\"warning\""), "{spouse}");
+    let active = people
+        .iter()
+        .find(|element| element.contains("\"TRUE,FALSE\""))
+        .expect("boolean dropdown");
+    assert!(!active.contains("errorStyle"), "{active}");
+
+    let knows = validations
+        .iter()
+        .find(|(name, _)| name.starts_with("https___schema.org_Person_"))
+        .map(|(_, elements)| elements)
+        .expect("child sheet");
+    assert_eq!(knows.len(), 2, "parent and target columns: {knows:?}");
+    assert!(validations["Entities"].is_empty());
+    assert!(validations["Metadata"].is_empty());
+}
+
+#[test]
+fn streamed_workbooks_get_the_same_validations() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.nq");
+    fs::write(&input, NQUADS).unwrap();
+
+    let whole = temp_dir.path().join("whole.xlsx");
+    let chunked = temp_dir.path().join("chunked.xlsx");
+    sync::rdf_to_excel_with_options(&input, &whole, &SyncOptions::default())
+        .expect("whole workbook");
+    let options = SyncOptions {
+        chunk_size: Some(1),
+        ..SyncOptions::default()
+    };
+    sync::rdf_to_excel_with_options(&input, &chunked, &options).expect("chunked workbook");
+
+    let expected = validations(&whole);
+    assert_eq!(expected["https___schema.org_Person"].len(), 2);
+    assert_eq!(validations(&chunked), expected);
+}
@@ -0,0 +1,137 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::{
+    DATASET_SHEET, ENTITIES_SHEET, SheetTable, build_workbook,
+};
+use aideon_tools::aideon::tools::io::{csv_read, csv_write, excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue};
+use calamine::{Reader, Xlsx, open_workbook};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const DATASET: &str = "https://example.com/datasets/people";
+
+fn nodes() -> Vec<Node> {
+    parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": DATASET,
+                "@type": "http://www.w3.org/ns/dcat#Dataset",
+                "http://purl.org/dc/terms/title": "People",
+                "http://www.w3.org/ns/dcat#version": "1.2",
+                "http://purl.org/dc/terms/license": {
+                    "@id": "https://creativecommons.org/licenses/by/4.0/"
+                },
+                "http://www.w3.org/ns/sparql-service-description#defaultGraph": {
+                    "@id": "https://example.com/graphs/people"
+                },
+                "http://www.w3.org/ns/dcat#keyword": ["staff", "directory"]
+            },
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada"
+            }
+        ]
+    }))
+}
+
+fn table<'a>(tables: &'a [SheetTable], name: &str) -> &'a SheetTable {
+    tables
+        .iter()
+        .find(|table| table.sheet_name == name)
+        .expect("sheet written")
+}
+
+#[test]
+fn the_dataset_node_round_trips_through_its_key_value_sheet() {
+    let nodes = nodes();
+    let workbook = build_workbook(&nodes).expect("workbook built");
+
+    let dataset = table(&workbook.tables, DATASET_SHEET);
+    assert_eq!(dataset.columns, ["key", "value"]);
+    let rows: Vec<[&str; 2]> = dataset
+        .rows
+        .iter()
+        .map(|row| [row[0].as_str(), row[1].as_str()])
+        .collect();
+    assert_eq!(rows[0], ["id", DATASET]);
+    assert!(rows.contains(&["title", "\"People\""]), "{rows:?}");
+    assert!(rows.contains(&["version", "\"1.2\""]));
+    assert!(rows.contains(&["licenseId", "https://creativecommons.org/licenses/by/4.0/"]));
+    assert!(rows.contains(&["defaultGraphId", "https://example.com/graphs/people"]));
+    assert!(rows.contains(&["http://www.w3.org/ns/dcat#keyword", "\"staff\""]));
+    assert!(rows.contains(&["http://www.w3.org/ns/dcat#keyword", "\"directory\""]));
+    let entities = table(&workbook.tables, ENTITIES_SHEET);
+    assert!(entities.rows.iter().all(|row| row[0] != DATASET));
+    assert!(
+        !workbook
+            .tables
+            .iter()
+            .any(|table| table.sheet_name.contains("Dataset_")
+                || table.sheet_name.contains("dcat"))
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("workbook written");
+    let mut read_nodes = excel_read::read_nodes(&path).expect("workbook read");
+    let mut expected = nodes;
+    read_nodes.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+    expected.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+    assert_eq!(read_nodes, expected);
+
+    let excel: Xlsx<_> = open_workbook(&path).expect("workbook opened");
+    let names = excel.defined_names();
+    assert!(
+        names
+            .iter()
+            .any(|(name, formula)| name == "dataset_title" && formula == "Dataset!$B$4"),
+        "{names:?}"
+    );
+    assert!(!names.iter().any(|(name, _)| name.contains("keyword")));
+}
+
+#[test]
+fn edited_dataset_sheets_are_read_with_their_keys() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let dir = temp_dir.path().join("people");
+    csv_write::write_workbook(&dir, &build_workbook(&nodes()).unwrap()).expect("CSV written");
+    let dataset = csv_write::table_path(&dir, DATASET_SHEET);
+    assert!(dataset.ends_with("dataset.csv"));
+    fs::write(
+        &dataset,
+        format!("key,value\nid,{DATASET}\ntitle,Staff\ndescription,\"Everyone, by team\"\n"),
+    )
+    .unwrap();
+
+    let nodes = csv_read::read_nodes(&dir).expect("CSV read");
+    let dataset = nodes
+        .iter()
+        .find(|node| node.id.as_str() == DATASET)
+        .expect("dataset node");
+    assert!(dataset.types.contains("http://www.w3.org/ns/dcat#Dataset"));
+    assert_eq!(
+        dataset.properties["http://purl.org/dc/terms/title"],
+        PropertyValue::Scalar(ScalarValue::String("Staff".into()))
+    );
+    assert_eq!(
+        dataset.properties["http://purl.org/dc/terms/description"],
+        PropertyValue::Scalar(ScalarValue::String("Everyone, by team".into()))
+    );
+    assert_eq!(dataset.properties.len(), 2);
+
+    fs::write(
+        csv_write::table_path(&dir, DATASET_SHEET),
+        "key,value\ntitle,Staff\n",
+    )
+    .unwrap();
+    let err = csv_read::read_nodes(&dir).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("sheet 'Dataset', the dataset has no `id` row"),
+        "{err}"
+    );
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+
+use aideon_tools::aideon::tools::flatten::{ENTITIES_SHEET, WorkbookData, build_workbook};
+use aideon_tools::aideon::tools::io::coercion::ScalarCoercions;
+use aideon_tools::aideon::tools::io::{csv_read, csv_write, excel_read, excel_write};
+use aideon_tools::aideon::tools::validate::{IssueKind, Severity};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{PERSON, parse_jsonld};
+
+fn workbook() -> WorkbookData {
+    let document = json!({
+        "@graph": [
+            { "@id": "https://example.com/people/1", "@type": PERSON, "https://schema.org/name": "Alice" },
+            { "@id": "https://example.com/people/2", "@type": PERSON, "https://schema.org/name": "Bob" },
+            { "@id": "https://example.com/people/3", "@type": PERSON, "https://schema.org/name": "Carol" },
+            { "@id": "https://example.com/acme", "@type": "https://schema.org/Organization" }
+        ]
+    });
+    let nodes = parse_jsonld(&document);
+    build_workbook(&nodes).expect("tables built")
+}
+
+#[test]
+fn entities_sheet_is_checked_against_type_sheets() {
+    let mut workbook = workbook();
+    // Users drop a row, retype a node, and list one that has no row.
+    let entities = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == ENTITIES_SHEET)
+        .expect("entities sheet");
+    entities
+        .rows
+        .retain(|row| row[0] != "https://example.com/people/2");
+    for row in &mut entities.rows {
+        if row[0] == "https://example.com/people/3" {
+            row[1] = "https://schema.org/Employee".into();
+        }
+    }
+    entities.rows.push(vec![
+        "https://example.com/people/9".into(),
+        PERSON.into(),
+        String::new(),
+    ]);
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("workbook written");
+    let (nodes, report) =
+        excel_read::read_nodes_with_report(&path, &ScalarCoercions::default(), &BTreeSet::new())
+            .expect("read");
+
+    let issues: Vec<(&str, &str)> = report
+        .issues
+        .iter()
+        .map(|issue| {
+            assert_eq!(issue.severity, Severity::Warning);
+            assert_eq!(issue.kind, IssueKind::EntityIndexMismatch);
+            (issue.node.as_str(), issue.message.as_str())
+        })
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (
+                "https://example.com/people/2",
+                "has a row on sheet 'https___schema.org_Person' but is missing from the Entities sheet"
+            ),
+            (
+                "https://example.com/people/3",
+                "is listed as https://schema.org/Employee in the Entities sheet but no sheet holds that type"
+            ),
+            (
+                "https://example.com/people/3",
+                "has a row on sheet 'https___schema.org_Person' as https://schema.org/Person but the Entities sheet lists it as https://schema.org/Employee"
+            ),
+            (
+                "https://example.com/people/9",
+                "is listed as https://schema.org/Person in the Entities sheet but has no row on sheet 'https___schema.org_Person'"
+            ),
+        ]
+    );
+
+    // Nodes still get the types of both.
+    let carol = nodes
+        .iter()
+        .find(|node| node.id == "https://example.com/people/3")
+        .expect("carol");
+    assert_eq!(carol.types.len(), 2);
+    assert!(
+        nodes
+            .iter()
+            .any(|node| node.id == "https://example.com/people/2")
+    );
+}
+
+#[test]
+fn consistent_tables_report_nothing() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let dir = temp_dir.path().join("tables");
+    csv_write::write_workbook(&dir, &workbook()).expect("CSV written");
+    let (_, report) =
+        csv_read::read_nodes_with_report(&dir, &ScalarCoercions::default(), &BTreeSet::new())
+            .expect("read");
+    assert!(report.issues.is_empty(), "{:?}", report.issues);
+}
@@ -0,0 +1,208 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::flatten::{WorkbookData, build_workbook};
+use aideon_tools::aideon::tools::io::{excel_read, excel_update, excel_write};
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use calamine::{Data, Reader, Xlsx, open_workbook};
+use rust_xlsxwriter::{Format, Note, Table, TableColumn, Workbook};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const NAME: &str = "https://schema.org/name";
+
+fn people_before() -> Vec<Node> {
+    parse_jsonld(&json!({
+        "@graph": [
+            { "@id": "https://example.com/people/1", "@type": "https://schema.org/Person", "https://schema.org/name": "Alice" },
+            { "@id": "https://example.com/people/2", "@type": "https://schema.org/Person", "https://schema.org/name": "Bob" },
+            { "@id": "https://example.com/people/4", "@type": "https://schema.org/Person", "https://schema.org/name": "Dan" }
+        ]
+    }))
+}
+
+fn people_after() -> Vec<Node> {
+    parse_jsonld(&json!({
+        "@graph": [
+            { "@id": "https://example.com/people/1", "@type": "https://schema.org/Person", "https://schema.org/name": "Alicia", "https://schema.org/age": 31 },
+            { "@id": "https://example.com/people/3", "@type": "https://schema.org/Person", "https://schema.org/name": "Carol" },
+            { "@id": "https://example.com/people/4", "@type": "https://schema.org/Person", "https://schema.org/name": "Dan" },
+            { "@id": "https://example.com/people/5", "@type": "https://schema.org/Person", "https://schema.org/name": "Eve" },
+            { "@id": "https://example.com/acme", "@type": "https://schema.org/Organization", "https://schema.org/name": "Acme" }
+        ]
+    }))
+}
+
+/// Writes `workbook` like `excel_write` does, strings unquoted, then adds what
+/// a user would in Excel: a formatted notes column, a cell note, and a sheet
+/// of their own.
+fn write_user_workbook(path: &Path, workbook: &WorkbookData) {
+    let mut writer = Workbook::new();
+    for table in &workbook.tables {
+        let worksheet = writer.add_worksheet();
+        worksheet.set_name(&table.sheet_name).unwrap();
+        let mut columns = table.columns.clone();
+        let notes = table.sheet_name == "https___schema.org_Person";
+        if notes {
+            columns.push("Notes".to_string());
+        }
+        for (col, header) in columns.iter().enumerate() {
+            worksheet.write_string(0, col as u16, header).unwrap();
+        }
+        for (row, cells) in table.rows.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                let text = serde_json::from_str::<String>(cell).unwrap_or_else(|_| cell.clone());
+                worksheet
+                    .write_string(row as u32 + 1, col as u16, text)
+                    .unwrap();
+            }
+        }
+        if notes {
+            let bold = Format::new().set_bold();
+            let notes_col = columns.len() as u16 - 1;
+            worksheet
+                .write_string_with_format(1, notes_col, "call back", &bold)
+                .unwrap();
+            worksheet
+                .insert_note(1, 0, &Note::new("first customer"))
+                .unwrap();
+        }
+        let table_columns: Vec<TableColumn> = columns
+            .iter()
+            .map(|header| TableColumn::new().set_header(header))
+            .collect();
+        worksheet
+            .add_table(
+                0,
+                0,
+                table.rows.len().max(1) as u32,
+                columns.len() as u16 - 1,
+                &Table::new().set_columns(&table_columns),
+            )
+            .unwrap();
+    }
+    writer
+        .add_worksheet()
+        .set_name("Summary")
+        .unwrap()
+        .write_string(0, 0, "hand written")
+        .unwrap();
+    writer.save(path).unwrap();
+}
+
+fn sheet_rows(path: &Path, sheet: &str) -> Vec<Vec<String>> {
+    let mut workbook: Xlsx<_> = open_workbook(path).expect("workbook opened");
+    let range = workbook.worksheet_range(sheet).expect("sheet read");
+    range
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|cell| match cell {
+                    Data::Empty => String::new(),
+                    other => other.to_string(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn part(path: &Path, name: &str) -> Option<String> {
+    let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+    let mut file = archive.by_name(name).ok()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content).unwrap();
+    Some(content)
+}
+
+#[test]
+fn update_rewrites_changed_rows_and_keeps_user_content() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    write_user_workbook(&path, &build_workbook(&people_before()).unwrap());
+    let comments = part(&path, "xl/comments1.xml").expect("note written");
+
+    let update = sync::update_excel(&people_after(), &path, &SyncOptions::default())
+        .expect("workbook updated");
+    assert_eq!(update.added_sheets, vec!["https___schema.org_Organization"]);
+    assert_eq!(update.updated_rows, 1);
+    assert!(update.deleted_rows >= 1);
+
+    // The workbook reads back as the new dataset, plus the user's column.
+    let fresh = temp_dir.path().join("fresh.xlsx");
+    excel_write::write_workbook(&fresh, &build_workbook(&people_after()).unwrap()).unwrap();
+    let mut updated = excel_read::read_nodes(&path).expect("updated workbook read");
+    for node in &mut updated {
+        node.properties.remove("Notes");
+    }
+    assert_eq!(
+        updated,
+        excel_read::read_nodes(&fresh).expect("fresh workbook read")
+    );
+
+    // Untouched rows keep their position, deleted ones leave their row to
+    // the first insert, and new columns go after the user's.
+    let people = sheet_rows(&path, "https___schema.org_Person");
+    assert_eq!(
+        people[0],
+        ["id", "graph", NAME, "Notes", "https://schema.org/age"]
+    );
+    assert_eq!(
+        people[1],
+        [
+            "https://example.com/people/1",
+            "",
+            "Alicia",
+            "call back",
+            "31"
+        ]
+    );
+    assert_eq!(people[2][0], "https://example.com/people/3");
+    assert_eq!(people[3][..3], ["https://example.com/people/4", "", "Dan"]);
+    assert_eq!(people[4][0], "https://example.com/people/5");
+    assert_eq!(sheet_rows(&path, "Summary"), [["hand written"]]);
+    assert_eq!(part(&path, "xl/comments1.xml"), Some(comments));
+
+    let table = part(&path, "xl/tables/table3.xml").expect("person table");
+    assert!(table.contains("ref=\"A1:E5\""), "{table}");
+    assert!(table.contains("name=\"https://schema.org/age\""), "{table}");
+
+    // Updating with the same data leaves the file alone.
+    let before = fs::read(&path).unwrap();
+    let update = excel_update::update_workbook(&path, &build_workbook(&people_after()).unwrap())
+        .expect("second update");
+    assert!(update.is_empty(), "{update:?}");
+    assert_eq!(fs::read(&path).unwrap(), before);
+}
+
+#[test]
+fn update_clears_retired_columns_and_sheets() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    sync::update_excel(&people_after(), &path, &SyncOptions::default()).expect("workbook written");
+
+    let remaining = parse_jsonld(&json!({
+        "@graph": [
+            { "@id": "https://example.com/people/1", "@type": "https://schema.org/Person", "https://schema.org/name": "Alicia" }
+        ]
+    }));
+    let update =
+        sync::update_excel(&remaining, &path, &SyncOptions::default()).expect("workbook updated");
+    assert!(update.added_sheets.is_empty());
+    assert_eq!(excel_read::read_nodes(&path).unwrap(), remaining);
+
+    let people = sheet_rows(&path, "https___schema.org_Person");
+    assert_eq!(people[0][2], "https://schema.org/age");
+    assert_eq!(people[1][2], "");
+    let organizations = sheet_rows(&path, "https___schema.org_Organization");
+    assert!(
+        organizations
+            .iter()
+            .skip(1)
+            .all(|row| row.iter().all(String::is_empty)),
+        "{organizations:?}"
+    );
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::{
+    METADATA_SHEET, WorkbookOptions, build_workbook_with_options,
+};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write, jsonld};
+use aideon_tools::aideon::tools::model::{Iri, Node};
+use calamine::{Data, Reader, Xlsx, open_workbook};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld, people};
+
+fn emailed_people() -> Vec<Node> {
+    let mut document = people();
+    document["@graph"][0]["https://schema.org/email"] = json!("ada@example.com");
+    parse_jsonld(&document)
+}
+
+#[test]
+fn context_terms_head_columns_and_read_back() {
+    let nodes = emailed_people();
+    let options = WorkbookOptions {
+        header_terms: BTreeMap::from([
+            (Iri::from("https://schema.org/name"), "name".to_string()),
+            (Iri::from("https://schema.org/knows"), "knows".to_string()),
+        ]),
+        ..WorkbookOptions::default()
+    };
+    let workbook = build_workbook_with_options(&nodes, &options).expect("workbook built");
+    let people = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == "https___schema.org_Person")
+        .expect("Person sheet");
+    for header in ["name", "knowsId", "https://schema.org/email"] {
+        assert!(
+            people.columns.iter().any(|column| column == header),
+            "{header} in {:?}",
+            people.columns
+        );
+    }
+    let metadata = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == METADATA_SHEET)
+        .expect("Metadata sheet");
+    let terms: Vec<(&str, &str)> = metadata
+        .rows
+        .iter()
+        .filter(|row| row[0] == "term")
+        .map(|row| (row[4].as_str(), row[3].as_str()))
+        .collect();
+    assert_eq!(
+        terms,
+        [
+            ("knows", "https://schema.org/knows"),
+            ("name", "https://schema.org/name")
+        ]
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("written");
+    assert_eq!(excel_read::read_nodes(&path).expect("read back"), nodes);
+}
+
+#[test]
+fn sync_heads_columns_with_the_given_context() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    let document = jsonld::nodes_to_jsonld(&emailed_people(), None).unwrap();
+    fs::write(&input, document.to_string()).unwrap();
+    let context = temp_dir.path().join("context.json");
+    fs::write(
+        &context,
+        json!({
+            "schema": "https://schema.org/",
+            "name": "schema:name",
+            "knows": { "@id": "schema:knows", "@type": "@id" }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let output = temp_dir.path().join("people.xlsx");
+
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "excel", "--output"])
+        .arg(&output)
+        .arg("--context")
+        .arg(&context)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    let mut workbook: Xlsx<_> = open_workbook(&output).expect("workbook opened");
+    let range = workbook
+        .worksheet_range("https___schema.org_Person")
+        .expect("Person sheet");
+    let headers: Vec<String> = range
+        .rows()
+        .next()
+        .expect("header row")
+        .iter()
+        .map(Data::to_string)
+        .collect();
+    assert!(headers.contains(&"name".to_string()), "{headers:?}");
+    assert!(headers.contains(&"knowsId".to_string()), "{headers:?}");
+}
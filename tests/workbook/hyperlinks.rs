@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::aideon::tools::model::Node;
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const PEOPLE: &str = "https___schema.org_Person";
+const ORGANIZATIONS: &str = "https___schema.org_Organization";
+
+fn nodes() -> Vec<Node> {
+    parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/worksFor": { "@id": "https://example.com/acme" },
+                "https://schema.org/spouse": { "@id": "https://example.com/people/2" },
+                "https://schema.org/knows": [
+                    { "@id": "https://example.com/outsider" },
+                    { "@id": "https://example.com/people/2" }
+                ]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person"
+            },
+            {
+                "@id": "https://example.com/acme",
+                "@type": "https://schema.org/Organization",
+                "https://schema.org/name": "ACME"
+            }
+        ]
+    }))
+}
+
+/// Returns the `(cell, location)` of the hyperlinks of each sheet of the
+/// workbook at `path`, by sheet name.
+fn hyperlinks(path: &Path) -> BTreeMap<String, Vec<(String, String)>> {
+    let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+    let mut read = |name: &str| {
+        let mut xml = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut xml)
+            .unwrap();
+        xml
+    };
+    let attribute = |element: &str, name: &str| {
+        element
+            .split(&format!(" {name}=\""))
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap_or_default()
+            .replace("&apos;", "'")
+    };
+    let workbook = read("xl/workbook.xml");
+    let names: Vec<String> = workbook
+        .split("<sheet name=\"")
+        .skip(1)
+        .map(|rest| rest.split('"').next().unwrap().to_string())
+        .collect();
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let sheet = read(&format!("xl/worksheets/sheet{}.xml", index + 1));
+            let links = sheet
+                .split("<hyperlink ")
+                .skip(1)
+                .map(|element| format!(" {element}"))
+                .map(|element| (attribute(&element, "ref"), attribute(&element, "location")))
+                .collect();
+            (name, links)
+        })
+        .collect()
+}
+
+#[test]
+fn references_and_entities_link_to_the_rows_of_their_nodes() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    let workbook = build_workbook(&nodes()).unwrap();
+    excel_write::write_workbook(&path, &workbook).expect("written");
+
+    let people = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == PEOPLE)
+        .unwrap();
+    let column = |header: &str| {
+        let index = people
+            .columns
+            .iter()
+            .position(|column| column == header)
+            .unwrap();
+        char::from(b'A' + index as u8)
+    };
+    let links = hyperlinks(&path);
+    let person_links = &links[PEOPLE];
+    assert!(
+        person_links.contains(&(
+            format!("{}2", column("https://schema.org/worksForId")),
+            format!("'{ORGANIZATIONS}'!A2")
+        )),
+        "{person_links:?}"
+    );
+    assert!(
+        person_links.contains(&(
+            format!("{}2", column("https://schema.org/spouseId")),
+            format!("'{PEOPLE}'!A3")
+        )),
+        "{person_links:?}"
+    );
+    assert_eq!(person_links.len(), 2);
+
+    let entities = &links["Entities"];
+    assert_eq!(entities.len(), 3, "{entities:?}");
+    assert!(entities.contains(&("A2".to_string(), format!("'{ORGANIZATIONS}'!A2"))));
+
+    let mut knows = links
+        .iter()
+        .find(|(name, _)| name.starts_with("https___schema.org_Person_"))
+        .map(|(_, links)| links.clone())
+        .expect("child sheet");
+    knows.sort();
+    assert_eq!(
+        knows,
+        [
+            ("A2".to_string(), format!("'{PEOPLE}'!A2")),
+            ("A3".to_string(), format!("'{PEOPLE}'!A2")),
+            ("C3".to_string(), format!("'{PEOPLE}'!A3")),
+        ],
+        "the outsider has no row to link to"
+    );
+
+    let mut read = excel_read::read_nodes(&path).expect("workbook read");
+    let mut expected = nodes();
+    read.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+    expected.sort_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+    assert_eq!(read, expected);
+}
+
+#[test]
+fn chunked_exports_are_written_without_links() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.nq");
+    fs::write(
+        &input,
+        "<https://example.com/people/1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://schema.org/Person> .\n\
+         <https://example.com/people/1> <https://schema.org/spouse> <https://example.com/people/2> .\n\
+         <https://example.com/people/2> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://schema.org/Person> .\n",
+    )
+    .unwrap();
+
+    let whole = temp_dir.path().join("whole.xlsx");
+    sync::rdf_to_excel_with_options(&input, &whole, &SyncOptions::default())
+        .expect("whole workbook");
+    assert_eq!(hyperlinks(&whole)[PEOPLE].len(), 1);
+
+    let chunked = temp_dir.path().join("chunked.xlsx");
+    let options = SyncOptions {
+        chunk_size: Some(1),
+        ..SyncOptions::default()
+    };
+    sync::rdf_to_excel_with_options(&input, &chunked, &options).expect("chunked workbook");
+    assert!(hyperlinks(&chunked).values().all(Vec::is_empty));
+    assert_eq!(
+        excel_read::read_nodes(&chunked).unwrap(),
+        excel_read::read_nodes(&whole).unwrap()
+    );
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::config::{CONFIG_FILE, Profile};
+use aideon_tools::aideon::tools::flatten::{
+    METADATA_SHEET, WorkbookOptions, build_workbook_with_options, check_labels,
+};
+use aideon_tools::aideon::tools::io::excel_write;
+use aideon_tools::aideon::tools::model::{Iri, Node};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use aideon_tools::{Result, ToolError};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{parse_jsonld, people, references};
+
+/// Returns [`people`] with Ada's employer and a second acquaintance, and
+/// Grace's email.
+fn staff() -> Vec<Node> {
+    let mut document = people();
+    let ada = &mut document["@graph"][0];
+    ada["https://schema.org/worksFor"] = json!({ "@id": "https://example.com/acme" });
+    ada["https://schema.org/knows"] = references(&[2, 3]);
+    document["@graph"][1]["https://schema.org/email"] = json!("grace@example.com");
+    parse_jsonld(&document)
+}
+
+fn load(config: &Value) -> Result<Profile> {
+    let temp_dir = tempdir().expect("temporary directory");
+    fs::write(
+        temp_dir.path().join(CONFIG_FILE),
+        toml::to_string(config).unwrap(),
+    )
+    .unwrap();
+    Profile::discover(temp_dir.path()).map(|profile| profile.expect("profile found"))
+}
+
+fn french() -> Value {
+    json!({
+        "prefixes": { "schema": "https://schema.org/" },
+        "labels": {
+            "fr": {
+                "schema:Person": "Personne",
+                "schema:name": "Nom",
+                "schema:worksFor": "Employeur",
+                "schema:knows": "Connaît"
+            },
+            "de": { "schema:Person": "Person" }
+        },
+        "language": "fr"
+    })
+}
+
+#[test]
+fn labels_name_sheets_and_columns_while_metadata_keeps_iris() {
+    let mut options = SyncOptions::default();
+    load(&french()).expect("profile loaded").apply(&mut options);
+    let workbook =
+        build_workbook_with_options(&staff(), &options.workbook_options()).expect("tables built");
+
+    let names: Vec<&str> = workbook
+        .tables
+        .iter()
+        .map(|table| table.sheet_name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        ["Entities", "Metadata", "Personne", "Personne__Connaît"]
+    );
+    let people_sheet = &workbook.tables[2];
+    assert_eq!(
+        people_sheet.columns,
+        [
+            "id",
+            "graph",
+            "EmployeurId",
+            "Nom",
+            "https://schema.org/email"
+        ]
+    );
+    assert_eq!(workbook.tables[3].columns[2], "ConnaîtId");
+    let metadata = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == METADATA_SHEET)
+        .unwrap();
+    assert!(metadata.rows.iter().any(|row| row[..5]
+        == [
+            "column",
+            "Personne",
+            "https://schema.org/Person",
+            "https://schema.org/name",
+            "Nom"
+        ]));
+    assert!(metadata.rows.iter().any(|row| row[..4]
+        == [
+            "child",
+            "Personne__Connaît",
+            "https://schema.org/Person",
+            "https://schema.org/knows"
+        ]));
+
+    // The localised workbook reads back as the original graph.
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("personnes.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("workbook written");
+    assert_eq!(
+        sync::read_excel(&path, &SyncOptions::default()).expect("workbook read"),
+        staff()
+    );
+}
+
+#[test]
+fn clashing_or_unknown_labels_are_rejected() {
+    let labels = |pairs: &[(&str, &str)]| -> BTreeMap<Iri, String> {
+        pairs
+            .iter()
+            .map(|(iri, label)| (Iri::from(*iri), label.to_string()))
+            .collect()
+    };
+    let shared = labels(&[
+        ("https://schema.org/name", "Nom"),
+        ("https://schema.org/familyName", "Nom"),
+    ]);
+    let err = check_labels(&shared).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid label translations: label 'Nom' is given to both \
+         https://schema.org/familyName and https://schema.org/name"
+    );
+    assert!(matches!(
+        check_labels(&labels(&[("https://schema.org/parent", "Parent")])),
+        Err(ToolError::InvalidLabels(_))
+    ));
+    let options = WorkbookOptions {
+        labels: shared,
+        ..WorkbookOptions::default()
+    };
+    assert!(matches!(
+        build_workbook_with_options(&staff(), &options),
+        Err(ToolError::InvalidLabels(_))
+    ));
+
+    let mut config = french();
+    config["language"] = json!("es");
+    let Err(err) = load(&config) else {
+        panic!("unknown language accepted");
+    };
+    assert_eq!(
+        err.to_string(),
+        "invalid configuration: no labels for language 'es'"
+    );
+    let profile = load(&french()).expect("profile loaded");
+    assert_eq!(
+        profile.labels("de").unwrap()["https://schema.org/Person"],
+        "Person"
+    );
+
+    // Long labels are cut to Excel's limit without splitting characters.
+    let long = labels(&[("https://schema.org/Person", &"é".repeat(40))]);
+    let options = WorkbookOptions {
+        labels: long,
+        ..WorkbookOptions::default()
+    };
+    let workbook = build_workbook_with_options(&staff(), &options).expect("tables built");
+    assert!(
+        workbook
+            .tables
+            .iter()
+            .any(|table| table.sheet_name == "é".repeat(31))
+    );
+}
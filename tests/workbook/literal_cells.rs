@@ -0,0 +1,78 @@
+use aideon_tools::aideon::tools::flatten::{CellEncoding, build_workbook_with};
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue};
+
+const VALUE: &str = "https://example.com/value";
+
+/// Returns the cell each of `scalars` is written to under `encoding`.
+fn cells(scalars: &[ScalarValue], encoding: CellEncoding) -> Vec<String> {
+    let nodes: Vec<Node> = scalars
+        .iter()
+        .enumerate()
+        .map(|(index, scalar)| {
+            let mut node = Node::new(format!("https://example.com/{index:03}"));
+            node.types.insert("https://example.com/Thing".into());
+            node.insert_property(VALUE.into(), PropertyValue::Scalar(scalar.clone()));
+            node
+        })
+        .collect();
+    let workbook = build_workbook_with(&nodes, encoding).expect("workbook built");
+    let table = workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == "https___example.com_Thing")
+        .expect("type sheet");
+    let column = table
+        .columns
+        .iter()
+        .position(|column| column == VALUE)
+        .expect("value column");
+    table.rows.iter().map(|row| row[column].clone()).collect()
+}
+
+#[test]
+fn literal_cells_hold_the_json_serde_gives_them() {
+    let scalars = [
+        ScalarValue::String("Ada".into()),
+        ScalarValue::String(String::new()),
+        ScalarValue::String("say \"hi\"\\ bye".into()),
+        ScalarValue::String("tab\there\nline\u{1f}".into()),
+        ScalarValue::String("Zürich / 東京 \u{7f}".into()),
+        ScalarValue::Integer(0),
+        ScalarValue::Integer(-42),
+        ScalarValue::Integer(i64::MAX),
+        ScalarValue::Integer(i64::MIN),
+        ScalarValue::Double(30.0),
+        ScalarValue::Double(-0.1),
+        ScalarValue::Double(1e300),
+        ScalarValue::Double(5e-324),
+        ScalarValue::Double(f64::NAN),
+        ScalarValue::Boolean(true),
+        ScalarValue::Boolean(false),
+        ScalarValue::Null,
+        ScalarValue::lang_tagged("Wien", "de"),
+    ];
+    let expected: Vec<String> = scalars
+        .iter()
+        .map(|scalar| serde_json::to_string(&scalar.to_json()).unwrap())
+        .collect();
+    assert_eq!(cells(&scalars, CellEncoding::Json), expected);
+}
+
+#[test]
+fn bare_cells_are_prefixed_only_when_they_would_read_as_json() {
+    let texts = [
+        "Ada", "30", "-1", "true", "null", "[1]", "{}", "\"q\"", "", "  ", "'quoted", "Ada 30",
+        "nullable", "-", "tr", " 30",
+    ];
+    let scalars: Vec<ScalarValue> = texts
+        .iter()
+        .map(|text| ScalarValue::String(text.to_string()))
+        .collect();
+    assert_eq!(
+        cells(&scalars, CellEncoding::Bare),
+        [
+            "Ada", "'30", "'-1", "'true", "'null", "'[1]", "'{}", "'\"q\"", "'", "'  ", "''quoted",
+            "Ada 30", "nullable", "-", "tr", "' 30",
+        ]
+    );
+}
@@ -0,0 +1,28 @@
+//! Integration tests of the workbooks: how graphs flatten into sheets and
+//! how the sheets read back.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod cardinality;
+mod cell_errors;
+mod column_defaults;
+mod column_groups;
+mod column_types;
+mod conflicting_cells;
+mod context_sheet;
+mod data_validation;
+mod dataset_sheet;
+mod entities_check;
+mod excel_update;
+mod header_terms;
+mod hyperlinks;
+mod labels;
+mod literal_cells;
+mod matrix_sheets;
+mod native_cells;
+mod preview;
+mod relationship_attributes;
+mod sheet_names;
+mod subclass_sheets;
+mod workbook_diff;
@@ -0,0 +1,196 @@
+use std::collections::BTreeSet;
+
+use aideon_tools::aideon::tools::flatten::{
+    MATRIX_MARK, METADATA_SHEET, SheetTable, WorkbookData, WorkbookOptions,
+    build_workbook_with_options,
+};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{ArrayValue, Iri, Node, PropertyValue};
+use aideon_tools::aideon::tools::sync::{self, SyncOptions};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const MEMBER_OF: &str = "https://schema.org/memberOf";
+const MATRIX_SHEET: &str = "https___schema.org_Person__http";
+
+fn members() -> Vec<Node> {
+    parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/memberOf": [
+                    { "@id": "https://example.com/orgs/acme" },
+                    { "@id": "https://example.com/orgs/globex" }
+                ]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Bob",
+                "https://schema.org/memberOf": [
+                    { "@id": "https://example.com/orgs/globex" },
+                    { "@id": "https://example.com/orgs/initech" }
+                ]
+            }
+        ]
+    }))
+}
+
+fn options() -> WorkbookOptions {
+    WorkbookOptions {
+        matrix_predicates: BTreeSet::from([Iri::from(MEMBER_OF)]),
+        ..WorkbookOptions::default()
+    }
+}
+
+fn table<'a>(workbook: &'a WorkbookData, name: &str) -> &'a SheetTable {
+    workbook
+        .tables
+        .iter()
+        .find(|table| table.sheet_name == name)
+        .unwrap_or_else(|| panic!("sheet {name}"))
+}
+
+fn memberships(nodes: &[Node]) -> Vec<(String, Vec<String>)> {
+    let mut memberships: Vec<_> = nodes
+        .iter()
+        .filter_map(|node| match node.properties.get(MEMBER_OF) {
+            Some(PropertyValue::Array(ArrayValue::ObjectRefs(targets))) => {
+                let mut targets: Vec<String> = targets.iter().map(|id| id.to_string()).collect();
+                targets.sort();
+                Some((node.id.to_string(), targets))
+            }
+            _ => None,
+        })
+        .collect();
+    memberships.sort();
+    memberships
+}
+
+#[test]
+fn matrix_sheets_mark_links_and_read_back() {
+    let mut workbook = build_workbook_with_options(&members(), &options()).expect("tables built");
+
+    let matrix = table(&workbook, MATRIX_SHEET);
+    assert_eq!(
+        matrix.columns,
+        [
+            "id",
+            "graph",
+            "https://example.com/orgs/acme",
+            "https://example.com/orgs/globex",
+            "https://example.com/orgs/initech",
+        ]
+    );
+    assert_eq!(
+        matrix.rows,
+        [
+            [
+                "https://example.com/people/1",
+                "",
+                MATRIX_MARK,
+                MATRIX_MARK,
+                ""
+            ],
+            [
+                "https://example.com/people/2",
+                "",
+                "",
+                MATRIX_MARK,
+                MATRIX_MARK
+            ],
+        ]
+    );
+    assert!(
+        table(&workbook, METADATA_SHEET)
+            .rows
+            .iter()
+            .any(|row| row[0] == "matrix" && row[1] == MATRIX_SHEET && row[3] == MEMBER_OF)
+    );
+    assert!(
+        workbook
+            .tables
+            .iter()
+            .all(|table| !table.columns.contains(&format!("{MEMBER_OF}Id")))
+    );
+
+    // Curators tick and clear cells, in any case, and add target columns.
+    let matrix = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == MATRIX_SHEET)
+        .unwrap();
+    matrix
+        .columns
+        .push("https://example.com/orgs/umbrella".into());
+    matrix.rows[0][3].clear();
+    matrix.rows[0].push("x".into());
+    matrix.rows[1].push(String::new());
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("members.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("workbook written");
+    let restored = excel_read::read_nodes(&path).expect("workbook read");
+    assert_eq!(
+        memberships(&restored),
+        [
+            (
+                "https://example.com/people/1".to_string(),
+                vec![
+                    "https://example.com/orgs/acme".to_string(),
+                    "https://example.com/orgs/umbrella".to_string(),
+                ]
+            ),
+            (
+                "https://example.com/people/2".to_string(),
+                vec![
+                    "https://example.com/orgs/globex".to_string(),
+                    "https://example.com/orgs/initech".to_string(),
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn updated_matrix_sheets_clear_targets_no_longer_linked() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("members.xlsx");
+    let options = SyncOptions {
+        matrix_predicates: options().matrix_predicates,
+        ..SyncOptions::default()
+    };
+    sync::update_excel(&members(), &path, &options).expect("workbook written");
+
+    let after = parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/memberOf": [
+                    { "@id": "https://example.com/orgs/globex" },
+                    { "@id": "https://example.com/orgs/initech" }
+                ]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Bob",
+                "https://schema.org/memberOf": [
+                    { "@id": "https://example.com/orgs/globex" },
+                    { "@id": "https://example.com/orgs/initech" }
+                ]
+            }
+        ]
+    }));
+    let update = sync::update_excel(&after, &path, &options).expect("workbook updated");
+    assert_eq!(update.updated_rows, 1);
+    assert_eq!(update.inserted_rows + update.deleted_rows, 0);
+    assert_eq!(
+        memberships(&excel_read::read_nodes(&path).expect("workbook read")),
+        memberships(&after)
+    );
+}
@@ -0,0 +1,174 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{Node, PropertyValue, ScalarValue};
+use calamine::{Data, Reader, Xlsx, open_workbook};
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const PERSON_SHEET: &str = "https___schema.org_Person";
+const BIRTH_DATE: &str = "https://schema.org/birthDate";
+const FOUNDED: &str = "https://schema.org/foundingYear";
+const XSD_DATE: &str = "http://www.w3.org/2001/XMLSchema#date";
+const XSD_GYEAR: &str = "http://www.w3.org/2001/XMLSchema#gYear";
+
+fn typed_person() -> Vec<Node> {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Alice",
+                "https://schema.org/age": 30,
+                "https://schema.org/active": true,
+                "https://schema.org/code": "30",
+                "https://schema.org/note": "'quoted",
+                "https://schema.org/tags": ["a", "b"],
+                "https://schema.org/birthDate": { "@value": "1990-04-02", "@type": XSD_DATE },
+                "https://schema.org/foundingYear": { "@value": "1999", "@type": XSD_GYEAR }
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+fn person_cells(path: &Path) -> Vec<(String, Data)> {
+    let mut workbook: Xlsx<_> = open_workbook(path).expect("workbook opened");
+    let range = workbook.worksheet_range(PERSON_SHEET).expect("sheet read");
+    let mut rows = range.rows();
+    let header = rows.next().expect("header row").to_vec();
+    let row = rows.next().expect("person row").to_vec();
+    header
+        .into_iter()
+        .map(|cell| cell.to_string())
+        .zip(row)
+        .collect()
+}
+
+#[test]
+fn single_literals_are_written_as_typed_cells() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    let nodes = typed_person();
+    excel_write::write_workbook(&path, &build_workbook(&nodes).unwrap()).expect("written");
+
+    let cells = person_cells(&path);
+    let cell = |name: &str| {
+        cells
+            .iter()
+            .find(|(header, _)| header == &format!("https://schema.org/{name}"))
+            .map(|(_, cell)| cell.clone())
+            .unwrap_or_else(|| panic!("{name} column"))
+    };
+    assert_eq!(cell("name"), Data::String("Alice".into()));
+    assert_eq!(cell("age"), Data::Float(30.0));
+    assert_eq!(cell("active"), Data::Bool(true));
+    // Text that would read back as something else keeps its quotes.
+    assert_eq!(cell("code"), Data::String("\"30\"".into()));
+    assert_eq!(cell("note"), Data::String("\"'quoted\"".into()));
+    assert_eq!(cell("tags"), Data::String("[\"a\",\"b\"]".into()));
+    assert_eq!(
+        cells[0].1,
+        Data::String("https://example.com/people/1".into())
+    );
+
+    assert_eq!(excel_read::read_nodes(&path).expect("read back"), nodes);
+}
+
+#[test]
+fn native_cells_typed_in_excel_follow_the_column_datatype() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    let workbook = build_workbook(&typed_person()).unwrap();
+
+    // Rewrite the dated columns the way a user would enter them in Excel.
+    let mut writer = Workbook::new();
+    let date_format = Format::new().set_num_format("yyyy-mm-dd");
+    for table in &workbook.tables {
+        let worksheet = writer.add_worksheet();
+        worksheet.set_name(&table.sheet_name).unwrap();
+        for (col, header) in table.columns.iter().enumerate() {
+            worksheet.write_string(0, col as u16, header).unwrap();
+        }
+        for (row, cells) in table.rows.iter().enumerate() {
+            let row = row as u32 + 1;
+            for (col, cell) in cells.iter().enumerate() {
+                let col = col as u16;
+                match table.columns[col as usize].as_str() {
+                    BIRTH_DATE if table.sheet_name == PERSON_SHEET => {
+                        let date = ExcelDateTime::from_ymd(1990, 4, 2).unwrap();
+                        worksheet
+                            .write_datetime_with_format(row, col, &date, &date_format)
+                            .unwrap();
+                    }
+                    FOUNDED if table.sheet_name == PERSON_SHEET => {
+                        worksheet.write_number(row, col, 1999).unwrap();
+                    }
+                    _ => {
+                        worksheet.write_string(row, col, cell).unwrap();
+                    }
+                }
+            }
+        }
+    }
+    writer.save(&path).unwrap();
+
+    let restored = excel_read::read_nodes(&path).expect("workbook read");
+    let value = |predicate: &str| restored[0].properties.get(predicate).cloned();
+    assert_eq!(
+        value(BIRTH_DATE),
+        Some(PropertyValue::Scalar(ScalarValue::from_lexical(
+            "1990-04-02",
+            XSD_DATE
+        )))
+    );
+    assert_eq!(
+        value(FOUNDED),
+        Some(PropertyValue::Scalar(ScalarValue::from_lexical(
+            "1999", XSD_GYEAR
+        )))
+    );
+    assert_eq!(restored, typed_person());
+}
+
+#[test]
+fn boolean_columns_are_checkboxes_and_read_excel_text() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    let mut workbook = build_workbook(&typed_person()).unwrap();
+    excel_write::write_workbook(&path, &workbook).expect("written");
+    let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+    let mut styles = String::new();
+    archive
+        .by_name("xl/styles.xml")
+        .unwrap()
+        .read_to_string(&mut styles)
+        .unwrap();
+    assert!(styles.contains("xfpb:xfComplement"), "{styles}");
+
+    // Excel saves booleans typed into text cells, or exported to CSV, as
+    // `TRUE` and `FALSE`.
+    let person = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == PERSON_SHEET)
+        .unwrap();
+    let active = person
+        .columns
+        .iter()
+        .position(|column| column == "https://schema.org/active")
+        .unwrap();
+    person.rows[0][active] = "FALSE".to_string();
+    excel_write::write_workbook(&path, &workbook).expect("rewritten");
+    let restored = excel_read::read_nodes(&path).expect("workbook read");
+    assert_eq!(
+        restored[0].properties.get("https://schema.org/active"),
+        Some(&PropertyValue::Scalar(ScalarValue::Boolean(false)))
+    );
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+
+use aideon_tools::aideon::tools::flatten::build_workbook;
+use aideon_tools::aideon::tools::io::coercion::ScalarCoercions;
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{Node, PropertyValue};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+const KNOWS: &str = "https://schema.org/knows";
+
+fn five_authors() -> Vec<Node> {
+    let people: Vec<Value> = (1..=5)
+        .map(|index| {
+            json!({
+                "@id": format!("https://example.com/people/{index}"),
+                "@type": ["https://schema.org/Person", "https://schema.org/Author"],
+                "https://schema.org/name": format!("Person {index}"),
+                "https://schema.org/knows": [
+                    { "@id": format!("https://example.com/people/{}", index % 5 + 1) },
+                    { "@id": format!("https://example.com/people/{}", (index + 1) % 5 + 1) }
+                ]
+            })
+        })
+        .collect();
+    parse_jsonld(&json!({ "@graph": people }))
+}
+
+#[test]
+fn previews_read_the_first_rows_and_what_they_describe() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    let nodes = five_authors();
+    excel_write::write_workbook(&path, &build_workbook(&nodes).unwrap()).expect("written");
+
+    let sample =
+        excel_read::read_nodes_preview(&path, &ScalarCoercions::default(), &BTreeSet::new(), 2)
+            .expect("sample read");
+    let ids: Vec<&str> = sample.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "https://example.com/people/1",
+            "https://example.com/people/2"
+        ]
+    );
+    for (node, original) in sample.iter().zip(&nodes) {
+        assert_eq!(node.types, original.types, "both types are sampled");
+        assert!(
+            matches!(node.properties[KNOWS], PropertyValue::Array(_)),
+            "{:?}",
+            node.properties[KNOWS]
+        );
+    }
+    assert_eq!(sample[0], nodes[0]);
+
+    let whole =
+        excel_read::read_nodes_preview(&path, &ScalarCoercions::default(), &BTreeSet::new(), 10)
+            .unwrap();
+    assert_eq!(whole, excel_read::read_nodes(&path).unwrap());
+}
+
+#[test]
+fn sync_preview_prints_a_sample() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &build_workbook(&five_authors()).unwrap()).expect("written");
+
+    let run = aideon()
+        .args(["sync", "--from", "excel", "--input"])
+        .arg(&path)
+        .args(["--to", "json-ld", "--preview", "1"])
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    let document: Value = serde_json::from_slice(&run.stdout).expect("JSON-LD printed");
+    let nodes = parse_jsonld(&document);
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].id.as_str(), "https://example.com/people/1");
+
+    let input = temp_dir.path().join("people.jsonld");
+    std::fs::write(&input, "{}").unwrap();
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "rdf", "--preview", "1"])
+        .output()
+        .expect("CLI ran");
+    assert!(!run.status.success());
+    assert!(
+        String::from_utf8_lossy(&run.stderr).contains("--preview reads workbooks"),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+}
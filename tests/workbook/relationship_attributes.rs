@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use aideon_tools::aideon::tools::flatten::{METADATA_SHEET, WorkbookData, build_workbook};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write};
+use aideon_tools::aideon::tools::model::{ArrayValue, Node, PropertyValue, ScalarValue};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::parse_jsonld;
+
+const MEMBER_OF: &str = "https://schema.org/memberOf";
+const ROLE: &str = "https://schema.org/roleName";
+const START: &str = "https://schema.org/startDate";
+const XSD_DATE: &str = "http://www.w3.org/2001/XMLSchema#date";
+
+fn members() -> Vec<Node> {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/memberOf": [
+                    { "@id": "https://example.com/orgs/acme" },
+                    { "@id": "https://example.com/orgs/globex" }
+                ]
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+fn write_and_read(path: &Path, nodes: &[Node]) -> Vec<Node> {
+    let mut workbook = build_workbook(nodes).expect("tables built");
+    edit_workbook(&mut workbook);
+    excel_write::write_workbook(path, &workbook).expect("workbook written");
+    excel_read::read_nodes(path).expect("workbook read")
+}
+
+/// Adds relationship columns to the memberOf child sheet, as a user would,
+/// and declares the start date column in `Metadata`.
+fn edit_workbook(workbook: &mut WorkbookData) {
+    let child = workbook
+        .tables
+        .iter_mut()
+        .find(|table| {
+            table.columns.last().map(String::as_str) == Some("https://schema.org/memberOfId")
+        })
+        .expect("child sheet");
+    let sheet = child.sheet_name.clone();
+    child.columns.extend([ROLE.to_string(), START.to_string()]);
+    for row in &mut child.rows {
+        let attributes = if row[2] == "https://example.com/orgs/acme" {
+            ["\"treasurer\"", "2021-03-01"]
+        } else {
+            ["", ""]
+        };
+        row.extend(attributes.map(str::to_string));
+    }
+
+    let metadata = workbook
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == METADATA_SHEET)
+        .expect("metadata sheet");
+    metadata.rows.push(
+        [
+            "column",
+            &sheet,
+            "https://schema.org/Person",
+            START,
+            START,
+            "scalar",
+            XSD_DATE,
+        ]
+        .map(str::to_string)
+        .to_vec(),
+    );
+}
+
+#[test]
+fn child_sheet_columns_qualify_relationships() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("members.xlsx");
+    let restored = write_and_read(&path, &members());
+
+    let person = restored
+        .iter()
+        .find(|node| node.id == "https://example.com/people/1")
+        .expect("person");
+    let Some(PropertyValue::Array(ArrayValue::ObjectRefs(targets))) =
+        person.properties.get(MEMBER_OF)
+    else {
+        panic!("memberOf references: {person:?}");
+    };
+    assert_eq!(targets.len(), 2);
+    assert!(targets[0].starts_with("urn:uuid:"), "{targets:?}");
+    assert_eq!(targets[1], "https://example.com/orgs/globex");
+
+    // The intermediate node links to the organisation and carries the
+    // relationship's attributes.
+    let role = restored
+        .iter()
+        .find(|node| node.id == targets[0])
+        .expect("relationship node");
+    assert!(role.types.is_empty());
+    assert_eq!(
+        role.properties.get(MEMBER_OF),
+        Some(&PropertyValue::ObjectRef(
+            "https://example.com/orgs/acme".into()
+        ))
+    );
+    assert_eq!(
+        role.properties.get(ROLE),
+        Some(&PropertyValue::Scalar(ScalarValue::String(
+            "treasurer".into()
+        )))
+    );
+    assert_eq!(
+        role.properties.get(START),
+        Some(&PropertyValue::Scalar(ScalarValue::from_lexical(
+            "2021-03-01",
+            XSD_DATE
+        )))
+    );
+
+    // Written again, the intermediate node is an ordinary node and reads
+    // back unchanged, up to the order of the references.
+    let again = temp_dir.path().join("again.xlsx");
+    excel_write::write_workbook(&again, &build_workbook(&restored).unwrap()).unwrap();
+    let again = excel_read::read_nodes(&again).unwrap();
+    assert_eq!(again[1], *role);
+    let Some(PropertyValue::Array(ArrayValue::ObjectRefs(mut again_targets))) =
+        again[0].properties.get(MEMBER_OF).cloned()
+    else {
+        panic!("memberOf references: {:?}", again[0]);
+    };
+    let mut targets = targets.clone();
+    targets.sort();
+    again_targets.sort();
+    assert_eq!(again_targets, targets);
+}
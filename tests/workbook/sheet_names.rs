@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::{
+    SheetNaming, WorkbookOptions, build_workbook_with_options,
+};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write, jsonld};
+use aideon_tools::aideon::tools::model::{Iri, Node};
+use calamine::{Reader, Xlsx, open_workbook};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+fn mixed_vocabularies() -> Vec<Node> {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada",
+                "https://schema.org/knows": [
+                    { "@id": "https://example.com/people/2" },
+                    { "@id": "https://example.com/people/3" }
+                ]
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "http://xmlns.com/foaf/0.1/Person",
+                "http://xmlns.com/foaf/0.1/nick": "bob"
+            },
+            {
+                "@id": "https://example.com/orgs/1",
+                "@type": "https://schema.org/Organization",
+                "https://schema.org/name": "Acme"
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+#[test]
+fn local_names_and_mapped_names_head_the_sheets() {
+    let nodes = mixed_vocabularies();
+    let options = WorkbookOptions {
+        sheet_naming: SheetNaming::LocalName,
+        sheet_names: BTreeMap::from([(
+            Iri::from("https://schema.org/Organization"),
+            "Companies".to_string(),
+        )]),
+        ..WorkbookOptions::default()
+    };
+    let workbook = build_workbook_with_options(&nodes, &options).expect("workbook built");
+    let names: Vec<&str> = workbook
+        .tables
+        .iter()
+        .map(|table| table.sheet_name.as_str())
+        .collect();
+    for name in ["Person", "Person_1", "Companies", "Person__knows"] {
+        assert!(names.contains(&name), "{name} in {names:?}");
+    }
+
+    // Metadata maps the names back, so the workbook reads back whole.
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("people.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("written");
+    assert_eq!(excel_read::read_nodes(&path).expect("read back"), nodes);
+}
+
+#[test]
+fn sync_names_sheets_after_context_terms() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("people.jsonld");
+    let document = jsonld::nodes_to_jsonld(&mixed_vocabularies(), None).unwrap();
+    fs::write(&input, document.to_string()).unwrap();
+    let context = temp_dir.path().join("context.json");
+    fs::write(
+        &context,
+        json!({ "People": "https://schema.org/Person", "knows": "https://schema.org/knows" })
+            .to_string(),
+    )
+    .unwrap();
+    let names = temp_dir.path().join("names.json");
+    fs::write(
+        &names,
+        json!({ "http://xmlns.com/foaf/0.1/Person": "Friends" }).to_string(),
+    )
+    .unwrap();
+    let output = temp_dir.path().join("people.xlsx");
+
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "excel", "--output"])
+        .arg(&output)
+        .arg("--context")
+        .arg(&context)
+        .args(["--sheet-names", "context", "--sheet-name-map"])
+        .arg(&names)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    let workbook: Xlsx<_> = open_workbook(&output).expect("workbook opened");
+    let sheets = workbook.sheet_names();
+    for name in ["People", "Friends", "Organization", "People__knows"] {
+        assert!(sheets.contains(&name.to_string()), "{name} in {sheets:?}");
+    }
+}
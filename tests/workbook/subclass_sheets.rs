@@ -0,0 +1,120 @@
+use std::fs;
+
+use aideon_tools::aideon::tools::flatten::{
+    SUBTYPE_COLUMN, WorkbookOptions, build_workbook_with_options, consolidate_subclasses,
+};
+use aideon_tools::aideon::tools::io::{excel_read, excel_write, jsonld};
+use aideon_tools::aideon::tools::model::{Iri, Node};
+use calamine::{Reader, Xlsx, open_workbook};
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+fn events() -> Vec<Node> {
+    let document = json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/events/1",
+                "@type": "https://schema.org/Event",
+                "https://schema.org/name": "Open day"
+            },
+            {
+                "@id": "https://example.com/events/2",
+                "@type": "https://schema.org/MusicEvent",
+                "https://schema.org/name": "Concert"
+            },
+            {
+                "@id": "https://example.com/events/3",
+                "@type": ["https://schema.org/Festival", "https://schema.org/Event"],
+                "https://schema.org/name": "Fair"
+            }
+        ]
+    });
+    parse_jsonld(&document)
+}
+
+#[test]
+fn subclasses_share_the_sheet_of_their_root() {
+    let hierarchy = parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://schema.org/MusicEvent",
+                "http://www.w3.org/2000/01/rdf-schema#subClassOf": { "@id": "https://schema.org/Event" }
+            },
+            {
+                "@id": "https://schema.org/Festival",
+                "http://www.w3.org/2000/01/rdf-schema#subClassOf": { "@id": "https://schema.org/MusicEvent" }
+            }
+        ]
+    }));
+    let consolidated_types = consolidate_subclasses(&hierarchy);
+    assert_eq!(
+        consolidated_types.get("https://schema.org/Festival"),
+        Some(&Iri::from("https://schema.org/Event"))
+    );
+
+    let nodes = events();
+    let options = WorkbookOptions {
+        consolidated_types,
+        ..WorkbookOptions::default()
+    };
+    let workbook = build_workbook_with_options(&nodes, &options).expect("workbook built");
+    let names: Vec<&str> = workbook
+        .tables
+        .iter()
+        .map(|table| table.sheet_name.as_str())
+        .collect();
+    assert_eq!(names, ["Entities", "Metadata", "https___schema.org_Event"]);
+    let events = &workbook.tables[2];
+    assert_eq!(events.columns[2], SUBTYPE_COLUMN);
+    let subtypes: Vec<&str> = events.rows.iter().map(|row| row[2].as_str()).collect();
+    assert_eq!(
+        subtypes,
+        [
+            "",
+            "https://schema.org/MusicEvent",
+            "https://schema.org/Event https://schema.org/Festival"
+        ]
+    );
+
+    let temp_dir = tempdir().expect("temporary directory");
+    let path = temp_dir.path().join("events.xlsx");
+    excel_write::write_workbook(&path, &workbook).expect("written");
+    assert_eq!(excel_read::read_nodes(&path).expect("read back"), nodes);
+}
+
+#[test]
+fn sync_consolidates_subclasses_from_an_rdf_hierarchy() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let input = temp_dir.path().join("events.jsonld");
+    let document = jsonld::nodes_to_jsonld(&events(), None).unwrap();
+    fs::write(&input, document.to_string()).unwrap();
+    let hierarchy = temp_dir.path().join("classes.ttl");
+    fs::write(
+        &hierarchy,
+        "@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n\
+         @prefix schema: <https://schema.org/> .\n\
+         schema:MusicEvent rdfs:subClassOf schema:Event .\n\
+         schema:Festival rdfs:subClassOf schema:Event .\n",
+    )
+    .unwrap();
+    let output = temp_dir.path().join("events.xlsx");
+
+    let run = aideon()
+        .args(["sync", "--from", "json-ld", "--input"])
+        .arg(&input)
+        .args(["--to", "excel", "--output"])
+        .arg(&output)
+        .args(["--sheet-names", "local-name", "--consolidate-subclasses"])
+        .arg(&hierarchy)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    let workbook: Xlsx<_> = open_workbook(&output).expect("workbook opened");
+    assert_eq!(workbook.sheet_names(), ["Entities", "Metadata", "Event"]);
+    assert_eq!(
+        excel_read::read_nodes(&output).expect("read back"),
+        events()
+    );
+}
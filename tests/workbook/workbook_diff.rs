@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use aideon_tools::aideon::tools::flatten::{WorkbookData, build_workbook};
+use aideon_tools::aideon::tools::io::excel_write;
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+use crate::common::{aideon, assert_success, parse_jsonld};
+
+fn workbook() -> WorkbookData {
+    let nodes = parse_jsonld(&json!({
+        "@graph": [
+            {
+                "@id": "https://example.com/people/1",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Ada"
+            },
+            {
+                "@id": "https://example.com/people/2",
+                "@type": "https://schema.org/Person",
+                "https://schema.org/name": "Grace"
+            },
+            {
+                "@id": "https://example.com/places/1",
+                "@type": "https://schema.org/Place",
+                "https://schema.org/name": "London"
+            }
+        ]
+    }));
+    build_workbook(&nodes).expect("workbook built")
+}
+
+fn diff(before: &Path, after: &Path) -> Vec<Value> {
+    let run = aideon()
+        .arg("diff")
+        .arg("--before")
+        .arg(before)
+        .arg("--after")
+        .arg(after)
+        .output()
+        .expect("CLI ran");
+    assert_success(&run);
+    String::from_utf8(run.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("NDJSON event"))
+        .collect()
+}
+
+#[test]
+fn reordered_sheets_and_rows_are_not_changes() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let before = temp_dir.path().join("before.xlsx");
+    excel_write::write_workbook(&before, &workbook()).unwrap();
+
+    let mut reordered = workbook();
+    reordered.tables.reverse();
+    for table in &mut reordered.tables {
+        table.rows.reverse();
+    }
+    let after = temp_dir.path().join("after.xlsx");
+    excel_write::write_workbook(&after, &reordered).unwrap();
+
+    assert_eq!(diff(&before, &after), Vec::<Value>::new());
+}
+
+#[test]
+fn edited_cells_are_reported_at_their_row_in_the_newer_workbook() {
+    let temp_dir = tempdir().expect("temporary directory");
+    let before = temp_dir.path().join("before.xlsx");
+    excel_write::write_workbook(&before, &workbook()).unwrap();
+
+    let mut edited = workbook();
+    let people = edited
+        .tables
+        .iter_mut()
+        .find(|table| table.sheet_name == "https___schema.org_Person")
+        .expect("Person sheet");
+    people.rows.reverse();
+    let name = people
+        .columns
+        .iter()
+        .position(|column| column == "https://schema.org/name")
+        .expect("name column");
+    people.rows[1][name] = "Augusta Ada".into();
+    let after = temp_dir.path().join("after.xlsx");
+    excel_write::write_workbook(&after, &edited).unwrap();
+
+    let changes = diff(&before, &after);
+    assert_eq!(changes.len(), 1, "{changes:?}");
+    assert_eq!(changes[0]["change"], "updated");
+    assert_eq!(changes[0]["id"], "https://example.com/people/1");
+    assert_eq!(
+        changes[0]["rows"],
+        json!([{ "sheet": "https___schema.org_Person", "row": 3 }])
+    );
+}